@@ -36,6 +36,7 @@ pub enum AgentCapability {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
 
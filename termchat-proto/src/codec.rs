@@ -1,9 +1,76 @@
 //! Serialization and deserialization for the `TermChat` wire protocol.
 //!
 //! Provides encode/decode functions using postcard, along with
-//! length-prefix framing variants for stream-based transports.
+//! length-prefix framing variants for stream-based transports (see
+//! [`crate::framing`] for the incremental version).
+//!
+//! [`decode`] distinguishes bytes it cannot parse at all from bytes that use
+//! an enum variant this build does not know ([`CodecError::UnknownVariant`]).
+//! The latter usually means the sender runs a newer protocol version, and
+//! the receiver should NACK rather than silently drop the message.
+//!
+//! # Compression
+//!
+//! [`encode_compressed`] optionally deflates large payloads before they are
+//! encrypted. A compressed payload is marked by the high bit of its first
+//! byte, which a plain postcard envelope never sets (envelope tags stay
+//! below 128, so their varint is a single byte):
+//!
+//! `[0x80 | algorithm][u32 uncompressed length (LE)][compressed bytes]`
+//!
+//! Older builds read the marker as an unknown envelope variant and NACK
+//! the message, so senders only compress for peers that negotiated
+//! [`FeatureFlags::COMPRESSION`](crate::version::FeatureFlags::COMPRESSION).
+//! [`decode_with_limit`] refuses to inflate beyond a caller-supplied cap,
+//! which guards against decompression bombs.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::{Envelope, MessageId, MessageMetadata};
+
+/// Postcard variant index of [`Envelope::Chat`].
+const ENVELOPE_CHAT_TAG: u32 = 0;
+
+/// High bit of the first byte marks a compressed payload.
+const COMPRESSED_FLAG: u8 = 0x80;
+
+/// Length of the compressed payload header: marker byte plus `u32` length.
+const COMPRESSED_HEADER_LEN: usize = 5;
+
+/// Decompressed size cap applied by [`decode`] (matches the default
+/// `max_payload_size` of the chat pipeline).
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 64 * 1024;
 
-use crate::message::Envelope;
+/// Compression algorithm applied to encoded envelopes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Payloads are sent as plain postcard.
+    None,
+    /// Raw deflate (RFC 1951).
+    #[default]
+    Deflate,
+}
+
+impl Compression {
+    /// Algorithm identifier stored in the low bits of the marker byte.
+    const fn id(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Deflate => 1,
+        }
+    }
+
+    /// Maps a marker identifier back to an algorithm.
+    const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+}
 
 /// Error type for codec encode/decode operations.
 #[derive(Debug, thiserror::Error)]
@@ -14,6 +81,29 @@ pub enum CodecError {
     /// Frame is incomplete or has an invalid length prefix.
     #[error("invalid frame: {0}")]
     InvalidFrame(String),
+    /// The bytes use an enum variant this build does not know, most likely
+    /// because the sender speaks a newer protocol version.
+    #[error("unknown variant in envelope (tag {tag}), sender may use a newer protocol version")]
+    UnknownVariant {
+        /// The envelope variant tag read from the wire.
+        tag: u32,
+        /// ID of the affected chat message, if it could still be recovered.
+        message_id: Option<MessageId>,
+    },
+    /// Compressing or inflating a payload failed.
+    #[error("compression error: {0}")]
+    Compression(String),
+    /// A compressed payload would inflate beyond the allowed size.
+    #[error("decompressed payload too large: {size} bytes (max {max})")]
+    DecompressedTooLarge {
+        /// Uncompressed size declared by the sender.
+        size: usize,
+        /// Maximum allowed uncompressed size.
+        max: usize,
+    },
+    /// Reading or writing the underlying stream failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Encodes an [`Envelope`] into a byte vector using postcard.
@@ -25,13 +115,153 @@ pub fn encode(envelope: &Envelope) -> Result<Vec<u8>, CodecError> {
     postcard::to_allocvec(envelope).map_err(|e| CodecError::Serialization(e.to_string()))
 }
 
+/// Encodes an [`Envelope`], compressing it when that pays off.
+///
+/// The payload is left uncompressed if `compression` is
+/// [`Compression::None`], if it is shorter than `threshold` bytes, or if
+/// compressing would not make it smaller.
+///
+/// # Errors
+///
+/// Returns `CodecError::Serialization` if the envelope cannot be serialized,
+/// or `CodecError::Compression` if the compressor fails.
+pub fn encode_compressed(
+    envelope: &Envelope,
+    compression: Compression,
+    threshold: usize,
+) -> Result<Vec<u8>, CodecError> {
+    let raw = encode(envelope)?;
+    if compression == Compression::None || raw.len() < threshold {
+        return Ok(raw);
+    }
+    let len = u32::try_from(raw.len()).map_err(|_| {
+        CodecError::Compression(format!(
+            "payload too large to compress: {} bytes",
+            raw.len()
+        ))
+    })?;
+
+    let mut header = Vec::with_capacity(COMPRESSED_HEADER_LEN + raw.len() / 2);
+    header.push(COMPRESSED_FLAG | compression.id());
+    header.extend_from_slice(&len.to_le_bytes());
+    let mut encoder = flate2::write::DeflateEncoder::new(header, flate2::Compression::default());
+    encoder
+        .write_all(&raw)
+        .map_err(|e| CodecError::Compression(e.to_string()))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| CodecError::Compression(e.to_string()))?;
+
+    Ok(if compressed.len() < raw.len() {
+        compressed
+    } else {
+        raw
+    })
+}
+
+/// Returns `true` if `bytes` carry the compressed payload marker.
+#[must_use]
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.first().is_some_and(|b| b & COMPRESSED_FLAG != 0)
+}
+
 /// Decodes an [`Envelope`] from a byte slice using postcard.
 ///
+/// Compressed payloads are inflated up to [`DEFAULT_MAX_DECOMPRESSED_SIZE`];
+/// use [`decode_with_limit`] to apply a different cap.
+///
 /// # Errors
 ///
-/// Returns `CodecError::Serialization` if the bytes cannot be deserialized.
+/// Returns `CodecError::UnknownVariant` if the bytes use an envelope or
+/// content variant this build does not know, `CodecError::Compression` or
+/// `CodecError::DecompressedTooLarge` if a compressed payload is invalid or
+/// too large, or `CodecError::Serialization` if the bytes cannot be
+/// deserialized for any other reason.
 pub fn decode(bytes: &[u8]) -> Result<Envelope, CodecError> {
-    postcard::from_bytes(bytes).map_err(|e| CodecError::Serialization(e.to_string()))
+    decode_with_limit(bytes, DEFAULT_MAX_DECOMPRESSED_SIZE)
+}
+
+/// Decodes an [`Envelope`], inflating compressed payloads to at most
+/// `max_decompressed` bytes.
+///
+/// The declared uncompressed length is checked before inflating, and the
+/// inflater is cut off at that length, so a forged header cannot make the
+/// receiver allocate more than `max_decompressed` bytes.
+///
+/// # Errors
+///
+/// Same as [`decode`].
+pub fn decode_with_limit(bytes: &[u8], max_decompressed: usize) -> Result<Envelope, CodecError> {
+    if is_compressed(bytes) {
+        let raw = decompress(bytes, max_decompressed)?;
+        return decode_raw(&raw);
+    }
+    decode_raw(bytes)
+}
+
+/// Decodes plain postcard bytes.
+fn decode_raw(bytes: &[u8]) -> Result<Envelope, CodecError> {
+    postcard::from_bytes(bytes).map_err(|e| classify_decode_error(bytes, &e))
+}
+
+/// Inflates a compressed payload, enforcing `max` on the output size.
+fn decompress(bytes: &[u8], max: usize) -> Result<Vec<u8>, CodecError> {
+    let Some((header, body)) = bytes.split_first_chunk::<COMPRESSED_HEADER_LEN>() else {
+        return Err(CodecError::InvalidFrame(format!(
+            "compressed payload needs a {COMPRESSED_HEADER_LEN}-byte header, got {} bytes",
+            bytes.len()
+        )));
+    };
+    let [marker, len @ ..] = *header;
+    let Some(Compression::Deflate) = Compression::from_id(marker & !COMPRESSED_FLAG) else {
+        return Err(CodecError::Compression(format!(
+            "unknown compression algorithm 0x{marker:02x}"
+        )));
+    };
+    let declared = u32::from_le_bytes(len) as usize;
+    if declared > max {
+        return Err(CodecError::DecompressedTooLarge {
+            size: declared,
+            max,
+        });
+    }
+
+    let mut raw = Vec::with_capacity(declared);
+    flate2::read::DeflateDecoder::new(body)
+        // One extra byte lets us detect a payload longer than declared.
+        .take(u64::from(u32::from_le_bytes(len)) + 1)
+        .read_to_end(&mut raw)
+        .map_err(|e| CodecError::Compression(e.to_string()))?;
+    if raw.len() != declared {
+        return Err(CodecError::Compression(format!(
+            "declared {declared} uncompressed bytes, inflated {}",
+            raw.len()
+        )));
+    }
+    Ok(raw)
+}
+
+/// Maps a postcard decode failure to a [`CodecError`].
+///
+/// Serde reports an out-of-range variant index as a custom error, so those
+/// are treated as unknown variants. For chat messages the metadata precedes
+/// the content, so the message ID can usually be recovered for the NACK.
+fn classify_decode_error(bytes: &[u8], err: &postcard::Error) -> CodecError {
+    if matches!(
+        err,
+        postcard::Error::SerdeDeCustom | postcard::Error::DeserializeBadEnum
+    ) && let Ok((tag, rest)) = postcard::take_from_bytes::<u32>(bytes)
+    {
+        let message_id = if tag == ENVELOPE_CHAT_TAG {
+            postcard::take_from_bytes::<MessageMetadata>(rest)
+                .ok()
+                .map(|(metadata, _)| metadata.message_id)
+        } else {
+            None
+        };
+        return CodecError::UnknownVariant { tag, message_id };
+    }
+    CodecError::Serialization(err.to_string())
 }
 
 /// Encodes an [`Envelope`] with a 4-byte little-endian length prefix.
@@ -97,6 +327,7 @@ pub fn decode_framed(bytes: &[u8]) -> Result<(Envelope, usize), CodecError> {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use crate::message::*;
@@ -162,6 +393,46 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn decode_unknown_envelope_variant_returns_unknown_variant() {
+        // Tag 0x7f is far beyond the current Envelope variants.
+        let bytes = vec![0x7f, 0x01, 0x02];
+        match decode(&bytes) {
+            Err(CodecError::UnknownVariant { tag, message_id }) => {
+                assert_eq!(tag, 0x7f);
+                assert!(message_id.is_none());
+            }
+            other => panic!("expected UnknownVariant, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_unknown_content_variant_recovers_message_id() {
+        let original = make_chat_envelope("from the future");
+        let Envelope::Chat(ref msg) = original else {
+            unreachable!()
+        };
+        let mut bytes = encode(&original).unwrap();
+
+        // The content tag follows the envelope tag and the metadata.
+        let metadata_len = postcard::to_allocvec(&msg.metadata).unwrap().len();
+        bytes[1 + metadata_len] = 0x7f;
+
+        match decode(&bytes) {
+            Err(CodecError::UnknownVariant { tag, message_id }) => {
+                assert_eq!(tag, ENVELOPE_CHAT_TAG);
+                assert_eq!(message_id, Some(msg.metadata.message_id.clone()));
+            }
+            other => panic!("expected UnknownVariant, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn chat_tag_matches_envelope_encoding() {
+        let bytes = encode(&make_chat_envelope("tag")).unwrap();
+        assert_eq!(u32::from(bytes[0]), ENVELOPE_CHAT_TAG);
+    }
+
     #[test]
     fn decode_truncated_bytes_returns_error() {
         let original = make_chat_envelope("truncation test");
@@ -178,6 +449,84 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // --- compression ---
+
+    /// Builds a compressed payload by hand with an arbitrary declared length.
+    fn forge_compressed(declared: u32, raw: &[u8]) -> Vec<u8> {
+        let mut header = vec![COMPRESSED_FLAG | Compression::Deflate.id()];
+        header.extend_from_slice(&declared.to_le_bytes());
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(header, flate2::Compression::default());
+        encoder.write_all(raw).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn compressed_round_trip_shrinks_large_payload() {
+        let original = make_chat_envelope(&"2024-01-01 INFO request ok\n".repeat(1000));
+        let plain = encode(&original).unwrap();
+        let bytes = encode_compressed(&original, Compression::Deflate, 512).unwrap();
+        assert!(is_compressed(&bytes));
+        assert!(bytes.len() < plain.len() / 10);
+        assert_eq!(decode(&bytes).unwrap(), original);
+    }
+
+    #[test]
+    fn small_or_disabled_payloads_stay_plain() {
+        let small = make_chat_envelope("hi");
+        let bytes = encode_compressed(&small, Compression::Deflate, 512).unwrap();
+        assert_eq!(bytes, encode(&small).unwrap());
+
+        let large = make_chat_envelope(&"x".repeat(4096));
+        let bytes = encode_compressed(&large, Compression::None, 0).unwrap();
+        assert!(!is_compressed(&bytes));
+        assert_eq!(decode(&bytes).unwrap(), large);
+    }
+
+    #[test]
+    fn plain_encoding_never_sets_compressed_flag() {
+        let bytes = encode(&Envelope::Handshake(vec![0xff; 16])).unwrap();
+        assert!(!is_compressed(&bytes));
+    }
+
+    #[test]
+    fn decompression_bomb_rejected_by_declared_length() {
+        let original = make_chat_envelope(&"a".repeat(10_000));
+        let bytes = encode_compressed(&original, Compression::Deflate, 0).unwrap();
+        match decode_with_limit(&bytes, 1024) {
+            Err(CodecError::DecompressedTooLarge { max: 1024, size }) => assert!(size > 10_000),
+            other => panic!("expected DecompressedTooLarge, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decompression_bomb_with_lying_header_is_cut_off() {
+        // Claims 100 bytes but inflates to 1 MB of zeros.
+        let bytes = forge_compressed(100, &vec![0u8; 1024 * 1024]);
+        assert!(matches!(
+            decode_with_limit(&bytes, 1024),
+            Err(CodecError::Compression(_))
+        ));
+    }
+
+    #[test]
+    fn compressed_payload_shorter_than_declared_rejected() {
+        let raw = encode(&make_chat_envelope("short")).unwrap();
+        let bytes = forge_compressed(u32::try_from(raw.len()).unwrap() + 10, &raw);
+        assert!(matches!(decode(&bytes), Err(CodecError::Compression(_))));
+    }
+
+    #[test]
+    fn unknown_compression_algorithm_rejected() {
+        let mut bytes = forge_compressed(4, &[0, 1, 2, 3]);
+        bytes[0] = COMPRESSED_FLAG | 0x7f;
+        assert!(matches!(decode(&bytes), Err(CodecError::Compression(_))));
+        assert!(matches!(
+            decode(&[COMPRESSED_FLAG | 1, 0x00]),
+            Err(CodecError::InvalidFrame(_))
+        ));
+    }
+
     #[test]
     fn decode_framed_too_short_returns_error() {
         // Less than 4 bytes for the length prefix
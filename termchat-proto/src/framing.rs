@@ -0,0 +1,268 @@
+//! Incremental length-prefixed framing for stream transports.
+//!
+//! [`codec::decode_framed`] needs the whole frame in one slice. Stream
+//! transports (QUIC streams, TCP) instead receive bytes in arbitrary chunks,
+//! so [`EnvelopeCodec`] implements [`tokio_util::codec::Decoder`] and
+//! [`Encoder`] over the same wire format:
+//!
+//! `[u32 length (LE)][payload bytes]`
+//!
+//! Partial frames stay buffered until complete. A length prefix above the
+//! configured maximum is rejected as soon as the prefix arrives, before any
+//! payload is buffered.
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use termchat_proto::framing::EnvelopeCodec;
+//! use termchat_proto::message::Envelope;
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! let mut codec = EnvelopeCodec::new();
+//! let mut buf = BytesMut::new();
+//! codec.encode(&Envelope::Handshake(vec![1, 2, 3]), &mut buf).unwrap();
+//!
+//! // Feed the frame one byte at a time.
+//! let mut incoming = BytesMut::new();
+//! let mut decoded = None;
+//! for byte in buf.iter() {
+//!     incoming.extend_from_slice(&[*byte]);
+//!     if let Some(envelope) = codec.decode(&mut incoming).unwrap() {
+//!         decoded = Some(envelope);
+//!     }
+//! }
+//! assert_eq!(decoded, Some(Envelope::Handshake(vec![1, 2, 3])));
+//! ```
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::{self, CodecError};
+use crate::message::Envelope;
+
+/// Size of the length prefix in bytes.
+const LENGTH_PREFIX_LEN: usize = 4;
+
+/// Default maximum payload size of a single frame (64 KB).
+pub const DEFAULT_MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// Streaming encoder/decoder for length-prefixed [`Envelope`] frames.
+///
+/// The same maximum applies to the frame payload and, for compressed
+/// envelopes, to the inflated payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeCodec {
+    /// Maximum payload length accepted or produced, in bytes.
+    max_frame_len: usize,
+}
+
+impl Default for EnvelopeCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvelopeCodec {
+    /// Creates a codec with [`DEFAULT_MAX_FRAME_LEN`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_max_frame_len(DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a codec that rejects frames larger than `max_frame_len` bytes.
+    #[must_use]
+    pub const fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self { max_frame_len }
+    }
+
+    /// Returns the maximum frame payload length in bytes.
+    #[must_use]
+    pub const fn max_frame_len(self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Builds the error returned for frames above the maximum.
+    fn oversized(self, len: usize) -> CodecError {
+        CodecError::InvalidFrame(format!(
+            "frame of {len} bytes exceeds maximum of {} bytes",
+            self.max_frame_len
+        ))
+    }
+}
+
+impl Decoder for EnvelopeCodec {
+    type Item = Envelope;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Envelope>, CodecError> {
+        let Some(prefix) = src.first_chunk::<LENGTH_PREFIX_LEN>() else {
+            return Ok(None);
+        };
+        let payload_len = u32::from_le_bytes(*prefix) as usize;
+        if payload_len > self.max_frame_len {
+            return Err(self.oversized(payload_len));
+        }
+
+        let frame_len = LENGTH_PREFIX_LEN + payload_len;
+        if src.len() < frame_len {
+            // Make room for the rest of the frame so the next read fills it.
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(LENGTH_PREFIX_LEN);
+        let payload = src.split_to(payload_len);
+        codec::decode_with_limit(&payload, self.max_frame_len).map(Some)
+    }
+}
+
+impl Encoder<&Envelope> for EnvelopeCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, envelope: &Envelope, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let payload = codec::encode(envelope)?;
+        if payload.len() > self.max_frame_len {
+            return Err(self.oversized(payload.len()));
+        }
+        let len = u32::try_from(payload.len()).map_err(|_| self.oversized(payload.len()))?;
+        dst.reserve(LENGTH_PREFIX_LEN + payload.len());
+        dst.put_u32_le(len);
+        dst.extend_from_slice(&payload);
+        Ok(())
+    }
+}
+
+impl Encoder<Envelope> for EnvelopeCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, envelope: Envelope, dst: &mut BytesMut) -> Result<(), CodecError> {
+        Encoder::<&Envelope>::encode(self, &envelope, dst)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::message::*;
+
+    fn make_chat_envelope(text: &str) -> Envelope {
+        Envelope::Chat(ChatMessage {
+            metadata: MessageMetadata {
+                message_id: MessageId::new(),
+                timestamp: Timestamp::now(),
+                sender_id: SenderId::new(vec![0xaa]),
+                conversation_id: ConversationId::new(),
+            },
+            content: MessageContent::Text(text.to_string()),
+        })
+    }
+
+    fn encode_all(envelopes: &[Envelope]) -> BytesMut {
+        let mut codec = EnvelopeCodec::new();
+        let mut buf = BytesMut::new();
+        for envelope in envelopes {
+            codec.encode(envelope, &mut buf).unwrap();
+        }
+        buf
+    }
+
+    // --- wire format ---
+
+    #[test]
+    fn encoder_matches_encode_framed() {
+        let envelope = make_chat_envelope("same bytes");
+        let buf = encode_all(std::slice::from_ref(&envelope));
+        assert_eq!(&buf[..], &codec::encode_framed(&envelope).unwrap()[..]);
+    }
+
+    #[test]
+    fn decoder_reads_encode_framed_output() {
+        let envelope = make_chat_envelope("legacy framing");
+        let mut buf = BytesMut::from(&codec::encode_framed(&envelope).unwrap()[..]);
+        assert_eq!(
+            EnvelopeCodec::new().decode(&mut buf).unwrap(),
+            Some(envelope)
+        );
+        assert!(buf.is_empty());
+    }
+
+    // --- partial reads ---
+
+    #[test]
+    fn partial_prefix_and_payload_are_buffered() {
+        let envelope = make_chat_envelope("split across reads");
+        let frame = encode_all(std::slice::from_ref(&envelope));
+        let mut codec = EnvelopeCodec::new();
+
+        let mut buf = BytesMut::from(&frame[..2]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&frame[2..10]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.capacity() >= frame.len(), "decoder should reserve room");
+        buf.extend_from_slice(&frame[10..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(envelope));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn multiple_frames_in_one_read() {
+        let first = make_chat_envelope("first");
+        let second = Envelope::Handshake(vec![9; 32]);
+        let mut buf = encode_all(&[first.clone(), second.clone()]);
+        buf.extend_from_slice(&[0x05, 0x00]);
+
+        let mut codec = EnvelopeCodec::new();
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(first));
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(second));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert_eq!(&buf[..], &[0x05, 0x00]);
+    }
+
+    // --- limits ---
+
+    #[test]
+    fn oversized_prefix_rejected_before_payload_arrives() {
+        let mut codec = EnvelopeCodec::with_max_frame_len(1024);
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(1025);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::InvalidFrame(_))
+        ));
+    }
+
+    #[test]
+    fn encoder_refuses_oversized_envelope() {
+        let mut codec = EnvelopeCodec::with_max_frame_len(64);
+        let mut buf = BytesMut::new();
+        let result = codec.encode(&make_chat_envelope(&"x".repeat(100)), &mut buf);
+        assert!(matches!(result, Err(CodecError::InvalidFrame(_))));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn compressed_payload_limited_by_max_frame_len() {
+        let envelope = make_chat_envelope(&"a".repeat(8 * 1024));
+        let payload = codec::encode_compressed(&envelope, codec::Compression::Deflate, 0).unwrap();
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(u32::try_from(payload.len()).unwrap());
+        buf.extend_from_slice(&payload);
+
+        let mut small = EnvelopeCodec::with_max_frame_len(1024);
+        assert!(matches!(
+            small.decode(&mut buf.clone()),
+            Err(CodecError::DecompressedTooLarge { max: 1024, .. })
+        ));
+        assert_eq!(
+            EnvelopeCodec::new().decode(&mut buf).unwrap(),
+            Some(envelope)
+        );
+    }
+
+    #[test]
+    fn trailing_partial_frame_is_error_at_eof() {
+        let mut buf = encode_all(&[make_chat_envelope("cut")]);
+        buf.truncate(buf.len() - 1);
+        assert!(EnvelopeCodec::new().decode_eof(&mut buf).is_err());
+    }
+}
@@ -2,9 +2,11 @@
 
 pub mod agent;
 pub mod codec;
+pub mod framing;
 pub mod message;
 pub mod presence;
 pub mod relay;
 pub mod room;
 pub mod task;
 pub mod typing;
+pub mod version;
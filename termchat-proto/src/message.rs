@@ -285,6 +285,12 @@ pub enum NackReason {
     SenderIdMismatch,
     /// Other reason (free-form string).
     Other(String),
+    /// The message uses a variant the receiver does not understand, most
+    /// likely because the sender runs a newer protocol version.
+    UnsupportedVersion {
+        /// The receiver's protocol version.
+        protocol_version: u16,
+    },
 }
 
 /// Top-level envelope wrapping all wire-level protocol messages.
@@ -35,6 +35,7 @@ pub struct PresenceMessage {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
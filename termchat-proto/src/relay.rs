@@ -2,9 +2,25 @@
 //!
 //! Defines the [`RelayMessage`] enum that is postcard-encoded and sent
 //! over WebSocket binary frames between relay clients and the relay server.
+//!
+//! ## NAT traversal
+//!
+//! The relay also brokers direct QUIC connections between peers behind NAT.
+//! A peer sends [`RelayMessage::PunchRequest`] with its QUIC port; the relay
+//! combines that port with the source IP it observes on the peer's
+//! connection and forwards the result to the target as
+//! [`RelayMessage::PunchCandidates`]. The target answers with its own
+//! request, and both sides then dial each other at the same time so that
+//! each NAT sees outbound traffic before the other side's packets arrive.
+//! Relays that support this advertise
+//! [`FeatureFlags::HOLE_PUNCH`](crate::version::FeatureFlags::HOLE_PUNCH).
+
+use std::net::SocketAddr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::version::ProtocolHello;
+
 /// Messages exchanged between relay clients and the relay server.
 ///
 /// The relay protocol is simple: clients register with a `PeerId`, then
@@ -19,12 +35,19 @@ pub enum RelayMessage {
     Register {
         /// The `PeerId` of the registering client.
         peer_id: String,
+        /// Signed invite token, required by relays that restrict registration
+        /// to invited peers. `None` on open relays.
+        invite_token: Option<String>,
+        /// Protocol version and features spoken by the client.
+        protocol: ProtocolHello,
     },
 
     /// Server acknowledges successful registration.
     Registered {
         /// The `PeerId` that was registered (echoed back for confirmation).
         peer_id: String,
+        /// Protocol version and features spoken by the relay.
+        protocol: ProtocolHello,
     },
 
     /// An encrypted payload to be relayed from one peer to another.
@@ -60,6 +83,45 @@ pub enum RelayMessage {
     /// The relay decodes the inner bytes as [`crate::room::RoomMessage`]
     /// to handle registry operations and route join requests.
     Room(Vec<u8>),
+
+    /// Server is shutting down (drain mode) and will not accept new
+    /// registrations.
+    ///
+    /// Sent to every connected peer before the relay closes its sockets,
+    /// and in place of [`RelayMessage::Registered`] to peers that try to
+    /// register while the relay is draining. Clients should reconnect to
+    /// `redirect_url` if one is given, otherwise to the same relay after
+    /// waiting at least `retry_after_ms`.
+    Shutdown {
+        /// Human-readable shutdown reason (e.g., "relay restarting").
+        reason: String,
+        /// Minimum delay in milliseconds before reconnecting to this relay.
+        retry_after_ms: u64,
+        /// Alternate relay URL to reconnect to instead, if any.
+        redirect_url: Option<String>,
+    },
+
+    /// Client asks the relay to broker a direct connection to `to`.
+    ///
+    /// The relay forwards the sender's observed public address (its source
+    /// IP plus `port`) and `local_addrs` to `to` as
+    /// [`RelayMessage::PunchCandidates`].
+    PunchRequest {
+        /// Recipient's `PeerId`.
+        to: String,
+        /// UDP port the sender's QUIC endpoint is bound to.
+        port: u16,
+        /// Additional addresses the sender is reachable at (e.g., LAN).
+        local_addrs: Vec<SocketAddr>,
+    },
+
+    /// Relay tells a peer where another peer can be dialed directly.
+    PunchCandidates {
+        /// The requesting peer (set by the relay from its registration).
+        from: String,
+        /// Candidate addresses, the relay-observed public address first.
+        addrs: Vec<SocketAddr>,
+    },
 }
 
 /// Encodes a [`RelayMessage`] into bytes using postcard.
@@ -81,6 +143,7 @@ pub fn decode(bytes: &[u8]) -> Result<RelayMessage, String> {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -88,6 +151,20 @@ mod tests {
     fn round_trip_register() {
         let msg = RelayMessage::Register {
             peer_id: "peer-abc".to_string(),
+            invite_token: None,
+            protocol: ProtocolHello::current(),
+        };
+        let bytes = encode(&msg).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn round_trip_register_with_invite_token() {
+        let msg = RelayMessage::Register {
+            peer_id: "peer-abc".to_string(),
+            invite_token: Some("v1.peer-abc.1700000000.deadbeef".to_string()),
+            protocol: ProtocolHello::current(),
         };
         let bytes = encode(&msg).unwrap();
         let decoded = decode(&bytes).unwrap();
@@ -98,6 +175,7 @@ mod tests {
     fn round_trip_registered() {
         let msg = RelayMessage::Registered {
             peer_id: "peer-abc".to_string(),
+            protocol: ProtocolHello::current(),
         };
         let bytes = encode(&msg).unwrap();
         let decoded = decode(&bytes).unwrap();
@@ -179,6 +257,56 @@ mod tests {
         assert_eq!(msg, decoded);
     }
 
+    #[test]
+    fn round_trip_shutdown() {
+        let msg = RelayMessage::Shutdown {
+            reason: "relay restarting".to_string(),
+            retry_after_ms: 5_000,
+            redirect_url: Some("ws://backup:9000/ws".to_string()),
+        };
+        let bytes = encode(&msg).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn round_trip_shutdown_without_redirect() {
+        let msg = RelayMessage::Shutdown {
+            reason: String::new(),
+            retry_after_ms: 0,
+            redirect_url: None,
+        };
+        let bytes = encode(&msg).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn round_trip_punch_request() {
+        let msg = RelayMessage::PunchRequest {
+            to: "bob".to_string(),
+            port: 7000,
+            local_addrs: vec!["192.168.1.10:7000".parse().unwrap()],
+        };
+        let bytes = encode(&msg).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn round_trip_punch_candidates() {
+        let msg = RelayMessage::PunchCandidates {
+            from: "alice".to_string(),
+            addrs: vec![
+                "203.0.113.7:40000".parse().unwrap(),
+                "[fe80::1]:7000".parse().unwrap(),
+            ],
+        };
+        let bytes = encode(&msg).unwrap();
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
     #[test]
     fn round_trip_large_payload() {
         let msg = RelayMessage::RelayPayload {
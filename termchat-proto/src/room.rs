@@ -111,6 +111,52 @@ pub struct MemberInfo {
     /// serialized data that predates agent support.
     #[serde(default)]
     pub is_agent: bool,
+    /// Whether this member has read-only access to the room's tasks.
+    ///
+    /// Defaults to `false`, like [`is_agent`](Self::is_agent).
+    #[serde(default)]
+    pub is_read_only: bool,
+}
+
+impl MemberInfo {
+    /// The member's role, which decides what it may do to the room's
+    /// tasks. An admin flag outranks read-only, which outranks agent.
+    #[must_use]
+    pub const fn role(&self) -> MemberRole {
+        if self.is_admin {
+            MemberRole::Admin
+        } else if self.is_read_only {
+            MemberRole::ReadOnly
+        } else if self.is_agent {
+            MemberRole::Agent
+        } else {
+            MemberRole::Member
+        }
+    }
+}
+
+/// A room member's role, derived from [`MemberInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemberRole {
+    /// Manages the room and all of its tasks.
+    Admin,
+    /// A regular member.
+    Member,
+    /// An AI agent participant.
+    Agent,
+    /// Can see the room's tasks but not change them.
+    ReadOnly,
+}
+
+impl std::fmt::Display for MemberRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Admin => write!(f, "admin"),
+            Self::Member => write!(f, "member"),
+            Self::Agent => write!(f, "agent"),
+            Self::ReadOnly => write!(f, "read-only"),
+        }
+    }
 }
 
 /// What changed in a membership update.
@@ -145,6 +191,7 @@ pub fn decode(bytes: &[u8]) -> Result<RoomMessage, String> {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -230,12 +277,14 @@ mod tests {
                     display_name: "Alice".to_string(),
                     is_admin: true,
                     is_agent: false,
+                    is_read_only: false,
                 },
                 MemberInfo {
                     peer_id: "peer-bob".to_string(),
                     display_name: "Bob".to_string(),
                     is_admin: false,
                     is_agent: false,
+                    is_read_only: false,
                 },
             ],
             target_peer_id: "peer-bob".to_string(),
@@ -329,6 +378,7 @@ mod tests {
             display_name: "Claude".to_string(),
             is_admin: false,
             is_agent: true,
+            is_read_only: false,
         };
         let bytes = postcard::to_allocvec(&member).expect("serialize");
         let decoded: MemberInfo = postcard::from_bytes(&bytes).expect("deserialize");
@@ -346,6 +396,7 @@ mod tests {
             display_name: "Alice".to_string(),
             is_admin: true,
             is_agent: false,
+            is_read_only: false,
         };
         let bytes = postcard::to_allocvec(&member).expect("serialize");
         let decoded: MemberInfo = postcard::from_bytes(&bytes).expect("deserialize");
@@ -353,6 +404,21 @@ mod tests {
         assert!(!decoded.is_agent);
     }
 
+    #[test]
+    fn member_role_precedence() {
+        let member = |is_admin, is_agent, is_read_only| MemberInfo {
+            peer_id: "peer-a".to_string(),
+            display_name: "A".to_string(),
+            is_admin,
+            is_agent,
+            is_read_only,
+        };
+        assert_eq!(member(false, false, false).role(), MemberRole::Member);
+        assert_eq!(member(false, true, false).role(), MemberRole::Agent);
+        assert_eq!(member(false, true, true).role(), MemberRole::ReadOnly);
+        assert_eq!(member(true, false, true).role(), MemberRole::Admin);
+    }
+
     #[test]
     fn decode_corrupted_bytes_fails() {
         let result = decode(&[0xFF, 0xFE, 0xFD, 0xFC]);
@@ -4,14 +4,29 @@
 //! per field, the sync protocol messages, and postcard encode/decode functions.
 //! Task sync messages are carried as opaque bytes in [`Envelope::TaskSync`].
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use uuid::Uuid;
 
 /// Maximum allowed task title length in characters.
 pub const MAX_TASK_TITLE_LENGTH: usize = 256;
 
+/// Maximum allowed task description length in characters.
+pub const MAX_TASK_DESCRIPTION_LENGTH: usize = 4096;
+
+/// Maximum allowed task label length in characters.
+pub const MAX_TASK_LABEL_LENGTH: usize = 32;
+
+/// Maximum allowed task comment length in characters.
+pub const MAX_TASK_COMMENT_LENGTH: usize = 2000;
+
+/// Number of buckets in a room digest (see [`room_digest`]).
+pub const TASK_DIGEST_BUCKETS: usize = 64;
+
 /// Unique identifier for a task, based on UUID v7 for time-ordering.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TaskId(Uuid);
 
 impl TaskId {
@@ -32,6 +47,22 @@ impl TaskId {
     pub const fn as_uuid(&self) -> &Uuid {
         &self.0
     }
+
+    /// The ID of the instance a recurring task spawns when completed.
+    ///
+    /// Derived from this ID alone, so peers that complete the same
+    /// instance concurrently create the same next task, which then merges
+    /// like any other.
+    #[must_use]
+    pub fn next_instance(&self) -> Self {
+        let hash = Sha256::new()
+            .chain_update(b"termchat-next-instance")
+            .chain_update(self.0.as_bytes())
+            .finalize();
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&hash[..16]);
+        Self(uuid::Builder::from_custom_bytes(bytes).into_uuid())
+    }
 }
 
 impl Default for TaskId {
@@ -46,6 +77,57 @@ impl std::fmt::Display for TaskId {
     }
 }
 
+/// Unique identifier for a task comment, based on UUID v7 so comments sort
+/// in posting order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CommentId(Uuid);
+
+impl CommentId {
+    /// Creates a new time-ordered comment identifier (UUID v7).
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Uuid::now_v7())
+    }
+
+    /// Creates a `CommentId` from an existing UUID.
+    #[must_use]
+    pub const fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+impl Default for CommentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for CommentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A comment on a task, optionally replying to another comment on the
+/// same task.
+///
+/// Comments are immutable once posted, so a task's comments form a
+/// grow-only set keyed by [`CommentId`] and merge by union.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskComment {
+    /// Unique comment identifier.
+    pub id: CommentId,
+    /// The comment this one replies to, if any.
+    pub reply_to: Option<CommentId>,
+    /// `PeerId` of the comment's author.
+    pub author: String,
+    /// Comment text.
+    pub body: String,
+    /// When the comment was posted (the author's clock, like LWW
+    /// register timestamps).
+    pub timestamp: u64,
+}
+
 /// A Last-Write-Wins register for CRDT-based conflict resolution.
 ///
 /// The merge rule:
@@ -56,7 +138,9 @@ impl std::fmt::Display for TaskId {
 pub struct LwwRegister<T> {
     /// The current value of the register.
     pub value: T,
-    /// Milliseconds since epoch when this value was written.
+    /// When this value was written. Clients stamp writes with a hybrid
+    /// logical clock: milliseconds since epoch in the upper bits and a
+    /// logical counter in the low 16 bits, compared as a plain integer.
     pub timestamp: u64,
     /// `PeerId` of the peer that wrote this value.
     pub author: String,
@@ -97,12 +181,110 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
+impl std::str::FromStr for TaskStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "open" => Ok(Self::Open),
+            "in_progress" => Ok(Self::InProgress),
+            "completed" => Ok(Self::Completed),
+            "deleted" => Ok(Self::Deleted),
+            other => Err(format!("unknown status: {other}")),
+        }
+    }
+}
+
+/// Priority of a task.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TaskPriority {
+    /// Can wait.
+    Low,
+    /// The default priority.
+    #[default]
+    Normal,
+    /// Should be picked up soon.
+    High,
+    /// Needs attention now.
+    Urgent,
+}
+
+impl std::fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Normal => write!(f, "normal"),
+            Self::High => write!(f, "high"),
+            Self::Urgent => write!(f, "urgent"),
+        }
+    }
+}
+
+impl std::str::FromStr for TaskPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            other => Err(format!("unknown priority: {other}")),
+        }
+    }
+}
+
+/// How a task repeats: completing it creates the next instance, due at
+/// the rule's next occurrence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recurrence {
+    /// Every day.
+    Daily,
+    /// Every week.
+    Weekly,
+    /// At the times matched by a five-field cron expression (`minute hour
+    /// day-of-month month day-of-week`, UTC). Clients validate the
+    /// expression before sending it and treat one they cannot parse as
+    /// not repeating.
+    Cron(String),
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly => write!(f, "weekly"),
+            Self::Cron(expr) => write!(f, "cron {expr}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Recurrence {
+    type Err = String;
+
+    /// Parses `daily`, `weekly` or `cron <expression>`. The cron
+    /// expression itself is not checked here.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, rest) = s.split_once(' ').unwrap_or((s, ""));
+        match (kind.to_ascii_lowercase().as_str(), rest.trim()) {
+            ("daily", "") => Ok(Self::Daily),
+            ("weekly", "") => Ok(Self::Weekly),
+            ("cron", expr) if !expr.is_empty() => Ok(Self::Cron(
+                expr.split_whitespace().collect::<Vec<_>>().join(" "),
+            )),
+            _ => Err(format!("unknown recurrence: {s}")),
+        }
+    }
+}
+
 /// A shared task with CRDT fields for conflict-free synchronization.
 ///
-/// Each mutable field (title, status, assignee) is wrapped in an
-/// [`LwwRegister`] so that concurrent edits to different fields
-/// both survive, and concurrent edits to the same field resolve
-/// deterministically.
+/// Each mutable field is wrapped in an [`LwwRegister`] so that concurrent
+/// edits to different fields both survive, and concurrent edits to the
+/// same field resolve deterministically. Labels form a set in which each
+/// label's membership is its own register, so concurrent additions and
+/// removals of different labels all survive. Comments are a grow-only set.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Task {
     /// Unique task identifier (UUID v7, time-ordered).
@@ -115,12 +297,59 @@ pub struct Task {
     pub status: LwwRegister<TaskStatus>,
     /// Optional assignee `PeerId` (LWW — concurrent assignment changes resolved by timestamp).
     pub assignee: LwwRegister<Option<String>>,
+    /// Multi-line description (LWW — concurrent edits resolved by timestamp).
+    pub description: LwwRegister<String>,
+    /// Optional due date in milliseconds since epoch (LWW).
+    pub due: LwwRegister<Option<u64>>,
+    /// How the task repeats, if it does (LWW).
+    pub recurrence: LwwRegister<Option<Recurrence>>,
+    /// Task priority (LWW).
+    pub priority: LwwRegister<TaskPriority>,
+    /// Label -> whether it is currently on the task (LWW per label).
+    ///
+    /// Removed labels stay as `false` entries so a stale addition cannot
+    /// bring them back.
+    pub labels: BTreeMap<String, LwwRegister<bool>>,
+    /// Parent task, making this a subtask (LWW).
+    pub parent: LwwRegister<Option<TaskId>>,
+    /// Blocking task -> whether this task is currently blocked by it (LWW
+    /// per blocker, like [`labels`](Self::labels)).
+    pub blocked_by: BTreeMap<TaskId, LwwRegister<bool>>,
+    /// Comments on the task (grow-only; see [`TaskComment`]).
+    pub comments: BTreeMap<CommentId, TaskComment>,
     /// When this task was originally created (milliseconds since epoch).
     pub created_at: u64,
     /// `PeerId` of the peer who created this task.
     pub created_by: String,
 }
 
+impl Task {
+    /// Labels currently on the task, in sorted order.
+    pub fn label_names(&self) -> impl Iterator<Item = &str> {
+        self.labels
+            .iter()
+            .filter(|(_, present)| present.value)
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Returns `true` if `label` is currently on the task.
+    #[must_use]
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.get(label).is_some_and(|present| present.value)
+    }
+
+    /// Tasks currently recorded as blocking this one.
+    ///
+    /// Edges that would form a cycle are still listed here; clients
+    /// resolve cycles when building the dependency graph.
+    pub fn blocker_ids(&self) -> impl Iterator<Item = &TaskId> {
+        self.blocked_by
+            .iter()
+            .filter(|(_, present)| present.value)
+            .map(|(blocker, _)| blocker)
+    }
+}
+
 /// An update to a single field of a task.
 ///
 /// Used in incremental sync messages so that only changed fields
@@ -134,13 +363,72 @@ pub enum TaskFieldUpdate {
     Status(LwwRegister<TaskStatus>),
     /// Update the task assignee.
     Assignee(LwwRegister<Option<String>>),
+    /// Update the task description.
+    Description(LwwRegister<String>),
+    /// Update the task due date.
+    Due(LwwRegister<Option<u64>>),
+    /// Update the task priority.
+    Priority(LwwRegister<TaskPriority>),
+    /// Add (`true`) or remove (`false`) a label.
+    Label {
+        /// The label being added or removed.
+        label: String,
+        /// Whether the label is on the task after this update.
+        present: LwwRegister<bool>,
+    },
+    /// Set or clear the parent task.
+    Parent(LwwRegister<Option<TaskId>>),
+    /// Add (`true`) or remove (`false`) a "blocked by" edge.
+    BlockedBy {
+        /// The blocking task.
+        blocker: TaskId,
+        /// Whether the task is blocked by `blocker` after this update.
+        present: LwwRegister<bool>,
+    },
+    /// Post a comment.
+    Comment(TaskComment),
+    /// Set or clear the recurrence rule.
+    Recurrence(LwwRegister<Option<Recurrence>>),
+}
+
+/// The digest bucket a task falls in, taken from the random low bits of
+/// its ID so buckets fill evenly.
+#[must_use]
+pub fn digest_bucket(id: &TaskId) -> usize {
+    usize::from(id.0.as_bytes()[15]) % TASK_DIGEST_BUCKETS
+}
+
+/// A 64-bit hash of a task's full CRDT state, identical on every peer
+/// holding the same state.
+#[must_use]
+pub fn task_digest(task: &Task) -> u64 {
+    let bytes = postcard::to_allocvec(task).unwrap_or_default();
+    let hash = Sha256::digest(&bytes);
+    let mut head = [0; 8];
+    head.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(head)
+}
+
+/// Summarizes a room's tasks as [`TASK_DIGEST_BUCKETS`] hashes, one per
+/// bucket: the XOR of the [`task_digest`] of every task in the bucket.
+///
+/// Two peers whose bucket hashes match hold the same tasks in that bucket,
+/// so anti-entropy only has to exchange the buckets that differ.
+#[must_use]
+pub fn room_digest<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Vec<u64> {
+    let mut buckets = vec![0; TASK_DIGEST_BUCKETS];
+    for task in tasks {
+        buckets[digest_bucket(&task.id)] ^= task_digest(task);
+    }
+    buckets
 }
 
 /// Sync protocol messages for task coordination between peers.
 ///
 /// These messages are postcard-encoded and carried as opaque bytes
-/// in [`Envelope::TaskSync`]. The three message types support both
-/// incremental updates and full-state catch-up synchronization.
+/// in [`Envelope::TaskSync`]. They support incremental updates, digest-based
+/// anti-entropy that exchanges only what differs, and full-state catch-up
+/// for peers that predate digests.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskSyncMessage {
     /// Incremental update to a single task field.
@@ -152,14 +440,16 @@ pub enum TaskSyncMessage {
         /// The field update with LWW metadata.
         field: TaskFieldUpdate,
     },
-    /// Full state snapshot for catch-up synchronization.
+    /// A set of tasks to merge, for catch-up synchronization.
     ///
-    /// Sent when a new peer joins a room, or for periodic reconciliation.
-    /// Also used for task creation (single-task `FullState` with add-wins semantics).
+    /// Carries the whole room in reply to [`RequestFullState`](Self::RequestFullState),
+    /// and only the differing buckets in reply to [`Digest`](Self::Digest)
+    /// or [`RequestBuckets`](Self::RequestBuckets). Also used for task
+    /// creation (single-task `FullState` with add-wins semantics).
     FullState {
         /// Which room these tasks belong to.
         room_id: String,
-        /// All tasks in the room (receivers merge via LWW).
+        /// Tasks to merge (receivers merge via LWW).
         tasks: Vec<Task>,
     },
     /// Request a full state snapshot from a room member.
@@ -169,6 +459,26 @@ pub enum TaskSyncMessage {
         /// Which room to request state for.
         room_id: String,
     },
+    /// Summary of the sender's tasks in a room, for anti-entropy.
+    ///
+    /// Sent on joining a room and periodically. The receiver replies with
+    /// a [`FullState`](Self::FullState) holding its tasks in every bucket
+    /// whose hash differs, plus a [`RequestBuckets`](Self::RequestBuckets)
+    /// for the sender's tasks in those buckets. Nothing is sent back when
+    /// the digests match.
+    Digest {
+        /// Which room the digest summarizes.
+        room_id: String,
+        /// Per-bucket hashes, as built by [`room_digest`].
+        buckets: Vec<u64>,
+    },
+    /// Request the sender's tasks in some digest buckets.
+    RequestBuckets {
+        /// Which room to request tasks for.
+        room_id: String,
+        /// Bucket indices, each below [`TASK_DIGEST_BUCKETS`].
+        buckets: Vec<u16>,
+    },
 }
 
 /// Encodes a [`TaskSyncMessage`] into bytes using postcard.
@@ -190,6 +500,7 @@ pub fn decode(bytes: &[u8]) -> Result<TaskSyncMessage, String> {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -224,6 +535,19 @@ mod tests {
         assert_eq!(TaskStatus::Deleted.to_string(), "deleted");
     }
 
+    #[test]
+    fn task_status_parse_round_trips_display() {
+        for status in [
+            TaskStatus::Open,
+            TaskStatus::InProgress,
+            TaskStatus::Completed,
+            TaskStatus::Deleted,
+        ] {
+            assert_eq!(status.to_string().parse::<TaskStatus>(), Ok(status));
+        }
+        assert!("started".parse::<TaskStatus>().is_err());
+    }
+
     fn make_test_task() -> Task {
         Task {
             id: TaskId::new(),
@@ -231,6 +555,14 @@ mod tests {
             title: LwwRegister::new("Fix the login bug".to_string(), 1000, "peer-a".to_string()),
             status: LwwRegister::new(TaskStatus::Open, 1000, "peer-a".to_string()),
             assignee: LwwRegister::new(None, 1000, "peer-a".to_string()),
+            description: LwwRegister::new(String::new(), 1000, "peer-a".to_string()),
+            due: LwwRegister::new(None, 1000, "peer-a".to_string()),
+            recurrence: LwwRegister::new(None, 1000, "peer-a".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 1000, "peer-a".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 1000, "peer-a".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: 1000,
             created_by: "peer-a".to_string(),
         }
@@ -345,6 +677,40 @@ mod tests {
         assert_eq!(msg, decoded);
     }
 
+    #[test]
+    fn round_trip_sync_digest_messages() {
+        let digest = TaskSyncMessage::Digest {
+            room_id: "room-1".to_string(),
+            buckets: room_digest(&[make_test_task()]),
+        };
+        let request = TaskSyncMessage::RequestBuckets {
+            room_id: "room-1".to_string(),
+            buckets: vec![0, 7, 63],
+        };
+        for msg in [digest, request] {
+            assert_eq!(decode(&encode(&msg).unwrap()).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn room_digest_ignores_order_and_tracks_changes() {
+        let a = make_test_task();
+        let mut b = make_test_task();
+        b.id = TaskId::new();
+        let digest = room_digest([&a, &b]);
+        assert_eq!(digest.len(), TASK_DIGEST_BUCKETS);
+        assert_eq!(digest, room_digest([&b, &a]));
+
+        let mut edited = b.clone();
+        edited.status = LwwRegister::new(TaskStatus::Completed, 2000, "peer-b".into());
+        let changed = room_digest([&a, &edited]);
+        let differing: Vec<usize> = (0..TASK_DIGEST_BUCKETS)
+            .filter(|&i| digest[i] != changed[i])
+            .collect();
+        assert_eq!(differing, [digest_bucket(&b.id)]);
+        assert_eq!(room_digest([]), vec![0; TASK_DIGEST_BUCKETS]);
+    }
+
     #[test]
     fn decode_corrupted_bytes_fails() {
         let result = decode(&[0xFF, 0xFE, 0xFD, 0xFC]);
@@ -380,6 +746,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn round_trip_task_with_rich_fields() {
+        let mut task = make_test_task();
+        task.description = LwwRegister::new("line 1\nline 2".to_string(), 2000, "peer-a".into());
+        task.due = LwwRegister::new(Some(1_750_000_000_000), 2000, "peer-a".into());
+        task.priority = LwwRegister::new(TaskPriority::Urgent, 2000, "peer-a".into());
+        task.labels.insert(
+            "bug".to_string(),
+            LwwRegister::new(true, 2000, "peer-a".into()),
+        );
+        task.labels.insert(
+            "ui".to_string(),
+            LwwRegister::new(false, 3000, "peer-b".into()),
+        );
+        let bytes = postcard::to_allocvec(&task).expect("serialize");
+        let decoded: Task = postcard::from_bytes(&bytes).expect("deserialize");
+        assert_eq!(task, decoded);
+        assert_eq!(decoded.label_names().collect::<Vec<_>>(), ["bug"]);
+        assert!(decoded.has_label("bug"));
+        assert!(!decoded.has_label("ui"));
+    }
+
+    #[test]
+    fn round_trip_field_update_label() {
+        let update = TaskFieldUpdate::Label {
+            label: "backend".to_string(),
+            present: LwwRegister::new(false, 4000, "peer-c".to_string()),
+        };
+        let bytes = postcard::to_allocvec(&update).expect("serialize");
+        let decoded: TaskFieldUpdate = postcard::from_bytes(&bytes).expect("deserialize");
+        assert_eq!(update, decoded);
+    }
+
+    #[test]
+    fn round_trip_task_with_dependencies() {
+        let mut task = make_test_task();
+        let (parent, blocker, unblocked) = (TaskId::new(), TaskId::new(), TaskId::new());
+        task.parent = LwwRegister::new(Some(parent), 2000, "peer-a".into());
+        task.blocked_by.insert(
+            blocker.clone(),
+            LwwRegister::new(true, 2000, "peer-a".into()),
+        );
+        task.blocked_by
+            .insert(unblocked, LwwRegister::new(false, 3000, "peer-b".into()));
+        let msg = TaskSyncMessage::FieldUpdate {
+            task_id: task.id.clone(),
+            room_id: "room-1".to_string(),
+            field: TaskFieldUpdate::BlockedBy {
+                blocker: blocker.clone(),
+                present: LwwRegister::new(true, 2000, "peer-a".into()),
+            },
+        };
+        assert_eq!(decode(&encode(&msg).unwrap()).unwrap(), msg);
+        let bytes = postcard::to_allocvec(&task).expect("serialize");
+        let decoded: Task = postcard::from_bytes(&bytes).expect("deserialize");
+        assert_eq!(task, decoded);
+        assert_eq!(decoded.blocker_ids().collect::<Vec<_>>(), [&blocker]);
+    }
+
+    #[test]
+    fn round_trip_task_with_comment_thread() {
+        let mut task = make_test_task();
+        let question = TaskComment {
+            id: CommentId::new(),
+            reply_to: None,
+            author: "peer-a".to_string(),
+            body: "Which browser?".to_string(),
+            timestamp: 2000,
+        };
+        let answer = TaskComment {
+            id: CommentId::new(),
+            reply_to: Some(question.id.clone()),
+            author: "peer-b".to_string(),
+            body: "Firefox".to_string(),
+            timestamp: 3000,
+        };
+        assert!(question.id < answer.id);
+        task.comments.insert(question.id.clone(), question);
+        let msg = TaskSyncMessage::FieldUpdate {
+            task_id: task.id.clone(),
+            room_id: "room-1".to_string(),
+            field: TaskFieldUpdate::Comment(answer.clone()),
+        };
+        assert_eq!(decode(&encode(&msg).unwrap()).unwrap(), msg);
+        task.comments.insert(answer.id.clone(), answer);
+        let bytes = postcard::to_allocvec(&task).expect("serialize");
+        let decoded: Task = postcard::from_bytes(&bytes).expect("deserialize");
+        assert_eq!(task, decoded);
+    }
+
+    #[test]
+    fn priority_parse_and_display() {
+        for priority in [
+            TaskPriority::Low,
+            TaskPriority::Normal,
+            TaskPriority::High,
+            TaskPriority::Urgent,
+        ] {
+            assert_eq!(priority.to_string().parse::<TaskPriority>(), Ok(priority));
+        }
+        assert_eq!("HIGH".parse::<TaskPriority>(), Ok(TaskPriority::High));
+        assert!("soon".parse::<TaskPriority>().is_err());
+        assert!(TaskPriority::Urgent > TaskPriority::Low);
+    }
+
     #[test]
     fn lww_register_generic_with_option() {
         let reg: LwwRegister<Option<String>> =
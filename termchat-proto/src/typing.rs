@@ -14,6 +14,7 @@ pub struct TypingMessage {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
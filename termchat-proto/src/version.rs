@@ -0,0 +1,245 @@
+//! Protocol version and feature negotiation.
+//!
+//! Postcard is not self-describing: a peer that receives an enum variant it
+//! does not know cannot skip it. To let old and new builds coexist, peers
+//! exchange a [`ProtocolHello`] when registering with the relay and with
+//! each other (in the Noise handshake payload, or in a handshake envelope
+//! when a chat session starts), then agree on a [`NegotiatedProtocol`]: the
+//! lower of the two versions and the intersection of their feature bits.
+//!
+//! Postcard ignores trailing bytes, so fields may be appended to
+//! [`ProtocolHello`] in later versions without breaking older decoders.
+
+use serde::{Deserialize, Serialize};
+
+/// Current wire protocol version spoken by this build.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Oldest protocol version this build can still talk to.
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
+
+/// Bit set of optional protocol features.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeatureFlags(u64);
+
+impl FeatureFlags {
+    /// No optional features.
+    pub const NONE: Self = Self(0);
+    /// Room directory and join protocol ([`crate::room`]).
+    pub const ROOMS: Self = Self(1 << 0);
+    /// Task synchronization ([`crate::task`]).
+    pub const TASK_SYNC: Self = Self(1 << 1);
+    /// Presence updates ([`crate::presence`]).
+    pub const PRESENCE: Self = Self(1 << 2);
+    /// Typing indicators ([`crate::typing`]).
+    pub const TYPING: Self = Self(1 << 3);
+    /// Compressed envelopes ([`crate::codec::encode_compressed`]).
+    pub const COMPRESSION: Self = Self(1 << 4);
+    /// Relay-brokered NAT traversal ([`crate::relay::RelayMessage::PunchRequest`]).
+    pub const HOLE_PUNCH: Self = Self(1 << 5);
+
+    /// All features supported by this build.
+    pub const SUPPORTED: Self = Self(
+        Self::ROOMS.0
+            | Self::TASK_SYNC.0
+            | Self::PRESENCE.0
+            | Self::TYPING.0
+            | Self::COMPRESSION.0
+            | Self::HOLE_PUNCH.0,
+    );
+
+    /// Creates a flag set from raw bits. Unknown bits are preserved.
+    #[must_use]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits.
+    #[must_use]
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if every flag in `other` is set in `self`.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the flags set in both `self` and `other`.
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Returns the flags set in either `self` or `other`.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Version and feature advertisement exchanged at connection setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolHello {
+    /// Highest protocol version the sender speaks.
+    pub version: u16,
+    /// Oldest protocol version the sender still accepts.
+    pub min_version: u16,
+    /// Optional features the sender supports.
+    pub features: FeatureFlags,
+}
+
+impl Default for ProtocolHello {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+/// Outcome of a successful version negotiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedProtocol {
+    /// Protocol version both sides will speak.
+    pub version: u16,
+    /// Features both sides support.
+    pub features: FeatureFlags,
+}
+
+/// Errors from protocol version negotiation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VersionError {
+    /// The two sides share no protocol version.
+    #[error(
+        "incompatible protocol versions: local {local_min}..={local_max}, remote {remote_min}..={remote_max}"
+    )]
+    Incompatible {
+        /// Oldest version the local side accepts.
+        local_min: u16,
+        /// Newest version the local side speaks.
+        local_max: u16,
+        /// Oldest version the remote side accepts.
+        remote_min: u16,
+        /// Newest version the remote side speaks.
+        remote_max: u16,
+    },
+}
+
+impl ProtocolHello {
+    /// The hello advertised by this build.
+    #[must_use]
+    pub const fn current() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            features: FeatureFlags::SUPPORTED,
+        }
+    }
+
+    /// Negotiates a common protocol with `remote`.
+    ///
+    /// Picks the lower of the two versions and the intersection of features.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VersionError::Incompatible`] if the chosen version is older
+    /// than either side's minimum.
+    pub fn negotiate(&self, remote: &Self) -> Result<NegotiatedProtocol, VersionError> {
+        let version = self.version.min(remote.version);
+        if version < self.min_version.max(remote.min_version) {
+            return Err(VersionError::Incompatible {
+                local_min: self.min_version,
+                local_max: self.version,
+                remote_min: remote.min_version,
+                remote_max: remote.version,
+            });
+        }
+        Ok(NegotiatedProtocol {
+            version,
+            features: self.features.intersection(remote.features),
+        })
+    }
+}
+
+/// Encodes a [`ProtocolHello`] using postcard.
+///
+/// # Errors
+///
+/// Returns an error string if serialization fails.
+pub fn encode_hello(hello: &ProtocolHello) -> Result<Vec<u8>, String> {
+    postcard::to_allocvec(hello).map_err(|e| format!("hello encode error: {e}"))
+}
+
+/// Decodes a [`ProtocolHello`] from postcard bytes.
+///
+/// Trailing bytes (fields added by newer versions) are ignored.
+///
+/// # Errors
+///
+/// Returns an error string if deserialization fails.
+pub fn decode_hello(bytes: &[u8]) -> Result<ProtocolHello, String> {
+    postcard::from_bytes(bytes).map_err(|e| format!("hello decode error: {e}"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn hello(min_version: u16, version: u16, features: FeatureFlags) -> ProtocolHello {
+        ProtocolHello {
+            version,
+            min_version,
+            features,
+        }
+    }
+
+    #[test]
+    fn hello_round_trip() {
+        let original = ProtocolHello::current();
+        let bytes = encode_hello(&original).unwrap();
+        assert_eq!(decode_hello(&bytes).unwrap(), original);
+    }
+
+    #[test]
+    fn decode_hello_ignores_appended_fields() {
+        let mut bytes = encode_hello(&ProtocolHello::current()).unwrap();
+        bytes.extend_from_slice(&[0x05, 0xaa, 0xbb]);
+        assert_eq!(decode_hello(&bytes).unwrap(), ProtocolHello::current());
+    }
+
+    #[test]
+    fn negotiate_picks_lower_version_and_common_features() {
+        let local = hello(1, 3, FeatureFlags::ROOMS.union(FeatureFlags::TYPING));
+        let remote = hello(1, 2, FeatureFlags::TYPING.union(FeatureFlags::PRESENCE));
+        let negotiated = local.negotiate(&remote).unwrap();
+        assert_eq!(negotiated.version, 2);
+        assert_eq!(negotiated.features, FeatureFlags::TYPING);
+        assert_eq!(remote.negotiate(&local).unwrap(), negotiated);
+    }
+
+    #[test]
+    fn negotiate_rejects_disjoint_versions() {
+        let local = hello(3, 4, FeatureFlags::SUPPORTED);
+        let remote = hello(1, 2, FeatureFlags::SUPPORTED);
+        assert!(matches!(
+            local.negotiate(&remote),
+            Err(VersionError::Incompatible {
+                local_min: 3,
+                remote_max: 2,
+                ..
+            })
+        ));
+        assert!(remote.negotiate(&local).is_err());
+    }
+
+    #[test]
+    fn feature_flags_ops() {
+        let flags = FeatureFlags::ROOMS.union(FeatureFlags::TASK_SYNC);
+        assert!(flags.contains(FeatureFlags::ROOMS));
+        assert!(!flags.contains(FeatureFlags::TYPING));
+        assert!(FeatureFlags::SUPPORTED.contains(flags));
+        assert_eq!(FeatureFlags::from_bits(flags.bits()), flags);
+        // Unknown bits from newer peers survive a round trip.
+        assert_eq!(FeatureFlags::from_bits(1 << 40).bits(), 1 << 40);
+    }
+}
@@ -0,0 +1,504 @@
+//! Access control for the relay server.
+//!
+//! By default the relay is open: any peer that sends a `Register` is
+//! admitted and may create rooms. Operators running a relay for a single
+//! organisation can restrict this with two independent policies:
+//!
+//! - [`AccessPolicy`] gates registration. A peer is admitted if the
+//!   `PeerId` it registers with is on the static allowlist, or if it
+//!   presents a valid signed invite token in `Register`.
+//! - [`RoomCreationPolicy`] gates `RegisterRoom`, limiting who may create
+//!   rooms at all and reserving specific room names for specific peers.
+//!
+//! # Invite tokens
+//!
+//! Tokens are minted by the relay operator with the shared `invite_secret`
+//! and have the form `v1.<peer>.<expires>.<signature>`:
+//!
+//! - `peer` is the `PeerId` the token is bound to, or `*` for any peer.
+//! - `expires` is the expiry time in seconds since the Unix epoch.
+//! - `signature` is the hex-encoded HMAC-SHA256 of `v1.<peer>.<expires>`.
+//!
+//! Peer IDs may contain dots, so the token is parsed from the right.
+//!
+//! A wildcard token is claimed by the first peer that redeems it and from
+//! then on only admits that peer, so a leaked wildcard token cannot be
+//! shared around. Claims are kept in memory and are forgotten when the
+//! relay restarts.
+//!
+//! # Limitations
+//!
+//! `Register` carries no proof that the client owns the `PeerId` it
+//! claims: `PeerId`s are not derived from identity keys, and the relay
+//! never sees a client's key. Anyone who knows an allowlisted `PeerId`, or
+//! holds a peer's invite token, can register as that peer. Treat the
+//! allowlist as a filter rather than authentication, prefer peer-bound
+//! invite tokens with short lifetimes, and distribute tokens over a
+//! private channel.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Version prefix of the invite token format.
+const TOKEN_VERSION: &str = "v1";
+
+/// Token peer field that matches any `PeerId`.
+const ANY_PEER: &str = "*";
+
+/// Reasons a registration or room creation can be refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AccessError {
+    /// The peer is not allowlisted and presented no invite token.
+    #[error("peer is not authorized to use this relay")]
+    NotAllowed,
+    /// The invite token could not be parsed.
+    #[error("malformed invite token")]
+    MalformedToken,
+    /// The invite token signature does not match.
+    #[error("invalid invite token signature")]
+    BadSignature,
+    /// The invite token has expired.
+    #[error("invite token expired")]
+    TokenExpired,
+    /// The invite token was issued to a different peer.
+    #[error("invite token was issued to a different peer")]
+    PeerMismatch,
+    /// The wildcard invite token was already redeemed by another peer.
+    #[error("invite token was already redeemed by another peer")]
+    TokenClaimed,
+}
+
+// ---------------------------------------------------------------------------
+// Registration policy
+// ---------------------------------------------------------------------------
+
+/// Registration policy: static allowlist and/or signed invite tokens.
+///
+/// An empty policy (the default) admits every peer. Clones share the
+/// record of claimed wildcard tokens.
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    allowed_peers: HashSet<String>,
+    invite_secret: Option<Vec<u8>>,
+    /// Wildcard token -> (`PeerId` that redeemed it, expiry).
+    claimed: Arc<Mutex<HashMap<String, (String, u64)>>>,
+}
+
+impl AccessPolicy {
+    /// Creates a policy from an allowlist and an optional invite secret.
+    ///
+    /// If both are empty the policy is open.
+    #[must_use]
+    pub fn new(
+        allowed_peers: impl IntoIterator<Item = String>,
+        invite_secret: Option<&str>,
+    ) -> Self {
+        Self {
+            allowed_peers: allowed_peers.into_iter().collect(),
+            invite_secret: invite_secret.map(|s| s.as_bytes().to_vec()),
+            claimed: Arc::default(),
+        }
+    }
+
+    /// Returns `true` if this policy admits every peer.
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.allowed_peers.is_empty() && self.invite_secret.is_none()
+    }
+
+    /// Checks whether `peer_id` may register, using the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AccessError`] describing why the peer was refused.
+    pub fn check_registration(
+        &self,
+        peer_id: &str,
+        invite_token: Option<&str>,
+    ) -> Result<(), AccessError> {
+        self.check_registration_at(peer_id, invite_token, unix_now())
+    }
+
+    /// Checks whether `peer_id` may register at time `now` (Unix seconds).
+    ///
+    /// Allowlisted peers are always admitted. Otherwise a valid invite
+    /// token is required when an invite secret is configured. A wildcard
+    /// token is claimed by the first peer it admits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccessError::NotAllowed`] if the peer is neither
+    /// allowlisted nor presented a token, [`AccessError::TokenClaimed`] if
+    /// another peer already redeemed the wildcard token, or the token
+    /// verification error.
+    pub fn check_registration_at(
+        &self,
+        peer_id: &str,
+        invite_token: Option<&str>,
+        now: u64,
+    ) -> Result<(), AccessError> {
+        if self.is_open() || self.allowed_peers.contains(peer_id) {
+            return Ok(());
+        }
+        match (&self.invite_secret, invite_token) {
+            (Some(secret), Some(token)) => {
+                verify_invite(secret, token, peer_id, now)?;
+                let invite = parse_invite(token)?;
+                if invite.peer == ANY_PEER {
+                    self.claim(token, invite.expires, peer_id, now)?;
+                }
+                Ok(())
+            }
+            _ => Err(AccessError::NotAllowed),
+        }
+    }
+
+    /// Claims a verified wildcard token for `peer_id`, or checks that
+    /// `peer_id` is the peer that claimed it. Expired claims are dropped.
+    fn claim(&self, token: &str, expires: u64, peer_id: &str, now: u64) -> Result<(), AccessError> {
+        let mut claimed = self.claimed.lock().unwrap_or_else(PoisonError::into_inner);
+        claimed.retain(|_, (_, expires)| now < *expires);
+        let (owner, _) = claimed
+            .entry(token.to_string())
+            .or_insert_with(|| (peer_id.to_string(), expires));
+        let claimed_by_peer = owner == peer_id;
+        drop(claimed);
+        if claimed_by_peer {
+            Ok(())
+        } else {
+            Err(AccessError::TokenClaimed)
+        }
+    }
+}
+
+/// Mints an invite token signed with `secret`.
+///
+/// `peer_id` binds the token to a single peer; `None` yields a token the
+/// first peer to present it claims. `expires_at` is in seconds since the Unix epoch.
+#[must_use]
+pub fn mint_invite(secret: &[u8], peer_id: Option<&str>, expires_at: u64) -> String {
+    let body = format!(
+        "{TOKEN_VERSION}.{}.{expires_at}",
+        peer_id.unwrap_or(ANY_PEER)
+    );
+    let sig = hex::encode(sign(secret, &body));
+    format!("{body}.{sig}")
+}
+
+/// Verifies an invite token for `peer_id` at time `now` (Unix seconds).
+///
+/// # Errors
+///
+/// Returns [`AccessError::MalformedToken`], [`AccessError::BadSignature`],
+/// [`AccessError::TokenExpired`], or [`AccessError::PeerMismatch`].
+pub fn verify_invite(
+    secret: &[u8],
+    token: &str,
+    peer_id: &str,
+    now: u64,
+) -> Result<(), AccessError> {
+    let invite = parse_invite(token)?;
+
+    // Constant-time comparison via the MAC itself.
+    let mut mac = new_mac(secret);
+    mac.update(invite.body.as_bytes());
+    mac.verify_slice(&invite.signature)
+        .map_err(|_| AccessError::BadSignature)?;
+
+    if now >= invite.expires {
+        return Err(AccessError::TokenExpired);
+    }
+    if invite.peer != ANY_PEER && invite.peer != peer_id {
+        return Err(AccessError::PeerMismatch);
+    }
+    Ok(())
+}
+
+/// The fields of an invite token, before its signature is checked.
+struct Invite<'a> {
+    /// The signed part, `v1.<peer>.<expires>`.
+    body: &'a str,
+    peer: &'a str,
+    expires: u64,
+    signature: Vec<u8>,
+}
+
+/// Splits an invite token into its fields.
+fn parse_invite(token: &str) -> Result<Invite<'_>, AccessError> {
+    let (body, sig_hex) = token.rsplit_once('.').ok_or(AccessError::MalformedToken)?;
+    let (head, expires) = body.rsplit_once('.').ok_or(AccessError::MalformedToken)?;
+    let (version, peer) = head.split_once('.').ok_or(AccessError::MalformedToken)?;
+    if version != TOKEN_VERSION || peer.is_empty() {
+        return Err(AccessError::MalformedToken);
+    }
+    Ok(Invite {
+        body,
+        peer,
+        expires: expires.parse().map_err(|_| AccessError::MalformedToken)?,
+        signature: hex::decode(sig_hex).map_err(|_| AccessError::MalformedToken)?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Room creation policy
+// ---------------------------------------------------------------------------
+
+/// Per-room creation permissions enforced by the room registry.
+///
+/// The default policy lets any registered peer create any room.
+#[derive(Debug, Clone, Default)]
+pub struct RoomCreationPolicy {
+    /// Peers allowed to create rooms; `None` means everyone.
+    creators: Option<HashSet<String>>,
+    /// Lowercased room name → peers allowed to create a room with that name.
+    reserved: HashMap<String, HashSet<String>>,
+}
+
+impl RoomCreationPolicy {
+    /// Creates a room creation policy.
+    ///
+    /// `creators` restricts room creation to the listed peers (`None` for
+    /// no restriction). `reserved` maps room names (case-insensitive) to
+    /// the peers allowed to create them, overriding `creators`.
+    #[must_use]
+    pub fn new(
+        creators: Option<impl IntoIterator<Item = String>>,
+        reserved: impl IntoIterator<Item = (String, Vec<String>)>,
+    ) -> Self {
+        Self {
+            creators: creators.map(|c| c.into_iter().collect()),
+            reserved: reserved
+                .into_iter()
+                .map(|(name, peers)| (name.to_lowercase(), peers.into_iter().collect()))
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if `peer_id` may create a room called `name`.
+    #[must_use]
+    pub fn permits(&self, name: &str, peer_id: &str) -> bool {
+        if let Some(peers) = self.reserved.get(&name.to_lowercase()) {
+            return peers.contains(peer_id);
+        }
+        self.creators
+            .as_ref()
+            .is_none_or(|creators| creators.contains(peer_id))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/// Builds an HMAC-SHA256 instance keyed with `secret`.
+fn new_mac(secret: &[u8]) -> HmacSha256 {
+    // HMAC accepts keys of any length, so this never fails.
+    <HmacSha256 as Mac>::new_from_slice(secret).unwrap_or_else(|_| unreachable!())
+}
+
+/// Computes the HMAC-SHA256 of `body` under `secret`.
+fn sign(secret: &[u8], body: &str) -> Vec<u8> {
+    let mut mac = new_mac(secret);
+    mac.update(body.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Current time in seconds since the Unix epoch.
+#[must_use]
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+    const NOW: u64 = 1_700_000_000;
+
+    // --- registration policy ---
+
+    #[test]
+    fn default_policy_is_open() {
+        let policy = AccessPolicy::default();
+        assert!(policy.is_open());
+        assert!(policy.check_registration_at("anyone", None, NOW).is_ok());
+    }
+
+    #[test]
+    fn allowlisted_peer_admitted() {
+        let policy = AccessPolicy::new(["alice".to_string()], None);
+        assert!(policy.check_registration_at("alice", None, NOW).is_ok());
+        assert_eq!(
+            policy.check_registration_at("mallory", None, NOW),
+            Err(AccessError::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn invite_token_admits_unlisted_peer() {
+        let policy = AccessPolicy::new(["alice".to_string()], Some("test-secret"));
+        let token = mint_invite(SECRET, Some("bob"), NOW + 60);
+        assert!(
+            policy
+                .check_registration_at("bob", Some(&token), NOW)
+                .is_ok()
+        );
+        assert_eq!(
+            policy.check_registration_at("bob", None, NOW),
+            Err(AccessError::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn token_ignored_without_secret() {
+        let policy = AccessPolicy::new(["alice".to_string()], None);
+        let token = mint_invite(SECRET, Some("bob"), NOW + 60);
+        assert_eq!(
+            policy.check_registration_at("bob", Some(&token), NOW),
+            Err(AccessError::NotAllowed)
+        );
+    }
+
+    // --- invite tokens ---
+
+    #[test]
+    fn wildcard_token_admits_any_peer() {
+        let token = mint_invite(SECRET, None, NOW + 60);
+        assert!(token.starts_with("v1.*."));
+        assert!(verify_invite(SECRET, &token, "carol", NOW).is_ok());
+    }
+
+    #[test]
+    fn wildcard_token_is_claimed_by_first_peer() {
+        let policy = AccessPolicy::new([], Some("test-secret"));
+        let token = mint_invite(SECRET, None, NOW + 60);
+        assert!(
+            policy
+                .check_registration_at("carol", Some(&token), NOW)
+                .is_ok()
+        );
+        // Carol can reconnect with it; nobody else can use it.
+        assert!(
+            policy
+                .check_registration_at("carol", Some(&token), NOW + 30)
+                .is_ok()
+        );
+        assert_eq!(
+            policy.check_registration_at("mallory", Some(&token), NOW + 30),
+            Err(AccessError::TokenClaimed)
+        );
+        // Peer-bound tokens are unaffected.
+        let bob = mint_invite(SECRET, Some("bob"), NOW + 60);
+        assert!(policy.check_registration_at("bob", Some(&bob), NOW).is_ok());
+    }
+
+    #[test]
+    fn clones_share_claims() {
+        let policy = AccessPolicy::new([], Some("test-secret"));
+        let shared = policy.clone();
+        let token = mint_invite(SECRET, None, NOW + 60);
+        assert!(
+            shared
+                .check_registration_at("carol", Some(&token), NOW)
+                .is_ok()
+        );
+        assert_eq!(
+            policy.check_registration_at("mallory", Some(&token), NOW),
+            Err(AccessError::TokenClaimed)
+        );
+    }
+
+    #[test]
+    fn peer_id_with_dots_round_trips() {
+        let token = mint_invite(SECRET, Some("bob.laptop.1"), NOW + 60);
+        assert!(verify_invite(SECRET, &token, "bob.laptop.1", NOW).is_ok());
+    }
+
+    #[test]
+    fn expired_token_rejected() {
+        let token = mint_invite(SECRET, Some("bob"), NOW);
+        assert_eq!(
+            verify_invite(SECRET, &token, "bob", NOW),
+            Err(AccessError::TokenExpired)
+        );
+    }
+
+    #[test]
+    fn token_for_other_peer_rejected() {
+        let token = mint_invite(SECRET, Some("bob"), NOW + 60);
+        assert_eq!(
+            verify_invite(SECRET, &token, "mallory", NOW),
+            Err(AccessError::PeerMismatch)
+        );
+    }
+
+    #[test]
+    fn forged_token_rejected() {
+        let token = mint_invite(b"other-secret", Some("bob"), NOW + 60);
+        assert_eq!(
+            verify_invite(SECRET, &token, "bob", NOW),
+            Err(AccessError::BadSignature)
+        );
+
+        // Tampering with the expiry invalidates the signature.
+        let genuine = mint_invite(SECRET, Some("bob"), NOW + 60);
+        let tampered = genuine.replacen(&(NOW + 60).to_string(), &(NOW + 9999).to_string(), 1);
+        assert_eq!(
+            verify_invite(SECRET, &tampered, "bob", NOW),
+            Err(AccessError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn malformed_tokens_rejected() {
+        for token in [
+            "",
+            "v1",
+            "v1.bob.123",
+            "v2.bob.123.00",
+            "v1.bob.soon.00",
+            "v1.bob.123.zz",
+        ] {
+            assert_eq!(
+                verify_invite(SECRET, token, "bob", NOW),
+                Err(AccessError::MalformedToken),
+                "token {token:?}"
+            );
+        }
+    }
+
+    // --- room creation policy ---
+
+    #[test]
+    fn default_room_policy_permits_everyone() {
+        let policy = RoomCreationPolicy::default();
+        assert!(policy.permits("General", "anyone"));
+    }
+
+    #[test]
+    fn room_creators_restrict_creation() {
+        let policy = RoomCreationPolicy::new(Some(["alice".to_string()]), []);
+        assert!(policy.permits("General", "alice"));
+        assert!(!policy.permits("General", "bob"));
+    }
+
+    #[test]
+    fn reserved_room_names_override_creators() {
+        let policy = RoomCreationPolicy::new(
+            None::<Vec<String>>,
+            [("Announcements".to_string(), vec!["admin".to_string()])],
+        );
+        assert!(policy.permits("announcements", "admin"));
+        assert!(!policy.permits("ANNOUNCEMENTS", "bob"));
+        assert!(policy.permits("random", "bob"));
+    }
+}
@@ -6,7 +6,11 @@
 //! 3. TOML config file (`~/.config/termchat-relay/config.toml`)
 //! 4. Compiled defaults
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::access::{AccessPolicy, RoomCreationPolicy};
 
 /// Errors that can occur when loading relay configuration.
 #[derive(Debug, thiserror::Error)]
@@ -34,6 +38,7 @@ pub enum ConfigError {
 #[serde(default)]
 struct RelayConfigFile {
     server: ServerFileConfig,
+    access: AccessFileConfig,
 }
 
 /// `[server]` section of the relay config file.
@@ -43,6 +48,20 @@ struct ServerFileConfig {
     bind_addr: Option<String>,
     max_payload_size: Option<usize>,
     max_queue_size: Option<usize>,
+    queue_file: Option<PathBuf>,
+    shutdown_grace_secs: Option<u64>,
+    shutdown_retry_after_secs: Option<u64>,
+    redirect_url: Option<String>,
+}
+
+/// `[access]` section of the relay config file.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct AccessFileConfig {
+    allowed_peers: Option<Vec<String>>,
+    invite_secret: Option<String>,
+    room_creators: Option<Vec<String>>,
+    reserved_rooms: Option<HashMap<String, Vec<String>>>,
 }
 
 // ---------------------------------------------------------------------------
@@ -69,11 +88,32 @@ pub struct RelayCliArgs {
     #[arg(long)]
     pub max_queue_size: Option<usize>,
 
+    /// File to persist queued messages to on shutdown (and load, then
+    /// remove, at startup).
+    #[arg(long)]
+    pub queue_file: Option<PathBuf>,
+
     /// Log level filter (trace, debug, info, warn, error).
     #[arg(long, default_value = "info", env = "RELAY_LOG")]
     pub log_level: String,
+
+    /// Secret used to sign and verify invite tokens.
+    #[arg(long, env = "RELAY_INVITE_SECRET", hide_env_values = true)]
+    pub invite_secret: Option<String>,
+
+    /// Print an invite token for the given `PeerId` (`*` for the first peer
+    /// to redeem it) and exit.
+    #[arg(long, value_name = "PEER")]
+    pub mint_invite: Option<String>,
+
+    /// Lifetime of tokens printed by `--mint-invite`, in seconds.
+    #[arg(long, default_value_t = DEFAULT_INVITE_TTL_SECS)]
+    pub invite_ttl_secs: u64,
 }
 
+/// Default lifetime of minted invite tokens (7 days).
+const DEFAULT_INVITE_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
 // ---------------------------------------------------------------------------
 // Resolved configuration
 // ---------------------------------------------------------------------------
@@ -89,6 +129,26 @@ pub struct RelayConfig {
     pub max_queue_size: usize,
     /// Log level filter string.
     pub log_level: String,
+    /// File to persist queued messages to on shutdown, if any.
+    pub queue_file: Option<PathBuf>,
+    /// How long to wait for connected peers to disconnect after a shutdown
+    /// notice before closing the server.
+    pub shutdown_grace: Duration,
+    /// Delay clients are asked to wait before reconnecting after a shutdown.
+    pub shutdown_retry_after: Duration,
+    /// Alternate relay URL advertised to clients on shutdown, if any.
+    pub redirect_url: Option<String>,
+    /// Peers (by `PeerId`) always allowed to register. Empty means no allowlist.
+    ///
+    /// `PeerId`s are not authenticated, so this filters rather than secures;
+    /// see [`crate::access`].
+    pub allowed_peers: Vec<String>,
+    /// Secret for signing and verifying invite tokens, if invites are enabled.
+    pub invite_secret: Option<String>,
+    /// Peers allowed to create rooms; `None` lets every peer create rooms.
+    pub room_creators: Option<Vec<String>>,
+    /// Room names (case-insensitive) reserved for specific creators.
+    pub reserved_rooms: HashMap<String, Vec<String>>,
 }
 
 impl Default for RelayConfig {
@@ -98,6 +158,14 @@ impl Default for RelayConfig {
             max_payload_size: 64 * 1024,
             max_queue_size: 1000,
             log_level: "info".to_string(),
+            queue_file: None,
+            shutdown_grace: Duration::from_secs(5),
+            shutdown_retry_after: Duration::from_secs(5),
+            redirect_url: None,
+            allowed_peers: Vec::new(),
+            invite_secret: None,
+            room_creators: None,
+            reserved_rooms: HashMap::new(),
         }
     }
 }
@@ -140,8 +208,48 @@ impl RelayConfig {
                 .or(file.server.max_queue_size)
                 .unwrap_or(defaults.max_queue_size),
             log_level: cli.log_level.clone(),
+            queue_file: cli
+                .queue_file
+                .clone()
+                .or_else(|| file.server.queue_file.clone()),
+            shutdown_grace: file
+                .server
+                .shutdown_grace_secs
+                .map_or(defaults.shutdown_grace, Duration::from_secs),
+            shutdown_retry_after: file
+                .server
+                .shutdown_retry_after_secs
+                .map_or(defaults.shutdown_retry_after, Duration::from_secs),
+            redirect_url: file.server.redirect_url.clone(),
+            allowed_peers: file.access.allowed_peers.clone().unwrap_or_default(),
+            invite_secret: cli
+                .invite_secret
+                .clone()
+                .or_else(|| file.access.invite_secret.clone()),
+            room_creators: file.access.room_creators.clone(),
+            reserved_rooms: file.access.reserved_rooms.clone().unwrap_or_default(),
         }
     }
+
+    /// Builds the registration [`AccessPolicy`] from this configuration.
+    #[must_use]
+    pub fn access_policy(&self) -> AccessPolicy {
+        AccessPolicy::new(
+            self.allowed_peers.iter().cloned(),
+            self.invite_secret.as_deref(),
+        )
+    }
+
+    /// Builds the [`RoomCreationPolicy`] from this configuration.
+    #[must_use]
+    pub fn room_creation_policy(&self) -> RoomCreationPolicy {
+        RoomCreationPolicy::new(
+            self.room_creators.clone(),
+            self.reserved_rooms
+                .iter()
+                .map(|(name, peers)| (name.clone(), peers.clone())),
+        )
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -173,6 +281,7 @@ fn load_config_file(
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -182,6 +291,14 @@ mod tests {
         assert_eq!(config.bind_addr, "0.0.0.0:9000");
         assert_eq!(config.max_payload_size, 64 * 1024);
         assert_eq!(config.max_queue_size, 1000);
+        assert!(config.queue_file.is_none());
+        assert_eq!(config.shutdown_grace, Duration::from_secs(5));
+        assert_eq!(config.shutdown_retry_after, Duration::from_secs(5));
+        assert!(config.redirect_url.is_none());
+        assert!(config.allowed_peers.is_empty());
+        assert!(config.invite_secret.is_none());
+        assert!(config.room_creators.is_none());
+        assert!(config.access_policy().is_open());
     }
 
     #[test]
@@ -203,10 +320,10 @@ max_queue_size = 500
 
     #[test]
     fn toml_parsing_partial() {
-        let toml_str = r#"
+        let toml_str = r"
 [server]
 max_queue_size = 2000
-"#;
+";
         let file: RelayConfigFile = toml::from_str(toml_str).unwrap();
         let cli = RelayCliArgs::default();
         let config = RelayConfig::resolve(&cli, &file);
@@ -246,6 +363,88 @@ max_payload_size = 32768
         assert_eq!(config.max_payload_size, 32768); // from file
     }
 
+    #[test]
+    fn toml_parsing_shutdown_settings() {
+        let toml_str = r#"
+[server]
+queue_file = "/var/lib/termchat-relay/queue.bin"
+shutdown_grace_secs = 10
+shutdown_retry_after_secs = 30
+redirect_url = "ws://backup:9000/ws"
+"#;
+        let file: RelayConfigFile = toml::from_str(toml_str).unwrap();
+        let cli = RelayCliArgs::default();
+        let config = RelayConfig::resolve(&cli, &file);
+
+        assert_eq!(
+            config.queue_file,
+            Some(PathBuf::from("/var/lib/termchat-relay/queue.bin"))
+        );
+        assert_eq!(config.shutdown_grace, Duration::from_secs(10));
+        assert_eq!(config.shutdown_retry_after, Duration::from_secs(30));
+        assert_eq!(config.redirect_url.as_deref(), Some("ws://backup:9000/ws"));
+    }
+
+    #[test]
+    fn cli_queue_file_overrides_file() {
+        let toml_str = r#"
+[server]
+queue_file = "/from/file.bin"
+"#;
+        let file: RelayConfigFile = toml::from_str(toml_str).unwrap();
+        let cli = RelayCliArgs {
+            queue_file: Some(PathBuf::from("/from/cli.bin")),
+            ..Default::default()
+        };
+        let config = RelayConfig::resolve(&cli, &file);
+        assert_eq!(config.queue_file, Some(PathBuf::from("/from/cli.bin")));
+    }
+
+    #[test]
+    fn toml_parsing_access_settings() {
+        let toml_str = r#"
+[access]
+allowed_peers = ["alice", "bob"]
+invite_secret = "from-file"
+room_creators = ["alice"]
+
+[access.reserved_rooms]
+Announcements = ["alice"]
+"#;
+        let file: RelayConfigFile = toml::from_str(toml_str).unwrap();
+        let cli = RelayCliArgs::default();
+        let config = RelayConfig::resolve(&cli, &file);
+
+        assert_eq!(config.allowed_peers, vec!["alice", "bob"]);
+        assert_eq!(config.invite_secret.as_deref(), Some("from-file"));
+        assert_eq!(config.room_creators, Some(vec!["alice".to_string()]));
+
+        let access = config.access_policy();
+        assert!(!access.is_open());
+        assert!(access.check_registration("bob", None).is_ok());
+        assert!(access.check_registration("mallory", None).is_err());
+
+        let rooms = config.room_creation_policy();
+        assert!(rooms.permits("general", "alice"));
+        assert!(!rooms.permits("general", "bob"));
+        assert!(!rooms.permits("announcements", "bob"));
+    }
+
+    #[test]
+    fn cli_invite_secret_overrides_file() {
+        let toml_str = r#"
+[access]
+invite_secret = "from-file"
+"#;
+        let file: RelayConfigFile = toml::from_str(toml_str).unwrap();
+        let cli = RelayCliArgs {
+            invite_secret: Some("from-cli".to_string()),
+            ..Default::default()
+        };
+        let config = RelayConfig::resolve(&cli, &file);
+        assert_eq!(config.invite_secret.as_deref(), Some("from-cli"));
+    }
+
     #[test]
     fn missing_config_file_returns_defaults() {
         let result = load_config_file(None);
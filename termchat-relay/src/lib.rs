@@ -2,9 +2,12 @@
 //!
 //! Exposes the relay server for use in tests and embedding.
 //! The relay server accepts WebSocket connections, registers peers,
-//! and routes encrypted payloads between them.
+//! and routes encrypted payloads between them. [`local::LocalConnection`]
+//! attaches peers in-process, without a socket, for simulations.
 
+pub mod access;
 pub mod config;
+pub mod local;
 pub mod relay;
 pub mod rooms;
 pub mod store;
@@ -0,0 +1,263 @@
+//! In-process relay connections.
+//!
+//! [`LocalConnection`] attaches a peer to a [`RelayState`] without a
+//! WebSocket: frames the peer sends go through the same admission and
+//! routing code as [`handle_socket`](crate::relay::handle_socket), and frames
+//! for the peer are read straight from its writer channel. Simulations use
+//! it to run many clients and a relay in one process, under virtual time.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::extract::ws::Message;
+use termchat_proto::relay::{self, RelayMessage};
+use termchat_proto::version::ProtocolHello;
+use tokio::sync::mpsc;
+
+use crate::relay::{Registration, RelayState, admit_registration, handle_binary_message};
+
+/// A registered peer's in-process connection to the relay.
+///
+/// Dropping the connection without calling [`LocalSender::disconnect`]
+/// behaves like a socket that died silently: the relay notices on its next
+/// attempt to forward to the peer, then stores the message and unregisters.
+pub struct LocalConnection {
+    /// Handle used to send frames as this peer.
+    sender: LocalSender,
+    /// Frames the relay writes to this peer.
+    rx: mpsc::UnboundedReceiver<Message>,
+    /// Messages stored while the peer was offline, delivered first.
+    queued: VecDeque<Vec<u8>>,
+}
+
+impl LocalConnection {
+    /// Register `peer_id` with the relay.
+    ///
+    /// Messages stored for the peer while it was offline are delivered
+    /// before anything routed after registration, as on a real connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message the relay would send before closing the
+    /// connection if the registration is refused (drain mode or access
+    /// policy).
+    pub async fn connect(state: Arc<RelayState>, peer_id: &str) -> Result<Self, RelayMessage> {
+        let registration = Registration {
+            peer_id: peer_id.to_string(),
+            invite_token: None,
+            protocol: ProtocolHello::current(),
+        };
+        admit_registration(&state, &registration).await?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let own = tx.downgrade();
+        state.register(peer_id, tx).await;
+
+        let queued = state
+            .store
+            .drain(peer_id)
+            .await
+            .into_iter()
+            .filter_map(|stored| {
+                relay::encode(&RelayMessage::RelayPayload {
+                    from: stored.from,
+                    to: peer_id.to_string(),
+                    payload: stored.payload,
+                })
+                .ok()
+            })
+            .collect();
+
+        Ok(Self {
+            sender: LocalSender {
+                peer_id: peer_id.to_string(),
+                state,
+                own,
+                open: Arc::new(AtomicBool::new(true)),
+            },
+            rx,
+            queued,
+        })
+    }
+
+    /// A handle for sending frames as this peer.
+    #[must_use]
+    pub fn sender(&self) -> LocalSender {
+        self.sender.clone()
+    }
+
+    /// Receive the next encoded [`RelayMessage`] for this peer.
+    ///
+    /// Returns `None` once the relay closes the connection or the peer
+    /// disconnects.
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        if let Some(frame) = self.queued.pop_front() {
+            return Some(frame);
+        }
+        loop {
+            match self.rx.recv().await? {
+                Message::Binary(data) => return Some(data.to_vec()),
+                Message::Close(_) => {
+                    self.sender.disconnect().await;
+                    return None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Sends frames to the relay on behalf of a [`LocalConnection`]'s peer.
+///
+/// Clones share the connection; once it is disconnected every clone stops
+/// sending.
+#[derive(Clone)]
+pub struct LocalSender {
+    peer_id: String,
+    state: Arc<RelayState>,
+    /// The connection's writer channel, to tell it apart from a newer
+    /// registration of the same peer.
+    own: mpsc::WeakUnboundedSender<Message>,
+    open: Arc<AtomicBool>,
+}
+
+impl LocalSender {
+    /// The peer this connection is registered as.
+    #[must_use]
+    pub fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    /// Returns `true` until the connection is disconnected.
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Hand an encoded [`RelayMessage`] to the relay as if it arrived on
+    /// the peer's socket.
+    ///
+    /// Returns `false` if the connection is closed.
+    pub async fn send(&self, frame: &[u8]) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+        handle_binary_message(&self.peer_id, None, frame, &self.state).await;
+        true
+    }
+
+    /// Close the connection and unregister the peer, unless it has since
+    /// registered again on a newer connection.
+    pub async fn disconnect(&self) {
+        if !self.open.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        let current = self.state.get_sender(&self.peer_id).await;
+        let is_ours = matches!(
+            (current, self.own.upgrade()),
+            (Some(current), Some(own)) if current.same_channel(&own)
+        );
+        if is_ours {
+            self.state.unregister(&self.peer_id).await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn payload_frame(from: &str, to: &str, payload: &[u8]) -> Vec<u8> {
+        relay::encode(&RelayMessage::RelayPayload {
+            from: from.to_string(),
+            to: to.to_string(),
+            payload: payload.to_vec(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn routes_payload_between_local_peers() {
+        let state = Arc::new(RelayState::new());
+        let alice = LocalConnection::connect(Arc::clone(&state), "alice")
+            .await
+            .unwrap();
+        let mut bob = LocalConnection::connect(Arc::clone(&state), "bob")
+            .await
+            .unwrap();
+
+        assert!(
+            alice
+                .sender()
+                .send(&payload_frame("spoofed", "bob", b"hi"))
+                .await
+        );
+        let frame = bob.recv().await.unwrap();
+        assert_eq!(
+            relay::decode(&frame).unwrap(),
+            RelayMessage::RelayPayload {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                payload: b"hi".to_vec(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn stored_messages_delivered_on_reconnect() {
+        let state = Arc::new(RelayState::new());
+        let alice = LocalConnection::connect(Arc::clone(&state), "alice")
+            .await
+            .unwrap();
+        let bob = LocalConnection::connect(Arc::clone(&state), "bob")
+            .await
+            .unwrap();
+        bob.sender().disconnect().await;
+        assert!(
+            !bob.sender()
+                .send(&payload_frame("bob", "alice", b"x"))
+                .await
+        );
+
+        alice
+            .sender()
+            .send(&payload_frame("alice", "bob", b"later"))
+            .await;
+        let mut bob = LocalConnection::connect(Arc::clone(&state), "bob")
+            .await
+            .unwrap();
+        let frame = bob.recv().await.unwrap();
+        assert!(matches!(
+            relay::decode(&frame).unwrap(),
+            RelayMessage::RelayPayload { payload, .. } if payload == b"later"
+        ));
+    }
+
+    #[tokio::test]
+    async fn stale_disconnect_keeps_newer_registration() {
+        let state = Arc::new(RelayState::new());
+        let old = LocalConnection::connect(Arc::clone(&state), "alice")
+            .await
+            .unwrap();
+        let _new = LocalConnection::connect(Arc::clone(&state), "alice")
+            .await
+            .unwrap();
+        old.sender().disconnect().await;
+        assert!(state.get_sender("alice").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn draining_relay_refuses_connection() {
+        let state = Arc::new(RelayState::new());
+        state
+            .drain(
+                crate::relay::ShutdownNotice::default(),
+                std::time::Duration::ZERO,
+            )
+            .await;
+        let refused = LocalConnection::connect(state, "alice").await;
+        assert!(matches!(refused, Err(RelayMessage::Shutdown { .. })));
+    }
+}
@@ -16,12 +16,27 @@
 //! # Or via environment variable (backward compatible)
 //! RELAY_ADDR=127.0.0.1:8080 cargo run --bin termchat-relay
 //! ```
+//!
+//! # Access control
+//!
+//! Registration can be restricted with an `[access]` allowlist and/or signed
+//! invite tokens. Mint a token for a peer (or `*` for whichever peer redeems
+//! it first) with:
+//!
+//! ```bash
+//! RELAY_INVITE_SECRET=... cargo run --bin termchat-relay -- --mint-invite alice
+//! ```
+//!
+//! On SIGTERM (or Ctrl-C) the relay drains: it stops accepting registrations,
+//! tells connected clients when to reconnect, waits for their writers to
+//! flush, and persists queued messages to `queue_file` if configured.
 
 use std::sync::Arc;
 
 use clap::Parser;
+use termchat_relay::access;
 use termchat_relay::config::{RelayCliArgs, RelayConfig};
-use termchat_relay::relay::{self, RelayState};
+use termchat_relay::relay::{self, RelayState, ShutdownNotice};
 use termchat_relay::store::MessageStore;
 
 #[tokio::main]
@@ -37,6 +52,20 @@ async fn main() {
         }
     };
 
+    if let Some(ref peer) = cli.mint_invite {
+        let Some(ref secret) = config.invite_secret else {
+            eprintln!("Error: --mint-invite requires an invite secret");
+            std::process::exit(1);
+        };
+        let peer = (peer != "*").then_some(peer.as_str());
+        let expires_at = access::unix_now().saturating_add(cli.invite_ttl_secs);
+        println!(
+            "{}",
+            access::mint_invite(secret.as_bytes(), peer, expires_at)
+        );
+        return;
+    }
+
     // Initialize tracing with the resolved log level.
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.log_level));
@@ -45,13 +74,38 @@ async fn main() {
     tracing::info!(addr = %config.bind_addr, "starting termchat relay server");
 
     let store = MessageStore::with_max_queue_size(config.max_queue_size);
-    let state = Arc::new(RelayState::with_config(config.max_payload_size, store));
+    if let Some(ref path) = config.queue_file {
+        match store.load_from(path).await {
+            Ok(count) => tracing::info!(count, path = %path.display(), "restored queued messages"),
+            Err(e) => tracing::warn!(error = %e, path = %path.display(), "failed to restore queue"),
+        }
+    }
+    let access_policy = config.access_policy();
+    if !access_policy.is_open() {
+        tracing::info!(
+            allowed_peers = config.allowed_peers.len(),
+            invites = config.invite_secret.is_some(),
+            "registration restricted by access policy"
+        );
+    }
+    let state = Arc::new(
+        RelayState::with_config(config.max_payload_size, store)
+            .with_access_control(access_policy, config.room_creation_policy()),
+    );
 
-    match relay::start_server_with_state(&config.bind_addr, state).await {
-        Ok((bound_addr, handle)) => {
+    match relay::start_server_with_state(&config.bind_addr, Arc::clone(&state)).await {
+        Ok((bound_addr, mut handle)) => {
             tracing::info!(addr = %bound_addr, "relay server listening");
-            if let Err(e) = handle.await {
-                tracing::error!(error = %e, "relay server task failed");
+            tokio::select! {
+                result = &mut handle => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "relay server task failed");
+                    }
+                }
+                () = shutdown_signal() => {
+                    drain_and_persist(&config, &state).await;
+                    handle.abort();
+                }
             }
         }
         Err(e) => {
@@ -60,3 +114,59 @@ async fn main() {
         }
     }
 }
+
+/// Drain connected peers and persist the offline queue before exiting.
+async fn drain_and_persist(config: &RelayConfig, state: &RelayState) {
+    let notice = ShutdownNotice {
+        reason: "relay shutting down".to_string(),
+        retry_after: config.shutdown_retry_after,
+        redirect_url: config.redirect_url.clone(),
+    };
+    let remaining = state.drain(notice, config.shutdown_grace).await;
+    tracing::info!(remaining, "relay drained");
+
+    if let Some(ref path) = config.queue_file {
+        match state.store.persist_to(path).await {
+            Ok(count) => tracing::info!(count, path = %path.display(), "persisted queued messages"),
+            Err(e) => {
+                tracing::error!(error = %e, path = %path.display(), "failed to persist queue");
+            }
+        }
+    } else {
+        let count = state.store.total_len().await;
+        if count > 0 {
+            tracing::warn!(count, "no queue_file configured, dropping queued messages");
+        }
+    }
+}
+
+/// Resolves when the process receives SIGTERM or Ctrl-C.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => tracing::info!("received Ctrl-C, shutting down"),
+        () = terminate => tracing::info!("received SIGTERM, shutting down"),
+    }
+}
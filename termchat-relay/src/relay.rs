@@ -5,22 +5,69 @@
 //! `PeerId`, and routes encrypted payloads between them. When a recipient is
 //! offline, messages are stored in a [`MessageStore`] and delivered when the
 //! peer reconnects.
+//!
+//! On shutdown the relay enters **drain mode** ([`RelayState::drain`]): new
+//! registrations are refused and every connected peer is sent a
+//! [`RelayMessage::Shutdown`] notice telling it when (and where) to reconnect,
+//! so clients back off instead of hammering a relay that is going away.
+//!
+//! Registration and room creation are subject to the [`crate::access`]
+//! policies installed via [`RelayState::with_access_control`] (open by
+//! default).
+//!
+//! For NAT traversal the relay acts as a rendezvous point: a
+//! [`RelayMessage::PunchRequest`] is forwarded to its target as
+//! [`RelayMessage::PunchCandidates`], led by the requester's public address
+//! as observed on its WebSocket connection.
 
 use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
 use termchat_proto::relay::{self, RelayMessage};
 use termchat_proto::room;
+use termchat_proto::version::{NegotiatedProtocol, ProtocolHello};
 use tokio::sync::{RwLock, mpsc};
 
+use crate::access::{AccessPolicy, RoomCreationPolicy};
 use crate::rooms::{self, RoomRegistry};
 use crate::store::MessageStore;
 
 /// Default maximum allowed payload size in bytes (64 KB).
 const DEFAULT_MAX_PAYLOAD_SIZE: usize = 64 * 1024;
 
+/// Maximum number of candidate addresses forwarded per punch request.
+const MAX_PUNCH_CANDIDATES: usize = 8;
+
+/// How often [`RelayState::drain`] checks whether all peers have disconnected.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shutdown notice broadcast to clients when the relay enters drain mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownNotice {
+    /// Human-readable shutdown reason.
+    pub reason: String,
+    /// Minimum delay clients should wait before reconnecting to this relay.
+    pub retry_after: Duration,
+    /// Alternate relay URL clients should reconnect to instead, if any.
+    pub redirect_url: Option<String>,
+}
+
+impl ShutdownNotice {
+    /// Builds the [`RelayMessage::Shutdown`] wire message for this notice.
+    #[must_use]
+    pub fn to_message(&self) -> RelayMessage {
+        RelayMessage::Shutdown {
+            reason: self.reason.clone(),
+            retry_after_ms: u64::try_from(self.retry_after.as_millis()).unwrap_or(u64::MAX),
+            redirect_url: self.redirect_url.clone(),
+        }
+    }
+}
+
 /// Shared relay server state holding the peer registry and message store.
 pub struct RelayState {
     /// Maps `PeerId` to a channel sender for delivering WebSocket messages.
@@ -31,6 +78,10 @@ pub struct RelayState {
     pub rooms: RoomRegistry,
     /// Maximum allowed payload size in bytes.
     max_payload_size: usize,
+    /// Set once the relay enters drain mode; new registrations are refused.
+    drain_notice: RwLock<Option<ShutdownNotice>>,
+    /// Who may register with this relay.
+    access: AccessPolicy,
 }
 
 impl Default for RelayState {
@@ -49,6 +100,8 @@ impl RelayState {
             store: MessageStore::new(),
             rooms: RoomRegistry::new(),
             max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            drain_notice: RwLock::new(None),
+            access: AccessPolicy::default(),
         }
     }
 
@@ -60,9 +113,21 @@ impl RelayState {
             store,
             rooms: RoomRegistry::new(),
             max_payload_size,
+            drain_notice: RwLock::new(None),
+            access: AccessPolicy::default(),
         }
     }
 
+    /// Installs registration and room creation policies.
+    ///
+    /// Replaces the room registry, so call this before serving connections.
+    #[must_use]
+    pub fn with_access_control(mut self, access: AccessPolicy, rooms: RoomCreationPolicy) -> Self {
+        self.access = access;
+        self.rooms = RoomRegistry::with_creation_policy(rooms);
+        self
+    }
+
     /// Registers a peer, storing the sender half of its message channel.
     ///
     /// If the peer was already registered, the old sender is replaced and
@@ -101,26 +166,101 @@ impl RelayState {
             let _ = sender.send(Message::Close(None));
         }
     }
+
+    /// Returns the number of currently registered peers.
+    pub async fn connection_count(&self) -> usize {
+        self.connections.read().await.len()
+    }
+
+    /// Returns the active shutdown notice if the relay is in drain mode.
+    pub async fn drain_notice(&self) -> Option<ShutdownNotice> {
+        self.drain_notice.read().await.clone()
+    }
+
+    /// Enter drain mode and disconnect all peers gracefully.
+    ///
+    /// 1. Refuse further registrations (late registrants get the notice).
+    /// 2. Queue a [`RelayMessage::Shutdown`] followed by a Close frame on
+    ///    every peer's writer channel. The channel is FIFO, so payloads
+    ///    already routed to a peer are flushed before the notice.
+    /// 3. Wait up to `grace` for all peers to disconnect.
+    ///
+    /// Payloads routed to peers that have already disconnected land in the
+    /// [`MessageStore`], which the caller should persist afterwards.
+    ///
+    /// Returns the number of peers still connected when the grace period
+    /// expired (0 if every writer flushed and closed in time).
+    pub async fn drain(&self, notice: ShutdownNotice, grace: Duration) -> usize {
+        let bytes = relay::encode(&notice.to_message()).ok();
+        *self.drain_notice.write().await = Some(notice);
+
+        {
+            let conns = self.connections.read().await;
+            tracing::info!(peers = conns.len(), "relay draining, notifying peers");
+            for (peer_id, sender) in conns.iter() {
+                if let Some(ref bytes) = bytes {
+                    let _ = sender.send(Message::Binary(bytes.clone().into()));
+                }
+                if sender.send(Message::Close(None)).is_err() {
+                    tracing::debug!(peer_id = %peer_id, "writer already closed during drain");
+                }
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + grace;
+        loop {
+            let remaining = self.connection_count().await;
+            if remaining == 0 || tokio::time::Instant::now() >= deadline {
+                if remaining > 0 {
+                    tracing::warn!(remaining, "drain grace period expired with peers connected");
+                }
+                return remaining;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
 }
 
 /// Handles an upgraded WebSocket connection for a single peer.
 ///
 /// The connection lifecycle:
-/// 1. Wait for a `Register` message.
+/// 1. Wait for a `Register` message and check it against drain mode, the
+///    access policy, and protocol version compatibility.
 /// 2. Register the peer and send `Registered` back.
 /// 3. Drain any queued messages for the peer.
 /// 4. Enter the message loop, routing payloads to recipients.
 /// 5. On disconnect, unregister the peer.
-pub async fn handle_socket(socket: WebSocket, state: Arc<RelayState>) {
+///
+/// `remote_addr` is the client's address as seen by the relay; its IP is
+/// reported to other peers as a hole-punching candidate.
+pub async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<RelayState>,
+    remote_addr: Option<SocketAddr>,
+) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     // Wait for the Register message.
-    let Some(peer_id) = wait_for_register(&mut ws_receiver).await else {
+    let Some(registration) = wait_for_register(&mut ws_receiver).await else {
         tracing::warn!("connection closed before registration");
         return;
     };
+    let peer_id = registration.peer_id.clone();
 
-    tracing::info!(peer_id = %peer_id, "peer registering");
+    let negotiated = match admit_registration(&state, &registration).await {
+        Ok(negotiated) => negotiated,
+        Err(refusal) => {
+            refuse_registration(&mut ws_sender, &refusal).await;
+            return;
+        }
+    };
+
+    tracing::info!(
+        peer_id = %peer_id,
+        version = negotiated.version,
+        features = negotiated.features.bits(),
+        "peer registering"
+    );
 
     // Create a channel for sending messages to this peer's WebSocket writer.
     let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
@@ -134,6 +274,7 @@ pub async fn handle_socket(socket: WebSocket, state: Arc<RelayState>) {
     // Send Registered acknowledgment.
     let ack = RelayMessage::Registered {
         peer_id: peer_id.clone(),
+        protocol: ProtocolHello::current(),
     };
     if let Err(e) = send_relay_msg(&mut ws_sender, &ack).await {
         tracing::error!(peer_id = %peer_id, error = %e, "failed to send Registered ack");
@@ -172,21 +313,28 @@ pub async fn handle_socket(socket: WebSocket, state: Arc<RelayState>) {
     let writer_peer_id = peer_id.clone();
     let mut write_task = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
+            let is_close = matches!(msg, Message::Close(_));
             if ws_sender.send(msg).await.is_err() {
                 tracing::warn!(peer_id = %writer_peer_id, "WebSocket write failed");
                 break;
             }
+            if is_close {
+                // Everything queued before the Close frame has been flushed;
+                // stop here so the connection is torn down promptly.
+                break;
+            }
         }
     });
 
     // Reader loop: process incoming messages from this peer.
     let reader_peer_id = peer_id.clone();
     let reader_state = Arc::clone(&state);
+    let observed_ip = remote_addr.map(|addr| addr.ip().to_canonical());
     let mut read_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = ws_receiver.next().await {
             match msg {
                 Message::Binary(data) => {
-                    handle_binary_message(&reader_peer_id, &data, &reader_state).await;
+                    handle_binary_message(&reader_peer_id, observed_ip, &data, &reader_state).await;
                 }
                 Message::Close(_) => {
                     tracing::info!(peer_id = %reader_peer_id, "received close frame");
@@ -214,22 +362,37 @@ pub async fn handle_socket(socket: WebSocket, state: Arc<RelayState>) {
     tracing::info!(peer_id = %peer_id, "peer disconnected and unregistered");
 }
 
+/// Fields of a client's `Register` message.
+pub(crate) struct Registration {
+    pub(crate) peer_id: String,
+    pub(crate) invite_token: Option<String>,
+    pub(crate) protocol: ProtocolHello,
+}
+
 /// Waits for the first message on the WebSocket, expecting a `Register` message.
 ///
-/// Returns the peer ID if a valid `Register` is received, or `None` if the
-/// connection closes or an invalid message arrives.
+/// Returns the registration details when a valid `Register` is received, or
+/// `None` if the connection closes or an invalid message arrives.
 async fn wait_for_register(
     receiver: &mut (impl StreamExt<Item = Result<Message, axum::Error>> + Unpin),
-) -> Option<String> {
+) -> Option<Registration> {
     while let Some(Ok(msg)) = receiver.next().await {
         match msg {
             Message::Binary(data) => match relay::decode(&data) {
-                Ok(RelayMessage::Register { peer_id }) => {
+                Ok(RelayMessage::Register {
+                    peer_id,
+                    invite_token,
+                    protocol,
+                }) => {
                     if peer_id.is_empty() {
                         tracing::warn!("received Register with empty peer_id");
                         return None;
                     }
-                    return Some(peer_id);
+                    return Some(Registration {
+                        peer_id,
+                        invite_token,
+                        protocol,
+                    });
                 }
                 Ok(other) => {
                     tracing::warn!(msg = ?other, "expected Register, got different message");
@@ -249,8 +412,64 @@ async fn wait_for_register(
     None
 }
 
+/// Decides whether a registration is admitted.
+///
+/// Checks, in order: drain mode, the access policy, and protocol version
+/// compatibility. Returns the negotiated protocol, or the message to send
+/// the peer before closing its connection.
+pub(crate) async fn admit_registration(
+    state: &RelayState,
+    registration: &Registration,
+) -> Result<NegotiatedProtocol, RelayMessage> {
+    let peer_id = &registration.peer_id;
+
+    // Drain mode: refuse the registration and tell the peer when to retry.
+    if let Some(notice) = state.drain_notice().await {
+        tracing::info!(peer_id = %peer_id, "refusing registration, relay is draining");
+        return Err(notice.to_message());
+    }
+
+    // Access control: refuse peers that are neither allowlisted nor invited.
+    if let Err(e) = state
+        .access
+        .check_registration(peer_id, registration.invite_token.as_deref())
+    {
+        tracing::warn!(peer_id = %peer_id, error = %e, "refusing registration");
+        return Err(RelayMessage::Error {
+            reason: e.to_string(),
+        });
+    }
+
+    // Version negotiation: refuse clients that share no protocol version.
+    ProtocolHello::current()
+        .negotiate(&registration.protocol)
+        .map_err(|e| {
+            tracing::warn!(peer_id = %peer_id, error = %e, "refusing registration");
+            RelayMessage::Error {
+                reason: e.to_string(),
+            }
+        })
+}
+
+/// Sends a final message to a peer whose registration was refused, then
+/// closes the connection.
+async fn refuse_registration(
+    ws_sender: &mut (impl SinkExt<Message, Error = axum::Error> + Unpin),
+    msg: &RelayMessage,
+) {
+    let _ = send_relay_msg(ws_sender, msg).await;
+    let _ = ws_sender.send(Message::Close(None)).await;
+}
+
 /// Handles a binary WebSocket message from a registered peer.
-async fn handle_binary_message(peer_id: &str, data: &[u8], state: &Arc<RelayState>) {
+///
+/// `observed_ip` is the peer's source IP, used for punch candidates.
+pub(crate) async fn handle_binary_message(
+    peer_id: &str,
+    observed_ip: Option<IpAddr>,
+    data: &[u8],
+    state: &Arc<RelayState>,
+) {
     let msg = match relay::decode(data) {
         Ok(m) => m,
         Err(e) => {
@@ -297,7 +516,9 @@ async fn handle_binary_message(peer_id: &str, data: &[u8], state: &Arc<RelayStat
 
             route_payload(state, &enforced_from, &to, payload).await;
         }
-        RelayMessage::Register { peer_id: new_id } => {
+        RelayMessage::Register {
+            peer_id: new_id, ..
+        } => {
             tracing::warn!(
                 peer_id = %peer_id,
                 new_id = %new_id,
@@ -307,6 +528,14 @@ async fn handle_binary_message(peer_id: &str, data: &[u8], state: &Arc<RelayStat
         RelayMessage::Room(room_bytes) => {
             handle_room_message(peer_id, &room_bytes, state).await;
         }
+        RelayMessage::PunchRequest {
+            to,
+            port,
+            local_addrs,
+        } => {
+            let addrs = punch_candidates(observed_ip, port, local_addrs);
+            handle_punch_request(peer_id, &to, addrs, state).await;
+        }
         other => {
             tracing::warn!(
                 peer_id = %peer_id,
@@ -317,6 +546,58 @@ async fn handle_binary_message(peer_id: &str, data: &[u8], state: &Arc<RelayStat
     }
 }
 
+/// Builds the candidate list for a punch request: the observed public
+/// address first, then the peer's own addresses, deduplicated and capped at
+/// [`MAX_PUNCH_CANDIDATES`]. Port 0 is never a valid candidate.
+fn punch_candidates(
+    observed_ip: Option<IpAddr>,
+    port: u16,
+    local_addrs: Vec<SocketAddr>,
+) -> Vec<SocketAddr> {
+    let observed = observed_ip.map(|ip| SocketAddr::new(ip, port));
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for addr in observed.into_iter().chain(local_addrs) {
+        if addrs.len() == MAX_PUNCH_CANDIDATES {
+            break;
+        }
+        if addr.port() != 0 && !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+    addrs
+}
+
+/// Forwards a peer's direct-connection candidates to the target peer.
+///
+/// Hole punching needs both peers online at once, so requests for offline
+/// targets are answered with an error instead of being queued.
+async fn handle_punch_request(
+    peer_id: &str,
+    to: &str,
+    addrs: Vec<SocketAddr>,
+    state: &Arc<RelayState>,
+) {
+    let refusal = if addrs.is_empty() {
+        Some("punch request has no usable candidates".to_string())
+    } else if state.get_sender(to).await.is_none() {
+        Some(format!("cannot punch to {to}: peer not connected"))
+    } else {
+        None
+    };
+    if let Some(reason) = refusal {
+        tracing::debug!(from = %peer_id, to = %to, reason = %reason, "punch request refused");
+        send_to_peer(state, peer_id, &RelayMessage::Error { reason }).await;
+        return;
+    }
+
+    tracing::debug!(from = %peer_id, to = %to, candidates = addrs.len(), "brokering punch");
+    let msg = RelayMessage::PunchCandidates {
+        from: peer_id.to_string(),
+        addrs,
+    };
+    send_to_peer(state, to, &msg).await;
+}
+
 /// Handles a room protocol message from a registered peer.
 #[allow(clippy::too_many_lines)]
 async fn handle_room_message(peer_id: &str, room_bytes: &[u8], state: &Arc<RelayState>) {
@@ -334,7 +615,14 @@ async fn handle_room_message(peer_id: &str, room_bytes: &[u8], state: &Arc<Relay
             name,
             admin_peer_id,
         } => {
-            match state.rooms.register(&room_id, &name, &admin_peer_id).await {
+            // The admin must be the registering peer, otherwise a peer could
+            // sidestep the creation policy by naming a permitted admin.
+            let result = if admin_peer_id == peer_id {
+                state.rooms.register(&room_id, &name, &admin_peer_id).await
+            } else {
+                Err(rooms::RegistryError::PermissionDenied)
+            };
+            match result {
                 Ok(()) => {
                     tracing::info!(
                         peer_id = %peer_id,
@@ -594,7 +882,8 @@ pub async fn start_server_with_state(
     let bound_addr = listener.local_addr()?;
 
     let handle = tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
+        let service = app.into_make_service_with_connect_info::<SocketAddr>();
+        if let Err(e) = axum::serve(listener, service).await {
             tracing::error!(error = %e, "relay server error");
         }
     });
@@ -606,7 +895,12 @@ pub async fn start_server_with_state(
 ///
 /// Binds to `127.0.0.1:0` (OS-assigned port) and returns the bound address
 /// and a [`tokio::task::JoinHandle`] for cleanup.
+///
+/// # Panics
+///
+/// Panics if the test server cannot bind.
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 pub async fn start_test_server() -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
     start_server("127.0.0.1:0")
         .await
@@ -616,12 +910,14 @@ pub async fn start_test_server() -> (std::net::SocketAddr, tokio::task::JoinHand
 /// axum handler that upgrades an HTTP request to a WebSocket connection.
 async fn ws_handler(
     ws: axum::extract::ws::WebSocketUpgrade,
+    axum::extract::ConnectInfo(remote_addr): axum::extract::ConnectInfo<SocketAddr>,
     axum::extract::State(state): axum::extract::State<Arc<RelayState>>,
 ) -> impl axum::response::IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, Some(remote_addr)))
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use futures_util::StreamExt;
@@ -641,6 +937,8 @@ mod tests {
         // Send Register.
         let reg = RelayMessage::Register {
             peer_id: peer_id.to_string(),
+            invite_token: None,
+            protocol: ProtocolHello::current(),
         };
         let bytes = relay::encode(&reg).unwrap();
         ws.send(tungstenite::Message::Binary(bytes.into()))
@@ -654,7 +952,8 @@ mod tests {
         assert_eq!(
             ack,
             RelayMessage::Registered {
-                peer_id: peer_id.to_string()
+                peer_id: peer_id.to_string(),
+                protocol: ProtocolHello::current(),
             }
         );
 
@@ -753,6 +1052,58 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn punch_request_forwards_observed_address_first() {
+        let (addr, _handle) = start_test_server().await;
+        let mut ws_alice = connect_and_register(addr, "alice").await;
+        let mut ws_bob = connect_and_register(addr, "bob").await;
+
+        let lan: SocketAddr = "192.168.1.10:7000".parse().unwrap();
+        let msg = RelayMessage::PunchRequest {
+            to: "bob".to_string(),
+            port: 7000,
+            local_addrs: vec![lan, "127.0.0.1:7000".parse().unwrap(), lan],
+        };
+        ws_send(&mut ws_alice, &msg).await;
+
+        match ws_recv(&mut ws_bob).await {
+            RelayMessage::PunchCandidates { from, addrs } => {
+                assert_eq!(from, "alice");
+                // Observed loopback address first, duplicates dropped.
+                assert_eq!(addrs, vec!["127.0.0.1:7000".parse().unwrap(), lan]);
+            }
+            other => panic!("expected PunchCandidates, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn punch_request_to_offline_peer_is_refused() {
+        let (addr, _handle) = start_test_server().await;
+        let mut ws_alice = connect_and_register(addr, "alice").await;
+
+        let msg = RelayMessage::PunchRequest {
+            to: "nobody".to_string(),
+            port: 7000,
+            local_addrs: Vec::new(),
+        };
+        ws_send(&mut ws_alice, &msg).await;
+
+        match ws_recv(&mut ws_alice).await {
+            RelayMessage::Error { reason } => assert!(reason.contains("not connected")),
+            other => panic!("expected Error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn punch_candidates_capped_and_port_zero_skipped() {
+        let local: Vec<SocketAddr> = (1..=20)
+            .map(|i| SocketAddr::from(([10, 0, 0, i], 7000)))
+            .collect();
+        let addrs = punch_candidates(Some(IpAddr::from([203, 0, 113, 7])), 0, local);
+        assert_eq!(addrs.len(), MAX_PUNCH_CANDIDATES);
+        assert_eq!(addrs[0], SocketAddr::from(([10, 0, 0, 1], 7000)));
+    }
+
     #[tokio::test]
     async fn peer_id_enforcement() {
         let (addr, _handle) = start_test_server().await;
@@ -839,6 +1190,259 @@ mod tests {
         }
     }
 
+    // --- Drain mode (graceful shutdown) ---
+
+    fn test_notice() -> ShutdownNotice {
+        ShutdownNotice {
+            reason: "relay restarting".to_string(),
+            retry_after: Duration::from_secs(3),
+            redirect_url: Some("ws://backup:9000/ws".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_notifies_connected_peers_and_waits_for_disconnect() {
+        let state = Arc::new(RelayState::new());
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+        let mut ws_alice = connect_and_register(addr, "alice").await;
+
+        let drain_state = Arc::clone(&state);
+        let drain = tokio::spawn(async move {
+            drain_state
+                .drain(test_notice(), Duration::from_secs(5))
+                .await
+        });
+
+        let notice = ws_recv(&mut ws_alice).await;
+        assert_eq!(notice, test_notice().to_message());
+
+        let close = ws_alice.next().await.unwrap().unwrap();
+        assert!(close.is_close());
+
+        let remaining = drain.await.unwrap();
+        assert_eq!(remaining, 0);
+        assert_eq!(state.connection_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn drain_flushes_payloads_queued_before_notice() {
+        let state = Arc::new(RelayState::new());
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+        let mut ws_alice = connect_and_register(addr, "alice").await;
+        let mut ws_bob = connect_and_register(addr, "bob").await;
+
+        let msg = RelayMessage::RelayPayload {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            payload: vec![7, 7, 7],
+        };
+        ws_send(&mut ws_alice, &msg).await;
+        // Give the relay time to route the payload onto Bob's writer channel.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let drain_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            drain_state
+                .drain(test_notice(), Duration::from_millis(500))
+                .await
+        });
+
+        match ws_recv(&mut ws_bob).await {
+            RelayMessage::RelayPayload { payload, .. } => assert_eq!(payload, vec![7, 7, 7]),
+            other => panic!("expected RelayPayload before shutdown, got {other:?}"),
+        }
+        assert!(matches!(
+            ws_recv(&mut ws_bob).await,
+            RelayMessage::Shutdown { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn register_refused_while_draining() {
+        use futures_util::SinkExt;
+
+        let state = Arc::new(RelayState::new());
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+        state.drain(test_notice(), Duration::ZERO).await;
+
+        let url = format!("ws://{addr}/ws");
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let reg = RelayMessage::Register {
+            peer_id: "late".to_string(),
+            invite_token: None,
+            protocol: ProtocolHello::current(),
+        };
+        ws.send(tungstenite::Message::Binary(
+            relay::encode(&reg).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+
+        let response = ws_recv(&mut ws).await;
+        assert_eq!(response, test_notice().to_message());
+        assert!(state.get_sender("late").await.is_none());
+    }
+
+    #[test]
+    fn shutdown_notice_to_message_converts_retry_after() {
+        let msg = test_notice().to_message();
+        match msg {
+            RelayMessage::Shutdown {
+                reason,
+                retry_after_ms,
+                redirect_url,
+            } => {
+                assert_eq!(reason, "relay restarting");
+                assert_eq!(retry_after_ms, 3_000);
+                assert_eq!(redirect_url.as_deref(), Some("ws://backup:9000/ws"));
+            }
+            other => panic!("expected Shutdown, got {other:?}"),
+        }
+    }
+
+    // --- access control ---
+
+    /// Helper: send a `Register` with an optional invite token and return the
+    /// relay's first response.
+    async fn register_with_token(
+        addr: std::net::SocketAddr,
+        peer_id: &str,
+        invite_token: Option<String>,
+    ) -> RelayMessage {
+        let reg = RelayMessage::Register {
+            peer_id: peer_id.to_string(),
+            invite_token,
+            protocol: ProtocolHello::current(),
+        };
+        send_register(addr, &reg).await
+    }
+
+    /// Helper: send an arbitrary `Register` message and return the relay's
+    /// first response.
+    async fn send_register(addr: std::net::SocketAddr, reg: &RelayMessage) -> RelayMessage {
+        use futures_util::SinkExt;
+
+        let url = format!("ws://{addr}/ws");
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        ws.send(tungstenite::Message::Binary(
+            relay::encode(reg).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+        ws_recv(&mut ws).await
+    }
+
+    #[tokio::test]
+    async fn access_policy_admits_allowlisted_and_invited_peers() {
+        let policy = AccessPolicy::new(["alice".to_string()], Some("org-secret"));
+        let state =
+            Arc::new(RelayState::new().with_access_control(policy, RoomCreationPolicy::default()));
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+
+        let _alice = connect_and_register(addr, "alice").await;
+
+        let token =
+            crate::access::mint_invite(b"org-secret", Some("bob"), crate::access::unix_now() + 60);
+        let response = register_with_token(addr, "bob", Some(token)).await;
+        assert_eq!(
+            response,
+            RelayMessage::Registered {
+                peer_id: "bob".to_string(),
+                protocol: ProtocolHello::current(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn access_policy_refuses_unknown_and_forged_peers() {
+        let policy = AccessPolicy::new(["alice".to_string()], Some("org-secret"));
+        let state =
+            Arc::new(RelayState::new().with_access_control(policy, RoomCreationPolicy::default()));
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+
+        let response = register_with_token(addr, "mallory", None).await;
+        assert!(matches!(response, RelayMessage::Error { .. }));
+
+        let forged = crate::access::mint_invite(
+            b"wrong-secret",
+            Some("mallory"),
+            crate::access::unix_now() + 60,
+        );
+        let response = register_with_token(addr, "mallory", Some(forged)).await;
+        assert!(matches!(response, RelayMessage::Error { .. }));
+        assert!(state.get_sender("mallory").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn room_creation_policy_enforced() {
+        let rooms = RoomCreationPolicy::new(Some(["alice".to_string()]), []);
+        let state = Arc::new(RelayState::new().with_access_control(AccessPolicy::default(), rooms));
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+        let mut ws_bob = connect_and_register(addr, "bob").await;
+
+        // Bob may not create rooms, even by naming alice as admin.
+        for admin in ["bob", "alice"] {
+            let register = room::RoomMessage::RegisterRoom {
+                room_id: format!("room-{admin}"),
+                name: "General".to_string(),
+                admin_peer_id: admin.to_string(),
+            };
+            ws_send_room(&mut ws_bob, &register).await;
+            match ws_recv(&mut ws_bob).await {
+                RelayMessage::Error { reason } => assert!(reason.contains("not permitted")),
+                other => panic!("expected Error, got {other:?}"),
+            }
+        }
+        assert!(state.rooms.list().await.is_empty());
+    }
+
+    // --- protocol versioning ---
+
+    #[tokio::test]
+    async fn register_ack_advertises_relay_protocol() {
+        let (addr, _handle) = start_test_server().await;
+        match register_with_token(addr, "alice", None).await {
+            RelayMessage::Registered { protocol, .. } => {
+                assert_eq!(protocol, ProtocolHello::current());
+            }
+            other => panic!("expected Registered, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn register_refused_for_incompatible_protocol() {
+        let state = Arc::new(RelayState::new());
+        let (addr, _handle) = start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .unwrap();
+        let reg = RelayMessage::Register {
+            peer_id: "future".to_string(),
+            invite_token: None,
+            protocol: ProtocolHello {
+                version: 99,
+                min_version: 99,
+                features: termchat_proto::version::FeatureFlags::NONE,
+            },
+        };
+        match send_register(addr, &reg).await {
+            RelayMessage::Error { reason } => assert!(reason.contains("incompatible")),
+            other => panic!("expected Error, got {other:?}"),
+        }
+        assert!(state.get_sender("future").await.is_none());
+    }
+
     /// Helper: send a room message wrapped in `RelayMessage::Room`.
     async fn ws_send_room(
         ws: &mut tokio_tungstenite::WebSocketStream<
@@ -908,12 +1512,14 @@ mod tests {
                     display_name: "Alice".to_string(),
                     is_admin: true,
                     is_agent: false,
+                    is_read_only: false,
                 },
                 room::MemberInfo {
                     peer_id: "bob".to_string(),
                     display_name: "Bob".to_string(),
                     is_admin: false,
                     is_agent: false,
+                    is_read_only: false,
                 },
             ],
             target_peer_id: "bob".to_string(),
@@ -14,6 +14,7 @@ use termchat_proto::relay::{self, RelayMessage};
 use termchat_proto::room::{self, RoomInfo, RoomMessage};
 use tokio::sync::RwLock;
 
+use crate::access::RoomCreationPolicy;
 use crate::relay::RelayState;
 
 /// Maximum number of rooms the registry will hold.
@@ -47,14 +48,19 @@ pub enum RegistryError {
     /// Failed to encode a protocol message.
     #[error("encoding failed: {0}")]
     EncodingFailed(String),
+    /// The peer is not permitted to create this room.
+    #[error("not permitted to create this room")]
+    PermissionDenied,
 }
 
 /// In-memory directory of registered rooms.
 ///
 /// Thread-safe via [`RwLock`]. Supports register, unregister, list, and
-/// admin lookup operations.
+/// admin lookup operations. Room creation is checked against a
+/// [`RoomCreationPolicy`] (open by default).
 pub struct RoomRegistry {
     rooms: RwLock<HashMap<String, RoomRegistryEntry>>,
+    creation_policy: RoomCreationPolicy,
 }
 
 impl Default for RoomRegistry {
@@ -67,25 +73,38 @@ impl RoomRegistry {
     /// Creates a new, empty room registry.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_creation_policy(RoomCreationPolicy::default())
+    }
+
+    /// Creates a new, empty room registry enforcing `policy` on registration.
+    #[must_use]
+    pub fn with_creation_policy(policy: RoomCreationPolicy) -> Self {
         Self {
             rooms: RwLock::new(HashMap::new()),
+            creation_policy: policy,
         }
     }
 
     /// Registers a room in the directory.
     ///
-    /// Returns an error if a room with the same name (case-insensitive)
-    /// already exists, or if the registry has reached its capacity limit.
+    /// Returns an error if `admin_peer_id` may not create a room with this
+    /// name, if a room with the same name (case-insensitive) already exists,
+    /// or if the registry has reached its capacity limit.
     ///
     /// # Errors
     ///
-    /// Returns [`RegistryError::NameConflict`] or [`RegistryError::CapacityReached`].
+    /// Returns [`RegistryError::PermissionDenied`], [`RegistryError::NameConflict`]
+    /// or [`RegistryError::CapacityReached`].
     pub async fn register(
         &self,
         room_id: &str,
         name: &str,
         admin_peer_id: &str,
     ) -> Result<(), RegistryError> {
+        if !self.creation_policy.permits(name, admin_peer_id) {
+            return Err(RegistryError::PermissionDenied);
+        }
+
         let mut rooms = self.rooms.write().await;
 
         if rooms.len() >= MAX_REGISTRY_ROOMS && !rooms.contains_key(room_id) {
@@ -195,6 +214,7 @@ pub async fn route_join_request(
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -213,6 +233,30 @@ mod tests {
         assert_eq!(rooms[0].member_count, 1);
     }
 
+    #[tokio::test]
+    async fn register_denied_by_creation_policy() {
+        let policy = RoomCreationPolicy::new(
+            Some(["alice".to_string()]),
+            [("Ops".to_string(), vec!["carol".to_string()])],
+        );
+        let registry = RoomRegistry::with_creation_policy(policy);
+
+        assert!(matches!(
+            registry.register("room-1", "General", "bob").await,
+            Err(RegistryError::PermissionDenied)
+        ));
+        assert!(matches!(
+            registry.register("room-2", "ops", "alice").await,
+            Err(RegistryError::PermissionDenied)
+        ));
+        registry
+            .register("room-1", "General", "alice")
+            .await
+            .unwrap();
+        registry.register("room-2", "Ops", "carol").await.unwrap();
+        assert_eq!(registry.list().await.len(), 2);
+    }
+
     #[tokio::test]
     async fn unregister_existing_room() {
         let registry = RoomRegistry::new();
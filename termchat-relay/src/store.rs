@@ -3,8 +3,15 @@
 //! The [`MessageStore`] holds per-peer FIFO queues of messages that could not
 //! be delivered because the recipient was not connected at the time. When a
 //! peer registers, its queue is drained and all stored messages are delivered.
+//!
+//! Queues can be persisted to disk with [`MessageStore::persist_to`] when the
+//! relay shuts down and restored with [`MessageStore::load_from`] at startup,
+//! so messages for offline peers survive a relay restart.
 
 use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tokio::time::Instant;
 
@@ -23,6 +30,17 @@ pub struct StoredMessage {
     pub queued_at: Instant,
 }
 
+/// On-disk representation of a queued message.
+///
+/// `queued_at` is not persisted: an [`Instant`] has no meaning across process
+/// restarts, so restored messages are stamped with the load time.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedMessage {
+    to: String,
+    from: String,
+    payload: Vec<u8>,
+}
+
 /// In-memory per-peer message queue with FIFO eviction.
 ///
 /// Thread-safe via [`RwLock`]. Each peer has an independent queue capped at
@@ -99,9 +117,80 @@ impl MessageStore {
         // Safe: MAX_QUEUE_SIZE is 1000, well within u32 range.
         queues.get(peer_id).map_or(0, |q| q.len() as u32)
     }
+
+    /// Returns the total number of queued messages across all peers.
+    pub async fn total_len(&self) -> usize {
+        let queues = self.queues.read().await;
+        queues.values().map(VecDeque::len).sum()
+    }
+
+    /// Writes all queued messages to `path` (postcard-encoded), returning the
+    /// number of messages written.
+    ///
+    /// The file is written to a temporary sibling first and then renamed, so
+    /// an interrupted write never leaves a truncated queue file behind. The
+    /// in-memory queues are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if encoding fails or the file cannot be written.
+    pub async fn persist_to(&self, path: &Path) -> std::io::Result<usize> {
+        let records: Vec<PersistedMessage> = {
+            let queues = self.queues.read().await;
+            queues
+                .iter()
+                .flat_map(|(to, queue)| {
+                    queue.iter().map(move |msg| PersistedMessage {
+                        to: to.clone(),
+                        from: msg.from.clone(),
+                        payload: msg.payload.clone(),
+                    })
+                })
+                .collect()
+        };
+
+        let bytes = postcard::to_allocvec(&records)
+            .map_err(|e| std::io::Error::other(format!("queue encode error: {e}")))?;
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, bytes).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(records.len())
+    }
+
+    /// Restores queued messages previously written by
+    /// [`persist_to`](Self::persist_to), returning the number of messages
+    /// loaded.
+    ///
+    /// Restored messages are appended to any existing queues in their
+    /// original FIFO order, subject to the usual per-peer cap. A missing file
+    /// is not an error and loads nothing. Once loaded, the file is removed,
+    /// so a relay that restarts without persisting again does not deliver
+    /// the same messages twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file exists but cannot be read, decoded
+    /// or removed. A file that fails to decode is left in place.
+    pub async fn load_from(&self, path: &Path) -> std::io::Result<usize> {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(b) => b,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let records: Vec<PersistedMessage> = postcard::from_bytes(&bytes)
+            .map_err(|e| std::io::Error::other(format!("queue decode error: {e}")))?;
+
+        let count = records.len();
+        for record in records {
+            self.enqueue(&record.to, &record.from, record.payload).await;
+        }
+        tokio::fs::remove_file(path).await?;
+        Ok(count)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::cast_possible_truncation, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -184,6 +273,53 @@ mod tests {
         assert_eq!(store.queue_len("peer").await, 0);
     }
 
+    #[tokio::test]
+    async fn total_len_counts_all_peers() {
+        let store = MessageStore::new();
+        store.enqueue("alice", "sender", vec![1]).await;
+        store.enqueue("bob", "sender", vec![2]).await;
+        store.enqueue("bob", "sender", vec![3]).await;
+        assert_eq!(store.total_len().await, 3);
+    }
+
+    #[tokio::test]
+    async fn persist_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("termchat-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("queue.bin");
+
+        let store = MessageStore::new();
+        store.enqueue("bob", "alice", vec![1, 2, 3]).await;
+        store.enqueue("bob", "carol", vec![4]).await;
+        store.enqueue("dave", "alice", vec![5]).await;
+        assert_eq!(store.persist_to(&path).await.unwrap(), 3);
+
+        let restored = MessageStore::new();
+        assert_eq!(restored.load_from(&path).await.unwrap(), 3);
+        assert!(!path.exists(), "queue file should be removed once loaded");
+        // Loading again finds nothing to deliver twice.
+        assert_eq!(MessageStore::new().load_from(&path).await.unwrap(), 0);
+
+        let bob = restored.drain("bob").await;
+        assert_eq!(bob.len(), 2);
+        assert_eq!(bob[0].from, "alice");
+        assert_eq!(bob[0].payload, vec![1, 2, 3]);
+        assert_eq!(bob[1].from, "carol");
+        assert_eq!(restored.queue_len("dave").await, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn load_missing_file_is_empty() {
+        let store = MessageStore::new();
+        let loaded = store
+            .load_from(Path::new("/nonexistent/termchat-queue.bin"))
+            .await
+            .unwrap();
+        assert_eq!(loaded, 0);
+    }
+
     #[tokio::test]
     async fn drain_clears_queue() {
         let store = MessageStore::new();
@@ -482,6 +482,7 @@ pub async fn heartbeat_loop(
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::unused_async)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
@@ -9,7 +9,7 @@ use tokio::sync::mpsc;
 
 use super::AgentError;
 use super::bridge::AgentConnection;
-use super::protocol::BridgeMessage;
+use super::protocol::{BridgeMessage, BridgeTaskInfo};
 
 /// Maximum message size in bytes (64 KB).
 const MAX_MESSAGE_SIZE: usize = 64 * 1024;
@@ -61,6 +61,11 @@ pub enum RoomEvent {
         /// Whether the affected member is an AI agent.
         is_agent: bool,
     },
+    /// A task in the room has come due.
+    TaskReminder {
+        /// The due task.
+        task: BridgeTaskInfo,
+    },
 }
 
 /// The reason an agent participant event loop terminated.
@@ -313,6 +318,43 @@ impl AgentParticipant {
                 })
                 .await
             }
+            AgentMessage::SetTaskDescription { task_id, .. } => {
+                self.handle_task_message_with_capability_check(|| {
+                    format!("SetTaskDescription: {task_id}")
+                })
+                .await
+            }
+            AgentMessage::SetTaskDueDate { task_id, due_date } => {
+                self.handle_task_message_with_capability_check(|| {
+                    format!("SetTaskDueDate: {task_id} -> {due_date:?}")
+                })
+                .await
+            }
+            AgentMessage::SetTaskPriority { task_id, priority } => {
+                self.handle_task_message_with_capability_check(|| {
+                    format!("SetTaskPriority: {task_id} -> {priority}")
+                })
+                .await
+            }
+            AgentMessage::SetTaskRecurrence {
+                task_id,
+                recurrence,
+            } => {
+                self.handle_task_message_with_capability_check(|| {
+                    format!("SetTaskRecurrence: {task_id} -> {recurrence:?}")
+                })
+                .await
+            }
+            AgentMessage::LabelTask {
+                task_id,
+                add,
+                remove,
+            } => {
+                self.handle_task_message_with_capability_check(|| {
+                    format!("LabelTask: {task_id} +{add:?} -{remove:?}")
+                })
+                .await
+            }
             AgentMessage::ListTasks => {
                 if !self.has_capability(CAPABILITY_TASK_MANAGEMENT) {
                     let err_msg = BridgeMessage::Error {
@@ -427,6 +469,10 @@ impl AgentParticipant {
                 display_name,
                 is_agent,
             },
+            RoomEvent::TaskReminder { task } => BridgeMessage::TaskReminder {
+                room_id: self.room_id.clone(),
+                task,
+            },
         };
         self.conn.write_message(&bridge_msg).await
     }
@@ -501,12 +547,17 @@ impl AgentParticipant {
         // Full integration with TaskManager will replace this.
         let response = BridgeMessage::TaskUpdate {
             room_id: self.room_id.clone(),
-            task: super::protocol::BridgeTaskInfo {
+            task: BridgeTaskInfo {
                 task_id: String::new(),
                 title: String::new(),
                 status: "pending".to_string(),
                 assignee: None,
                 created_by: self.peer_id.clone(),
+                description: String::new(),
+                due_date: None,
+                priority: "normal".to_string(),
+                labels: Vec::new(),
+                recurrence: None,
             },
         };
         let _ = self.conn.write_message(&response).await;
@@ -520,6 +571,7 @@ impl AgentParticipant {
 }
 
 #[cfg(test)]
+#[allow(clippy::doc_markdown, clippy::expect_used, clippy::unwrap_used)]
 mod tests {
     use super::*;
     use crate::agent::bridge::AgentBridge;
@@ -5,6 +5,7 @@
 //! agent ID validation utilities.
 
 use serde::{Deserialize, Serialize};
+use termchat_proto::task::Task;
 
 use super::AgentError;
 
@@ -56,6 +57,46 @@ pub enum AgentMessage {
         /// Peer ID or display name of the assignee.
         assignee: String,
     },
+    /// Agent wants to replace a task's description.
+    SetTaskDescription {
+        /// ID of the task to update.
+        task_id: String,
+        /// New description; may span multiple lines, empty clears it.
+        description: String,
+    },
+    /// Agent wants to set or clear a task's due date.
+    SetTaskDueDate {
+        /// ID of the task to update.
+        task_id: String,
+        /// Due date as `YYYY-MM-DD`, or `null` to clear it.
+        due_date: Option<String>,
+    },
+    /// Agent wants to change a task's priority.
+    SetTaskPriority {
+        /// ID of the task to update.
+        task_id: String,
+        /// New priority (`"low"`, `"normal"`, `"high"` or `"urgent"`).
+        priority: String,
+    },
+    /// Agent wants to set or clear how a task repeats.
+    SetTaskRecurrence {
+        /// ID of the task to update.
+        task_id: String,
+        /// `"daily"`, `"weekly"` or `"cron <expression>"`, or `null` to
+        /// stop repeating.
+        recurrence: Option<String>,
+    },
+    /// Agent wants to add and/or remove task labels.
+    LabelTask {
+        /// ID of the task to update.
+        task_id: String,
+        /// Labels to add.
+        #[serde(default)]
+        add: Vec<String>,
+        /// Labels to remove.
+        #[serde(default)]
+        remove: Vec<String>,
+    },
     /// Agent requests the current task list for the room.
     ListTasks,
     /// Agent is gracefully disconnecting.
@@ -128,6 +169,13 @@ pub enum BridgeMessage {
         /// ID of the deleted task.
         task_id: String,
     },
+    /// A task in the room has come due and is not done yet.
+    TaskReminder {
+        /// Room the task belongs to.
+        room_id: String,
+        /// The due task.
+        task: BridgeTaskInfo,
+    },
     /// An error from the bridge.
     Error {
         /// Machine-readable error code (e.g. `"invalid_agent_id"`, `"room_not_found"`).
@@ -182,6 +230,50 @@ pub struct BridgeTaskInfo {
     pub assignee: Option<String>,
     /// Peer ID of the task creator.
     pub created_by: String,
+    /// Multi-line description (empty if none).
+    #[serde(default)]
+    pub description: String,
+    /// Due date as `YYYY-MM-DD`, if set.
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// Priority (`"low"`, `"normal"`, `"high"` or `"urgent"`).
+    #[serde(default = "default_priority")]
+    pub priority: String,
+    /// Labels on the task, sorted.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// How the task repeats (`"daily"`, `"weekly"` or `"cron <expression>"`),
+    /// if it does.
+    #[serde(default)]
+    pub recurrence: Option<String>,
+}
+
+impl BridgeTaskInfo {
+    /// Describes a task for an agent.
+    #[must_use]
+    pub fn from_task(task: &Task) -> Self {
+        Self {
+            task_id: task.id.to_string(),
+            title: task.title.value.clone(),
+            status: task.status.value.to_string(),
+            assignee: task.assignee.value.clone(),
+            created_by: task.created_by.clone(),
+            description: task.description.value.clone(),
+            due_date: task
+                .due
+                .value
+                .and_then(|ms| i64::try_from(ms).ok())
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|due| due.date_naive().to_string()),
+            priority: task.priority.value.to_string(),
+            labels: task.label_names().map(str::to_string).collect(),
+            recurrence: task.recurrence.value.as_ref().map(ToString::to_string),
+        }
+    }
+}
+
+fn default_priority() -> String {
+    "normal".to_string()
 }
 
 // ---------------------------------------------------------------------------
@@ -274,6 +366,7 @@ pub fn decode_line<T: for<'de> Deserialize<'de>>(line: &str) -> Result<T, AgentE
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
 
@@ -751,6 +844,11 @@ mod tests {
                 status: "open".to_string(),
                 assignee: Some("alice".to_string()),
                 created_by: "peer-bob".to_string(),
+                description: String::new(),
+                due_date: None,
+                priority: "normal".to_string(),
+                labels: Vec::new(),
+                recurrence: None,
             }],
         };
         let line = encode_line(&msg).expect("encode");
@@ -779,6 +877,11 @@ mod tests {
                 status: "in_progress".to_string(),
                 assignee: None,
                 created_by: "agent:claude".to_string(),
+                description: String::new(),
+                due_date: None,
+                priority: "normal".to_string(),
+                labels: Vec::new(),
+                recurrence: None,
             },
         };
         let line = encode_line(&msg).expect("encode");
@@ -808,6 +911,81 @@ mod tests {
         assert_eq!(json["task_id"], "t-1");
     }
 
+    #[test]
+    fn agent_label_task_defaults_to_no_changes() {
+        let decoded: AgentMessage =
+            decode_line(r#"{"type":"label_task","task_id":"t-1","add":["bug"]}"#).expect("decode");
+        assert_eq!(
+            decoded,
+            AgentMessage::LabelTask {
+                task_id: "t-1".to_string(),
+                add: vec!["bug".to_string()],
+                remove: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn agent_set_task_due_date_null_clears() {
+        let msg = AgentMessage::SetTaskDueDate {
+            task_id: "t-3".to_string(),
+            due_date: None,
+        };
+        let json = serde_json::to_value(&msg).expect("to_value");
+        assert_eq!(json["type"], "set_task_due_date");
+        assert!(json["due_date"].is_null());
+        let line = encode_line(&msg).expect("encode");
+        assert_eq!(decode_line::<AgentMessage>(&line).expect("decode"), msg);
+    }
+
+    #[test]
+    fn bridge_task_info_without_rich_fields_uses_defaults() {
+        let json =
+            r#"{"task_id":"t-1","title":"Old","status":"open","assignee":null,"created_by":"p"}"#;
+        let info: BridgeTaskInfo = serde_json::from_str(json).expect("decode");
+        assert!(info.description.is_empty());
+        assert_eq!(info.due_date, None);
+        assert_eq!(info.priority, "normal");
+        assert!(info.labels.is_empty());
+    }
+
+    #[test]
+    fn bridge_task_reminder_json_shape() {
+        use termchat_proto::task::{LwwRegister, Recurrence, TaskId, TaskPriority, TaskStatus};
+
+        let task = Task {
+            id: TaskId::new(),
+            room_id: "room-1".to_string(),
+            title: LwwRegister::new("Standup".to_string(), 1, "peer-a".to_string()),
+            status: LwwRegister::new(TaskStatus::Open, 1, "peer-a".to_string()),
+            assignee: LwwRegister::new(None, 1, "peer-a".to_string()),
+            description: LwwRegister::new(String::new(), 1, "peer-a".to_string()),
+            due: LwwRegister::new(Some(1_772_442_000_000), 1, "peer-a".to_string()),
+            recurrence: LwwRegister::new(
+                Some(Recurrence::Cron("0 9 * * 1-5".to_string())),
+                1,
+                "peer-a".to_string(),
+            ),
+            priority: LwwRegister::new(TaskPriority::Normal, 1, "peer-a".to_string()),
+            labels: std::collections::BTreeMap::new(),
+            parent: LwwRegister::new(None, 1, "peer-a".to_string()),
+            blocked_by: std::collections::BTreeMap::new(),
+            comments: std::collections::BTreeMap::new(),
+            created_at: 1,
+            created_by: "peer-a".to_string(),
+        };
+        let msg = BridgeMessage::TaskReminder {
+            room_id: "room-1".to_string(),
+            task: BridgeTaskInfo::from_task(&task),
+        };
+        let json = serde_json::to_value(&msg).expect("to_value");
+        assert_eq!(json["type"], "task_reminder");
+        assert_eq!(json["task"]["due_date"], "2026-03-02");
+        assert_eq!(json["task"]["recurrence"], "cron 0 9 * * 1-5");
+        let line = encode_line(&msg).expect("encode");
+        assert_eq!(decode_line::<BridgeMessage>(&line).expect("decode"), msg);
+    }
+
     #[test]
     fn bridge_task_info_round_trip() {
         let info = BridgeTaskInfo {
@@ -816,6 +994,11 @@ mod tests {
             status: "completed".to_string(),
             assignee: Some("peer-charlie".to_string()),
             created_by: "peer-alice".to_string(),
+            description: String::new(),
+            due_date: None,
+            priority: "normal".to_string(),
+            labels: Vec::new(),
+            recurrence: None,
         };
         let json = serde_json::to_string(&info).expect("encode");
         let decoded: BridgeTaskInfo = serde_json::from_str(&json).expect("decode");
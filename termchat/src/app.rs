@@ -1,13 +1,25 @@
 //! Application state and event handling.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, mpsc};
 use std::time::Instant;
+use std::{fs, io, thread};
 
+use chrono::{DateTime, NaiveDate, NaiveTime};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use parking_lot::Mutex;
 
 use termchat_proto::presence::PresenceStatus;
+use termchat_proto::task::{
+    CommentId, MAX_TASK_COMMENT_LENGTH, MAX_TASK_DESCRIPTION_LENGTH, MAX_TASK_LABEL_LENGTH,
+    Recurrence, Task, TaskId, TaskPriority, TaskStatus, TaskSyncMessage,
+};
 
 use crate::net::NetCommand;
+use crate::tasks::board::{self, BoardFilter};
+use crate::tasks::interchange::TaskListFormat;
+use crate::tasks::recurrence;
+use crate::tasks::{ActivityEntry, HybridClock, TaskError, TaskManager};
 
 /// Which panel is currently focused.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,6 +56,37 @@ impl TaskDisplayStatus {
         }
     }
 
+    /// Status name as used in sync messages, e.g. `"in_progress"`.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+        }
+    }
+
+    /// The task status this display status shows.
+    #[must_use]
+    pub const fn status(self) -> TaskStatus {
+        match self {
+            Self::Open => TaskStatus::Open,
+            Self::InProgress => TaskStatus::InProgress,
+            Self::Completed => TaskStatus::Completed,
+        }
+    }
+
+    /// The display status for a task status; `None` for deleted tasks.
+    #[must_use]
+    pub const fn from_status(status: TaskStatus) -> Option<Self> {
+        match status {
+            TaskStatus::Open => Some(Self::Open),
+            TaskStatus::InProgress => Some(Self::InProgress),
+            TaskStatus::Completed => Some(Self::Completed),
+            TaskStatus::Deleted => None,
+        }
+    }
+
     /// Cycle to the next status: `Open` -> `InProgress` -> `Completed` -> `Open`.
     #[must_use]
     pub const fn next(self) -> Self {
@@ -58,8 +101,8 @@ impl TaskDisplayStatus {
 /// A task for display in the task panel.
 #[derive(Debug, Clone)]
 pub struct DisplayTask {
-    /// Unique task ID (for CRDT integration).
-    pub id: String,
+    /// ID of the task in the task manager.
+    pub id: TaskId,
     /// Human-readable title.
     pub title: String,
     /// Current display status.
@@ -68,6 +111,206 @@ pub struct DisplayTask {
     pub assignee: Option<String>,
     /// Sequential task number (displayed as #N).
     pub number: usize,
+    /// Multi-line description (empty if none).
+    pub description: String,
+    /// Optional due date.
+    pub due: Option<NaiveDate>,
+    /// How the task repeats once completed, if it does.
+    pub recurrence: Option<Recurrence>,
+    /// Task priority.
+    pub priority: TaskPriority,
+    /// Labels on the task, sorted.
+    pub labels: BTreeSet<String>,
+    /// Number of the parent task, if this is a subtask.
+    pub parent: Option<usize>,
+    /// Numbers of the tasks blocking this one.
+    pub blocked_by: BTreeSet<usize>,
+}
+
+/// State of the full-screen task board.
+#[derive(Debug, Clone)]
+pub struct TaskBoard {
+    /// Column of the selected card.
+    pub column: TaskStatus,
+    /// Index of the selected card within its column.
+    pub row: usize,
+    /// Which cards are shown.
+    pub filter: BoardFilter,
+    /// Text being entered at the top of the board, if any.
+    pub prompt: Option<BoardPrompt>,
+}
+
+/// What the board's input line is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardPrompt {
+    /// Title of a new card in the selected column.
+    NewCard(String),
+    /// A new filter, as `@name #label`.
+    Filter(String),
+}
+
+/// Whether a task is waiting on its blockers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockerState {
+    /// The task has no blockers.
+    None,
+    /// These blockers (task numbers) are not completed yet.
+    Blocked(Vec<usize>),
+    /// Every blocker is completed.
+    Unblocked,
+}
+
+impl DisplayTask {
+    /// Priority marker, due date, recurrence and labels shown after the
+    /// title, e.g. `" !! due 2026-03-01 repeats weekly #bug"`. Empty for a
+    /// normal-priority task without any of them.
+    #[must_use]
+    pub fn details(&self) -> String {
+        let marker = match self.priority {
+            TaskPriority::Low => Some("\u{2193}".to_string()),
+            TaskPriority::Normal => None,
+            TaskPriority::High => Some("!".to_string()),
+            TaskPriority::Urgent => Some("!!".to_string()),
+        };
+        let due = self.due.map(|due| format!("due {due}"));
+        let repeats = self
+            .recurrence
+            .as_ref()
+            .map(|rule| format!("repeats {rule}"));
+        let labels = self.labels.iter().map(|label| format!("#{label}"));
+        let parts: Vec<String> = marker
+            .into_iter()
+            .chain(due)
+            .chain(repeats)
+            .chain(labels)
+            .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", parts.join(" "))
+        }
+    }
+}
+
+/// Task numbers, for turning the task manager's tasks into
+/// [`DisplayTask`]s.
+struct TaskView<'a> {
+    /// Number of each live task.
+    live: HashMap<&'a TaskId, usize>,
+}
+
+impl TaskView<'_> {
+    /// `task` as shown in the task panel; `None` if it is not live.
+    fn task(&self, task: &Task) -> Option<DisplayTask> {
+        let number = *self.live.get(&task.id)?;
+        Some(DisplayTask {
+            id: task.id.clone(),
+            title: task.title.value.clone(),
+            status: TaskDisplayStatus::from_status(task.status.value)?,
+            assignee: task.assignee.value.clone(),
+            number,
+            description: task.description.value.clone(),
+            due: task.due.value.and_then(due_date),
+            recurrence: task.recurrence.value.clone(),
+            priority: task.priority.value,
+            labels: task.label_names().map(str::to_string).collect(),
+            parent: task
+                .parent
+                .value
+                .as_ref()
+                .and_then(|parent| self.live.get(parent).copied()),
+            blocked_by: task
+                .blocker_ids()
+                .filter_map(|blocker| self.live.get(blocker).copied())
+                .collect(),
+        })
+    }
+}
+
+/// The current state of one task, as a message for the room.
+fn task_state(tasks: &TaskManager, room_id: &str, task_id: &TaskId) -> TaskSyncMessage {
+    TaskSyncMessage::FullState {
+        room_id: room_id.to_string(),
+        tasks: tasks
+            .get_task(room_id, task_id)
+            .cloned()
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Local `HH:MM` time of a hybrid clock timestamp.
+#[must_use]
+pub fn format_hlc_time(timestamp: u64) -> String {
+    i64::try_from(HybridClock::physical_ms(timestamp))
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Date of a due time in milliseconds since epoch (UTC).
+fn due_date(due_ms: u64) -> Option<NaiveDate> {
+    i64::try_from(due_ms)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|due| due.date_naive())
+}
+
+/// Due time of a date: midnight UTC, in milliseconds since epoch.
+fn due_ms(date: NaiveDate) -> u64 {
+    u64::try_from(date.and_time(NaiveTime::MIN).and_utc().timestamp_millis()).unwrap_or_default()
+}
+
+/// The task list format a `/task export` or `/task import` file name
+/// asks for, by extension.
+fn task_list_format(path: &str) -> Option<TaskListFormat> {
+    if path.is_empty() {
+        return None;
+    }
+    TaskListFormat::from_path(std::path::Path::new(path))
+}
+
+/// A task list file read or written off the UI thread, handed back to
+/// [`App::tick_task_files`].
+#[derive(Debug)]
+enum TaskFile {
+    /// `/task export` finished writing `count` tasks to `path`.
+    Exported {
+        path: String,
+        count: usize,
+        result: io::Result<()>,
+    },
+    /// `/task import` finished reading `path`.
+    Read {
+        path: String,
+        format: TaskListFormat,
+        result: io::Result<String>,
+    },
+}
+
+/// Write an exported task list of `count` tasks to `path`.
+fn write_task_list(path: String, count: usize, text: &str) -> TaskFile {
+    let result = fs::write(&path, text);
+    TaskFile::Exported {
+        path,
+        count,
+        result,
+    }
+}
+
+/// Read a task list to import from `path`.
+fn read_task_list(path: String, format: TaskListFormat) -> TaskFile {
+    let result = fs::read_to_string(&path);
+    TaskFile::Read {
+        path,
+        format,
+        result,
+    }
 }
 
 /// A message for display in the chat panel.
@@ -128,6 +371,17 @@ pub struct ConversationItem {
     pub presence: Option<PresenceStatus>,
 }
 
+/// A peer discovered on the local network, listed in the sidebar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanPeerItem {
+    /// The peer's advertised identity.
+    pub peer_id: String,
+    /// The peer's direct address (e.g., "192.168.1.20:7000").
+    pub addr: String,
+    /// The peer's identity key fingerprint, if advertised.
+    pub fingerprint: Option<String>,
+}
+
 /// Default duration after which typing indicator expires (3 seconds).
 const DEFAULT_TYPING_TIMEOUT_SECS: u64 = 3;
 
@@ -156,6 +410,11 @@ pub struct App {
     pub tasks: Vec<DisplayTask>,
     /// Currently selected task index.
     pub selected_task: usize,
+    /// Number of the task whose detail view the task panel shows instead
+    /// of the list.
+    pub task_detail: Option<usize>,
+    /// The task board, when it replaces the panels.
+    pub task_board: Option<TaskBoard>,
     /// Presence status per peer (`peer_id` -> status).
     pub presence_map: HashMap<String, PresenceStatus>,
     /// Typing peers per room (`room_id` -> set of typing peer names).
@@ -168,10 +427,29 @@ pub struct App {
     pub is_connected: bool,
     /// Transport type description (e.g., "Relay", "P2P", "").
     pub connection_info: String,
+    /// Last relay round-trip time in milliseconds, measured by keepalives.
+    pub relay_latency_ms: Option<u64>,
+    /// Peers currently visible on the local network (`None` when the
+    /// sidebar does not list LAN peers).
+    lan_peers: Option<Vec<LanPeerItem>>,
     /// Typing indicator timeout in seconds (configurable).
     typing_timeout_secs: u64,
     /// Maximum task title length in characters (configurable).
     max_task_title_len: usize,
+    /// Shared task state the task list is drawn from; the networking
+    /// layer merges remote changes into it.
+    task_manager: Arc<Mutex<TaskManager>>,
+    /// Task room the task panel shows and edits.
+    task_room: String,
+    /// Number shown for each task (`#N`), in the order tasks were first
+    /// seen.
+    task_numbers: HashMap<TaskId, usize>,
+    /// Number of task list files being read or written in the background.
+    task_files_pending: usize,
+    /// Where background task list file IO reports back.
+    task_files_tx: mpsc::Sender<TaskFile>,
+    /// Finished task list file IO, drained by `tick_task_files`.
+    task_files_rx: mpsc::Receiver<TaskFile>,
     /// Counter for generating unique message IDs.
     next_message_id: u64,
 }
@@ -183,6 +461,7 @@ impl App {
     /// Use [`add_conversation`] to populate from network events or CLI args.
     #[must_use]
     pub fn new() -> Self {
+        let (task_files_tx, task_files_rx) = mpsc::channel();
         Self {
             input: String::new(),
             cursor_position: 0,
@@ -194,14 +473,24 @@ impl App {
             should_quit: false,
             tasks: Vec::new(),
             selected_task: 0,
+            task_detail: None,
+            task_board: None,
             presence_map: HashMap::new(),
             typing_peers: HashMap::new(),
             typing_timer: None,
             local_typing: false,
             is_connected: false,
             connection_info: String::new(),
+            relay_latency_ms: None,
+            lan_peers: None,
             typing_timeout_secs: DEFAULT_TYPING_TIMEOUT_SECS,
             max_task_title_len: DEFAULT_MAX_TASK_TITLE_LEN,
+            task_manager: Arc::new(Mutex::new(TaskManager::new("local".to_string()))),
+            task_room: "local".to_string(),
+            task_numbers: HashMap::new(),
+            task_files_pending: 0,
+            task_files_tx,
+            task_files_rx,
             next_message_id: 0,
         }
     }
@@ -284,6 +573,24 @@ impl App {
         self
     }
 
+    /// Set whether LAN peers are listed in the sidebar.
+    #[must_use]
+    pub fn with_lan_peers(mut self, show: bool) -> Self {
+        self.lan_peers = show.then(Vec::new);
+        self
+    }
+
+    /// Show and edit the tasks of `room` kept by `manager`, instead of an
+    /// in-memory task list of this app's own.
+    #[must_use]
+    pub fn with_tasks(mut self, manager: Arc<Mutex<TaskManager>>, room: String) -> Self {
+        self.task_manager = manager;
+        self.task_room = room;
+        self.task_numbers.clear();
+        self.refresh_tasks();
+        self
+    }
+
     /// Generate a unique message ID.
     fn next_msg_id(&mut self) -> String {
         let id = self.next_message_id;
@@ -292,9 +599,17 @@ impl App {
     }
 
     /// Update connection status.
+    ///
+    /// Clears the measured relay latency; the new connection reports its own.
     pub fn set_connection_status(&mut self, connected: bool, info: &str) {
         self.is_connected = connected;
         self.connection_info = info.to_string();
+        self.relay_latency_ms = None;
+    }
+
+    /// Record the relay round-trip time reported by the keepalive.
+    pub const fn set_relay_latency(&mut self, rtt_ms: u64) {
+        self.relay_latency_ms = Some(rtt_ms);
     }
 
     /// Check if the app is able to send messages.
@@ -379,8 +694,19 @@ impl App {
     /// to be dispatched to the networking layer (e.g., sending a message or a
     /// slash command like `/create-room`).
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Option<NetCommand> {
+        // The board takes every key but Ctrl+C while it is open.
+        if self.task_board.is_some()
+            && (key.code, key.modifiers) != (KeyCode::Char('c'), KeyModifiers::CONTROL)
+        {
+            return self.handle_board_key(key);
+        }
+
         // Global shortcuts
         match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) if self.task_detail.is_some() => {
+                self.task_detail = None;
+                return None;
+            }
             (KeyCode::Char('c'), KeyModifiers::CONTROL) | (KeyCode::Esc, _) => {
                 self.should_quit = true;
                 return None;
@@ -407,10 +733,7 @@ impl App {
                 self.handle_chat_key(key);
                 None
             }
-            PanelFocus::Tasks => {
-                self.handle_tasks_key(key);
-                None
-            }
+            PanelFocus::Tasks => self.handle_tasks_key(key),
         }
     }
 
@@ -477,146 +800,1084 @@ impl App {
     }
 
     /// Handle key event when task panel is focused.
-    fn handle_tasks_key(&mut self, key: KeyEvent) {
+    ///
+    /// Returns the change to broadcast when Enter moves the selected task
+    /// to its next status.
+    fn handle_tasks_key(&mut self, key: KeyEvent) -> Option<NetCommand> {
+        if key.code == KeyCode::Char('b') {
+            self.open_board(BoardFilter::default());
+            return None;
+        }
         if self.tasks.is_empty() {
-            return;
+            return None;
         }
+        let mut sync = None;
         match key.code {
-            KeyCode::Down | KeyCode::Char('j') => {
-                if self.selected_task + 1 < self.tasks.len() {
-                    self.selected_task += 1;
-                }
+            KeyCode::Down | KeyCode::Char('j') if self.selected_task + 1 < self.tasks.len() => {
+                self.selected_task += 1;
             }
             KeyCode::Up | KeyCode::Char('k') => {
                 self.selected_task = self.selected_task.saturating_sub(1);
             }
             KeyCode::Enter => {
-                let task = &mut self.tasks[self.selected_task];
-                task.status = task.status.next();
+                let status = self.tasks[self.selected_task].status.next().status();
+                sync = self.set_task_status(self.selected_task, status);
             }
+            KeyCode::Char('o') => self.task_detail = Some(self.tasks[self.selected_task].number),
             _ => {}
         }
+        // An open detail view follows the selection.
+        if self.task_detail.is_some() {
+            self.task_detail = self.tasks.get(self.selected_task).map(|t| t.number);
+        }
+        sync.map(NetCommand::SendTaskSync)
     }
 
     /// Handle the `/task` command with subcommands.
-    fn handle_task_command(&mut self, args: &str) {
+    ///
+    /// Returns the task change to broadcast, if the subcommand made one.
+    fn handle_task_command(&mut self, args: &str) -> Option<NetCommand> {
         let parts: Vec<&str> = args.splitn(2, ' ').collect();
         let subcommand = parts[0];
         let sub_args = parts.get(1).copied().unwrap_or("").trim();
 
-        match subcommand {
+        let sync = match subcommand {
             "add" => self.task_cmd_add(sub_args),
             "done" => self.task_cmd_done(sub_args),
             "assign" => self.task_cmd_assign(sub_args),
             "delete" => self.task_cmd_delete(sub_args),
-            "list" => self.task_cmd_list(),
+            "list" => {
+                self.task_cmd_list();
+                None
+            }
+            "desc" => self.task_cmd_desc(sub_args),
+            "due" => self.task_cmd_due(sub_args),
+            "repeat" => self.task_cmd_repeat(sub_args),
+            "priority" => self.task_cmd_priority(sub_args),
+            "label" => self.task_cmd_label(sub_args),
+            "parent" => self.task_cmd_parent(sub_args),
+            "block" => self.task_cmd_block(sub_args, true),
+            "unblock" => self.task_cmd_block(sub_args, false),
+            "show" => {
+                self.task_cmd_show(sub_args);
+                None
+            }
+            "comment" => self.task_cmd_comment(sub_args),
+            "reply" => self.task_cmd_reply(sub_args),
+            "board" => {
+                self.task_cmd_board(sub_args);
+                None
+            }
+            "export" => {
+                self.task_cmd_export(sub_args);
+                None
+            }
+            "import" => {
+                self.task_cmd_import(sub_args);
+                None
+            }
             _ => {
-                self.push_system_message("Usage: /task add|done|assign|delete|list".to_string());
+                self.push_system_message(
+                    "Usage: /task add|done|assign|delete|list|desc|due|repeat|priority|label|parent|block|unblock|show|comment|reply|board|export|import"
+                        .to_string(),
+                );
+                None
             }
-        }
+        };
+        sync.map(NetCommand::SendTaskSync)
     }
 
     /// `/task add <title>` — create a new task.
-    fn task_cmd_add(&mut self, title: &str) {
+    fn task_cmd_add(&mut self, title: &str) -> Option<TaskSyncMessage> {
+        self.add_task(title, TaskStatus::Open).map(|(_, sync)| sync)
+    }
+
+    /// Validate and create a task with `status`, returning its number and
+    /// the change to broadcast.
+    fn add_task(&mut self, title: &str, status: TaskStatus) -> Option<(usize, TaskSyncMessage)> {
         if title.is_empty() {
             self.push_system_message("Task title cannot be empty".to_string());
-            return;
+            return None;
         }
         if title.len() > self.max_task_title_len {
             self.push_system_message(format!(
                 "Task title too long (max {} characters)",
                 self.max_task_title_len
             ));
-            return;
+            return None;
         }
-        let number = self.tasks.iter().map(|t| t.number).max().unwrap_or(0) + 1;
-        self.tasks.push(DisplayTask {
-            id: format!("local-{number}"),
-            title: title.to_string(),
-            status: TaskDisplayStatus::Open,
-            assignee: None,
-            number,
-        });
+        let created = {
+            let mut tasks = self.task_manager.lock();
+            let room = self.task_room.as_str();
+            tasks.create_task(room, title).and_then(|(task, sync)| {
+                if status == TaskStatus::Open {
+                    return Ok((task.id, sync));
+                }
+                // One message carrying the task as created and moved.
+                tasks.update_status(room, &task.id, status)?;
+                let sync = task_state(&tasks, room, &task.id);
+                Ok((task.id, sync))
+            })
+        };
+        self.refresh_tasks();
+        let (id, sync) = match created {
+            Ok(created) => created,
+            Err(e) => {
+                self.push_system_message(format!("Could not create task: {e}"));
+                return None;
+            }
+        };
+        let number = self.task_numbers.get(&id).copied()?;
         self.push_system_message(format!("Task created: {title}"));
+        Some((number, sync))
+    }
+
+    /// Index in the task list of task `number`.
+    fn task_index(&self, number: usize) -> Option<usize> {
+        self.tasks.iter().position(|t| t.number == number)
+    }
+
+    /// Apply `change` to the task at `index` through the task manager and
+    /// redraw the task list from it.
+    fn try_change_task(
+        &mut self,
+        index: usize,
+        change: impl FnOnce(&mut TaskManager, &str, &TaskId) -> Result<TaskSyncMessage, TaskError>,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        let id = self.tasks[index].id.clone();
+        let result = change(&mut self.task_manager.lock(), &self.task_room, &id);
+        self.refresh_tasks();
+        result
+    }
+
+    /// Like [`try_change_task`](Self::try_change_task), reporting a
+    /// rejected change as a system message. Returns the change to
+    /// broadcast.
+    fn change_task(
+        &mut self,
+        index: usize,
+        change: impl FnOnce(&mut TaskManager, &str, &TaskId) -> Result<TaskSyncMessage, TaskError>,
+    ) -> Option<TaskSyncMessage> {
+        let number = self.tasks[index].number;
+        match self.try_change_task(index, change) {
+            Ok(sync) => Some(sync),
+            Err(e) => {
+                self.push_system_message(format!("Task #{number}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Move the task at `index` to `status`, returning the change to
+    /// broadcast. Completing it also announces the tasks it unblocked and
+    /// its next instance, if it repeats.
+    fn set_task_status(&mut self, index: usize, status: TaskStatus) -> Option<TaskSyncMessage> {
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.update_status(room, id, status)
+        })?;
+        if status == TaskStatus::Completed {
+            self.push_system_message(format!("Task #{number} marked as completed"));
+            self.task_completed(number, &sync);
+        }
+        Some(sync)
     }
 
     /// `/task done <number>` — mark a task as completed.
-    fn task_cmd_done(&mut self, args: &str) {
+    fn task_cmd_done(&mut self, args: &str) -> Option<TaskSyncMessage> {
         let Some(number) = args.parse::<usize>().ok() else {
             self.push_system_message("Usage: /task done <number>".to_string());
-            return;
+            return None;
         };
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.number == number) {
-            task.status = TaskDisplayStatus::Completed;
-            self.push_system_message(format!("Task #{number} marked as completed"));
-        } else {
+        let Some(index) = self.task_index(number) else {
             self.push_system_message(format!("Task #{number} not found"));
-        }
+            return None;
+        };
+        self.set_task_status(index, TaskStatus::Completed)
     }
 
     /// `/task assign <number> @<name>` — assign a task.
-    fn task_cmd_assign(&mut self, args: &str) {
+    fn task_cmd_assign(&mut self, args: &str) -> Option<TaskSyncMessage> {
         let parts: Vec<&str> = args.splitn(2, ' ').collect();
         if parts.len() < 2 {
             self.push_system_message("Usage: /task assign <number> @<name>".to_string());
-            return;
+            return None;
         }
         let Some(number) = parts[0].parse::<usize>().ok() else {
             self.push_system_message("Usage: /task assign <number> @<name>".to_string());
-            return;
+            return None;
         };
         let name = parts[1].trim_start_matches('@').trim();
         if name.is_empty() {
             self.push_system_message("Usage: /task assign <number> @<name>".to_string());
+            return None;
+        }
+        let Some(index) = self.task_index(number) else {
+            self.push_system_message(format!("Task #{number} not found"));
+            return None;
+        };
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.update_assignee(room, id, Some(name.to_string()))
+        })?;
+        self.push_system_message(format!("Task #{number} assigned to {name}"));
+        Some(sync)
+    }
+
+    /// `/task delete <number>` — remove a task.
+    fn task_cmd_delete(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let Some(number) = args.parse::<usize>().ok() else {
+            self.push_system_message("Usage: /task delete <number>".to_string());
+            return None;
+        };
+        let Some(index) = self.task_index(number) else {
+            self.push_system_message(format!("Task #{number} not found"));
+            return None;
+        };
+        let sync = self.change_task(index, TaskManager::delete_task)?;
+        self.push_system_message(format!("Task #{number} deleted"));
+        Some(sync)
+    }
+
+    /// `/task list` — list all tasks as system messages.
+    fn task_cmd_list(&mut self) {
+        if self.tasks.is_empty() {
+            self.push_system_message("No tasks".to_string());
+            return;
+        }
+        let lines: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                let assignee_str = task
+                    .assignee
+                    .as_ref()
+                    .map_or(String::new(), |a| format!(" (@{a})"));
+                let status = task.status.symbol();
+                format!(
+                    "#{} {status} {}{assignee_str}{}{}",
+                    task.number,
+                    task.title,
+                    task.details(),
+                    self.dependency_details(task)
+                )
+            })
+            .collect();
+        for line in lines {
+            self.push_system_message(line);
+        }
+    }
+
+    /// Split `<number> <rest>` arguments and look up the task, reporting
+    /// `usage` or a missing task as a system message.
+    fn task_with_args<'a>(&mut self, args: &'a str, usage: &str) -> Option<(usize, &'a str)> {
+        let (number, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let Ok(number) = number.parse::<usize>() else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        let Some(index) = self.task_index(number) else {
+            self.push_system_message(format!("Task #{number} not found"));
+            return None;
+        };
+        Some((index, rest.trim()))
+    }
+
+    /// `/task desc <number> <text>` — set the description; `\n` starts a
+    /// new line and an empty text clears it.
+    fn task_cmd_desc(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let (index, text) = self.task_with_args(args, "/task desc <number> <text>")?;
+        let description = text.replace("\\n", "\n");
+        if description.chars().count() > MAX_TASK_DESCRIPTION_LENGTH {
+            self.push_system_message(format!(
+                "Task description too long (max {MAX_TASK_DESCRIPTION_LENGTH} characters)"
+            ));
+            return None;
+        }
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.update_description(room, id, &description)
+        })?;
+        self.push_system_message(format!("Task #{number} description updated"));
+        Some(sync)
+    }
+
+    /// `/task due <number> <YYYY-MM-DD|none>` — set or clear the due date.
+    fn task_cmd_due(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task due <number> <YYYY-MM-DD|none>";
+        let (index, date) = self.task_with_args(args, usage)?;
+        let due = if date == "none" {
+            None
+        } else if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Some(date)
+        } else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.update_due(room, id, due.map(due_ms))
+        })?;
+        self.push_system_message(due.map_or_else(
+            || format!("Task #{number} due date cleared"),
+            |due| format!("Task #{number} due {due}"),
+        ));
+        Some(sync)
+    }
+
+    /// `/task repeat <number> <daily|weekly|cron <expr>|none>` — set or
+    /// clear how the task repeats once completed.
+    fn task_cmd_repeat(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task repeat <number> <daily|weekly|cron <expr>|none>";
+        let (index, rule) = self.task_with_args(args, usage)?;
+        let rule = if rule == "none" {
+            None
+        } else if let Ok(rule) = rule.parse::<Recurrence>() {
+            if let Err(e) = recurrence::validate(&rule) {
+                self.push_system_message(format!("Invalid recurrence: {e}"));
+                return None;
+            }
+            Some(rule)
+        } else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.set_recurrence(room, id, rule.clone())
+        })?;
+        self.push_system_message(rule.map_or_else(
+            || format!("Task #{number} no longer repeats"),
+            |rule| format!("Task #{number} repeats {rule}"),
+        ));
+        Some(sync)
+    }
+
+    /// `/task priority <number> <low|normal|high|urgent>` — set the priority.
+    fn task_cmd_priority(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task priority <number> <low|normal|high|urgent>";
+        let (index, priority) = self.task_with_args(args, usage)?;
+        let Ok(priority) = priority.parse::<TaskPriority>() else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.update_priority(room, id, priority)
+        })?;
+        self.push_system_message(format!("Task #{number} priority set to {priority}"));
+        Some(sync)
+    }
+
+    /// `/task label <number> [+|-]<label>...` — add (`+` or bare) or
+    /// remove (`-`) labels.
+    fn task_cmd_label(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task label <number> [+|-]<label>...";
+        let (index, changes) = self.task_with_args(args, usage)?;
+        let mut edits = Vec::new();
+        for change in changes.split_whitespace() {
+            let (add, label) = match change.split_at(1) {
+                ("-", label) => (false, label),
+                ("+", label) => (true, label),
+                _ => (true, change),
+            };
+            let label = label.to_lowercase();
+            if label.is_empty() || label.chars().count() > MAX_TASK_LABEL_LENGTH {
+                self.push_system_message(format!("Invalid label: {change:?}"));
+                return None;
+            }
+            edits.push((add, label));
+        }
+        if edits.is_empty() {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        }
+        let number = self.tasks[index].number;
+        let sync = self.change_task(index, |tasks, room, id| {
+            for (add, label) in &edits {
+                if *add {
+                    tasks.add_label(room, id, label)?;
+                } else {
+                    tasks.remove_label(room, id, label)?;
+                }
+            }
+            // One message carrying every label edit.
+            Ok(task_state(tasks, room, id))
+        })?;
+        let labels = self
+            .task_index(number)
+            .map(|index| {
+                self.tasks[index]
+                    .labels
+                    .iter()
+                    .map(|l| format!("#{l}"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        self.push_system_message(if labels.is_empty() {
+            format!("Task #{number} has no labels")
+        } else {
+            format!("Task #{number} labels: {}", labels.join(" "))
+        });
+        Some(sync)
+    }
+
+    /// `/task parent <number> <parent|none>` — make a task a subtask of
+    /// another, or a top-level task again.
+    fn task_cmd_parent(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task parent <number> <parent|none>";
+        let (index, parent) = self.task_with_args(args, usage)?;
+        let parent = if parent == "none" {
+            None
+        } else {
+            Some(self.task_number_arg(parent, usage)?)
+        };
+        let parent_id = parent
+            .and_then(|parent| self.task_index(parent))
+            .map(|index| self.tasks[index].id.clone());
+        let number = self.tasks[index].number;
+        match self.try_change_task(index, |tasks, room, id| {
+            tasks.set_parent(room, id, parent_id.as_ref())
+        }) {
+            Ok(sync) => {
+                self.push_system_message(parent.map_or_else(
+                    || format!("Task #{number} is no longer a subtask"),
+                    |parent| format!("Task #{number} is now a subtask of #{parent}"),
+                ));
+                Some(sync)
+            }
+            Err(TaskError::DependencyCycle) => {
+                self.push_system_message(format!(
+                    "Task #{} is a subtask of #{number}; that would form a cycle",
+                    parent.unwrap_or(number)
+                ));
+                None
+            }
+            Err(e) => {
+                self.push_system_message(format!("Task #{number}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// `/task block <number> <blocker>` and `/task unblock <number>
+    /// <blocker>` — add or remove a "blocked by" edge.
+    fn task_cmd_block(&mut self, args: &str, block: bool) -> Option<TaskSyncMessage> {
+        let usage = if block {
+            "/task block <number> <blocker>"
+        } else {
+            "/task unblock <number> <blocker>"
+        };
+        let (index, blocker) = self.task_with_args(args, usage)?;
+        let blocker = self.task_number_arg(blocker, usage)?;
+        let blocker_id = self.tasks[self.task_index(blocker)?].id.clone();
+        let number = self.tasks[index].number;
+        match self.try_change_task(index, |tasks, room, id| {
+            if block {
+                tasks.add_blocker(room, id, &blocker_id)
+            } else {
+                tasks.remove_blocker(room, id, &blocker_id)
+            }
+        }) {
+            Ok(sync) => {
+                self.push_system_message(if block {
+                    format!("Task #{number} is blocked by #{blocker}")
+                } else {
+                    format!("Task #{number} is no longer blocked by #{blocker}")
+                });
+                Some(sync)
+            }
+            Err(TaskError::DependencyCycle) => {
+                self.push_system_message(format!(
+                    "Task #{blocker} is waiting on #{number}; that would form a cycle"
+                ));
+                None
+            }
+            Err(e) => {
+                self.push_system_message(format!("Task #{number}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// `/task show <number>` — open a task's detail view (comments and
+    /// activity) in the task panel. Esc closes it.
+    fn task_cmd_show(&mut self, args: &str) {
+        let Some((index, _)) = self.task_with_args(args, "/task show <number>") else {
             return;
+        };
+        self.selected_task = index;
+        self.task_detail = Some(self.tasks[index].number);
+    }
+
+    /// `/task comment <number> <text>` — comment on a task.
+    fn task_cmd_comment(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let (index, text) = self.task_with_args(args, "/task comment <number> <text>")?;
+        self.add_comment(index, None, text)
+    }
+
+    /// `/task reply <number> <comment> <text>` — reply to a comment on a
+    /// task.
+    fn task_cmd_reply(&mut self, args: &str) -> Option<TaskSyncMessage> {
+        let usage = "/task reply <number> <comment> <text>";
+        let (index, rest) = self.task_with_args(args, usage)?;
+        let (comment, text) = rest.split_once(' ').unwrap_or((rest, ""));
+        let Ok(comment) = comment.parse::<usize>() else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        let task = &self.tasks[index];
+        let Some(reply_to) = self.comment_id(&task.id, comment) else {
+            let number = task.number;
+            self.push_system_message(format!("Comment [{comment}] not found on task #{number}"));
+            return None;
+        };
+        self.add_comment(index, Some(&reply_to), text.trim())
+    }
+
+    /// `/task board [@name] [#label]` — open the task board, showing only
+    /// matching cards.
+    fn task_cmd_board(&mut self, args: &str) {
+        let Some(filter) = BoardFilter::parse(args) else {
+            self.push_system_message("Usage: /task board [@name] [#label]".to_string());
+            return;
+        };
+        self.open_board(filter);
+    }
+
+    /// `/task export <file>` — write the task list to a `.json`, `.csv` or
+    /// `.md` file. The file is written in the background and reported by
+    /// [`tick_task_files`](Self::tick_task_files).
+    fn task_cmd_export(&mut self, args: &str) {
+        let Some(format) = task_list_format(args) else {
+            self.push_system_message(
+                "Usage: /task export <file.json|file.csv|file.md>".to_string(),
+            );
+            return;
+        };
+        let exported = self
+            .task_manager
+            .lock()
+            .export_tasks(&self.task_room, format);
+        let text = match exported {
+            Ok(text) => text,
+            Err(e) => {
+                self.push_system_message(format!("Export failed: {e}"));
+                return;
+            }
+        };
+        let path = args.to_string();
+        let count = self.tasks.len();
+        self.run_task_file(move || write_task_list(path, count, &text));
+    }
+
+    /// `/task import <file>` — add the tasks of a `.json`, `.csv` or `.md`
+    /// task list, keeping their status, assignee and other fields. Nothing
+    /// is added if any entry is invalid. The file is read in the
+    /// background and imported by [`tick_task_files`](Self::tick_task_files).
+    fn task_cmd_import(&mut self, args: &str) {
+        let Some(format) = task_list_format(args) else {
+            self.push_system_message(
+                "Usage: /task import <file.json|file.csv|file.md>".to_string(),
+            );
+            return;
+        };
+        let path = args.to_string();
+        self.run_task_file(move || read_task_list(path, format));
+    }
+
+    /// Run task list file IO on a background thread, keeping the UI
+    /// responsive while the file is read or written.
+    fn run_task_file(&mut self, io: impl FnOnce() -> TaskFile + Send + 'static) {
+        let tx = self.task_files_tx.clone();
+        self.task_files_pending += 1;
+        thread::spawn(move || {
+            // The app may have quit in the meantime.
+            let _ = tx.send(io());
+        });
+    }
+
+    /// Report finished task list exports and apply finished imports.
+    ///
+    /// Should be called on each tick of the event loop. Returns the task
+    /// changes to broadcast for imported tasks.
+    pub fn tick_task_files(&mut self) -> Vec<NetCommand> {
+        let mut commands = Vec::new();
+        while let Ok(file) = self.task_files_rx.try_recv() {
+            self.task_files_pending -= 1;
+            match file {
+                TaskFile::Exported {
+                    path,
+                    count,
+                    result,
+                } => self.push_system_message(match result {
+                    Ok(()) => format!("Exported {count} tasks to {path}"),
+                    Err(e) => format!("Export failed: {e}"),
+                }),
+                TaskFile::Read {
+                    path,
+                    format,
+                    result,
+                } => {
+                    if let Some(sync) = self.import_task_list(&path, format, result) {
+                        commands.push(NetCommand::SendTaskSync(sync));
+                    }
+                }
+            }
+        }
+        commands
+    }
+
+    /// Import the task list read from `path`, returning the change to
+    /// broadcast.
+    fn import_task_list(
+        &mut self,
+        path: &str,
+        format: TaskListFormat,
+        text: io::Result<String>,
+    ) -> Option<TaskSyncMessage> {
+        let imported = text.map_err(|e| e.to_string()).and_then(|text| {
+            self.task_manager
+                .lock()
+                .import_tasks(&self.task_room, &text, format)
+                .map_err(|e| e.to_string())
+        });
+        self.refresh_tasks();
+        match imported {
+            Ok(sync) => {
+                let count = match &sync {
+                    TaskSyncMessage::FullState { tasks, .. } => tasks.len(),
+                    _ => 0,
+                };
+                self.push_system_message(format!("Imported {count} tasks from {path}"));
+                Some(sync)
+            }
+            Err(e) => {
+                self.push_system_message(format!("Import failed: {e}"));
+                None
+            }
+        }
+    }
+
+    /// ID of comment `number` (counted from 1 in posting order) on a task.
+    fn comment_id(&self, task_id: &TaskId, number: usize) -> Option<CommentId> {
+        self.task_manager
+            .lock()
+            .get_task(&self.task_room, task_id)?
+            .comments
+            .keys()
+            .nth(number.checked_sub(1)?)
+            .cloned()
+    }
+
+    /// Validate and post a comment on the task at `index`, returning the
+    /// change to broadcast.
+    fn add_comment(
+        &mut self,
+        index: usize,
+        reply_to: Option<&CommentId>,
+        body: &str,
+    ) -> Option<TaskSyncMessage> {
+        if body.is_empty() {
+            self.push_system_message("Comment cannot be empty".to_string());
+            return None;
+        }
+        if body.chars().count() > MAX_TASK_COMMENT_LENGTH {
+            self.push_system_message(format!(
+                "Comment too long (max {MAX_TASK_COMMENT_LENGTH} characters)"
+            ));
+            return None;
         }
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.number == number) {
-            task.assignee = Some(name.to_string());
-            self.push_system_message(format!("Task #{number} assigned to {name}"));
+        let number = self.tasks[index].number;
+        let id = self.tasks[index].id.clone();
+        let sync = self.change_task(index, |tasks, room, id| {
+            tasks.add_comment(room, id, body, reply_to)
+        })?;
+        let comment = self
+            .task_manager
+            .lock()
+            .get_task(&self.task_room, &id)
+            .map_or(0, |task| task.comments.len());
+        self.push_system_message(format!("Comment [{comment}] added to task #{number}"));
+        Some(sync)
+    }
+
+    /// Parse the number of an existing task, reporting `usage` or a
+    /// missing task as a system message.
+    fn task_number_arg(&mut self, arg: &str, usage: &str) -> Option<usize> {
+        let Ok(number) = arg.parse::<usize>() else {
+            self.push_system_message(format!("Usage: {usage}"));
+            return None;
+        };
+        if self.task_index(number).is_none() {
+            self.push_system_message(format!("Task #{number} not found"));
+            return None;
+        }
+        Some(number)
+    }
+
+    /// Open the task board with `filter`, replacing the panels.
+    fn open_board(&mut self, filter: BoardFilter) {
+        self.task_detail = None;
+        self.task_board = Some(TaskBoard {
+            column: TaskStatus::Open,
+            row: 0,
+            filter,
+            prompt: None,
+        });
+    }
+
+    /// Cards shown in a board column, as [`TaskManager::board`] lays them
+    /// out. Empty when the board is closed.
+    #[must_use]
+    pub fn board_cards(&self, column: TaskStatus) -> Vec<&DisplayTask> {
+        let Some(board) = &self.task_board else {
+            return Vec::new();
+        };
+        let Some(index) = board::BOARD_COLUMNS.iter().position(|s| *s == column) else {
+            return Vec::new();
+        };
+        let ids: Vec<TaskId> = {
+            let tasks = self.task_manager.lock();
+            tasks.board(&self.task_room, &board.filter)[index]
+                .iter()
+                .map(|t| t.id.clone())
+                .collect()
+        };
+        let shown: HashMap<&TaskId, &DisplayTask> = self.tasks.iter().map(|t| (&t.id, t)).collect();
+        ids.iter().filter_map(|id| shown.get(id).copied()).collect()
+    }
+
+    /// The card selected on the board, if its column has any.
+    #[must_use]
+    pub fn selected_card(&self) -> Option<&DisplayTask> {
+        let board = self.task_board.as_ref()?;
+        self.board_cards(board.column).get(board.row).copied()
+    }
+
+    /// Handle key event while the task board is open.
+    ///
+    /// ←→/hl pick a column, ↑↓/jk a card, Shift+←→ or H/L move the card
+    /// to the next column, `n` adds a card at the top of the column, `f`
+    /// edits the filter and Esc closes the prompt or the board.
+    ///
+    /// Returns the change to broadcast when a card was moved or created.
+    fn handle_board_key(&mut self, key: KeyEvent) -> Option<NetCommand> {
+        let board = self.task_board.as_mut()?;
+        if let Some(prompt) = board.prompt.as_mut() {
+            let text = match prompt {
+                BoardPrompt::NewCard(text) | BoardPrompt::Filter(text) => text,
+            };
+            match key.code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Esc => board.prompt = None,
+                KeyCode::Enter => {
+                    return self.submit_board_prompt().map(NetCommand::SendTaskSync);
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let mut sync = None;
+        match key.code {
+            KeyCode::Left if shift => sync = self.move_card(board::column_left),
+            KeyCode::Right if shift => sync = self.move_card(board::column_right),
+            KeyCode::Char('H') => sync = self.move_card(board::column_left),
+            KeyCode::Char('L') => sync = self.move_card(board::column_right),
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(column) = board::column_left(board.column) {
+                    board.column = column;
+                    board.row = 0;
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(column) = board::column_right(board.column) {
+                    board.column = column;
+                    board.row = 0;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => board.row = board.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let column = board.column;
+                let row = board.row + 1;
+                if row < self.board_cards(column).len()
+                    && let Some(board) = self.task_board.as_mut()
+                {
+                    board.row = row;
+                }
+            }
+            KeyCode::Char('n') => board.prompt = Some(BoardPrompt::NewCard(String::new())),
+            KeyCode::Char('f') => {
+                board.prompt = Some(BoardPrompt::Filter(board.filter.to_string()));
+            }
+            KeyCode::Esc | KeyCode::Char('b') => self.task_board = None,
+            _ => {}
+        }
+        sync.map(NetCommand::SendTaskSync)
+    }
+
+    /// Create the card or apply the filter typed into the board prompt,
+    /// returning the change to broadcast for a new card.
+    fn submit_board_prompt(&mut self) -> Option<TaskSyncMessage> {
+        let board = self.task_board.as_mut()?;
+        let column = board.column;
+        match board.prompt.take() {
+            Some(BoardPrompt::NewCard(title)) => {
+                let (number, sync) = self.add_task(title.trim(), column)?;
+                self.select_card(number);
+                return Some(sync);
+            }
+            Some(BoardPrompt::Filter(text)) => {
+                if let Some(filter) = BoardFilter::parse(&text) {
+                    board.filter = filter;
+                    board.row = 0;
+                } else {
+                    // Leave an invalid filter open for editing.
+                    board.prompt = Some(BoardPrompt::Filter(text));
+                }
+            }
+            None => {}
+        }
+        None
+    }
+
+    /// Move the selected card to the column `to` gives for its current one,
+    /// keeping it selected, and return the change to broadcast.
+    fn move_card(&mut self, to: fn(TaskStatus) -> Option<TaskStatus>) -> Option<TaskSyncMessage> {
+        let number = self.selected_card()?.number;
+        let index = self.task_index(number)?;
+        let status = to(self.tasks[index].status.status())?;
+        let sync = self.set_task_status(index, status);
+        self.select_card(number);
+        sync
+    }
+
+    /// Select task `number` on the board, switching to its column.
+    fn select_card(&mut self, number: usize) {
+        let Some(status) = self
+            .tasks
+            .iter()
+            .find(|t| t.number == number)
+            .map(|t| t.status.status())
+        else {
+            return;
+        };
+        if let Some(board) = self.task_board.as_mut() {
+            board.column = status;
+        }
+        let row = self
+            .board_cards(status)
+            .iter()
+            .position(|t| t.number == number)
+            .unwrap_or(0);
+        if let Some(board) = self.task_board.as_mut() {
+            board.row = row;
+        }
+    }
+
+    /// Roll-up progress of a task's subtasks as (completed, total), or
+    /// `None` if it has none.
+    #[must_use]
+    pub fn subtask_progress(&self, number: usize) -> Option<(usize, usize)> {
+        let subtasks: Vec<&DisplayTask> = self
+            .tasks
+            .iter()
+            .filter(|t| t.parent == Some(number))
+            .collect();
+        if subtasks.is_empty() {
+            return None;
+        }
+        let done = subtasks
+            .iter()
+            .filter(|t| t.status == TaskDisplayStatus::Completed)
+            .count();
+        Some((done, subtasks.len()))
+    }
+
+    /// Whether a task is still waiting on any of its blockers.
+    #[must_use]
+    pub fn blocker_state(&self, task: &DisplayTask) -> BlockerState {
+        if task.blocked_by.is_empty() {
+            return BlockerState::None;
+        }
+        let open: Vec<usize> = task
+            .blocked_by
+            .iter()
+            .copied()
+            .filter(|&blocker| {
+                self.tasks
+                    .iter()
+                    .any(|t| t.number == blocker && t.status != TaskDisplayStatus::Completed)
+            })
+            .collect();
+        if open.is_empty() {
+            BlockerState::Unblocked
         } else {
-            self.push_system_message(format!("Task #{number} not found"));
+            BlockerState::Blocked(open)
         }
     }
 
-    /// `/task delete <number>` — remove a task.
-    fn task_cmd_delete(&mut self, args: &str) {
-        let Some(number) = args.parse::<usize>().ok() else {
-            self.push_system_message("Usage: /task delete <number>".to_string());
-            return;
-        };
-        let before = self.tasks.len();
-        self.tasks.retain(|t| t.number != number);
-        if self.tasks.len() < before {
-            // Adjust selected_task if needed
-            if self.selected_task >= self.tasks.len() && !self.tasks.is_empty() {
-                self.selected_task = self.tasks.len() - 1;
+    /// Subtask progress and blocker state shown after a task, e.g.
+    /// `" (1/3) [blocked by #2]"`.
+    fn dependency_details(&self, task: &DisplayTask) -> String {
+        let progress = self
+            .subtask_progress(task.number)
+            .map(|(done, total)| format!("({done}/{total})"));
+        let blockers = match self.blocker_state(task) {
+            BlockerState::Blocked(open) => {
+                let open: Vec<String> = open.iter().map(|n| format!("#{n}")).collect();
+                Some(format!("[blocked by {}]", open.join(" ")))
             }
-            self.push_system_message(format!("Task #{number} deleted"));
+            BlockerState::Unblocked if task.status != TaskDisplayStatus::Completed => {
+                Some("[unblocked]".to_string())
+            }
+            BlockerState::None | BlockerState::Unblocked => None,
+        };
+        let parts: Vec<String> = progress.into_iter().chain(blockers).collect();
+        if parts.is_empty() {
+            String::new()
         } else {
-            self.push_system_message(format!("Task #{number} not found"));
+            format!(" {}", parts.join(" "))
         }
     }
 
-    /// `/task list` — list all tasks as system messages.
-    fn task_cmd_list(&mut self) {
-        if self.tasks.is_empty() {
-            self.push_system_message("No tasks".to_string());
+    /// Follow-up once task `number` is completed by `sync`: announce the
+    /// tasks it unblocked and, if it repeats, its next instance, which the
+    /// task manager created alongside it.
+    fn task_completed(&mut self, number: usize, sync: &TaskSyncMessage) {
+        self.notify_unblocked(number);
+        if let TaskSyncMessage::FullState { tasks, .. } = sync
+            && let [_, next] = tasks.as_slice()
+            && let Some(&next_number) = self.task_numbers.get(&next.id)
+            && let Some(due) = next.due.value.and_then(due_date)
+        {
+            self.push_system_message(format!(
+                "Task #{number} repeats as #{next_number}, due {due}"
+            ));
+        }
+    }
+
+    /// Post a reminder for each open task that has come due, once per due
+    /// date.
+    ///
+    /// Should be called on each tick of the event loop.
+    pub fn tick_reminders(&mut self) {
+        self.remind_due(chrono::Local::now().date_naive());
+    }
+
+    /// Post a reminder for each open task due on or before `today` that
+    /// has not been reminded about for its current due date.
+    fn remind_due(&mut self, today: NaiveDate) {
+        let due = self.task_manager.lock().due_reminders(due_ms(today));
+        if due.is_empty() {
             return;
         }
-        let lines: Vec<String> = self
+        // A task that just arrived from a peer may not be numbered yet.
+        self.refresh_tasks();
+        let room = self.task_room.clone();
+        for task in due.iter().filter(|t| t.room_id == room) {
+            let (Some(&number), Some(due)) = (
+                self.task_numbers.get(&task.id),
+                task.due.value.and_then(due_date),
+            ) else {
+                continue;
+            };
+            self.push_system_message(format!(
+                "Reminder: task #{number} \"{}\" is due {due}",
+                task.title.value
+            ));
+        }
+    }
+
+    /// Announce tasks whose last open blocker was task `completed`.
+    fn notify_unblocked(&mut self, completed: usize) {
+        let unblocked: Vec<usize> = self
             .tasks
             .iter()
-            .map(|task| {
-                let assignee_str = task
-                    .assignee
-                    .as_ref()
-                    .map_or(String::new(), |a| format!(" (@{a})"));
-                let status = task.status.symbol();
-                format!("#{} {status} {}{assignee_str}", task.number, task.title)
+            .filter(|t| {
+                t.blocked_by.contains(&completed) && t.status != TaskDisplayStatus::Completed
             })
+            .filter(|t| self.blocker_state(t) == BlockerState::Unblocked)
+            .map(|t| t.number)
             .collect();
-        for line in lines {
-            self.push_system_message(line);
+        for number in unblocked {
+            self.push_system_message(format!("Task #{number} is unblocked"));
+        }
+    }
+
+    /// Redraw the task list from the task manager, numbering the tasks
+    /// seen for the first time. Numbers are never reused, so a task keeps
+    /// its number while others come and go.
+    ///
+    /// Called after every local change; call it as well when remote
+    /// changes were merged (see
+    /// [`NetEvent::TasksChanged`](crate::net::NetEvent::TasksChanged)).
+    pub fn refresh_tasks(&mut self) {
+        let mut tasks: Vec<DisplayTask> = {
+            let manager = self.task_manager.lock();
+            let live = manager.get_tasks(&self.task_room);
+            for task in &live {
+                if !self.task_numbers.contains_key(&task.id) {
+                    let number = self.task_numbers.len() + 1;
+                    self.task_numbers.insert(task.id.clone(), number);
+                }
+            }
+            let view = TaskView {
+                live: live
+                    .iter()
+                    .filter_map(|t| Some((&t.id, *self.task_numbers.get(&t.id)?)))
+                    .collect(),
+            };
+            let tasks = live.iter().filter_map(|task| view.task(task)).collect();
+            drop(manager);
+            tasks
+        };
+        tasks.sort_by_key(|t| t.number);
+        self.tasks = tasks;
+
+        self.selected_task = self.selected_task.min(self.tasks.len().saturating_sub(1));
+        if self
+            .task_detail
+            .is_some_and(|number| self.task_index(number).is_none())
+        {
+            self.task_detail = None;
+        }
+    }
+
+    /// The task manager the task panel is drawn from.
+    #[must_use]
+    pub fn task_manager(&self) -> &Mutex<TaskManager> {
+        &self.task_manager
+    }
+
+    /// Task room the task panel shows and edits.
+    #[must_use]
+    pub fn task_room(&self) -> &str {
+        &self.task_room
+    }
+
+    /// What an activity entry changed, e.g. `"changed status: open →
+    /// completed"`, naming tasks by their `#N` number.
+    #[must_use]
+    pub fn describe_change(&self, entry: &ActivityEntry) -> String {
+        let value = |value: &str| {
+            self.task_numbers
+                .iter()
+                .find(|(id, _)| id.to_string() == value)
+                .map_or_else(|| value.to_string(), |(_, number)| format!("#{number}"))
+        };
+        match entry.field {
+            "comment" => "commented".to_string(),
+            "description" => "changed description".to_string(),
+            field => format!(
+                "changed {field}: {} \u{2192} {}",
+                value(&entry.old),
+                value(&entry.new)
+            ),
         }
     }
 
@@ -711,10 +1972,7 @@ impl App {
                 self.handle_invite_agent(args);
                 None
             }
-            "/task" => {
-                self.handle_task_command(args);
-                None
-            }
+            "/task" => self.handle_task_command(args),
             "/create-room" => {
                 if !self.is_connected {
                     self.push_system_message("Not connected".to_string());
@@ -948,6 +2206,33 @@ impl App {
         }
     }
 
+    /// Record a peer discovered on the local network, replacing any
+    /// previous entry for the same peer.
+    ///
+    /// Ignored unless LAN peers are listed (see [`App::with_lan_peers`]).
+    pub fn set_lan_peer(&mut self, peer: LanPeerItem) {
+        let Some(peers) = self.lan_peers.as_mut() else {
+            return;
+        };
+        match peers.iter_mut().find(|p| p.peer_id == peer.peer_id) {
+            Some(existing) => *existing = peer,
+            None => peers.push(peer),
+        }
+    }
+
+    /// Forget a LAN peer that withdrew its advertisement.
+    pub fn remove_lan_peer(&mut self, peer_id: &str) {
+        if let Some(peers) = self.lan_peers.as_mut() {
+            peers.retain(|p| p.peer_id != peer_id);
+        }
+    }
+
+    /// LAN peers to list in the sidebar (empty when the list is disabled).
+    #[must_use]
+    pub fn visible_lan_peers(&self) -> &[LanPeerItem] {
+        self.lan_peers.as_deref().unwrap_or_default()
+    }
+
     /// Set a remote peer as typing in a room.
     pub fn set_peer_typing(&mut self, room_id: &str, peer_name: &str, is_typing: bool) {
         let entry = self.typing_peers.entry(room_id.to_string()).or_default();
@@ -982,6 +2267,7 @@ impl Default for App {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -1293,10 +2579,310 @@ mod tests {
         let mut app = App::new();
         submit_input(&mut app, "/task foobar");
         let last = last_msg(&app);
+        assert!(last.content.contains(
+            "Usage: /task add|done|assign|delete|list|desc|due|repeat|priority|label|parent|block|unblock"
+        ));
+    }
+
+    #[test]
+    fn task_desc_sets_multiline_description() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Write docs");
+        submit_input(&mut app, "/task desc 1 Intro\\nThen the details");
+        assert_eq!(app.tasks[0].description, "Intro\nThen the details");
+        submit_input(&mut app, "/task desc 1");
+        assert!(app.tasks[0].description.is_empty());
+    }
+
+    #[test]
+    fn task_due_parses_and_clears_date() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Ship");
+        submit_input(&mut app, "/task due 1 2026-03-01");
+        assert_eq!(app.tasks[0].due, NaiveDate::from_ymd_opt(2026, 3, 1));
+        submit_input(&mut app, "/task due 1 next week");
+        assert!(last_msg(&app).content.contains("Usage: /task due"));
+        assert!(app.tasks[0].due.is_some());
+        submit_input(&mut app, "/task due 1 none");
+        assert!(app.tasks[0].due.is_none());
+    }
+
+    #[test]
+    fn task_repeat_sets_validates_and_clears() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Standup");
+        submit_input(&mut app, "/task repeat 1 cron 30  9 * * 1-5");
+        assert_eq!(
+            app.tasks[0].recurrence,
+            Some(Recurrence::Cron("30 9 * * 1-5".to_string()))
+        );
+        assert_eq!(app.tasks[0].details(), " repeats cron 30 9 * * 1-5");
+        submit_input(&mut app, "/task repeat 1 cron 61 * * * *");
+        assert!(last_msg(&app).content.contains("Invalid recurrence"));
+        submit_input(&mut app, "/task repeat 1 hourly");
+        assert!(last_msg(&app).content.contains("Usage: /task repeat"));
+        submit_input(&mut app, "/task repeat 1 none");
+        assert!(app.tasks[0].recurrence.is_none());
+    }
+
+    #[test]
+    fn completing_repeating_task_creates_next_instance() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Water plants");
+        submit_input(&mut app, "/task assign 1 @alice");
+        submit_input(&mut app, "/task label 1 home");
+        submit_input(&mut app, "/task due 1 2099-03-02");
+        submit_input(&mut app, "/task repeat 1 weekly");
+        submit_input(&mut app, "/task done 1");
+        assert_eq!(app.tasks.len(), 2);
+        let next = &app.tasks[1];
+        assert_eq!(next.number, 2);
+        assert_eq!(next.status, TaskDisplayStatus::Open);
+        assert_eq!(next.assignee.as_deref(), Some("alice"));
+        assert!(next.labels.contains("home"));
+        assert_eq!(next.due, NaiveDate::from_ymd_opt(2099, 3, 9));
+        assert_eq!(next.recurrence, Some(Recurrence::Weekly));
+        assert!(
+            last_msg(&app)
+                .content
+                .contains("repeats as #2, due 2099-03-09")
+        );
+
+        // A task that does not repeat is just completed.
+        submit_input(&mut app, "/task add Once");
+        submit_input(&mut app, "/task done 3");
+        assert_eq!(app.tasks.len(), 3);
+    }
+
+    #[test]
+    fn reminds_due_tasks_once_per_due_date() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Ship");
+        submit_input(&mut app, "/task add Later");
+        submit_input(&mut app, "/task add Done");
+        submit_input(&mut app, "/task due 1 2026-03-01");
+        submit_input(&mut app, "/task due 2 2026-03-05");
+        submit_input(&mut app, "/task due 3 2026-03-01");
+        submit_input(&mut app, "/task done 3");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        app.remind_due(today);
+        assert_eq!(
+            last_msg(&app).content,
+            "Reminder: task #1 \"Ship\" is due 2026-03-01"
+        );
+        let count = app.messages.values().map(Vec::len).sum::<usize>();
+        app.remind_due(today);
+        assert_eq!(app.messages.values().map(Vec::len).sum::<usize>(), count);
+
+        // Moving the due date reminds again once it comes due.
+        submit_input(&mut app, "/task due 1 2026-03-02");
+        app.remind_due(today);
+        assert!(last_msg(&app).content.contains("is due 2026-03-02"));
+    }
+
+    /// Wait for background task list file IO, returning the task changes
+    /// it produced.
+    fn finish_task_files(app: &mut App) -> Vec<NetCommand> {
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        let mut commands = app.tick_task_files();
+        while app.task_files_pending > 0 {
+            assert!(Instant::now() < deadline, "task file IO timed out");
+            thread::sleep(std::time::Duration::from_millis(5));
+            commands.extend(app.tick_task_files());
+        }
+        commands
+    }
+
+    #[test]
+    fn task_export_import_round_trips() {
+        let dir = std::env::temp_dir().join(format!("termchat-test-app-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Ship, finally");
+        submit_input(&mut app, "/task assign 1 @alice");
+        submit_input(&mut app, "/task add Announce");
+        submit_input(&mut app, "/task done 2");
+        for ext in ["json", "csv", "md"] {
+            let path = dir.join(format!("tasks.{ext}"));
+            submit_input(&mut app, &format!("/task export {}", path.display()));
+            assert!(finish_task_files(&mut app).is_empty());
+            assert!(
+                last_msg(&app).content.starts_with("Exported 2 tasks"),
+                "{ext}"
+            );
+
+            let mut other = App::new();
+            submit_input(&mut other, &format!("/task import {}", path.display()));
+            let commands = finish_task_files(&mut other);
+            assert!(matches!(
+                commands.as_slice(),
+                [NetCommand::SendTaskSync(TaskSyncMessage::FullState { tasks, .. })]
+                    if tasks.len() == 2
+            ));
+            assert!(last_msg(&other).content.starts_with("Imported 2 tasks"));
+            assert_eq!(other.tasks[0].title, "Ship, finally");
+            assert_eq!(other.tasks[0].assignee.as_deref(), Some("alice"));
+            assert_eq!(other.tasks[1].status, TaskDisplayStatus::Completed);
+        }
+
+        submit_input(&mut app, "/task export tasks.txt");
+        assert!(last_msg(&app).content.contains("Usage: /task export"));
+        let bad = dir.join("bad.json");
+        std::fs::write(&bad, r#"[{"title":"Fine"},{"title":""}]"#).unwrap();
+        let mut other = App::new();
+        submit_input(&mut other, &format!("/task import {}", bad.display()));
+        assert!(finish_task_files(&mut other).is_empty());
+        assert!(last_msg(&other).content.contains("Import failed: task 2"));
+        assert!(other.tasks.is_empty());
+        submit_input(
+            &mut other,
+            &format!("/task import {}", dir.join("missing.md").display()),
+        );
+        finish_task_files(&mut other);
+        assert!(last_msg(&other).content.starts_with("Import failed: "));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn task_priority_sets_and_rejects() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Hotfix");
+        submit_input(&mut app, "/task priority 1 urgent");
+        assert_eq!(app.tasks[0].priority, TaskPriority::Urgent);
+        submit_input(&mut app, "/task priority 1 asap");
+        assert!(last_msg(&app).content.contains("Usage: /task priority"));
+        assert_eq!(app.tasks[0].priority, TaskPriority::Urgent);
+        submit_input(&mut app, "/task priority 9 low");
+        assert!(last_msg(&app).content.contains("Task #9 not found"));
+    }
+
+    #[test]
+    fn task_label_adds_and_removes() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Triage");
+        submit_input(&mut app, "/task label 1 +Bug ui backend");
+        submit_input(&mut app, "/task label 1 -ui");
+        let labels: Vec<&str> = app.tasks[0].labels.iter().map(String::as_str).collect();
+        assert_eq!(labels, ["backend", "bug"]);
+        assert!(last_msg(&app).content.contains("#backend #bug"));
+    }
+
+    #[test]
+    fn task_list_shows_rich_fields() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add Release");
+        submit_input(&mut app, "/task priority 1 high");
+        submit_input(&mut app, "/task due 1 2026-03-01");
+        submit_input(&mut app, "/task label 1 release");
+        let before = msg_count(&app);
+        submit_input(&mut app, "/task list");
+        let msgs = system_msgs(&app);
+        assert!(
+            msgs[before]
+                .content
+                .ends_with("Release ! due 2026-03-01 #release")
+        );
+    }
+
+    #[test]
+    fn task_parent_rolls_up_progress() {
+        let mut app = App::new();
+        for title in ["Epic", "Part one", "Part two"] {
+            submit_input(&mut app, &format!("/task add {title}"));
+        }
+        submit_input(&mut app, "/task parent 2 1");
+        submit_input(&mut app, "/task parent 3 1");
+        submit_input(&mut app, "/task done 2");
+        assert_eq!(app.subtask_progress(1), Some((1, 2)));
+        assert_eq!(app.subtask_progress(2), None);
+
+        submit_input(&mut app, "/task parent 1 3");
+        assert!(last_msg(&app).content.contains("cycle"));
+        submit_input(&mut app, "/task parent 1 1");
+        assert!(last_msg(&app).content.contains("cycle"));
+        assert_eq!(app.tasks[0].parent, None);
+
+        submit_input(&mut app, "/task parent 3 none");
+        assert_eq!(app.subtask_progress(1), Some((1, 1)));
+        submit_input(&mut app, "/task parent 3 9");
+        assert!(last_msg(&app).content.contains("Task #9 not found"));
+    }
+
+    #[test]
+    fn task_block_rejects_cycles() {
+        let mut app = App::new();
+        for title in ["A", "B", "C"] {
+            submit_input(&mut app, &format!("/task add {title}"));
+        }
+        submit_input(&mut app, "/task block 1 2");
+        submit_input(&mut app, "/task block 2 3");
+        submit_input(&mut app, "/task block 3 1");
+        assert!(last_msg(&app).content.contains("cycle"));
+        assert!(app.tasks[2].blocked_by.is_empty());
+
+        submit_input(&mut app, "/task unblock 2 3");
+        submit_input(&mut app, "/task block 3 1");
+        assert_eq!(app.tasks[2].blocked_by, BTreeSet::from([1]));
+    }
+
+    #[test]
+    fn completing_last_blocker_announces_unblocked() {
+        let mut app = App::new();
+        for title in ["Ship", "Review", "Tests"] {
+            submit_input(&mut app, &format!("/task add {title}"));
+        }
+        submit_input(&mut app, "/task block 1 2");
+        submit_input(&mut app, "/task block 1 3");
+        assert_eq!(
+            app.blocker_state(&app.tasks[0]),
+            BlockerState::Blocked(vec![2, 3])
+        );
+
+        submit_input(&mut app, "/task done 2");
+        assert!(last_msg(&app).content.contains("#2 marked as completed"));
+        assert_eq!(
+            app.blocker_state(&app.tasks[0]),
+            BlockerState::Blocked(vec![3])
+        );
+
+        // Completing via the task panel announces too.
+        app.focus = PanelFocus::Tasks;
+        app.selected_task = 2;
+        app.handle_tasks_key(key(KeyCode::Enter));
+        app.handle_tasks_key(key(KeyCode::Enter));
+        assert_eq!(last_msg(&app).content, "Task #1 is unblocked");
+        assert_eq!(app.blocker_state(&app.tasks[0]), BlockerState::Unblocked);
+
+        let before = msg_count(&app);
+        submit_input(&mut app, "/task list");
+        assert!(
+            system_msgs(&app)[before]
+                .content
+                .ends_with("Ship [unblocked]")
+        );
+    }
+
+    #[test]
+    fn task_list_shows_dependencies_and_delete_clears_them() {
+        let mut app = App::new();
+        for title in ["Epic", "Part", "Blocker"] {
+            submit_input(&mut app, &format!("/task add {title}"));
+        }
+        submit_input(&mut app, "/task parent 2 1");
+        submit_input(&mut app, "/task block 1 3");
+        let before = msg_count(&app);
+        submit_input(&mut app, "/task list");
         assert!(
-            last.content
-                .contains("Usage: /task add|done|assign|delete|list")
+            system_msgs(&app)[before]
+                .content
+                .ends_with("Epic (0/1) [blocked by #3]")
         );
+
+        submit_input(&mut app, "/task delete 3");
+        submit_input(&mut app, "/task delete 1");
+        assert!(app.tasks[0].parent.is_none());
+        assert_eq!(app.blocker_state(&app.tasks[0]), BlockerState::None);
     }
 
     // --- Keyboard handling tests (task #10) ---
@@ -1430,4 +3016,250 @@ mod tests {
         submit_input(&mut app, "/task delete 2"); // delete B
         assert_eq!(app.selected_task, 0); // should adjust down
     }
+
+    /// The activity log of the task at `index`, as the detail view
+    /// describes it.
+    fn activity(app: &App, index: usize) -> Vec<String> {
+        app.task_manager()
+            .lock()
+            .activity(&app.tasks[index].id)
+            .iter()
+            .map(|entry| app.describe_change(entry))
+            .collect()
+    }
+
+    #[test]
+    fn task_comments_thread_and_record_activity() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task add A");
+        submit_input(&mut app, "/task priority 1 high");
+        submit_input(&mut app, "/task comment 1 Which browser?");
+        submit_input(&mut app, "/task reply 1 1 Firefox");
+        submit_input(&mut app, "/task reply 1 7 Nope");
+        assert!(last_msg(&app).content.contains("Comment [7] not found"));
+
+        let tasks = app.task_manager().lock();
+        let thread: Vec<(usize, &str)> = tasks
+            .comment_thread(app.task_room(), &app.tasks[0].id)
+            .into_iter()
+            .map(|(depth, c)| (depth, c.body.as_str()))
+            .collect();
+        assert_eq!(thread, [(0, "Which browser?"), (1, "Firefox")]);
+        drop(tasks);
+        let changes = activity(&app, 0);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0], "changed priority: normal \u{2192} high");
+
+        submit_input(&mut app, "/task show 1");
+        assert_eq!(app.task_detail, Some(1));
+        app.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(app.task_detail, None);
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn task_changes_go_through_the_shared_task_manager() {
+        let tasks = Arc::new(Mutex::new(TaskManager::new("alice".to_string())));
+        let mut app = App::new().with_tasks(Arc::clone(&tasks), "dm:alice:bob".to_string());
+        app.input = "/task add Ship".to_string();
+        assert!(matches!(
+            app.submit_message(),
+            Some(NetCommand::SendTaskSync(TaskSyncMessage::FullState { .. }))
+        ));
+        assert_eq!(
+            tasks.lock().get_tasks("dm:alice:bob")[0].title.value,
+            "Ship"
+        );
+
+        // A task merged from a peer shows up once the list is refreshed.
+        let mut bob = TaskManager::new("bob".to_string());
+        let (task, sync) = bob.create_task("dm:alice:bob", "Review").unwrap();
+        tasks
+            .lock()
+            .apply_remote(&crate::transport::PeerId::new("bob"), &sync);
+        app.refresh_tasks();
+        assert_eq!(app.tasks[1].id, task.id);
+        assert_eq!(app.tasks[1].number, 2);
+
+        app.focus = PanelFocus::Tasks;
+        app.selected_task = 1;
+        assert!(matches!(
+            app.handle_key_event(key(KeyCode::Enter)),
+            Some(NetCommand::SendTaskSync(
+                TaskSyncMessage::FieldUpdate { .. }
+            ))
+        ));
+        assert_eq!(
+            tasks.lock().get_tasks("dm:alice:bob")[1].status.value,
+            TaskStatus::InProgress
+        );
+        assert_eq!(
+            activity(&app, 1)[0],
+            "changed status: open \u{2192} in_progress"
+        );
+        assert_eq!(tasks.lock().activity(&app.tasks[1].id)[0].author, "alice");
+    }
+
+    // --- Task board tests ---
+
+    fn type_keys(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn board_moves_cards_between_columns() {
+        let mut app = app_with_tasks();
+        app.handle_key_event(key(KeyCode::Char('b')));
+        assert!(app.task_board.is_some());
+        app.handle_key_event(key(KeyCode::Down));
+        assert_eq!(app.selected_card().map(|t| t.number), Some(2));
+
+        let sync = app.handle_key_event(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT));
+        assert!(matches!(
+            sync,
+            Some(NetCommand::SendTaskSync(
+                TaskSyncMessage::FieldUpdate { .. }
+            ))
+        ));
+        assert_eq!(app.tasks[1].status, TaskDisplayStatus::InProgress);
+        assert_eq!(
+            activity(&app, 1)[0],
+            "changed status: open \u{2192} in_progress"
+        );
+        // The moved card stays selected in its new column.
+        assert_eq!(
+            app.task_board.as_ref().map(|b| b.column),
+            Some(TaskStatus::InProgress)
+        );
+        assert_eq!(app.selected_card().map(|t| t.number), Some(2));
+
+        app.handle_key_event(key(KeyCode::Char('L')));
+        app.handle_key_event(key(KeyCode::Char('L')));
+        assert_eq!(app.tasks[1].status, TaskDisplayStatus::Completed);
+        assert_eq!(app.board_cards(TaskStatus::Open).len(), 2);
+
+        app.handle_key_event(key(KeyCode::Esc));
+        assert!(app.task_board.is_none());
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn board_quick_create_adds_card_to_column() {
+        let mut app = App::new();
+        submit_input(&mut app, "/task board");
+        app.handle_key_event(key(KeyCode::Char('l')));
+        app.handle_key_event(key(KeyCode::Char('n')));
+        type_keys(&mut app, "Write docs");
+        // Keys go to the prompt, not to board navigation.
+        assert_eq!(
+            app.task_board.as_ref().map(|b| b.column),
+            Some(TaskStatus::InProgress)
+        );
+        app.handle_key_event(key(KeyCode::Enter));
+
+        assert_eq!(app.tasks.len(), 1);
+        assert_eq!(app.tasks[0].title, "Write docs");
+        assert_eq!(app.tasks[0].status, TaskDisplayStatus::InProgress);
+        assert_eq!(app.selected_card().map(|t| t.number), Some(1));
+
+        // A second card goes on top of the column.
+        app.handle_key_event(key(KeyCode::Char('n')));
+        type_keys(&mut app, "Review docs");
+        let sync = app.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(
+            sync,
+            Some(NetCommand::SendTaskSync(TaskSyncMessage::FullState { .. }))
+        ));
+        assert_eq!(app.task_board.as_ref().map(|b| b.row), Some(0));
+        assert_eq!(
+            app.board_cards(TaskStatus::InProgress)
+                .iter()
+                .map(|t| t.number)
+                .collect::<Vec<_>>(),
+            [2, 1]
+        );
+    }
+
+    #[test]
+    fn board_filters_by_assignee_and_label() {
+        let mut app = app_with_tasks();
+        submit_input(&mut app, "/task assign 1 @alice");
+        submit_input(&mut app, "/task assign 2 @alice");
+        submit_input(&mut app, "/task label 2 +bug");
+        submit_input(&mut app, "/task board @alice");
+        let numbers = |app: &App| -> Vec<usize> {
+            app.board_cards(TaskStatus::Open)
+                .iter()
+                .map(|t| t.number)
+                .collect()
+        };
+        assert_eq!(numbers(&app), [2, 1]);
+
+        app.handle_key_event(key(KeyCode::Char('f')));
+        type_keys(&mut app, " #bug");
+        app.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(numbers(&app), [2]);
+
+        app.handle_key_event(key(KeyCode::Char('f')));
+        type_keys(&mut app, " bug");
+        app.handle_key_event(key(KeyCode::Enter));
+        // An invalid filter stays open for editing.
+        assert!(app.task_board.as_ref().is_some_and(|b| b.prompt.is_some()));
+
+        submit_input(&mut app, "/task board nope");
+        assert!(last_msg(&app).content.contains("Usage: /task board"));
+    }
+
+    // --- LAN peer tests ---
+
+    fn lan_peer(peer_id: &str, addr: &str) -> LanPeerItem {
+        LanPeerItem {
+            peer_id: peer_id.to_string(),
+            addr: addr.to_string(),
+            fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn set_lan_peer_replaces_existing_entry() {
+        let mut app = App::new().with_lan_peers(true);
+        app.set_lan_peer(lan_peer("bob", "192.168.1.20:7000"));
+        app.set_lan_peer(lan_peer("carol", "192.168.1.21:7000"));
+        app.set_lan_peer(lan_peer("bob", "192.168.1.30:7000"));
+        assert_eq!(
+            app.visible_lan_peers(),
+            &[
+                lan_peer("bob", "192.168.1.30:7000"),
+                lan_peer("carol", "192.168.1.21:7000")
+            ]
+        );
+
+        app.remove_lan_peer("bob");
+        assert_eq!(
+            app.visible_lan_peers(),
+            &[lan_peer("carol", "192.168.1.21:7000")]
+        );
+    }
+
+    #[test]
+    fn lan_peers_hidden_unless_enabled() {
+        let mut app = App::new();
+        app.set_lan_peer(lan_peer("bob", "192.168.1.20:7000"));
+        assert!(app.visible_lan_peers().is_empty());
+    }
+
+    // --- Relay latency tests ---
+
+    #[test]
+    fn relay_latency_cleared_on_connection_change() {
+        let mut app = App::new();
+        app.set_connection_status(true, "Relay");
+        app.set_relay_latency(42);
+        assert_eq!(app.relay_latency_ms, Some(42));
+
+        app.set_connection_status(false, "Reconnecting");
+        assert_eq!(app.relay_latency_ms, None);
+    }
 }
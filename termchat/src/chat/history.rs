@@ -354,7 +354,7 @@ impl MessageStore for InMemoryStore {
             .collect();
 
         // Sort by timestamp, most recent first
-        results.sort_by(|a, b| b.0.metadata.timestamp.cmp(&a.0.metadata.timestamp));
+        results.sort_by_key(|(msg, _)| std::cmp::Reverse(msg.metadata.timestamp));
         results.truncate(limit);
 
         Ok(results)
@@ -362,6 +362,11 @@ impl MessageStore for InMemoryStore {
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::doc_markdown,
+    clippy::match_wildcard_for_single_variants,
+    clippy::unwrap_used
+)]
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicBool, Ordering};
@@ -3,9 +3,13 @@
 //! Contains the [`ChatManager`] which orchestrates the send pipeline
 //! (validate -> serialize -> encrypt -> transmit), delivery acknowledgment
 //! flow, message status tracking, and local history persistence.
+//!
+//! Messages that cannot be sent while the peer is unreachable wait in an
+//! [`outbox::Outbox`], which can be persisted to survive restarts.
 
 pub mod ack;
 pub mod history;
+pub mod outbox;
 pub mod receive;
 pub mod room;
 pub mod send;
@@ -18,13 +22,23 @@ use tokio::sync::{Mutex, mpsc};
 
 use termchat_proto::codec;
 use termchat_proto::message::{ChatMessage, Envelope, MessageId, MessageStatus, SenderId};
+use termchat_proto::version::FeatureFlags;
 
 use crate::config::ChatConfig;
 use crate::crypto::{CryptoError, CryptoSession};
-use crate::transport::{PeerId, Transport, TransportError};
+use crate::transport::{PeerId, TrafficClass, Transport, TransportError};
 
 use history::{MessageStore, ResilientHistoryWriter};
 
+/// First byte of an [`Envelope::Handshake`] carrying our
+/// [`ProtocolHello`](termchat_proto::version::ProtocolHello); the peer
+/// answers with its own.
+const HELLO: u8 = 0x01;
+
+/// First byte of an [`Envelope::Handshake`] answering a [`HELLO`]. Replies
+/// are not answered.
+const HELLO_REPLY: u8 = 0x02;
+
 /// Errors that can occur when sending a message through the pipeline.
 #[derive(Debug, thiserror::Error)]
 pub enum SendError {
@@ -100,6 +114,13 @@ pub enum ChatEvent {
         /// Whether the peer is currently typing.
         is_typing: bool,
     },
+    /// A task sync message was received from a peer.
+    TaskSync {
+        /// The decoded sync message.
+        message: termchat_proto::task::TaskSyncMessage,
+        /// The peer that sent it, as reported by the transport.
+        from: PeerId,
+    },
 }
 
 /// Manages the chat send/receive pipeline with status tracking and history.
@@ -132,6 +153,8 @@ pub struct ChatManager<C: CryptoSession, T: Transport, S: MessageStore> {
     pending_acks: Mutex<Vec<(MessageId, PeerId)>>,
     /// Chat subsystem configuration (payload limits, dedup tracking, clock skew).
     chat_config: ChatConfig,
+    /// Protocol features the remote peer is known to support.
+    peer_features: Mutex<FeatureFlags>,
 }
 
 impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
@@ -182,6 +205,7 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
             seen_message_ids: Mutex::new(HashSet::new()),
             pending_acks: Mutex::new(Vec::new()),
             chat_config,
+            peer_features: Mutex::new(FeatureFlags::NONE),
         };
         (manager, event_rx)
     }
@@ -220,6 +244,7 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
             seen_message_ids: Mutex::new(HashSet::new()),
             pending_acks: Mutex::new(Vec::new()),
             chat_config: ChatConfig::default(),
+            peer_features: Mutex::new(FeatureFlags::NONE),
         };
         (manager, event_rx, warning_rx)
     }
@@ -253,20 +278,70 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         self.history.as_ref()
     }
 
+    /// Records the protocol features the remote peer negotiated (for
+    /// example from [`NoiseHandshake::negotiated`](crate::crypto::noise::NoiseHandshake::negotiated)).
+    ///
+    /// [`receive_one`](Self::receive_one) calls this when a hello sent with
+    /// [`send_hello`](Self::send_hello) arrives. Until then, the peer is
+    /// assumed to support no optional features and outgoing envelopes are
+    /// never compressed.
+    pub async fn set_peer_features(&self, features: FeatureFlags) {
+        *self.peer_features.lock().await = features;
+    }
+
+    /// Returns the protocol features the remote peer is known to support.
+    pub async fn peer_features(&self) -> FeatureFlags {
+        *self.peer_features.lock().await
+    }
+
+    /// Internal: serialize an envelope, compressing it if the peer
+    /// negotiated [`FeatureFlags::COMPRESSION`].
+    async fn encode_for_peer(&self, envelope: &Envelope) -> Result<Vec<u8>, codec::CodecError> {
+        if self
+            .peer_features()
+            .await
+            .contains(FeatureFlags::COMPRESSION)
+        {
+            codec::encode_compressed(
+                envelope,
+                self.chat_config.compression,
+                self.chat_config.compression_threshold,
+            )
+        } else {
+            codec::encode(envelope)
+        }
+    }
+
     /// Internal: encrypt, serialize, and send an envelope to a peer.
     async fn send_envelope(&self, envelope: &Envelope, peer: &PeerId) -> Result<(), SendError> {
-        let serialized = codec::encode(envelope)?;
+        let serialized = self.encode_for_peer(envelope).await?;
         let encrypted = self.crypto.encrypt(&serialized)?;
-        self.transport.send(peer, &encrypted).await?;
+        self.transport
+            .send_class(peer, traffic_class(envelope), &encrypted)
+            .await?;
         Ok(())
     }
 }
 
+/// The transport traffic class an envelope is sent with.
+const fn traffic_class(envelope: &Envelope) -> TrafficClass {
+    match envelope {
+        Envelope::Chat(_) | Envelope::Ack(_) | Envelope::Nack(_) | Envelope::Handshake(_) => {
+            TrafficClass::Chat
+        }
+        Envelope::TaskSync(_) => TrafficClass::Sync,
+        Envelope::PresenceUpdate(_) | Envelope::TypingIndicator(_) => TrafficClass::Ephemeral,
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::doc_markdown, clippy::type_complexity, clippy::unwrap_used)]
 mod tests {
     use std::time::Duration;
 
-    use termchat_proto::message::{ConversationId, Envelope, MessageContent};
+    use termchat_proto::message::{
+        ConversationId, Envelope, MessageContent, MessageMetadata, Timestamp,
+    };
 
     use super::*;
     use crate::crypto::noise::StubNoiseSession;
@@ -633,6 +708,61 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn duplicate_message_is_acked_again() {
+        let (alice, _alice_events, bob, mut bob_events) = setup_pair();
+        let message = ChatMessage {
+            metadata: MessageMetadata {
+                message_id: MessageId::new(),
+                timestamp: Timestamp::now(),
+                sender_id: SenderId::new(vec![0xaa]),
+                conversation_id: ConversationId::new(),
+            },
+            content: MessageContent::Text("resent".into()),
+        };
+
+        // The first ack is lost; Alice resends under the same ID.
+        alice.send_chat_message(message.clone()).await.unwrap();
+        bob.receive_one().await.unwrap();
+        alice.transport().recv().await.unwrap();
+        alice.send_chat_message(message.clone()).await.unwrap();
+        bob.receive_one().await.unwrap();
+
+        match alice.receive_one().await.unwrap() {
+            Envelope::Ack(ack) => assert_eq!(ack.message_id, message.metadata.message_id),
+            other => panic!("expected Ack, got {other:?}"),
+        }
+        assert!(matches!(
+            bob_events.try_recv(),
+            Ok(ChatEvent::MessageReceived { .. })
+        ));
+        assert!(bob_events.try_recv().is_err(), "duplicate must not re-emit");
+    }
+
+    #[tokio::test]
+    async fn task_sync_round_trip() {
+        let (alice, _alice_events, bob, mut bob_events) = setup_pair();
+        let msg = termchat_proto::task::TaskSyncMessage::RequestFullState {
+            room_id: "room-1".to_string(),
+        };
+
+        alice.send_task_sync(&msg).await;
+
+        match bob.receive_one().await.unwrap() {
+            Envelope::TaskSync(data) => {
+                assert_eq!(termchat_proto::task::decode(&data).unwrap(), msg);
+            }
+            other => panic!("expected TaskSync, got {other:?}"),
+        }
+        assert_eq!(
+            bob_events.try_recv().unwrap(),
+            ChatEvent::TaskSync {
+                message: msg,
+                from: PeerId::new("alice"),
+            }
+        );
+    }
+
     // --- History integration tests ---
 
     #[tokio::test]
@@ -846,4 +976,24 @@ mod tests {
         assert_eq!(config.ack_timeout, Duration::from_secs(10));
         assert_eq!(config.ack_retries, 1);
     }
+
+    #[test]
+    fn envelopes_map_to_traffic_classes() {
+        assert_eq!(
+            traffic_class(&Envelope::Handshake(vec![1])),
+            TrafficClass::Chat
+        );
+        assert_eq!(
+            traffic_class(&Envelope::TaskSync(vec![1])),
+            TrafficClass::Sync
+        );
+        assert_eq!(
+            traffic_class(&Envelope::TypingIndicator(vec![1])),
+            TrafficClass::Ephemeral
+        );
+        assert_eq!(
+            traffic_class(&Envelope::PresenceUpdate(vec![1])),
+            TrafficClass::Ephemeral
+        );
+    }
 }
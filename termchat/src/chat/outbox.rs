@@ -0,0 +1,306 @@
+//! Persistent outbound message queue.
+//!
+//! [`Outbox`] holds chat messages that could not be sent while the peer was
+//! unreachable. Entries keep the message's original [`MessageId`], so a
+//! message resent after a restart is deduplicated by the receiver even if
+//! an earlier attempt already got through.
+//!
+//! When backed by a file, the queue is rewritten after every change (to a
+//! temporary file that is then renamed over the original), so a crash never
+//! leaves a half-written queue behind.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use termchat_proto::message::{ChatMessage, MessageId};
+
+/// On-disk format version of the outbox file.
+const OUTBOX_FORMAT_VERSION: u8 = 1;
+
+/// Errors that can occur while loading or persisting the outbox.
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    /// Reading or writing the outbox file failed.
+    #[error("outbox I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The outbox file could not be encoded or decoded.
+    #[error("outbox codec error: {0}")]
+    Codec(#[from] postcard::Error),
+
+    /// The outbox file was written by an unknown format version.
+    #[error("unsupported outbox format version {0}")]
+    UnsupportedVersion(u8),
+}
+
+/// Serialized form of the outbox file.
+#[derive(Serialize, Deserialize)]
+struct OutboxFile {
+    /// Format version ([`OUTBOX_FORMAT_VERSION`]).
+    version: u8,
+    /// Queued messages, oldest first.
+    messages: Vec<ChatMessage>,
+}
+
+/// A bounded FIFO of unsent chat messages, optionally persisted to disk.
+#[derive(Debug)]
+pub struct Outbox {
+    /// File the queue is persisted to (`None` keeps it in memory only).
+    path: Option<PathBuf>,
+    /// Maximum number of queued messages.
+    cap: usize,
+    /// Queued messages, oldest first.
+    messages: VecDeque<ChatMessage>,
+}
+
+impl Outbox {
+    /// Create an empty outbox that is not persisted.
+    #[must_use]
+    pub const fn in_memory(cap: usize) -> Self {
+        Self {
+            path: None,
+            cap,
+            messages: VecDeque::new(),
+        }
+    }
+
+    /// Open the outbox persisted at `path`, loading any queued messages.
+    ///
+    /// A missing file yields an empty outbox. If the file holds more than
+    /// `cap` messages, only the oldest `cap` are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutboxError`] if the file exists but cannot be read or
+    /// decoded.
+    pub fn open(path: impl Into<PathBuf>, cap: usize) -> Result<Self, OutboxError> {
+        let path = path.into();
+        let mut messages = match std::fs::read(&path) {
+            Ok(bytes) => {
+                let file: OutboxFile = postcard::from_bytes(&bytes)?;
+                if file.version != OUTBOX_FORMAT_VERSION {
+                    return Err(OutboxError::UnsupportedVersion(file.version));
+                }
+                VecDeque::from(file.messages)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if messages.len() > cap {
+            tracing::warn!(
+                queued = messages.len(),
+                cap,
+                "outbox exceeds queue cap, dropping newest messages"
+            );
+            messages.truncate(cap);
+        }
+        Ok(Self {
+            path: Some(path),
+            cap,
+            messages,
+        })
+    }
+
+    /// The file backing this outbox, if any.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of queued messages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if no messages are queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns `true` if the outbox holds `cap` messages.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.messages.len() >= self.cap
+    }
+
+    /// Queued messages, oldest first.
+    pub fn messages(&self) -> impl Iterator<Item = &ChatMessage> {
+        self.messages.iter()
+    }
+
+    /// Append `message` and persist the queue.
+    ///
+    /// Returns `Ok(false)` (and queues nothing) if the outbox is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutboxError`] if the queue cannot be persisted. The message
+    /// stays queued in memory.
+    pub fn push(&mut self, message: ChatMessage) -> Result<bool, OutboxError> {
+        if self.is_full() {
+            return Ok(false);
+        }
+        self.messages.push_back(message);
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// Remove the message with `id` and persist the queue.
+    ///
+    /// Returns `Ok(false)` if no such message was queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OutboxError`] if the queue cannot be persisted. The message
+    /// is removed from memory regardless.
+    pub fn remove(&mut self, id: &MessageId) -> Result<bool, OutboxError> {
+        let before = self.messages.len();
+        self.messages.retain(|m| m.metadata.message_id != *id);
+        if self.messages.len() == before {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// Write the queue to disk, if persisted.
+    fn persist(&self) -> Result<(), OutboxError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = OutboxFile {
+            version: OUTBOX_FORMAT_VERSION,
+            messages: self.messages.iter().cloned().collect(),
+        };
+        let bytes = postcard::to_allocvec(&file)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use termchat_proto::message::{
+        ConversationId, MessageContent, MessageMetadata, SenderId, Timestamp,
+    };
+
+    use super::*;
+
+    fn message(text: &str) -> ChatMessage {
+        ChatMessage {
+            metadata: MessageMetadata {
+                message_id: MessageId::new(),
+                timestamp: Timestamp::now(),
+                sender_id: SenderId::new(b"alice".to_vec()),
+                conversation_id: ConversationId::new(),
+            },
+            content: MessageContent::Text(text.to_string()),
+        }
+    }
+
+    fn texts(outbox: &Outbox) -> Vec<String> {
+        outbox
+            .messages()
+            .map(|m| match &m.content {
+                MessageContent::Text(text) => text.clone(),
+            })
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("termchat-test-outbox");
+        let path = dir.join(format!("{name}-{}.bin", uuid::Uuid::now_v7()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn push_respects_cap() {
+        let mut outbox = Outbox::in_memory(2);
+        assert!(outbox.push(message("one")).unwrap());
+        assert!(outbox.push(message("two")).unwrap());
+        assert!(outbox.is_full());
+        assert!(!outbox.push(message("three")).unwrap());
+        assert_eq!(texts(&outbox), ["one", "two"]);
+    }
+
+    #[test]
+    fn remove_by_message_id() {
+        let mut outbox = Outbox::in_memory(10);
+        let first = message("one");
+        let id = first.metadata.message_id.clone();
+        outbox.push(first).unwrap();
+        outbox.push(message("two")).unwrap();
+
+        assert!(outbox.remove(&id).unwrap());
+        assert!(!outbox.remove(&id).unwrap());
+        assert_eq!(texts(&outbox), ["two"]);
+    }
+
+    #[test]
+    fn reopen_restores_messages_with_original_ids() {
+        let path = temp_path("reopen");
+        let queued = message("while offline");
+        {
+            let mut outbox = Outbox::open(&path, 10).unwrap();
+            assert!(outbox.is_empty());
+            outbox.push(queued.clone()).unwrap();
+            outbox.push(message("second")).unwrap();
+        }
+
+        let outbox = Outbox::open(&path, 10).unwrap();
+        assert_eq!(outbox.len(), 2);
+        assert_eq!(outbox.messages().next(), Some(&queued));
+        assert_eq!(texts(&outbox), ["while offline", "second"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn removal_is_persisted() {
+        let path = temp_path("remove");
+        let queued = message("sent after restart");
+        let id = queued.metadata.message_id.clone();
+        {
+            let mut outbox = Outbox::open(&path, 10).unwrap();
+            outbox.push(queued).unwrap();
+        }
+        {
+            let mut outbox = Outbox::open(&path, 10).unwrap();
+            assert!(outbox.remove(&id).unwrap());
+        }
+        assert!(Outbox::open(&path, 10).unwrap().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reopen_with_smaller_cap_keeps_oldest() {
+        let path = temp_path("cap");
+        {
+            let mut outbox = Outbox::open(&path, 10).unwrap();
+            for text in ["a", "b", "c"] {
+                outbox.push(message(text)).unwrap();
+            }
+        }
+        let outbox = Outbox::open(&path, 2).unwrap();
+        assert_eq!(texts(&outbox), ["a", "b"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let path = temp_path("corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, [0xFF, 0xFF, 0xFF]).unwrap();
+        assert!(Outbox::open(&path, 10).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -7,12 +7,13 @@ use termchat_proto::codec;
 use termchat_proto::message::{
     DeliveryAck, Envelope, MessageId, MessageStatus, Nack, NackReason, SenderId, Timestamp,
 };
+use termchat_proto::version::{self, FeatureFlags, PROTOCOL_VERSION, ProtocolHello};
 
 use crate::crypto::CryptoSession;
 use crate::transport::{PeerId, Transport};
 
 use super::history::MessageStore;
-use super::{ChatEvent, ChatManager, SendError};
+use super::{ChatEvent, ChatManager, HELLO, HELLO_REPLY, SendError};
 
 impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
     /// Receive and process one incoming envelope from the transport.
@@ -20,11 +21,23 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
     /// Handles the following cases:
     /// - **Chat message**: Validates, decrypts, deserializes, checks for duplicates,
     ///   stores in history, and automatically sends back a [`DeliveryAck`].
-    ///   Emits a [`ChatEvent::MessageReceived`].
+    ///   Emits a [`ChatEvent::MessageReceived`]. Duplicates are acked again
+    ///   but not re-emitted.
     /// - **Delivery ack**: Updates the tracked status from `Sent` to
     ///   `Delivered`. Updates history if configured. Emits a
     ///   [`ChatEvent::StatusChanged`].
-    /// - **Nack**: Logs the negative acknowledgment (UC-002 Extension 5a).
+    /// - **Nack**: Logs the negative acknowledgment (UC-002 Extension 5a). A
+    ///   NACK for an unsupported protocol version marks the message `Failed`.
+    /// - **Compressed payload**: Inflated up to
+    ///   [`ChatConfig::max_decompressed_size`](crate::config::ChatConfig::max_decompressed_size);
+    ///   the peer is then known to support compression for replies.
+    /// - **Hello**: Records the features negotiated with the peer and
+    ///   answers a first hello with our own.
+    /// - **Task sync**: Emits a [`ChatEvent::TaskSync`] with the sending
+    ///   peer, for the task manager to apply.
+    /// - **Unknown variant**: Messages from a newer protocol version are
+    ///   rejected with [`NackReason::UnsupportedVersion`] so the sender learns
+    ///   why they were not delivered.
     ///
     /// # Errors
     ///
@@ -50,16 +63,36 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         // Step 4: Decrypt (Extension 4a handled by crypto layer)
         let decrypted = self.crypto.decrypt(&encrypted)?;
 
-        // Step 5: Deserialize (Extension 5a)
-        let envelope = match codec::decode(&decrypted) {
-            Ok(env) => env,
+        // Step 5: Deserialize (Extension 5a). Compressed payloads may only
+        // inflate to a bounded multiple of the wire limit.
+        let envelope = match codec::decode_with_limit(
+            &decrypted,
+            self.chat_config.max_decompressed_size(),
+        ) {
+            Ok(env) => {
+                // A peer that sends compressed payloads can evidently read them.
+                if codec::is_compressed(&decrypted) {
+                    let mut features = self.peer_features.lock().await;
+                    *features = features.union(FeatureFlags::COMPRESSION);
+                }
+                env
+            }
             Err(e) => {
                 tracing::warn!(peer = %from, error = %e, "deserialization failed, sending NACK");
-                // Send NACK for deserialization failure
-                // We can't extract a message ID since deserialization failed, so use a dummy
-                let nack = Nack {
-                    message_id: MessageId::new(),
-                    reason: NackReason::DeserializationFailed,
+                // Unknown variants usually mean the peer runs a newer protocol;
+                // echo the message ID when the codec could recover it. Otherwise
+                // we can't extract a message ID, so use a dummy.
+                let nack = match &e {
+                    codec::CodecError::UnknownVariant { message_id, .. } => Nack {
+                        message_id: message_id.clone().unwrap_or_else(MessageId::new),
+                        reason: NackReason::UnsupportedVersion {
+                            protocol_version: PROTOCOL_VERSION,
+                        },
+                    },
+                    _ => Nack {
+                        message_id: MessageId::new(),
+                        reason: NackReason::DeserializationFailed,
+                    },
                 };
                 let _ = self.send_envelope(&Envelope::Nack(nack), &from).await;
                 return Err(SendError::Codec(e));
@@ -73,7 +106,15 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
                 {
                     let mut seen = self.seen_message_ids.lock().await;
                     if seen.contains(&msg_id) {
+                        drop(seen);
                         tracing::debug!(message_id = %msg_id, "duplicate message dropped");
+                        // The sender may be retransmitting because our ack was
+                        // lost, so acknowledge it again.
+                        let ack = DeliveryAck {
+                            message_id: msg_id,
+                            timestamp: Timestamp::now(),
+                        };
+                        let _ = self.send_envelope(&Envelope::Ack(ack), &from).await;
                         return Ok(envelope);
                     }
                     // Track this message ID
@@ -171,11 +212,26 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
                     reason = ?nack.reason,
                     "received NACK from peer"
                 );
-                // For now, just log. Future work: update message status to Failed.
+                if let NackReason::UnsupportedVersion { protocol_version } = nack.reason {
+                    self.fail_unsupported(&nack.message_id, protocol_version)
+                        .await;
+                }
             }
-            Envelope::Handshake(_) | Envelope::TaskSync(_) => {
-                // Handshake: handled by the crypto layer (UC-005).
-                // TaskSync: handled by the tasks module (UC-008).
+            Envelope::Handshake(data) => self.handle_hello(&from, data).await,
+            Envelope::TaskSync(data) => {
+                // Decode and hand to the tasks module (UC-008), which checks
+                // the change against the transport-level sender.
+                match termchat_proto::task::decode(data) {
+                    Ok(message) => {
+                        let _ = self.event_tx.try_send(ChatEvent::TaskSync {
+                            message,
+                            from: from.clone(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!(peer = %from, error = %e, "failed to decode task sync message");
+                    }
+                }
             }
             Envelope::PresenceUpdate(data) => {
                 // Decode presence message and emit event to UI
@@ -209,6 +265,58 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         Ok(envelope)
     }
 
+    /// Record the features negotiated from a peer's hello, answering a
+    /// [`HELLO`] with our own.
+    ///
+    /// Other handshake payloads belong to the crypto layer (UC-005) and are
+    /// ignored here.
+    async fn handle_hello(&self, from: &PeerId, data: &[u8]) {
+        let Some((&kind, hello)) = data.split_first() else {
+            return;
+        };
+        if kind != HELLO && kind != HELLO_REPLY {
+            return;
+        }
+        let negotiated = version::decode_hello(hello).and_then(|remote| {
+            ProtocolHello::current()
+                .negotiate(&remote)
+                .map_err(|e| e.to_string())
+        });
+        match negotiated {
+            Ok(protocol) => self.set_peer_features(protocol.features).await,
+            Err(e) => {
+                tracing::warn!(peer = %from, error = %e, "ignoring peer hello");
+                return;
+            }
+        }
+        if kind == HELLO
+            && let Err(e) = self.send_handshake(HELLO_REPLY, from).await
+        {
+            tracing::debug!(peer = %from, error = %e, "failed to answer peer hello");
+        }
+    }
+
+    /// Mark a sent message as failed because the peer runs an older protocol
+    /// that cannot decode it, updating history and notifying the UI.
+    async fn fail_unsupported(&self, message_id: &MessageId, peer_version: u16) {
+        let status = MessageStatus::Failed(format!(
+            "peer cannot decode this message (protocol v{peer_version})"
+        ));
+        let mut statuses = self.statuses.lock().await;
+        let Some(tracked) = statuses.get_mut(message_id) else {
+            return;
+        };
+        *tracked = status.clone();
+        drop(statuses);
+        if let Some(ref history) = self.history {
+            history.update_status(message_id, status.clone()).await;
+        }
+        let _ = self.event_tx.try_send(ChatEvent::StatusChanged {
+            message_id: message_id.clone(),
+            status,
+        });
+    }
+
     /// Check if the sender ID matches the authenticated peer.
     ///
     /// For now, this is a simple comparison. In the real system with Noise,
@@ -218,6 +218,7 @@ impl RoomManager {
             display_name: admin_display_name.to_string(),
             is_admin: true,
             is_agent: false,
+            is_read_only: false,
         };
 
         let room = Room {
@@ -368,6 +369,7 @@ impl RoomManager {
             display_name: display_name.clone(),
             is_admin: false,
             is_agent: false,
+            is_read_only: false,
         };
 
         // Must re-borrow mutably after the immutable borrow above
@@ -564,6 +566,40 @@ impl RoomManager {
 
         Ok(removed)
     }
+
+    /// Grants or revokes a member's read-only access to the room's tasks,
+    /// returning the updated [`MemberInfo`]. Admins cannot be made
+    /// read-only (see [`MemberInfo::role`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RoomError`] if:
+    /// - The room doesn't exist ([`RoomError::RoomNotFound`])
+    /// - The local user is not admin ([`RoomError::NotAdmin`])
+    /// - The member is not in the room ([`RoomError::MemberNotFound`])
+    pub fn set_read_only(
+        &mut self,
+        room_id: &str,
+        peer_id: &str,
+        read_only: bool,
+    ) -> Result<MemberInfo, RoomError> {
+        let room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| RoomError::RoomNotFound(room_id.to_string()))?;
+
+        if !room.is_admin {
+            return Err(RoomError::NotAdmin(room_id.to_string()));
+        }
+
+        let member = room
+            .members
+            .iter_mut()
+            .find(|m| m.peer_id == peer_id)
+            .ok_or_else(|| RoomError::MemberNotFound(peer_id.to_string()))?;
+        member.is_read_only = read_only;
+        Ok(member.clone())
+    }
 }
 
 /// Validates and sanitizes a room name.
@@ -602,7 +638,10 @@ pub fn validate_room_name(name: &str) -> Result<String, RoomError> {
 }
 
 #[cfg(test)]
+#[allow(clippy::redundant_clone, clippy::unwrap_used)]
 mod tests {
+    use termchat_proto::room::MemberRole;
+
     use super::*;
 
     // --- Name validation tests ---
@@ -1045,6 +1084,7 @@ mod tests {
             display_name: "Helper Bot".to_string(),
             is_admin: false,
             is_agent: true,
+            is_read_only: false,
         };
         let members = mgr.add_member(&room.room_id, agent).unwrap();
         assert_eq!(members.len(), 2);
@@ -1073,6 +1113,7 @@ mod tests {
             display_name: "Helper Bot".to_string(),
             is_admin: false,
             is_agent: true,
+            is_read_only: false,
         };
         mgr.add_member(&room.room_id, agent.clone()).unwrap();
         let _ = rx.try_recv(); // drain first MemberJoined
@@ -1093,6 +1134,7 @@ mod tests {
             display_name: "Helper Bot".to_string(),
             is_admin: false,
             is_agent: true,
+            is_read_only: false,
         };
         let result = mgr.add_member("nonexistent", agent);
         assert_eq!(
@@ -1113,6 +1155,7 @@ mod tests {
                 display_name: format!("User {i}"),
                 is_admin: false,
                 is_agent: false,
+                is_read_only: false,
             };
             mgr.add_member(&room.room_id, member).unwrap();
         }
@@ -1123,6 +1166,7 @@ mod tests {
             display_name: "Overflow".to_string(),
             is_admin: false,
             is_agent: false,
+            is_read_only: false,
         };
         let result = mgr.add_member(&room.room_id, overflow);
         assert_eq!(result, Err(RoomError::RoomFull));
@@ -1141,6 +1185,7 @@ mod tests {
             display_name: "Helper Bot".to_string(),
             is_admin: false,
             is_agent: true,
+            is_read_only: false,
         };
         mgr.add_member(&room.room_id, agent).unwrap();
         let _ = rx.try_recv(); // drain MemberJoined
@@ -1188,6 +1233,36 @@ mod tests {
         );
     }
 
+    // --- set_read_only tests ---
+
+    #[test]
+    fn set_read_only_changes_role() {
+        let (mut mgr, _rx) = RoomManager::new();
+        let room = mgr.create_room("General", "peer-alice", "Alice").unwrap();
+        let bob = MemberInfo {
+            peer_id: "peer-bob".to_string(),
+            display_name: "Bob".to_string(),
+            is_admin: false,
+            is_agent: false,
+            is_read_only: false,
+        };
+        mgr.add_member(&room.room_id, bob).unwrap();
+
+        let updated = mgr.set_read_only(&room.room_id, "peer-bob", true).unwrap();
+        assert_eq!(updated.role(), MemberRole::ReadOnly);
+        let members = mgr.get_room_members(&room.room_id).unwrap();
+        assert!(members[1].is_read_only);
+
+        let result = mgr.set_read_only(&room.room_id, "peer-nobody", true);
+        assert_eq!(
+            result,
+            Err(RoomError::MemberNotFound("peer-nobody".to_string()))
+        );
+        mgr.rooms.get_mut(&room.room_id).unwrap().is_admin = false;
+        let result = mgr.set_read_only(&room.room_id, "peer-bob", false);
+        assert_eq!(result, Err(RoomError::NotAdmin(room.room_id.clone())));
+    }
+
     #[test]
     fn remove_member_emits_event_with_display_name() {
         let (mut mgr, mut rx) = RoomManager::new();
@@ -1199,6 +1274,7 @@ mod tests {
             display_name: "Bob".to_string(),
             is_admin: false,
             is_agent: false,
+            is_read_only: false,
         };
         mgr.add_member(&room.room_id, bob).unwrap();
         let _ = rx.try_recv(); // drain MemberJoined
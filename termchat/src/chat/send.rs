@@ -6,23 +6,27 @@
 use termchat_proto::codec;
 use termchat_proto::message::{
     ChatMessage, ConversationId, Envelope, MessageContent, MessageId, MessageMetadata,
-    MessageStatus, Timestamp,
+    MessageStatus, Timestamp, ValidationError,
 };
+use termchat_proto::version::{self, ProtocolHello};
 
 use crate::crypto::CryptoSession;
-use crate::transport::Transport;
+use crate::transport::{PeerId, Transport};
 
 use super::history::MessageStore;
-use super::{ChatEvent, ChatManager, RetryConfig, SendError};
+use super::{ChatEvent, ChatManager, HELLO, RetryConfig, SendError};
 
 impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
     /// Send a message through the full pipeline.
     ///
     /// Pipeline steps (MSS 2-6):
     /// 1. Build [`ChatMessage`] with metadata (ID, timestamp, sender, conversation)
-    /// 2. Validate the message (non-empty, within size limit)
-    /// 3. Serialize via [`codec::encode`]
-    /// 4. Encrypt via [`CryptoSession::encrypt`]
+    /// 2. Validate the message (non-empty, no larger than the peer will
+    ///    inflate)
+    /// 3. Serialize via [`codec::encode`](termchat_proto::codec::encode), compressing large payloads if the
+    ///    peer negotiated compression
+    /// 4. Encrypt via [`CryptoSession::encrypt`], checking the result against
+    ///    `max_payload_size`
     /// 5. Transmit via [`Transport::send`]
     /// 6. Save to history (if configured)
     ///
@@ -39,31 +43,69 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         conversation: ConversationId,
     ) -> Result<(MessageId, MessageStatus), SendError> {
         // Step 1: Build the ChatMessage with metadata
-        let message_id = MessageId::new();
         let message = ChatMessage {
             metadata: MessageMetadata {
-                message_id: message_id.clone(),
+                message_id: MessageId::new(),
                 timestamp: Timestamp::now(),
                 sender_id: self.sender_id.clone(),
                 conversation_id: conversation,
             },
             content,
         };
+        let message_id = message.metadata.message_id.clone();
+        let status = self.send_chat_message(message).await?;
+        Ok((message_id, status))
+    }
 
-        // Step 2: Validate
-        message.validate()?;
+    /// Send an already-built message through pipeline steps 2-6.
+    ///
+    /// The message keeps its own [`MessageId`] and timestamp, so a message
+    /// queued while offline (possibly across a restart) is resent under its
+    /// original ID and deduplicated by the receiver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] if any pipeline step fails.
+    pub async fn send_chat_message(
+        &self,
+        message: ChatMessage,
+    ) -> Result<MessageStatus, SendError> {
+        // Step 2: Validate. The wire size limit applies after compression
+        // (step 4), so only text the receiver would refuse to inflate is
+        // rejected here.
+        match message.validate() {
+            Ok(()) | Err(ValidationError::TooLarge { .. }) => {}
+            Err(e) => return Err(e.into()),
+        }
+        let MessageContent::Text(text) = &message.content;
+        let max = self.chat_config.max_decompressed_size();
+        if text.len() > max {
+            return Err(ValidationError::TooLarge {
+                size: text.len(),
+                max,
+            }
+            .into());
+        }
 
         // Step 3: Serialize
         let envelope = Envelope::Chat(message.clone());
-        let serialized = codec::encode(&envelope)?;
+        let serialized = self.encode_for_peer(&envelope).await?;
 
         // Step 4: Encrypt (Invariant 1: plaintext never leaves app boundary)
         let encrypted = self.crypto.encrypt(&serialized)?;
+        if encrypted.len() > self.chat_config.max_payload_size {
+            return Err(ValidationError::TooLarge {
+                size: encrypted.len(),
+                max: self.chat_config.max_payload_size,
+            }
+            .into());
+        }
 
         // Step 5: Transmit
         self.transport.send(&self.peer_id, &encrypted).await?;
 
         // Track status
+        let message_id = message.metadata.message_id.clone();
         let status = MessageStatus::Sent;
         self.statuses
             .lock()
@@ -77,11 +119,11 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
 
         // Notify UI
         let _ = self.event_tx.try_send(ChatEvent::StatusChanged {
-            message_id: message_id.clone(),
+            message_id,
             status: status.clone(),
         });
 
-        Ok((message_id, status))
+        Ok(status)
     }
 
     /// Send a message with transport-level retry on failure (Extension 6a).
@@ -122,6 +164,31 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         Err(last_err.unwrap_or_else(|| unreachable!("loop ran at least once")))
     }
 
+    /// Announce our protocol version and features to the connected peer.
+    ///
+    /// The peer records the features both sides support and answers with
+    /// its own hello, which [`receive_one`](Self::receive_one) records in
+    /// turn. Until then, outgoing envelopes are not compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`] if the hello cannot be encoded, encrypted, or
+    /// sent.
+    pub async fn send_hello(&self) -> Result<(), SendError> {
+        self.send_handshake(HELLO, &self.peer_id).await
+    }
+
+    /// Internal: send a hello of the given kind ([`HELLO`] or
+    /// [`HELLO_REPLY`](super::HELLO_REPLY)).
+    pub(super) async fn send_handshake(&self, kind: u8, peer: &PeerId) -> Result<(), SendError> {
+        let mut data = vec![kind];
+        data.extend(
+            version::encode_hello(&ProtocolHello::current())
+                .map_err(codec::CodecError::Serialization)?,
+        );
+        self.send_envelope(&Envelope::Handshake(data), peer).await
+    }
+
     /// Send a presence update to the connected peer.
     ///
     /// Presence messages are fire-and-forget: no ack is expected, and send
@@ -137,6 +204,24 @@ impl<C: CryptoSession, T: Transport, S: MessageStore> ChatManager<C, T, S> {
         }
     }
 
+    /// Send a task sync message to the connected peer.
+    ///
+    /// Task sync is fire-and-forget like presence: convergence comes from
+    /// the CRDT merge and periodic full-state exchange, not from acks.
+    pub async fn send_task_sync(&self, msg: &termchat_proto::task::TaskSyncMessage) {
+        let data = match termchat_proto::task::encode(msg) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to serialize task sync message");
+                return;
+            }
+        };
+        let envelope = Envelope::TaskSync(data);
+        if let Err(e) = self.send_envelope(&envelope, &self.peer_id).await {
+            tracing::debug!(error = %e, "failed to send task sync (fire-and-forget)");
+        }
+    }
+
     /// Send a typing indicator to the connected peer.
     ///
     /// Typing indicators are fire-and-forget: no ack is expected, and send
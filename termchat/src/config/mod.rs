@@ -9,10 +9,15 @@
 //! Missing config file is not an error (defaults are used). An explicit
 //! `--config` path that doesn't exist is an error.
 
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::time::Duration;
 
-use crate::net::NetConfig;
+use termchat_proto::codec::Compression;
+
+use crate::net::{NetConfig, P2pConfig};
+use crate::transport::proxy::Socks5Proxy;
+use crate::transport::relay::RelayConnectOptions;
 
 /// Errors that can occur when loading configuration.
 #[derive(Debug, thiserror::Error)]
@@ -46,6 +51,7 @@ struct ConfigFile {
     network: NetworkFileConfig,
     chat: ChatFileConfig,
     ui: UiFileConfig,
+    tasks: TasksFileConfig,
     agent: AgentFileConfig,
 }
 
@@ -54,16 +60,32 @@ struct ConfigFile {
 #[serde(default)]
 struct NetworkFileConfig {
     relay_url: Option<String>,
+    relay_urls: Option<Vec<String>>,
+    relay_health_interval_secs: Option<u64>,
+    task_sync_interval_secs: Option<u64>,
     peer_id: Option<String>,
     remote_peer: Option<String>,
+    invite_token: Option<String>,
+    proxy: Option<Socks5Proxy>,
     connect_timeout_secs: Option<u64>,
     register_timeout_secs: Option<u64>,
+    keepalive_interval_secs: Option<u64>,
+    keepalive_max_missed: Option<u32>,
     channel_capacity: Option<usize>,
     reconnect_initial_delay_ms: Option<u64>,
     reconnect_max_delay_ms: Option<u64>,
     reconnect_max_attempts: Option<u32>,
     reconnect_stability_threshold_secs: Option<u64>,
     reconnect_message_queue_cap: Option<usize>,
+    p2p_listen: Option<SocketAddr>,
+    peer_addr: Option<SocketAddr>,
+    p2p_connect_timeout_secs: Option<u64>,
+    p2p_upgrade_interval_secs: Option<u64>,
+    hole_punch: Option<bool>,
+    punch_timeout_secs: Option<u64>,
+    lan_discovery: Option<bool>,
+    key_fingerprint: Option<String>,
+    outbox_path: Option<PathBuf>,
 }
 
 /// `[chat]` section of the config file.
@@ -77,6 +99,8 @@ struct ChatFileConfig {
     max_duplicate_tracking: Option<usize>,
     clock_skew_tolerance_secs: Option<u64>,
     chat_event_buffer: Option<usize>,
+    compression: Option<Compression>,
+    compression_threshold: Option<usize>,
 }
 
 /// `[ui]` section of the config file.
@@ -87,6 +111,14 @@ struct UiFileConfig {
     typing_timeout_secs: Option<u64>,
     timestamp_format: Option<String>,
     max_task_title_len: Option<usize>,
+    show_lan_peers: Option<bool>,
+}
+
+/// `[tasks]` section of the config file.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct TasksFileConfig {
+    store_dir: Option<PathBuf>,
 }
 
 /// `[agent]` section of the config file.
@@ -100,6 +132,9 @@ struct AgentFileConfig {
 // Resolved configuration (concrete types, all fields populated)
 // ---------------------------------------------------------------------------
 
+/// How many times `max_payload_size` a compressed payload may inflate to.
+pub const MAX_INFLATE_RATIO: usize = 16;
+
 /// Chat subsystem configuration (used by `ChatManager`).
 #[derive(Debug, Clone)]
 pub struct ChatConfig {
@@ -109,6 +144,41 @@ pub struct ChatConfig {
     pub max_duplicate_tracking: usize,
     /// Clock skew tolerance in milliseconds.
     pub clock_skew_tolerance_ms: u64,
+    /// Compression applied to outgoing payloads for peers that support it.
+    pub compression: Compression,
+    /// Serialized payloads smaller than this (bytes) are sent uncompressed.
+    pub compression_threshold: usize,
+}
+
+impl ChatConfig {
+    /// Largest payload accepted after decompression ([`MAX_INFLATE_RATIO`]
+    /// times `max_payload_size`).
+    ///
+    /// Compression lets a message larger than `max_payload_size` fit on the
+    /// wire; this bound keeps a small compressed payload from inflating
+    /// without limit.
+    #[must_use]
+    pub const fn max_decompressed_size(&self) -> usize {
+        self.max_payload_size.saturating_mul(MAX_INFLATE_RATIO)
+    }
+
+    /// Resolve the chat section from the config file, falling back to
+    /// `defaults` for missing keys.
+    fn resolve(file: &ChatFileConfig, defaults: &Self) -> Self {
+        Self {
+            max_payload_size: file.max_payload_size.unwrap_or(defaults.max_payload_size),
+            max_duplicate_tracking: file
+                .max_duplicate_tracking
+                .unwrap_or(defaults.max_duplicate_tracking),
+            clock_skew_tolerance_ms: file
+                .clock_skew_tolerance_secs
+                .map_or(defaults.clock_skew_tolerance_ms, |s| s * 1000),
+            compression: file.compression.unwrap_or(defaults.compression),
+            compression_threshold: file
+                .compression_threshold
+                .unwrap_or(defaults.compression_threshold),
+        }
+    }
 }
 
 impl Default for ChatConfig {
@@ -117,6 +187,8 @@ impl Default for ChatConfig {
             max_payload_size: 64 * 1024,
             max_duplicate_tracking: 10_000,
             clock_skew_tolerance_ms: 5 * 60 * 1000,
+            compression: Compression::Deflate,
+            compression_threshold: 512,
         }
     }
 }
@@ -154,14 +226,30 @@ pub struct ClientConfig {
     // -- Network --
     /// Relay server WebSocket URL.
     pub relay_url: Option<String>,
+    /// Additional relays to fail over to, in order of preference after
+    /// `relay_url`.
+    pub relay_urls: Vec<String>,
+    /// How often alternate relays are health-checked.
+    pub relay_health_interval: Duration,
+    /// How often task rooms are reconciled with the remote peer.
+    pub task_sync_interval: Duration,
     /// Local peer identity string.
     pub peer_id: Option<String>,
     /// Remote peer identity string.
     pub remote_peer: Option<String>,
+    /// Invite token presented to relays that restrict registration.
+    pub invite_token: Option<String>,
+    /// SOCKS5 proxy for relay connections. When set, direct P2P and LAN
+    /// discovery are disabled so no traffic bypasses the proxy.
+    pub proxy: Option<Socks5Proxy>,
     /// Timeout for connecting to the relay server.
     pub connect_timeout: Duration,
     /// Timeout for relay registration acknowledgment.
     pub register_timeout: Duration,
+    /// Interval between relay keepalive pings (zero disables them).
+    pub keepalive_interval: Duration,
+    /// Unanswered keepalive pings before the relay is considered dead.
+    pub keepalive_max_missed: u32,
     /// Channel capacity for command/event mpsc channels.
     pub channel_capacity: usize,
 
@@ -186,10 +274,22 @@ pub struct ClientConfig {
     pub timestamp_format: String,
     /// Maximum task title length in characters.
     pub max_task_title_len: usize,
+    /// Whether peers discovered on the LAN are listed in the sidebar.
+    pub show_lan_peers: bool,
 
     // -- Reconnect --
     /// Reconnection configuration (backoff, retries, queue).
     pub reconnect: ReconnectConfig,
+    /// Direct peer-to-peer (QUIC) settings.
+    pub p2p: P2pConfig,
+    /// File the offline message queue is persisted to. When unset, a
+    /// per-conversation file under the user's local data directory is used.
+    pub outbox_path: Option<PathBuf>,
+
+    // -- Tasks --
+    /// Directory task rooms are persisted to. When unset, a per-peer
+    /// directory under the user's local data directory is used.
+    pub task_store_dir: Option<PathBuf>,
 
     // -- Agent --
     /// Directory for agent Unix sockets.
@@ -200,10 +300,17 @@ impl Default for ClientConfig {
     fn default() -> Self {
         Self {
             relay_url: None,
+            relay_urls: Vec::new(),
+            relay_health_interval: Duration::from_mins(1),
+            task_sync_interval: Duration::from_mins(1),
             peer_id: None,
             remote_peer: None,
+            invite_token: None,
+            proxy: None,
             connect_timeout: Duration::from_secs(10),
             register_timeout: Duration::from_secs(5),
+            keepalive_interval: Duration::from_secs(15),
+            keepalive_max_missed: 3,
             channel_capacity: 256,
             send_retries: 1,
             ack_timeout: Duration::from_secs(10),
@@ -211,10 +318,14 @@ impl Default for ClientConfig {
             chat: ChatConfig::default(),
             chat_event_buffer: 64,
             reconnect: ReconnectConfig::default(),
+            p2p: P2pConfig::default(),
+            outbox_path: None,
+            task_store_dir: None,
             poll_timeout: Duration::from_millis(50),
             typing_timeout_secs: 3,
             timestamp_format: "%H:%M".to_string(),
             max_task_title_len: 256,
+            show_lan_peers: false,
             agent_socket_dir: "/tmp".to_string(),
         }
     }
@@ -243,6 +354,7 @@ impl ClientConfig {
     /// Priority: CLI > file > default. This is separated from `load()` to
     /// enable unit testing without CLI parsing.
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     fn resolve(cli: &CliArgs, file: &ConfigFile) -> Self {
         let defaults = Self::default();
 
@@ -251,11 +363,25 @@ impl ClientConfig {
                 .relay_url
                 .clone()
                 .or_else(|| file.network.relay_url.clone()),
+            relay_urls: file.network.relay_urls.clone().unwrap_or_default(),
+            relay_health_interval: file
+                .network
+                .relay_health_interval_secs
+                .map_or(defaults.relay_health_interval, Duration::from_secs),
+            task_sync_interval: file
+                .network
+                .task_sync_interval_secs
+                .map_or(defaults.task_sync_interval, Duration::from_secs),
             peer_id: cli.peer_id.clone().or_else(|| file.network.peer_id.clone()),
             remote_peer: cli
                 .remote_peer
                 .clone()
                 .or_else(|| file.network.remote_peer.clone()),
+            invite_token: cli
+                .invite_token
+                .clone()
+                .or_else(|| file.network.invite_token.clone()),
+            proxy: cli.proxy.clone().or_else(|| file.network.proxy.clone()),
             connect_timeout: file
                 .network
                 .connect_timeout_secs
@@ -264,6 +390,14 @@ impl ClientConfig {
                 .network
                 .register_timeout_secs
                 .map_or(defaults.register_timeout, Duration::from_secs),
+            keepalive_interval: file
+                .network
+                .keepalive_interval_secs
+                .map_or(defaults.keepalive_interval, Duration::from_secs),
+            keepalive_max_missed: file
+                .network
+                .keepalive_max_missed
+                .unwrap_or(defaults.keepalive_max_missed),
             channel_capacity: file
                 .network
                 .channel_capacity
@@ -274,20 +408,7 @@ impl ClientConfig {
                 .ack_timeout_secs
                 .map_or(defaults.ack_timeout, Duration::from_secs),
             ack_retries: file.chat.ack_retries.unwrap_or(defaults.ack_retries),
-            chat: ChatConfig {
-                max_payload_size: file
-                    .chat
-                    .max_payload_size
-                    .unwrap_or(defaults.chat.max_payload_size),
-                max_duplicate_tracking: file
-                    .chat
-                    .max_duplicate_tracking
-                    .unwrap_or(defaults.chat.max_duplicate_tracking),
-                clock_skew_tolerance_ms: file
-                    .chat
-                    .clock_skew_tolerance_secs
-                    .map_or(defaults.chat.clock_skew_tolerance_ms, |s| s * 1000),
-            },
+            chat: ChatConfig::resolve(&file.chat, &defaults.chat),
             chat_event_buffer: file
                 .chat
                 .chat_event_buffer
@@ -314,6 +435,8 @@ impl ClientConfig {
                     .reconnect_message_queue_cap
                     .unwrap_or(defaults.reconnect.message_queue_cap),
             },
+            p2p: resolve_p2p(cli, file, &defaults.p2p),
+            outbox_path: file.network.outbox_path.clone().or(defaults.outbox_path),
             poll_timeout: file
                 .ui
                 .poll_timeout_ms
@@ -331,6 +454,8 @@ impl ClientConfig {
                 .ui
                 .max_task_title_len
                 .unwrap_or(defaults.max_task_title_len),
+            show_lan_peers: file.ui.show_lan_peers.unwrap_or(defaults.show_lan_peers),
+            task_store_dir: file.tasks.store_dir.clone().or(defaults.task_store_dir),
             agent_socket_dir: file
                 .agent
                 .socket_dir
@@ -342,11 +467,16 @@ impl ClientConfig {
     /// Build a [`NetConfig`] from this configuration, if all required
     /// networking fields are present.
     ///
-    /// Returns `None` if `relay_url`, `peer_id`, or `remote_peer` is missing
+    /// The first of `relay_url` and `relay_urls` becomes the primary relay;
+    /// the rest are failover relays.
+    ///
+    /// Returns `None` if no relay, `peer_id`, or `remote_peer` is configured
     /// (offline demo mode).
     #[must_use]
     pub fn to_net_config(&self) -> Option<NetConfig> {
-        let relay_url = self.relay_url.clone()?;
+        let mut relays = self.relay_url.iter().chain(&self.relay_urls).cloned();
+        let relay_url = relays.next()?;
+        let fallback_relays = relays.filter(|url| *url != relay_url).collect();
         let local_peer_id = self.peer_id.clone()?;
         let remote_peer_id = self.remote_peer.clone()?;
 
@@ -356,11 +486,45 @@ impl ClientConfig {
 
         Some(NetConfig {
             relay_url,
-            local_peer_id,
-            remote_peer_id,
+            fallback_relays,
+            relay_health_interval: self.relay_health_interval,
+            task_sync_interval: self.task_sync_interval,
             channel_capacity: self.channel_capacity,
             chat_event_buffer: self.chat_event_buffer,
             reconnect: self.reconnect.clone(),
+            relay: RelayConnectOptions {
+                connect_timeout: self.connect_timeout,
+                register_timeout: self.register_timeout,
+                invite_token: self.invite_token.clone(),
+                keepalive_interval: self.keepalive_interval,
+                keepalive_max_missed: self.keepalive_max_missed,
+                proxy: self.proxy.clone(),
+            },
+            p2p: self.p2p.clone(),
+            outbox_path: self
+                .outbox_path
+                .clone()
+                .or_else(|| default_outbox_path(&local_peer_id, &remote_peer_id)),
+            local_peer_id,
+            remote_peer_id,
+        })
+    }
+
+    /// Directory the task store lives in: `task_store_dir` if set,
+    /// otherwise `<local data dir>/termchat/tasks/<peer>`, with `local`
+    /// standing in for an unset peer ID.
+    ///
+    /// Returns `None` if neither is configured nor a local data directory
+    /// can be determined.
+    #[must_use]
+    pub fn task_store_dir(&self) -> Option<PathBuf> {
+        self.task_store_dir.clone().or_else(|| {
+            let peer = self.peer_id.as_deref().unwrap_or("local");
+            dirs::data_local_dir().map(|dir| {
+                dir.join("termchat")
+                    .join("tasks")
+                    .join(sanitize_path_component(peer))
+            })
         })
     }
 }
@@ -384,6 +548,31 @@ pub struct CliArgs {
     #[arg(long, env = "REMOTE_PEER")]
     pub remote_peer: Option<String>,
 
+    /// Invite token for relays that restrict registration.
+    #[arg(long, env = "INVITE_TOKEN", hide_env_values = true)]
+    pub invite_token: Option<String>,
+
+    /// SOCKS5 proxy for relay connections (e.g. `socks5://127.0.0.1:9050`).
+    /// Disables direct P2P and LAN discovery.
+    #[arg(long, env = "RELAY_PROXY", hide_env_values = true)]
+    pub proxy: Option<Socks5Proxy>,
+
+    /// Local address to accept direct P2P (QUIC) connections on.
+    #[arg(long, env = "P2P_LISTEN")]
+    pub p2p_listen: Option<SocketAddr>,
+
+    /// Remote peer's direct P2P (QUIC) address to dial before the relay.
+    #[arg(long, env = "PEER_ADDR")]
+    pub peer_addr: Option<SocketAddr>,
+
+    /// Ask the relay to broker NAT hole punching with the remote peer.
+    #[arg(long)]
+    pub hole_punch: bool,
+
+    /// Advertise and discover peers on the local network via mDNS.
+    #[arg(long)]
+    pub lan_discovery: bool,
+
     /// Path to config file (default: `~/.config/termchat/config.toml`).
     #[arg(short, long)]
     pub config: Option<PathBuf>,
@@ -405,6 +594,58 @@ pub struct CliArgs {
 // Internal helpers
 // ---------------------------------------------------------------------------
 
+/// Resolve the P2P settings from CLI args and the `[network]` section.
+fn resolve_p2p(cli: &CliArgs, file: &ConfigFile, defaults: &P2pConfig) -> P2pConfig {
+    P2pConfig {
+        listen_addr: cli.p2p_listen.or(file.network.p2p_listen),
+        peer_addr: cli.peer_addr.or(file.network.peer_addr),
+        connect_timeout: file
+            .network
+            .p2p_connect_timeout_secs
+            .map_or(defaults.connect_timeout, Duration::from_secs),
+        upgrade_interval: file
+            .network
+            .p2p_upgrade_interval_secs
+            .map_or(defaults.upgrade_interval, Duration::from_secs),
+        hole_punch: cli.hole_punch || file.network.hole_punch.unwrap_or(defaults.hole_punch),
+        punch_timeout: file
+            .network
+            .punch_timeout_secs
+            .map_or(defaults.punch_timeout, Duration::from_secs),
+        lan_discovery: cli.lan_discovery
+            || file.network.lan_discovery.unwrap_or(defaults.lan_discovery),
+        fingerprint: file
+            .network
+            .key_fingerprint
+            .clone()
+            .or_else(|| defaults.fingerprint.clone()),
+    }
+}
+
+/// Default location of the offline message queue for a conversation:
+/// `<local data dir>/termchat/outbox/<local>/<remote>.bin`.
+fn default_outbox_path(local_peer_id: &str, remote_peer_id: &str) -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| {
+        dir.join("termchat")
+            .join("outbox")
+            .join(sanitize_path_component(local_peer_id))
+            .join(format!("{}.bin", sanitize_path_component(remote_peer_id)))
+    })
+}
+
+/// A peer ID made safe to use as a single path component.
+fn sanitize_path_component(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 /// Load and parse a TOML config file.
 ///
 /// If `explicit_path` is `Some`, the file must exist (error if not).
@@ -433,6 +674,7 @@ fn load_config_file(explicit_path: Option<&std::path::Path>) -> Result<ConfigFil
 }
 
 #[cfg(test)]
+#[allow(clippy::duration_suboptimal_units, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -462,6 +704,8 @@ mod tests {
         assert_eq!(cc.max_payload_size, 64 * 1024);
         assert_eq!(cc.max_duplicate_tracking, 10_000);
         assert_eq!(cc.clock_skew_tolerance_ms, 300_000);
+        assert_eq!(cc.compression, Compression::Deflate);
+        assert_eq!(cc.compression_threshold, 512);
     }
 
     #[test]
@@ -483,6 +727,8 @@ max_payload_size = 32768
 max_duplicate_tracking = 5000
 clock_skew_tolerance_secs = 600
 chat_event_buffer = 128
+compression = "none"
+compression_threshold = 2048
 
 [ui]
 poll_timeout_ms = 100
@@ -512,6 +758,8 @@ socket_dir = "/var/run"
         assert_eq!(config.chat.max_payload_size, 32768);
         assert_eq!(config.chat.max_duplicate_tracking, 5000);
         assert_eq!(config.chat.clock_skew_tolerance_ms, 600_000);
+        assert_eq!(config.chat.compression, Compression::None);
+        assert_eq!(config.chat.compression_threshold, 2048);
         assert_eq!(config.chat_event_buffer, 128);
         assert_eq!(config.poll_timeout, Duration::from_millis(100));
         assert_eq!(config.typing_timeout_secs, 5);
@@ -597,6 +845,238 @@ peer_id = "file-peer"
         assert_eq!(net.chat_event_buffer, 64);
         assert_eq!(net.reconnect.max_attempts, 10);
         assert_eq!(net.reconnect.initial_delay, Duration::from_secs(1));
+        assert_eq!(net.relay.connect_timeout, Duration::from_secs(10));
+        assert!(net.relay.invite_token.is_none());
+    }
+
+    #[test]
+    fn relay_list_becomes_primary_and_fallbacks() {
+        let toml_str = r#"
+[network]
+relay_urls = ["ws://a:9000/ws", "ws://b:9000/ws"]
+relay_health_interval_secs = 10
+task_sync_interval_secs = 20
+peer_id = "alice"
+remote_peer = "bob"
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let net = ClientConfig::resolve(&CliArgs::default(), &file)
+            .to_net_config()
+            .unwrap();
+        assert_eq!(net.relay_url, "ws://a:9000/ws");
+        assert_eq!(net.fallback_relays, ["ws://b:9000/ws"]);
+        assert_eq!(net.relay_health_interval, Duration::from_secs(10));
+        assert_eq!(net.task_sync_interval, Duration::from_secs(20));
+
+        // An explicit relay URL takes precedence over the list.
+        let cli = CliArgs {
+            relay_url: Some("ws://b:9000/ws".to_string()),
+            ..Default::default()
+        };
+        let net = ClientConfig::resolve(&cli, &file).to_net_config().unwrap();
+        assert_eq!(net.relay_url, "ws://b:9000/ws");
+        assert_eq!(net.fallback_relays, ["ws://a:9000/ws"]);
+        assert_eq!(net.relay_urls(), ["ws://b:9000/ws", "ws://a:9000/ws"]);
+    }
+
+    #[test]
+    fn proxy_threads_into_net_config() {
+        let toml_str = r#"
+[network]
+relay_url = "ws://relay.example:9000/ws"
+peer_id = "alice"
+remote_peer = "bob"
+proxy = "socks5://127.0.0.1:9050"
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let config = ClientConfig::resolve(&CliArgs::default(), &file);
+        let net = config.to_net_config().unwrap();
+        let proxy = net.relay.proxy.unwrap();
+        assert_eq!(proxy.host(), "127.0.0.1");
+        assert_eq!(proxy.port(), 9050);
+
+        let cli = CliArgs {
+            proxy: Some("socks5://tor:9150".parse().unwrap()),
+            ..Default::default()
+        };
+        let config = ClientConfig::resolve(&cli, &file);
+        assert_eq!(config.proxy.unwrap().host(), "tor");
+    }
+
+    #[test]
+    fn invalid_proxy_is_a_parse_error() {
+        let toml_str = r#"
+[network]
+proxy = "http://proxy:8080"
+"#;
+        assert!(toml::from_str::<ConfigFile>(toml_str).is_err());
+    }
+
+    #[test]
+    fn keepalive_settings_thread_into_net_config() {
+        let toml_str = r#"
+[network]
+relay_url = "ws://example.com:9000/ws"
+peer_id = "alice"
+remote_peer = "bob"
+keepalive_interval_secs = 5
+keepalive_max_missed = 2
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let config = ClientConfig::resolve(&CliArgs::default(), &file);
+        assert_eq!(config.keepalive_interval, Duration::from_secs(5));
+        assert_eq!(config.keepalive_max_missed, 2);
+
+        let net = config.to_net_config().unwrap();
+        assert_eq!(net.relay.keepalive_interval, Duration::from_secs(5));
+        assert_eq!(net.relay.keepalive_max_missed, 2);
+
+        let defaults = ClientConfig::resolve(&CliArgs::default(), &ConfigFile::default());
+        assert_eq!(defaults.keepalive_interval, Duration::from_secs(15));
+        assert_eq!(defaults.keepalive_max_missed, 3);
+    }
+
+    #[test]
+    fn invite_token_threads_into_net_config() {
+        let toml_str = r#"
+[network]
+relay_url = "ws://example.com:9000/ws"
+peer_id = "alice"
+remote_peer = "bob"
+invite_token = "v1.alice.1700000000.abcd"
+register_timeout_secs = 2
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let config = ClientConfig::resolve(&CliArgs::default(), &file);
+        assert_eq!(
+            config.invite_token.as_deref(),
+            Some("v1.alice.1700000000.abcd")
+        );
+
+        let net = config.to_net_config().unwrap();
+        assert_eq!(
+            net.relay.invite_token.as_deref(),
+            Some("v1.alice.1700000000.abcd")
+        );
+        assert_eq!(net.relay.register_timeout, Duration::from_secs(2));
+        assert_eq!(net.relay.keepalive_interval, Duration::from_secs(15));
+
+        let cli = CliArgs {
+            invite_token: Some("from-cli".to_string()),
+            ..Default::default()
+        };
+        let config = ClientConfig::resolve(&cli, &file);
+        assert_eq!(config.invite_token.as_deref(), Some("from-cli"));
+    }
+
+    #[test]
+    fn p2p_settings_thread_into_net_config() {
+        let toml_str = r#"
+[network]
+relay_url = "ws://example.com:9000/ws"
+peer_id = "alice"
+remote_peer = "bob"
+p2p_listen = "0.0.0.0:7000"
+peer_addr = "192.0.2.10:7000"
+p2p_connect_timeout_secs = 1
+p2p_upgrade_interval_secs = 5
+hole_punch = true
+punch_timeout_secs = 2
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let net = ClientConfig::resolve(&CliArgs::default(), &file)
+            .to_net_config()
+            .unwrap();
+        assert!(net.p2p.is_enabled());
+        assert_eq!(net.p2p.listen_addr, Some("0.0.0.0:7000".parse().unwrap()));
+        assert_eq!(net.p2p.peer_addr, Some("192.0.2.10:7000".parse().unwrap()));
+        assert_eq!(net.p2p.connect_timeout, Duration::from_secs(1));
+        assert_eq!(net.p2p.upgrade_interval, Duration::from_secs(5));
+        assert!(net.p2p.hole_punch);
+        assert_eq!(net.p2p.punch_timeout, Duration::from_secs(2));
+
+        let cli = CliArgs {
+            peer_addr: Some("198.51.100.1:9000".parse().unwrap()),
+            ..Default::default()
+        };
+        let config = ClientConfig::resolve(&cli, &file);
+        assert_eq!(
+            config.p2p.peer_addr,
+            Some("198.51.100.1:9000".parse().unwrap())
+        );
+        assert!(!ClientConfig::default().p2p.is_enabled());
+    }
+
+    #[test]
+    fn outbox_path_from_file_or_per_conversation_default() {
+        let toml_str = r#"
+[network]
+relay_url = "ws://example.com:9000/ws"
+peer_id = "alice"
+remote_peer = "bob/laptop"
+outbox_path = "/var/lib/termchat/outbox.bin"
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let net = ClientConfig::resolve(&CliArgs::default(), &file)
+            .to_net_config()
+            .unwrap();
+        assert_eq!(
+            net.outbox_path,
+            Some(PathBuf::from("/var/lib/termchat/outbox.bin"))
+        );
+
+        let path = default_outbox_path("alice", "bob/laptop");
+        if let Some(path) = path {
+            assert!(path.ends_with("termchat/outbox/alice/bob_laptop.bin"));
+        }
+    }
+
+    #[test]
+    fn task_store_dir_from_file_or_per_peer_default() {
+        let toml_str = r#"
+[tasks]
+store_dir = "/var/lib/termchat/tasks"
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let config = ClientConfig::resolve(&CliArgs::default(), &file);
+        assert_eq!(
+            config.task_store_dir(),
+            Some(PathBuf::from("/var/lib/termchat/tasks"))
+        );
+
+        let config = ClientConfig {
+            peer_id: Some("alice/laptop".to_string()),
+            ..ClientConfig::default()
+        };
+        if let Some(dir) = config.task_store_dir() {
+            assert!(dir.ends_with("termchat/tasks/alice_laptop"));
+        }
+    }
+
+    #[test]
+    fn lan_discovery_settings() {
+        let toml_str = r#"
+[network]
+lan_discovery = true
+key_fingerprint = "a1b2c3d4e5f60708"
+
+[ui]
+show_lan_peers = true
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let config = ClientConfig::resolve(&CliArgs::default(), &file);
+        assert!(config.p2p.lan_discovery);
+        assert!(config.p2p.is_enabled());
+        assert_eq!(config.p2p.fingerprint.as_deref(), Some("a1b2c3d4e5f60708"));
+        assert!(config.show_lan_peers);
+
+        let cli = CliArgs {
+            lan_discovery: true,
+            ..Default::default()
+        };
+        let config = ClientConfig::resolve(&cli, &ConfigFile::default());
+        assert!(config.p2p.lan_discovery);
+        assert!(!config.show_lan_peers);
     }
 
     #[test]
@@ -221,6 +221,7 @@ impl Default for PeerKeyCache {
 }
 
 #[cfg(test)]
+#[allow(clippy::redundant_clone, clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -89,6 +89,7 @@ pub trait CryptoSession: Send + Sync {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::noise::StubNoiseSession;
     use super::*;
@@ -89,6 +89,7 @@ impl CryptoSession for StubNoiseSession {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -177,6 +178,7 @@ mod tests {
 
 use super::keys::Identity;
 use parking_lot::Mutex;
+use termchat_proto::version::{self, NegotiatedProtocol, ProtocolHello};
 
 /// State of the Noise XX handshake.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -194,10 +196,16 @@ pub enum HandshakeState {
 }
 
 /// Manages the 3-message Noise XX handshake.
+///
+/// Peers advertise their protocol version and features by sending a
+/// [`ProtocolHello`] as the handshake payload ([`write_hello`](Self::write_hello)
+/// / [`read_hello`](Self::read_hello)); the agreed protocol is carried over
+/// to the resulting [`NoiseXXSession`].
 pub struct NoiseHandshake {
     handshake: snow::HandshakeState,
     is_initiator: bool,
     state: HandshakeState,
+    negotiated: Option<NegotiatedProtocol>,
 }
 
 impl NoiseHandshake {
@@ -221,6 +229,7 @@ impl NoiseHandshake {
             handshake,
             is_initiator: true,
             state: HandshakeState::Idle,
+            negotiated: None,
         })
     }
 
@@ -244,6 +253,7 @@ impl NoiseHandshake {
             handshake,
             is_initiator: false,
             state: HandshakeState::Idle,
+            negotiated: None,
         })
     }
 
@@ -322,6 +332,57 @@ impl NoiseHandshake {
         Ok(buf)
     }
 
+    /// Write the next handshake message carrying this build's [`ProtocolHello`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`write_message`](Self::write_message).
+    pub fn write_hello(&mut self) -> Result<Vec<u8>, CryptoError> {
+        let hello = version::encode_hello(&ProtocolHello::current())
+            .map_err(CryptoError::HandshakeFailed)?;
+        self.write_message(&hello)
+    }
+
+    /// Read a handshake message and negotiate the protocol from its payload.
+    ///
+    /// An empty payload (a peer that predates version negotiation) is
+    /// accepted and leaves the negotiated protocol unset. Returns the
+    /// protocol agreed so far.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`read_message`](Self::read_message), plus
+    /// [`CryptoError::HandshakeFailed`] if the payload is not a valid hello
+    /// or the peer shares no protocol version with us. The handshake is
+    /// marked failed in that case.
+    pub fn read_hello(
+        &mut self,
+        message: &[u8],
+    ) -> Result<Option<NegotiatedProtocol>, CryptoError> {
+        let payload = self.read_message(message)?;
+        if payload.is_empty() {
+            return Ok(self.negotiated);
+        }
+        let negotiated = version::decode_hello(&payload)
+            .and_then(|remote| {
+                ProtocolHello::current()
+                    .negotiate(&remote)
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(|e| {
+                self.state = HandshakeState::Failed(e.clone());
+                CryptoError::HandshakeFailed(e)
+            })?;
+        self.negotiated = Some(negotiated);
+        Ok(self.negotiated)
+    }
+
+    /// The protocol agreed with the peer, once a hello has been read.
+    #[must_use]
+    pub const fn negotiated(&self) -> Option<NegotiatedProtocol> {
+        self.negotiated
+    }
+
     /// Check if the handshake is complete.
     #[must_use]
     pub fn is_complete(&self) -> bool {
@@ -361,6 +422,7 @@ impl NoiseHandshake {
 
         Ok(NoiseXXSession {
             transport: Mutex::new(transport),
+            negotiated: self.negotiated,
         })
     }
 }
@@ -371,9 +433,16 @@ impl NoiseHandshake {
 /// Uses `ChaCha20-Poly1305` AEAD for encryption/decryption.
 pub struct NoiseXXSession {
     transport: Mutex<snow::TransportState>,
+    negotiated: Option<NegotiatedProtocol>,
 }
 
 impl NoiseXXSession {
+    /// The protocol agreed during the handshake, if hellos were exchanged.
+    #[must_use]
+    pub const fn negotiated(&self) -> Option<NegotiatedProtocol> {
+        self.negotiated
+    }
+
     /// Get the remote peer's static public key.
     #[must_use]
     pub fn remote_public_key(&self) -> Vec<u8> {
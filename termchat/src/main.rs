@@ -18,6 +18,7 @@
 
 use std::io;
 use std::path::Path;
+use std::sync::Arc;
 
 use clap::Parser;
 use crossterm::{
@@ -25,13 +26,15 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use parking_lot::Mutex;
 use ratatui::{Terminal, backend::CrosstermBackend};
 use tokio::sync::mpsc;
 use tracing_appender::non_blocking::WorkerGuard;
 
-use termchat::app::{App, DisplayMessage, MessageStatus};
+use termchat::app::{App, DisplayMessage, LanPeerItem, MessageStatus};
 use termchat::config::{CliArgs, ClientConfig};
 use termchat::net::{self, NetCommand, NetConfig, NetEvent};
+use termchat::tasks::{self, TaskManager, TaskStore, TaskStoreError};
 use termchat::ui;
 use termchat_proto::presence::PresenceStatus;
 
@@ -108,9 +111,27 @@ async fn run_app(
     net_config: Option<NetConfig>,
     client_config: &ClientConfig,
 ) -> io::Result<()> {
+    // Tasks are shared with the networking layer, which merges remote
+    // changes into the same manager.
+    let local_peer = client_config.peer_id.as_deref().unwrap_or("local");
+    let task_room = net_config.as_ref().map_or_else(
+        || "local".to_string(),
+        |config| tasks::direct_room_id(&config.local_peer_id, &config.remote_peer_id),
+    );
+    let (task_manager, task_store_error) = match open_task_manager(client_config, local_peer) {
+        Ok(manager) => (manager, None),
+        Err(e) => (TaskManager::new(local_peer.to_string()), Some(e)),
+    };
+    let task_manager = Arc::new(Mutex::new(task_manager));
+
     let mut app = App::new()
         .with_typing_timeout(client_config.typing_timeout_secs)
-        .with_max_task_title_len(client_config.max_task_title_len);
+        .with_max_task_title_len(client_config.max_task_title_len)
+        .with_lan_peers(client_config.show_lan_peers)
+        .with_tasks(Arc::clone(&task_manager), task_room);
+    if let Some(e) = task_store_error {
+        app.push_system_message(format!("Tasks will not be saved: {e}"));
+    }
 
     // Attempt to connect to the relay if config is provided.
     let (cmd_tx, mut evt_rx) = match net_config {
@@ -120,7 +141,7 @@ async fn run_app(
             if !remote.is_empty() {
                 app.add_conversation(&format!("@ {remote}"), None);
             }
-            match net::spawn_net(config.clone()).await {
+            match net::spawn_net_with_tasks(config.clone(), Arc::clone(&task_manager)).await {
                 Ok((tx, rx)) => {
                     app.set_connection_status(true, "Relay");
                     app.push_system_message("Connected via Relay".to_string());
@@ -146,8 +167,12 @@ async fn run_app(
             drain_net_events(&mut app, rx);
         }
 
-        // Step 3: Tick typing timer.
+        // Step 3: Tick typing timer, task reminders and task list files.
         app.tick_typing();
+        app.tick_reminders();
+        for net_cmd in app.tick_task_files() {
+            dispatch_net_command(&mut app, cmd_tx.as_ref(), net_cmd);
+        }
 
         // Step 4: Poll for terminal input events.
         if event::poll(client_config.poll_timeout)?
@@ -160,22 +185,8 @@ async fn run_app(
             // handle_key_event returns Some(NetCommand) when user action
             // requires network dispatch (e.g., sending a message, slash
             // commands like /create-room, /join-room, /approve, /deny).
-            if let Some(net_cmd) = app.handle_key_event(key)
-                && let Some(ref tx) = cmd_tx
-            {
-                if app.can_send() {
-                    match tx.try_send(net_cmd) {
-                        Ok(()) => {}
-                        Err(mpsc::error::TrySendError::Full(_)) => {
-                            app.push_system_message("Message queued, network busy".to_string());
-                        }
-                        Err(mpsc::error::TrySendError::Closed(_)) => {
-                            app.push_system_message("Network disconnected".to_string());
-                        }
-                    }
-                } else {
-                    app.push_system_message("Not connected \u{2014} command not sent".to_string());
-                }
+            if let Some(net_cmd) = app.handle_key_event(key) {
+                dispatch_net_command(&mut app, cmd_tx.as_ref(), net_cmd);
             }
         }
 
@@ -189,6 +200,58 @@ async fn run_app(
     }
 }
 
+/// Hand a command from the app to the networking tasks, if there are any.
+fn dispatch_net_command(
+    app: &mut App,
+    cmd_tx: Option<&mpsc::Sender<NetCommand>>,
+    net_cmd: NetCommand,
+) {
+    let Some(tx) = cmd_tx else {
+        return;
+    };
+    // Messages typed while disconnected are queued by the networking
+    // layer and resent after reconnecting. Task changes are kept locally
+    // and repaired by the periodic task digests.
+    if app.can_send()
+        || matches!(
+            net_cmd,
+            NetCommand::SendMessage { .. } | NetCommand::SendTaskSync(_)
+        )
+    {
+        match tx.try_send(net_cmd) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                app.push_system_message("Message queued, network busy".to_string());
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                app.push_system_message("Network disconnected".to_string());
+            }
+        }
+    } else {
+        app.push_system_message("Not connected \u{2014} command not sent".to_string());
+    }
+}
+
+/// Open the task manager over the task store configured for `local_peer`,
+/// loading the rooms saved there.
+///
+/// # Errors
+///
+/// Returns [`TaskStoreError`] if the store cannot be opened or a saved room
+/// cannot be read.
+fn open_task_manager(
+    client_config: &ClientConfig,
+    local_peer: &str,
+) -> Result<TaskManager, TaskStoreError> {
+    let dir = client_config.task_store_dir().ok_or_else(|| {
+        TaskStoreError::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no local data directory for the task store",
+        ))
+    })?;
+    TaskManager::open(local_peer.to_string(), TaskStore::open(dir)?)
+}
+
 /// Drain all pending `NetEvent`s from the receiver and apply them to the app.
 #[allow(clippy::too_many_lines)]
 fn drain_net_events(app: &mut App, rx: &mut mpsc::Receiver<NetEvent>) {
@@ -234,7 +297,7 @@ fn drain_net_events(app: &mut App, rx: &mut mpsc::Receiver<NetEvent>) {
                 connected,
                 transport_type,
             } => {
-                app.set_connection_status(connected, &transport_type);
+                app.set_connection_status(connected, &transport_type.to_string());
                 if connected {
                     app.push_system_message(format!("Connected via {transport_type}"));
                 } else {
@@ -256,9 +319,58 @@ fn drain_net_events(app: &mut App, rx: &mut mpsc::Receiver<NetEvent>) {
                     "Reconnection failed — will retry in background".to_string(),
                 );
             }
+            NetEvent::RelayShutdown {
+                reason,
+                retry_after_ms,
+            } => {
+                app.push_system_message(format!(
+                    "Relay shutting down ({reason}) — reconnecting in {}s",
+                    retry_after_ms.div_ceil(1000)
+                ));
+            }
             NetEvent::Error(msg) => {
                 app.push_system_message(format!("Network error: {msg}"));
             }
+            NetEvent::TasksChanged => app.refresh_tasks(),
+            NetEvent::TaskChangeRejected(warning) => {
+                app.push_system_message(format!("Task sync: {warning}"));
+            }
+            NetEvent::RelayLatency { rtt_ms } => {
+                app.set_relay_latency(rtt_ms);
+            }
+            NetEvent::RelayChanged { url } => {
+                app.push_system_message(format!("Switched to relay {url}"));
+            }
+            NetEvent::LanPeerDiscovered {
+                peer_id,
+                addr,
+                fingerprint,
+            } => {
+                app.set_lan_peer(LanPeerItem {
+                    peer_id,
+                    addr: addr.to_string(),
+                    fingerprint,
+                });
+            }
+            NetEvent::QueuedMessageRestored {
+                recipient,
+                text,
+                timestamp_ms,
+            } => {
+                app.push_message(
+                    &format!("@ {recipient}"),
+                    DisplayMessage {
+                        sender: "You".to_string(),
+                        content: text,
+                        timestamp: format_timestamp_ms(timestamp_ms),
+                        status: MessageStatus::Sending,
+                        message_id: None,
+                    },
+                );
+            }
+            NetEvent::LanPeerLost { peer_id } => {
+                app.remove_lan_peer(&peer_id);
+            }
             NetEvent::PresenceChanged { peer_id, status } => {
                 let presence = match status.as_str() {
                     "Online" => PresenceStatus::Online,
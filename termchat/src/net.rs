@@ -32,38 +32,139 @@
 //! the task completion, applies exponential backoff with jitter, and attempts
 //! to reconnect. Messages sent during disconnection are queued and drained
 //! after reconnection succeeds.
-
-use std::collections::VecDeque;
+//!
+//! If the relay announced a graceful shutdown ([`RelayShutdown`]) before the
+//! connection dropped, the first reconnect attempt waits at least the
+//! server's `retry_after` delay, or goes straight to its `redirect_url`.
+//!
+//! ## Relay failover
+//!
+//! With `fallback_relays` configured, every relay is scored in a
+//! [`RelayPool`] by connect latency, keepalive round trips, and recent
+//! failures. Each reconnect attempt goes to the healthiest relay, so a relay
+//! that keeps failing rotates out. A health monitor probes the other relays
+//! every `relay_health_interval` and *sweeps* relays this client was
+//! registered on before: it registers there briefly, collects the messages
+//! the relay stored while this client was away, and hands them to the
+//! current connection.
+//!
+//! ## Direct P2P links
+//!
+//! The `ChatManager` sends over a [`HybridTransport`] whose preferred side is
+//! a [`TransportSlot`] holding an optional direct [`QuicTransport`] to the
+//! remote peer, with the relay as fallback. When [`P2pConfig`] is enabled,
+//! `spawn_net` first tries to dial the peer directly, and a P2P task keeps
+//! accepting incoming connections and re-dialing every `upgrade_interval`
+//! while only the relay is available. The relay connection is still required:
+//! it is the supervisor's liveness signal and carries traffic whenever the
+//! direct link is down. Every switch between P2P and relay is reported as a
+//! [`NetEvent::ConnectionStatus`] carrying the active [`TransportType`].
+//!
+//! With `lan_discovery` enabled, the P2P task also announces its listener
+//! over mDNS ([`LanDiscovery`]) and dials the remote peer as soon as it is
+//! seen on the local network.
+//!
+//! ## Feature negotiation
+//!
+//! Every new `ChatManager` sends the remote peer a protocol hello. The peer
+//! answers with its own, and each side then uses the optional features both
+//! support, such as compressing large messages.
+//!
+//! ## Task sync
+//!
+//! Task changes made in the TUI go out as [`NetCommand::SendTaskSync`].
+//! Incoming task sync messages are applied to the [`TaskManager`] shared
+//! with the TUI, checked against the peer the transport received them from,
+//! and answered when they ask for state. The TUI is told to redraw its task
+//! list with [`NetEvent::TasksChanged`], and changes the sender's room role
+//! does not allow are reported as [`NetEvent::TaskChangeRejected`]. While the
+//! supervisor runs, a digest of every task room is also sent every
+//! [`NetConfig::task_sync_interval`] so changes missed while offline are
+//! repaired (see [`spawn_anti_entropy`]).
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use parking_lot::Mutex as SyncMutex;
 use rand::Rng;
 use tokio::sync::{RwLock, mpsc};
 use uuid::Uuid;
 
-use termchat_proto::message::{ConversationId, MessageContent, SenderId};
+use termchat_proto::message::{
+    ChatMessage, ConversationId, MessageContent, MessageId, MessageMetadata, SenderId, Timestamp,
+};
 use termchat_proto::room::RoomMessage;
+use termchat_proto::task::TaskSyncMessage;
 
 use crate::chat::history::InMemoryStore;
-use crate::chat::{ChatEvent, ChatManager};
+use crate::chat::outbox::Outbox;
+use crate::chat::{ChatEvent, ChatManager, SendError};
 use crate::config::ReconnectConfig;
 use crate::crypto::noise::StubNoiseSession;
-use crate::transport::PeerId;
-use crate::transport::relay::RelayTransport;
+use crate::tasks::{PermissionWarning, TaskManager, spawn_anti_entropy};
+use crate::transport::discovery::{DiscoveryEvent, LanDiscovery};
+use crate::transport::hybrid::{HybridTransport, TransportSlot};
+use crate::transport::quic::{QuicListener, QuicTransport};
+use crate::transport::relay::{PunchOffer, RelayConnectOptions, RelayShutdown, RelayTransport};
+use crate::transport::relay_pool::RelayPool;
+use crate::transport::{PeerId, Transport, TransportError, TransportType};
+
+/// Slot holding the optional direct QUIC link to the remote peer.
+///
+/// Lives for the whole session, so a direct link survives relay reconnects.
+type PeerLink = TransportSlot<QuicTransport>;
+
+/// Transport used by the `ChatManager`: direct link first, relay fallback.
+type LinkTransport = HybridTransport<PeerLink, RelayTransport>;
+
+/// The `ChatManager` type driven by the networking tasks.
+type NetChatManager = ChatManager<StubNoiseSession, LinkTransport, InMemoryStore>;
 
 /// Type alias for the shared, swappable `ChatManager`.
 ///
 /// The supervisor writes `Some(...)` after (re)connection and `None` on
 /// disconnect. The command handler reads it for sending messages.
-type SharedChatManager =
-    Arc<RwLock<Option<ChatManager<StubNoiseSession, RelayTransport, InMemoryStore>>>>;
+type SharedChatManager = Arc<RwLock<Option<NetChatManager>>>;
+
+/// Direct-link state that outlives individual relay connections.
+#[derive(Clone)]
+struct DirectLink {
+    /// Slot holding the direct QUIC link, if one is up.
+    slot: PeerLink,
+    /// Where each relay connection delivers hole-punching offers, when
+    /// hole punching is enabled.
+    punch_tx: Option<mpsc::Sender<PunchOffer>>,
+}
+
+/// Capacity of the channel carrying hole-punching offers.
+const PUNCH_OFFER_BUFFER: usize = 8;
+
+/// Health scores for the configured relays, shared by the supervisor and
+/// the health monitor.
+type SharedRelayPool = Arc<SyncMutex<RelayPool>>;
+
+/// Task state shared by the TUI and the networking tasks.
+type SharedTasks = Arc<SyncMutex<TaskManager>>;
+
+/// How long a relay sweep waits for further stored messages before
+/// disconnecting.
+const RELAY_SWEEP_IDLE: Duration = Duration::from_millis(500);
+
+/// Capacity of the channel carrying LAN discovery events.
+const LAN_EVENT_BUFFER: usize = 16;
+
+/// Maximum time an accepted direct connection may take to identify itself.
+const P2P_IDENTIFY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Type alias for the shared offline message queue.
 ///
-/// When the `ChatManager` is `None` (disconnected), the command handler
-/// pushes messages here. The supervisor drains the queue after reconnection.
-type MessageQueue = Arc<tokio::sync::Mutex<VecDeque<String>>>;
+/// When the `ChatManager` is `None` (disconnected) or a send fails, the
+/// command handler pushes the message here. The supervisor drains the queue
+/// after reconnection, and on startup if messages survived a restart.
+type MessageQueue = Arc<tokio::sync::Mutex<Outbox>>;
 
 /// Commands sent from the TUI main loop to the networking background tasks.
 #[derive(Debug)]
@@ -108,6 +209,10 @@ pub enum NetCommand {
         /// The peer to deny.
         peer_id: String,
     },
+    /// Send a task sync message (usually a local task change) to the
+    /// remote peer. Dropped while disconnected; anti-entropy catches the
+    /// peer up later.
+    SendTaskSync(TaskSyncMessage),
     /// Gracefully shut down the networking tasks.
     Shutdown,
 }
@@ -182,12 +287,21 @@ pub enum NetEvent {
         /// The reason for denial.
         reason: String,
     },
+    /// A remote task change was not merged because the sending peer's
+    /// room role does not allow it (see [`TaskManager::set_members`]).
+    ///
+    /// [`TaskManager::set_members`]: crate::tasks::TaskManager::set_members
+    TaskChangeRejected(PermissionWarning),
+    /// A remote task change or task state was merged into the shared
+    /// [`TaskManager`]; the task list should be redrawn from it.
+    TasksChanged,
     /// Connection status update.
     ConnectionStatus {
-        /// Whether currently connected to the relay.
+        /// Whether the remote peer is currently reachable.
         connected: bool,
-        /// Human-readable transport description.
-        transport_type: String,
+        /// Transport currently carrying traffic to the remote peer (or the
+        /// one that was lost, when `connected` is `false`).
+        transport_type: TransportType,
     },
     /// An error occurred in the networking layer.
     Error(String),
@@ -200,6 +314,47 @@ pub enum NetEvent {
     },
     /// All reconnection attempts exhausted.
     ReconnectFailed,
+    /// The relay announced a graceful shutdown before disconnecting.
+    RelayShutdown {
+        /// Human-readable reason given by the relay.
+        reason: String,
+        /// Delay in milliseconds before the next reconnect attempt.
+        retry_after_ms: u64,
+    },
+    /// A `TermChat` peer was found on the local network.
+    LanPeerDiscovered {
+        /// The peer's advertised identity.
+        peer_id: String,
+        /// The peer's preferred direct address.
+        addr: SocketAddr,
+        /// The peer's identity key fingerprint, if advertised.
+        fingerprint: Option<String>,
+    },
+    /// A LAN peer withdrew its advertisement.
+    LanPeerLost {
+        /// The peer's advertised identity.
+        peer_id: String,
+    },
+    /// A message queued before a restart was loaded from the outbox and
+    /// will be resent.
+    QueuedMessageRestored {
+        /// The peer the message is addressed to.
+        recipient: String,
+        /// The message text.
+        text: String,
+        /// When the message was originally written (ms since epoch).
+        timestamp_ms: u64,
+    },
+    /// A fresh round-trip time was measured by the relay keepalive.
+    RelayLatency {
+        /// Round-trip time in milliseconds.
+        rtt_ms: u64,
+    },
+    /// Reconnection landed on a different relay than the one that failed.
+    RelayChanged {
+        /// URL of the relay now in use.
+        url: String,
+    },
 }
 
 /// Configuration for the networking layer.
@@ -207,6 +362,14 @@ pub enum NetEvent {
 pub struct NetConfig {
     /// WebSocket URL of the relay server (e.g., `ws://127.0.0.1:9000/ws`).
     pub relay_url: String,
+    /// Relays to fail over to when the current relay is unreachable.
+    pub fallback_relays: Vec<String>,
+    /// How often alternate relays are health-checked (and relays this
+    /// client has left are swept for stored messages).
+    pub relay_health_interval: Duration,
+    /// How often a digest of every task room is sent to the peer so missed
+    /// task changes are repaired (see [`spawn_anti_entropy`]).
+    pub task_sync_interval: Duration,
     /// Local peer identity string.
     pub local_peer_id: String,
     /// Remote peer identity string (who we're chatting with).
@@ -217,6 +380,64 @@ pub struct NetConfig {
     pub chat_event_buffer: usize,
     /// Reconnection configuration (backoff, retries, queue).
     pub reconnect: ReconnectConfig,
+    /// Relay connection options (timeouts, invite token).
+    pub relay: RelayConnectOptions,
+    /// Direct peer-to-peer (QUIC) settings.
+    pub p2p: P2pConfig,
+    /// File the offline message queue is persisted to (`None` keeps it in
+    /// memory only).
+    pub outbox_path: Option<PathBuf>,
+}
+
+/// Direct peer-to-peer (QUIC) settings.
+///
+/// P2P is disabled unless `listen_addr`, `peer_addr`, `hole_punch`, or
+/// `lan_discovery` is set, and always when the relay is reached through a
+/// proxy.
+#[derive(Debug, Clone)]
+pub struct P2pConfig {
+    /// Local address to accept direct QUIC connections on.
+    pub listen_addr: Option<SocketAddr>,
+    /// The remote peer's direct QUIC address to dial.
+    pub peer_addr: Option<SocketAddr>,
+    /// Timeout for a single direct connection attempt.
+    pub connect_timeout: Duration,
+    /// How often to retry the direct connection while only the relay is up.
+    pub upgrade_interval: Duration,
+    /// Ask the relay to broker NAT hole punching with the remote peer.
+    pub hole_punch: bool,
+    /// How long simultaneous dials may take before settling on the relay.
+    pub punch_timeout: Duration,
+    /// Advertise and browse for peers on the local network via mDNS.
+    pub lan_discovery: bool,
+    /// Identity key fingerprint advertised alongside the LAN announcement.
+    pub fingerprint: Option<String>,
+}
+
+impl Default for P2pConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: None,
+            peer_addr: None,
+            connect_timeout: Duration::from_secs(3),
+            upgrade_interval: Duration::from_secs(30),
+            hole_punch: false,
+            punch_timeout: Duration::from_secs(5),
+            lan_discovery: false,
+            fingerprint: None,
+        }
+    }
+}
+
+impl P2pConfig {
+    /// Returns `true` if direct connections should be attempted or accepted.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.listen_addr.is_some()
+            || self.peer_addr.is_some()
+            || self.hole_punch
+            || self.lan_discovery
+    }
 }
 
 /// Default channel capacity for commands and events.
@@ -225,17 +446,38 @@ const DEFAULT_CHANNEL_CAPACITY: usize = 256;
 /// Default channel capacity for `ChatManager` internal events.
 const DEFAULT_CHAT_EVENT_BUFFER: usize = 64;
 
+/// Default interval between relay health checks.
+const DEFAULT_RELAY_HEALTH_INTERVAL: Duration = Duration::from_mins(1);
+
+/// Default interval between task anti-entropy digests.
+const DEFAULT_TASK_SYNC_INTERVAL: Duration = Duration::from_mins(1);
+
 impl NetConfig {
+    /// All configured relays: the primary first, then the fallbacks.
+    #[must_use]
+    pub fn relay_urls(&self) -> Vec<String> {
+        std::iter::once(&self.relay_url)
+            .chain(&self.fallback_relays)
+            .cloned()
+            .collect()
+    }
+
     /// Creates a `NetConfig` with default channel capacities and reconnect config.
     #[must_use]
     pub fn new(relay_url: String, local_peer_id: String, remote_peer_id: String) -> Self {
         Self {
             relay_url,
+            fallback_relays: Vec::new(),
+            relay_health_interval: DEFAULT_RELAY_HEALTH_INTERVAL,
+            task_sync_interval: DEFAULT_TASK_SYNC_INTERVAL,
             local_peer_id,
             remote_peer_id,
             channel_capacity: DEFAULT_CHANNEL_CAPACITY,
             chat_event_buffer: DEFAULT_CHAT_EVENT_BUFFER,
             reconnect: ReconnectConfig::default(),
+            relay: RelayConnectOptions::default(),
+            p2p: P2pConfig::default(),
+            outbox_path: None,
         }
     }
 }
@@ -261,41 +503,110 @@ impl NetConfig {
 pub async fn spawn_net(
     config: NetConfig,
 ) -> Result<(mpsc::Sender<NetCommand>, mpsc::Receiver<NetEvent>), String> {
-    // Initial connection.
-    let transport = RelayTransport::connect(&config.relay_url, PeerId::new(&config.local_peer_id))
-        .await
-        .map_err(|e| format!("relay connection failed: {e}"))?;
+    let tasks = TaskManager::new(config.local_peer_id.clone());
+    spawn_net_with_tasks(config, Arc::new(SyncMutex::new(tasks))).await
+}
 
-    // Create the initial ChatManager.
-    let crypto = StubNoiseSession::new(true);
-    let sender_id = SenderId::new(config.local_peer_id.as_bytes().to_vec());
+/// Like [`spawn_net`], syncing `tasks` with the remote peer.
+///
+/// Incoming task sync messages are applied to `tasks`, which the caller
+/// shares with the TUI, so remote changes show up there directly.
+///
+/// # Errors
+///
+/// Same as [`spawn_net`].
+#[allow(clippy::too_many_lines)]
+pub async fn spawn_net_with_tasks(
+    mut config: NetConfig,
+    tasks: Arc<SyncMutex<TaskManager>>,
+) -> Result<(mpsc::Sender<NetCommand>, mpsc::Receiver<NetEvent>), String> {
+    apply_proxy_policy(&mut config);
+    let local_peer = PeerId::new(&config.local_peer_id);
     let remote_peer = PeerId::new(&config.remote_peer_id);
 
-    let (chat_mgr, chat_event_rx) =
-        ChatManager::<StubNoiseSession, RelayTransport, InMemoryStore>::new(
-            crypto,
-            transport,
-            sender_id,
-            remote_peer.clone(),
-            config.chat_event_buffer,
-        );
+    // Try the direct path first; the relay is connected either way.
+    let (punch_tx, punch_rx) = if config.p2p.hole_punch {
+        let (tx, rx) = mpsc::channel(PUNCH_OFFER_BUFFER);
+        (Some(tx), Some(rx))
+    } else {
+        (None, None)
+    };
+    let direct = DirectLink {
+        slot: PeerLink::new(TransportType::P2p, config.channel_capacity),
+        punch_tx,
+    };
+    if let Some(addr) = config.p2p.peer_addr {
+        dial_peer(
+            addr,
+            &local_peer,
+            &remote_peer,
+            &direct.slot,
+            config.p2p.connect_timeout,
+        )
+        .await;
+    }
+
+    // Initial connection, to the first reachable relay.
+    let relay_pool: SharedRelayPool = Arc::new(SyncMutex::new(RelayPool::new(config.relay_urls())));
+    let transport = connect_first_relay(&config, &local_peer, &relay_pool).await?;
+
+    // Create the initial ChatManager.
+    let (chat_mgr, chat_event_rx) = new_chat_manager(&config, &direct, transport);
+    send_hello(&chat_mgr).await;
+    let initial_transport = active_transport(&chat_mgr, &remote_peer);
 
     // Shared state for the supervisor pattern.
     let shared_mgr: SharedChatManager = Arc::new(RwLock::new(Some(chat_mgr)));
-    let message_queue: MessageQueue = Arc::new(tokio::sync::Mutex::new(VecDeque::new()));
+    let outbox = open_outbox(&config);
+    let restored: Vec<NetEvent> = outbox
+        .messages()
+        .map(|message| NetEvent::QueuedMessageRestored {
+            recipient: config.remote_peer_id.clone(),
+            text: message_text(message).to_string(),
+            timestamp_ms: message.metadata.timestamp.as_millis(),
+        })
+        .collect();
+    let message_queue: MessageQueue = Arc::new(tokio::sync::Mutex::new(outbox));
     let shutdown_flag = Arc::new(AtomicBool::new(false));
 
     // Create the command/event channels for TUI communication.
     let (cmd_tx, cmd_rx) = mpsc::channel::<NetCommand>(config.channel_capacity);
     let (evt_tx, evt_rx) = mpsc::channel::<NetEvent>(config.channel_capacity);
 
-    // Send initial connection status.
+    // Show messages that survived a restart, then report the connection
+    // and resend them.
+    for event in restored {
+        let _ = evt_tx.send(event).await;
+    }
     let _ = evt_tx
         .send(NetEvent::ConnectionStatus {
             connected: true,
-            transport_type: "Relay".to_string(),
+            transport_type: initial_transport,
         })
         .await;
+    drain_message_queue(&shared_mgr, &message_queue, &evt_tx).await;
+
+    // Keep the direct link alive (accept, re-dial, report switches).
+    if config.p2p.is_enabled() {
+        let p2p = config.p2p.clone();
+        let p2p_link = direct.slot.clone();
+        let p2p_mgr = Arc::clone(&shared_mgr);
+        let p2p_evt_tx = evt_tx.clone();
+        let p2p_shutdown = Arc::clone(&shutdown_flag);
+        tokio::spawn(async move {
+            p2p_supervisor(
+                p2p,
+                local_peer,
+                remote_peer,
+                p2p_link,
+                punch_rx,
+                p2p_mgr,
+                p2p_evt_tx,
+                p2p_shutdown,
+            )
+            .await;
+        });
+    }
 
     // Spawn the command handler (persists across reconnects).
     let cmd_mgr = Arc::clone(&shared_mgr);
@@ -303,7 +614,6 @@ pub async fn spawn_net(
     let cmd_queue = Arc::clone(&message_queue);
     let cmd_shutdown = Arc::clone(&shutdown_flag);
     let conversation = ConversationId::new();
-    let queue_cap = config.reconnect.message_queue_cap;
     let local_peer_id_clone = config.local_peer_id.clone();
     tokio::spawn(async move {
         command_handler(
@@ -313,12 +623,32 @@ pub async fn spawn_net(
             conversation,
             cmd_queue,
             cmd_shutdown,
-            queue_cap,
             local_peer_id_clone,
         )
         .await;
     });
 
+    // Health-check alternate relays and sweep the ones we left.
+    if !config.fallback_relays.is_empty() {
+        let monitor_mgr = Arc::clone(&shared_mgr);
+        let monitor_pool = Arc::clone(&relay_pool);
+        let monitor_shutdown = Arc::clone(&shutdown_flag);
+        let local = PeerId::new(&config.local_peer_id);
+        let options = config.relay.clone();
+        let interval = config.relay_health_interval;
+        tokio::spawn(async move {
+            relay_health_monitor(
+                local,
+                options,
+                interval,
+                monitor_mgr,
+                monitor_pool,
+                monitor_shutdown,
+            )
+            .await;
+        });
+    }
+
     // Spawn the supervisor (owns reconnect lifecycle).
     let sup_mgr = Arc::clone(&shared_mgr);
     let sup_evt_tx = evt_tx;
@@ -328,9 +658,12 @@ pub async fn spawn_net(
         supervisor(
             config,
             sup_mgr,
+            direct,
             chat_event_rx,
             sup_evt_tx,
             sup_queue,
+            relay_pool,
+            tasks,
             sup_shutdown,
         )
         .await;
@@ -343,18 +676,41 @@ pub async fn spawn_net(
 ///
 /// After the initial connection, spawns the receive loop and chat event
 /// forwarder. When the receive loop exits (connection dropped), the
-/// supervisor attempts reconnection with exponential backoff and jitter.
+/// supervisor counts a failure against the relay in `relay_pool` and
+/// attempts reconnection with exponential backoff and jitter. Task
+/// anti-entropy runs for the supervisor's whole lifetime.
+#[allow(clippy::too_many_arguments)]
 async fn supervisor(
     config: NetConfig,
     shared_mgr: SharedChatManager,
+    direct: DirectLink,
     initial_chat_event_rx: mpsc::Receiver<ChatEvent>,
     evt_tx: mpsc::Sender<NetEvent>,
     message_queue: MessageQueue,
+    relay_pool: SharedRelayPool,
+    tasks: SharedTasks,
     shutdown_flag: Arc<AtomicBool>,
 ) {
     let mut chat_event_rx = initial_chat_event_rx;
     let mut last_connected_at: Option<Instant> = Some(Instant::now());
 
+    // Reconcile task rooms with the peer for as long as the supervisor
+    // runs; digests due while disconnected are skipped.
+    let sync_mgr = Arc::clone(&shared_mgr);
+    let anti_entropy = spawn_anti_entropy(
+        Arc::clone(&tasks),
+        config.task_sync_interval,
+        move |digest| {
+            let sync_mgr = Arc::clone(&sync_mgr);
+            async move {
+                let mgr_guard = sync_mgr.read().await;
+                if let Some(ref mgr) = *mgr_guard {
+                    mgr.send_task_sync(&digest).await;
+                }
+            }
+        },
+    );
+
     loop {
         // Spawn the receive loop for the current connection.
         let recv_mgr = Arc::clone(&shared_mgr);
@@ -365,12 +721,27 @@ async fn supervisor(
 
         // Spawn the chat event forwarder for the current connection.
         let fwd_evt_tx = evt_tx.clone();
+        let fwd_tasks = Arc::clone(&tasks);
+        let fwd_mgr = Arc::clone(&shared_mgr);
         let fwd_handle = tokio::spawn(async move {
-            chat_event_forwarder(chat_event_rx, fwd_evt_tx).await;
+            chat_event_forwarder(chat_event_rx, fwd_evt_tx, fwd_tasks, fwd_mgr).await;
+        });
+
+        // Report keepalive latency while this connection is up.
+        let latency_handle = (!config.relay.keepalive_interval.is_zero()).then(|| {
+            tokio::spawn(latency_reporter(
+                Arc::clone(&shared_mgr),
+                evt_tx.clone(),
+                config.relay.keepalive_interval,
+            ))
         });
 
-        // Wait for the receive loop to finish (connection dropped).
+        // Wait for the receive loop to finish (connection dropped, or the
+        // relay stopped answering keepalives).
         let _ = recv_handle.await;
+        if let Some(handle) = latency_handle {
+            handle.abort();
+        }
 
         // Check for shutdown.
         if shutdown_flag.load(Ordering::Relaxed) {
@@ -378,17 +749,33 @@ async fn supervisor(
             break;
         }
 
-        // Mark the ChatManager as disconnected.
-        {
+        // Mark the ChatManager as disconnected, keeping any drain notice the
+        // relay sent before it went away.
+        let (relay_shutdown, dropped_url) = {
             let mut mgr = shared_mgr.write().await;
-            *mgr = None;
+            mgr.take().map_or((None, None), |m| {
+                let relay = m.transport().fallback();
+                (relay.shutdown_notice(), Some(relay.relay_url().to_string()))
+            })
+        };
+        if let Some(url) = &dropped_url {
+            relay_pool.lock().record_failure(url);
+        }
+        if let Some(ref notice) = relay_shutdown {
+            let _ = evt_tx
+                .send(NetEvent::RelayShutdown {
+                    reason: notice.reason.clone(),
+                    retry_after_ms: u64::try_from(notice.retry_after.as_millis())
+                        .unwrap_or(u64::MAX),
+                })
+                .await;
         }
 
         // Send disconnection status.
         let _ = evt_tx
             .send(NetEvent::ConnectionStatus {
                 connected: false,
-                transport_type: "Relay".to_string(),
+                transport_type: TransportType::Relay,
             })
             .await;
 
@@ -399,10 +786,14 @@ async fn supervisor(
         let reconnect_result = reconnect_with_backoff(
             &config,
             &shared_mgr,
+            &direct,
             &evt_tx,
             &message_queue,
+            &relay_pool,
             &shutdown_flag,
             &mut last_connected_at,
+            relay_shutdown,
+            dropped_url.as_deref(),
         )
         .await;
 
@@ -418,22 +809,83 @@ async fn supervisor(
             break;
         }
     }
+    anti_entropy.abort();
+}
+
+/// Emit [`NetEvent::RelayLatency`] whenever the relay keepalive measures a
+/// new round-trip time.
+///
+/// Polls once per keepalive `interval`; runs until aborted by the supervisor.
+async fn latency_reporter(
+    shared_mgr: SharedChatManager,
+    evt_tx: mpsc::Sender<NetEvent>,
+    interval: Duration,
+) {
+    let mut last = None;
+    loop {
+        tokio::time::sleep(interval).await;
+        let latency = shared_mgr
+            .read()
+            .await
+            .as_ref()
+            .and_then(|mgr| mgr.transport().fallback().latency());
+        if latency.is_some() && latency != last {
+            last = latency;
+            let rtt_ms =
+                latency.map_or(0, |rtt| u64::try_from(rtt.as_millis()).unwrap_or(u64::MAX));
+            if evt_tx
+                .send(NetEvent::RelayLatency { rtt_ms })
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
 }
 
 /// Attempt reconnection with exponential backoff and jitter.
 ///
+/// Each attempt targets the healthiest relay in `relay_pool`; attempts are
+/// scored as they succeed or fail, so a dead relay rotates out in favour of
+/// the next one. Landing on a relay other than `previous_url` is reported as
+/// [`NetEvent::RelayChanged`].
+///
+/// If the relay sent a drain notice (`relay_shutdown`), or refuses a
+/// reconnect attempt because it is draining, the next attempt waits at least
+/// the advertised `retry_after` delay — or targets the advertised
+/// `redirect_url` immediately — instead of hammering the departing relay.
+///
 /// Returns `Some(chat_event_rx)` on success, `None` if all attempts fail
 /// or shutdown is requested.
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 async fn reconnect_with_backoff(
     config: &NetConfig,
     shared_mgr: &SharedChatManager,
+    direct: &DirectLink,
     evt_tx: &mpsc::Sender<NetEvent>,
     message_queue: &MessageQueue,
+    relay_pool: &SharedRelayPool,
     shutdown_flag: &Arc<AtomicBool>,
     last_connected_at: &mut Option<Instant>,
+    relay_shutdown: Option<RelayShutdown>,
+    previous_url: Option<&str>,
 ) -> Option<mpsc::Receiver<ChatEvent>> {
     let reconnect = &config.reconnect;
 
+    // Drain hint from the relay: a minimum delay for the next attempt and
+    // possibly a different relay to try.
+    let mut redirect = None;
+    let mut min_delay = Duration::ZERO;
+    if let Some(notice) = relay_shutdown {
+        apply_drain_hint(
+            &mut redirect,
+            &mut min_delay,
+            notice.retry_after,
+            notice.redirect_url,
+        );
+    }
+
     // Flap detection: if we were connected for less than the stability
     // threshold, don't reset the backoff counter (the connection was unstable).
     // Since this is the first reconnect cycle after a drop, we start from 0
@@ -459,12 +911,13 @@ async fn reconnect_with_backoff(
         let jitter_range = capped_delay.as_millis() / 4;
         let jitter = if jitter_range > 0 {
             let jitter_ms = rand::rng().random_range(0..=jitter_range);
-            std::time::Duration::from_millis(u64::try_from(jitter_ms).unwrap_or(0))
+            Duration::from_millis(u64::try_from(jitter_ms).unwrap_or(0))
         } else {
-            std::time::Duration::ZERO
+            Duration::ZERO
         };
 
-        let total_delay = capped_delay + jitter;
+        let total_delay = std::cmp::max(capped_delay + jitter, min_delay);
+        min_delay = Duration::ZERO;
         let delay_ms = u64::try_from(total_delay.as_millis()).unwrap_or(u64::MAX);
         tracing::info!(
             attempt = attempt + 1,
@@ -487,24 +940,37 @@ async fn reconnect_with_backoff(
             })
             .await;
 
-        // Try to connect.
-        match RelayTransport::connect(&config.relay_url, PeerId::new(&config.local_peer_id)).await {
+        // Try to connect, to the redirect target or else the healthiest relay.
+        let best = relay_pool.lock().best().map(str::to_string);
+        let relay_url = redirect
+            .take()
+            .or(best)
+            .unwrap_or_else(|| config.relay_url.clone());
+        let started = Instant::now();
+        match RelayTransport::connect_with_options(
+            &relay_url,
+            PeerId::new(&config.local_peer_id),
+            &config.relay,
+        )
+        .await
+        {
             Ok(transport) => {
-                tracing::info!(attempt = attempt + 1, "reconnected to relay successfully");
-
-                // Create a new ChatManager.
-                let crypto = StubNoiseSession::new(true);
-                let sender_id = SenderId::new(config.local_peer_id.as_bytes().to_vec());
-                let remote_peer = PeerId::new(&config.remote_peer_id);
-
-                let (new_mgr, new_chat_event_rx) =
-                    ChatManager::<StubNoiseSession, RelayTransport, InMemoryStore>::new(
-                        crypto,
-                        transport,
-                        sender_id,
-                        remote_peer,
-                        config.chat_event_buffer,
-                    );
+                tracing::info!(
+                    attempt = attempt + 1,
+                    url = %relay_url,
+                    "reconnected to relay successfully"
+                );
+                {
+                    let mut pool = relay_pool.lock();
+                    pool.record_success(&relay_url, started.elapsed());
+                    pool.mark_registered(&relay_url);
+                }
+
+                // Create a new ChatManager, keeping the direct link (if any).
+                let (new_mgr, new_chat_event_rx) = new_chat_manager(config, direct, transport);
+                send_hello(&new_mgr).await;
+                let transport_type =
+                    active_transport(&new_mgr, &PeerId::new(&config.remote_peer_id));
 
                 // Swap in the new ChatManager.
                 {
@@ -519,22 +985,39 @@ async fn reconnect_with_backoff(
                 *last_connected_at = Some(Instant::now());
 
                 // Send reconnected status.
+                if previous_url != Some(relay_url.as_str()) {
+                    let _ = evt_tx.send(NetEvent::RelayChanged { url: relay_url }).await;
+                }
                 let _ = evt_tx
                     .send(NetEvent::ConnectionStatus {
                         connected: true,
-                        transport_type: "Relay".to_string(),
+                        transport_type,
                     })
                     .await;
 
                 return Some(new_chat_event_rx);
             }
+            Err(TransportError::Draining {
+                retry_after,
+                redirect_url,
+            }) => {
+                tracing::warn!(
+                    attempt = attempt + 1,
+                    retry_after_ms = u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX),
+                    "relay is draining, deferring next attempt"
+                );
+                relay_pool.lock().record_failure(&relay_url);
+                apply_drain_hint(&mut redirect, &mut min_delay, retry_after, redirect_url);
+            }
             Err(e) => {
                 tracing::warn!(
                     attempt = attempt + 1,
                     max_attempts = reconnect.max_attempts,
+                    url = %relay_url,
                     error = %e,
                     "reconnect attempt failed"
                 );
+                relay_pool.lock().record_failure(&relay_url);
             }
         }
     }
@@ -546,43 +1029,744 @@ async fn reconnect_with_backoff(
     None
 }
 
+/// Disable direct paths when the relay is reached through a proxy.
+///
+/// A direct QUIC link or an mDNS announcement would reveal this host's
+/// address to the peer and the local network, defeating the proxy.
+fn apply_proxy_policy(config: &mut NetConfig) {
+    if config.relay.proxy.is_some() && config.p2p.is_enabled() {
+        tracing::warn!("relay proxy configured, disabling direct P2P and LAN discovery");
+        config.p2p = P2pConfig::default();
+    }
+}
+
+/// Connect to the first reachable relay, trying the pool's relays in rank
+/// order.
+///
+/// # Errors
+///
+/// Returns an error string (from the last attempt) if no relay accepts the
+/// connection and registration.
+#[allow(clippy::significant_drop_tightening)]
+async fn connect_first_relay(
+    config: &NetConfig,
+    local: &PeerId,
+    relay_pool: &SharedRelayPool,
+) -> Result<RelayTransport, String> {
+    let candidates: Vec<String> = relay_pool
+        .lock()
+        .ranked()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut last_error = String::from("no relay configured");
+    for url in candidates {
+        let started = Instant::now();
+        match RelayTransport::connect_with_options(&url, local.clone(), &config.relay).await {
+            Ok(transport) => {
+                let mut pool = relay_pool.lock();
+                pool.record_success(&url, started.elapsed());
+                pool.mark_registered(&url);
+                return Ok(transport);
+            }
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "relay unavailable");
+                relay_pool.lock().record_failure(&url);
+                last_error = e.to_string();
+            }
+        }
+    }
+    Err(format!("relay connection failed: {last_error}"))
+}
+
+/// Periodically health-check the relays other than the active one.
+///
+/// Relays this client has registered on before are *swept* (see
+/// [`sweep_relay`]) so messages they stored while it was away are
+/// delivered; the rest are only probed. Results feed `relay_pool`, along
+/// with the active relay's keepalive latency. Runs until shutdown.
+#[allow(clippy::significant_drop_tightening)]
+async fn relay_health_monitor(
+    local: PeerId,
+    options: RelayConnectOptions,
+    interval: Duration,
+    shared_mgr: SharedChatManager,
+    relay_pool: SharedRelayPool,
+    shutdown_flag: Arc<AtomicBool>,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+        if shutdown_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        // Skip the round while the supervisor is reconnecting.
+        let Some(active) = current_relay(&shared_mgr).await else {
+            continue;
+        };
+        let (sweep, probe) = {
+            let mut pool = relay_pool.lock();
+            if let Some(rtt) = active.1 {
+                pool.record_latency(&active.0, rtt);
+            }
+            let sweep = pool.registered_except(&active.0);
+            let probe: Vec<String> = pool
+                .ranked()
+                .into_iter()
+                .filter(|url| *url != active.0 && !sweep.iter().any(|s| s == url))
+                .map(str::to_string)
+                .collect();
+            (sweep, probe)
+        };
+
+        for url in probe {
+            let result = RelayTransport::probe(&url, &options).await;
+            let mut pool = relay_pool.lock();
+            match result {
+                Ok(rtt) => pool.record_success(&url, rtt),
+                Err(e) => {
+                    tracing::debug!(url = %url, error = %e, "relay health check failed");
+                    pool.record_failure(&url);
+                }
+            }
+        }
+
+        for url in sweep {
+            // Never register a second connection on the relay in use.
+            if current_relay(&shared_mgr)
+                .await
+                .is_none_or(|(active, _)| active == url)
+            {
+                continue;
+            }
+            match sweep_relay(&url, &local, &options).await {
+                Ok((rtt, payloads)) => {
+                    relay_pool.lock().record_success(&url, rtt);
+                    if !payloads.is_empty() {
+                        tracing::info!(
+                            url = %url,
+                            count = payloads.len(),
+                            "collected stored messages from previous relay"
+                        );
+                        deliver_swept(&shared_mgr, payloads, &shutdown_flag).await;
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!(url = %url, error = %e, "relay sweep failed");
+                    relay_pool.lock().record_failure(&url);
+                }
+            }
+        }
+    }
+}
+
+/// URL and keepalive latency of the relay the `ChatManager` is using.
+async fn current_relay(shared_mgr: &SharedChatManager) -> Option<(String, Option<Duration>)> {
+    shared_mgr.read().await.as_ref().map(|mgr| {
+        let relay = mgr.transport().fallback();
+        (relay.relay_url().to_string(), relay.latency())
+    })
+}
+
+/// Register on a relay this client has moved away from, collect the
+/// messages it stored for this peer, and disconnect again.
+///
+/// Returns the connect latency and the collected `(sender, payload)` pairs.
+async fn sweep_relay(
+    url: &str,
+    local: &PeerId,
+    options: &RelayConnectOptions,
+) -> Result<(Duration, Vec<(PeerId, Vec<u8>)>), TransportError> {
+    let options = RelayConnectOptions {
+        keepalive_interval: Duration::ZERO,
+        ..options.clone()
+    };
+    let started = Instant::now();
+    let transport = RelayTransport::connect_with_options(url, local.clone(), &options).await?;
+    let rtt = started.elapsed();
+
+    // The relay flushes stored messages right after registration.
+    let mut payloads = Vec::new();
+    while let Ok(Ok(item)) = tokio::time::timeout(RELAY_SWEEP_IDLE, transport.recv()).await {
+        payloads.push(item);
+    }
+    // Anything routed here before the relay processes the close is still
+    // read, so nothing is lost on the way out.
+    transport.close().await;
+    while let Ok(Ok(item)) = tokio::time::timeout(RELAY_SWEEP_IDLE, transport.recv()).await {
+        payloads.push(item);
+    }
+    Ok((rtt, payloads))
+}
+
+/// Hand swept payloads to the current relay connection's receive path,
+/// waiting out any reconnect in progress.
+async fn deliver_swept(
+    shared_mgr: &SharedChatManager,
+    payloads: Vec<(PeerId, Vec<u8>)>,
+    shutdown_flag: &AtomicBool,
+) {
+    for (from, payload) in payloads {
+        loop {
+            let delivered = {
+                let mgr = shared_mgr.read().await;
+                match mgr.as_ref() {
+                    Some(mgr) => tokio::time::timeout(
+                        RELAY_SWEEP_IDLE,
+                        mgr.transport()
+                            .fallback()
+                            .inject_incoming(from.clone(), payload.clone()),
+                    )
+                    .await
+                    .is_ok_and(|result| result.is_ok()),
+                    None => false,
+                }
+            };
+            if delivered || shutdown_flag.load(Ordering::Relaxed) {
+                break;
+            }
+            tokio::time::sleep(RELAY_SWEEP_IDLE).await;
+        }
+    }
+}
+
+/// Build a `ChatManager` over a fresh relay connection and the session's
+/// direct peer link.
+fn new_chat_manager(
+    config: &NetConfig,
+    direct: &DirectLink,
+    relay: RelayTransport,
+) -> (NetChatManager, mpsc::Receiver<ChatEvent>) {
+    if let Some(punch_tx) = &direct.punch_tx {
+        relay.set_punch_handler(punch_tx.clone());
+    }
+    NetChatManager::new(
+        StubNoiseSession::new(true),
+        HybridTransport::new(direct.slot.clone(), relay),
+        SenderId::new(config.local_peer_id.as_bytes().to_vec()),
+        PeerId::new(&config.remote_peer_id),
+        config.chat_event_buffer,
+    )
+}
+
+/// Announce our protocol features to the remote peer.
+///
+/// The peer's answer is handled by the receive loop and turns on the
+/// features both sides support, such as compression. If the peer is
+/// offline the relay queues the hello until it registers.
+async fn send_hello(mgr: &NetChatManager) {
+    if let Err(e) = mgr.send_hello().await {
+        tracing::debug!(error = %e, "failed to send protocol hello");
+    }
+}
+
+/// The transport currently carrying traffic to `remote`.
+///
+/// Falls back to reporting the relay when neither side claims the peer,
+/// matching where `HybridTransport` would send.
+fn active_transport(mgr: &NetChatManager, remote: &PeerId) -> TransportType {
+    mgr.transport()
+        .active_type(remote)
+        .unwrap_or(TransportType::Relay)
+}
+
+/// P2P task: keeps the direct link to the remote peer alive.
+///
+/// Accepts incoming direct connections (if a listener is bound), re-dials
+/// `peer_addr` every `upgrade_interval` while no direct link is up, and
+/// emits a [`NetEvent::ConnectionStatus`] whenever traffic switches between
+/// the direct link and the relay.
+///
+/// With hole punching enabled, the task also asks the relay to broker a
+/// direct connection on every upgrade tick and answers the remote peer's
+/// offers (see [`dial_candidates`]).
+///
+/// With LAN discovery enabled, the listener is advertised via mDNS and a
+/// discovered remote peer is dialed directly as soon as it appears, so a
+/// LAN path is preferred over waiting for the next upgrade tick. Every
+/// discovered peer is reported as a [`NetEvent::LanPeerDiscovered`].
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+async fn p2p_supervisor(
+    p2p: P2pConfig,
+    local_peer: PeerId,
+    remote_peer: PeerId,
+    peer_link: PeerLink,
+    mut punch_rx: Option<mpsc::Receiver<PunchOffer>>,
+    shared_mgr: SharedChatManager,
+    evt_tx: mpsc::Sender<NetEvent>,
+    shutdown_flag: Arc<AtomicBool>,
+) {
+    // Hole punching dials from the listening socket and LAN discovery
+    // advertises it, so both need one even when no listen address is
+    // configured.
+    let bind_addr = p2p.listen_addr.or_else(|| {
+        (p2p.hole_punch || p2p.lan_discovery).then(|| SocketAddr::from(([0, 0, 0, 0], 0)))
+    });
+    let listener = bind_addr.and_then(|addr| match QuicListener::bind(addr, local_peer.clone()) {
+        Ok(listener) => {
+            tracing::info!(addr = %addr, "listening for direct P2P connections");
+            Some(Arc::new(listener))
+        }
+        Err(e) => {
+            tracing::warn!(addr = %addr, error = %e, "could not listen for P2P connections");
+            None
+        }
+    });
+    let accept_handle = listener.as_ref().map(|listener| {
+        let listener = Arc::clone(listener);
+        let local = local_peer.clone();
+        let remote = remote_peer.clone();
+        let link = peer_link.clone();
+        tokio::spawn(async move {
+            accept_direct(&listener, &local, &remote, &link).await;
+        })
+    });
+    let (_lan, mut lan_rx) = match &listener {
+        Some(listener) if p2p.lan_discovery => start_lan_discovery(listener, &local_peer, &p2p),
+        _ => (None, None),
+    };
+    let lan_listener = listener.clone().filter(|_| p2p.lan_discovery);
+    let punch_listener = listener.filter(|_| p2p.hole_punch);
+
+    let mut link_changes = peer_link.subscribe();
+    let mut upgrade_tick = tokio::time::interval(p2p.upgrade_interval);
+    // The first tick fires immediately. spawn_net already dialed
+    // `peer_addr`, but no punch has been requested yet.
+    upgrade_tick.tick().await;
+    let requested = match &punch_listener {
+        Some(listener) => request_punch(&shared_mgr, &remote_peer, listener).await,
+        None => false,
+    };
+    let mut last_punch_request = requested.then(Instant::now);
+    let mut was_direct = peer_link.is_connected(&remote_peer);
+
+    loop {
+        tokio::select! {
+            _ = upgrade_tick.tick() => {
+                if !peer_link.is_connected(&remote_peer) {
+                    if let Some(addr) = p2p.peer_addr {
+                        dial_peer(addr, &local_peer, &remote_peer, &peer_link, p2p.connect_timeout)
+                            .await;
+                    }
+                    if let Some(listener) = &punch_listener
+                        && !peer_link.is_connected(&remote_peer)
+                        && request_punch(&shared_mgr, &remote_peer, listener).await
+                    {
+                        last_punch_request = Some(Instant::now());
+                    }
+                }
+            }
+            Some(offer) = async {
+                match punch_rx.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                if let Some(listener) = &punch_listener
+                    && offer.from == remote_peer
+                    && !peer_link.is_connected(&remote_peer)
+                {
+                    // Answer with our own candidates unless this offer is the
+                    // answer to our request, then dial at the same time as
+                    // the remote peer.
+                    let answered = last_punch_request
+                        .is_some_and(|at: Instant| at.elapsed() < p2p.punch_timeout);
+                    if !answered && request_punch(&shared_mgr, &remote_peer, listener).await {
+                        last_punch_request = Some(Instant::now());
+                    }
+                    if !dial_candidates(
+                        listener,
+                        &offer.addrs,
+                        &local_peer,
+                        &remote_peer,
+                        &peer_link,
+                        p2p.punch_timeout,
+                    )
+                    .await
+                    {
+                        tracing::info!("hole punching failed, staying on relay");
+                    }
+                }
+            }
+            Some(event) = async {
+                match lan_rx.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => match event {
+                DiscoveryEvent::Discovered(peer) => {
+                    let Some(&addr) = peer.addrs.first() else {
+                        continue;
+                    };
+                    tracing::debug!(peer = %peer.peer_id, addr = %addr, "LAN peer discovered");
+                    if let Some(listener) = &lan_listener
+                        && peer.peer_id == remote_peer
+                        && !peer_link.is_connected(&remote_peer)
+                        && dial_candidates(
+                            listener,
+                            &peer.addrs,
+                            &local_peer,
+                            &remote_peer,
+                            &peer_link,
+                            p2p.connect_timeout,
+                        )
+                        .await
+                    {
+                        tracing::info!(addr = %addr, "connected to peer over LAN");
+                    }
+                    let _ = evt_tx
+                        .send(NetEvent::LanPeerDiscovered {
+                            peer_id: peer.peer_id.to_string(),
+                            addr,
+                            fingerprint: peer.fingerprint,
+                        })
+                        .await;
+                }
+                DiscoveryEvent::Lost(peer_id) => {
+                    let _ = evt_tx
+                        .send(NetEvent::LanPeerLost {
+                            peer_id: peer_id.to_string(),
+                        })
+                        .await;
+                }
+            },
+            changed = link_changes.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+        if shutdown_flag.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let direct = peer_link.is_connected(&remote_peer);
+        if direct != was_direct {
+            was_direct = direct;
+            // While the relay is reconnecting the supervisor owns status
+            // reporting; only report switches on a live ChatManager.
+            let transport_type = shared_mgr
+                .read()
+                .await
+                .as_ref()
+                .map(|mgr| active_transport(mgr, &remote_peer));
+            if let Some(transport_type) = transport_type {
+                tracing::info!(transport = %transport_type, "active transport changed");
+                let _ = evt_tx
+                    .send(NetEvent::ConnectionStatus {
+                        connected: true,
+                        transport_type,
+                    })
+                    .await;
+            }
+        }
+    }
+
+    if let Some(handle) = accept_handle {
+        handle.abort();
+    }
+}
+
+/// Ask the relay to send our direct-connection candidates to `remote_peer`.
+///
+/// Returns `false` if there is no live relay connection, the relay does not
+/// broker hole punching, or the request could not be sent.
+#[allow(clippy::significant_drop_tightening)]
+async fn request_punch(
+    shared_mgr: &SharedChatManager,
+    remote_peer: &PeerId,
+    listener: &QuicListener,
+) -> bool {
+    let Ok(local_addr) = listener.local_addr() else {
+        return false;
+    };
+    // A concrete bind address is a useful candidate on its own (same LAN);
+    // the relay adds the public address it observes.
+    let local_addrs = if local_addr.ip().is_unspecified() {
+        Vec::new()
+    } else {
+        vec![local_addr]
+    };
+
+    let result = {
+        let mgr = shared_mgr.read().await;
+        let Some(relay) = mgr.as_ref().map(|m| m.transport().fallback()) else {
+            return false;
+        };
+        if !relay.supports_hole_punch() {
+            tracing::debug!("relay does not broker hole punching");
+            return false;
+        }
+        relay
+            .request_punch(remote_peer, local_addr.port(), local_addrs)
+            .await
+    };
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!(error = %e, "punch request failed");
+            false
+        }
+    }
+}
+
+/// Start advertising `listener` on the LAN and browsing for other peers.
+///
+/// Returns `(None, None)` if mDNS is unavailable; direct links then rely on
+/// configured addresses and hole punching.
+fn start_lan_discovery(
+    listener: &QuicListener,
+    local_peer: &PeerId,
+    p2p: &P2pConfig,
+) -> (Option<LanDiscovery>, Option<mpsc::Receiver<DiscoveryEvent>>) {
+    let started = listener.local_addr().and_then(|addr| {
+        LanDiscovery::start(
+            local_peer,
+            addr.port(),
+            p2p.fingerprint.as_deref(),
+            LAN_EVENT_BUFFER,
+        )
+    });
+    match started {
+        Ok((discovery, rx)) => (Some(discovery), Some(rx)),
+        Err(e) => {
+            tracing::warn!(error = %e, "LAN discovery unavailable");
+            (None, None)
+        }
+    }
+}
+
+/// Dial every candidate address from the listening socket at once.
+///
+/// For hole punching, the remote peer dials our candidates at the same
+/// time, so each NAT sees outbound packets before the other side's
+/// handshake arrives. The first handshake to complete within `timeout` is
+/// installed; if none does, the relay remains the active transport and
+/// `false` is returned.
+async fn dial_candidates(
+    listener: &QuicListener,
+    addrs: &[SocketAddr],
+    local_peer: &PeerId,
+    remote_peer: &PeerId,
+    peer_link: &PeerLink,
+    timeout: Duration,
+) -> bool {
+    if addrs.is_empty() {
+        return false;
+    }
+    let attempts = addrs.iter().map(|&addr| {
+        Box::pin(async move {
+            listener
+                .connect(addr, remote_peer.clone(), timeout)
+                .await
+                .map(|transport| (addr, transport))
+        })
+    });
+    match futures_util::future::select_ok(attempts).await {
+        Ok(((addr, transport), _)) => {
+            install_dialed(transport, addr, local_peer, remote_peer, peer_link).await;
+            true
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "direct dial to candidates failed");
+            false
+        }
+    }
+}
+
+/// Dial the remote peer directly and install the link on success.
+async fn dial_peer(
+    addr: SocketAddr,
+    local_peer: &PeerId,
+    remote_peer: &PeerId,
+    peer_link: &PeerLink,
+    timeout: Duration,
+) {
+    let transport = match QuicTransport::connect_with_timeout(
+        addr,
+        local_peer.clone(),
+        remote_peer.clone(),
+        timeout,
+    )
+    .await
+    {
+        Ok(transport) => transport,
+        Err(e) => {
+            tracing::debug!(addr = %addr, error = %e, "direct P2P dial failed");
+            return;
+        }
+    };
+    install_dialed(transport, addr, local_peer, remote_peer, peer_link).await;
+}
+
+/// Identify ourselves on an outbound direct connection and install it.
+///
+/// If both peers dial each other at once, the connection initiated by the
+/// peer with the lower ID wins on both sides (see [`accept_direct`]).
+async fn install_dialed(
+    transport: QuicTransport,
+    addr: SocketAddr,
+    local_peer: &PeerId,
+    remote_peer: &PeerId,
+    peer_link: &PeerLink,
+) {
+    if let Err(e) = transport.announce().await {
+        tracing::debug!(addr = %addr, error = %e, "direct P2P announce failed");
+        return;
+    }
+    if !peer_link.is_connected(remote_peer) || local_peer.as_str() < remote_peer.as_str() {
+        tracing::info!(addr = %addr, "direct P2P link established");
+        peer_link.install(transport);
+    }
+}
+
+/// Accept direct connections from the remote peer and install them.
+///
+/// Connections announcing any other peer ID are dropped.
+async fn accept_direct(
+    listener: &QuicListener,
+    local_peer: &PeerId,
+    remote_peer: &PeerId,
+    peer_link: &PeerLink,
+) {
+    loop {
+        let transport = match listener.accept().await {
+            Ok(transport) => transport,
+            Err(TransportError::ConnectionClosed) => break,
+            Err(e) => {
+                tracing::debug!(error = %e, "direct P2P accept failed");
+                continue;
+            }
+        };
+        let transport = match tokio::time::timeout(P2P_IDENTIFY_TIMEOUT, transport.identify()).await
+        {
+            Ok(Ok(transport)) => transport,
+            Ok(Err(e)) => {
+                tracing::debug!(error = %e, "direct P2P identify failed");
+                continue;
+            }
+            Err(_) => {
+                tracing::debug!("direct P2P peer did not identify in time");
+                continue;
+            }
+        };
+        if transport.remote_id() != remote_peer {
+            tracing::warn!(
+                peer = %transport.remote_id(),
+                "rejecting direct connection from unexpected peer"
+            );
+            continue;
+        }
+        if !peer_link.is_connected(remote_peer) || remote_peer.as_str() < local_peer.as_str() {
+            tracing::info!(peer = %remote_peer, "direct P2P link accepted");
+            peer_link.install(transport);
+        }
+    }
+}
+
+/// Apply a relay drain hint to the reconnect state.
+///
+/// A redirect is taken immediately (the delay only applies to the relay that
+/// is shutting down); otherwise the next attempt waits at least `retry_after`.
+fn apply_drain_hint(
+    redirect: &mut Option<String>,
+    min_delay: &mut Duration,
+    retry_after: Duration,
+    redirect_url: Option<String>,
+) {
+    if let Some(url) = redirect_url {
+        tracing::info!(url = %url, "relay redirected reconnect");
+        *redirect = Some(url);
+        *min_delay = Duration::ZERO;
+    } else {
+        *min_delay = retry_after;
+    }
+}
+
 /// Drain the offline message queue by sending all queued messages.
 ///
-/// Messages that fail to send are reported as errors but not re-queued
-/// (to avoid infinite retry loops).
+/// Messages are resent oldest first under their original [`MessageId`]s and
+/// removed from the queue once the transport accepts them. Draining stops
+/// at the first transport failure, leaving that message and the rest
+/// queued for the next reconnect. Messages that fail for any other reason
+/// (e.g. validation) are reported and dropped.
+#[allow(clippy::significant_drop_tightening)]
 async fn drain_message_queue(
     shared_mgr: &SharedChatManager,
     message_queue: &MessageQueue,
     evt_tx: &mpsc::Sender<NetEvent>,
 ) {
-    // Drain the queue into a local vec to release the lock quickly.
-    let messages: Vec<String> = {
-        let mut queue = message_queue.lock().await;
-        let count = queue.len();
-        if count == 0 {
+    // Snapshot the queue to release the lock while sending.
+    let messages: Vec<ChatMessage> = {
+        let queue = message_queue.lock().await;
+        if queue.is_empty() {
             return;
         }
-        tracing::info!(count, "draining offline message queue");
-        queue.drain(..).collect()
+        tracing::info!(count = queue.len(), "draining offline message queue");
+        queue.messages().cloned().collect()
     };
 
-    for text in messages {
-        let mgr_guard = shared_mgr.read().await;
-        if let Some(ref mgr) = *mgr_guard {
-            let content = MessageContent::Text(text);
-            let conversation = ConversationId::new();
-            if let Err(e) = mgr.send_message(content, conversation).await {
+    for message in messages {
+        let id = message.metadata.message_id.clone();
+        let result = {
+            let mgr_guard = shared_mgr.read().await;
+            let Some(ref mgr) = *mgr_guard else {
+                tracing::warn!("ChatManager unavailable during queue drain, keeping messages");
+                return;
+            };
+            mgr.send_chat_message(message).await
+        };
+        match result {
+            Ok(_) => {}
+            Err(SendError::Transport(e)) => {
+                tracing::info!(error = %e, "queued message not sent, keeping it queued");
+                return;
+            }
+            Err(e) => {
                 let _ = evt_tx
                     .send(NetEvent::Error(format!(
                         "Failed to send queued message: {e}"
                     )))
                     .await;
             }
-        } else {
-            // ChatManager gone again during drain; remaining messages are lost.
-            tracing::warn!("ChatManager unavailable during queue drain, messages lost");
-            break;
         }
+        dequeue(message_queue, &id).await;
+    }
+}
+
+/// Remove a sent (or unsendable) message from the offline queue.
+async fn dequeue(message_queue: &MessageQueue, id: &MessageId) {
+    let removed = message_queue.lock().await.remove(id);
+    if let Err(e) = removed {
+        tracing::warn!(error = %e, "failed to persist offline message queue");
+    }
+}
+
+/// Open the offline message queue, falling back to memory if the
+/// persisted queue cannot be loaded.
+fn open_outbox(config: &NetConfig) -> Outbox {
+    let cap = config.reconnect.message_queue_cap;
+    let Some(path) = &config.outbox_path else {
+        return Outbox::in_memory(cap);
+    };
+    match Outbox::open(path, cap) {
+        Ok(outbox) => {
+            if !outbox.is_empty() {
+                tracing::info!(count = outbox.len(), path = %path.display(), "restored queued messages");
+            }
+            outbox
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, path = %path.display(), "could not load message queue");
+            Outbox::in_memory(cap)
+        }
+    }
+}
+
+/// The display text of a chat message.
+const fn message_text(message: &ChatMessage) -> &str {
+    match &message.content {
+        MessageContent::Text(text) => text.as_str(),
     }
 }
 
@@ -613,10 +1797,7 @@ async fn drain_message_queue(
 /// the command handler to complete without errors. Room message sending
 /// will be completed when `RelayTransport` is extended with `send_raw()`.
 #[allow(clippy::unused_async)]
-async fn send_room_message(
-    _mgr: &ChatManager<StubNoiseSession, RelayTransport, InMemoryStore>,
-    room_msg: &RoomMessage,
-) -> Result<(), String> {
+async fn send_room_message(_mgr: &NetChatManager, room_msg: &RoomMessage) -> Result<(), String> {
     tracing::warn!(
         ?room_msg,
         "room message send not yet implemented — requires RelayTransport::send_raw()"
@@ -690,7 +1871,7 @@ async fn receive_loop(shared_mgr: SharedChatManager, evt_tx: mpsc::Sender<NetEve
                     let _ = evt_tx
                         .send(NetEvent::ConnectionStatus {
                             connected: false,
-                            transport_type: "Relay".to_string(),
+                            transport_type: TransportType::Relay,
                         })
                         .await;
                     break;
@@ -718,23 +1899,31 @@ async fn command_handler(
     conversation: ConversationId,
     message_queue: MessageQueue,
     shutdown_flag: Arc<AtomicBool>,
-    queue_cap: usize,
     local_peer_id: String,
 ) {
+    let sender_id = SenderId::new(local_peer_id.as_bytes().to_vec());
     while let Some(cmd) = cmd_rx.recv().await {
         match cmd {
             NetCommand::SendMessage {
                 conversation_id,
                 text,
             } => {
+                // Build the message up front so a queued copy keeps its ID.
+                let message = ChatMessage {
+                    metadata: MessageMetadata {
+                        message_id: MessageId::new(),
+                        timestamp: Timestamp::now(),
+                        sender_id: sender_id.clone(),
+                        conversation_id: conversation.clone(),
+                    },
+                    content: MessageContent::Text(text),
+                };
+
                 // Try to send if connected; queue on failure or disconnect.
                 let sent = {
                     let mgr_guard = shared_mgr.read().await;
                     if let Some(ref mgr) = *mgr_guard {
-                        let content = MessageContent::Text(text.clone());
-                        mgr.send_message(content, conversation.clone())
-                            .await
-                            .is_ok()
+                        mgr.send_chat_message(message.clone()).await.is_ok()
                     } else {
                         false
                     }
@@ -742,21 +1931,15 @@ async fn command_handler(
 
                 if !sent {
                     // Disconnected or send failed: queue for later delivery.
-                    let queue_full = {
-                        let mut queue = message_queue.lock().await;
-                        if queue.len() < queue_cap {
-                            queue.push_back(text);
-                            false
-                        } else {
-                            true
+                    let queued = message_queue.lock().await.push(message);
+                    let msg = match queued {
+                        Ok(true) => "Disconnected, message queued for delivery",
+                        Ok(false) => "Disconnected, message queue full — message dropped",
+                        Err(e) => {
+                            tracing::warn!(error = %e, "failed to persist offline message queue");
+                            "Disconnected, message queued for delivery (not saved to disk)"
                         }
                     };
-
-                    let msg = if queue_full {
-                        "Disconnected, message queue full — message dropped"
-                    } else {
-                        "Disconnected, message queued for delivery"
-                    };
                     let _ = evt_tx.send(NetEvent::Error(msg.to_string())).await;
                 }
                 // NOTE: conversation_id will be used in T-017-10 for room routing
@@ -886,6 +2069,14 @@ async fn command_handler(
                         .await;
                 }
             }
+            NetCommand::SendTaskSync(msg) => {
+                let mgr_guard = shared_mgr.read().await;
+                if let Some(ref mgr) = *mgr_guard {
+                    mgr.send_task_sync(&msg).await;
+                } else {
+                    tracing::debug!("disconnected, task sync not sent");
+                }
+            }
             NetCommand::Shutdown => {
                 tracing::info!("net command handler shutting down");
                 shutdown_flag.store(true, Ordering::Relaxed);
@@ -898,10 +2089,14 @@ async fn command_handler(
 /// Background task: forward `ChatEvent`s as `NetEvent`s to the TUI.
 ///
 /// Maps the internal `ChatEvent` variants to the simpler `NetEvent` enum
-/// that the TUI main loop consumes.
+/// that the TUI main loop consumes. Task sync messages are applied to
+/// `tasks` instead (see [`apply_task_sync`]); the TUI only hears that the
+/// tasks changed, and which changes room permissions rejected.
 async fn chat_event_forwarder(
     mut chat_rx: mpsc::Receiver<ChatEvent>,
     evt_tx: mpsc::Sender<NetEvent>,
+    tasks: SharedTasks,
+    shared_mgr: SharedChatManager,
 ) {
     while let Some(event) = chat_rx.recv().await {
         let net_event = match event {
@@ -941,6 +2136,26 @@ async fn chat_event_forwarder(
                 room_id,
                 is_typing,
             }),
+            ChatEvent::TaskSync { message, from } => {
+                let warnings = apply_task_sync(&tasks, &shared_mgr, &from, &message).await;
+                if matches!(
+                    message,
+                    TaskSyncMessage::FieldUpdate { .. } | TaskSyncMessage::FullState { .. }
+                ) && evt_tx.send(NetEvent::TasksChanged).await.is_err()
+                {
+                    return;
+                }
+                for warning in warnings {
+                    if evt_tx
+                        .send(NetEvent::TaskChangeRejected(warning))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                None
+            }
         };
 
         if let Some(evt) = net_event
@@ -952,7 +2167,33 @@ async fn chat_event_forwarder(
     }
 }
 
+/// Apply a task sync message received from `from` to `tasks`, and send
+/// back the replies it asks for (see [`TaskManager::reply_to`]).
+///
+/// Returns the changes left out because `from` may not make them.
+async fn apply_task_sync(
+    tasks: &SharedTasks,
+    shared_mgr: &SharedChatManager,
+    from: &PeerId,
+    message: &TaskSyncMessage,
+) -> Vec<PermissionWarning> {
+    let (warnings, replies) = {
+        let mut tasks = tasks.lock();
+        (tasks.apply_remote(from, message), tasks.reply_to(message))
+    };
+    if !replies.is_empty() {
+        let mgr_guard = shared_mgr.read().await;
+        if let Some(ref mgr) = *mgr_guard {
+            for reply in &replies {
+                mgr.send_task_sync(reply).await;
+            }
+        }
+    }
+    warnings
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -1026,6 +2267,50 @@ mod tests {
         assert!(debug.contains("10"));
     }
 
+    #[test]
+    fn proxy_disables_direct_paths() {
+        let mut config = NetConfig::new(
+            "ws://relay/ws".to_string(),
+            "alice".to_string(),
+            "bob".to_string(),
+        );
+        config.p2p.listen_addr = Some("0.0.0.0:7000".parse().unwrap());
+        config.p2p.lan_discovery = true;
+        config.p2p.hole_punch = true;
+
+        let mut direct = config.clone();
+        apply_proxy_policy(&mut direct);
+        assert!(direct.p2p.is_enabled());
+
+        config.relay.proxy = Some("socks5://127.0.0.1:9050".parse().unwrap());
+        apply_proxy_policy(&mut config);
+        assert!(!config.p2p.is_enabled());
+        assert!(!config.p2p.lan_discovery);
+    }
+
+    #[test]
+    fn drain_hint_without_redirect_sets_min_delay() {
+        let mut redirect = None;
+        let mut min_delay = Duration::ZERO;
+        apply_drain_hint(&mut redirect, &mut min_delay, Duration::from_secs(5), None);
+        assert_eq!(redirect, None);
+        assert_eq!(min_delay, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn drain_hint_with_redirect_switches_url_without_delay() {
+        let mut redirect = None;
+        let mut min_delay = Duration::from_secs(1);
+        apply_drain_hint(
+            &mut redirect,
+            &mut min_delay,
+            Duration::from_secs(5),
+            Some("ws://backup/ws".to_string()),
+        );
+        assert_eq!(redirect.as_deref(), Some("ws://backup/ws"));
+        assert_eq!(min_delay, Duration::ZERO);
+    }
+
     #[test]
     fn net_event_reconnect_failed_debug_format() {
         let evt = NetEvent::ReconnectFailed;
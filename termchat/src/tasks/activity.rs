@@ -0,0 +1,290 @@
+//! Per-task activity feed and comment threads.
+//!
+//! The CRDT keeps only the winning value of each field, so it cannot say who
+//! changed what. [`TaskManager`](super::TaskManager) records an
+//! [`ActivityEntry`] for every field update it applies, local or remote,
+//! with the value before and after. The feed covers the updates this client
+//! has applied since it started: state merged in bulk (a full state or a
+//! digest repair) carries no history.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use termchat_proto::task::{Task, TaskFieldUpdate, TaskId};
+
+/// Maximum number of activity entries kept per task; older ones are
+/// dropped first.
+pub const MAX_ACTIVITY_ENTRIES: usize = 200;
+
+/// One applied change to a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityEntry {
+    /// `PeerId` of the peer who made the change.
+    pub author: String,
+    /// Hybrid clock timestamp of the change.
+    pub timestamp: u64,
+    /// Name of the changed field (e.g. `"status"`), or `"comment"`.
+    pub field: &'static str,
+    /// The field's value before the change, for display.
+    pub old: String,
+    /// The field's value after the change (the comment text for comments).
+    pub new: String,
+}
+
+impl ActivityEntry {
+    /// Describes `update` as applied to `task`. Must be called before the
+    /// update is applied, so `old` is still the previous value.
+    #[must_use]
+    pub fn describe(task: &Task, update: &TaskFieldUpdate) -> Self {
+        let (field, old, new, timestamp, author) = match update {
+            TaskFieldUpdate::Title(reg) => (
+                "title",
+                task.title.value.clone(),
+                reg.value.clone(),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Status(reg) => (
+                "status",
+                task.status.value.to_string(),
+                reg.value.to_string(),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Assignee(reg) => (
+                "assignee",
+                optional(task.assignee.value.as_ref()),
+                optional(reg.value.as_ref()),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Description(reg) => (
+                "description",
+                task.description.value.clone(),
+                reg.value.clone(),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Due(reg) => (
+                "due",
+                due_date(task.due.value),
+                due_date(reg.value),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Recurrence(reg) => (
+                "repeats",
+                optional(task.recurrence.value.as_ref()),
+                optional(reg.value.as_ref()),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Priority(reg) => (
+                "priority",
+                task.priority.value.to_string(),
+                reg.value.to_string(),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::Label { label, present } => (
+                "label",
+                membership(task.has_label(label), &format!("#{label}")),
+                membership(present.value, &format!("#{label}")),
+                present.timestamp,
+                &present.author,
+            ),
+            TaskFieldUpdate::Parent(reg) => (
+                "parent",
+                optional(task.parent.value.as_ref()),
+                optional(reg.value.as_ref()),
+                reg.timestamp,
+                &reg.author,
+            ),
+            TaskFieldUpdate::BlockedBy { blocker, present } => (
+                "blocked by",
+                membership(is_blocked_by(task, blocker), &blocker.to_string()),
+                membership(present.value, &blocker.to_string()),
+                present.timestamp,
+                &present.author,
+            ),
+            TaskFieldUpdate::Comment(comment) => (
+                "comment",
+                String::new(),
+                comment.body.clone(),
+                comment.timestamp,
+                &comment.author,
+            ),
+        };
+        Self {
+            author: author.clone(),
+            timestamp,
+            field,
+            old,
+            new,
+        }
+    }
+}
+
+impl fmt::Display for ActivityEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field == "comment" {
+            write!(f, "{} commented", self.author)
+        } else {
+            write!(
+                f,
+                "{} changed {}: {} \u{2192} {}",
+                self.author, self.field, self.old, self.new
+            )
+        }
+    }
+}
+
+fn optional(value: Option<&impl fmt::Display>) -> String {
+    value.map_or_else(|| "none".to_string(), ToString::to_string)
+}
+
+fn membership(present: bool, element: &str) -> String {
+    if present {
+        element.to_string()
+    } else {
+        "none".to_string()
+    }
+}
+
+fn is_blocked_by(task: &Task, blocker: &TaskId) -> bool {
+    task.blocked_by
+        .get(blocker)
+        .is_some_and(|present| present.value)
+}
+
+fn due_date(due: Option<u64>) -> String {
+    due.and_then(|ms| i64::try_from(ms).ok())
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map_or_else(|| "none".to_string(), |due| due.date_naive().to_string())
+}
+
+/// Orders comments as threads, each paired with its reply depth: every
+/// comment is followed by its replies, oldest first. `comments` must be in
+/// posting order.
+///
+/// Replies to a comment that is not known yet (still in flight) are shown
+/// at the top level rather than hidden.
+pub fn comment_thread<'a, T, K: Ord + 'a>(
+    comments: impl IntoIterator<Item = &'a T>,
+    id: impl Fn(&'a T) -> &'a K,
+    reply_to: impl Fn(&'a T) -> Option<&'a K>,
+) -> Vec<(usize, &'a T)> {
+    let comments: Vec<&T> = comments.into_iter().collect();
+    let known: BTreeSet<&K> = comments.iter().map(|c| id(c)).collect();
+    let mut roots = Vec::new();
+    let mut replies: BTreeMap<&K, Vec<&T>> = BTreeMap::new();
+    for &comment in &comments {
+        match reply_to(comment).filter(|parent| known.contains(parent)) {
+            Some(parent) => replies.entry(parent).or_default().push(comment),
+            None => roots.push(comment),
+        }
+    }
+
+    let mut thread = Vec::with_capacity(comments.len());
+    let mut visited = BTreeSet::new();
+    let mut stack: Vec<(usize, &T)> = roots.into_iter().rev().map(|c| (0, c)).collect();
+    while let Some((depth, comment)) = stack.pop() {
+        visited.insert(id(comment));
+        thread.push((depth, comment));
+        if let Some(children) = replies.get(id(comment)) {
+            stack.extend(children.iter().rev().map(|&reply| (depth + 1, reply)));
+        }
+    }
+    // Replies that only reach each other, which only a misbehaving peer
+    // can produce, are not under any root.
+    for comment in comments {
+        if !visited.contains(id(comment)) {
+            thread.push((0, comment));
+        }
+    }
+    thread
+}
+
+#[cfg(test)]
+mod tests {
+    use termchat_proto::task::{LwwRegister, TaskPriority, TaskStatus};
+
+    use super::*;
+
+    fn task() -> Task {
+        Task {
+            id: TaskId::new(),
+            room_id: "room-1".to_string(),
+            title: LwwRegister::new("Old".to_string(), 100, "peer-a".to_string()),
+            status: LwwRegister::new(TaskStatus::Open, 100, "peer-a".to_string()),
+            assignee: LwwRegister::new(None, 100, "peer-a".to_string()),
+            description: LwwRegister::new(String::new(), 100, "peer-a".to_string()),
+            due: LwwRegister::new(None, 100, "peer-a".to_string()),
+            recurrence: LwwRegister::new(None, 100, "peer-a".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 100, "peer-a".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 100, "peer-a".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            created_at: 100,
+            created_by: "peer-a".to_string(),
+        }
+    }
+
+    #[test]
+    fn describes_old_and_new_values() {
+        let task = task();
+        let status = TaskFieldUpdate::Status(LwwRegister::new(
+            TaskStatus::Completed,
+            200,
+            "peer-b".to_string(),
+        ));
+        let entry = ActivityEntry::describe(&task, &status);
+        assert_eq!(entry.field, "status");
+        assert_eq!(entry.timestamp, 200);
+        assert_eq!(
+            entry.to_string(),
+            "peer-b changed status: open \u{2192} completed"
+        );
+
+        let due = TaskFieldUpdate::Due(LwwRegister::new(
+            Some(1_772_323_200_000),
+            200,
+            "peer-b".to_string(),
+        ));
+        let entry = ActivityEntry::describe(&task, &due);
+        assert_eq!(
+            (entry.old.as_str(), entry.new.as_str()),
+            ("none", "2026-03-01")
+        );
+
+        let label = TaskFieldUpdate::Label {
+            label: "bug".to_string(),
+            present: LwwRegister::new(true, 200, "peer-b".to_string()),
+        };
+        assert_eq!(ActivityEntry::describe(&task, &label).new, "#bug");
+    }
+
+    #[test]
+    fn threads_replies_under_their_comment() {
+        // (id, reply_to)
+        let comments = [
+            (1, None),
+            (2, Some(1)),
+            (3, None),
+            (4, Some(2)),
+            (5, Some(1)),
+            (6, Some(9)),
+        ];
+        let thread = comment_thread(&comments, |c| &c.0, |c| c.1.as_ref());
+        let order: Vec<(usize, i32)> = thread.iter().map(|(depth, c)| (*depth, c.0)).collect();
+        assert_eq!(order, [(0, 1), (1, 2), (2, 4), (1, 5), (0, 3), (0, 6)]);
+    }
+
+    #[test]
+    fn reply_cycles_are_still_shown() {
+        let comments = [(1, Some(2)), (2, Some(1))];
+        let thread = comment_thread(&comments, |c| &c.0, |c| c.1.as_ref());
+        assert_eq!(thread.len(), 2);
+    }
+}
@@ -0,0 +1,163 @@
+//! Kanban board layout: status columns and card filters.
+//!
+//! The board is a view over the room's tasks, not extra state: a card's
+//! column is its status, so moving a card is a status update and syncs
+//! like any other.
+
+use std::fmt;
+
+use termchat_proto::task::{Task, TaskStatus};
+
+/// Board columns, left to right. Deleted tasks are not shown.
+pub const BOARD_COLUMNS: [TaskStatus; 3] = [
+    TaskStatus::Open,
+    TaskStatus::InProgress,
+    TaskStatus::Completed,
+];
+
+/// Heading of a board column, e.g. `"In progress"`.
+#[must_use]
+pub const fn column_title(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Open => "Open",
+        TaskStatus::InProgress => "In progress",
+        TaskStatus::Completed => "Completed",
+        TaskStatus::Deleted => "Deleted",
+    }
+}
+
+/// The column left of `status`'s, if any.
+#[must_use]
+pub fn column_left(status: TaskStatus) -> Option<TaskStatus> {
+    let index = BOARD_COLUMNS.iter().position(|s| *s == status)?;
+    BOARD_COLUMNS.get(index.checked_sub(1)?).copied()
+}
+
+/// The column right of `status`'s, if any.
+#[must_use]
+pub fn column_right(status: TaskStatus) -> Option<TaskStatus> {
+    let index = BOARD_COLUMNS.iter().position(|s| *s == status)?;
+    BOARD_COLUMNS.get(index + 1).copied()
+}
+
+/// Which cards the board shows, written as `@assignee #label`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardFilter {
+    /// Only show cards assigned to this name.
+    pub assignee: Option<String>,
+    /// Only show cards with this label.
+    pub label: Option<String>,
+}
+
+impl BoardFilter {
+    /// Parses a filter from `@name` and `#label` words in any order. An
+    /// empty string clears the filter.
+    ///
+    /// Returns `None` for any other word, or a repeated or empty term.
+    #[must_use]
+    pub fn parse(args: &str) -> Option<Self> {
+        let mut filter = Self::default();
+        for word in args.split_whitespace() {
+            let (slot, value) = if let Some(name) = word.strip_prefix('@') {
+                (&mut filter.assignee, name)
+            } else if let Some(label) = word.strip_prefix('#') {
+                (&mut filter.label, label)
+            } else {
+                return None;
+            };
+            if value.is_empty() || slot.is_some() {
+                return None;
+            }
+            *slot = Some(value.to_string());
+        }
+        Some(filter)
+    }
+
+    /// Returns `true` if the filter shows every card.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.assignee.is_none() && self.label.is_none()
+    }
+
+    /// Returns `true` if a card with this assignee and labels is shown.
+    #[must_use]
+    pub fn matches(&self, assignee: Option<&str>, has_label: impl Fn(&str) -> bool) -> bool {
+        self.assignee
+            .as_deref()
+            .is_none_or(|name| assignee == Some(name))
+            && self.label.as_deref().is_none_or(has_label)
+    }
+}
+
+impl fmt::Display for BoardFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .assignee
+            .iter()
+            .map(|name| format!("@{name}"))
+            .chain(self.label.iter().map(|label| format!("#{label}")))
+            .collect();
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+/// Sorts `tasks` into [`BOARD_COLUMNS`], keeping their order and dropping
+/// cards the filter hides.
+pub fn columns<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    filter: &BoardFilter,
+) -> [Vec<&'a Task>; 3] {
+    let mut columns: [Vec<&Task>; 3] = Default::default();
+    for task in tasks {
+        let Some(column) = BOARD_COLUMNS.iter().position(|s| *s == task.status.value) else {
+            continue;
+        };
+        if filter.matches(task.assignee.value.as_deref(), |label| {
+            task.has_label(label)
+        }) {
+            columns[column].push(task);
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_filter_terms() {
+        let filter = BoardFilter::parse("#bug @alice").unwrap();
+        assert_eq!(filter.assignee.as_deref(), Some("alice"));
+        assert_eq!(filter.label.as_deref(), Some("bug"));
+        assert_eq!(filter.to_string(), "@alice #bug");
+
+        assert_eq!(BoardFilter::parse("  "), Some(BoardFilter::default()));
+        assert_eq!(BoardFilter::parse("alice"), None);
+        assert_eq!(BoardFilter::parse("@"), None);
+        assert_eq!(BoardFilter::parse("@alice @bob"), None);
+    }
+
+    #[test]
+    fn columns_step_left_and_right() {
+        assert_eq!(column_left(TaskStatus::Open), None);
+        assert_eq!(
+            column_left(TaskStatus::Completed),
+            Some(TaskStatus::InProgress)
+        );
+        assert_eq!(column_right(TaskStatus::Open), Some(TaskStatus::InProgress));
+        assert_eq!(column_right(TaskStatus::Completed), None);
+        assert_eq!(column_right(TaskStatus::Deleted), None);
+    }
+
+    #[test]
+    fn filter_matches_assignee_and_label() {
+        let filter = BoardFilter::parse("@alice #bug").unwrap();
+        assert!(filter.matches(Some("alice"), |label| label == "bug"));
+        assert!(!filter.matches(Some("bob"), |label| label == "bug"));
+        assert!(!filter.matches(Some("alice"), |_| false));
+        assert!(!filter.matches(None, |_| true));
+        assert!(BoardFilter::default().matches(None, |_| false));
+    }
+}
@@ -0,0 +1,198 @@
+//! Hybrid logical clock for task LWW timestamps.
+//!
+//! Wall-clock timestamps let a peer whose clock runs fast win every
+//! concurrent edit until real time catches up. A [`HybridClock`] tracks the
+//! highest timestamp it has seen, local or remote, and always issues a
+//! larger one, so an edit made after seeing another peer's edit wins
+//! as long as the two clocks are within the clock's maximum skew of each
+//! other. Timestamps stay close to physical time while clocks agree.
+//!
+//! A remote timestamp more than the maximum skew ahead of local physical
+//! time is clamped when observed, so a peer with a bad or malicious clock
+//! cannot drag every other peer's clock along with it. Its own writes still
+//! win until real time catches up, but writes by everyone else stay close
+//! to physical time.
+//!
+//! Timestamps pack the physical time in milliseconds into the upper bits of
+//! a `u64` and a logical counter into the low [`LOGICAL_BITS`] bits, so they
+//! fit the existing [`LwwRegister`](termchat_proto::task::LwwRegister)
+//! field and compare with plain integer ordering. Registers written by
+//! peers that still stamp raw milliseconds lose to any hybrid timestamp.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of low bits holding the logical counter.
+pub const LOGICAL_BITS: u32 = 16;
+
+/// Default limit on how far ahead of local physical time an observed
+/// timestamp may advance the clock, in milliseconds.
+pub const DEFAULT_MAX_SKEW_MS: u64 = 60_000;
+
+/// A per-peer hybrid logical clock.
+#[derive(Debug, Clone)]
+pub struct HybridClock {
+    /// Highest timestamp issued or observed so far.
+    last: u64,
+    /// Milliseconds added to the system clock, to simulate skew in tests.
+    offset_ms: i64,
+    /// How far ahead of physical time an observed timestamp may move the
+    /// clock, in milliseconds.
+    max_skew_ms: u64,
+}
+
+impl Default for HybridClock {
+    fn default() -> Self {
+        Self::with_offset(0)
+    }
+}
+
+impl HybridClock {
+    /// Creates a clock driven by the system clock.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a clock whose physical time is the system clock shifted by
+    /// `offset_ms` (negative for a slow clock).
+    #[must_use]
+    pub const fn with_offset(offset_ms: i64) -> Self {
+        Self {
+            last: 0,
+            offset_ms,
+            max_skew_ms: DEFAULT_MAX_SKEW_MS,
+        }
+    }
+
+    /// Sets how far ahead of local physical time, in milliseconds, an
+    /// observed timestamp may advance the clock.
+    #[must_use]
+    pub const fn with_max_skew(mut self, max_skew_ms: u64) -> Self {
+        self.max_skew_ms = max_skew_ms;
+        self
+    }
+
+    /// Issues a timestamp for a local write, greater than every timestamp
+    /// issued or observed before.
+    pub fn now(&mut self) -> u64 {
+        let physical = self.physical_ms_now() << LOGICAL_BITS;
+        self.last = if physical > self.last {
+            physical
+        } else {
+            self.last.saturating_add(1)
+        };
+        self.last
+    }
+
+    /// Advances the clock past a timestamp received from another peer.
+    ///
+    /// A timestamp more than the maximum skew ahead of local physical time
+    /// only advances the clock to that limit. Returns `false` in that case.
+    pub fn observe(&mut self, remote: u64) -> bool {
+        let limit = self
+            .physical_ms_now()
+            .saturating_add(self.max_skew_ms)
+            .min(u64::MAX >> LOGICAL_BITS);
+        // Clamp to the start of that millisecond, leaving the logical
+        // counter free for local writes.
+        let bound = limit << LOGICAL_BITS;
+        self.last = self.last.max(remote.min(bound));
+        remote <= bound
+    }
+
+    /// Highest timestamp issued or observed so far.
+    #[must_use]
+    pub const fn last(&self) -> u64 {
+        self.last
+    }
+
+    /// The physical component of `timestamp`, in milliseconds since epoch.
+    #[must_use]
+    pub const fn physical_ms(timestamp: u64) -> u64 {
+        timestamp >> LOGICAL_BITS
+    }
+
+    /// The clock's physical time in milliseconds since epoch.
+    fn physical_ms_now(&self) -> u64 {
+        let wall = u64::try_from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+        )
+        .unwrap_or(u64::MAX);
+        wall.saturating_add_signed(self.offset_ms)
+            .min(u64::MAX >> LOGICAL_BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_strictly_increase() {
+        let mut clock = HybridClock::new();
+        let mut prev = clock.now();
+        for _ in 0..10_000 {
+            let next = clock.now();
+            assert!(next > prev);
+            prev = next;
+        }
+    }
+
+    #[test]
+    fn tracks_physical_time() {
+        let mut clock = HybridClock::new();
+        let wall = HybridClock::new().physical_ms_now();
+        let physical = HybridClock::physical_ms(clock.now());
+        assert!(physical.abs_diff(wall) < 1_000);
+    }
+
+    #[test]
+    fn observe_moves_past_remote() {
+        let mut slow = HybridClock::with_offset(-20_000);
+        let mut fast = HybridClock::with_offset(20_000);
+        let remote = fast.now();
+        assert!(slow.observe(remote));
+        assert!(slow.now() > remote);
+    }
+
+    #[test]
+    fn far_future_timestamp_is_clamped() {
+        let mut clock = HybridClock::new();
+        let wall = HybridClock::new().physical_ms_now();
+        assert!(!clock.observe(u64::MAX));
+        let physical = HybridClock::physical_ms(clock.now());
+        assert!(physical <= wall + DEFAULT_MAX_SKEW_MS + 1_000);
+
+        let mut tolerant = HybridClock::new().with_max_skew(3_600_000);
+        let remote = HybridClock::with_offset(1_800_000).now();
+        assert!(tolerant.observe(remote));
+        assert!(tolerant.now() > remote);
+    }
+
+    #[test]
+    fn observe_older_timestamp_is_noop() {
+        let mut clock = HybridClock::new();
+        let ts = clock.now();
+        clock.observe(ts - 1);
+        clock.observe(1_000);
+        assert_eq!(clock.last(), ts);
+    }
+
+    #[test]
+    fn legacy_millisecond_timestamps_lose() {
+        let mut clock = HybridClock::new();
+        let legacy = HybridClock::new().physical_ms_now() + 3_600_000;
+        clock.observe(legacy);
+        assert!(clock.now() > legacy);
+    }
+
+    #[test]
+    fn offset_shifts_physical_time() {
+        let fast = HybridClock::with_offset(60_000).physical_ms_now();
+        let wall = HybridClock::new().physical_ms_now();
+        assert!(fast >= wall + 59_000);
+    }
+}
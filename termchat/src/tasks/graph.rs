@@ -0,0 +1,231 @@
+//! Subtask and "blocked by" graphs with cycle detection.
+//!
+//! Parent links and blocker edges are synced as LWW registers, so two peers
+//! can each make an edit that is valid locally and together form a cycle
+//! (A under B on one peer, B under A on another). Rejecting such edits on
+//! arrival would make the result depend on delivery order, so every edge is
+//! kept in the CRDT state and cycles are broken when the graph is built:
+//! edges are added oldest first, and an edge that would close a cycle is
+//! ignored. Replicas with the same state therefore see the same graph.
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use termchat_proto::task::{Task, TaskId, TaskStatus};
+
+/// A directed graph that refuses edges closing a cycle.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph<K> {
+    edges: HashMap<K, BTreeSet<K>>,
+}
+
+impl<K> Default for DependencyGraph<K> {
+    fn default() -> Self {
+        Self {
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Ord + Clone> DependencyGraph<K> {
+    /// Creates an empty graph.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if an edge `from -> to` would close a cycle,
+    /// including a self-loop.
+    #[must_use]
+    pub fn would_cycle(&self, from: &K, to: &K) -> bool {
+        self.reaches(to, from)
+    }
+
+    /// Adds the edge `from -> to` unless it would close a cycle.
+    ///
+    /// Returns `false` if the edge was refused.
+    pub fn try_add(&mut self, from: K, to: K) -> bool {
+        if self.would_cycle(&from, &to) {
+            return false;
+        }
+        self.edges.entry(from).or_default().insert(to);
+        true
+    }
+
+    /// Targets of the edges leaving `from`.
+    pub fn targets(&self, from: &K) -> impl Iterator<Item = &K> {
+        self.edges.get(from).into_iter().flatten()
+    }
+
+    /// Returns `true` if `to` can be reached from `from` (a node reaches
+    /// itself).
+    fn reaches(&self, from: &K, to: &K) -> bool {
+        let mut stack = vec![from];
+        let mut seen = BTreeSet::new();
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if seen.insert(node) {
+                stack.extend(self.targets(node));
+            }
+        }
+        false
+    }
+}
+
+/// The effective subtask and blocker structure of one room's tasks.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGraph {
+    /// Child -> parent edges.
+    parents: DependencyGraph<TaskId>,
+    /// Task -> blocker edges.
+    blockers: DependencyGraph<TaskId>,
+    /// Parent -> children, derived from `parents`.
+    children: HashMap<TaskId, BTreeSet<TaskId>>,
+    /// Edges ignored because they would close a cycle, as (from, to).
+    ignored: Vec<(TaskId, TaskId)>,
+}
+
+impl TaskGraph {
+    /// Builds the graph from a room's tasks, oldest edges first.
+    pub fn build<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        let mut parent_edges = Vec::new();
+        let mut blocker_edges = Vec::new();
+        for task in tasks {
+            if let Some(parent) = &task.parent.value {
+                let stamp = (task.parent.timestamp, task.parent.author.clone());
+                parent_edges.push((stamp, task.id.clone(), parent.clone()));
+            }
+            for (blocker, present) in &task.blocked_by {
+                if present.value {
+                    let stamp = (present.timestamp, present.author.clone());
+                    blocker_edges.push((stamp, task.id.clone(), blocker.clone()));
+                }
+            }
+        }
+        parent_edges.sort();
+        blocker_edges.sort();
+
+        let mut graph = Self::default();
+        for (_, child, parent) in parent_edges {
+            if graph.parents.try_add(child.clone(), parent.clone()) {
+                graph.children.entry(parent).or_default().insert(child);
+            } else {
+                graph.ignored.push((child, parent));
+            }
+        }
+        for (_, task, blocker) in blocker_edges {
+            if !graph.blockers.try_add(task.clone(), blocker.clone()) {
+                graph.ignored.push((task, blocker));
+            }
+        }
+        graph
+    }
+
+    /// The effective parent of a task.
+    #[must_use]
+    pub fn parent(&self, task: &TaskId) -> Option<&TaskId> {
+        self.parents.targets(task).next()
+    }
+
+    /// Direct subtasks of a task.
+    pub fn children(&self, task: &TaskId) -> impl Iterator<Item = &TaskId> {
+        self.children.get(task).into_iter().flatten()
+    }
+
+    /// Effective blockers of a task.
+    pub fn blockers(&self, task: &TaskId) -> impl Iterator<Item = &TaskId> {
+        self.blockers.targets(task)
+    }
+
+    /// Returns `true` if making `parent` the parent of `child` would
+    /// close a cycle.
+    #[must_use]
+    pub fn parent_would_cycle(&self, child: &TaskId, parent: &TaskId) -> bool {
+        self.parents.would_cycle(child, parent)
+    }
+
+    /// Returns `true` if blocking `task` on `blocker` would close a cycle.
+    #[must_use]
+    pub fn blocker_would_cycle(&self, task: &TaskId, blocker: &TaskId) -> bool {
+        self.blockers.would_cycle(task, blocker)
+    }
+
+    /// Edges present in the synced state but ignored because they closed a
+    /// cycle, as (task, parent or blocker).
+    #[must_use]
+    pub fn ignored(&self) -> &[(TaskId, TaskId)] {
+        &self.ignored
+    }
+}
+
+/// Roll-up progress of a task's direct subtasks as (completed, total),
+/// ignoring deleted subtasks. `None` if the task has no subtasks.
+#[must_use]
+pub fn subtask_progress<S: BuildHasher>(
+    graph: &TaskGraph,
+    tasks: &HashMap<TaskId, Task, S>,
+    task: &TaskId,
+) -> Option<(usize, usize)> {
+    let statuses: Vec<TaskStatus> = graph
+        .children(task)
+        .filter_map(|child| tasks.get(child))
+        .map(|child| child.status.value)
+        .filter(|status| *status != TaskStatus::Deleted)
+        .collect();
+    if statuses.is_empty() {
+        return None;
+    }
+    let done = statuses
+        .iter()
+        .filter(|status| **status == TaskStatus::Completed)
+        .count();
+    Some((done, statuses.len()))
+}
+
+/// Blockers of a task that are neither completed nor deleted. Blockers not
+/// synced yet count as open.
+#[must_use]
+pub fn open_blockers<'a, S: BuildHasher>(
+    graph: &'a TaskGraph,
+    tasks: &HashMap<TaskId, Task, S>,
+    task: &TaskId,
+) -> Vec<&'a TaskId> {
+    graph
+        .blockers(task)
+        .filter(|blocker| {
+            tasks.get(*blocker).is_none_or(|b| {
+                !matches!(b.status.value, TaskStatus::Completed | TaskStatus::Deleted)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_self_loop_and_cycles() {
+        let mut graph = DependencyGraph::new();
+        assert!(!graph.try_add(1, 1));
+        assert!(graph.try_add(1, 2));
+        assert!(graph.try_add(2, 3));
+        assert!(graph.would_cycle(&3, &1));
+        assert!(!graph.try_add(3, 1));
+        assert!(graph.try_add(1, 3));
+        assert_eq!(graph.targets(&1).copied().collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn diamond_is_not_a_cycle() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.try_add("a", "b"));
+        assert!(graph.try_add("a", "c"));
+        assert!(graph.try_add("b", "d"));
+        assert!(graph.try_add("c", "d"));
+        assert!(!graph.would_cycle(&"a", &"d"));
+        assert!(graph.would_cycle(&"d", &"a"));
+    }
+}
@@ -0,0 +1,600 @@
+//! Plain task lists for moving tasks to and from other tools.
+//!
+//! Unlike the snapshots of [`store`](super::store), which carry LWW
+//! metadata and tombstones so an import merges like a sync, these formats
+//! hold only what an issue tracker or a notes file would: one
+//! [`TaskRecord`] per live task. Importing a list creates new tasks (see
+//! [`TaskManager::import_tasks`](super::TaskManager::import_tasks)).
+//!
+//! - **JSON**: an array of records.
+//! - **CSV**: a header row naming the columns, then one task per row. On
+//!   import only the `title` column is required and unknown columns are
+//!   ignored, so a tracker's own export can be read directly.
+//! - **Markdown**: a checklist with one section per status. Completed
+//!   tasks are checked and the assignee follows the title as `(@name)`.
+
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use termchat_proto::task::{Task, TaskPriority, TaskStatus};
+
+use super::TaskError;
+
+/// CSV columns written on export, in order.
+const CSV_COLUMNS: [&str; 7] = [
+    "title",
+    "status",
+    "assignee",
+    "priority",
+    "due",
+    "labels",
+    "description",
+];
+
+/// Statuses in the order their Markdown sections are written.
+const MARKDOWN_SECTIONS: [TaskStatus; 3] = [
+    TaskStatus::Open,
+    TaskStatus::InProgress,
+    TaskStatus::Completed,
+];
+
+/// Errors that can occur while reading or writing a task list.
+#[derive(Debug, thiserror::Error)]
+pub enum TaskListError {
+    /// A JSON list could not be encoded or decoded.
+    #[error("task list JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A line of a CSV list could not be parsed.
+    #[error("line {line}: {reason}")]
+    Parse {
+        /// 1-based line number.
+        line: usize,
+        /// What was wrong with it.
+        reason: String,
+    },
+
+    /// An entry of the list does not make a valid task.
+    #[error("task {index}: {source}")]
+    Invalid {
+        /// 1-based position of the entry in the list.
+        index: usize,
+        /// Why the task was rejected.
+        source: TaskError,
+    },
+}
+
+/// A task list file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskListFormat {
+    /// An array of [`TaskRecord`]s.
+    Json,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// A Markdown checklist.
+    Markdown,
+}
+
+impl TaskListFormat {
+    /// The format named by a file's extension (`.json`, `.csv`, `.md` or
+    /// `.markdown`).
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for TaskListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "md" | "markdown" => Ok(Self::Markdown),
+            other => Err(format!("unknown task list format: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for TaskListFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Csv => write!(f, "csv"),
+            Self::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+/// One task in a task list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    /// Task title.
+    pub title: String,
+    /// Open, in progress or completed; never deleted.
+    #[serde(with = "status_name", default = "open")]
+    pub status: TaskStatus,
+    /// Assignee, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// Task priority.
+    #[serde(with = "priority_name", default)]
+    pub priority: TaskPriority,
+    /// Due date (UTC), if any.
+    #[serde(with = "due_date", default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Labels, as given; the importer normalizes them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Description (empty if none).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+impl TaskRecord {
+    /// The record for a task's current field values.
+    #[must_use]
+    pub fn from_task(task: &Task) -> Self {
+        Self {
+            title: task.title.value.clone(),
+            status: task.status.value,
+            assignee: task.assignee.value.clone(),
+            priority: task.priority.value,
+            due: task
+                .due
+                .value
+                .and_then(|ms| i64::try_from(ms).ok())
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|due| due.date_naive()),
+            labels: task.label_names().map(str::to_string).collect(),
+            description: task.description.value.clone(),
+        }
+    }
+}
+
+const fn open() -> TaskStatus {
+    TaskStatus::Open
+}
+
+/// Parses a status a task list may hold: anything but `deleted`.
+fn parse_status(s: &str) -> Result<TaskStatus, String> {
+    match s.parse()? {
+        TaskStatus::Deleted => Err("deleted tasks cannot be imported".to_string()),
+        status => Ok(status),
+    }
+}
+
+/// Splits a labels cell on whitespace and commas.
+fn parse_labels(s: &str) -> Vec<String> {
+    s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|label| !label.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Writes `records` as `format`.
+///
+/// # Errors
+///
+/// Returns [`TaskListError::Json`] if JSON encoding fails.
+pub fn export(records: &[TaskRecord], format: TaskListFormat) -> Result<String, TaskListError> {
+    match format {
+        TaskListFormat::Json => Ok(serde_json::to_string_pretty(records)?),
+        TaskListFormat::Csv => Ok(export_csv(records)),
+        TaskListFormat::Markdown => Ok(export_markdown(records)),
+    }
+}
+
+/// Reads the records of a task list in `format`.
+///
+/// Records are checked for well-formed fields only; titles, labels and
+/// descriptions are validated when the tasks are created.
+///
+/// # Errors
+///
+/// Returns [`TaskListError::Json`] or [`TaskListError::Parse`] if the list
+/// is malformed.
+pub fn import(text: &str, format: TaskListFormat) -> Result<Vec<TaskRecord>, TaskListError> {
+    match format {
+        TaskListFormat::Json => Ok(serde_json::from_str(text)?),
+        TaskListFormat::Csv => import_csv(text),
+        TaskListFormat::Markdown => Ok(import_markdown(text)),
+    }
+}
+
+fn export_csv(records: &[TaskRecord]) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+    for record in records {
+        let cells = [
+            record.title.clone(),
+            record.status.to_string(),
+            record.assignee.clone().unwrap_or_default(),
+            record.priority.to_string(),
+            record.due.map(|due| due.to_string()).unwrap_or_default(),
+            record.labels.join(" "),
+            record.description.clone(),
+        ];
+        let cells: Vec<String> = cells.iter().map(|cell| csv_cell(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes a CSV cell if it contains a separator, quote or line break.
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn import_csv(text: &str) -> Result<Vec<TaskRecord>, TaskListError> {
+    let mut rows = parse_csv(text)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|cell| cell.trim().eq_ignore_ascii_case(name))
+    };
+    let Some(title) = column("title") else {
+        return Err(TaskListError::Parse {
+            line: 1,
+            reason: "missing title column".to_string(),
+        });
+    };
+    let [status, assignee, priority, due, labels, description] = [
+        "status",
+        "assignee",
+        "priority",
+        "due",
+        "labels",
+        "description",
+    ]
+    .map(column);
+
+    rows.map(|(line, row)| {
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map_or("", |cell| cell.trim())
+        };
+        Ok(TaskRecord {
+            title: cell(Some(title)).to_string(),
+            status: parse_cell(cell(status), line, parse_status)?.unwrap_or(TaskStatus::Open),
+            assignee: Some(cell(assignee).trim_start_matches('@'))
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            priority: parse_cell(cell(priority), line, str::parse)?.unwrap_or_default(),
+            due: parse_cell(cell(due), line, |s| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .map_err(|e| format!("invalid due date {s:?}: {e}"))
+            })?,
+            labels: parse_labels(cell(labels)),
+            description: cell(description).to_string(),
+        })
+    })
+    .collect()
+}
+
+/// Parses a non-empty CSV cell, reporting failures against `line`.
+fn parse_cell<T>(
+    cell: &str,
+    line: usize,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Option<T>, TaskListError> {
+    if cell.is_empty() {
+        return Ok(None);
+    }
+    parse(cell)
+        .map(Some)
+        .map_err(|reason| TaskListError::Parse { line, reason })
+}
+
+/// Splits CSV text into rows of cells, each with the line it starts on.
+/// Quoted cells may contain separators, doubled quotes and line breaks;
+/// blank lines are skipped.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, TaskListError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let (mut line, mut row_line) = (1, 1);
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    cell.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut cell)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            _ => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(TaskListError::Parse {
+            line: row_line,
+            reason: "unterminated quoted cell".to_string(),
+        });
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    rows.retain(|(_, row)| !matches!(row.as_slice(), [cell] if cell.trim().is_empty()));
+    Ok(rows)
+}
+
+/// Markdown section heading for a status.
+const fn section_heading(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Open => "Open",
+        TaskStatus::InProgress => "In progress",
+        TaskStatus::Completed => "Completed",
+        TaskStatus::Deleted => "Deleted",
+    }
+}
+
+fn export_markdown(records: &[TaskRecord]) -> String {
+    let mut out = String::new();
+    for status in MARKDOWN_SECTIONS {
+        let mut section = records.iter().filter(|r| r.status == status).peekable();
+        if section.peek().is_none() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "## {}\n", section_heading(status));
+        let mark = if status == TaskStatus::Completed {
+            'x'
+        } else {
+            ' '
+        };
+        for record in section {
+            let _ = write!(out, "- [{mark}] {}", record.title);
+            if let Some(assignee) = &record.assignee {
+                let _ = write!(out, " (@{assignee})");
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Reads every checklist item (`- [ ]`, `- [x]`, or with `*`) as a task.
+/// Checked items are completed; unchecked ones take the status of the
+/// section heading they are under, open by default. Other lines are
+/// ignored.
+fn import_markdown(text: &str) -> Vec<TaskRecord> {
+    let mut section = TaskStatus::Open;
+    let mut records = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') {
+            let heading = line.trim_start_matches('#').trim();
+            section = MARKDOWN_SECTIONS
+                .into_iter()
+                .find(|&status| heading.eq_ignore_ascii_case(section_heading(status)))
+                .unwrap_or(TaskStatus::Open);
+            continue;
+        }
+        let Some(item) = line
+            .strip_prefix("- [")
+            .or_else(|| line.strip_prefix("* ["))
+        else {
+            continue;
+        };
+        let Some((mark, rest)) = item.split_once(']') else {
+            continue;
+        };
+        let status = match mark {
+            "x" | "X" => TaskStatus::Completed,
+            " " | "" if section == TaskStatus::Completed => TaskStatus::Open,
+            " " | "" => section,
+            _ => continue,
+        };
+        let rest = rest.trim();
+        let (title, assignee) = rest
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once(" (@"))
+            .map_or((rest, None), |(title, name)| {
+                (title, Some(name.to_string()))
+            });
+        records.push(TaskRecord {
+            title: title.trim().to_string(),
+            status,
+            assignee,
+            priority: TaskPriority::Normal,
+            due: None,
+            labels: Vec::new(),
+            description: String::new(),
+        });
+    }
+    records
+}
+
+/// Serde adapter writing a [`TaskStatus`] by name, e.g. `"in_progress"`.
+mod status_name {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use termchat_proto::task::TaskStatus;
+
+    #[allow(clippy::trivially_copy_pass_by_ref)] // serde's signature
+    pub fn serialize<S: Serializer>(status: &TaskStatus, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(status)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TaskStatus, D::Error> {
+        super::parse_status(&String::deserialize(d)?).map_err(D::Error::custom)
+    }
+}
+
+/// Serde adapter writing a [`TaskPriority`] by name, e.g. `"high"`.
+mod priority_name {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use termchat_proto::task::TaskPriority;
+
+    #[allow(clippy::trivially_copy_pass_by_ref)] // serde's signature
+    pub fn serialize<S: Serializer>(priority: &TaskPriority, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(priority)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TaskPriority, D::Error> {
+        String::deserialize(d)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Serde adapter writing an optional due date as `"YYYY-MM-DD"`.
+mod due_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)] // serde's signature
+    pub fn serialize<S: Serializer>(due: &Option<NaiveDate>, s: S) -> Result<S::Ok, S::Error> {
+        match due {
+            Some(due) => s.collect_str(due),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<NaiveDate>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|due| NaiveDate::parse_from_str(&due, "%Y-%m-%d").map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<TaskRecord> {
+        vec![
+            TaskRecord {
+                title: "Fix login, again".to_string(),
+                status: TaskStatus::InProgress,
+                assignee: Some("alice".to_string()),
+                priority: TaskPriority::High,
+                due: NaiveDate::from_ymd_opt(2026, 3, 1),
+                labels: vec!["auth".to_string(), "bug".to_string()],
+                description: "Says \"expired\"\nafter 5 minutes".to_string(),
+            },
+            TaskRecord {
+                title: "Write release notes".to_string(),
+                status: TaskStatus::Completed,
+                assignee: None,
+                priority: TaskPriority::Normal,
+                due: None,
+                labels: Vec::new(),
+                description: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn json_and_csv_round_trip() {
+        for format in [TaskListFormat::Json, TaskListFormat::Csv] {
+            let text = export(&records(), format).unwrap();
+            assert_eq!(import(&text, format).unwrap(), records(), "{format}");
+        }
+    }
+
+    #[test]
+    fn markdown_round_trips_titles_status_and_assignee() {
+        let text = export(&records(), TaskListFormat::Markdown).unwrap();
+        assert_eq!(
+            text,
+            "## In progress\n\n- [ ] Fix login, again (@alice)\n\n\
+             ## Completed\n\n- [x] Write release notes\n"
+        );
+        let imported = import(&text, TaskListFormat::Markdown).unwrap();
+        let summary: Vec<(&str, TaskStatus, Option<&str>)> = imported
+            .iter()
+            .map(|r| (r.title.as_str(), r.status, r.assignee.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Fix login, again", TaskStatus::InProgress, Some("alice")),
+                ("Write release notes", TaskStatus::Completed, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_import_reads_plain_checklists() {
+        let text = "# Sprint\n\nSome notes.\n\n* [X] Done thing\n- [ ] Todo (@bob)\n- not a task\n";
+        let imported = import(text, TaskListFormat::Markdown).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].status, TaskStatus::Completed);
+        assert_eq!(imported[1].status, TaskStatus::Open);
+        assert_eq!(imported[1].assignee.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn csv_import_reads_tracker_columns() {
+        let text = "ID,Title,Assignee,Status,Labels\r\n\
+                    17,Triage,@carol,completed,\"ui, backend\"\r\n\
+                    \r\n\
+                    18,Plan,,,\r\n";
+        let imported = import(text, TaskListFormat::Csv).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].assignee.as_deref(), Some("carol"));
+        assert_eq!(imported[0].status, TaskStatus::Completed);
+        assert_eq!(imported[0].labels, ["ui", "backend"]);
+        assert_eq!(imported[1].status, TaskStatus::Open);
+        assert_eq!(imported[1].assignee, None);
+    }
+
+    #[test]
+    fn malformed_lists_are_rejected() {
+        let err = import("name\nx\n", TaskListFormat::Csv).unwrap_err();
+        assert!(matches!(err, TaskListError::Parse { line: 1, .. }));
+        let err = import("title,status\nA,open\nB,deleted\n", TaskListFormat::Csv).unwrap_err();
+        assert!(matches!(err, TaskListError::Parse { line: 3, .. }));
+        assert!(import("title\n\"unterminated\n", TaskListFormat::Csv).is_err());
+        assert!(import(r#"[{"title":"A","due":"soon"}]"#, TaskListFormat::Json).is_err());
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            TaskListFormat::from_path(Path::new("out/tasks.MD")),
+            Some(TaskListFormat::Markdown)
+        );
+        assert_eq!(
+            TaskListFormat::from_path(Path::new("tasks.csv")),
+            Some(TaskListFormat::Csv)
+        );
+        assert_eq!(TaskListFormat::from_path(Path::new("tasks.txt")), None);
+        assert_eq!(TaskListFormat::from_path(Path::new("tasks")), None);
+    }
+}
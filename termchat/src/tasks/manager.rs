@@ -3,35 +3,103 @@
 //! `TaskManager` provides the application-layer interface for creating,
 //! updating, deleting, and synchronizing tasks within rooms.
 
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use chrono::{DateTime, NaiveTime, Utc};
+use termchat_proto::room::{MemberInfo, MemberRole};
 use termchat_proto::task::{
-    LwwRegister, MAX_TASK_TITLE_LENGTH, Task, TaskFieldUpdate, TaskId, TaskStatus, TaskSyncMessage,
+    CommentId, LwwRegister, MAX_TASK_COMMENT_LENGTH, MAX_TASK_DESCRIPTION_LENGTH,
+    MAX_TASK_LABEL_LENGTH, MAX_TASK_TITLE_LENGTH, Recurrence, TASK_DIGEST_BUCKETS, Task,
+    TaskComment, TaskFieldUpdate, TaskId, TaskPriority, TaskStatus, TaskSyncMessage, digest_bucket,
+    room_digest,
 };
 
 use super::TaskError;
-use super::merge::{apply_field_update, merge_task_list};
+use super::activity::{self, ActivityEntry, MAX_ACTIVITY_ENTRIES};
+use super::board::{self, BoardFilter};
+use super::clock::HybridClock;
+use super::graph::{self, TaskGraph};
+use super::interchange::{self, TaskListError, TaskListFormat, TaskRecord};
+use super::merge::{adopt_origin, apply_field_update, merge_task_list, task_field_updates};
+use super::permissions::{PermissionWarning, RoomPermissions, TaskAction};
+use super::recurrence;
+use super::store::{self, TaskStore, TaskStoreError};
+use crate::transport::PeerId;
 
 /// Manages room-scoped task lists with CRDT-based synchronization.
 ///
 /// Each room has its own independent task map. All mutations generate
 /// [`TaskSyncMessage`] values that should be broadcast to room members.
+/// A manager created with [`open`](Self::open) also writes every change,
+/// local or remote, to its [`TaskStore`].
+///
+/// Once a room's members are registered with
+/// [`set_members`](Self::set_members), every local change to its tasks
+/// can also fail with [`TaskError::PermissionDenied`].
 pub struct TaskManager {
     /// Room ID -> (Task ID -> Task) mapping.
     tasks: HashMap<String, HashMap<TaskId, Task>>,
     /// The local peer's identifier, used as author in LWW registers.
     local_peer_id: String,
+    /// Clock stamping every local LWW register write.
+    clock: HybridClock,
+    /// Where room state is persisted, if anywhere.
+    store: Option<TaskStore>,
+    /// Task ID -> changes applied to it, oldest first.
+    activity: HashMap<TaskId, Vec<ActivityEntry>>,
+    /// Task ID -> the due time it was last reminded about.
+    reminded: HashMap<TaskId, u64>,
+    /// Room ID -> member roles; rooms without an entry are unrestricted.
+    permissions: HashMap<String, RoomPermissions>,
+    /// Remote changes already reported as rejected, as (task, timestamp,
+    /// author), so a change that keeps arriving is reported once. Cleared
+    /// when it reaches [`MAX_REJECTED_REPORTS`].
+    rejected: HashSet<(TaskId, u64, String)>,
 }
 
+/// How many rejected remote changes are remembered for reporting each
+/// once. Past that the memory starts over, so a peer that keeps sending
+/// disallowed changes can cost a repeated warning but not unbounded memory.
+const MAX_REJECTED_REPORTS: usize = 1024;
+
 impl TaskManager {
     /// Creates a new `TaskManager` for the given local peer.
     #[must_use]
     pub fn new(local_peer_id: String) -> Self {
+        Self::with_clock(local_peer_id, HybridClock::new())
+    }
+
+    /// Creates a new `TaskManager` stamping writes with the given clock.
+    #[must_use]
+    pub fn with_clock(local_peer_id: String, clock: HybridClock) -> Self {
         Self {
             tasks: HashMap::new(),
             local_peer_id,
+            clock,
+            store: None,
+            activity: HashMap::new(),
+            reminded: HashMap::new(),
+            permissions: HashMap::new(),
+            rejected: HashSet::new(),
+        }
+    }
+
+    /// Creates a `TaskManager` that loads every room from `store` and
+    /// persists each later change back to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError`] if a stored room cannot be read.
+    pub fn open(local_peer_id: String, store: TaskStore) -> Result<Self, TaskStoreError> {
+        let local = PeerId::new(local_peer_id.clone());
+        let mut manager = Self::new(local_peer_id);
+        for (room_id, tasks) in store.load_all()? {
+            manager.apply_remote(&local, &TaskSyncMessage::FullState { room_id, tasks });
         }
+        manager.store = Some(store);
+        Ok(manager)
     }
 
     /// Returns the current timestamp in milliseconds since epoch.
@@ -45,37 +113,59 @@ impl TaskManager {
         .unwrap_or(u64::MAX)
     }
 
+    /// `PeerId` local changes are authored by.
+    #[must_use]
+    pub fn local_peer_id(&self) -> &str {
+        &self.local_peer_id
+    }
+
+    /// Restricts changes to a room's tasks to `members`, by role (see
+    /// [`permissions`](super::permissions)), replacing any earlier member
+    /// list. Until this is called for a room, its tasks are unrestricted;
+    /// call it again whenever the room's members or their roles change.
+    pub fn set_members(&mut self, room_id: &str, members: &[MemberInfo]) {
+        self.permissions
+            .insert(room_id.to_string(), RoomPermissions::new(members));
+    }
+
     /// Creates a new task in the given room.
     ///
     /// # Errors
     ///
-    /// Returns [`TaskError::TitleEmpty`] if the title is empty, or
-    /// [`TaskError::TitleTooLong`] if it exceeds 256 characters.
+    /// Returns [`TaskError::TitleEmpty`] if the title is empty,
+    /// [`TaskError::TitleTooLong`] if it exceeds 256 characters, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not create
+    /// tasks in the room.
     pub fn create_task(
         &mut self,
         room_id: &str,
         title: &str,
     ) -> Result<(Task, TaskSyncMessage), TaskError> {
-        if title.is_empty() {
-            return Err(TaskError::TitleEmpty);
-        }
-        if title.chars().count() > MAX_TASK_TITLE_LENGTH {
-            return Err(TaskError::TitleTooLong);
-        }
+        validate_title(title)?;
+        self.authorize(room_id, None, TaskAction::Create)?;
 
-        let now = Self::now_ms();
+        let now = self.clock.now();
         let task = Task {
             id: TaskId::new(),
             room_id: room_id.to_string(),
             title: LwwRegister::new(title.to_string(), now, self.local_peer_id.clone()),
             status: LwwRegister::new(TaskStatus::Open, now, self.local_peer_id.clone()),
             assignee: LwwRegister::new(None, now, self.local_peer_id.clone()),
-            created_at: now,
+            description: LwwRegister::new(String::new(), now, self.local_peer_id.clone()),
+            due: LwwRegister::new(None, now, self.local_peer_id.clone()),
+            recurrence: LwwRegister::new(None, now, self.local_peer_id.clone()),
+            priority: LwwRegister::new(TaskPriority::Normal, now, self.local_peer_id.clone()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, now, self.local_peer_id.clone()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            created_at: Self::now_ms(),
             created_by: self.local_peer_id.clone(),
         };
 
         let room_tasks = self.tasks.entry(room_id.to_string()).or_default();
         room_tasks.insert(task.id.clone(), task.clone());
+        self.persist(room_id);
 
         let msg = TaskSyncMessage::FullState {
             room_id: room_id.to_string(),
@@ -85,27 +175,71 @@ impl TaskManager {
         Ok((task, msg))
     }
 
+    /// Renames a task.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::TitleEmpty`] or [`TaskError::TitleTooLong`] for
+    /// an invalid title, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn update_title(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        title: &str,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        validate_title(title)?;
+        let title = title.to_string();
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Title(LwwRegister::new(title, now, peer_id))
+        })
+    }
+
     /// Updates the status of a task.
     ///
+    /// Completing a task with a [`Recurrence`] also creates its next
+    /// instance (see [`recurrence`]); the returned
+    /// message is then a [`TaskSyncMessage::FullState`] carrying both tasks.
+    ///
     /// # Errors
     ///
     /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
-    /// if the room or task does not exist.
+    /// if the room or task does not exist, and
+    /// [`TaskError::PermissionDenied`] if the local peer may not make the
+    /// change or, when completing a recurring task, create its next
+    /// instance.
     pub fn update_status(
         &mut self,
         room_id: &str,
         task_id: &TaskId,
         new_status: TaskStatus,
     ) -> Result<TaskSyncMessage, TaskError> {
-        let now = Self::now_ms();
-        let peer_id = self.local_peer_id.clone();
         let task = self.get_task_mut(room_id, task_id)?;
-        task.status = LwwRegister::new(new_status, now, peer_id.clone());
-
-        Ok(TaskSyncMessage::FieldUpdate {
-            task_id: task_id.clone(),
+        let was_completed = task.status.value == TaskStatus::Completed;
+        if new_status == TaskStatus::Completed && !was_completed && task.recurrence.value.is_some()
+        {
+            self.authorize(room_id, None, TaskAction::Create)?;
+        }
+        let msg = self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Status(LwwRegister::new(new_status, now, peer_id))
+        })?;
+        if new_status != TaskStatus::Completed || was_completed {
+            return Ok(msg);
+        }
+        let completed = self.get_task_mut(room_id, task_id)?.clone();
+        let Some(next) = self.next_instance(&completed) else {
+            return Ok(msg);
+        };
+        self.tasks
+            .entry(room_id.to_string())
+            .or_default()
+            .insert(next.id.clone(), next.clone());
+        self.persist(room_id);
+        Ok(TaskSyncMessage::FullState {
             room_id: room_id.to_string(),
-            field: TaskFieldUpdate::Status(LwwRegister::new(new_status, now, peer_id)),
+            tasks: vec![completed, next],
         })
     }
 
@@ -114,31 +248,270 @@ impl TaskManager {
     /// # Errors
     ///
     /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
-    /// if the room or task does not exist.
+    /// if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not reassign
+    /// the task.
     pub fn update_assignee(
         &mut self,
         room_id: &str,
         task_id: &TaskId,
         assignee: Option<String>,
     ) -> Result<TaskSyncMessage, TaskError> {
-        let now = Self::now_ms();
-        let peer_id = self.local_peer_id.clone();
-        let task = self.get_task_mut(room_id, task_id)?;
-        task.assignee = LwwRegister::new(assignee.clone(), now, peer_id.clone());
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Assignee(LwwRegister::new(assignee, now, peer_id))
+        })
+    }
 
-        Ok(TaskSyncMessage::FieldUpdate {
-            task_id: task_id.clone(),
-            room_id: room_id.to_string(),
-            field: TaskFieldUpdate::Assignee(LwwRegister::new(assignee, now, peer_id)),
+    /// Replaces the description of a task. An empty description clears it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::DescriptionTooLong`] if it exceeds 4096
+    /// characters, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn update_description(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        description: &str,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        if description.chars().count() > MAX_TASK_DESCRIPTION_LENGTH {
+            return Err(TaskError::DescriptionTooLong);
+        }
+        let description = description.to_string();
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Description(LwwRegister::new(description, now, peer_id))
+        })
+    }
+
+    /// Sets or clears the due date of a task (milliseconds since epoch).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
+    /// if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn update_due(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        due: Option<u64>,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Due(LwwRegister::new(due, now, peer_id))
+        })
+    }
+
+    /// Sets or clears how a task repeats.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::InvalidRecurrence`] for a cron expression that
+    /// does not parse, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn set_recurrence(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        rule: Option<Recurrence>,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        if let Some(rule) = &rule {
+            recurrence::validate(rule).map_err(TaskError::InvalidRecurrence)?;
+        }
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Recurrence(LwwRegister::new(rule, now, peer_id))
+        })
+    }
+
+    /// Updates the priority of a task.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
+    /// if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn update_priority(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        priority: TaskPriority,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Priority(LwwRegister::new(priority, now, peer_id))
+        })
+    }
+
+    /// Adds a label to a task. Labels are trimmed and lowercased.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::InvalidLabel`] for an empty, overlong or
+    /// whitespace-containing label, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn add_label(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        label: &str,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.set_label(room_id, task_id, label, true)
+    }
+
+    /// Removes a label from a task. Labels are trimmed and lowercased.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`add_label`](Self::add_label).
+    pub fn remove_label(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        label: &str,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.set_label(room_id, task_id, label, false)
+    }
+
+    /// Makes a task a subtask of `parent`, or a top-level task with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::DependencyCycle`] if `parent` is the task
+    /// itself or one of its subtasks, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room, task or parent does not
+    /// exist, or [`TaskError::PermissionDenied`] if the local peer may not
+    /// edit the task.
+    pub fn set_parent(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        parent: Option<&TaskId>,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        if let Some(parent) = parent {
+            self.check_edge(room_id, task_id, parent, TaskGraph::parent_would_cycle)?;
+        }
+        let parent = parent.cloned();
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Parent(LwwRegister::new(parent, now, peer_id))
+        })
+    }
+
+    /// Marks a task as blocked by `blocker`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::DependencyCycle`] if `blocker` is the task
+    /// itself or (transitively) blocked by it,
+    /// [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`] if the
+    /// room, task or blocker does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn add_blocker(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        blocker: &TaskId,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.check_edge(room_id, task_id, blocker, TaskGraph::blocker_would_cycle)?;
+        self.set_blocker(room_id, task_id, blocker, true)
+    }
+
+    /// Removes a "blocked by" edge.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
+    /// if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not edit the
+    /// task.
+    pub fn remove_blocker(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        blocker: &TaskId,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        self.set_blocker(room_id, task_id, blocker, false)
+    }
+
+    /// Posts a comment on a task, optionally as a reply to one of its
+    /// comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskError::CommentEmpty`] or [`TaskError::CommentTooLong`]
+    /// for an invalid body, [`TaskError::CommentNotFound`] if `reply_to` is
+    /// not a comment on the task, [`TaskError::RoomNotFound`] or
+    /// [`TaskError::TaskNotFound`] if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not comment
+    /// in the room.
+    pub fn add_comment(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        body: &str,
+        reply_to: Option<&CommentId>,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        if body.trim().is_empty() {
+            return Err(TaskError::CommentEmpty);
+        }
+        if body.chars().count() > MAX_TASK_COMMENT_LENGTH {
+            return Err(TaskError::CommentTooLong);
+        }
+        let task = self.get_task_mut(room_id, task_id)?;
+        if let Some(parent) = reply_to
+            && !task.comments.contains_key(parent)
+        {
+            return Err(TaskError::CommentNotFound(parent.to_string()));
+        }
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::Comment(TaskComment {
+                id: CommentId::new(),
+                reply_to: reply_to.cloned(),
+                author: peer_id,
+                body: body.to_string(),
+                timestamp: now,
+            })
         })
     }
 
+    /// A task's comments in thread order, each with its reply depth (see
+    /// [`activity::comment_thread`]). Empty if the task does not exist.
+    #[must_use]
+    pub fn comment_thread(&self, room_id: &str, task_id: &TaskId) -> Vec<(usize, &TaskComment)> {
+        self.tasks
+            .get(room_id)
+            .and_then(|room_tasks| room_tasks.get(task_id))
+            .map(|task| {
+                activity::comment_thread(
+                    task.comments.values(),
+                    |comment| &comment.id,
+                    |comment| comment.reply_to.as_ref(),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Changes applied to a task since this manager started, oldest first.
+    #[must_use]
+    pub fn activity(&self, task_id: &TaskId) -> &[ActivityEntry] {
+        self.activity.get(task_id).map_or(&[], Vec::as_slice)
+    }
+
     /// Soft-deletes a task by setting its status to [`TaskStatus::Deleted`].
     ///
     /// # Errors
     ///
     /// Returns [`TaskError::RoomNotFound`] or [`TaskError::TaskNotFound`]
-    /// if the room or task does not exist.
+    /// if the room or task does not exist, or
+    /// [`TaskError::PermissionDenied`] if the local peer may not delete
+    /// the task.
     pub fn delete_task(
         &mut self,
         room_id: &str,
@@ -156,9 +529,31 @@ impl TaskManager {
     /// For [`TaskSyncMessage::FullState`]: merges all remote tasks into
     /// the local state using [`merge_task_list`].
     ///
-    /// For [`TaskSyncMessage::RequestFullState`]: no-op (caller should
-    /// use [`build_full_state`](Self::build_full_state) to respond).
-    pub fn apply_remote(&mut self, msg: &TaskSyncMessage) {
+    /// For [`TaskSyncMessage::RequestFullState`],
+    /// [`TaskSyncMessage::Digest`] and [`TaskSyncMessage::RequestBuckets`]:
+    /// no-op (caller should use [`reply_to`](Self::reply_to) to respond).
+    ///
+    /// `sender` is the peer the message came from, as reported by the
+    /// transport; it is what permissions are checked against, never an
+    /// author named inside the message. In a room with registered members
+    /// (see [`set_members`](Self::set_members)), a field update must be
+    /// written by its sender and allowed by the sender's role, judged
+    /// against the local copy of the task. A full state is checked field
+    /// by field: fields written by another peer are only taken from an
+    /// admin, and skipped without a warning otherwise, since members pass
+    /// each other's writes on during anti-entropy. Changes the sender may
+    /// not make are left out and returned as warnings, each reported once.
+    ///
+    /// Every accepted remote timestamp advances the local clock, so later
+    /// local writes win over what was just applied.
+    pub fn apply_remote(
+        &mut self,
+        sender: &PeerId,
+        msg: &TaskSyncMessage,
+    ) -> Vec<PermissionWarning> {
+        let sender = sender.as_str();
+        let mut rejected: Vec<Rejected<'_>> = Vec::new();
+        let mut accepted = Vec::new();
         match msg {
             TaskSyncMessage::FieldUpdate {
                 task_id,
@@ -166,39 +561,87 @@ impl TaskManager {
                 field,
             } => {
                 let room_tasks = self.tasks.entry(room_id.clone()).or_default();
-                if let Some(task) = room_tasks.get_mut(task_id) {
-                    apply_field_update(task, field);
+                let task = room_tasks.get(task_id);
+                let denied = self.permissions.get(room_id).and_then(|permissions| {
+                    if field_stamp(field).1 == sender {
+                        denied(permissions, sender, task, field)
+                    } else {
+                        Some(TaskAction::of(task, field))
+                    }
+                });
+                if let Some(action) = denied {
+                    if task.is_none_or(|task| changes(task, field)) {
+                        let timestamp = field_stamp(field).0;
+                        rejected.push((room_id, task_id, timestamp, sender.to_string(), action));
+                    }
                 } else {
-                    // Add-wins: create a stub task from the field update
-                    let (timestamp, author) = match field {
-                        TaskFieldUpdate::Title(reg) => (reg.timestamp, reg.author.clone()),
-                        TaskFieldUpdate::Status(reg) => (reg.timestamp, reg.author.clone()),
-                        TaskFieldUpdate::Assignee(reg) => (reg.timestamp, reg.author.clone()),
-                    };
-                    let mut task = Task {
-                        id: task_id.clone(),
-                        room_id: room_id.clone(),
-                        title: LwwRegister::new(String::new(), 0, String::new()),
-                        status: LwwRegister::new(TaskStatus::Open, 0, String::new()),
-                        assignee: LwwRegister::new(None, 0, String::new()),
-                        created_at: timestamp,
-                        created_by: author,
-                    };
-                    apply_field_update(&mut task, field);
-                    room_tasks.insert(task_id.clone(), task);
+                    accepted.push(field_stamp(field).0);
+                    // Add-wins: an update to an unknown task creates a stub
+                    let task = room_tasks
+                        .entry(task_id.clone())
+                        .or_insert_with(|| stub_task(task_id, room_id, field));
+                    let entry = ActivityEntry::describe(task, field);
+                    if apply_field_update(task, field) {
+                        self.record(task_id, entry);
+                        self.persist(room_id);
+                    }
                 }
             }
             TaskSyncMessage::FullState { room_id, tasks } => {
                 let room_tasks = self.tasks.entry(room_id.clone()).or_default();
-                merge_task_list(room_tasks, tasks);
+                if let Some(permissions) = self.permissions.get(room_id) {
+                    accepted = merge_task_list_checked(
+                        room_tasks,
+                        permissions,
+                        (room_id, sender),
+                        tasks,
+                        &mut rejected,
+                    );
+                } else {
+                    merge_task_list(room_tasks, tasks);
+                    accepted = tasks
+                        .iter()
+                        .flat_map(task_field_updates)
+                        .map(|update| field_stamp(&update).0)
+                        .collect();
+                }
+                self.persist(room_id);
+            }
+            TaskSyncMessage::RequestFullState { .. }
+            | TaskSyncMessage::Digest { .. }
+            | TaskSyncMessage::RequestBuckets { .. } => {
+                // No-op: caller should use reply_to() to respond
+            }
+        }
+        self.observe(accepted);
+
+        let mut warnings = Vec::new();
+        for (room_id, task_id, timestamp, sender, action) in rejected {
+            let key = (task_id.clone(), timestamp, sender.clone());
+            if self.rejected.len() >= MAX_REJECTED_REPORTS && !self.rejected.contains(&key) {
+                self.rejected.clear();
             }
-            TaskSyncMessage::RequestFullState { .. } => {
-                // No-op: caller should use build_full_state() to respond
+            if self.rejected.insert(key) {
+                tracing::warn!(room_id, %task_id, sender, %action, "rejected remote task change");
+                warnings.push(PermissionWarning {
+                    room_id: room_id.to_string(),
+                    task_id: task_id.clone(),
+                    sender,
+                    action,
+                });
             }
         }
+        warnings
     }
 
-    /// Returns all non-deleted tasks in a room, sorted by creation time.
+    /// Returns a task in a room, including a deleted one.
+    #[must_use]
+    pub fn get_task(&self, room_id: &str, task_id: &TaskId) -> Option<&Task> {
+        self.tasks.get(room_id)?.get(task_id)
+    }
+
+    /// Returns all non-deleted tasks in a room, sorted by creation time
+    /// (ties broken by ID, so every call lists them in the same order).
     ///
     /// Returns an empty vec if the room has no tasks.
     #[must_use]
@@ -210,10 +653,48 @@ impl TaskManager {
             .values()
             .filter(|t| t.status.value != TaskStatus::Deleted)
             .collect();
-        tasks.sort_by_key(|t| t.created_at);
+        tasks.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
         tasks
     }
 
+    /// A room's tasks laid out in [`board::BOARD_COLUMNS`], newest first
+    /// within each column (a new card lands at the top, where the board
+    /// creates it), showing only cards that match `filter`.
+    ///
+    /// Moving a card is [`update_status`](Self::update_status) with the
+    /// target column's status.
+    #[must_use]
+    pub fn board(&self, room_id: &str, filter: &BoardFilter) -> [Vec<&Task>; 3] {
+        board::columns(self.get_tasks(room_id).into_iter().rev(), filter)
+    }
+
+    /// Tasks that are not done and whose due time is at or before
+    /// `now_ms`, oldest due first.
+    ///
+    /// Each task is returned once per due time, so moving the due date
+    /// re-arms its reminder.
+    pub fn due_reminders(&mut self, now_ms: u64) -> Vec<Task> {
+        let mut due: Vec<Task> = self
+            .tasks
+            .values()
+            .flat_map(HashMap::values)
+            .filter(|task| {
+                matches!(task.status.value, TaskStatus::Open | TaskStatus::InProgress)
+                    && task.due.value.is_some_and(|due| {
+                        due <= now_ms && self.reminded.get(&task.id) != Some(&due)
+                    })
+            })
+            .cloned()
+            .collect();
+        due.sort_by_key(|task| task.due.value);
+        for task in &due {
+            if let Some(at) = task.due.value {
+                self.reminded.insert(task.id.clone(), at);
+            }
+        }
+        due
+    }
+
     /// Builds a full state snapshot for a room, suitable for sending
     /// to a newly-joined peer.
     ///
@@ -230,75 +711,717 @@ impl TaskManager {
         })
     }
 
-    /// Returns a mutable reference to a task, or an error if not found.
-    fn get_task_mut(&mut self, room_id: &str, task_id: &TaskId) -> Result<&mut Task, TaskError> {
-        let room_tasks = self
-            .tasks
-            .get_mut(room_id)
-            .ok_or_else(|| TaskError::RoomNotFound(room_id.to_string()))?;
-        room_tasks
-            .get_mut(task_id)
-            .ok_or_else(|| TaskError::TaskNotFound(task_id.to_string()))
+    /// Rooms this manager holds tasks for, sorted.
+    #[must_use]
+    pub fn rooms(&self) -> Vec<String> {
+        let mut rooms: Vec<String> = self.tasks.keys().cloned().collect();
+        rooms.sort();
+        rooms
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    fn make_manager() -> TaskManager {
-        TaskManager::new("local-peer".to_string())
+    /// Builds a digest of a room's tasks for anti-entropy. Sent on joining
+    /// a room and periodically; peers answer only with what differs.
+    #[must_use]
+    pub fn build_digest(&self, room_id: &str) -> TaskSyncMessage {
+        TaskSyncMessage::Digest {
+            room_id: room_id.to_string(),
+            buckets: self.digest_buckets(room_id),
+        }
     }
 
-    // --- create_task tests ---
-
-    #[test]
-    fn create_task_success() {
-        let mut mgr = make_manager();
-        let (task, msg) = mgr.create_task("room-1", "Fix login bug").unwrap();
-        assert_eq!(task.title.value, "Fix login bug");
-        assert_eq!(task.status.value, TaskStatus::Open);
-        assert_eq!(task.assignee.value, None);
-        assert_eq!(task.room_id, "room-1");
-        assert_eq!(task.created_by, "local-peer");
-        assert!(matches!(msg, TaskSyncMessage::FullState { .. }));
+    fn digest_buckets(&self, room_id: &str) -> Vec<u64> {
+        room_digest(
+            self.tasks
+                .get(room_id)
+                .into_iter()
+                .flat_map(HashMap::values),
+        )
     }
 
-    #[test]
-    fn create_task_empty_title_error() {
-        let mut mgr = make_manager();
-        let err = mgr.create_task("room-1", "").unwrap_err();
-        assert_eq!(err, TaskError::TitleEmpty);
+    /// Builds the replies to a sync request from another peer.
+    ///
+    /// - [`TaskSyncMessage::RequestFullState`]: every task in the room.
+    /// - [`TaskSyncMessage::Digest`]: local tasks in the buckets whose hash
+    ///   differs, and a [`TaskSyncMessage::RequestBuckets`] for the peer's
+    ///   tasks in them. Nothing when the digests match.
+    /// - [`TaskSyncMessage::RequestBuckets`]: local tasks in the requested
+    ///   buckets.
+    ///
+    /// Other messages need no reply. Empty task sets are not sent.
+    #[must_use]
+    pub fn reply_to(&self, msg: &TaskSyncMessage) -> Vec<TaskSyncMessage> {
+        match msg {
+            TaskSyncMessage::RequestFullState { room_id } => {
+                self.build_full_state(room_id).into_iter().collect()
+            }
+            TaskSyncMessage::Digest { room_id, buckets } => {
+                let local = self.digest_buckets(room_id);
+                let differing: BTreeSet<usize> = (0..TASK_DIGEST_BUCKETS)
+                    .filter(|&i| buckets.get(i) != Some(&local[i]))
+                    .collect();
+                if differing.is_empty() {
+                    return Vec::new();
+                }
+                let mut replies: Vec<TaskSyncMessage> =
+                    self.bucket_state(room_id, &differing).into_iter().collect();
+                replies.push(TaskSyncMessage::RequestBuckets {
+                    room_id: room_id.clone(),
+                    buckets: differing
+                        .iter()
+                        .filter_map(|&i| u16::try_from(i).ok())
+                        .collect(),
+                });
+                replies
+            }
+            TaskSyncMessage::RequestBuckets { room_id, buckets } => {
+                let buckets = buckets.iter().map(|&i| usize::from(i)).collect();
+                self.bucket_state(room_id, &buckets).into_iter().collect()
+            }
+            TaskSyncMessage::FieldUpdate { .. } | TaskSyncMessage::FullState { .. } => Vec::new(),
+        }
     }
 
-    #[test]
-    fn create_task_title_too_long_error() {
-        let mut mgr = make_manager();
-        let long_title = "x".repeat(257);
-        let err = mgr.create_task("room-1", &long_title).unwrap_err();
-        assert_eq!(err, TaskError::TitleTooLong);
+    /// The room's tasks in the given digest buckets, or `None` if there
+    /// are none.
+    fn bucket_state(&self, room_id: &str, buckets: &BTreeSet<usize>) -> Option<TaskSyncMessage> {
+        let tasks: Vec<Task> = self
+            .tasks
+            .get(room_id)?
+            .values()
+            .filter(|task| buckets.contains(&digest_bucket(&task.id)))
+            .cloned()
+            .collect();
+        if tasks.is_empty() {
+            return None;
+        }
+        Some(TaskSyncMessage::FullState {
+            room_id: room_id.to_string(),
+            tasks,
+        })
     }
 
-    #[test]
-    fn create_task_max_length_title_ok() {
-        let mut mgr = make_manager();
-        let title = "x".repeat(256);
-        let result = mgr.create_task("room-1", &title);
-        assert!(result.is_ok());
+    /// Builds the effective subtask and blocker graph of a room.
+    ///
+    /// Edges that concurrent remote edits turned into a cycle are
+    /// resolved the same way on every peer; see [`graph`].
+    #[must_use]
+    pub fn graph(&self, room_id: &str) -> TaskGraph {
+        self.tasks
+            .get(room_id)
+            .map(|room_tasks| TaskGraph::build(room_tasks.values()))
+            .unwrap_or_default()
     }
 
-    #[test]
-    fn create_task_appears_in_get_tasks() {
-        let mut mgr = make_manager();
-        mgr.create_task("room-1", "Task A").unwrap();
-        mgr.create_task("room-1", "Task B").unwrap();
-        let tasks = mgr.get_tasks("room-1");
-        assert_eq!(tasks.len(), 2);
+    /// Roll-up progress of a task's subtasks as (completed, total), or
+    /// `None` if it has none.
+    #[must_use]
+    pub fn subtask_progress(&self, room_id: &str, task_id: &TaskId) -> Option<(usize, usize)> {
+        let room_tasks = self.tasks.get(room_id)?;
+        graph::subtask_progress(&self.graph(room_id), room_tasks, task_id)
     }
 
-    // --- update_status tests ---
-
-    #[test]
+    /// Blockers of a task that are not completed yet.
+    #[must_use]
+    pub fn open_blockers(&self, room_id: &str, task_id: &TaskId) -> Vec<TaskId> {
+        let Some(room_tasks) = self.tasks.get(room_id) else {
+            return Vec::new();
+        };
+        graph::open_blockers(&self.graph(room_id), room_tasks, task_id)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Drops deleted tasks whose deletion is older than `horizon`, along
+    /// with blocker edges pointing at them, and returns how many were
+    /// dropped.
+    ///
+    /// A tombstone is what stops a peer that missed the deletion from
+    /// resurrecting the task on its next sync, so `horizon` must exceed
+    /// the longest time a room member can stay offline. A peer that
+    /// reconnects later still sends the task back as live.
+    pub fn compact_tombstones(&mut self, horizon: Duration) -> usize {
+        let horizon_ms = u64::try_from(horizon.as_millis()).unwrap_or(u64::MAX);
+        let cutoff = HybridClock::physical_ms(self.clock.now()).saturating_sub(horizon_ms);
+        let mut compacted = Vec::new();
+        let mut purged = 0;
+        for (room_id, room_tasks) in &mut self.tasks {
+            let expired: BTreeSet<TaskId> = room_tasks
+                .values()
+                .filter(|t| {
+                    t.status.value == TaskStatus::Deleted
+                        && HybridClock::physical_ms(t.status.timestamp) <= cutoff
+                })
+                .map(|t| t.id.clone())
+                .collect();
+            if expired.is_empty() {
+                continue;
+            }
+            room_tasks.retain(|id, _| !expired.contains(id));
+            self.activity.retain(|id, _| !expired.contains(id));
+            self.reminded.retain(|id, _| !expired.contains(id));
+            self.rejected.retain(|(id, ..)| !expired.contains(id));
+            for task in room_tasks.values_mut() {
+                task.blocked_by
+                    .retain(|blocker, _| !expired.contains(blocker));
+            }
+            purged += expired.len();
+            compacted.push(room_id.clone());
+        }
+        for room_id in compacted {
+            self.persist(&room_id);
+        }
+        purged
+    }
+
+    /// Exports a room's full task state, tombstones included, as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError::Json`] if encoding fails.
+    pub fn export_json(&self, room_id: &str) -> Result<String, TaskStoreError> {
+        let mut tasks: Vec<&Task> = self
+            .tasks
+            .get(room_id)
+            .map(|room_tasks| room_tasks.values().collect())
+            .unwrap_or_default();
+        tasks.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        store::export_json(room_id, tasks)
+    }
+
+    /// Merges a JSON export into its room, as if the local peer had sent
+    /// the room's full state, and returns that state to broadcast.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError`] if the JSON is malformed or was written
+    /// by an unknown format version.
+    pub fn import_json(&mut self, json: &str) -> Result<TaskSyncMessage, TaskStoreError> {
+        let (room_id, tasks) = store::import_json(json)?;
+        let msg = TaskSyncMessage::FullState { room_id, tasks };
+        let local = PeerId::new(self.local_peer_id.clone());
+        self.apply_remote(&local, &msg);
+        Ok(msg)
+    }
+
+    /// Exports a room's live tasks as a plain task list (see
+    /// [`interchange`]), oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskListError::Json`] if JSON encoding fails.
+    pub fn export_tasks(
+        &self,
+        room_id: &str,
+        format: TaskListFormat,
+    ) -> Result<String, TaskListError> {
+        let records: Vec<TaskRecord> = self
+            .get_tasks(room_id)
+            .into_iter()
+            .map(TaskRecord::from_task)
+            .collect();
+        interchange::export(&records, format)
+    }
+
+    /// Creates a task in `room_id` for every entry of a plain task list,
+    /// keeping each entry's status, assignee, priority, due date, labels
+    /// and description, and returns the new tasks as one
+    /// [`TaskSyncMessage::FullState`] to broadcast.
+    ///
+    /// Every entry is validated first, so an invalid one creates nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskListError::Json`] or [`TaskListError::Parse`] if the
+    /// list is malformed, or [`TaskListError::Invalid`] for an entry with
+    /// an invalid title, description or label, or one the local peer may
+    /// not create ([`TaskError::PermissionDenied`]).
+    pub fn import_tasks(
+        &mut self,
+        room_id: &str,
+        text: &str,
+        format: TaskListFormat,
+    ) -> Result<TaskSyncMessage, TaskListError> {
+        let records = interchange::import(text, format)?;
+        for (index, record) in records.iter().enumerate() {
+            validate_record(record).map_err(|source| TaskListError::Invalid {
+                index: index + 1,
+                source,
+            })?;
+        }
+        let mut tasks = Vec::with_capacity(records.len());
+        for (index, record) in records.into_iter().enumerate() {
+            let task = self.create_from_record(room_id, record).map_err(|source| {
+                TaskListError::Invalid {
+                    index: index + 1,
+                    source,
+                }
+            })?;
+            tasks.push(task);
+        }
+        Ok(TaskSyncMessage::FullState {
+            room_id: room_id.to_string(),
+            tasks,
+        })
+    }
+
+    /// Creates one imported task and sets the fields that differ from a
+    /// new task's, returning the resulting state.
+    fn create_from_record(&mut self, room_id: &str, record: TaskRecord) -> Result<Task, TaskError> {
+        let (task, _) = self.create_task(room_id, &record.title)?;
+        let id = &task.id;
+        if record.status != TaskStatus::Open {
+            self.update_status(room_id, id, record.status)?;
+        }
+        if record.assignee.is_some() {
+            self.update_assignee(room_id, id, record.assignee)?;
+        }
+        if record.priority != TaskPriority::Normal {
+            self.update_priority(room_id, id, record.priority)?;
+        }
+        if let Some(due) = record.due {
+            let due = u64::try_from(due.and_time(NaiveTime::MIN).and_utc().timestamp_millis())
+                .unwrap_or_default();
+            self.update_due(room_id, id, Some(due))?;
+        }
+        for label in &record.labels {
+            self.add_label(room_id, id, label)?;
+        }
+        if !record.description.is_empty() {
+            self.update_description(room_id, id, &record.description)?;
+        }
+        Ok(self.get_task_mut(room_id, id)?.clone())
+    }
+
+    /// The task that follows a completed recurring one, unless it repeats
+    /// no more, its rule cannot be evaluated or the next instance already
+    /// exists (a task completed, reopened and completed again).
+    fn next_instance(&mut self, task: &Task) -> Option<Task> {
+        let rule = task.recurrence.value.clone()?;
+        let id = task.id.next_instance();
+        if self
+            .tasks
+            .get(&task.room_id)
+            .is_some_and(|room_tasks| room_tasks.contains_key(&id))
+        {
+            return None;
+        }
+        let to_time = |ms: u64| DateTime::<Utc>::from_timestamp_millis(i64::try_from(ms).ok()?);
+        let due = recurrence::next_occurrence(
+            &rule,
+            task.due.value.and_then(to_time),
+            to_time(Self::now_ms())?,
+        )?;
+        let due = u64::try_from(due.timestamp_millis()).ok()?;
+
+        let now = self.clock.now();
+        let peer = &self.local_peer_id;
+        Some(Task {
+            id,
+            room_id: task.room_id.clone(),
+            title: LwwRegister::new(task.title.value.clone(), now, peer.clone()),
+            status: LwwRegister::new(TaskStatus::Open, now, peer.clone()),
+            assignee: LwwRegister::new(task.assignee.value.clone(), now, peer.clone()),
+            description: LwwRegister::new(task.description.value.clone(), now, peer.clone()),
+            due: LwwRegister::new(Some(due), now, peer.clone()),
+            recurrence: LwwRegister::new(Some(rule), now, peer.clone()),
+            priority: LwwRegister::new(task.priority.value, now, peer.clone()),
+            labels: task
+                .label_names()
+                .map(|label| (label.to_string(), LwwRegister::new(true, now, peer.clone())))
+                .collect(),
+            parent: LwwRegister::new(task.parent.value.clone(), now, peer.clone()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            created_at: Self::now_ms(),
+            created_by: self.local_peer_id.clone(),
+        })
+    }
+
+    /// Appends to a task's activity feed, dropping the oldest entries
+    /// beyond [`MAX_ACTIVITY_ENTRIES`].
+    fn record(&mut self, task_id: &TaskId, entry: ActivityEntry) {
+        let feed = self.activity.entry(task_id.clone()).or_default();
+        feed.push(entry);
+        if feed.len() > MAX_ACTIVITY_ENTRIES {
+            feed.drain(..feed.len() - MAX_ACTIVITY_ENTRIES);
+        }
+    }
+
+    /// Writes a room's state to the store, if there is one. Failures are
+    /// logged rather than returned: the in-memory state stays correct and
+    /// the next change retries the write.
+    fn persist(&self, room_id: &str) {
+        let (Some(store), Some(room_tasks)) = (&self.store, self.tasks.get(room_id)) else {
+            return;
+        };
+        if let Err(e) = store.save_room(room_id, room_tasks.values()) {
+            tracing::warn!(room_id, error = %e, "failed to persist tasks");
+        }
+    }
+
+    /// Advances the clock past `stamps`, as far as the clock's maximum
+    /// skew allows.
+    fn observe(&mut self, stamps: impl IntoIterator<Item = u64>) {
+        let mut clamped = false;
+        for stamp in stamps {
+            clamped |= !self.clock.observe(stamp);
+        }
+        if clamped {
+            tracing::warn!("remote task timestamp too far ahead of the local clock, clamped");
+        }
+    }
+
+    /// Stamps a local write with the clock, checks it against the room's
+    /// permissions, applies it to the task and returns the update to
+    /// broadcast.
+    fn write_field(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        field: impl FnOnce(u64, String) -> TaskFieldUpdate,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        let field = field(self.clock.now(), self.local_peer_id.clone());
+        if let Some(task) = self.tasks.get(room_id).and_then(|r| r.get(task_id)) {
+            self.authorize(room_id, Some(task), TaskAction::of(Some(task), &field))?;
+        }
+        let task = self.get_task_mut(room_id, task_id)?;
+        let entry = ActivityEntry::describe(task, &field);
+        if apply_field_update(task, &field) {
+            self.record(task_id, entry);
+            self.persist(room_id);
+        }
+
+        Ok(TaskSyncMessage::FieldUpdate {
+            task_id: task_id.clone(),
+            room_id: room_id.to_string(),
+            field,
+        })
+    }
+
+    /// Checks that the local peer may perform `action` on `task` (`None`
+    /// for a new task) under the room's permissions, if it has any.
+    fn authorize(
+        &self,
+        room_id: &str,
+        task: Option<&Task>,
+        action: TaskAction,
+    ) -> Result<(), TaskError> {
+        match self.permissions.get(room_id) {
+            Some(permissions) if !permissions.allows(&self.local_peer_id, action, task) => {
+                Err(TaskError::PermissionDenied(action))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn set_label(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        label: &str,
+        present: bool,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        let label = normalize_label(label)?;
+        self.write_field(room_id, task_id, |now, peer_id| TaskFieldUpdate::Label {
+            label,
+            present: LwwRegister::new(present, now, peer_id),
+        })
+    }
+
+    fn set_blocker(
+        &mut self,
+        room_id: &str,
+        task_id: &TaskId,
+        blocker: &TaskId,
+        present: bool,
+    ) -> Result<TaskSyncMessage, TaskError> {
+        let blocker = blocker.clone();
+        self.write_field(room_id, task_id, |now, peer_id| {
+            TaskFieldUpdate::BlockedBy {
+                blocker,
+                present: LwwRegister::new(present, now, peer_id),
+            }
+        })
+    }
+
+    /// Checks that both ends of a new edge exist and that `would_cycle`
+    /// does not refuse it.
+    fn check_edge(
+        &self,
+        room_id: &str,
+        from: &TaskId,
+        to: &TaskId,
+        would_cycle: fn(&TaskGraph, &TaskId, &TaskId) -> bool,
+    ) -> Result<(), TaskError> {
+        let room_tasks = self
+            .tasks
+            .get(room_id)
+            .ok_or_else(|| TaskError::RoomNotFound(room_id.to_string()))?;
+        for id in [from, to] {
+            if !room_tasks.contains_key(id) {
+                return Err(TaskError::TaskNotFound(id.to_string()));
+            }
+        }
+        if would_cycle(&self.graph(room_id), from, to) {
+            return Err(TaskError::DependencyCycle);
+        }
+        Ok(())
+    }
+
+    /// Returns a mutable reference to a task, or an error if not found.
+    fn get_task_mut(&mut self, room_id: &str, task_id: &TaskId) -> Result<&mut Task, TaskError> {
+        let room_tasks = self
+            .tasks
+            .get_mut(room_id)
+            .ok_or_else(|| TaskError::RoomNotFound(room_id.to_string()))?;
+        room_tasks
+            .get_mut(task_id)
+            .ok_or_else(|| TaskError::TaskNotFound(task_id.to_string()))
+    }
+}
+
+/// Checks a task title against the length limits.
+fn validate_title(title: &str) -> Result<(), TaskError> {
+    if title.is_empty() {
+        return Err(TaskError::TitleEmpty);
+    }
+    if title.chars().count() > MAX_TASK_TITLE_LENGTH {
+        return Err(TaskError::TitleTooLong);
+    }
+    Ok(())
+}
+
+/// Checks an imported task's title, description and labels.
+fn validate_record(record: &TaskRecord) -> Result<(), TaskError> {
+    validate_title(&record.title)?;
+    if record.description.chars().count() > MAX_TASK_DESCRIPTION_LENGTH {
+        return Err(TaskError::DescriptionTooLong);
+    }
+    for label in &record.labels {
+        normalize_label(label)?;
+    }
+    Ok(())
+}
+
+/// Trims and lowercases a label, rejecting empty, overlong or
+/// whitespace-containing labels.
+fn normalize_label(label: &str) -> Result<String, TaskError> {
+    let label = label.trim().to_lowercase();
+    if label.is_empty()
+        || label.chars().count() > MAX_TASK_LABEL_LENGTH
+        || label.chars().any(char::is_whitespace)
+    {
+        return Err(TaskError::InvalidLabel(label));
+    }
+    Ok(label)
+}
+
+/// The timestamp and author of the register carried by a field update.
+fn field_stamp(field: &TaskFieldUpdate) -> (u64, &str) {
+    match field {
+        TaskFieldUpdate::Title(reg) | TaskFieldUpdate::Description(reg) => {
+            (reg.timestamp, &reg.author)
+        }
+        TaskFieldUpdate::Status(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Assignee(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Due(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Recurrence(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Priority(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Parent(reg) => (reg.timestamp, &reg.author),
+        TaskFieldUpdate::Label { present, .. } | TaskFieldUpdate::BlockedBy { present, .. } => {
+            (present.timestamp, &present.author)
+        }
+        TaskFieldUpdate::Comment(comment) => (comment.timestamp, &comment.author),
+    }
+}
+
+/// An empty task for a field update that arrived before the task itself,
+/// dated by the physical time of the update. Every register loses to any
+/// real write, and it is nobody's own until the first real copy of the
+/// task names its creator (see [`adopt_origin`]).
+fn stub_task(task_id: &TaskId, room_id: &str, field: &TaskFieldUpdate) -> Task {
+    let timestamp = field_stamp(field).0;
+    blank_task(task_id, room_id, HybridClock::physical_ms(timestamp), "")
+}
+
+/// A task with every field unset, for field updates to fill in.
+fn blank_task(task_id: &TaskId, room_id: &str, created_at: u64, created_by: &str) -> Task {
+    Task {
+        id: task_id.clone(),
+        room_id: room_id.to_string(),
+        title: LwwRegister::new(String::new(), 0, String::new()),
+        status: LwwRegister::new(TaskStatus::Open, 0, String::new()),
+        assignee: LwwRegister::new(None, 0, String::new()),
+        description: LwwRegister::new(String::new(), 0, String::new()),
+        due: LwwRegister::new(None, 0, String::new()),
+        recurrence: LwwRegister::new(None, 0, String::new()),
+        priority: LwwRegister::new(TaskPriority::Normal, 0, String::new()),
+        labels: BTreeMap::new(),
+        parent: LwwRegister::new(None, 0, String::new()),
+        blocked_by: BTreeMap::new(),
+        comments: BTreeMap::new(),
+        created_at,
+        created_by: created_by.to_string(),
+    }
+}
+
+/// A rejected remote change: (room, task, timestamp, sender, action).
+type Rejected<'a> = (&'a str, &'a TaskId, u64, String, TaskAction);
+
+/// Merges a remote task list sent by `sender` into a room's tasks field
+/// by field, leaving out the changes `permissions` does not allow and
+/// collecting them in `rejected`. Fields and tasks another peer wrote are
+/// skipped unless `sender` is an admin, and so is the creator a stub
+/// would take from a task. Returns the timestamps of the changes it let
+/// through.
+fn merge_task_list_checked<'a>(
+    room_tasks: &mut HashMap<TaskId, Task>,
+    permissions: &RoomPermissions,
+    (room_id, sender): (&'a str, &str),
+    tasks: &'a [Task],
+    rejected: &mut Vec<Rejected<'a>>,
+) -> Vec<u64> {
+    let relay = permissions.role(sender) == Some(MemberRole::Admin);
+    let mut accepted = Vec::new();
+    for remote in tasks {
+        let task = match room_tasks.entry(remote.id.clone()) {
+            Entry::Occupied(local) => {
+                let task = local.into_mut();
+                if relay || remote.created_by == sender {
+                    adopt_origin(task, remote);
+                }
+                task
+            }
+            Entry::Vacant(_) if !relay && remote.created_by != sender => continue,
+            Entry::Vacant(_) if !permissions.allows(sender, TaskAction::Create, None) => {
+                rejected.push((
+                    room_id,
+                    &remote.id,
+                    remote.created_at,
+                    sender.to_string(),
+                    TaskAction::Create,
+                ));
+                continue;
+            }
+            Entry::Vacant(slot) => slot.insert(blank_task(
+                &remote.id,
+                room_id,
+                remote.created_at,
+                &remote.created_by,
+            )),
+        };
+        for update in task_field_updates(remote) {
+            if !relay && field_stamp(&update).1 != sender {
+                continue;
+            }
+            match denied(permissions, sender, Some(task), &update) {
+                None => {
+                    accepted.push(field_stamp(&update).0);
+                    apply_field_update(task, &update);
+                }
+                Some(action) if changes(task, &update) => {
+                    let timestamp = field_stamp(&update).0;
+                    rejected.push((room_id, &remote.id, timestamp, sender.to_string(), action));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    accepted
+}
+
+/// The action `update` performs on `task` if `sender` may not perform
+/// it. Updating an unknown task creates it, which needs permission too.
+fn denied(
+    permissions: &RoomPermissions,
+    sender: &str,
+    task: Option<&Task>,
+    update: &TaskFieldUpdate,
+) -> Option<TaskAction> {
+    if task.is_none() && !permissions.allows(sender, TaskAction::Create, None) {
+        return Some(TaskAction::Create);
+    }
+    let action = TaskAction::of(task, update);
+    (!permissions.allows(sender, action, task)).then_some(action)
+}
+
+/// Whether applying `update` would change `task`.
+fn changes(task: &Task, update: &TaskFieldUpdate) -> bool {
+    let mut probe = task.clone();
+    apply_field_update(&mut probe, update) && probe != *task
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::cloned_ref_to_slice_refs,
+    clippy::duration_suboptimal_units,
+    clippy::manual_repeat_n,
+    clippy::manual_str_repeat,
+    clippy::redundant_clone,
+    clippy::unwrap_used
+)]
+mod tests {
+    use super::super::PermissionWarning;
+    use super::*;
+
+    fn make_manager() -> TaskManager {
+        TaskManager::new("local-peer".to_string())
+    }
+
+    fn sender(id: &str) -> PeerId {
+        PeerId::new(id)
+    }
+
+    // --- create_task tests ---
+
+    #[test]
+    fn create_task_success() {
+        let mut mgr = make_manager();
+        let (task, msg) = mgr.create_task("room-1", "Fix login bug").unwrap();
+        assert_eq!(task.title.value, "Fix login bug");
+        assert_eq!(task.status.value, TaskStatus::Open);
+        assert_eq!(task.assignee.value, None);
+        assert_eq!(task.room_id, "room-1");
+        assert_eq!(task.created_by, "local-peer");
+        assert!(matches!(msg, TaskSyncMessage::FullState { .. }));
+    }
+
+    #[test]
+    fn create_task_empty_title_error() {
+        let mut mgr = make_manager();
+        let err = mgr.create_task("room-1", "").unwrap_err();
+        assert_eq!(err, TaskError::TitleEmpty);
+    }
+
+    #[test]
+    fn create_task_title_too_long_error() {
+        let mut mgr = make_manager();
+        let long_title = "x".repeat(257);
+        let err = mgr.create_task("room-1", &long_title).unwrap_err();
+        assert_eq!(err, TaskError::TitleTooLong);
+    }
+
+    #[test]
+    fn create_task_max_length_title_ok() {
+        let mut mgr = make_manager();
+        let title = "x".repeat(256);
+        let result = mgr.create_task("room-1", &title);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn create_task_appears_in_get_tasks() {
+        let mut mgr = make_manager();
+        mgr.create_task("room-1", "Task A").unwrap();
+        mgr.create_task("room-1", "Task B").unwrap();
+        let tasks = mgr.get_tasks("room-1");
+        assert_eq!(tasks.len(), 2);
+    }
+
+    // --- update_status tests ---
+
+    #[test]
     fn update_status_success() {
         let mut mgr = make_manager();
         let (task, _) = mgr.create_task("room-1", "My task").unwrap();
@@ -430,6 +1553,14 @@ mod tests {
             title: LwwRegister::new("Remote task".to_string(), 100, "peer-b".to_string()),
             status: LwwRegister::new(TaskStatus::Open, 100, "peer-b".to_string()),
             assignee: LwwRegister::new(None, 100, "peer-b".to_string()),
+            description: LwwRegister::new(String::new(), 100, "peer-b".to_string()),
+            due: LwwRegister::new(None, 100, "peer-b".to_string()),
+            recurrence: LwwRegister::new(None, 100, "peer-b".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 100, "peer-b".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 100, "peer-b".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: 100,
             created_by: "peer-b".to_string(),
         };
@@ -437,7 +1568,7 @@ mod tests {
             room_id: "room-1".to_string(),
             tasks: vec![task],
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         assert_eq!(mgr.get_tasks("room-1").len(), 1);
     }
 
@@ -454,7 +1585,7 @@ mod tests {
                 "peer-b".to_string(),
             )),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         let tasks = mgr.get_tasks("room-1");
         assert_eq!(tasks[0].status.value, TaskStatus::Completed);
     }
@@ -472,7 +1603,7 @@ mod tests {
                 "peer-b".to_string(),
             )),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         // Task should exist even though we never created it locally
         let room_tasks = mgr.tasks.get("room-1").unwrap();
         assert!(room_tasks.contains_key(&task_id));
@@ -485,7 +1616,7 @@ mod tests {
         let msg = TaskSyncMessage::RequestFullState {
             room_id: "room-1".to_string(),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         // Should not change anything
         assert_eq!(mgr.get_tasks("room-1").len(), 1);
     }
@@ -506,7 +1637,7 @@ mod tests {
                 "peer-b".to_string(),
             )),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         // Title should not have changed
         let tasks = mgr.get_tasks("room-1");
         assert_eq!(tasks[0].title.value, "My task");
@@ -525,11 +1656,30 @@ mod tests {
                 "peer-b".to_string(),
             )),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         let room_tasks = mgr.tasks.get("room-1").unwrap();
         let stub = room_tasks.get(&task_id).unwrap();
         assert_eq!(stub.status.value, TaskStatus::Completed);
-        assert_eq!(stub.created_by, "peer-b");
+        assert_eq!(stub.created_by, "");
+    }
+
+    #[test]
+    fn stub_is_dated_by_the_physical_time_of_the_update() {
+        let mut mgr = make_manager();
+        let task_id = TaskId::new();
+        let timestamp = HybridClock::new().now();
+        let msg = TaskSyncMessage::FieldUpdate {
+            task_id: task_id.clone(),
+            room_id: "room-1".to_string(),
+            field: TaskFieldUpdate::Title(LwwRegister::new(
+                "Early".to_string(),
+                timestamp,
+                "peer-b".to_string(),
+            )),
+        };
+        mgr.apply_remote(&sender("peer-b"), &msg);
+        let stub = mgr.tasks["room-1"].get(&task_id).unwrap();
+        assert_eq!(stub.created_at, HybridClock::physical_ms(timestamp));
     }
 
     #[test]
@@ -545,7 +1695,7 @@ mod tests {
                 "peer-b".to_string(),
             )),
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         let room_tasks = mgr.tasks.get("room-1").unwrap();
         let stub = room_tasks.get(&task_id).unwrap();
         assert_eq!(stub.assignee.value, Some("alice".to_string()));
@@ -605,6 +1755,14 @@ mod tests {
             ),
             status: LwwRegister::new(TaskStatus::Open, 0, "peer-a".to_string()),
             assignee: LwwRegister::new(None, 0, "peer-a".to_string()),
+            description: LwwRegister::new(String::new(), 0, "peer-a".to_string()),
+            due: LwwRegister::new(None, 0, "peer-a".to_string()),
+            recurrence: LwwRegister::new(None, 0, "peer-a".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 0, "peer-a".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 0, "peer-a".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: local_task.created_at,
             created_by: "local-peer".to_string(),
         };
@@ -614,6 +1772,14 @@ mod tests {
             title: LwwRegister::new("Remote-only task".to_string(), 100, "peer-b".to_string()),
             status: LwwRegister::new(TaskStatus::Open, 100, "peer-b".to_string()),
             assignee: LwwRegister::new(None, 100, "peer-b".to_string()),
+            description: LwwRegister::new(String::new(), 100, "peer-b".to_string()),
+            due: LwwRegister::new(None, 100, "peer-b".to_string()),
+            recurrence: LwwRegister::new(None, 100, "peer-b".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 100, "peer-b".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 100, "peer-b".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: 100,
             created_by: "peer-b".to_string(),
         };
@@ -621,7 +1787,7 @@ mod tests {
             room_id: "room-1".to_string(),
             tasks: vec![updated_local, new_remote],
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-a"), &msg);
         let tasks = mgr.get_tasks("room-1");
         assert_eq!(tasks.len(), 2);
         // Find the updated local task
@@ -651,7 +1817,7 @@ mod tests {
         let state_msg = mgr_a.build_full_state("room-1").unwrap();
 
         let mut mgr_b = TaskManager::new("peer-b".to_string());
-        mgr_b.apply_remote(&state_msg);
+        mgr_b.apply_remote(&sender("peer-a"), &state_msg);
 
         assert_eq!(mgr_b.get_tasks("room-1").len(), 2);
     }
@@ -669,6 +1835,14 @@ mod tests {
             title: LwwRegister::new("Local task".to_string(), 0, "peer-b".to_string()),
             status: LwwRegister::new(TaskStatus::Open, 0, "peer-b".to_string()),
             assignee: LwwRegister::new(None, 0, "peer-b".to_string()),
+            description: LwwRegister::new(String::new(), 0, "peer-b".to_string()),
+            due: LwwRegister::new(None, 0, "peer-b".to_string()),
+            recurrence: LwwRegister::new(None, 0, "peer-b".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 0, "peer-b".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 0, "peer-b".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: 0,
             created_by: "peer-b".to_string(),
         };
@@ -676,7 +1850,7 @@ mod tests {
             room_id: "room-1".to_string(),
             tasks: vec![remote_task],
         };
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
 
         let tasks = mgr.get_tasks("room-1");
         assert_eq!(tasks[0].status.value, TaskStatus::InProgress);
@@ -691,6 +1865,14 @@ mod tests {
             title: LwwRegister::new("Remote".to_string(), 100, "peer-b".to_string()),
             status: LwwRegister::new(TaskStatus::Open, 100, "peer-b".to_string()),
             assignee: LwwRegister::new(None, 100, "peer-b".to_string()),
+            description: LwwRegister::new(String::new(), 100, "peer-b".to_string()),
+            due: LwwRegister::new(None, 100, "peer-b".to_string()),
+            recurrence: LwwRegister::new(None, 100, "peer-b".to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, 100, "peer-b".to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, 100, "peer-b".to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: 100,
             created_by: "peer-b".to_string(),
         };
@@ -698,8 +1880,8 @@ mod tests {
             room_id: "room-1".to_string(),
             tasks: vec![remote_task],
         };
-        mgr.apply_remote(&msg);
-        mgr.apply_remote(&msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
+        mgr.apply_remote(&sender("peer-b"), &msg);
         assert_eq!(mgr.get_tasks("room-1").len(), 1);
     }
 
@@ -712,7 +1894,7 @@ mod tests {
         let request = TaskSyncMessage::RequestFullState {
             room_id: "room-1".to_string(),
         };
-        mgr.apply_remote(&request);
+        mgr.apply_remote(&sender("peer-b"), &request);
 
         let response = mgr.build_full_state("room-1").unwrap();
         if let TaskSyncMessage::FullState { tasks, room_id } = response {
@@ -731,4 +1913,926 @@ mod tests {
         let state = mgr.build_full_state("room-1");
         assert!(state.is_some());
     }
+
+    // --- Rich field tests ---
+
+    #[test]
+    fn rich_field_updates_apply_locally_and_remotely() {
+        let mut mgr = make_manager();
+        let mut replica = TaskManager::new("replica".to_string());
+        let (task, created) = mgr.create_task("room-1", "Task").unwrap();
+        replica.apply_remote(&sender("local-peer"), &created);
+
+        let updates = [
+            mgr.update_title("room-1", &task.id, "Renamed").unwrap(),
+            mgr.update_description("room-1", &task.id, "line 1\nline 2")
+                .unwrap(),
+            mgr.update_due("room-1", &task.id, Some(1_000)).unwrap(),
+            mgr.update_priority("room-1", &task.id, TaskPriority::High)
+                .unwrap(),
+            mgr.add_label("room-1", &task.id, " Bug ").unwrap(),
+            mgr.add_label("room-1", &task.id, "ui").unwrap(),
+            mgr.remove_label("room-1", &task.id, "UI").unwrap(),
+        ];
+        for update in &updates {
+            replica.apply_remote(&sender("local-peer"), update);
+        }
+
+        for tasks in [mgr.get_tasks("room-1"), replica.get_tasks("room-1")] {
+            let task = tasks[0];
+            assert_eq!(task.title.value, "Renamed");
+            assert_eq!(task.description.value, "line 1\nline 2");
+            assert_eq!(task.due.value, Some(1_000));
+            assert_eq!(task.priority.value, TaskPriority::High);
+            assert_eq!(task.label_names().collect::<Vec<_>>(), ["bug"]);
+        }
+    }
+
+    #[test]
+    fn invalid_rich_field_values_rejected() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Task").unwrap();
+        assert_eq!(
+            mgr.update_title("room-1", &task.id, ""),
+            Err(TaskError::TitleEmpty)
+        );
+        let long = "x".repeat(MAX_TASK_DESCRIPTION_LENGTH + 1);
+        assert_eq!(
+            mgr.update_description("room-1", &task.id, &long),
+            Err(TaskError::DescriptionTooLong)
+        );
+        assert!(matches!(
+            mgr.add_label("room-1", &task.id, "two words"),
+            Err(TaskError::InvalidLabel(_))
+        ));
+        assert!(matches!(
+            mgr.add_label("room-1", &task.id, "  "),
+            Err(TaskError::InvalidLabel(_))
+        ));
+    }
+
+    #[test]
+    fn label_update_for_unknown_task_creates_stub() {
+        let mut mgr = make_manager();
+        let task_id = TaskId::new();
+        mgr.apply_remote(
+            &sender("peer-x"),
+            &TaskSyncMessage::FieldUpdate {
+                task_id: task_id.clone(),
+                room_id: "room-1".to_string(),
+                field: TaskFieldUpdate::Label {
+                    label: "bug".to_string(),
+                    present: LwwRegister::new(true, 500, "peer-x".to_string()),
+                },
+            },
+        );
+        let tasks = mgr.get_tasks("room-1");
+        assert_eq!(tasks[0].id, task_id);
+        assert!(tasks[0].has_label("bug"));
+        assert_eq!(tasks[0].created_by, "");
+    }
+
+    // --- Dependency tests ---
+
+    #[test]
+    fn subtasks_roll_up_progress() {
+        let mut mgr = make_manager();
+        let (parent, _) = mgr.create_task("room-1", "Parent").unwrap();
+        assert_eq!(mgr.subtask_progress("room-1", &parent.id), None);
+        let mut children = Vec::new();
+        for title in ["a", "b", "c"] {
+            let (child, _) = mgr.create_task("room-1", title).unwrap();
+            mgr.set_parent("room-1", &child.id, Some(&parent.id))
+                .unwrap();
+            children.push(child.id);
+        }
+        mgr.update_status("room-1", &children[0], TaskStatus::Completed)
+            .unwrap();
+        mgr.delete_task("room-1", &children[1]).unwrap();
+        assert_eq!(mgr.subtask_progress("room-1", &parent.id), Some((1, 2)));
+
+        mgr.set_parent("room-1", &children[2], None).unwrap();
+        assert_eq!(mgr.subtask_progress("room-1", &parent.id), Some((1, 1)));
+    }
+
+    #[test]
+    fn local_dependency_cycles_rejected() {
+        let mut mgr = make_manager();
+        let (a, _) = mgr.create_task("room-1", "A").unwrap();
+        let (b, _) = mgr.create_task("room-1", "B").unwrap();
+        let (c, _) = mgr.create_task("room-1", "C").unwrap();
+
+        assert_eq!(
+            mgr.set_parent("room-1", &a.id, Some(&a.id)),
+            Err(TaskError::DependencyCycle)
+        );
+        mgr.set_parent("room-1", &b.id, Some(&a.id)).unwrap();
+        mgr.set_parent("room-1", &c.id, Some(&b.id)).unwrap();
+        assert_eq!(
+            mgr.set_parent("room-1", &a.id, Some(&c.id)),
+            Err(TaskError::DependencyCycle)
+        );
+
+        mgr.add_blocker("room-1", &a.id, &b.id).unwrap();
+        mgr.add_blocker("room-1", &b.id, &c.id).unwrap();
+        assert_eq!(
+            mgr.add_blocker("room-1", &c.id, &a.id),
+            Err(TaskError::DependencyCycle)
+        );
+        mgr.remove_blocker("room-1", &b.id, &c.id).unwrap();
+        mgr.add_blocker("room-1", &c.id, &a.id).unwrap();
+
+        let unknown = TaskId::new();
+        assert_eq!(
+            mgr.add_blocker("room-1", &a.id, &unknown),
+            Err(TaskError::TaskNotFound(unknown.to_string()))
+        );
+    }
+
+    #[test]
+    fn concurrent_cycle_resolves_identically() {
+        let mut left = make_manager();
+        let mut right = TaskManager::new("remote-peer".to_string());
+        let (a, created_a) = left.create_task("room-1", "A").unwrap();
+        let (b, created_b) = left.create_task("room-1", "B").unwrap();
+        right.apply_remote(&sender("local-peer"), &created_a);
+        right.apply_remote(&sender("local-peer"), &created_b);
+
+        // Each edit is valid locally; together they form a cycle.
+        let a_under_b = left.set_parent("room-1", &a.id, Some(&b.id)).unwrap();
+        let b_under_a = right.set_parent("room-1", &b.id, Some(&a.id)).unwrap();
+        let a_blocks_b = left.add_blocker("room-1", &b.id, &a.id).unwrap();
+        let b_blocks_a = right.add_blocker("room-1", &a.id, &b.id).unwrap();
+        right.apply_remote(&sender("local-peer"), &a_under_b);
+        right.apply_remote(&sender("local-peer"), &a_blocks_b);
+        left.apply_remote(&sender("remote-peer"), &b_blocks_a);
+        left.apply_remote(&sender("remote-peer"), &b_under_a);
+
+        let (left_graph, right_graph) = (left.graph("room-1"), right.graph("room-1"));
+        assert_eq!(left_graph.ignored().len(), 2);
+        assert_eq!(left_graph.ignored(), right_graph.ignored());
+        for id in [&a.id, &b.id] {
+            assert_eq!(left_graph.parent(id), right_graph.parent(id));
+            assert_eq!(
+                left_graph.blockers(id).collect::<Vec<_>>(),
+                right_graph.blockers(id).collect::<Vec<_>>()
+            );
+        }
+        // The older edits win.
+        assert_eq!(left_graph.parent(&a.id), Some(&b.id));
+        assert_eq!(left_graph.blockers(&b.id).collect::<Vec<_>>(), [&a.id]);
+    }
+
+    #[test]
+    fn completing_blocker_unblocks_task() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Ship").unwrap();
+        let (review, _) = mgr.create_task("room-1", "Review").unwrap();
+        let (tests, _) = mgr.create_task("room-1", "Tests").unwrap();
+        mgr.add_blocker("room-1", &task.id, &review.id).unwrap();
+        mgr.add_blocker("room-1", &task.id, &tests.id).unwrap();
+        assert_eq!(mgr.open_blockers("room-1", &task.id).len(), 2);
+
+        mgr.update_status("room-1", &review.id, TaskStatus::Completed)
+            .unwrap();
+        assert_eq!(mgr.open_blockers("room-1", &task.id), [tests.id.clone()]);
+        mgr.delete_task("room-1", &tests.id).unwrap();
+        assert!(mgr.open_blockers("room-1", &task.id).is_empty());
+    }
+
+    // --- Recurrence and reminder tests ---
+
+    #[test]
+    fn completing_recurring_task_creates_next_instance() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Standup").unwrap();
+        let due = 1_772_442_000_000; // 2026-03-02T09:00:00Z
+        mgr.update_due("room-1", &task.id, Some(due)).unwrap();
+        mgr.add_label("room-1", &task.id, "team").unwrap();
+        mgr.set_recurrence("room-1", &task.id, Some(Recurrence::Weekly))
+            .unwrap();
+
+        let msg = mgr
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        let TaskSyncMessage::FullState { tasks, .. } = &msg else {
+            panic!("expected FullState, got {msg:?}");
+        };
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].status.value, TaskStatus::Completed);
+        let next = &tasks[1];
+        assert_eq!(next.id, task.id.next_instance());
+        assert_eq!(next.title.value, "Standup");
+        assert_eq!(next.status.value, TaskStatus::Open);
+        assert_eq!(next.recurrence.value, Some(Recurrence::Weekly));
+        assert!(next.has_label("team"));
+        // A week apart, and after now.
+        let next_due = next.due.value.unwrap();
+        assert_eq!((next_due - due) % (7 * 24 * 60 * 60 * 1000), 0);
+        assert!(next_due > TaskManager::now_ms());
+
+        // A peer receiving the message gets both tasks.
+        let mut remote = TaskManager::new("remote".to_string());
+        remote.apply_remote(&sender("local-peer"), &msg);
+        assert_eq!(remote.get_tasks("room-1").len(), 2);
+
+        // Reopening and completing again does not create a second copy.
+        mgr.update_status("room-1", &task.id, TaskStatus::Open)
+            .unwrap();
+        let again = mgr
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        assert!(matches!(again, TaskSyncMessage::FieldUpdate { .. }));
+        assert_eq!(mgr.get_tasks("room-1").len(), 2);
+    }
+
+    #[test]
+    fn invalid_cron_recurrence_rejected() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Task").unwrap();
+        assert!(matches!(
+            mgr.set_recurrence(
+                "room-1",
+                &task.id,
+                Some(Recurrence::Cron("* * *".to_string()))
+            ),
+            Err(TaskError::InvalidRecurrence(_))
+        ));
+        mgr.set_recurrence(
+            "room-1",
+            &task.id,
+            Some(Recurrence::Cron("0 9 * * 1-5".to_string())),
+        )
+        .unwrap();
+        let msg = mgr
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        assert!(matches!(msg, TaskSyncMessage::FullState { .. }));
+    }
+
+    #[test]
+    fn due_reminders_fire_once_per_due_time() {
+        let mut mgr = make_manager();
+        let (a, _) = mgr.create_task("room-1", "A").unwrap();
+        let (b, _) = mgr.create_task("room-2", "B").unwrap();
+        let (done, _) = mgr.create_task("room-1", "Done").unwrap();
+        mgr.update_due("room-1", &a.id, Some(2_000)).unwrap();
+        mgr.update_due("room-2", &b.id, Some(1_000)).unwrap();
+        mgr.update_due("room-1", &done.id, Some(1_000)).unwrap();
+        mgr.update_status("room-1", &done.id, TaskStatus::Completed)
+            .unwrap();
+
+        assert!(mgr.due_reminders(500).is_empty());
+        let due: Vec<TaskId> = mgr.due_reminders(5_000).into_iter().map(|t| t.id).collect();
+        assert_eq!(due, [b.id.clone(), a.id.clone()]);
+        assert!(mgr.due_reminders(5_000).is_empty());
+
+        // A new due date re-arms the reminder.
+        mgr.update_due("room-1", &a.id, Some(3_000)).unwrap();
+        assert_eq!(mgr.due_reminders(5_000).len(), 1);
+    }
+
+    // --- Board tests ---
+
+    #[test]
+    fn board_columns_follow_status_and_filter() {
+        let mut mgr = make_manager();
+        let (a, _) = mgr.create_task("room-1", "A").unwrap();
+        let (b, _) = mgr.create_task("room-1", "B").unwrap();
+        let (c, _) = mgr.create_task("room-1", "C").unwrap();
+        mgr.update_assignee("room-1", &b.id, Some("alice".to_string()))
+            .unwrap();
+        let moved = mgr
+            .update_status("room-1", &b.id, TaskStatus::InProgress)
+            .unwrap();
+        assert!(matches!(
+            moved,
+            TaskSyncMessage::FieldUpdate {
+                field: TaskFieldUpdate::Status(_),
+                ..
+            }
+        ));
+        mgr.delete_task("room-1", &c.id).unwrap();
+        mgr.create_task("room-1", "D").unwrap();
+
+        let titles = |columns: [Vec<&Task>; 3]| {
+            columns.map(|column| {
+                column
+                    .iter()
+                    .map(|t| t.title.value.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            titles(mgr.board("room-1", &BoardFilter::default())),
+            [
+                vec!["D".to_string(), "A".to_string()],
+                vec!["B".to_string()],
+                vec![]
+            ]
+        );
+        let alice = BoardFilter::parse("@alice").unwrap();
+        assert_eq!(
+            titles(mgr.board("room-1", &alice)),
+            [vec![], vec!["B".to_string()], vec![]]
+        );
+        assert!(mgr.board("room-1", &alice)[0].iter().all(|t| t.id != a.id));
+    }
+
+    // --- Activity and comment tests ---
+
+    #[test]
+    fn activity_records_applied_local_and_remote_updates() {
+        let mut mgr = make_manager();
+        let (task, created) = mgr.create_task("room-1", "Task").unwrap();
+        mgr.update_status("room-1", &task.id, TaskStatus::InProgress)
+            .unwrap();
+
+        let mut remote = TaskManager::new("remote".to_string());
+        remote.apply_remote(&sender("local-peer"), &created);
+        let stale = remote
+            .update_assignee("room-1", &task.id, Some("alice".to_string()))
+            .unwrap();
+        let newer = remote
+            .update_assignee("room-1", &task.id, Some("bob".to_string()))
+            .unwrap();
+        mgr.apply_remote(&sender("remote"), &newer);
+        mgr.apply_remote(&sender("remote"), &stale);
+
+        let feed = mgr.activity(&task.id);
+        assert_eq!(feed.len(), 2);
+        assert_eq!(
+            feed[0].to_string(),
+            "local-peer changed status: open \u{2192} in_progress"
+        );
+        assert_eq!(
+            feed[1].to_string(),
+            "remote changed assignee: none \u{2192} bob"
+        );
+        assert!(mgr.activity(&TaskId::new()).is_empty());
+    }
+
+    #[test]
+    fn threaded_comments_sync_between_peers() {
+        let mut alice = TaskManager::new("alice".to_string());
+        let mut bob = TaskManager::new("bob".to_string());
+        let (task, created) = alice.create_task("room-1", "Task").unwrap();
+        bob.apply_remote(&sender("alice"), &created);
+
+        let question = alice
+            .add_comment("room-1", &task.id, "Which browser?", None)
+            .unwrap();
+        bob.apply_remote(&sender("alice"), &question);
+        let question_id = bob.comment_thread("room-1", &task.id)[0].1.id.clone();
+        let answer = bob
+            .add_comment("room-1", &task.id, "Firefox", Some(&question_id))
+            .unwrap();
+        alice.apply_remote(&sender("bob"), &answer);
+
+        let thread: Vec<(usize, &str, &str)> = alice
+            .comment_thread("room-1", &task.id)
+            .into_iter()
+            .map(|(depth, c)| (depth, c.author.as_str(), c.body.as_str()))
+            .collect();
+        assert_eq!(
+            thread,
+            [(0, "alice", "Which browser?"), (1, "bob", "Firefox")]
+        );
+        assert_eq!(alice.activity(&task.id).len(), 2);
+    }
+
+    #[test]
+    fn invalid_comments_rejected() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Task").unwrap();
+        assert_eq!(
+            mgr.add_comment("room-1", &task.id, "  ", None),
+            Err(TaskError::CommentEmpty)
+        );
+        let long = "x".repeat(MAX_TASK_COMMENT_LENGTH + 1);
+        assert_eq!(
+            mgr.add_comment("room-1", &task.id, &long, None),
+            Err(TaskError::CommentTooLong)
+        );
+        assert!(matches!(
+            mgr.add_comment("room-1", &task.id, "Reply", Some(&CommentId::new())),
+            Err(TaskError::CommentNotFound(_))
+        ));
+        assert!(mgr.comment_thread("room-1", &task.id).is_empty());
+    }
+
+    // --- Persistence tests ---
+
+    fn temp_store(name: &str) -> TaskStore {
+        let dir = std::env::temp_dir()
+            .join("termchat-test-tasks")
+            .join(format!("{name}-{}", uuid::Uuid::now_v7()));
+        TaskStore::open(dir).unwrap()
+    }
+
+    #[test]
+    fn local_and_remote_changes_survive_reopen() {
+        let store = temp_store("reopen");
+        let mut mgr = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        let (task, _) = mgr.create_task("room-1", "Local").unwrap();
+        mgr.update_status("room-1", &task.id, TaskStatus::InProgress)
+            .unwrap();
+
+        let mut remote = TaskManager::new("remote".to_string());
+        let (_, created) = remote.create_task("room-2", "Remote").unwrap();
+        mgr.apply_remote(&sender("remote"), &created);
+        drop(mgr);
+
+        let reopened = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        assert_eq!(reopened.rooms(), ["room-1", "room-2"]);
+        let tasks = reopened.get_tasks("room-1");
+        assert_eq!(tasks[0].title.value, "Local");
+        assert_eq!(tasks[0].status.value, TaskStatus::InProgress);
+        assert_eq!(reopened.get_tasks("room-2")[0].title.value, "Remote");
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn reopened_manager_writes_win_over_stored_state() {
+        let store = temp_store("clock");
+        let mut mgr = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        let (task, _) = mgr.create_task("room-1", "Task").unwrap();
+        let stored = mgr
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        drop(mgr);
+
+        let mut reopened = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        let reopen = reopened
+            .update_status("room-1", &task.id, TaskStatus::Open)
+            .unwrap();
+        assert!(field_stamp_of(&reopen) > field_stamp_of(&stored));
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+
+    fn field_stamp_of(msg: &TaskSyncMessage) -> u64 {
+        let TaskSyncMessage::FieldUpdate { field, .. } = msg else {
+            panic!("expected FieldUpdate");
+        };
+        field_stamp(field).0
+    }
+
+    #[test]
+    fn compaction_drops_only_expired_tombstones() {
+        let store = temp_store("compact");
+        let mut mgr = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        let (kept, _) = mgr.create_task("room-1", "Kept").unwrap();
+        let (deleted, _) = mgr.create_task("room-1", "Deleted").unwrap();
+        mgr.add_blocker("room-1", &kept.id, &deleted.id).unwrap();
+        mgr.delete_task("room-1", &deleted.id).unwrap();
+
+        assert_eq!(mgr.compact_tombstones(Duration::from_secs(3600)), 0);
+        assert_eq!(mgr.compact_tombstones(Duration::ZERO), 1);
+        let TaskSyncMessage::FullState { tasks, .. } = mgr.build_full_state("room-1").unwrap()
+        else {
+            panic!("expected FullState");
+        };
+        assert_eq!(tasks.len(), 1);
+        assert!(tasks[0].blocked_by.is_empty());
+
+        let reopened = TaskManager::open("local-peer".to_string(), store.clone()).unwrap();
+        let TaskSyncMessage::FullState { tasks, .. } = reopened.build_full_state("room-1").unwrap()
+        else {
+            panic!("expected FullState");
+        };
+        assert_eq!(tasks.len(), 1);
+        let _ = std::fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn json_import_merges_and_returns_full_state() {
+        let mut source = make_manager();
+        let (task, _) = source.create_task("room-1", "Exported").unwrap();
+        source
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        let json = source.export_json("room-1").unwrap();
+
+        let mut target = TaskManager::new("target".to_string());
+        let (_, existing) = target.create_task("room-1", "Existing").unwrap();
+        let msg = target.import_json(&json).unwrap();
+        assert!(matches!(msg, TaskSyncMessage::FullState { ref tasks, .. } if tasks.len() == 1));
+        assert_eq!(target.get_tasks("room-1").len(), 2);
+
+        let mut peer = TaskManager::new("peer".to_string());
+        peer.apply_remote(&sender("target"), &existing);
+        peer.apply_remote(&sender("target"), &msg);
+        assert_eq!(peer.get_tasks("room-1").len(), 2);
+        assert!(target.import_json("{}").is_err());
+    }
+
+    #[test]
+    fn task_list_export_import_keeps_status_and_assignee() {
+        let mut source = make_manager();
+        let (a, _) = source.create_task("room-1", "Ship it").unwrap();
+        source
+            .update_status("room-1", &a.id, TaskStatus::InProgress)
+            .unwrap();
+        source
+            .update_assignee("room-1", &a.id, Some("alice".to_string()))
+            .unwrap();
+        source.add_label("room-1", &a.id, "release").unwrap();
+        let (b, _) = source.create_task("room-1", "Gone").unwrap();
+        source.delete_task("room-1", &b.id).unwrap();
+
+        for format in [
+            TaskListFormat::Json,
+            TaskListFormat::Csv,
+            TaskListFormat::Markdown,
+        ] {
+            let text = source.export_tasks("room-1", format).unwrap();
+            let mut target = TaskManager::new("target".to_string());
+            let msg = target.import_tasks("room-2", &text, format).unwrap();
+            let tasks = target.get_tasks("room-2");
+            assert_eq!(tasks.len(), 1, "{format}");
+            assert_eq!(tasks[0].title.value, "Ship it");
+            assert_eq!(tasks[0].status.value, TaskStatus::InProgress);
+            assert_eq!(tasks[0].assignee.value.as_deref(), Some("alice"));
+            assert_eq!(
+                tasks[0].has_label("release"),
+                format != TaskListFormat::Markdown
+            );
+
+            // The returned state recreates the tasks on a peer.
+            let mut peer = TaskManager::new("peer".to_string());
+            peer.apply_remote(&sender("target"), &msg);
+            assert_eq!(peer.get_tasks("room-2")[0].id, tasks[0].id);
+        }
+    }
+
+    #[test]
+    fn invalid_task_list_entry_imports_nothing() {
+        let mut mgr = make_manager();
+        let json = r#"[{"title":"Fine"},{"title":""}]"#;
+        let err = mgr
+            .import_tasks("room-1", json, TaskListFormat::Json)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TaskListError::Invalid {
+                index: 2,
+                source: TaskError::TitleEmpty
+            }
+        ));
+        assert!(mgr.get_tasks("room-1").is_empty());
+    }
+
+    // --- Permission tests ---
+
+    fn members() -> Vec<MemberInfo> {
+        let member = |peer_id: &str, is_admin, is_agent, is_read_only| MemberInfo {
+            peer_id: peer_id.to_string(),
+            display_name: peer_id.to_string(),
+            is_admin,
+            is_agent,
+            is_read_only,
+        };
+        vec![
+            member("admin", true, false, false),
+            member("bob", false, false, false),
+            member("bot", false, true, false),
+            member("viewer", false, false, true),
+        ]
+    }
+
+    #[test]
+    fn local_writes_follow_role() {
+        let mut admin = TaskManager::new("admin".to_string());
+        let (theirs, create) = admin.create_task("room-1", "Admin's").unwrap();
+
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.set_members("room-1", &members());
+        bob.apply_remote(&sender("admin"), &create);
+        let (own, _) = bob.create_task("room-1", "Bob's").unwrap();
+        bob.update_title("room-1", &theirs.id, "Renamed").unwrap();
+        assert_eq!(
+            bob.update_assignee("room-1", &theirs.id, Some("bob".to_string())),
+            Err(TaskError::PermissionDenied(TaskAction::Reassign))
+        );
+        assert_eq!(
+            bob.delete_task("room-1", &theirs.id),
+            Err(TaskError::PermissionDenied(TaskAction::Delete))
+        );
+        bob.delete_task("room-1", &own.id).unwrap();
+
+        let mut viewer = TaskManager::new("viewer".to_string());
+        viewer.set_members("room-1", &members());
+        viewer.apply_remote(&sender("admin"), &create);
+        assert_eq!(
+            viewer.create_task("room-1", "Nope").map(|_| ()),
+            Err(TaskError::PermissionDenied(TaskAction::Create))
+        );
+        assert_eq!(
+            viewer
+                .add_comment("room-1", &theirs.id, "Looks good", None)
+                .map(|_| ()),
+            Err(TaskError::PermissionDenied(TaskAction::Comment))
+        );
+        // Other rooms are unrestricted.
+        viewer.create_task("room-2", "Mine").unwrap();
+    }
+
+    #[test]
+    fn remote_changes_beyond_role_are_rejected_with_warning() {
+        let mut admin = TaskManager::new("admin".to_string());
+        admin.set_members("room-1", &members());
+        let (task, create) = admin.create_task("room-1", "Shared").unwrap();
+
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.apply_remote(&sender("admin"), &create);
+        let rename = bob.update_title("room-1", &task.id, "Renamed").unwrap();
+        let delete = bob.delete_task("room-1", &task.id).unwrap();
+
+        assert!(admin.apply_remote(&sender("bob"), &rename).is_empty());
+        let warnings = admin.apply_remote(&sender("bob"), &delete);
+        assert_eq!(
+            warnings,
+            [PermissionWarning {
+                room_id: "room-1".to_string(),
+                task_id: task.id.clone(),
+                sender: "bob".to_string(),
+                action: TaskAction::Delete,
+            }]
+        );
+        let shared = &admin.get_tasks("room-1")[0];
+        assert_eq!(shared.title.value, "Renamed");
+        assert_eq!(shared.status.value, TaskStatus::Open);
+        // The same change arriving again is not reported twice.
+        assert!(admin.apply_remote(&sender("bob"), &delete).is_empty());
+        // Nor when it comes back in bob's full state.
+        let state = bob.build_full_state("room-1").unwrap();
+        assert!(admin.apply_remote(&sender("bob"), &state).is_empty());
+        assert_eq!(admin.get_tasks("room-1").len(), 1);
+    }
+
+    #[test]
+    fn stub_is_owned_by_the_creator_of_the_first_real_copy() {
+        let mut admin = TaskManager::new("admin".to_string());
+        let (task, create) = admin.create_task("room-1", "Admin's").unwrap();
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.apply_remote(&sender("admin"), &create);
+        let comment = bob.add_comment("room-1", &task.id, "On it", None).unwrap();
+        let delete = bob.delete_task("room-1", &task.id).unwrap();
+
+        // Bob's comment outruns the task itself.
+        let mut carol = TaskManager::new("carol".to_string());
+        carol.set_members("room-1", &members());
+        assert!(carol.apply_remote(&sender("bob"), &comment).is_empty());
+        assert_eq!(carol.tasks["room-1"][&task.id].created_by, "");
+        assert!(carol.apply_remote(&sender("admin"), &create).is_empty());
+        let copy = &carol.tasks["room-1"][&task.id];
+        assert_eq!(copy.created_by, "admin");
+        assert_eq!(copy.created_at, task.created_at);
+
+        let warnings = carol.apply_remote(&sender("bob"), &delete);
+        assert_eq!(
+            warnings,
+            [PermissionWarning {
+                room_id: "room-1".to_string(),
+                task_id: task.id.clone(),
+                sender: "bob".to_string(),
+                action: TaskAction::Delete,
+            }]
+        );
+        assert_eq!(carol.get_tasks("room-1").len(), 1);
+    }
+
+    #[test]
+    fn rejected_reports_are_bounded() {
+        let mut admin = TaskManager::new("admin".to_string());
+        admin.set_members("room-1", &members());
+        let (task, _) = admin.create_task("room-1", "Shared").unwrap();
+        let now = admin.clock.now();
+        for timestamp in now..=now + MAX_REJECTED_REPORTS as u64 {
+            let delete = TaskSyncMessage::FieldUpdate {
+                task_id: task.id.clone(),
+                room_id: "room-1".to_string(),
+                field: TaskFieldUpdate::Status(LwwRegister::new(
+                    TaskStatus::Deleted,
+                    timestamp,
+                    "viewer".to_string(),
+                )),
+            };
+            assert_eq!(admin.apply_remote(&sender("viewer"), &delete).len(), 1);
+            assert!(admin.rejected.len() <= MAX_REJECTED_REPORTS);
+        }
+    }
+
+    #[test]
+    fn full_state_is_checked_field_by_field() {
+        let mut admin = TaskManager::new("admin".to_string());
+        admin.set_members("room-1", &members());
+
+        let mut bot = TaskManager::new("bot".to_string());
+        let (tidy, _) = bot.create_task("room-1", "Tidy up").unwrap();
+        bot.add_label("room-1", &tidy.id, "chore").unwrap();
+        bot.delete_task("room-1", &tidy.id).unwrap();
+        let mut stranger = TaskManager::new("stranger".to_string());
+        let (spam, _) = stranger.create_task("room-1", "Spam").unwrap();
+
+        let from_bot = admin.apply_remote(&sender("bot"), &bot.build_full_state("room-1").unwrap());
+        let from_stranger = admin.apply_remote(
+            &sender("stranger"),
+            &stranger.build_full_state("room-1").unwrap(),
+        );
+
+        let rejected: Vec<(&TaskId, &str, TaskAction)> = from_bot
+            .iter()
+            .chain(&from_stranger)
+            .map(|w| (&w.task_id, w.sender.as_str(), w.action))
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                (&tidy.id, "bot", TaskAction::Delete),
+                (&spam.id, "stranger", TaskAction::Create),
+            ]
+        );
+        // The agent's task is kept, undeleted, with its label.
+        let kept = admin.get_tasks("room-1");
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].title.value, "Tidy up");
+        assert!(kept[0].has_label("chore"));
+    }
+
+    #[test]
+    fn forged_authors_are_rejected() {
+        let mut admin = TaskManager::new("admin".to_string());
+        let (task, create) = admin.create_task("room-1", "Shared").unwrap();
+        let forged = TaskSyncMessage::FieldUpdate {
+            task_id: task.id.clone(),
+            room_id: "room-1".to_string(),
+            field: TaskFieldUpdate::Status(LwwRegister::new(
+                TaskStatus::Deleted,
+                admin.clock.now(),
+                "admin".to_string(),
+            )),
+        };
+
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.set_members("room-1", &members());
+        bob.apply_remote(&sender("admin"), &create);
+        let warnings = bob.apply_remote(&sender("viewer"), &forged);
+        assert_eq!(
+            warnings,
+            [PermissionWarning {
+                room_id: "room-1".to_string(),
+                task_id: task.id.clone(),
+                sender: "viewer".to_string(),
+                action: TaskAction::Delete,
+            }]
+        );
+        assert_eq!(bob.get_tasks("room-1").len(), 1);
+
+        // A full state naming someone else as a field's writer or as the
+        // task's creator is only taken from an admin.
+        let mut state = admin.build_full_state("room-1").unwrap();
+        if let TaskSyncMessage::FullState { tasks, .. } = &mut state {
+            tasks[0].title =
+                LwwRegister::new("Renamed".to_string(), u64::MAX >> 1, "admin".to_string());
+            tasks.push(Task {
+                id: TaskId::new(),
+                created_by: "admin".to_string(),
+                ..tasks[0].clone()
+            });
+        }
+        assert!(bob.apply_remote(&sender("viewer"), &state).is_empty());
+        let tasks = bob.get_tasks("room-1");
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title.value, "Shared");
+        bob.apply_remote(&sender("admin"), &state);
+        assert_eq!(bob.get_tasks("room-1").len(), 2);
+    }
+
+    #[test]
+    fn ownership_follows_local_creator() {
+        let mut admin = TaskManager::new("admin".to_string());
+        let (task, create) = admin.create_task("room-1", "Admin's").unwrap();
+
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.apply_remote(&sender("admin"), &create);
+        // Bob claims the task as his own, then reassigns it.
+        let mut state = bob.build_full_state("room-1").unwrap();
+        if let TaskSyncMessage::FullState { tasks, .. } = &mut state {
+            tasks[0].created_by = "bob".to_string();
+        }
+        let reassign = bob
+            .update_assignee("room-1", &task.id, Some("bob".to_string()))
+            .unwrap();
+
+        let mut bot = TaskManager::new("bot".to_string());
+        bot.set_members("room-1", &members());
+        bot.apply_remote(&sender("admin"), &create);
+        bot.apply_remote(&sender("bob"), &state);
+        let warnings = bot.apply_remote(&sender("bob"), &reassign);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].action, TaskAction::Reassign);
+        let kept = &bot.get_tasks("room-1")[0];
+        assert_eq!(kept.created_by, "admin");
+        assert_eq!(kept.assignee.value, None);
+    }
+
+    #[test]
+    fn rejected_changes_do_not_advance_clock() {
+        let mut admin = TaskManager::new("admin".to_string());
+        admin.set_members("room-1", &members());
+        let (task, _) = admin.create_task("room-1", "Shared").unwrap();
+        let before = admin.clock.last();
+
+        let ahead = HybridClock::with_offset(30_000).now();
+        let warnings = admin.apply_remote(
+            &sender("viewer"),
+            &TaskSyncMessage::FieldUpdate {
+                task_id: task.id.clone(),
+                room_id: "room-1".to_string(),
+                field: TaskFieldUpdate::Title(LwwRegister::new(
+                    "Mine now".to_string(),
+                    ahead,
+                    "viewer".to_string(),
+                )),
+            },
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(admin.clock.last(), before);
+        assert_eq!(admin.get_tasks("room-1")[0].title.value, "Shared");
+    }
+
+    #[test]
+    fn completing_recurring_task_needs_create_permission() {
+        let mut admin = TaskManager::new("admin".to_string());
+        let (task, _) = admin.create_task("room-1", "Standup").unwrap();
+        admin
+            .set_recurrence("room-1", &task.id, Some(Recurrence::Weekly))
+            .unwrap();
+        let state = admin.build_full_state("room-1").unwrap();
+
+        let mut viewer = TaskManager::new("viewer".to_string());
+        viewer.set_members("room-1", &members());
+        viewer.apply_remote(&sender("admin"), &state);
+        assert_eq!(
+            viewer
+                .update_status("room-1", &task.id, TaskStatus::Completed)
+                .map(|_| ()),
+            Err(TaskError::PermissionDenied(TaskAction::Create))
+        );
+        let tasks = viewer.get_tasks("room-1");
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status.value, TaskStatus::Open);
+
+        let mut bob = TaskManager::new("bob".to_string());
+        bob.set_members("room-1", &members());
+        bob.apply_remote(&sender("admin"), &state);
+        bob.update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        assert_eq!(bob.get_tasks("room-1").len(), 2);
+    }
+
+    // --- Hybrid clock tests ---
+
+    #[test]
+    fn repeated_writes_in_same_millisecond_are_ordered() {
+        let mut mgr = make_manager();
+        let (task, _) = mgr.create_task("room-1", "Task").unwrap();
+        let first = mgr
+            .update_status("room-1", &task.id, TaskStatus::InProgress)
+            .unwrap();
+        let second = mgr
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+
+        let mut replica = TaskManager::new("replica".to_string());
+        replica.apply_remote(
+            &sender("local-peer"),
+            &mgr.build_full_state("room-1").unwrap(),
+        );
+        replica.apply_remote(&sender("local-peer"), &second);
+        replica.apply_remote(&sender("local-peer"), &first);
+        assert_eq!(
+            replica.get_tasks("room-1")[0].status.value,
+            TaskStatus::Completed
+        );
+    }
+
+    #[test]
+    fn edit_after_seeing_fast_peer_wins() {
+        let mut fast = TaskManager::with_clock("aaa".to_string(), HybridClock::with_offset(20_000));
+        let mut slow =
+            TaskManager::with_clock("zzz".to_string(), HybridClock::with_offset(-20_000));
+        let (task, created) = fast.create_task("room-1", "Task").unwrap();
+        slow.apply_remote(&sender("aaa"), &created);
+
+        let edit = slow
+            .update_status("room-1", &task.id, TaskStatus::Completed)
+            .unwrap();
+        fast.apply_remote(&sender("zzz"), &edit);
+        assert_eq!(
+            fast.get_tasks("room-1")[0].status.value,
+            TaskStatus::Completed
+        );
+    }
 }
@@ -17,7 +17,8 @@
 //!   for the task sync protocol; `crdts` uses different conflict resolution.
 //! - Zero external dependencies beyond `termchat-proto` types.
 
-use std::collections::HashMap;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::BuildHasher;
 
 use termchat_proto::task::{LwwRegister, Task, TaskFieldUpdate, TaskId};
@@ -41,12 +42,57 @@ pub fn merge_lww<T: Clone>(local: &LwwRegister<T>, remote: &LwwRegister<T>) -> L
 
 /// Merges a remote task into a local task, field by field.
 ///
-/// Each field (title, status, assignee) is merged independently using
-/// [`merge_lww`], so concurrent edits to different fields both survive.
+/// Each field is merged independently using [`merge_lww`], so concurrent
+/// edits to different fields both survive. Labels and blockers merge per
+/// element with [`merge_lww_set`], and comments by union. A stub takes
+/// its creator from the remote copy (see [`adopt_origin`]).
 pub fn merge_task(local: &mut Task, remote: &Task) {
+    adopt_origin(local, remote);
     local.title = merge_lww(&local.title, &remote.title);
     local.status = merge_lww(&local.status, &remote.status);
     local.assignee = merge_lww(&local.assignee, &remote.assignee);
+    local.description = merge_lww(&local.description, &remote.description);
+    local.due = merge_lww(&local.due, &remote.due);
+    local.recurrence = merge_lww(&local.recurrence, &remote.recurrence);
+    local.priority = merge_lww(&local.priority, &remote.priority);
+    merge_lww_set(&mut local.labels, &remote.labels);
+    local.parent = merge_lww(&local.parent, &remote.parent);
+    merge_lww_set(&mut local.blocked_by, &remote.blocked_by);
+    for (id, comment) in &remote.comments {
+        local
+            .comments
+            .entry(id.clone())
+            .or_insert_with(|| comment.clone());
+    }
+}
+
+/// Gives a stub, a task made for an update that arrived before the task
+/// itself and so created by nobody, the creation time and creator of a
+/// real copy of it. Any other task keeps its own.
+pub fn adopt_origin(local: &mut Task, remote: &Task) {
+    if local.created_by.is_empty() && !remote.created_by.is_empty() {
+        local.created_at = remote.created_at;
+        local.created_by.clone_from(&remote.created_by);
+    }
+}
+
+/// Merges a remote LWW-element set (labels, blockers) into a local one.
+///
+/// Each element's membership is an LWW register, so whichever add or
+/// remove of an element is newest wins, while changes to different
+/// elements never conflict. Elements known to only one side are taken as
+/// they are.
+pub fn merge_lww_set<K: Ord + Clone>(
+    local: &mut BTreeMap<K, LwwRegister<bool>>,
+    remote: &BTreeMap<K, LwwRegister<bool>>,
+) {
+    for (element, remote_present) in remote {
+        let merged = local.get(element).map_or_else(
+            || remote_present.clone(),
+            |local_present| merge_lww(local_present, remote_present),
+        );
+        local.insert(element.clone(), merged);
+    }
 }
 
 /// Merges a list of remote tasks into a local task map.
@@ -64,44 +110,106 @@ pub fn merge_task_list<S: BuildHasher>(local: &mut HashMap<TaskId, Task, S>, rem
     }
 }
 
+/// Splits a task into one field update per register, set element and
+/// comment. Applying them all to a blank copy of the task rebuilds it.
+#[must_use]
+pub fn task_field_updates(task: &Task) -> Vec<TaskFieldUpdate> {
+    let mut updates = vec![
+        TaskFieldUpdate::Title(task.title.clone()),
+        TaskFieldUpdate::Status(task.status.clone()),
+        TaskFieldUpdate::Assignee(task.assignee.clone()),
+        TaskFieldUpdate::Description(task.description.clone()),
+        TaskFieldUpdate::Due(task.due.clone()),
+        TaskFieldUpdate::Recurrence(task.recurrence.clone()),
+        TaskFieldUpdate::Priority(task.priority.clone()),
+        TaskFieldUpdate::Parent(task.parent.clone()),
+    ];
+    updates.extend(
+        task.labels
+            .iter()
+            .map(|(label, present)| TaskFieldUpdate::Label {
+                label: label.clone(),
+                present: present.clone(),
+            }),
+    );
+    updates.extend(
+        task.blocked_by
+            .iter()
+            .map(|(blocker, present)| TaskFieldUpdate::BlockedBy {
+                blocker: blocker.clone(),
+                present: present.clone(),
+            }),
+    );
+    updates.extend(
+        task.comments
+            .values()
+            .cloned()
+            .map(TaskFieldUpdate::Comment),
+    );
+    updates
+}
+
 /// Applies a single field update to a task using LWW logic.
 ///
-/// Returns `true` if the update was applied (newer), `false` if rejected (stale).
+/// Returns `true` if the update was applied (newer), `false` if rejected
+/// (stale, or a comment the task already has).
 pub fn apply_field_update(task: &mut Task, update: &TaskFieldUpdate) -> bool {
     match update {
-        TaskFieldUpdate::Title(reg) => {
-            let merged = merge_lww(&task.title, reg);
-            if merged.timestamp == reg.timestamp && merged.author == reg.author {
-                task.title = merged;
-                true
-            } else {
-                false
-            }
+        TaskFieldUpdate::Title(reg) => apply_register(&mut task.title, reg),
+        TaskFieldUpdate::Status(reg) => apply_register(&mut task.status, reg),
+        TaskFieldUpdate::Assignee(reg) => apply_register(&mut task.assignee, reg),
+        TaskFieldUpdate::Description(reg) => apply_register(&mut task.description, reg),
+        TaskFieldUpdate::Due(reg) => apply_register(&mut task.due, reg),
+        TaskFieldUpdate::Recurrence(reg) => apply_register(&mut task.recurrence, reg),
+        TaskFieldUpdate::Priority(reg) => apply_register(&mut task.priority, reg),
+        TaskFieldUpdate::Label { label, present } => {
+            apply_set_member(&mut task.labels, label, present)
         }
-        TaskFieldUpdate::Status(reg) => {
-            let merged = merge_lww(&task.status, reg);
-            if merged.timestamp == reg.timestamp && merged.author == reg.author {
-                task.status = merged;
-                true
-            } else {
-                false
-            }
+        TaskFieldUpdate::Parent(reg) => apply_register(&mut task.parent, reg),
+        TaskFieldUpdate::BlockedBy { blocker, present } => {
+            apply_set_member(&mut task.blocked_by, blocker, present)
         }
-        TaskFieldUpdate::Assignee(reg) => {
-            let merged = merge_lww(&task.assignee, reg);
-            if merged.timestamp == reg.timestamp && merged.author == reg.author {
-                task.assignee = merged;
+        TaskFieldUpdate::Comment(comment) => match task.comments.entry(comment.id.clone()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(slot) => {
+                slot.insert(comment.clone());
                 true
-            } else {
-                false
             }
+        },
+    }
+}
+
+/// Applies a membership change to an LWW-element set, adding the element
+/// if it is new.
+fn apply_set_member<K: Ord + Clone>(
+    set: &mut BTreeMap<K, LwwRegister<bool>>,
+    element: &K,
+    present: &LwwRegister<bool>,
+) -> bool {
+    match set.entry(element.clone()) {
+        Entry::Occupied(mut local) => apply_register(local.get_mut(), present),
+        Entry::Vacant(slot) => {
+            slot.insert(present.clone());
+            true
         }
     }
 }
 
+/// Replaces `local` with `remote` if `remote` wins the LWW merge.
+fn apply_register<T: Clone>(local: &mut LwwRegister<T>, remote: &LwwRegister<T>) -> bool {
+    let merged = merge_lww(local, remote);
+    if merged.timestamp == remote.timestamp && merged.author == remote.author {
+        *local = merged;
+        true
+    } else {
+        false
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::cloned_ref_to_slice_refs, clippy::unwrap_used)]
 mod tests {
-    use termchat_proto::task::TaskStatus;
+    use termchat_proto::task::{CommentId, TaskComment, TaskPriority, TaskStatus};
 
     use super::*;
 
@@ -124,6 +232,14 @@ mod tests {
             title: LwwRegister::new(title.to_string(), ts, author.to_string()),
             status: LwwRegister::new(TaskStatus::Open, ts, author.to_string()),
             assignee: LwwRegister::new(None, ts, author.to_string()),
+            description: LwwRegister::new(String::new(), ts, author.to_string()),
+            due: LwwRegister::new(None, ts, author.to_string()),
+            recurrence: LwwRegister::new(None, ts, author.to_string()),
+            priority: LwwRegister::new(TaskPriority::Normal, ts, author.to_string()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, ts, author.to_string()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
             created_at: ts,
             created_by: author.to_string(),
         }
@@ -236,6 +352,30 @@ mod tests {
         assert_eq!(local, before);
     }
 
+    #[test]
+    fn field_updates_rebuild_task() {
+        let id = TaskId::new();
+        let mut task = make_task(id.clone(), "title", 100, "peer-a");
+        task.labels.insert(
+            "bug".to_string(),
+            LwwRegister::new(true, 120, "peer-b".to_string()),
+        );
+        let comment = TaskComment {
+            id: CommentId::new(),
+            reply_to: None,
+            author: "peer-b".to_string(),
+            body: "On it".to_string(),
+            timestamp: 130,
+        };
+        task.comments.insert(comment.id.clone(), comment);
+        let mut rebuilt = make_task(id, "", 0, "");
+        (rebuilt.created_at, rebuilt.created_by) = (task.created_at, task.created_by.clone());
+        for update in task_field_updates(&task) {
+            apply_field_update(&mut rebuilt, &update);
+        }
+        assert_eq!(rebuilt, task);
+    }
+
     // --- merge_task_list tests ---
 
     #[test]
@@ -343,4 +483,131 @@ mod tests {
         assert!(!apply_field_update(&mut task, &update));
         assert_eq!(task.assignee.value, Some("alice".to_string()));
     }
+
+    #[test]
+    fn apply_field_update_rich_fields() {
+        let mut task = make_task(TaskId::new(), "task", 100, "peer-a");
+        let updates = [
+            TaskFieldUpdate::Description(make_reg("steps\nto reproduce", 200, "peer-b")),
+            TaskFieldUpdate::Due(LwwRegister::new(Some(5_000), 200, "peer-b".to_string())),
+            TaskFieldUpdate::Priority(LwwRegister::new(
+                TaskPriority::High,
+                200,
+                "peer-b".to_string(),
+            )),
+        ];
+        for update in &updates {
+            assert!(apply_field_update(&mut task, update));
+        }
+        assert_eq!(task.description.value, "steps\nto reproduce");
+        assert_eq!(task.due.value, Some(5_000));
+        assert_eq!(task.priority.value, TaskPriority::High);
+
+        let stale = TaskFieldUpdate::Priority(LwwRegister::new(
+            TaskPriority::Low,
+            150,
+            "peer-c".to_string(),
+        ));
+        assert!(!apply_field_update(&mut task, &stale));
+        assert_eq!(task.priority.value, TaskPriority::High);
+    }
+
+    // --- label set tests ---
+
+    fn label_update(label: &str, present: bool, ts: u64, author: &str) -> TaskFieldUpdate {
+        TaskFieldUpdate::Label {
+            label: label.to_string(),
+            present: LwwRegister::new(present, ts, author.to_string()),
+        }
+    }
+
+    #[test]
+    fn label_add_and_remove() {
+        let mut task = make_task(TaskId::new(), "task", 100, "peer-a");
+        assert!(apply_field_update(
+            &mut task,
+            &label_update("bug", true, 200, "peer-a")
+        ));
+        assert!(task.has_label("bug"));
+        assert!(apply_field_update(
+            &mut task,
+            &label_update("bug", false, 300, "peer-b")
+        ));
+        assert!(!task.has_label("bug"));
+    }
+
+    #[test]
+    fn stale_label_add_does_not_resurrect_removed_label() {
+        let mut task = make_task(TaskId::new(), "task", 100, "peer-a");
+        apply_field_update(&mut task, &label_update("bug", true, 200, "peer-a"));
+        apply_field_update(&mut task, &label_update("bug", false, 300, "peer-b"));
+        assert!(!apply_field_update(
+            &mut task,
+            &label_update("bug", true, 250, "peer-c")
+        ));
+        assert!(!task.has_label("bug"));
+    }
+
+    #[test]
+    fn concurrent_changes_to_different_labels_survive() {
+        let id = TaskId::new();
+        let mut a = make_task(id.clone(), "task", 100, "peer-a");
+        let mut b = make_task(id, "task", 100, "peer-a");
+        apply_field_update(&mut a, &label_update("bug", true, 200, "peer-a"));
+        apply_field_update(&mut b, &label_update("ui", true, 200, "peer-b"));
+        apply_field_update(&mut b, &label_update("bug", false, 150, "peer-b"));
+
+        let (a_before, b_before) = (a.clone(), b.clone());
+        merge_task(&mut a, &b_before);
+        merge_task(&mut b, &a_before);
+        assert_eq!(a, b);
+        assert_eq!(a.label_names().collect::<Vec<_>>(), ["bug", "ui"]);
+    }
+
+    #[test]
+    fn merge_lww_set_idempotent() {
+        let mut labels = BTreeMap::new();
+        labels.insert(
+            "bug".to_string(),
+            LwwRegister::new(true, 100, "peer-a".into()),
+        );
+        let snapshot = labels.clone();
+        merge_lww_set(&mut labels, &snapshot);
+        assert_eq!(labels, snapshot);
+    }
+
+    // --- comment tests ---
+
+    #[test]
+    fn comments_merge_by_union() {
+        let id = TaskId::new();
+        let comment = |body: &str, author: &str| TaskComment {
+            id: CommentId::new(),
+            reply_to: None,
+            author: author.to_string(),
+            body: body.to_string(),
+            timestamp: 100,
+        };
+        let mut a = make_task(id.clone(), "Task", 100, "peer-a");
+        let mut b = make_task(id, "Task", 100, "peer-a");
+        let first = comment("first", "peer-a");
+        assert!(apply_field_update(
+            &mut a,
+            &TaskFieldUpdate::Comment(first.clone())
+        ));
+        assert!(!apply_field_update(
+            &mut a,
+            &TaskFieldUpdate::Comment(first)
+        ));
+        assert!(apply_field_update(
+            &mut b,
+            &TaskFieldUpdate::Comment(comment("second", "peer-b"))
+        ));
+
+        let snapshot = a.clone();
+        merge_task(&mut a, &b);
+        merge_task(&mut b, &snapshot);
+        assert_eq!(a, b);
+        assert_eq!(a.comments.len(), 2);
+    }
 }
@@ -1,17 +1,45 @@
 //! Shared task coordination for `TermChat` rooms.
 //!
 //! Provides room-scoped task lists with CRDT-based synchronization
-//! using Last-Write-Wins (LWW) registers per field. Task changes
-//! are broadcast to all room members as encrypted `TaskSync` messages.
+//! using Last-Write-Wins (LWW) registers per field, stamped by a
+//! per-peer hybrid logical clock. Task changes are broadcast to all
+//! room members as encrypted `TaskSync` messages. Peers periodically
+//! exchange per-room digests and resend only the tasks that differ.
+//! Tasks carry threaded comments, and the manager keeps an activity
+//! feed of the changes it applies. Room state can be persisted to a
+//! [`TaskStore`] so it survives restarts, and task lists can be
+//! exchanged with other tools as plain JSON, CSV or Markdown (see
+//! [`interchange`]). What each member may change follows its room role
+//! (see [`permissions`]).
 
+pub mod activity;
+pub mod board;
+pub mod clock;
+pub mod graph;
+pub mod interchange;
 pub mod manager;
 pub mod merge;
+pub mod permissions;
+pub mod recurrence;
+pub mod reminder;
+pub mod store;
+pub mod sync;
 
+pub use activity::ActivityEntry;
+pub use board::BoardFilter;
+pub use clock::HybridClock;
+pub use graph::TaskGraph;
 pub use manager::TaskManager;
-pub use merge::{apply_field_update, merge_lww, merge_task, merge_task_list};
+pub use merge::{apply_field_update, merge_lww, merge_lww_set, merge_task, merge_task_list};
+pub use permissions::PermissionWarning;
+pub use reminder::spawn_reminders;
+pub use store::{TaskStore, TaskStoreError};
+pub use sync::spawn_anti_entropy;
 
 use thiserror::Error;
 
+use permissions::TaskAction;
+
 /// Errors that can occur during task operations.
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum TaskError {
@@ -30,4 +58,49 @@ pub enum TaskError {
     /// Invalid assignee peer ID.
     #[error("invalid assignee: {0}")]
     InvalidAssignee(String),
+    /// Task description exceeds the maximum length.
+    #[error("task description too long (max 4096 characters)")]
+    DescriptionTooLong,
+    /// Label is empty, too long, or contains whitespace.
+    #[error("invalid label: {0:?}")]
+    InvalidLabel(String),
+    /// The parent or blocker edge would make the task depend on itself.
+    #[error("task dependency would form a cycle")]
+    DependencyCycle,
+    /// Comment body cannot be empty.
+    #[error("comment cannot be empty")]
+    CommentEmpty,
+    /// Comment body exceeds the maximum length.
+    #[error("comment too long (max 2000 characters)")]
+    CommentTooLong,
+    /// The comment being replied to is not on the task.
+    #[error("comment not found: {0}")]
+    CommentNotFound(String),
+    /// The recurrence rule cannot be evaluated.
+    #[error("invalid recurrence: {0}")]
+    InvalidRecurrence(String),
+    /// The local peer's role in the room does not allow the change.
+    #[error("not allowed to {0} this task")]
+    PermissionDenied(TaskAction),
+}
+
+/// Task room shared by the two peers of a direct conversation.
+///
+/// The ID is the same whichever side computes it, so both peers file
+/// their tasks under one room.
+#[must_use]
+pub fn direct_room_id(a: &str, b: &str) -> String {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    format!("dm:{first}:{second}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_room_id_is_symmetric() {
+        assert_eq!(direct_room_id("alice", "bob"), "dm:alice:bob");
+        assert_eq!(direct_room_id("bob", "alice"), "dm:alice:bob");
+    }
 }
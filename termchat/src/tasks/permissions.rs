@@ -0,0 +1,204 @@
+//! Room-scoped task permissions.
+//!
+//! What a member may do to a room's tasks follows its [`MemberRole`]:
+//!
+//! | Role      | Create, edit, comment | Reassign   | Delete     |
+//! |-----------|-----------------------|------------|------------|
+//! | Admin     | yes                   | yes        | yes        |
+//! | Member    | yes                   | own tasks  | own tasks  |
+//! | Agent     | yes                   | own tasks  | no         |
+//! | Read-only | no                    | no         | no         |
+//!
+//! A member's own tasks are those it created or is assigned to. Peers that
+//! are not members may do nothing, and a room whose members were never
+//! registered with the [`TaskManager`](super::TaskManager) is unrestricted.
+//!
+//! The manager checks local writes against the rules and remote changes
+//! against the peer that sent them, as the transport reports it. A remote
+//! field update must also be written by its sender; only an admin may pass
+//! on other peers' writes in a full state. Remote changes that break the
+//! rules are not merged; they are reported as [`PermissionWarning`]s.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use termchat_proto::room::{MemberInfo, MemberRole};
+use termchat_proto::task::{Task, TaskFieldUpdate, TaskId, TaskStatus};
+
+/// A kind of change to a task, as far as permissions are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskAction {
+    /// Create a new task.
+    Create,
+    /// Change a field other than the assignee, or the status of a task
+    /// that is not and does not become deleted.
+    Edit,
+    /// Change the assignee.
+    Reassign,
+    /// Post a comment.
+    Comment,
+    /// Delete a task or restore a deleted one.
+    Delete,
+}
+
+impl TaskAction {
+    /// The action `update` performs on `task` (`None` for a task that is
+    /// not known yet).
+    #[must_use]
+    pub fn of(task: Option<&Task>, update: &TaskFieldUpdate) -> Self {
+        match update {
+            TaskFieldUpdate::Status(reg)
+                if reg.value == TaskStatus::Deleted
+                    || task.is_some_and(|t| t.status.value == TaskStatus::Deleted) =>
+            {
+                Self::Delete
+            }
+            TaskFieldUpdate::Assignee(_) => Self::Reassign,
+            TaskFieldUpdate::Comment(_) => Self::Comment,
+            _ => Self::Edit,
+        }
+    }
+}
+
+impl fmt::Display for TaskAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create => write!(f, "create"),
+            Self::Edit => write!(f, "edit"),
+            Self::Reassign => write!(f, "reassign"),
+            Self::Comment => write!(f, "comment on"),
+            Self::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// Whether `role` may perform `action` on a task, `own` saying whether
+/// the task is the member's own.
+#[must_use]
+pub const fn role_allows(role: MemberRole, action: TaskAction, own: bool) -> bool {
+    match (role, action) {
+        (MemberRole::ReadOnly, _) | (MemberRole::Agent, TaskAction::Delete) => false,
+        (MemberRole::Member | MemberRole::Agent, TaskAction::Reassign | TaskAction::Delete) => own,
+        _ => true,
+    }
+}
+
+/// The roles of one room's members.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomPermissions {
+    /// `PeerId` -> role.
+    roles: HashMap<String, MemberRole>,
+}
+
+impl RoomPermissions {
+    /// Permissions for a room with these members.
+    #[must_use]
+    pub fn new(members: &[MemberInfo]) -> Self {
+        Self {
+            roles: members
+                .iter()
+                .map(|member| (member.peer_id.clone(), member.role()))
+                .collect(),
+        }
+    }
+
+    /// A member's role, or `None` if the peer is not a member.
+    #[must_use]
+    pub fn role(&self, peer_id: &str) -> Option<MemberRole> {
+        self.roles.get(peer_id).copied()
+    }
+
+    /// Whether `peer_id` may perform `action` on `task` (`None` for a task
+    /// that is not known yet, which is nobody's own).
+    #[must_use]
+    pub fn allows(&self, peer_id: &str, action: TaskAction, task: Option<&Task>) -> bool {
+        let own = task.is_some_and(|task| {
+            task.created_by == peer_id || task.assignee.value.as_deref() == Some(peer_id)
+        });
+        self.role(peer_id)
+            .is_some_and(|role| role_allows(role, action, own))
+    }
+}
+
+/// A remote change that was not merged because its sender may not make it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionWarning {
+    /// The room the task belongs to.
+    pub room_id: String,
+    /// The task the change was for.
+    pub task_id: TaskId,
+    /// `PeerId` of the peer that sent the change.
+    pub sender: String,
+    /// What the change would have done.
+    pub action: TaskAction,
+}
+
+impl fmt::Display for PermissionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ignored change from {}: not allowed to {} task {}",
+            self.sender, self.action, self.task_id
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termchat_proto::task::LwwRegister;
+
+    use super::*;
+
+    fn member(peer_id: &str, is_admin: bool, is_agent: bool, is_read_only: bool) -> MemberInfo {
+        MemberInfo {
+            peer_id: peer_id.to_string(),
+            display_name: peer_id.to_string(),
+            is_admin,
+            is_agent,
+            is_read_only,
+        }
+    }
+
+    #[test]
+    fn role_table() {
+        use TaskAction::{Comment, Create, Delete, Edit, Reassign};
+        for action in [Create, Edit, Comment, Reassign, Delete] {
+            assert!(role_allows(MemberRole::Admin, action, false));
+            assert!(!role_allows(MemberRole::ReadOnly, action, true));
+        }
+        for role in [MemberRole::Member, MemberRole::Agent] {
+            assert!(role_allows(role, Edit, false));
+            assert!(role_allows(role, Comment, false));
+            assert!(!role_allows(role, Reassign, false));
+            assert!(role_allows(role, Reassign, true));
+        }
+        assert!(role_allows(MemberRole::Member, Delete, true));
+        assert!(!role_allows(MemberRole::Member, Delete, false));
+        assert!(!role_allows(MemberRole::Agent, Delete, true));
+    }
+
+    #[test]
+    fn status_changes_touching_deleted_are_deletes() {
+        let deleted = TaskFieldUpdate::Status(LwwRegister::new(
+            TaskStatus::Deleted,
+            1,
+            "peer-a".to_string(),
+        ));
+        assert_eq!(TaskAction::of(None, &deleted), TaskAction::Delete);
+        let reopened =
+            TaskFieldUpdate::Status(LwwRegister::new(TaskStatus::Open, 1, "peer-a".to_string()));
+        assert_eq!(TaskAction::of(None, &reopened), TaskAction::Edit);
+    }
+
+    #[test]
+    fn non_members_may_do_nothing() {
+        let permissions = RoomPermissions::new(&[
+            member("peer-admin", true, false, false),
+            member("peer-viewer", false, false, true),
+        ]);
+        assert!(permissions.allows("peer-admin", TaskAction::Delete, None));
+        assert!(!permissions.allows("peer-viewer", TaskAction::Comment, None));
+        assert!(!permissions.allows("peer-stranger", TaskAction::Create, None));
+        assert_eq!(permissions.role("peer-viewer"), Some(MemberRole::ReadOnly));
+    }
+}
@@ -0,0 +1,290 @@
+//! Recurrence rules: when a repeating task's next instance is due.
+//!
+//! Completing a task with a [`Recurrence`] creates its next instance (see
+//! [`TaskManager::update_status`](super::TaskManager::update_status)),
+//! due at the rule's first occurrence after both the old due date and the
+//! completion, so a late completion skips the occurrences it missed.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeDelta, Timelike, Utc};
+use termchat_proto::task::Recurrence;
+
+/// Days searched for a cron match before giving up. Long enough for any
+/// valid day-of-month and month combination, including 29 February.
+const MAX_SEARCH_DAYS: i64 = 366 * 8;
+
+/// A parsed five-field cron expression: `minute hour day-of-month month
+/// day-of-week`, evaluated in UTC.
+///
+/// Each field is `*`, a number, a range `a-b`, any of those with a step
+/// (`*/15`, `1-5/2`), or a comma-separated list of them. Day of week runs
+/// from 0 (Sunday) to 6, with 7 also meaning Sunday. As in cron, when both
+/// day fields are restricted a day matching either one matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 cron fields, got {}", fields.len()));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7 is another name for Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl CronSchedule {
+    /// The first matching minute strictly after `after`, or `None` if none
+    /// falls within the search window (e.g. `0 0 31 2 *`).
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start
+                .date_naive()
+                .checked_add_signed(TimeDelta::days(offset))?;
+            if !self.matches_day(date) {
+                continue;
+            }
+            let from = if offset == 0 {
+                start.time()
+            } else {
+                NaiveTime::MIN
+            };
+            if let Some(time) = self.first_time_from(from) {
+                return Some(date.and_time(time).and_utc());
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => day,
+            (true, false) => weekday,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// The first matching time of day at or after `from`.
+    fn first_time_from(&self, from: NaiveTime) -> Option<NaiveTime> {
+        (from.hour()..24)
+            .filter(|&hour| has(self.hours, hour))
+            .find_map(|hour| {
+                let first_minute = if hour == from.hour() {
+                    from.minute()
+                } else {
+                    0
+                };
+                (first_minute..60)
+                    .find(|&minute| has(self.minutes, minute))
+                    .and_then(|minute| NaiveTime::from_hms_opt(hour, minute, 0))
+            })
+    }
+}
+
+const fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parses one cron field into a bit set of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_value(step, 1, max)?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let start = parse_value(range, min, max)?;
+            // `5/10` means every 10th value from 5.
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            return Err(format!("invalid cron range: {range}"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("invalid cron value: {value} (expected {min}-{max})"))
+}
+
+/// Checks that a recurrence rule can be evaluated.
+///
+/// # Errors
+///
+/// Returns a description of the problem with a cron expression.
+pub fn validate(rule: &Recurrence) -> Result<(), String> {
+    match rule {
+        Recurrence::Daily | Recurrence::Weekly => Ok(()),
+        Recurrence::Cron(expr) => expr.parse::<CronSchedule>().map(|_| ()),
+    }
+}
+
+/// When the instance following one due at `due` and completed at
+/// `completed` is due: the rule's first occurrence after both.
+///
+/// Daily and weekly rules keep the time of day of the old due date (or of
+/// the completion if there was none). Returns `None` for a cron
+/// expression that does not parse or never matches.
+#[must_use]
+pub fn next_occurrence(
+    rule: &Recurrence,
+    due: Option<DateTime<Utc>>,
+    completed: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let period = match rule {
+        Recurrence::Daily => Duration::days(1),
+        Recurrence::Weekly => Duration::weeks(1),
+        Recurrence::Cron(expr) => {
+            let schedule = expr.parse::<CronSchedule>().ok()?;
+            return schedule.next_after(due.map_or(completed, |due| due.max(completed)));
+        }
+    };
+    let base = due.unwrap_or(completed);
+    let missed = (completed - base).num_seconds() / period.num_seconds();
+    let periods = i32::try_from(missed.max(0) + 1).ok()?;
+    base.checked_add_signed(period * periods)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn daily_and_weekly_follow_the_due_date() {
+        let due = at("2026-03-02T09:00:00Z");
+        let early = at("2026-03-01T18:00:00Z");
+        assert_eq!(
+            next_occurrence(&Recurrence::Daily, Some(due), early),
+            Some(at("2026-03-03T09:00:00Z"))
+        );
+        assert_eq!(
+            next_occurrence(&Recurrence::Weekly, Some(due), early),
+            Some(at("2026-03-09T09:00:00Z"))
+        );
+        // Completed three days late: the missed days are skipped.
+        let late = at("2026-03-05T10:00:00Z");
+        assert_eq!(
+            next_occurrence(&Recurrence::Daily, Some(due), late),
+            Some(at("2026-03-06T09:00:00Z"))
+        );
+        assert_eq!(
+            next_occurrence(&Recurrence::Daily, None, late),
+            Some(at("2026-03-06T10:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn cron_weekday_mornings() {
+        let rule = Recurrence::Cron("30 9 * * 1-5".to_string());
+        // Friday afternoon -> Monday morning.
+        assert_eq!(
+            next_occurrence(&rule, None, at("2026-03-06T15:00:00Z")),
+            Some(at("2026-03-09T09:30:00Z"))
+        );
+        // Before the slot on a weekday -> the same day.
+        assert_eq!(
+            next_occurrence(&rule, None, at("2026-03-09T09:29:59Z")),
+            Some(at("2026-03-09T09:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn cron_fields() {
+        let schedule: CronSchedule = "*/15 0 1,15 * *".parse().unwrap();
+        assert_eq!(
+            schedule.next_after(at("2026-03-01T00:15:00Z")),
+            Some(at("2026-03-01T00:30:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2026-03-01T00:45:00Z")),
+            Some(at("2026-03-15T00:00:00Z"))
+        );
+        // Either day field matches when both are restricted; 7 is Sunday.
+        let schedule: CronSchedule = "0 12 13 * 7".parse().unwrap();
+        assert_eq!(
+            schedule.next_after(at("2026-03-09T00:00:00Z")),
+            Some(at("2026-03-13T12:00:00Z"))
+        );
+        assert_eq!(
+            schedule.next_after(at("2026-03-13T12:00:00Z")),
+            Some(at("2026-03-15T12:00:00Z"))
+        );
+        let leap_day: CronSchedule = "0 0 29 2 *".parse().unwrap();
+        assert_eq!(
+            leap_day.next_after(at("2026-03-01T00:00:00Z")),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        let never: CronSchedule = "0 0 31 2 *".parse().unwrap();
+        assert_eq!(never.next_after(at("2026-03-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn invalid_cron_rejected() {
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "* 5-1 * * *",
+            "*/0 * * * *",
+            "a * * * *",
+        ] {
+            assert!(
+                validate(&Recurrence::Cron(expr.to_string())).is_err(),
+                "{expr}"
+            );
+        }
+        assert!(validate(&Recurrence::Cron("0 9 * * 1-5".to_string())).is_ok());
+    }
+}
@@ -0,0 +1,82 @@
+//! Due-date reminders for tasks.
+//!
+//! [`spawn_reminders`] checks every room on an interval and hands each task
+//! that has come due to a callback once, which raises the notification in
+//! the TUI and forwards it to connected agents.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use termchat_proto::task::Task;
+
+use super::TaskManager;
+
+/// Spawn a background task that passes every task in `tasks` that has come
+/// due to `notify`, checking every `interval` starting immediately.
+///
+/// Each task is reported once per due time (see
+/// [`TaskManager::due_reminders`]). The task stops when the returned
+/// [`tokio::task::JoinHandle`] is aborted or the runtime shuts down.
+pub fn spawn_reminders<F, Fut>(
+    tasks: Arc<Mutex<TaskManager>>,
+    interval: Duration,
+    mut notify: F,
+) -> tokio::task::JoinHandle<()>
+where
+    F: FnMut(Task) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tick.tick().await;
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+            let due = tasks.lock().due_reminders(now_ms);
+            for task in due {
+                notify(task).await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::duration_suboptimal_units, clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test(start_paused = true)]
+    async fn reminds_each_due_task_once() {
+        let tasks = Arc::new(Mutex::new(TaskManager::new("local".to_string())));
+        let (overdue, _) = tasks.lock().create_task("room-1", "Overdue").unwrap();
+        let (later, _) = tasks.lock().create_task("room-1", "Later").unwrap();
+        tasks
+            .lock()
+            .update_due("room-1", &overdue.id, Some(1_000))
+            .unwrap();
+        tasks
+            .lock()
+            .update_due("room-1", &later.id, Some(u64::MAX))
+            .unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = spawn_reminders(Arc::clone(&tasks), Duration::from_secs(60), move |task| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(task);
+            }
+        });
+
+        let reminded = rx.recv().await.unwrap();
+        assert_eq!(reminded.id, overdue.id);
+        // Later ticks do not repeat it.
+        tokio::time::sleep(Duration::from_secs(180)).await;
+        assert!(rx.try_recv().is_err());
+        handle.abort();
+    }
+}
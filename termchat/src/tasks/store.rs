@@ -0,0 +1,270 @@
+//! Durable per-room task storage.
+//!
+//! [`TaskStore`] keeps one file per room holding the room's full CRDT task
+//! state, tombstones included, so a restarted client can rejoin the room
+//! without waiting for another member to resend it. Files are rewritten
+//! after every change (to a temporary file that is then renamed over the
+//! original), so a crash never leaves a half-written room behind.
+//!
+//! The same room snapshot can be exported to and imported from JSON, for
+//! backups and for moving tasks between machines.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use termchat_proto::task::Task;
+
+/// On-disk format version of room files and JSON exports.
+const TASK_STORE_FORMAT_VERSION: u8 = 1;
+
+/// Extension of room files in the store directory.
+const ROOM_FILE_EXTENSION: &str = "bin";
+
+/// Errors that can occur while loading or persisting task state.
+#[derive(Debug, thiserror::Error)]
+pub enum TaskStoreError {
+    /// Reading or writing a room file failed.
+    #[error("task store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A room file could not be encoded or decoded.
+    #[error("task store codec error: {0}")]
+    Codec(#[from] postcard::Error),
+
+    /// A JSON export could not be encoded or decoded.
+    #[error("task JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A room file or export was written by an unknown format version.
+    #[error("unsupported task store format version {0}")]
+    UnsupportedVersion(u8),
+}
+
+/// Serialized form of one room's tasks, on disk and in JSON exports.
+#[derive(Serialize, Deserialize)]
+struct RoomSnapshot {
+    /// Format version ([`TASK_STORE_FORMAT_VERSION`]).
+    version: u8,
+    /// The room the tasks belong to.
+    room_id: String,
+    /// Every task in the room, deleted ones included.
+    tasks: Vec<Task>,
+}
+
+impl RoomSnapshot {
+    fn new<'a>(room_id: &str, tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        Self {
+            version: TASK_STORE_FORMAT_VERSION,
+            room_id: room_id.to_string(),
+            tasks: tasks.into_iter().cloned().collect(),
+        }
+    }
+
+    fn check_version(self) -> Result<Self, TaskStoreError> {
+        if self.version == TASK_STORE_FORMAT_VERSION {
+            Ok(self)
+        } else {
+            Err(TaskStoreError::UnsupportedVersion(self.version))
+        }
+    }
+}
+
+/// A directory of per-room task files.
+#[derive(Debug, Clone)]
+pub struct TaskStore {
+    dir: PathBuf,
+}
+
+impl TaskStore {
+    /// Use `dir` as the store, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError::Io`] if the directory cannot be created.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, TaskStoreError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// The directory holding the room files.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load every room in the store, as room ID -> tasks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError`] if a room file cannot be read or decoded.
+    pub fn load_all(&self) -> Result<HashMap<String, Vec<Task>>, TaskStoreError> {
+        let mut rooms = HashMap::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(ROOM_FILE_EXTENSION) {
+                continue;
+            }
+            let bytes = std::fs::read(&path)?;
+            let snapshot = postcard::from_bytes::<RoomSnapshot>(&bytes)?.check_version()?;
+            rooms.insert(snapshot.room_id, snapshot.tasks);
+        }
+        Ok(rooms)
+    }
+
+    /// Replace the stored state of `room_id` with `tasks`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TaskStoreError`] if the room file cannot be written.
+    pub fn save_room<'a>(
+        &self,
+        room_id: &str,
+        tasks: impl IntoIterator<Item = &'a Task>,
+    ) -> Result<(), TaskStoreError> {
+        let bytes = postcard::to_allocvec(&RoomSnapshot::new(room_id, tasks))?;
+        let path = self.room_path(room_id);
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// File holding `room_id`'s tasks.
+    fn room_path(&self, room_id: &str) -> PathBuf {
+        let name: String = room_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{name}.{ROOM_FILE_EXTENSION}"))
+    }
+}
+
+/// Export a room's tasks as pretty-printed JSON, tombstones and LWW
+/// metadata included, so an import merges exactly like a sync.
+///
+/// # Errors
+///
+/// Returns [`TaskStoreError::Json`] if encoding fails.
+pub fn export_json<'a>(
+    room_id: &str,
+    tasks: impl IntoIterator<Item = &'a Task>,
+) -> Result<String, TaskStoreError> {
+    Ok(serde_json::to_string_pretty(&RoomSnapshot::new(
+        room_id, tasks,
+    ))?)
+}
+
+/// Parse a JSON export into its room ID and tasks.
+///
+/// # Errors
+///
+/// Returns [`TaskStoreError`] if the JSON is malformed or was written by an
+/// unknown format version.
+pub fn import_json(json: &str) -> Result<(String, Vec<Task>), TaskStoreError> {
+    let snapshot = serde_json::from_str::<RoomSnapshot>(json)?.check_version()?;
+    Ok((snapshot.room_id, snapshot.tasks))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use termchat_proto::task::{LwwRegister, TaskId, TaskPriority, TaskStatus};
+
+    use super::*;
+
+    fn task(title: &str) -> Task {
+        let (ts, author) = (100, "peer-a".to_string());
+        let mut task = Task {
+            id: TaskId::new(),
+            room_id: "room-1".to_string(),
+            title: LwwRegister::new(title.to_string(), ts, author.clone()),
+            status: LwwRegister::new(TaskStatus::Open, ts, author.clone()),
+            assignee: LwwRegister::new(None, ts, author.clone()),
+            description: LwwRegister::new(String::new(), ts, author.clone()),
+            due: LwwRegister::new(Some(5_000), ts, author.clone()),
+            recurrence: LwwRegister::new(None, ts, author.clone()),
+            priority: LwwRegister::new(TaskPriority::High, ts, author.clone()),
+            labels: BTreeMap::new(),
+            parent: LwwRegister::new(None, ts, author.clone()),
+            blocked_by: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            created_at: ts,
+            created_by: author.clone(),
+        };
+        task.labels.insert(
+            "bug".to_string(),
+            LwwRegister::new(true, ts, author.clone()),
+        );
+        task.blocked_by
+            .insert(TaskId::new(), LwwRegister::new(true, ts, author));
+        task
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join("termchat-test-tasks")
+            .join(format!("{name}-{}", uuid::Uuid::now_v7()))
+    }
+
+    #[test]
+    fn save_and_load_rooms() {
+        let dir = temp_dir("save");
+        let store = TaskStore::open(&dir).unwrap();
+        let (a, b) = (task("A"), task("B"));
+        store.save_room("room-1", [&a, &b]).unwrap();
+        store.save_room("room/2", [&b]).unwrap();
+        store.save_room("room-1", [&a]).unwrap();
+
+        let rooms = TaskStore::open(&dir).unwrap().load_all().unwrap();
+        assert_eq!(rooms.len(), 2);
+        assert_eq!(rooms["room-1"], [a]);
+        assert_eq!(rooms["room/2"], [b]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn corrupt_room_file_is_an_error() {
+        let dir = temp_dir("corrupt");
+        let store = TaskStore::open(&dir).unwrap();
+        std::fs::write(dir.join("room.bin"), [0xFF, 0xFF, 0xFF]).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        assert!(store.load_all().is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let tasks = [task("A"), task("B")];
+        let json = export_json("room-1", &tasks).unwrap();
+        assert!(json.contains("\"title\""));
+        let (room_id, imported) = import_json(&json).unwrap();
+        assert_eq!(room_id, "room-1");
+        assert_eq!(imported, tasks);
+    }
+
+    #[test]
+    fn json_with_unknown_version_rejected() {
+        let json = export_json("room-1", &[]).unwrap().replace(
+            &format!("\"version\": {TASK_STORE_FORMAT_VERSION}"),
+            "\"version\": 99",
+        );
+        assert!(matches!(
+            import_json(&json),
+            Err(TaskStoreError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            import_json("not json"),
+            Err(TaskStoreError::Json(_))
+        ));
+    }
+}
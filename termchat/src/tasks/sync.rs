@@ -0,0 +1,122 @@
+//! Periodic anti-entropy for task sync.
+//!
+//! Field updates are fire-and-forget, so a peer that was offline or lost a
+//! message drifts from the rest of the room until something repairs it.
+//! [`spawn_anti_entropy`] regularly sends a [`TaskSyncMessage::Digest`] for
+//! every room; peers answer via [`TaskManager::reply_to`] with only the
+//! digest buckets that differ, so rooms already in sync cost one small
+//! message per interval regardless of how many tasks they hold.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use termchat_proto::task::TaskSyncMessage;
+
+use super::TaskManager;
+
+/// Spawn a background task that sends a digest of every room in `tasks`
+/// through `send` every `interval`, starting immediately.
+///
+/// `send` is expected to deliver the digest to the room's members. The
+/// task stops when the returned [`tokio::task::JoinHandle`] is aborted or
+/// the runtime shuts down.
+pub fn spawn_anti_entropy<F, Fut>(
+    tasks: Arc<Mutex<TaskManager>>,
+    interval: Duration,
+    mut send: F,
+) -> tokio::task::JoinHandle<()>
+where
+    F: FnMut(TaskSyncMessage) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tick.tick().await;
+            let digests: Vec<TaskSyncMessage> = {
+                let tasks = tasks.lock();
+                tasks
+                    .rooms()
+                    .iter()
+                    .map(|room| tasks.build_digest(room))
+                    .collect()
+            };
+            for digest in digests {
+                send(digest).await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::transport::PeerId;
+    use tokio::sync::mpsc;
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_a_digest_per_room_every_interval() {
+        let tasks = Arc::new(Mutex::new(TaskManager::new("local".to_string())));
+        tasks.lock().create_task("room-a", "A").unwrap();
+        tasks.lock().create_task("room-b", "B").unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = spawn_anti_entropy(Arc::clone(&tasks), Duration::from_secs(30), move |msg| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(msg);
+            }
+        });
+
+        for _ in 0..2 {
+            let mut rooms = Vec::new();
+            for _ in 0..2 {
+                match rx.recv().await.unwrap() {
+                    TaskSyncMessage::Digest { room_id, .. } => rooms.push(room_id),
+                    other => panic!("expected Digest, got {other:?}"),
+                }
+            }
+            assert_eq!(rooms, ["room-a", "room-b"]);
+        }
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn digest_exchange_repairs_divergence() {
+        let (alice, bob) = (
+            Arc::new(Mutex::new(TaskManager::new("alice".to_string()))),
+            Arc::new(Mutex::new(TaskManager::new("bob".to_string()))),
+        );
+        // Bob missed Alice's task entirely.
+        alice.lock().create_task("room-1", "Only on Alice").unwrap();
+        bob.lock().create_task("room-1", "Only on Bob").unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = spawn_anti_entropy(Arc::clone(&alice), Duration::from_secs(30), move |msg| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(msg);
+            }
+        });
+        let digest = rx.recv().await.unwrap();
+        handle.abort();
+
+        // Bob answers with his differing buckets and asks for Alice's.
+        let replies = bob.lock().reply_to(&digest);
+        for reply in replies {
+            alice.lock().apply_remote(&PeerId::new("bob"), &reply);
+            let answers = alice.lock().reply_to(&reply);
+            for answer in answers {
+                bob.lock().apply_remote(&PeerId::new("alice"), &answer);
+            }
+        }
+        assert_eq!(alice.lock().get_tasks("room-1").len(), 2);
+        assert_eq!(bob.lock().get_tasks("room-1").len(), 2);
+        let digest = alice.lock().build_digest("room-1");
+        assert!(bob.lock().reply_to(&digest).is_empty());
+    }
+}
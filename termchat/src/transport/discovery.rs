@@ -0,0 +1,272 @@
+//! LAN peer discovery via mDNS / DNS-SD.
+//!
+//! [`LanDiscovery`] advertises this peer's QUIC listener as a
+//! `_termchat._udp.local.` service and browses for other peers on the same
+//! network. Each advertisement carries the peer's [`PeerId`], the listener
+//! port, and (if known) its identity key fingerprint in TXT records, so a
+//! discovered peer can be dialed directly instead of through the relay.
+//!
+//! Discovery is advisory: the advertised `PeerId` is not authenticated here.
+//! Peer authentication still happens in the Noise handshake.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tokio::sync::mpsc;
+
+use super::{PeerId, TransportError};
+
+/// DNS-SD service type advertised by `TermChat` peers.
+pub const SERVICE_TYPE: &str = "_termchat._udp.local.";
+
+/// TXT record key holding the advertised `PeerId`.
+const TXT_PEER_ID: &str = "peer_id";
+
+/// TXT record key holding the identity key fingerprint.
+const TXT_FINGERPRINT: &str = "fp";
+
+/// Maximum length of the peer-ID part of an mDNS instance name.
+///
+/// DNS labels are limited to 63 bytes; the rest holds a random suffix.
+const MAX_INSTANCE_PREFIX_LEN: usize = 40;
+
+/// A `TermChat` peer found on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanPeer {
+    /// The peer's advertised identity.
+    pub peer_id: PeerId,
+    /// Addresses of the peer's QUIC listener.
+    pub addrs: Vec<SocketAddr>,
+    /// The peer's identity key fingerprint, if it advertised one.
+    pub fingerprint: Option<String>,
+}
+
+/// Changes in the set of peers visible on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryEvent {
+    /// A peer was found (or its advertisement changed).
+    Discovered(LanPeer),
+    /// A previously discovered peer withdrew its advertisement.
+    Lost(PeerId),
+}
+
+/// Advertises the local peer and browses for others via mDNS.
+///
+/// Dropping the handle withdraws the advertisement and stops browsing.
+pub struct LanDiscovery {
+    /// The mDNS responder/browser daemon.
+    daemon: ServiceDaemon,
+    /// Full DNS-SD name of our own advertisement.
+    fullname: String,
+}
+
+impl LanDiscovery {
+    /// Start advertising `local_id` on `port` and browsing for other peers.
+    ///
+    /// Discovery events for every other `TermChat` peer (never ourselves)
+    /// are delivered on the returned channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError::Io`] if the mDNS daemon cannot be started
+    /// or the service cannot be registered.
+    pub fn start(
+        local_id: &PeerId,
+        port: u16,
+        fingerprint: Option<&str>,
+        buffer: usize,
+    ) -> Result<(Self, mpsc::Receiver<DiscoveryEvent>), TransportError> {
+        let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+
+        let instance = instance_name(local_id);
+        let host_name = format!("{instance}.local.");
+        let mut properties = vec![(TXT_PEER_ID, local_id.as_str())];
+        if let Some(fingerprint) = fingerprint {
+            properties.push((TXT_FINGERPRINT, fingerprint));
+        }
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            &instance,
+            &host_name,
+            "",
+            port,
+            properties.as_slice(),
+        )
+        .map_err(mdns_error)?
+        .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon.register(service).map_err(mdns_error)?;
+
+        let browse = daemon.browse(SERVICE_TYPE).map_err(mdns_error)?;
+        let (tx, rx) = mpsc::channel(buffer);
+        let own_name = fullname.clone();
+        let local = local_id.clone();
+        tokio::spawn(async move {
+            // Removal events only carry the instance name.
+            let mut instances: HashMap<String, PeerId> = HashMap::new();
+            while let Ok(event) = browse.recv_async().await {
+                let event = match event {
+                    ServiceEvent::ServiceResolved(info) if info.get_fullname() != own_name => {
+                        match lan_peer(&info) {
+                            Some(peer) if peer.peer_id != local => {
+                                instances
+                                    .insert(info.get_fullname().to_string(), peer.peer_id.clone());
+                                DiscoveryEvent::Discovered(peer)
+                            }
+                            _ => continue,
+                        }
+                    }
+                    ServiceEvent::ServiceRemoved(_, name) => match instances.remove(&name) {
+                        Some(peer_id) => DiscoveryEvent::Lost(peer_id),
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        tracing::info!(instance = %fullname, port, "advertising on LAN");
+        Ok((Self { daemon, fullname }, rx))
+    }
+}
+
+impl Drop for LanDiscovery {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Convert a resolved DNS-SD service into a [`LanPeer`].
+///
+/// Returns `None` if the service carries no `peer_id` or no addresses.
+fn lan_peer(info: &ServiceInfo) -> Option<LanPeer> {
+    let peer_id = info
+        .get_property_val_str(TXT_PEER_ID)
+        .filter(|id| !id.is_empty())?;
+    let port = info.get_port();
+    let mut addrs: Vec<SocketAddr> = info
+        .get_addresses()
+        .iter()
+        .map(|ip| SocketAddr::new(*ip, port))
+        .collect();
+    if addrs.is_empty() || port == 0 {
+        return None;
+    }
+    // IPv4 first: link-local IPv6 needs a scope ID that DNS-SD does not carry.
+    addrs.sort_by_key(|addr| (addr.is_ipv6(), *addr));
+    Some(LanPeer {
+        peer_id: PeerId::new(peer_id),
+        addrs,
+        fingerprint: info
+            .get_property_val_str(TXT_FINGERPRINT)
+            .filter(|fp| !fp.is_empty())
+            .map(str::to_string),
+    })
+}
+
+/// Build a unique, DNS-safe instance name for `peer_id`.
+fn instance_name(peer_id: &PeerId) -> String {
+    let prefix: String = peer_id
+        .as_str()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(MAX_INSTANCE_PREFIX_LEN)
+        .collect();
+    format!("{prefix}-{:08x}", rand::random::<u32>())
+}
+
+/// Map an mDNS daemon error into a [`TransportError`].
+fn mdns_error(err: mdns_sd::Error) -> TransportError {
+    TransportError::Io(std::io::Error::other(err))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn service(properties: &[(&str, &str)], ip: &str, port: u16) -> ServiceInfo {
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            "bob-0000",
+            "bob-0000.local.",
+            ip,
+            port,
+            properties,
+        )
+        .unwrap()
+    }
+
+    // --- TXT parsing ---
+
+    #[test]
+    fn lan_peer_reads_txt_records() {
+        let info = service(
+            &[(TXT_PEER_ID, "bob"), (TXT_FINGERPRINT, "a1b2c3d4e5f60708")],
+            "192.168.1.20,fe80::1",
+            7000,
+        );
+        let peer = lan_peer(&info).unwrap();
+        assert_eq!(peer.peer_id, PeerId::new("bob"));
+        assert_eq!(peer.fingerprint.as_deref(), Some("a1b2c3d4e5f60708"));
+        assert_eq!(
+            peer.addrs,
+            vec![
+                "192.168.1.20:7000".parse().unwrap(),
+                "[fe80::1]:7000".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn lan_peer_without_fingerprint() {
+        let info = service(&[(TXT_PEER_ID, "bob")], "10.0.0.5", 7000);
+        assert_eq!(lan_peer(&info).unwrap().fingerprint, None);
+    }
+
+    #[test]
+    fn lan_peer_requires_peer_id_and_address() {
+        assert!(lan_peer(&service(&[], "10.0.0.5", 7000)).is_none());
+        assert!(lan_peer(&service(&[(TXT_PEER_ID, "")], "10.0.0.5", 7000)).is_none());
+        assert!(lan_peer(&service(&[(TXT_PEER_ID, "bob")], "", 7000)).is_none());
+        assert!(lan_peer(&service(&[(TXT_PEER_ID, "bob")], "10.0.0.5", 0)).is_none());
+    }
+
+    // --- live discovery ---
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn peers_discover_each_other() {
+        let (_alice, mut alice_rx) =
+            LanDiscovery::start(&PeerId::new("alice-lan"), 7001, Some("aa"), 8).unwrap();
+        let (_bob, _bob_rx) = LanDiscovery::start(&PeerId::new("bob-lan"), 7002, None, 8).unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            loop {
+                if let Some(DiscoveryEvent::Discovered(peer)) = alice_rx.recv().await
+                    && peer.peer_id == PeerId::new("bob-lan")
+                {
+                    return peer;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(event.addrs[0].port(), 7002);
+    }
+
+    // --- instance names ---
+
+    #[test]
+    fn instance_name_is_dns_safe_and_unique() {
+        let id = PeerId::new("alice@example.com with a very long display name indeed");
+        let first = instance_name(&id);
+        assert!(first.len() <= 63);
+        assert!(first.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        assert!(first.starts_with("alice-example-com"));
+        assert_ne!(first, instance_name(&id));
+    }
+}
@@ -4,18 +4,25 @@
 //! When sending, it tries the preferred transport first. If that fails,
 //! it falls back to the secondary transport. If both fail, the message
 //! is queued in a [`PendingQueue`] and retried when connectivity is
-//! restored.
+//! restored. Ephemeral traffic (presence, typing) is never queued.
 //!
 //! A background flush task can be spawned via [`HybridTransport::spawn_flush_task`]
 //! to periodically drain the pending queue.
+//!
+//! A preferred link that comes and goes at runtime (a direct QUIC connection
+//! to a peer that is only sometimes reachable) is wrapped in a
+//! [`TransportSlot`], which lets the link be installed, replaced, or lost
+//! without rebuilding the `HybridTransport` above it.
 
 use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 
-use tokio::sync::Mutex;
+use parking_lot::RwLock;
+use tokio::sync::{Mutex, mpsc, watch};
 use tracing;
 
-use super::{PeerId, Transport, TransportError, TransportType};
+use super::{PeerId, TrafficClass, Transport, TransportError, TransportType};
 
 /// A message waiting to be sent when transport becomes available.
 #[derive(Debug, Clone)]
@@ -24,6 +31,8 @@ pub struct PendingMessage {
     pub peer: PeerId,
     /// The encrypted payload.
     pub payload: Vec<u8>,
+    /// Traffic class the payload was sent with.
+    pub class: TrafficClass,
 }
 
 /// Queue of messages that could not be sent due to transport failure.
@@ -44,10 +53,19 @@ impl PendingQueue {
         }
     }
 
-    /// Add a message to the back of the queue.
+    /// Add a chat-class message to the back of the queue.
     pub async fn enqueue(&self, peer: PeerId, payload: Vec<u8>) {
+        self.enqueue_class(peer, TrafficClass::Chat, payload).await;
+    }
+
+    /// Add a message of the given traffic class to the back of the queue.
+    pub async fn enqueue_class(&self, peer: PeerId, class: TrafficClass, payload: Vec<u8>) {
         let mut q = self.queue.lock().await;
-        q.push_back(PendingMessage { peer, payload });
+        q.push_back(PendingMessage {
+            peer,
+            payload,
+            class,
+        });
         tracing::info!(queue_len = q.len(), "message queued for offline delivery");
     }
 
@@ -118,6 +136,31 @@ impl<P: Transport, F: Transport> HybridTransport<P, F> {
         }
     }
 
+    /// Returns a reference to the preferred transport.
+    #[must_use]
+    pub const fn preferred(&self) -> &P {
+        &self.preferred
+    }
+
+    /// Returns a reference to the fallback transport.
+    #[must_use]
+    pub const fn fallback(&self) -> &F {
+        &self.fallback
+    }
+
+    /// Returns the type of the transport that would carry a message to
+    /// `peer` right now, or `None` if neither is connected to it.
+    #[must_use]
+    pub fn active_type(&self, peer: &PeerId) -> Option<TransportType> {
+        if self.preferred.is_connected(peer) {
+            Some(self.preferred.transport_type())
+        } else if self.fallback.is_connected(peer) {
+            Some(self.fallback.transport_type())
+        } else {
+            None
+        }
+    }
+
     /// Attempt to flush all pending messages through the preferred transport,
     /// falling back to the secondary if needed.
     ///
@@ -128,11 +171,17 @@ impl<P: Transport, F: Transport> HybridTransport<P, F> {
         let mut sent = 0;
 
         for msg in messages {
-            if self.try_send(&msg.peer, &msg.payload).await.is_ok() {
+            if self
+                .try_send(&msg.peer, msg.class, &msg.payload)
+                .await
+                .is_ok()
+            {
                 sent += 1;
             } else {
                 // Re-queue messages that still cannot be sent.
-                self.pending.enqueue(msg.peer, msg.payload).await;
+                self.pending
+                    .enqueue_class(msg.peer, msg.class, msg.payload)
+                    .await;
             }
         }
 
@@ -174,8 +223,13 @@ impl<P: Transport, F: Transport> HybridTransport<P, F> {
 
     /// Internal: try preferred, then fallback. Returns the first success
     /// or the last error.
-    async fn try_send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
-        match self.preferred.send(peer, payload).await {
+    async fn try_send(
+        &self,
+        peer: &PeerId,
+        class: TrafficClass,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        match self.preferred.send_class(peer, class, payload).await {
             Ok(()) => Ok(()),
             Err(preferred_err) => {
                 tracing::debug!(
@@ -183,7 +237,7 @@ impl<P: Transport, F: Transport> HybridTransport<P, F> {
                     err = %preferred_err,
                     "preferred transport failed, trying fallback"
                 );
-                self.fallback.send(peer, payload).await
+                self.fallback.send_class(peer, class, payload).await
             }
         }
     }
@@ -191,14 +245,27 @@ impl<P: Transport, F: Transport> HybridTransport<P, F> {
 
 impl<P: Transport, F: Transport> Transport for HybridTransport<P, F> {
     async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
-        match self.try_send(peer, payload).await {
+        self.send_class(peer, TrafficClass::Chat, payload).await
+    }
+
+    async fn send_class(
+        &self,
+        peer: &PeerId,
+        class: TrafficClass,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        match self.try_send(peer, class, payload).await {
             Ok(()) => Ok(()),
+            // Stale presence or typing updates are not worth delivering later.
+            Err(err) if class == TrafficClass::Ephemeral => Err(err),
             Err(err) => {
                 tracing::warn!(
                     err = %err,
                     "all transports failed, queuing message for later delivery"
                 );
-                self.pending.enqueue(peer.clone(), payload.to_vec()).await;
+                self.pending
+                    .enqueue_class(peer.clone(), class, payload.to_vec())
+                    .await;
                 // Return the error so the caller knows it was queued, not delivered.
                 Err(err)
             }
@@ -229,7 +296,206 @@ impl<P: Transport, F: Transport> Transport for HybridTransport<P, F> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// TransportSlot
+// ---------------------------------------------------------------------------
+
+/// A swappable transport link that can be installed, replaced, or lost at
+/// runtime.
+///
+/// Each installed transport is drained by a background pump task into a
+/// channel shared by all links, so [`recv`](Transport::recv) is cancel-safe
+/// (it is raced against the fallback in [`HybridTransport::recv`]) and
+/// simply waits while no link is installed. A link whose `recv` fails, or
+/// whose `send` reports [`TransportError::ConnectionClosed`], is dropped
+/// from the slot; the loss is visible through [`subscribe`](Self::subscribe)
+/// rather than as a `recv` error.
+///
+/// Cloning a slot yields another handle to the same link.
+pub struct TransportSlot<T: Transport> {
+    /// Shared state.
+    inner: Arc<SlotInner<T>>,
+}
+
+/// Shared state behind a [`TransportSlot`].
+struct SlotInner<T> {
+    /// The installed link and the generation it was installed at.
+    current: RwLock<Option<(u64, Arc<T>)>>,
+    /// Bumped whenever a link is installed, cleared, or lost.
+    generation: watch::Sender<u64>,
+    /// Sender cloned into each pump task.
+    incoming_tx: mpsc::Sender<(PeerId, Vec<u8>)>,
+    /// Receiver drained by [`Transport::recv`].
+    incoming_rx: Mutex<mpsc::Receiver<(PeerId, Vec<u8>)>>,
+    /// Transport type reported by the slot.
+    kind: TransportType,
+}
+
+impl<T> SlotInner<T> {
+    /// Clear the slot if it still holds the link from `generation`.
+    fn clear_generation(&self, generation: u64) {
+        let mut current = self.current.write();
+        if current.as_ref().is_some_and(|(g, _)| *g == generation) {
+            *current = None;
+            drop(current);
+            self.generation.send_modify(|g| *g += 1);
+        }
+    }
+}
+
+impl<T: Transport> Clone for TransportSlot<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Transport + 'static> TransportSlot<T> {
+    /// Create an empty slot reporting `kind` as its transport type.
+    ///
+    /// `buffer` is the capacity of the channel between the pump tasks and
+    /// [`recv`](Transport::recv).
+    #[must_use]
+    pub fn new(kind: TransportType, buffer: usize) -> Self {
+        let (incoming_tx, incoming_rx) = mpsc::channel(buffer);
+        Self {
+            inner: Arc::new(SlotInner {
+                current: RwLock::new(None),
+                generation: watch::Sender::new(0),
+                incoming_tx,
+                incoming_rx: Mutex::new(incoming_rx),
+                kind,
+            }),
+        }
+    }
+
+    /// Install `transport` as the active link, replacing (and dropping) any
+    /// previous one.
+    ///
+    /// Spawns a pump task, so this must be called within a tokio runtime.
+    pub fn install(&self, transport: T) {
+        let transport = Arc::new(transport);
+        let mut generation = 0;
+        self.inner.generation.send_modify(|g| {
+            *g += 1;
+            generation = *g;
+        });
+        *self.inner.current.write() = Some((generation, Arc::clone(&transport)));
+
+        let mut generation_rx = self.inner.generation.subscribe();
+        let incoming_tx = self.inner.incoming_tx.clone();
+        let inner: Weak<SlotInner<T>> = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // Check for replacement first so a stale link never
+                    // forwards another message once it has been swapped out.
+                    biased;
+                    changed = generation_rx.changed() => {
+                        // Replaced, cleared, or the slot was dropped.
+                        if changed.is_err() || *generation_rx.borrow() != generation {
+                            break;
+                        }
+                    }
+                    result = transport.recv() => match result {
+                        Ok(message) => {
+                            if incoming_tx.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) => {
+                            tracing::info!(
+                                transport = %transport.transport_type(),
+                                err = %err,
+                                "link lost"
+                            );
+                            if let Some(inner) = inner.upgrade() {
+                                inner.clear_generation(generation);
+                            }
+                            break;
+                        }
+                    },
+                }
+            }
+        });
+    }
+
+    /// Drop the active link, if any.
+    pub fn clear(&self) {
+        let previous = self.inner.current.write().take();
+        if previous.is_some() {
+            self.inner.generation.send_modify(|g| *g += 1);
+        }
+    }
+
+    /// Returns `true` if a link is installed.
+    #[must_use]
+    pub fn is_installed(&self) -> bool {
+        self.inner.current.read().is_some()
+    }
+
+    /// Returns a receiver that changes whenever a link is installed,
+    /// cleared, or lost.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.inner.generation.subscribe()
+    }
+
+    /// Returns the installed link and its generation.
+    fn current(&self) -> Option<(u64, Arc<T>)> {
+        self.inner.current.read().clone()
+    }
+}
+
+impl<T: Transport + 'static> Transport for TransportSlot<T> {
+    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+        self.send_class(peer, TrafficClass::Chat, payload).await
+    }
+
+    async fn send_class(
+        &self,
+        peer: &PeerId,
+        class: TrafficClass,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        let Some((generation, link)) = self.current() else {
+            return Err(TransportError::Unreachable(peer.clone()));
+        };
+        let result = link.send_class(peer, class, payload).await;
+        if matches!(result, Err(TransportError::ConnectionClosed)) {
+            self.inner.clear_generation(generation);
+        }
+        result
+    }
+
+    async fn recv(&self) -> Result<(PeerId, Vec<u8>), TransportError> {
+        // The slot keeps a sender, so this only waits; it never sees `None`.
+        self.inner
+            .incoming_rx
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)
+    }
+
+    fn is_connected(&self, peer: &PeerId) -> bool {
+        self.current()
+            .is_some_and(|(_, link)| link.is_connected(peer))
+    }
+
+    fn transport_type(&self) -> TransportType {
+        self.inner.kind
+    }
+}
+
 #[cfg(test)]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::expect_used,
+    clippy::unwrap_used
+)]
 mod tests {
     use super::*;
     use crate::transport::loopback::LoopbackTransport;
@@ -532,4 +798,96 @@ mod tests {
         assert_eq!(from, PeerId::new("bob"));
         assert_eq!(data, b"only preferred");
     }
+
+    // --- TransportSlot ---
+
+    /// Hybrid whose preferred side is an (initially empty) slot reporting P2P.
+    fn slot_hybrid() -> (
+        HybridTransport<TransportSlot<LoopbackTransport>, LoopbackTransport>,
+        TransportSlot<LoopbackTransport>,
+        LoopbackTransport,
+    ) {
+        let slot = TransportSlot::new(TransportType::P2p, 16);
+        let (fall_a, fall_b) = fallback_pair();
+        (HybridTransport::new(slot.clone(), fall_a), slot, fall_b)
+    }
+
+    #[tokio::test]
+    async fn empty_slot_falls_back_to_secondary() {
+        let (hybrid, slot, fall_b) = slot_hybrid();
+        let bob = PeerId::new("bob");
+        assert!(!slot.is_installed());
+        assert_eq!(hybrid.active_type(&bob), Some(TransportType::Loopback));
+
+        hybrid.send(&bob, b"via relay").await.unwrap();
+        let (_, data) = fall_b.recv().await.unwrap();
+        assert_eq!(data, b"via relay");
+    }
+
+    #[tokio::test]
+    async fn installed_link_becomes_preferred_both_ways() {
+        let (hybrid, slot, fall_b) = slot_hybrid();
+        let bob = PeerId::new("bob");
+        let (link_a, link_b) = preferred_pair();
+        slot.install(link_a);
+        assert_eq!(hybrid.active_type(&bob), Some(TransportType::P2p));
+
+        hybrid.send(&bob, b"direct").await.unwrap();
+        let (_, data) = link_b.recv().await.unwrap();
+        assert_eq!(data, b"direct");
+
+        link_b.send(&PeerId::new("alice"), b"back").await.unwrap();
+        let (_, data) = hybrid.recv().await.unwrap();
+        assert_eq!(data, b"back");
+
+        // Fallback traffic is still received while the link is up.
+        fall_b
+            .send(&PeerId::new("alice"), b"relayed")
+            .await
+            .unwrap();
+        let (_, data) = hybrid.recv().await.unwrap();
+        assert_eq!(data, b"relayed");
+    }
+
+    #[tokio::test]
+    async fn lost_link_is_cleared_and_fallback_takes_over() {
+        let (hybrid, slot, fall_b) = slot_hybrid();
+        let bob = PeerId::new("bob");
+        let (link_a, link_b) = preferred_pair();
+        let mut changes = slot.subscribe();
+        slot.install(link_a);
+        changes.borrow_and_update();
+
+        drop(link_b);
+        tokio::time::timeout(Duration::from_secs(1), changes.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!slot.is_installed());
+        assert_eq!(hybrid.active_type(&bob), Some(TransportType::Loopback));
+
+        hybrid.send(&bob, b"after loss").await.unwrap();
+        let (_, data) = fall_b.recv().await.unwrap();
+        assert_eq!(data, b"after loss");
+    }
+
+    #[tokio::test]
+    async fn replacing_link_routes_to_new_link() {
+        let (hybrid, slot, _fall_b) = slot_hybrid();
+        let bob = PeerId::new("bob");
+        let (old_a, old_b) = preferred_pair();
+        let (new_a, new_b) = preferred_pair();
+        slot.install(old_a);
+        slot.install(new_a);
+
+        hybrid.send(&bob, b"fresh").await.unwrap();
+        let (_, data) = new_b.recv().await.unwrap();
+        assert_eq!(data, b"fresh");
+
+        // The old link's pump has stopped, so it no longer feeds recv.
+        old_b.send(&PeerId::new("alice"), b"stale").await.ok();
+        new_b.send(&PeerId::new("alice"), b"current").await.unwrap();
+        let (_, data) = hybrid.recv().await.unwrap();
+        assert_eq!(data, b"current");
+    }
 }
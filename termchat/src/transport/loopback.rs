@@ -97,6 +97,7 @@ impl Transport for LoopbackTransport {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -3,13 +3,24 @@
 //! Defines the [`Transport`] trait that all transport implementations must satisfy.
 //! Concrete implementations include:
 //! - [`loopback::LoopbackTransport`] — in-process channel-based transport for testing
+//! - [`sim::SimTransport`] — wrapper that injects latency, loss, duplication,
+//!   reordering and disconnects (seeded) around any transport, for testing
 //! - [`quic::QuicTransport`] — QUIC-based P2P transport (UC-003)
-//! - [`relay::RelayTransport`] — WebSocket relay fallback (UC-004)
+//! - [`relay::RelayTransport`] — WebSocket relay fallback (UC-004),
+//!   optionally tunnelled through a [`proxy::Socks5Proxy`]
+//!
+//! [`discovery::LanDiscovery`] finds peers on the local network via mDNS so
+//! they can be dialed directly over QUIC, and [`relay_pool::RelayPool`] ranks
+//! the configured relays by health for failover.
 
+pub mod discovery;
 pub mod hybrid;
 pub mod loopback;
+pub mod proxy;
 pub mod quic;
 pub mod relay;
+pub mod relay_pool;
+pub mod sim;
 
 use std::fmt;
 
@@ -61,6 +72,37 @@ impl fmt::Display for TransportType {
     }
 }
 
+/// Kind of traffic a payload belongs to.
+///
+/// Transports that can carry classes independently (separate QUIC streams)
+/// use it so a bulk transfer or full task sync never holds up typing
+/// indicators or chat. The payload itself stays opaque.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+    /// Chat messages, acks, and handshakes.
+    #[default]
+    Chat,
+    /// Task and state synchronization.
+    Sync,
+    /// Presence and typing indicators; may be dropped under loss.
+    Ephemeral,
+    /// Large transfers such as file chunks.
+    Bulk,
+}
+
+impl TrafficClass {
+    /// Scheduling priority of this class (higher is sent first).
+    #[must_use]
+    pub const fn priority(self) -> i32 {
+        match self {
+            Self::Ephemeral => 3,
+            Self::Chat => 2,
+            Self::Sync => 1,
+            Self::Bulk => 0,
+        }
+    }
+}
+
 /// Errors that can occur during transport operations.
 #[derive(Debug, thiserror::Error)]
 pub enum TransportError {
@@ -76,6 +118,17 @@ pub enum TransportError {
     #[error("peer {0} is unreachable")]
     Unreachable(PeerId),
 
+    /// The relay refused registration because it is shutting down (drain
+    /// mode). The caller should wait `retry_after` before reconnecting, or
+    /// connect to `redirect_url` instead if one was given.
+    #[error("relay is shutting down (retry after {retry_after:?})")]
+    Draining {
+        /// Minimum delay before reconnecting to the same relay.
+        retry_after: std::time::Duration,
+        /// Alternate relay URL advertised by the server, if any.
+        redirect_url: Option<String>,
+    },
+
     /// An underlying I/O error occurred.
     #[error("transport I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -104,6 +157,19 @@ pub trait Transport: Send + Sync {
         payload: &[u8],
     ) -> impl std::future::Future<Output = Result<(), TransportError>> + Send;
 
+    /// Send an encrypted payload tagged with its [`TrafficClass`].
+    ///
+    /// Transports that multiplex classes use it to pick a stream and
+    /// priority; the default ignores the class and calls [`Transport::send`].
+    fn send_class(
+        &self,
+        peer: &PeerId,
+        _class: TrafficClass,
+        payload: &[u8],
+    ) -> impl std::future::Future<Output = Result<(), TransportError>> + Send {
+        self.send(peer, payload)
+    }
+
     /// Receive the next encrypted payload from any connected peer.
     ///
     /// Blocks asynchronously until a message arrives. Returns the
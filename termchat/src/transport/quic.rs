@@ -5,14 +5,18 @@
 //!
 //! QUIC TLS provides transport encryption. Peer authentication uses the
 //! Noise XX handshake (UC-005), not TLS certificates.
+//!
+//! Traffic is multiplexed per [`TrafficClass`]: chat, sync, and bulk data
+//! use separate prioritized streams, and ephemeral updates use datagrams,
+//! so one class never blocks another behind a single stream.
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell, mpsc};
 
-use super::{PeerId, Transport, TransportError, TransportType};
+use super::{PeerId, TrafficClass, Transport, TransportError, TransportType};
 
 /// Maximum payload size accepted by recv (64 KB).
 const MAX_PAYLOAD_SIZE: u32 = 65_536;
@@ -25,6 +29,12 @@ const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 /// returns promptly.
 const STREAM_INIT_MARKER: u8 = 0x01;
 
+/// Maximum length of a peer ID announced by [`QuicTransport::announce`].
+const MAX_PEER_ID_LEN: u32 = 256;
+
+/// Capacity of the queue merging frames from every inbound stream.
+const INCOMING_BUFFER: usize = 64;
+
 // ---------------------------------------------------------------------------
 // TLS configuration (T-003-02)
 // ---------------------------------------------------------------------------
@@ -248,13 +258,38 @@ impl QuicListener {
             map_read_exact_error(e)
         })?;
 
-        Ok(QuicTransport {
-            local_id: self.local_id.clone(),
+        Ok(QuicTransport::new(
+            self.local_id.clone(),
             remote_id,
             connection,
-            send_stream: Mutex::new(send_stream),
-            recv_stream: Mutex::new(recv_stream),
-        })
+            send_stream,
+            recv_stream,
+        ))
+    }
+
+    /// Dial a remote peer from this listener's UDP socket.
+    ///
+    /// Used for NAT hole punching: outbound packets leave from the same
+    /// address the remote peer was told to dial, so both NATs see traffic
+    /// in each direction. Both peers may dial and accept at the same time.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`QuicTransport::connect_with_timeout`].
+    pub async fn connect(
+        &self,
+        addr: SocketAddr,
+        remote_id: PeerId,
+        timeout: Duration,
+    ) -> Result<QuicTransport, TransportError> {
+        QuicTransport::dial(
+            &self.endpoint,
+            addr,
+            self.local_id.clone(),
+            remote_id,
+            timeout,
+        )
+        .await
     }
 
     /// Close the listener endpoint, rejecting any in-flight connections.
@@ -269,9 +304,21 @@ impl QuicListener {
 
 /// QUIC-based P2P transport implementing the [`Transport`] trait.
 ///
-/// Wraps a single point-to-point QUIC connection with one bidirectional
-/// stream. Messages are length-prefixed on the wire (4-byte LE prefix
-/// followed by the payload).
+/// Wraps a single point-to-point QUIC connection. Messages are
+/// length-prefixed on the wire (4-byte LE prefix followed by the payload).
+///
+/// Each [`TrafficClass`] travels independently so a bulk transfer never
+/// holds up chat or typing indicators:
+///
+/// - Chat traffic uses the bidirectional stream opened at connect time.
+/// - Sync and bulk traffic each get a unidirectional stream, opened on first
+///   use and tagged with a one-byte class header.
+/// - Ephemeral traffic is sent as unreliable datagrams when the payload
+///   fits, and over its own unidirectional stream otherwise.
+///
+/// Streams are scheduled by [`TrafficClass::priority`]. Inbound frames from
+/// every stream and datagram are merged into a single [`recv`](Transport::recv)
+/// queue; ordering is preserved within a class, not across classes.
 ///
 /// Created either via [`QuicTransport::connect`] (initiator) or
 /// [`QuicListener::accept`] (responder).
@@ -282,12 +329,22 @@ pub struct QuicTransport {
     remote_id: PeerId,
     /// The underlying QUIC connection (for status checks).
     connection: quinn::Connection,
-    /// Write half of the bidirectional stream (mutex for thread safety).
+    /// Write half of the bidirectional stream, carrying chat traffic.
     send_stream: Mutex<quinn::SendStream>,
-    /// Read half of the bidirectional stream (mutex for exclusive read access).
-    recv_stream: Mutex<quinn::RecvStream>,
+    /// Unidirectional streams for sync, ephemeral, and bulk traffic.
+    class_streams: [OnceCell<Mutex<quinn::SendStream>>; 3],
+    /// Read half of the bidirectional stream and the sender for inbound
+    /// frames, held until the background readers start.
+    unstarted: parking_lot::Mutex<Option<(quinn::RecvStream, mpsc::Sender<InboundFrame>)>>,
+    /// Frames from every inbound stream and datagram.
+    incoming: Mutex<mpsc::Receiver<InboundFrame>>,
+    /// Background readers feeding `incoming`, aborted on drop.
+    readers: parking_lot::Mutex<Vec<tokio::task::AbortHandle>>,
 }
 
+/// A frame (or read failure) from one of the connection's inbound streams.
+type InboundFrame = Result<Vec<u8>, TransportError>;
+
 impl QuicTransport {
     /// Connect to a remote peer as the initiator.
     ///
@@ -324,16 +381,26 @@ impl QuicTransport {
         remote_id: PeerId,
         timeout: Duration,
     ) -> Result<Self, TransportError> {
-        let client_config = make_client_config()?;
-
         // Bind to an OS-assigned port for the client endpoint.
-        let mut endpoint = quinn::Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?;
-        endpoint.set_default_client_config(client_config);
+        let endpoint = quinn::Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?;
+        Self::dial(&endpoint, addr, local_id, remote_id, timeout).await
+    }
 
-        let connecting = endpoint.connect(addr, "localhost").map_err(|e| {
-            tracing::warn!(err = %e, addr = %addr, "QUIC connect initiation failed");
-            TransportError::Unreachable(remote_id.clone())
-        })?;
+    /// Dial `addr` from an existing endpoint and open the message stream.
+    async fn dial(
+        endpoint: &quinn::Endpoint,
+        addr: SocketAddr,
+        local_id: PeerId,
+        remote_id: PeerId,
+        timeout: Duration,
+    ) -> Result<Self, TransportError> {
+        let client_config = make_client_config()?;
+        let connecting = endpoint
+            .connect_with(client_config, addr, "localhost")
+            .map_err(|e| {
+                tracing::warn!(err = %e, addr = %addr, "QUIC connect initiation failed");
+                TransportError::Unreachable(remote_id.clone())
+            })?;
 
         let connection =
             tokio::time::timeout(timeout, connecting)
@@ -365,13 +432,35 @@ impl QuicTransport {
                 map_write_error(e)
             })?;
 
-        Ok(Self {
+        Ok(Self::new(
+            local_id,
+            remote_id,
+            connection,
+            send_stream,
+            recv_stream,
+        ))
+    }
+
+    /// Wrap an established connection and its chat stream.
+    fn new(
+        local_id: PeerId,
+        remote_id: PeerId,
+        connection: quinn::Connection,
+        send_stream: quinn::SendStream,
+        recv_stream: quinn::RecvStream,
+    ) -> Self {
+        let _ = send_stream.set_priority(TrafficClass::Chat.priority());
+        let (incoming_tx, incoming_rx) = mpsc::channel(INCOMING_BUFFER);
+        Self {
             local_id,
             remote_id,
             connection,
             send_stream: Mutex::new(send_stream),
-            recv_stream: Mutex::new(recv_stream),
-        })
+            class_streams: Default::default(),
+            unstarted: parking_lot::Mutex::new(Some((recv_stream, incoming_tx))),
+            incoming: Mutex::new(incoming_rx),
+            readers: parking_lot::Mutex::new(Vec::new()),
+        }
     }
 
     /// Return the local peer ID.
@@ -385,68 +474,172 @@ impl QuicTransport {
     pub const fn remote_id(&self) -> &PeerId {
         &self.remote_id
     }
-}
 
-impl Transport for QuicTransport {
-    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
-        if *peer != self.remote_id {
-            return Err(TransportError::Unreachable(peer.clone()));
-        }
+    /// Send the local peer ID as the first frame on the stream.
+    ///
+    /// [`QuicListener::accept`] can only name the remote side by its socket
+    /// address; the initiator calls this right after connecting so the
+    /// responder can learn who it is talking to via [`identify`](Self::identify).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError`] if the frame cannot be written.
+    pub async fn announce(&self) -> Result<(), TransportError> {
+        self.write_frame(self.local_id.as_str().as_bytes()).await
+    }
 
-        let len = u32::try_from(payload.len()).map_err(|_| {
-            TransportError::Io(std::io::Error::new(
+    /// Read the initiator's [`announce`](Self::announce) frame and adopt the
+    /// announced peer ID as the remote identity.
+    ///
+    /// Must be called before the first [`recv`](Transport::recv). The ID is
+    /// self-asserted; authenticating it is up to the Noise handshake layered
+    /// on top.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError::Io`] if the frame is too long or not valid
+    /// UTF-8, or if receiving has already started, or another
+    /// [`TransportError`] if reading fails.
+    pub async fn identify(mut self) -> Result<Self, TransportError> {
+        let Some((stream, _)) = self.unstarted.get_mut().as_mut() else {
+            return Err(TransportError::Io(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                "payload exceeds u32::MAX bytes",
+                "identify called after receiving started",
+            )));
+        };
+        let frame = read_frame(stream, MAX_PEER_ID_LEN).await?;
+        let id = String::from_utf8(frame).map_err(|_| {
+            TransportError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "announced peer ID is not valid UTF-8",
             ))
         })?;
-        let len_bytes = len.to_le_bytes();
+        self.remote_id = PeerId::new(id);
+        Ok(self)
+    }
 
-        let mut stream = self.send_stream.lock().await;
-        stream.write_all(&len_bytes).await.map_err(|e| {
-            tracing::error!(err = %e, "QUIC send: failed to write length prefix");
-            map_write_error(e)
-        })?;
-        stream.write_all(payload).await.map_err(|e| {
-            tracing::error!(err = %e, "QUIC send: failed to write payload");
-            map_write_error(e)
-        })?;
-        drop(stream);
+    /// Write one length-prefixed frame on the chat stream.
+    async fn write_frame(&self, payload: &[u8]) -> Result<(), TransportError> {
+        write_frame(&self.send_stream, payload).await
+    }
 
-        Ok(())
+    /// Send `payload` with the stream (or datagram) assigned to `class`.
+    async fn write_class(&self, class: TrafficClass, payload: &[u8]) -> Result<(), TransportError> {
+        let slot = match class {
+            TrafficClass::Chat => return self.write_frame(payload).await,
+            TrafficClass::Sync => &self.class_streams[0],
+            TrafficClass::Ephemeral => {
+                if self.try_datagram(payload)? {
+                    return Ok(());
+                }
+                &self.class_streams[1]
+            }
+            TrafficClass::Bulk => &self.class_streams[2],
+        };
+        let stream = slot
+            .get_or_try_init(|| async {
+                let mut stream = self.connection.open_uni().await.map_err(|e| {
+                    tracing::warn!(err = %e, ?class, "QUIC stream open failed");
+                    map_connection_error(&e, &self.remote_id)
+                })?;
+                let _ = stream.set_priority(class.priority());
+                stream
+                    .write_all(&[class_tag(class)])
+                    .await
+                    .map_err(map_write_error)?;
+                Ok::<_, TransportError>(Mutex::new(stream))
+            })
+            .await?;
+        write_frame(stream, payload).await
     }
 
-    async fn recv(&self) -> Result<(PeerId, Vec<u8>), TransportError> {
-        let mut stream = self.recv_stream.lock().await;
+    /// Send `payload` as a datagram if the peer accepts one that large.
+    ///
+    /// Returns `Ok(false)` if the payload must go over a stream instead.
+    fn try_datagram(&self, payload: &[u8]) -> Result<bool, TransportError> {
+        if self
+            .connection
+            .max_datagram_size()
+            .is_none_or(|max| payload.len() > max)
+        {
+            return Ok(false);
+        }
+        match self
+            .connection
+            .send_datagram(bytes::Bytes::copy_from_slice(payload))
+        {
+            Ok(()) => Ok(true),
+            Err(quinn::SendDatagramError::ConnectionLost(e)) => {
+                Err(map_connection_error(&e, &self.remote_id))
+            }
+            Err(e) => {
+                tracing::debug!(err = %e, "QUIC datagram rejected, using a stream");
+                Ok(false)
+            }
+        }
+    }
 
-        // Read the 4-byte length prefix.
-        let mut len_buf = [0u8; 4];
-        stream
-            .read_exact(&mut len_buf)
-            .await
-            .map_err(map_read_exact_error)?;
-
-        let len = u32::from_le_bytes(len_buf);
-        if len > MAX_PAYLOAD_SIZE {
-            tracing::error!(
-                payload_size = len,
-                max = MAX_PAYLOAD_SIZE,
-                "QUIC recv: payload exceeds maximum size"
-            );
-            return Err(TransportError::Io(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("payload size {len} exceeds maximum {MAX_PAYLOAD_SIZE}"),
-            )));
+    /// Start the background readers on first use.
+    fn start_readers(&self) {
+        let Some((recv_stream, incoming_tx)) = self.unstarted.lock().take() else {
+            return;
+        };
+        let chat = tokio::spawn(read_chat_stream(
+            self.connection.clone(),
+            recv_stream,
+            incoming_tx.clone(),
+        ));
+        let uni = tokio::spawn(accept_class_streams(
+            self.connection.clone(),
+            incoming_tx.clone(),
+        ));
+        let datagrams = tokio::spawn(read_datagrams(self.connection.clone(), incoming_tx));
+        self.readers.lock().extend([
+            chat.abort_handle(),
+            uni.abort_handle(),
+            datagrams.abort_handle(),
+        ]);
+    }
+}
+
+impl Drop for QuicTransport {
+    fn drop(&mut self) {
+        // The readers hold connection handles; close explicitly so the
+        // remote peer sees the link go away immediately.
+        for reader in self.readers.get_mut().drain(..) {
+            reader.abort();
         }
+        self.connection.close(0u32.into(), b"closed");
+    }
+}
 
-        // Read the payload.
-        let mut payload = vec![0u8; len as usize];
-        stream
-            .read_exact(&mut payload)
-            .await
-            .map_err(map_read_exact_error)?;
-        drop(stream);
+impl Transport for QuicTransport {
+    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+        self.send_class(peer, TrafficClass::Chat, payload).await
+    }
+
+    async fn send_class(
+        &self,
+        peer: &PeerId,
+        class: TrafficClass,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        if *peer != self.remote_id {
+            return Err(TransportError::Unreachable(peer.clone()));
+        }
+        self.write_class(class, payload).await
+    }
 
-        Ok((self.remote_id.clone(), payload))
+    async fn recv(&self) -> Result<(PeerId, Vec<u8>), TransportError> {
+        self.start_readers();
+        let frame = self
+            .incoming
+            .lock()
+            .await
+            .recv()
+            .await
+            .unwrap_or(Err(TransportError::ConnectionClosed))?;
+        Ok((self.remote_id.clone(), frame))
     }
 
     fn is_connected(&self, peer: &PeerId) -> bool {
@@ -458,6 +651,159 @@ impl Transport for QuicTransport {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Stream framing and background readers
+// ---------------------------------------------------------------------------
+
+/// Header byte identifying the traffic class of a unidirectional stream.
+const fn class_tag(class: TrafficClass) -> u8 {
+    match class {
+        TrafficClass::Chat => 0,
+        TrafficClass::Sync => 1,
+        TrafficClass::Ephemeral => 2,
+        TrafficClass::Bulk => 3,
+    }
+}
+
+/// Inverse of [`class_tag`].
+const fn class_from_tag(tag: u8) -> Option<TrafficClass> {
+    match tag {
+        0 => Some(TrafficClass::Chat),
+        1 => Some(TrafficClass::Sync),
+        2 => Some(TrafficClass::Ephemeral),
+        3 => Some(TrafficClass::Bulk),
+        _ => None,
+    }
+}
+
+/// Write one length-prefixed frame to `stream`.
+async fn write_frame(
+    stream: &Mutex<quinn::SendStream>,
+    payload: &[u8],
+) -> Result<(), TransportError> {
+    let len = u32::try_from(payload.len()).map_err(|_| {
+        TransportError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "payload exceeds u32::MAX bytes",
+        ))
+    })?;
+    let len_bytes = len.to_le_bytes();
+
+    let mut stream = stream.lock().await;
+    stream.write_all(&len_bytes).await.map_err(|e| {
+        tracing::error!(err = %e, "QUIC send: failed to write length prefix");
+        map_write_error(e)
+    })?;
+    stream.write_all(payload).await.map_err(|e| {
+        tracing::error!(err = %e, "QUIC send: failed to write payload");
+        map_write_error(e)
+    })?;
+    drop(stream);
+
+    Ok(())
+}
+
+/// Read one length-prefixed frame of at most `max_len` bytes.
+async fn read_frame(
+    stream: &mut quinn::RecvStream,
+    max_len: u32,
+) -> Result<Vec<u8>, TransportError> {
+    // Read the 4-byte length prefix.
+    let mut len_buf = [0u8; 4];
+    stream
+        .read_exact(&mut len_buf)
+        .await
+        .map_err(map_read_exact_error)?;
+
+    let len = u32::from_le_bytes(len_buf);
+    if len > max_len {
+        tracing::error!(
+            payload_size = len,
+            max = max_len,
+            "QUIC recv: payload exceeds maximum size"
+        );
+        return Err(TransportError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("payload size {len} exceeds maximum {max_len}"),
+        )));
+    }
+
+    // Read the payload.
+    let mut payload = vec![0u8; len as usize];
+    stream
+        .read_exact(&mut payload)
+        .await
+        .map_err(map_read_exact_error)?;
+
+    Ok(payload)
+}
+
+/// Forward frames from the chat stream until it fails.
+///
+/// A broken chat stream ends the link: the error is passed to `recv` and
+/// the connection is closed so the other readers stop too.
+async fn read_chat_stream(
+    connection: quinn::Connection,
+    mut stream: quinn::RecvStream,
+    incoming_tx: mpsc::Sender<InboundFrame>,
+) {
+    loop {
+        let frame = read_frame(&mut stream, MAX_PAYLOAD_SIZE).await;
+        let failed = frame.is_err();
+        if incoming_tx.send(frame).await.is_err() || failed {
+            break;
+        }
+    }
+    connection.close(0u32.into(), b"stream closed");
+}
+
+/// Accept the remote peer's class streams and forward their frames.
+async fn accept_class_streams(
+    connection: quinn::Connection,
+    incoming_tx: mpsc::Sender<InboundFrame>,
+) {
+    // Dropping the set (when this task ends or is aborted) stops every reader.
+    let mut streams = tokio::task::JoinSet::new();
+    while let Ok(stream) = connection.accept_uni().await {
+        streams.spawn(read_class_stream(stream, incoming_tx.clone()));
+    }
+}
+
+/// Forward frames from one class stream until it finishes.
+async fn read_class_stream(mut stream: quinn::RecvStream, incoming_tx: mpsc::Sender<InboundFrame>) {
+    let mut tag = [0u8; 1];
+    if stream.read_exact(&mut tag).await.is_err() {
+        return;
+    }
+    let Some(class) = class_from_tag(tag[0]) else {
+        tracing::warn!(tag = tag[0], "QUIC recv: unknown traffic class stream");
+        return;
+    };
+    loop {
+        match read_frame(&mut stream, MAX_PAYLOAD_SIZE).await {
+            Ok(frame) => {
+                if incoming_tx.send(Ok(frame)).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                // Connection loss surfaces through the chat stream.
+                tracing::debug!(err = %e, ?class, "QUIC class stream ended");
+                break;
+            }
+        }
+    }
+}
+
+/// Forward inbound datagrams (ephemeral traffic) until the connection ends.
+async fn read_datagrams(connection: quinn::Connection, incoming_tx: mpsc::Sender<InboundFrame>) {
+    while let Ok(datagram) = connection.read_datagram().await {
+        if incoming_tx.send(Ok(datagram.to_vec())).await.is_err() {
+            break;
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Error mapping helpers
 // ---------------------------------------------------------------------------
@@ -510,6 +856,13 @@ fn map_read_exact_error(err: quinn::ReadExactError) -> TransportError {
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::expect_used,
+    clippy::match_same_arms,
+    clippy::redundant_locals
+)]
 mod tests {
     use super::*;
 
@@ -632,6 +985,7 @@ mod tests {
             Err(TransportError::Unreachable(_)) => {}   // also acceptable
             Err(TransportError::Io(_)) => {}            // OS may reject immediately
             Err(TransportError::ConnectionClosed) => {} // quinn may report this
+            Err(e @ TransportError::Draining { .. }) => panic!("unexpected relay error: {e}"),
             Ok(_) => panic!("expected error, got Ok"),
         }
     }
@@ -1040,4 +1394,190 @@ mod tests {
             "CidsExhausted should map to Unreachable"
         );
     }
+
+    // -- Peer identification --
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn announce_identify_names_responder_remote() {
+        let (initiator, responder) = create_connected_pair().await;
+        assert_ne!(responder.remote_id(), &PeerId::new("initiator"));
+
+        initiator.announce().await.expect("announce");
+        let responder = responder.identify().await.expect("identify");
+        assert_eq!(responder.remote_id(), &PeerId::new("initiator"));
+
+        // Data frames still flow after the identification frame.
+        responder
+            .send(&PeerId::new("initiator"), b"hi")
+            .await
+            .expect("send");
+        let (_, data) = initiator.recv().await.expect("recv");
+        assert_eq!(data, b"hi");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn identify_rejects_oversized_peer_id() {
+        let (initiator, responder) = create_connected_pair().await;
+        initiator
+            .write_frame(&vec![b'a'; MAX_PEER_ID_LEN as usize + 1])
+            .await
+            .expect("write");
+        assert!(responder.identify().await.is_err());
+    }
+
+    // -- Traffic classes --
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn every_class_round_trips() {
+        let (initiator, responder) = create_connected_pair().await;
+        let peer = initiator.remote_id().clone();
+
+        let classes = [
+            TrafficClass::Chat,
+            TrafficClass::Sync,
+            TrafficClass::Ephemeral,
+            TrafficClass::Bulk,
+        ];
+        for class in classes {
+            let payload = format!("{class:?}").into_bytes();
+            initiator
+                .send_class(&peer, class, &payload)
+                .await
+                .expect("send_class");
+        }
+
+        let mut received = Vec::new();
+        for _ in classes {
+            let (_, data) = tokio::time::timeout(Duration::from_secs(5), responder.recv())
+                .await
+                .expect("recv timed out")
+                .expect("recv");
+            received.push(String::from_utf8(data).expect("utf-8"));
+        }
+        received.sort();
+        assert_eq!(received, ["Bulk", "Chat", "Ephemeral", "Sync"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn class_streams_preserve_order() {
+        let (initiator, responder) = create_connected_pair().await;
+        let peer = initiator.remote_id().clone();
+
+        for i in 0u8..10 {
+            initiator
+                .send_class(&peer, TrafficClass::Sync, &[i])
+                .await
+                .expect("send");
+        }
+        for i in 0u8..10 {
+            let (_, data) = responder.recv().await.expect("recv");
+            assert_eq!(data, [i]);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn bulk_transfer_does_not_block_chat() {
+        const BULK_FRAMES: usize = 64;
+        let (initiator, responder) = create_connected_pair().await;
+        let initiator = Arc::new(initiator);
+        let peer = initiator.remote_id().clone();
+
+        let bulk = tokio::spawn({
+            let initiator = Arc::clone(&initiator);
+            let peer = peer.clone();
+            async move {
+                let chunk = vec![0xAB; MAX_PAYLOAD_SIZE as usize];
+                for _ in 0..BULK_FRAMES {
+                    initiator
+                        .send_class(&peer, TrafficClass::Bulk, &chunk)
+                        .await
+                        .expect("bulk send");
+                }
+            }
+        });
+        // Let the bulk stream fill the flow-control window first.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        initiator.send(&peer, b"chat").await.expect("chat send");
+
+        let mut bulk_before_chat = 0;
+        loop {
+            let (_, data) = responder.recv().await.expect("recv");
+            if data == b"chat" {
+                break;
+            }
+            bulk_before_chat += 1;
+        }
+        assert!(
+            bulk_before_chat < BULK_FRAMES,
+            "chat waited behind the whole bulk transfer"
+        );
+        bulk.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn ephemeral_traffic_uses_datagrams() {
+        let (initiator, responder) = create_connected_pair().await;
+        assert!(initiator.connection.max_datagram_size().is_some());
+
+        initiator
+            .send_class(initiator.remote_id(), TrafficClass::Ephemeral, b"typing")
+            .await
+            .expect("send");
+        // No class stream was opened for the update.
+        assert!(initiator.class_streams[1].get().is_none());
+        let (_, data) = responder.recv().await.expect("recv");
+        assert_eq!(data, b"typing");
+    }
+
+    #[test]
+    fn class_tags_round_trip() {
+        for class in [
+            TrafficClass::Chat,
+            TrafficClass::Sync,
+            TrafficClass::Ephemeral,
+            TrafficClass::Bulk,
+        ] {
+            assert_eq!(class_from_tag(class_tag(class)), Some(class));
+        }
+        assert_eq!(class_from_tag(0xFF), None);
+    }
+
+    // -- Hole punching --
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn listeners_dial_each_other_simultaneously() {
+        let any: SocketAddr = "127.0.0.1:0".parse().expect("valid addr");
+        let alice = Arc::new(QuicListener::bind(any, PeerId::new("alice")).expect("bind"));
+        let bob = Arc::new(QuicListener::bind(any, PeerId::new("bob")).expect("bind"));
+        let alice_addr = alice.local_addr().expect("addr");
+        let bob_addr = bob.local_addr().expect("addr");
+
+        let accept_alice = tokio::spawn({
+            let alice = Arc::clone(&alice);
+            async move { alice.accept().await }
+        });
+        let accept_bob = tokio::spawn({
+            let bob = Arc::clone(&bob);
+            async move { bob.accept().await }
+        });
+        let timeout = Duration::from_secs(5);
+        let (to_bob, to_alice) = tokio::join!(
+            alice.connect(bob_addr, PeerId::new("bob"), timeout),
+            bob.connect(alice_addr, PeerId::new("alice"), timeout),
+        );
+        let to_bob = to_bob.expect("alice -> bob");
+        let _to_alice = to_alice.expect("bob -> alice");
+
+        // Outbound traffic leaves from the listening socket.
+        let from_alice = accept_bob.await.expect("task").expect("accept");
+        assert_eq!(from_alice.connection.remote_address(), alice_addr);
+        accept_alice.await.expect("task").expect("accept");
+
+        to_bob
+            .send(&PeerId::new("bob"), b"punched")
+            .await
+            .expect("send");
+        let (_, data) = from_alice.recv().await.expect("recv");
+        assert_eq!(data, b"punched");
+    }
 }
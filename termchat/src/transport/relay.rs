@@ -6,18 +6,37 @@
 //!
 //! The relay server never sees plaintext — only opaque encrypted payloads
 //! are forwarded, identified by `PeerId` for routing.
+//!
+//! The relay also brokers NAT traversal: [`RelayTransport::request_punch`]
+//! sends this peer's QUIC port to another peer, and candidates received from
+//! other peers are delivered as [`PunchOffer`]s to the channel installed with
+//! [`RelayTransport::set_punch_handler`].
+//!
+//! A half-open connection (the relay vanished without closing the socket)
+//! never fails a read, so the transport sends WebSocket pings on a fixed
+//! interval. After too many unanswered pings it declares the relay dead and
+//! ends its receive channel, which lets the reconnect supervisor take over.
+//! Pong round trips double as the latency shown in the status bar.
+//!
+//! With [`RelayConnectOptions::proxy`] set, the WebSocket connection is
+//! tunnelled through a SOCKS5 proxy and the relay host name is resolved by
+//! the proxy.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex as SyncMutex;
 use tokio::sync::{Mutex, mpsc};
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, client_async, connect_async};
 
 use termchat_proto::relay::{self, RelayMessage};
+use termchat_proto::version::{FeatureFlags, NegotiatedProtocol, ProtocolHello};
 
+use super::proxy::Socks5Proxy;
 use super::{PeerId, Transport, TransportError, TransportType};
 
 /// Type alias for the write half of a WebSocket connection.
@@ -36,6 +55,103 @@ const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default timeout for waiting for a `Registered` acknowledgment from the server.
 const DEFAULT_REGISTER_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Default interval between keepalive pings.
+const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Default number of consecutive unanswered pings before the relay is
+/// considered dead.
+const DEFAULT_KEEPALIVE_MAX_MISSED: u32 = 3;
+
+/// A drain notice received from the relay server ([`RelayMessage::Shutdown`]).
+///
+/// Recorded by the background reader so the reconnect supervisor can honour
+/// the server's requested delay (or redirect) instead of retrying at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayShutdown {
+    /// Human-readable shutdown reason from the server.
+    pub reason: String,
+    /// Minimum delay before reconnecting to the same relay.
+    pub retry_after: Duration,
+    /// Alternate relay URL to reconnect to instead, if any.
+    pub redirect_url: Option<String>,
+}
+
+/// Direct-connection candidates for another peer, brokered by the relay
+/// ([`RelayMessage::PunchCandidates`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PunchOffer {
+    /// The peer that asked for a direct connection.
+    pub from: PeerId,
+    /// Addresses to dial, the relay-observed public address first.
+    pub addrs: Vec<SocketAddr>,
+}
+
+/// Sink for [`PunchOffer`]s, shared with the background reader.
+type PunchHandler = Arc<SyncMutex<Option<mpsc::Sender<PunchOffer>>>>;
+
+/// Options controlling how [`RelayTransport`] connects and registers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayConnectOptions {
+    /// Timeout for establishing the WebSocket connection.
+    pub connect_timeout: Duration,
+    /// Timeout for receiving the `Registered` acknowledgment.
+    pub register_timeout: Duration,
+    /// Signed invite token presented in `Register`, for relays that
+    /// restrict registration to invited peers.
+    pub invite_token: Option<String>,
+    /// Interval between WebSocket keepalive pings (`Duration::ZERO`
+    /// disables keepalives).
+    pub keepalive_interval: Duration,
+    /// Consecutive unanswered pings after which the connection is
+    /// considered dead.
+    pub keepalive_max_missed: u32,
+    /// SOCKS5 proxy to tunnel the connection through, if any.
+    pub proxy: Option<Socks5Proxy>,
+}
+
+impl Default for RelayConnectOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            register_timeout: DEFAULT_REGISTER_TIMEOUT,
+            invite_token: None,
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            keepalive_max_missed: DEFAULT_KEEPALIVE_MAX_MISSED,
+            proxy: None,
+        }
+    }
+}
+
+/// Keepalive bookkeeping shared between the pinger and the reader.
+#[derive(Debug, Default)]
+struct KeepaliveState {
+    /// Nonce and send time of the ping still awaiting its pong, if any.
+    outstanding: SyncMutex<Option<(u64, Instant)>>,
+    /// Round-trip time of the most recently answered ping.
+    latency: SyncMutex<Option<Duration>>,
+}
+
+impl KeepaliveState {
+    /// Record a pong, returning `true` if it answers the outstanding ping.
+    fn on_pong(&self, payload: &[u8]) -> bool {
+        let Ok(nonce) = <[u8; 8]>::try_from(payload).map(u64::from_be_bytes) else {
+            return false;
+        };
+        let sent_at = {
+            let mut outstanding = self.outstanding.lock();
+            match *outstanding {
+                Some((expected, sent_at)) if expected == nonce => {
+                    *outstanding = None;
+                    sent_at
+                }
+                _ => return false,
+            }
+        };
+        *self.latency.lock() = Some(sent_at.elapsed());
+        true
+    }
+}
+
 /// WebSocket relay transport implementing the [`Transport`] trait.
 ///
 /// Connects to a relay server over WebSocket and sends/receives encrypted
@@ -53,8 +169,21 @@ pub struct RelayTransport {
     ws_sender: Arc<Mutex<WsSender>>,
     /// Channel for messages received from the background reader task.
     incoming: Mutex<mpsc::Receiver<(PeerId, Vec<u8>)>>,
+    /// Weak handle to the reader's side of `incoming`, for handing over
+    /// messages collected elsewhere without keeping the channel open.
+    inbound: mpsc::WeakSender<(PeerId, Vec<u8>)>,
     /// Whether the WebSocket connection to the relay is active.
     connected: Arc<AtomicBool>,
+    /// Drain notice from the server, if it announced a shutdown.
+    shutdown_notice: Arc<SyncMutex<Option<RelayShutdown>>>,
+    /// Where brokered punch candidates are delivered, if anywhere.
+    punch_handler: PunchHandler,
+    /// Protocol version and features agreed with the relay at registration.
+    protocol: NegotiatedProtocol,
+    /// Keepalive state (last measured round-trip time).
+    keepalive: Arc<KeepaliveState>,
+    /// Handle to the keepalive pinger, if keepalives are enabled.
+    keepalive_handle: Option<tokio::task::AbortHandle>,
     /// Handle to the background reader task (kept alive for the transport's lifetime).
     _reader_handle: tokio::task::JoinHandle<()>,
 }
@@ -69,46 +198,69 @@ impl RelayTransport {
     ///
     /// See [`connect_with_timeouts`](Self::connect_with_timeouts).
     pub async fn connect(relay_url: &str, local_id: PeerId) -> Result<Self, TransportError> {
-        Self::connect_with_timeouts(
-            relay_url,
-            local_id,
-            DEFAULT_CONNECT_TIMEOUT,
-            DEFAULT_REGISTER_TIMEOUT,
-        )
-        .await
+        Self::connect_with_options(relay_url, local_id, &RelayConnectOptions::default()).await
     }
 
     /// Connect to a relay server and register this peer with custom timeouts.
     ///
+    /// # Errors
+    ///
+    /// See [`connect_with_options`](Self::connect_with_options).
+    pub async fn connect_with_timeouts(
+        relay_url: &str,
+        local_id: PeerId,
+        connect_timeout: Duration,
+        register_timeout: Duration,
+    ) -> Result<Self, TransportError> {
+        let options = RelayConnectOptions {
+            connect_timeout,
+            register_timeout,
+            ..RelayConnectOptions::default()
+        };
+        Self::connect_with_options(relay_url, local_id, &options).await
+    }
+
+    /// Connect to a relay server and register this peer.
+    ///
     /// Performs the following steps:
     /// 1. Establishes a WebSocket connection to `relay_url`
-    /// 2. Sends a `Register` message with the local `PeerId`
-    /// 3. Waits for a `Registered` acknowledgment
+    /// 2. Sends a `Register` message with the local `PeerId` (and the
+    ///    invite token from `options`, if any)
+    /// 3. Waits for a `Registered` acknowledgment and negotiates the
+    ///    protocol version with the relay
     /// 4. Spawns a background task to read incoming messages
     ///
     /// # Errors
     ///
     /// - [`TransportError::Timeout`] if connection or registration times out.
     /// - [`TransportError::Unreachable`] if the relay URL cannot be resolved or connected.
-    /// - [`TransportError::Io`] for TLS failures or registration rejection.
-    pub async fn connect_with_timeouts(
+    /// - [`TransportError::Draining`] if the relay is shutting down.
+    /// - [`TransportError::Io`] for TLS failures or registration rejection
+    ///   (including access-control refusals).
+    #[allow(clippy::too_many_lines)]
+    pub async fn connect_with_options(
         relay_url: &str,
         local_id: PeerId,
-        connect_timeout: Duration,
-        register_timeout: Duration,
+        options: &RelayConnectOptions,
     ) -> Result<Self, TransportError> {
-        // Step 1: Connect to the relay WebSocket URL with a timeout.
-        let (ws_stream, _response) =
-            tokio::time::timeout(connect_timeout, connect_async(relay_url))
+        let RelayConnectOptions {
+            connect_timeout,
+            register_timeout,
+            ref invite_token,
+            keepalive_interval,
+            keepalive_max_missed,
+            ref proxy,
+        } = *options;
+
+        // Step 1: Connect to the relay WebSocket URL (through the proxy, if
+        // any) with a timeout.
+        let ws_stream =
+            tokio::time::timeout(connect_timeout, open_websocket(relay_url, proxy.as_ref()))
                 .await
                 .map_err(|_| {
                     tracing::warn!(url = relay_url, "relay WebSocket connect timed out");
                     TransportError::Timeout
-                })?
-                .map_err(|e| {
-                    tracing::warn!(url = relay_url, err = %e, "relay WebSocket connect failed");
-                    map_ws_connect_error(e)
-                })?;
+                })??;
 
         // Step 2: Split into sender and receiver halves.
         let (mut ws_sender, mut ws_reader) = ws_stream.split();
@@ -116,6 +268,8 @@ impl RelayTransport {
         // Step 3: Send Register message.
         let register = RelayMessage::Register {
             peer_id: local_id.as_str().to_string(),
+            invite_token: invite_token.clone(),
+            protocol: ProtocolHello::current(),
         };
         let register_bytes =
             relay::encode(&register).map_err(|e| TransportError::Io(std::io::Error::other(e)))?;
@@ -140,14 +294,36 @@ impl RelayTransport {
                 TransportError::Timeout
             })?;
 
-        match ack {
+        let protocol = match ack {
             Some(Ok(Message::Binary(data))) => match relay::decode(&data) {
-                Ok(RelayMessage::Registered { peer_id }) => {
+                Ok(RelayMessage::Registered { peer_id, protocol }) => {
+                    let negotiated =
+                        ProtocolHello::current().negotiate(&protocol).map_err(|e| {
+                            tracing::warn!(err = %e, "relay speaks an incompatible protocol");
+                            TransportError::Io(std::io::Error::other(e.to_string()))
+                        })?;
                     tracing::info!(
                         peer_id = %peer_id,
                         url = relay_url,
+                        version = negotiated.version,
                         "registered with relay server"
                     );
+                    negotiated
+                }
+                Ok(RelayMessage::Shutdown {
+                    reason,
+                    retry_after_ms,
+                    redirect_url,
+                }) => {
+                    tracing::warn!(
+                        reason = %reason,
+                        retry_after_ms,
+                        "relay is draining, registration refused"
+                    );
+                    return Err(TransportError::Draining {
+                        retry_after: Duration::from_millis(retry_after_ms),
+                        redirect_url,
+                    });
                 }
                 Ok(RelayMessage::Error { reason }) => {
                     tracing::warn!(reason = %reason, "relay registration rejected");
@@ -188,25 +364,126 @@ impl RelayTransport {
                 tracing::warn!("relay WebSocket stream ended during registration");
                 return Err(TransportError::ConnectionClosed);
             }
-        }
+        };
 
         // Step 5: Spawn background reader task.
         let (tx, rx) = mpsc::channel(256);
         let connected = Arc::new(AtomicBool::new(true));
         let reader_connected = Arc::clone(&connected);
-
-        let reader_handle = tokio::spawn(reader_loop(ws_reader, tx, reader_connected));
+        let shutdown_notice = Arc::new(SyncMutex::new(None));
+        let reader_notice = Arc::clone(&shutdown_notice);
+        let punch_handler: PunchHandler = Arc::new(SyncMutex::new(None));
+        let reader_punch = Arc::clone(&punch_handler);
+        let keepalive = Arc::new(KeepaliveState::default());
+        let inbound = tx.downgrade();
+
+        let reader_handle = tokio::spawn(reader_loop(
+            ws_reader,
+            tx,
+            reader_connected,
+            reader_notice,
+            reader_punch,
+            Arc::clone(&keepalive),
+        ));
+
+        // Step 6: Spawn the keepalive pinger, if enabled.
+        let ws_sender = Arc::new(Mutex::new(ws_sender));
+        let keepalive_handle = (!keepalive_interval.is_zero()).then(|| {
+            tokio::spawn(keepalive_loop(
+                Arc::clone(&ws_sender),
+                Arc::clone(&keepalive),
+                Arc::clone(&connected),
+                reader_handle.abort_handle(),
+                keepalive_interval,
+                keepalive_max_missed,
+            ))
+            .abort_handle()
+        });
 
         Ok(Self {
             local_id,
             relay_url: relay_url.to_string(),
-            ws_sender: Arc::new(Mutex::new(ws_sender)),
+            ws_sender,
             incoming: Mutex::new(rx),
+            inbound,
             connected,
+            shutdown_notice,
+            punch_handler,
+            protocol,
+            keepalive,
+            keepalive_handle,
             _reader_handle: reader_handle,
         })
     }
 
+    /// Check that a relay accepts WebSocket connections, without
+    /// registering, and return how long the handshake took.
+    ///
+    /// Uses the connect timeout and proxy from `options`.
+    ///
+    /// # Errors
+    ///
+    /// - [`TransportError::Timeout`] if the handshake takes longer than the
+    ///   connect timeout.
+    /// - [`TransportError::Unreachable`] or [`TransportError::Io`] if the
+    ///   connection fails.
+    pub async fn probe(
+        relay_url: &str,
+        options: &RelayConnectOptions,
+    ) -> Result<Duration, TransportError> {
+        let started = Instant::now();
+        let mut ws_stream = tokio::time::timeout(
+            options.connect_timeout,
+            open_websocket(relay_url, options.proxy.as_ref()),
+        )
+        .await
+        .map_err(|_| TransportError::Timeout)??;
+        let elapsed = started.elapsed();
+        let _ = ws_stream.close(None).await;
+        Ok(elapsed)
+    }
+
+    /// Close the connection gracefully.
+    ///
+    /// Sends a WebSocket close frame so the relay stops routing to this
+    /// client. Messages the relay delivered before processing the close can
+    /// still be read with [`recv`](Transport::recv) until it reports
+    /// [`TransportError::ConnectionClosed`].
+    pub async fn close(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+        let _ = self.ws_sender.lock().await.send(Message::Close(None)).await;
+    }
+
+    /// Queue a payload for [`recv`](Transport::recv) as if this relay had
+    /// delivered it from `from`.
+    ///
+    /// Used to hand over messages collected from a relay this client has
+    /// since moved away from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError::ConnectionClosed`] if the connection (and
+    /// with it the receive channel) is gone.
+    pub async fn inject_incoming(
+        &self,
+        from: PeerId,
+        payload: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        let tx = self
+            .inbound
+            .upgrade()
+            .ok_or(TransportError::ConnectionClosed)?;
+        tx.send((from, payload))
+            .await
+            .map_err(|_| TransportError::ConnectionClosed)
+    }
+
+    /// Return the protocol version and features agreed with the relay.
+    #[must_use]
+    pub const fn protocol(&self) -> NegotiatedProtocol {
+        self.protocol
+    }
+
     /// Return the relay server URL this transport is connected to.
     #[must_use]
     pub fn relay_url(&self) -> &str {
@@ -218,30 +495,71 @@ impl RelayTransport {
     pub const fn local_id(&self) -> &PeerId {
         &self.local_id
     }
-}
 
-impl Transport for RelayTransport {
-    /// Send an encrypted payload to a peer via the relay server.
+    /// Return the round-trip time of the most recently answered keepalive
+    /// ping, or `None` before the first pong (or with keepalives disabled).
+    #[must_use]
+    pub fn latency(&self) -> Option<Duration> {
+        *self.keepalive.latency.lock()
+    }
+
+    /// Return the drain notice sent by the relay, if it announced a shutdown.
+    #[must_use]
+    pub fn shutdown_notice(&self) -> Option<RelayShutdown> {
+        self.shutdown_notice.lock().clone()
+    }
+
+    /// Returns `true` if the relay brokers hole punching.
+    #[must_use]
+    pub const fn supports_hole_punch(&self) -> bool {
+        self.protocol.features.contains(FeatureFlags::HOLE_PUNCH)
+    }
+
+    /// Deliver [`PunchOffer`]s from other peers to `tx`.
     ///
-    /// Encodes the payload as a [`RelayMessage::RelayPayload`] and sends it
-    /// as a WebSocket binary frame. The relay server routes by the `to` field.
+    /// Replaces any previously installed handler. Offers arriving while the
+    /// channel is full are dropped; the sender will retry.
+    pub fn set_punch_handler(&self, tx: mpsc::Sender<PunchOffer>) {
+        *self.punch_handler.lock() = Some(tx);
+    }
+
+    /// Ask the relay to forward this peer's direct-connection candidates to
+    /// `peer`.
+    ///
+    /// The relay prepends the public address it observes for this client
+    /// (source IP plus `port`) to `local_addrs`.
     ///
     /// # Errors
     ///
+    /// - [`TransportError::Unreachable`] if the relay does not support hole
+    ///   punching.
     /// - [`TransportError::ConnectionClosed`] if the relay connection is down.
-    /// - [`TransportError::Io`] for encoding or WebSocket send failures.
-    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+    /// - [`TransportError::Io`] for encoding failures.
+    pub async fn request_punch(
+        &self,
+        peer: &PeerId,
+        port: u16,
+        local_addrs: Vec<SocketAddr>,
+    ) -> Result<(), TransportError> {
+        if !self.supports_hole_punch() {
+            return Err(TransportError::Unreachable(peer.clone()));
+        }
+        self.send_message(&RelayMessage::PunchRequest {
+            to: peer.as_str().to_string(),
+            port,
+            local_addrs,
+        })
+        .await
+    }
+
+    /// Encode and send a relay message, marking the transport disconnected
+    /// if the WebSocket write fails.
+    async fn send_message(&self, msg: &RelayMessage) -> Result<(), TransportError> {
         if !self.connected.load(Ordering::Relaxed) {
             return Err(TransportError::ConnectionClosed);
         }
 
-        let msg = RelayMessage::RelayPayload {
-            from: self.local_id.as_str().to_string(),
-            to: peer.as_str().to_string(),
-            payload: payload.to_vec(),
-        };
-        let bytes =
-            relay::encode(&msg).map_err(|e| TransportError::Io(std::io::Error::other(e)))?;
+        let bytes = relay::encode(msg).map_err(|e| TransportError::Io(std::io::Error::other(e)))?;
 
         self.ws_sender
             .lock()
@@ -252,9 +570,35 @@ impl Transport for RelayTransport {
                 tracing::warn!(err = %e, "relay send failed");
                 self.connected.store(false, Ordering::Relaxed);
                 TransportError::ConnectionClosed
-            })?;
+            })
+    }
+}
 
-        Ok(())
+impl Drop for RelayTransport {
+    fn drop(&mut self) {
+        if let Some(handle) = &self.keepalive_handle {
+            handle.abort();
+        }
+    }
+}
+
+impl Transport for RelayTransport {
+    /// Send an encrypted payload to a peer via the relay server.
+    ///
+    /// Encodes the payload as a [`RelayMessage::RelayPayload`] and sends it
+    /// as a WebSocket binary frame. The relay server routes by the `to` field.
+    ///
+    /// # Errors
+    ///
+    /// - [`TransportError::ConnectionClosed`] if the relay connection is down.
+    /// - [`TransportError::Io`] for encoding or WebSocket send failures.
+    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+        self.send_message(&RelayMessage::RelayPayload {
+            from: self.local_id.as_str().to_string(),
+            to: peer.as_str().to_string(),
+            payload: payload.to_vec(),
+        })
+        .await
     }
 
     /// Receive the next message from any peer via the relay.
@@ -292,13 +636,19 @@ impl Transport for RelayTransport {
 /// Parses incoming binary frames as [`RelayMessage`] variants and pushes
 /// received payloads into the `tx` channel. Handles protocol messages
 /// (`Queued`, `Error`) by logging. Malformed frames are logged and skipped
-/// (ext 10a) — the task does not disconnect on bad data.
+/// (ext 10a) — the task does not disconnect on bad data. A `Shutdown` notice
+/// is stored in `shutdown_notice` for the reconnect supervisor, and
+/// `PunchCandidates` are forwarded to the installed punch handler, and pongs
+/// answering a keepalive ping update the measured latency.
 ///
 /// Sets `connected` to `false` when the WebSocket closes or errors out.
 async fn reader_loop(
     mut ws_reader: WsReader,
     tx: mpsc::Sender<(PeerId, Vec<u8>)>,
     connected: Arc<AtomicBool>,
+    shutdown_notice: Arc<SyncMutex<Option<RelayShutdown>>>,
+    punch_handler: PunchHandler,
+    keepalive: Arc<KeepaliveState>,
 ) {
     while let Some(msg_result) = ws_reader.next().await {
         match msg_result {
@@ -320,6 +670,35 @@ async fn reader_loop(
                     Ok(RelayMessage::Error { reason }) => {
                         tracing::warn!(reason = %reason, "relay server error");
                     }
+                    Ok(RelayMessage::Shutdown {
+                        reason,
+                        retry_after_ms,
+                        redirect_url,
+                    }) => {
+                        tracing::info!(
+                            reason = %reason,
+                            retry_after_ms,
+                            "relay server is shutting down"
+                        );
+                        *shutdown_notice.lock() = Some(RelayShutdown {
+                            reason,
+                            retry_after: Duration::from_millis(retry_after_ms),
+                            redirect_url,
+                        });
+                    }
+                    Ok(RelayMessage::PunchCandidates { from, addrs }) => {
+                        let offer = PunchOffer {
+                            from: PeerId::new(from),
+                            addrs,
+                        };
+                        if let Some(handler) = punch_handler.lock().as_ref() {
+                            if handler.try_send(offer).is_err() {
+                                tracing::debug!("punch handler busy, dropping offer");
+                            }
+                        } else {
+                            tracing::debug!(from = %offer.from, "ignoring punch offer");
+                        }
+                    }
                     Ok(other) => {
                         tracing::debug!(?other, "unexpected relay message type");
                     }
@@ -333,8 +712,13 @@ async fn reader_loop(
                 tracing::info!("relay WebSocket closed by server");
                 break;
             }
-            Ok(Message::Ping(_) | Message::Pong(_) | Message::Text(_) | Message::Frame(_)) => {
-                // Ignore ping/pong/text/raw frames.
+            Ok(Message::Pong(data)) => {
+                if !keepalive.on_pong(&data) {
+                    tracing::debug!("ignoring unsolicited pong");
+                }
+            }
+            Ok(Message::Ping(_) | Message::Text(_) | Message::Frame(_)) => {
+                // Pings are answered by tungstenite; ignore text/raw frames.
             }
             Err(e) => {
                 tracing::warn!(err = %e, "relay WebSocket read error");
@@ -346,11 +730,104 @@ async fn reader_loop(
     tracing::info!("relay reader task exiting");
 }
 
+/// Background task that pings the relay every `interval`.
+///
+/// If `max_missed` consecutive pings go unanswered (or a ping cannot be
+/// written), the connection is declared dead: `connected` is cleared and the
+/// reader task is aborted, which closes the incoming channel so that
+/// [`RelayTransport::recv`] fails with [`TransportError::ConnectionClosed`].
+async fn keepalive_loop(
+    ws_sender: Arc<Mutex<WsSender>>,
+    state: Arc<KeepaliveState>,
+    connected: Arc<AtomicBool>,
+    reader: tokio::task::AbortHandle,
+    interval: Duration,
+    max_missed: u32,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately; the first ping waits a full interval.
+    ticker.tick().await;
+
+    let mut nonce: u64 = 0;
+    let mut missed: u32 = 0;
+    loop {
+        ticker.tick().await;
+        if !connected.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let unanswered = state.outstanding.lock().is_some();
+        missed = if unanswered { missed + 1 } else { 0 };
+        if missed >= max_missed.max(1) {
+            tracing::warn!(
+                missed,
+                "relay stopped answering keepalives, dropping connection"
+            );
+            break;
+        }
+
+        nonce = nonce.wrapping_add(1);
+        *state.outstanding.lock() = Some((nonce, Instant::now()));
+        let ping = Message::Ping(nonce.to_be_bytes().to_vec().into());
+        // A write stuck behind a full socket buffer counts as a missed ping.
+        match tokio::time::timeout(interval, async { ws_sender.lock().await.send(ping).await })
+            .await
+        {
+            Ok(Ok(())) | Err(_) => {}
+            Ok(Err(e)) => {
+                tracing::warn!(err = %e, "relay keepalive ping failed");
+                break;
+            }
+        }
+    }
+    connected.store(false, Ordering::Relaxed);
+    reader.abort();
+}
+
+/// Open a WebSocket connection to `relay_url`, directly or through `proxy`.
+///
+/// Through a proxy, the URL's host is handed to the proxy unresolved. Only
+/// `ws://` URLs can be tunnelled, since this build has no TLS support.
+async fn open_websocket(
+    relay_url: &str,
+    proxy: Option<&Socks5Proxy>,
+) -> Result<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>, TransportError> {
+    let result = match proxy {
+        None => connect_async(relay_url).await,
+        Some(proxy) => {
+            let url = url::Url::parse(relay_url).map_err(|e| {
+                TransportError::Io(std::io::Error::other(format!("invalid relay URL: {e}")))
+            })?;
+            if url.scheme() != "ws" {
+                return Err(TransportError::Io(std::io::Error::other(format!(
+                    "cannot tunnel {} relay URLs through a proxy",
+                    url.scheme()
+                ))));
+            }
+            let host = url
+                .host_str()
+                .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+                .ok_or_else(|| {
+                    TransportError::Io(std::io::Error::other("relay URL has no host"))
+                })?;
+            let port = url.port_or_known_default().unwrap_or(80);
+            let stream = proxy.connect(host, port).await?;
+            client_async(relay_url, MaybeTlsStream::Plain(stream)).await
+        }
+    };
+    result.map(|(ws_stream, _response)| ws_stream).map_err(|e| {
+        tracing::warn!(url = relay_url, err = %e, "relay WebSocket connect failed");
+        map_ws_connect_error(e)
+    })
+}
+
 /// Start a relay server in-process for testing.
 ///
 /// Binds to `127.0.0.1:0` (OS-assigned port) and returns the bound address
 /// and a [`tokio::task::JoinHandle`] for cleanup.
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 async fn start_test_relay() -> (std::net::SocketAddr, tokio::task::JoinHandle<()>) {
     termchat_relay::relay::start_server("127.0.0.1:0")
         .await
@@ -386,6 +863,14 @@ fn map_ws_connect_error(err: tokio_tungstenite::tungstenite::Error) -> Transport
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::collapsible_if,
+    clippy::duration_suboptimal_units,
+    clippy::expect_used,
+    clippy::match_wild_err_arm,
+    clippy::uninlined_format_args,
+    clippy::unwrap_used
+)]
 mod tests {
     use super::*;
     use std::time::Duration;
@@ -401,6 +886,12 @@ mod tests {
     /// the relay handshake, then closes the connection. Used to test disconnect
     /// detection on the client side.
     async fn start_disconnect_server() -> (String, tokio::task::JoinHandle<()>) {
+        start_mock_relay(ProtocolHello::current()).await
+    }
+
+    /// Like [`start_disconnect_server`], but the `Registered` ack advertises
+    /// the given protocol hello.
+    async fn start_mock_relay(hello: ProtocolHello) -> (String, tokio::task::JoinHandle<()>) {
         use futures_util::SinkExt;
         use termchat_proto::relay;
         use tokio::net::TcpListener;
@@ -417,9 +908,12 @@ mod tests {
 
             // Read Register message.
             if let Some(Ok(ws::Message::Binary(data))) = ws_stream.next().await {
-                if let Ok(RelayMessage::Register { peer_id }) = relay::decode(&data) {
+                if let Ok(RelayMessage::Register { peer_id, .. }) = relay::decode(&data) {
                     // Send Registered ack.
-                    let ack = RelayMessage::Registered { peer_id };
+                    let ack = RelayMessage::Registered {
+                        peer_id,
+                        protocol: hello,
+                    };
                     let bytes = relay::encode(&ack).unwrap();
                     let _ = ws_stream.send(ws::Message::Binary(bytes.into())).await;
                 }
@@ -434,6 +928,47 @@ mod tests {
         (url, handle)
     }
 
+    /// Start a WebSocket server that completes the relay handshake and then
+    /// goes silent: it keeps the socket open but never reads again, so pings
+    /// are never answered. Simulates a half-open connection.
+    async fn start_silent_server() -> (String, tokio::task::JoinHandle<()>) {
+        use futures_util::SinkExt;
+        use termchat_proto::relay;
+        use tokio::net::TcpListener;
+        use tokio_tungstenite::tungstenite as ws;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("ws://{addr}/ws");
+
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            if let Some(Ok(ws::Message::Binary(data))) = ws_stream.next().await {
+                if let Ok(RelayMessage::Register { peer_id, .. }) = relay::decode(&data) {
+                    let ack = RelayMessage::Registered {
+                        peer_id,
+                        protocol: ProtocolHello::current(),
+                    };
+                    let bytes = relay::encode(&ack).unwrap();
+                    let _ = ws_stream.send(ws::Message::Binary(bytes.into())).await;
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            drop(ws_stream);
+        });
+
+        (url, handle)
+    }
+
+    fn fast_keepalive() -> RelayConnectOptions {
+        RelayConnectOptions {
+            keepalive_interval: Duration::from_millis(50),
+            keepalive_max_missed: 2,
+            ..RelayConnectOptions::default()
+        }
+    }
+
     #[tokio::test]
     async fn connect_and_register_successfully() {
         let (url, _handle) = test_relay_url().await;
@@ -441,6 +976,35 @@ mod tests {
         assert!(transport.is_ok(), "connect failed: {:?}", transport.err());
     }
 
+    #[tokio::test]
+    async fn connect_negotiates_protocol_with_relay() {
+        let (url, _handle) = test_relay_url().await;
+        let transport = RelayTransport::connect(&url, PeerId::new("alice"))
+            .await
+            .unwrap();
+        let protocol = transport.protocol();
+        assert_eq!(protocol.version, termchat_proto::version::PROTOCOL_VERSION);
+        assert_eq!(
+            protocol.features,
+            termchat_proto::version::FeatureFlags::SUPPORTED
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_to_incompatible_relay_fails() {
+        let (url, _handle) = start_mock_relay(ProtocolHello {
+            version: 99,
+            min_version: 99,
+            features: termchat_proto::version::FeatureFlags::NONE,
+        })
+        .await;
+        let result = RelayTransport::connect(&url, PeerId::new("alice")).await;
+        match result {
+            Err(TransportError::Io(e)) => assert!(e.to_string().contains("incompatible")),
+            other => panic!("expected Io error, got: {:?}", other.err()),
+        }
+    }
+
     #[tokio::test]
     async fn transport_type_returns_relay() {
         let (url, _handle) = test_relay_url().await;
@@ -507,6 +1071,33 @@ mod tests {
         assert_eq!(data, b"hello bob");
     }
 
+    #[tokio::test]
+    async fn punch_request_delivers_offer_with_observed_address() {
+        let (url, _handle) = test_relay_url().await;
+
+        let alice = RelayTransport::connect(&url, PeerId::new("alice"))
+            .await
+            .unwrap();
+        let bob = RelayTransport::connect(&url, PeerId::new("bob"))
+            .await
+            .unwrap();
+        assert!(alice.supports_hole_punch());
+        let (offer_tx, mut offer_rx) = mpsc::channel(4);
+        bob.set_punch_handler(offer_tx);
+
+        alice
+            .request_punch(&PeerId::new("bob"), 7000, Vec::new())
+            .await
+            .unwrap();
+
+        let offer = tokio::time::timeout(Duration::from_secs(5), offer_rx.recv())
+            .await
+            .expect("offer timed out")
+            .unwrap();
+        assert_eq!(offer.from, PeerId::new("alice"));
+        assert_eq!(offer.addrs, vec!["127.0.0.1:7000".parse().unwrap()]);
+    }
+
     #[tokio::test]
     async fn bidirectional_send_recv() {
         let (url, _handle) = test_relay_url().await;
@@ -643,6 +1234,106 @@ mod tests {
         assert_eq!(transport.relay_url(), url);
     }
 
+    #[tokio::test]
+    async fn shutdown_notice_recorded_when_relay_drains() {
+        let state = Arc::new(termchat_relay::relay::RelayState::new());
+        let (addr, _handle) =
+            termchat_relay::relay::start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+                .await
+                .unwrap();
+        let url = format!("ws://{addr}/ws");
+        let transport = RelayTransport::connect(&url, PeerId::new("alice"))
+            .await
+            .unwrap();
+        assert!(transport.shutdown_notice().is_none());
+
+        let notice = termchat_relay::relay::ShutdownNotice {
+            reason: "maintenance".to_string(),
+            retry_after: Duration::from_secs(2),
+            redirect_url: None,
+        };
+        state.drain(notice, Duration::from_secs(5)).await;
+
+        // recv() returns ConnectionClosed once the reader observes the close.
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.recv()).await;
+        assert!(matches!(result, Ok(Err(TransportError::ConnectionClosed))));
+        assert_eq!(
+            transport.shutdown_notice(),
+            Some(RelayShutdown {
+                reason: "maintenance".to_string(),
+                retry_after: Duration::from_secs(2),
+                redirect_url: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_to_draining_relay_returns_draining() {
+        let state = Arc::new(termchat_relay::relay::RelayState::new());
+        let (addr, _handle) =
+            termchat_relay::relay::start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+                .await
+                .unwrap();
+        let notice = termchat_relay::relay::ShutdownNotice {
+            reason: "maintenance".to_string(),
+            retry_after: Duration::from_secs(7),
+            redirect_url: Some("ws://backup/ws".to_string()),
+        };
+        state.drain(notice, Duration::ZERO).await;
+
+        let url = format!("ws://{addr}/ws");
+        let result = RelayTransport::connect(&url, PeerId::new("alice")).await;
+        match result {
+            Err(TransportError::Draining {
+                retry_after,
+                redirect_url,
+            }) => {
+                assert_eq!(retry_after, Duration::from_secs(7));
+                assert_eq!(redirect_url.as_deref(), Some("ws://backup/ws"));
+            }
+            other => panic!("expected Draining, got: {:?}", other.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn invite_token_admits_peer_on_restricted_relay() {
+        use termchat_relay::access::{self, AccessPolicy, RoomCreationPolicy};
+
+        let state = Arc::new(
+            termchat_relay::relay::RelayState::new().with_access_control(
+                AccessPolicy::new(Vec::new(), Some("org-secret")),
+                RoomCreationPolicy::default(),
+            ),
+        );
+        let (addr, _handle) =
+            termchat_relay::relay::start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+                .await
+                .unwrap();
+        let url = format!("ws://{addr}/ws");
+
+        // Without a token the relay refuses registration.
+        let result = RelayTransport::connect(&url, PeerId::new("alice")).await;
+        match result {
+            Err(TransportError::Io(e)) => {
+                assert!(e.to_string().contains("registration rejected"));
+            }
+            other => panic!("expected Io rejection, got: {:?}", other.err()),
+        }
+
+        let options = RelayConnectOptions {
+            invite_token: Some(access::mint_invite(
+                b"org-secret",
+                Some("alice"),
+                access::unix_now() + 60,
+            )),
+            ..RelayConnectOptions::default()
+        };
+        let transport = RelayTransport::connect_with_options(&url, PeerId::new("alice"), &options)
+            .await
+            .unwrap();
+        assert!(transport.is_connected(&PeerId::new("alice")));
+    }
+
     #[tokio::test]
     async fn three_peers_exchange_messages() {
         let (url, _handle) = test_relay_url().await;
@@ -687,4 +1378,194 @@ mod tests {
         assert_eq!(from, PeerId::new("carol"));
         assert_eq!(data, b"hi bob");
     }
+
+    #[tokio::test]
+    async fn keepalive_measures_latency() {
+        let (url, _handle) = test_relay_url().await;
+        let transport =
+            RelayTransport::connect_with_options(&url, PeerId::new("alice"), &fast_keepalive())
+                .await
+                .unwrap();
+        assert!(transport.latency().is_none());
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while transport.latency().is_none() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let latency = transport.latency().expect("pong should be measured");
+        assert!(latency < Duration::from_secs(1));
+        assert!(transport.is_connected(&PeerId::new("anyone")));
+    }
+
+    #[tokio::test]
+    async fn unanswered_keepalives_close_half_open_connection() {
+        let (url, _handle) = start_silent_server().await;
+        let transport =
+            RelayTransport::connect_with_options(&url, PeerId::new("alice"), &fast_keepalive())
+                .await
+                .unwrap();
+        assert!(transport.is_connected(&PeerId::new("anyone")));
+
+        // The server never answers pings, so recv() fails once the misses
+        // add up instead of blocking forever.
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.recv()).await;
+        assert!(matches!(result, Ok(Err(TransportError::ConnectionClosed))));
+        assert!(!transport.is_connected(&PeerId::new("anyone")));
+        assert!(transport.latency().is_none());
+    }
+
+    #[tokio::test]
+    async fn keepalive_disabled_with_zero_interval() {
+        let (url, _handle) = start_silent_server().await;
+        let options = RelayConnectOptions {
+            keepalive_interval: Duration::ZERO,
+            ..RelayConnectOptions::default()
+        };
+        let transport = RelayTransport::connect_with_options(&url, PeerId::new("alice"), &options)
+            .await
+            .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_millis(300), transport.recv()).await;
+        assert!(result.is_err(), "recv should still be waiting");
+        assert!(transport.is_connected(&PeerId::new("anyone")));
+    }
+
+    #[tokio::test]
+    async fn probe_reports_reachable_relay() {
+        let (url, _handle) = test_relay_url().await;
+        let options = RelayConnectOptions::default();
+        let rtt = RelayTransport::probe(&url, &options).await.unwrap();
+        assert!(rtt < Duration::from_secs(5));
+
+        let result = RelayTransport::probe("ws://127.0.0.1:1/ws", &options).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn injected_payload_is_received() {
+        let (url, _handle) = test_relay_url().await;
+        let transport = RelayTransport::connect(&url, PeerId::new("alice"))
+            .await
+            .unwrap();
+        transport
+            .inject_incoming(PeerId::new("bob"), b"swept".to_vec())
+            .await
+            .unwrap();
+        let (from, data) = transport.recv().await.unwrap();
+        assert_eq!(from, PeerId::new("bob"));
+        assert_eq!(data, b"swept");
+    }
+
+    /// Start a minimal SOCKS5 proxy (no authentication, CONNECT only) that
+    /// records each requested target host and forwards to it.
+    async fn start_socks5_proxy() -> (Socks5Proxy, Arc<SyncMutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("socks5://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let targets = Arc::new(SyncMutex::new(Vec::new()));
+        let seen = Arc::clone(&targets);
+
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                let seen = Arc::clone(&seen);
+                tokio::spawn(async move {
+                    // Greeting: VER, NMETHODS, METHODS; choose "no auth".
+                    let mut head = [0u8; 2];
+                    client.read_exact(&mut head).await.unwrap();
+                    let mut methods = vec![0u8; usize::from(head[1])];
+                    client.read_exact(&mut methods).await.unwrap();
+                    client.write_all(&[5, 0]).await.unwrap();
+
+                    // Request: VER, CMD, RSV, ATYP, ADDR, PORT.
+                    let mut request = [0u8; 4];
+                    client.read_exact(&mut request).await.unwrap();
+                    let host = match request[3] {
+                        1 => {
+                            let mut ip = [0u8; 4];
+                            client.read_exact(&mut ip).await.unwrap();
+                            std::net::Ipv4Addr::from(ip).to_string()
+                        }
+                        3 => {
+                            let mut len = [0u8; 1];
+                            client.read_exact(&mut len).await.unwrap();
+                            let mut name = vec![0u8; usize::from(len[0])];
+                            client.read_exact(&mut name).await.unwrap();
+                            String::from_utf8(name).unwrap()
+                        }
+                        other => panic!("unsupported address type {other}"),
+                    };
+                    let mut port = [0u8; 2];
+                    client.read_exact(&mut port).await.unwrap();
+                    let port = u16::from_be_bytes(port);
+                    seen.lock().push(host.clone());
+
+                    let mut upstream = None;
+                    for addr in tokio::net::lookup_host((host.as_str(), port))
+                        .await
+                        .unwrap()
+                    {
+                        if let Ok(stream) = tokio::net::TcpStream::connect(addr).await {
+                            upstream = Some(stream);
+                            break;
+                        }
+                    }
+                    let mut upstream = upstream.unwrap();
+                    client
+                        .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+                        .await
+                        .unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                });
+            }
+        });
+
+        (proxy, targets)
+    }
+
+    #[tokio::test]
+    async fn connect_through_socks5_proxy_resolves_relay_remotely() {
+        let (addr, _handle) = start_test_relay().await;
+        let (proxy, targets) = start_socks5_proxy().await;
+        let options = RelayConnectOptions {
+            proxy: Some(proxy),
+            ..RelayConnectOptions::default()
+        };
+
+        let proxied_url = format!("ws://localhost:{}/ws", addr.port());
+        let alice =
+            RelayTransport::connect_with_options(&proxied_url, PeerId::new("alice"), &options)
+                .await
+                .unwrap();
+        // The host name reached the proxy unresolved.
+        assert_eq!(*targets.lock(), ["localhost"]);
+
+        let bob = RelayTransport::connect(&format!("ws://{addr}/ws"), PeerId::new("bob"))
+            .await
+            .unwrap();
+        alice
+            .send(&PeerId::new("bob"), b"through the proxy")
+            .await
+            .unwrap();
+        let (from, data) = tokio::time::timeout(Duration::from_secs(5), bob.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(from, PeerId::new("alice"));
+        assert_eq!(data, b"through the proxy");
+    }
+
+    #[tokio::test]
+    async fn unreachable_proxy_fails_connect() {
+        let (url, _handle) = test_relay_url().await;
+        let options = RelayConnectOptions {
+            proxy: Some("socks5://127.0.0.1:1".parse().unwrap()),
+            ..RelayConnectOptions::default()
+        };
+        let result =
+            RelayTransport::connect_with_options(&url, PeerId::new("alice"), &options).await;
+        assert!(matches!(result, Err(TransportError::Unreachable(_))));
+    }
 }
@@ -0,0 +1,248 @@
+//! Health scoring for a set of relay servers.
+//!
+//! [`RelayPool`] tracks every configured relay's measured latency and recent
+//! connection outcomes, and ranks them so the reconnect supervisor tries the
+//! healthiest relay first. A relay that just failed is penalised, so repeated
+//! failures rotate through the list instead of retrying the same dead relay.
+//!
+//! The pool also remembers which relays this client has registered on. A
+//! relay stores messages for peers that are offline *there*, so after moving
+//! to another relay those messages can only be collected by registering on
+//! the old relay again.
+
+use std::time::Duration;
+
+/// Latency assumed for a relay that has never been measured.
+const UNKNOWN_LATENCY: Duration = Duration::from_millis(250);
+
+/// Score penalty per failure among the last eight connection outcomes.
+const RECENT_FAILURE_PENALTY: Duration = Duration::from_secs(1);
+
+/// Score penalty per consecutive failure (since the last success).
+const CONSECUTIVE_FAILURE_PENALTY: Duration = Duration::from_secs(5);
+
+/// Health record for a single relay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayHealth {
+    /// The relay's WebSocket URL.
+    pub url: String,
+    /// Smoothed round-trip latency, if measured.
+    pub latency: Option<Duration>,
+    /// Failures since the last successful connection.
+    pub consecutive_failures: u32,
+    /// The last eight outcomes as a bit history, newest in bit 0
+    /// (1 = failure).
+    history: u8,
+    /// Whether this client has registered on the relay.
+    pub registered: bool,
+}
+
+impl RelayHealth {
+    const fn new(url: String) -> Self {
+        Self {
+            url,
+            latency: None,
+            consecutive_failures: 0,
+            history: 0,
+            registered: false,
+        }
+    }
+
+    /// Number of failures among the last eight connection outcomes.
+    #[must_use]
+    pub const fn recent_failures(&self) -> u32 {
+        self.history.count_ones()
+    }
+
+    /// Ranking score; lower is healthier.
+    #[must_use]
+    pub fn score(&self) -> Duration {
+        self.latency.unwrap_or(UNKNOWN_LATENCY)
+            + RECENT_FAILURE_PENALTY * self.recent_failures()
+            + CONSECUTIVE_FAILURE_PENALTY * self.consecutive_failures
+    }
+
+    fn record_outcome(&mut self, failed: bool) {
+        self.history = (self.history << 1) | u8::from(failed);
+    }
+
+    fn record_latency(&mut self, sample: Duration) {
+        // Exponentially weighted moving average (weight 1/4 for new samples).
+        self.latency = Some(self.latency.map_or(sample, |old| (old * 3 + sample) / 4));
+    }
+}
+
+/// The set of relays a client may connect to, ranked by health.
+#[derive(Debug, Clone, Default)]
+pub struct RelayPool {
+    /// Relays in configuration order (used to break ties).
+    relays: Vec<RelayHealth>,
+}
+
+impl RelayPool {
+    /// Create a pool from relay URLs, dropping duplicates.
+    pub fn new(urls: impl IntoIterator<Item = String>) -> Self {
+        let mut relays: Vec<RelayHealth> = Vec::new();
+        for url in urls {
+            if !relays.iter().any(|r| r.url == url) {
+                relays.push(RelayHealth::new(url));
+            }
+        }
+        Self { relays }
+    }
+
+    /// Number of relays in the pool.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.relays.len()
+    }
+
+    /// Returns `true` if the pool has no relays.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.relays.is_empty()
+    }
+
+    /// Health record for `url`, if it is in the pool.
+    #[must_use]
+    pub fn health(&self, url: &str) -> Option<&RelayHealth> {
+        self.relays.iter().find(|r| r.url == url)
+    }
+
+    /// Relay URLs, healthiest first. Ties keep configuration order.
+    #[must_use]
+    pub fn ranked(&self) -> Vec<&str> {
+        let mut relays: Vec<&RelayHealth> = self.relays.iter().collect();
+        relays.sort_by_key(|r| r.score());
+        relays.into_iter().map(|r| r.url.as_str()).collect()
+    }
+
+    /// The healthiest relay, if the pool is not empty.
+    #[must_use]
+    pub fn best(&self) -> Option<&str> {
+        self.relays
+            .iter()
+            .min_by_key(|r| r.score())
+            .map(|r| r.url.as_str())
+    }
+
+    /// Relays this client has registered on, other than `active`.
+    #[must_use]
+    pub fn registered_except(&self, active: &str) -> Vec<String> {
+        self.relays
+            .iter()
+            .filter(|r| r.registered && r.url != active)
+            .map(|r| r.url.clone())
+            .collect()
+    }
+
+    /// Record a successful connection to `url` that took `latency`.
+    pub fn record_success(&mut self, url: &str, latency: Duration) {
+        if let Some(relay) = self.get_mut(url) {
+            relay.consecutive_failures = 0;
+            relay.record_outcome(false);
+            relay.record_latency(latency);
+        }
+    }
+
+    /// Record that this client registered on `url`.
+    pub fn mark_registered(&mut self, url: &str) {
+        if let Some(relay) = self.get_mut(url) {
+            relay.registered = true;
+        }
+    }
+
+    /// Record a failed connection to (or a dropped connection from) `url`.
+    pub fn record_failure(&mut self, url: &str) {
+        if let Some(relay) = self.get_mut(url) {
+            relay.consecutive_failures = relay.consecutive_failures.saturating_add(1);
+            relay.record_outcome(true);
+        }
+    }
+
+    /// Record a round-trip time measured on an established connection.
+    pub fn record_latency(&mut self, url: &str, rtt: Duration) {
+        if let Some(relay) = self.get_mut(url) {
+            relay.record_latency(rtt);
+        }
+    }
+
+    fn get_mut(&mut self, url: &str) -> Option<&mut RelayHealth> {
+        self.relays.iter_mut().find(|r| r.url == url)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn pool() -> RelayPool {
+        RelayPool::new(["ws://a", "ws://b", "ws://c"].map(String::from))
+    }
+
+    #[test]
+    fn new_drops_duplicates_and_keeps_order() {
+        let pool = RelayPool::new(["ws://a", "ws://b", "ws://a"].map(String::from));
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.ranked(), ["ws://a", "ws://b"]);
+    }
+
+    #[test]
+    fn unmeasured_relays_rank_in_config_order() {
+        assert_eq!(pool().best(), Some("ws://a"));
+        assert_eq!(pool().ranked(), ["ws://a", "ws://b", "ws://c"]);
+    }
+
+    #[test]
+    fn failure_rotates_to_next_relay() {
+        let mut pool = pool();
+        pool.record_failure("ws://a");
+        assert_eq!(pool.best(), Some("ws://b"));
+        pool.record_failure("ws://b");
+        assert_eq!(pool.best(), Some("ws://c"));
+        pool.record_failure("ws://c");
+        assert_eq!(pool.ranked(), ["ws://a", "ws://b", "ws://c"]);
+    }
+
+    #[test]
+    fn lower_latency_ranks_first() {
+        let mut pool = pool();
+        pool.record_success("ws://a", Duration::from_millis(400));
+        pool.record_success("ws://c", Duration::from_millis(20));
+        assert_eq!(pool.ranked(), ["ws://c", "ws://b", "ws://a"]);
+    }
+
+    #[test]
+    fn failure_history_outweighs_latency() {
+        let mut pool = pool();
+        pool.record_success("ws://a", Duration::from_millis(5));
+        pool.record_failure("ws://a");
+        pool.record_success("ws://a", Duration::from_millis(5));
+        pool.record_success("ws://b", Duration::from_millis(200));
+        let a = pool.health("ws://a").unwrap();
+        assert_eq!(a.consecutive_failures, 0);
+        assert_eq!(a.recent_failures(), 1);
+        assert_eq!(pool.best(), Some("ws://b"));
+    }
+
+    #[test]
+    fn latency_is_smoothed() {
+        let mut pool = pool();
+        pool.record_success("ws://a", Duration::from_millis(100));
+        pool.record_latency("ws://a", Duration::from_millis(20));
+        assert_eq!(
+            pool.health("ws://a").unwrap().latency,
+            Some(Duration::from_millis(80))
+        );
+    }
+
+    #[test]
+    fn registered_except_skips_active_relay() {
+        let mut pool = pool();
+        pool.mark_registered("ws://a");
+        pool.mark_registered("ws://b");
+        pool.mark_registered("ws://unknown");
+        assert_eq!(pool.registered_except("ws://b"), ["ws://a"]);
+    }
+}
@@ -0,0 +1,558 @@
+//! Network condition simulator for testing.
+//!
+//! [`SimTransport`] wraps any [`Transport`] and degrades it the way a real
+//! network would: each outgoing payload may be delayed (fixed latency plus
+//! random jitter), dropped, duplicated, or held back so that later payloads
+//! overtake it, and the link can go down entirely. Every decision is drawn
+//! from an RNG seeded by [`NetworkConditions::seed`], so a failing test
+//! replays the same sequence of losses, duplicates and delays.
+//!
+//! Endpoints that share a [`SimLink`] go down and come back together, like
+//! the two ends of one connection. Use [`SimTransport::loopback_pair`] to
+//! get two such endpoints over a [`LoopbackTransport`].
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use parking_lot::Mutex as SyncMutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use super::loopback::LoopbackTransport;
+use super::{PeerId, TrafficClass, Transport, TransportError, TransportType};
+
+/// Channel capacity of the loopback pair built by [`SimTransport::loopback_pair`].
+const LOOPBACK_BUFFER: usize = 256;
+
+/// Conditions applied to every payload a [`SimTransport`] sends.
+///
+/// Probabilities are in `0.0..=1.0`; values outside that range saturate.
+/// The default is a perfect link: no latency, loss, duplication,
+/// reordering or disconnects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkConditions {
+    /// Fixed one-way delay added to every payload.
+    pub latency: Duration,
+    /// Maximum random delay added on top of `latency`.
+    pub jitter: Duration,
+    /// Probability that a payload is silently dropped.
+    pub loss: f64,
+    /// Probability that a payload is delivered twice.
+    pub duplicate: f64,
+    /// Probability that a payload is held back by `reorder_delay`, letting
+    /// payloads sent after it arrive first.
+    pub reorder: f64,
+    /// Extra delay applied to reordered payloads.
+    pub reorder_delay: Duration,
+    /// Probability that a send takes the link down for `outage`.
+    pub disconnect: f64,
+    /// How long a random disconnect keeps the link down.
+    pub outage: Duration,
+    /// Seed for the RNG behind every decision.
+    pub seed: u64,
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            loss: 0.0,
+            duplicate: 0.0,
+            reorder: 0.0,
+            reorder_delay: Duration::from_millis(20),
+            disconnect: 0.0,
+            outage: Duration::from_millis(500),
+            seed: 0,
+        }
+    }
+}
+
+/// Counters describing what a [`SimTransport`] did to its payloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimStats {
+    /// Payloads accepted for sending (including dropped ones).
+    pub sent: u64,
+    /// Payloads lost, either dropped outright or caught by an outage.
+    pub dropped: u64,
+    /// Payloads delivered a second time.
+    pub duplicated: u64,
+    /// Payloads held back so later ones could overtake them.
+    pub reordered: u64,
+    /// Random disconnects triggered by sends.
+    pub disconnects: u64,
+}
+
+#[derive(Debug, Default)]
+struct SimCounters {
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    duplicated: AtomicU64,
+    reordered: AtomicU64,
+    disconnects: AtomicU64,
+}
+
+impl SimCounters {
+    fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> SimStats {
+        SimStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            duplicated: self.duplicated.load(Ordering::Relaxed),
+            reordered: self.reordered.load(Ordering::Relaxed),
+            disconnects: self.disconnects.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LinkState {
+    /// Down until [`SimLink::reconnect`] is called.
+    down: bool,
+    /// Down until this instant (a timed outage).
+    down_until: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct LinkInner {
+    state: SyncMutex<LinkState>,
+    /// Woken whenever the link goes down, so pending receives fail.
+    went_down: Notify,
+}
+
+/// Up/down state shared by the endpoints of a simulated connection.
+///
+/// Cloning yields another handle to the same link. While the link is down,
+/// sends and receives fail with [`TransportError::ConnectionClosed`] and
+/// payloads in flight are lost.
+#[derive(Debug, Clone, Default)]
+pub struct SimLink {
+    inner: Arc<LinkInner>,
+}
+
+impl SimLink {
+    /// Create a link that is up.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the link is up.
+    #[must_use]
+    pub fn is_up(&self) -> bool {
+        let state = self.inner.state.lock();
+        !state.down && state.down_until.is_none_or(|until| Instant::now() >= until)
+    }
+
+    /// Take the link down until [`reconnect`](Self::reconnect) is called.
+    pub fn disconnect(&self) {
+        self.inner.state.lock().down = true;
+        self.inner.went_down.notify_waiters();
+    }
+
+    /// Take the link down for `outage`, after which it comes back by itself.
+    pub fn disconnect_for(&self, outage: Duration) {
+        self.inner.state.lock().down_until = Some(Instant::now() + outage);
+        self.inner.went_down.notify_waiters();
+    }
+
+    /// Bring the link back up, ending any outage.
+    pub fn reconnect(&self) {
+        let mut state = self.inner.state.lock();
+        state.down = false;
+        state.down_until = None;
+    }
+}
+
+/// What to do with one outgoing payload.
+enum Fate {
+    /// Trip a random disconnect and fail the send.
+    Disconnect,
+    /// Lose the payload but report success, as a real network would.
+    Drop,
+    /// Deliver the payload once per delay.
+    Deliver(Vec<Duration>),
+}
+
+/// A [`Transport`] wrapper that applies [`NetworkConditions`] to everything
+/// it sends.
+///
+/// Conditions act on the sending side; the receiving side only observes
+/// the [`SimLink`] state. Wrap both ends of a connection (sharing one link)
+/// to degrade traffic in both directions.
+pub struct SimTransport<T> {
+    /// The wrapped transport.
+    inner: Arc<T>,
+    /// Conditions applied to outgoing payloads.
+    conditions: NetworkConditions,
+    /// Seeded RNG behind every decision.
+    rng: SyncMutex<StdRng>,
+    /// Up/down state, possibly shared with the remote endpoint.
+    link: SimLink,
+    /// What has been done to outgoing payloads so far.
+    counters: Arc<SimCounters>,
+}
+
+impl<T: Transport + 'static> SimTransport<T> {
+    /// Wrap `inner` with its own link.
+    #[must_use]
+    pub fn new(inner: T, conditions: NetworkConditions) -> Self {
+        Self::with_link(inner, conditions, SimLink::new())
+    }
+
+    /// Wrap `inner`, sharing `link` with other endpoints.
+    #[must_use]
+    pub fn with_link(inner: T, conditions: NetworkConditions, link: SimLink) -> Self {
+        let rng = StdRng::seed_from_u64(conditions.seed);
+        Self {
+            inner: Arc::new(inner),
+            conditions,
+            rng: SyncMutex::new(rng),
+            link,
+            counters: Arc::default(),
+        }
+    }
+
+    /// The wrapped transport.
+    #[must_use]
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// The link this endpoint sends and receives over.
+    #[must_use]
+    pub const fn link(&self) -> &SimLink {
+        &self.link
+    }
+
+    /// The conditions applied to outgoing payloads.
+    #[must_use]
+    pub const fn conditions(&self) -> &NetworkConditions {
+        &self.conditions
+    }
+
+    /// What has been done to outgoing payloads so far.
+    #[must_use]
+    pub fn stats(&self) -> SimStats {
+        self.counters.snapshot()
+    }
+
+    /// Decide the fate of the next outgoing payload.
+    fn next_fate(&self) -> Fate {
+        let conditions = &self.conditions;
+        let mut rng = self.rng.lock();
+        if chance(&mut rng, conditions.disconnect) {
+            return Fate::Disconnect;
+        }
+        if chance(&mut rng, conditions.loss) {
+            return Fate::Drop;
+        }
+        let copies = if chance(&mut rng, conditions.duplicate) {
+            2
+        } else {
+            1
+        };
+        let delays = (0..copies)
+            .map(|_| {
+                let mut delay =
+                    conditions.latency + rng.random_range(Duration::ZERO..=conditions.jitter);
+                if chance(&mut rng, conditions.reorder) {
+                    SimCounters::bump(&self.counters.reordered);
+                    delay += conditions.reorder_delay;
+                }
+                delay
+            })
+            .collect();
+        drop(rng);
+        Fate::Deliver(delays)
+    }
+
+    async fn transmit(
+        &self,
+        peer: &PeerId,
+        class: Option<TrafficClass>,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        if !self.link.is_up() {
+            return Err(TransportError::ConnectionClosed);
+        }
+        let delays = match self.next_fate() {
+            Fate::Disconnect => {
+                SimCounters::bump(&self.counters.disconnects);
+                self.link.disconnect_for(self.conditions.outage);
+                return Err(TransportError::ConnectionClosed);
+            }
+            Fate::Drop => {
+                SimCounters::bump(&self.counters.sent);
+                SimCounters::bump(&self.counters.dropped);
+                return Ok(());
+            }
+            Fate::Deliver(delays) => delays,
+        };
+        SimCounters::bump(&self.counters.sent);
+        if delays.len() > 1 {
+            SimCounters::bump(&self.counters.duplicated);
+        }
+
+        for delay in delays {
+            if delay.is_zero() {
+                forward(&*self.inner, peer, class, payload).await?;
+                continue;
+            }
+            let inner = Arc::clone(&self.inner);
+            let link = self.link.clone();
+            let counters = Arc::clone(&self.counters);
+            let peer = peer.clone();
+            let payload = payload.to_vec();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                if !link.is_up() {
+                    SimCounters::bump(&counters.dropped);
+                    return;
+                }
+                if let Err(e) = forward(&*inner, &peer, class, &payload).await {
+                    tracing::debug!(peer = %peer, error = %e, "delayed simulated send failed");
+                    SimCounters::bump(&counters.dropped);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl SimTransport<LoopbackTransport> {
+    /// Create two simulated endpoints over a loopback pair, sharing one link.
+    ///
+    /// Both ends apply `conditions`; the second end's RNG is seeded with
+    /// `conditions.seed + 1` so the two directions see different losses.
+    #[must_use]
+    pub fn loopback_pair(
+        id_a: PeerId,
+        id_b: PeerId,
+        conditions: NetworkConditions,
+    ) -> (Self, Self) {
+        let (a, b) = LoopbackTransport::create_pair(id_a, id_b, LOOPBACK_BUFFER);
+        let link = SimLink::new();
+        let conditions_b = NetworkConditions {
+            seed: conditions.seed.wrapping_add(1),
+            ..conditions
+        };
+        (
+            Self::with_link(a, conditions, link.clone()),
+            Self::with_link(b, conditions_b, link),
+        )
+    }
+}
+
+impl<T: Transport + 'static> Transport for SimTransport<T> {
+    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+        self.transmit(peer, None, payload).await
+    }
+
+    async fn send_class(
+        &self,
+        peer: &PeerId,
+        class: TrafficClass,
+        payload: &[u8],
+    ) -> Result<(), TransportError> {
+        self.transmit(peer, Some(class), payload).await
+    }
+
+    async fn recv(&self) -> Result<(PeerId, Vec<u8>), TransportError> {
+        // Created before the check so a disconnect in between still wakes us.
+        let went_down = self.link.inner.went_down.notified();
+        if !self.link.is_up() {
+            return Err(TransportError::ConnectionClosed);
+        }
+        tokio::select! {
+            result = self.inner.recv() => {
+                let received = result?;
+                if self.link.is_up() {
+                    Ok(received)
+                } else {
+                    // Arrived during an outage: lost with the connection.
+                    Err(TransportError::ConnectionClosed)
+                }
+            }
+            () = went_down => Err(TransportError::ConnectionClosed),
+        }
+    }
+
+    fn is_connected(&self, peer: &PeerId) -> bool {
+        self.link.is_up() && self.inner.is_connected(peer)
+    }
+
+    fn transport_type(&self) -> TransportType {
+        self.inner.transport_type()
+    }
+}
+
+/// Roll for an event with probability `p`. NaN and non-positive values never
+/// happen.
+fn chance(rng: &mut StdRng, p: f64) -> bool {
+    p > 0.0 && rng.random::<f64>() < p
+}
+
+/// Hand a payload to the wrapped transport, keeping its traffic class.
+async fn forward<T: Transport>(
+    inner: &T,
+    peer: &PeerId,
+    class: Option<TrafficClass>,
+    payload: &[u8],
+) -> Result<(), TransportError> {
+    match class {
+        Some(class) => inner.send_class(peer, class, payload).await,
+        None => inner.send(peer, payload).await,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn pair(
+        conditions: NetworkConditions,
+    ) -> (
+        SimTransport<LoopbackTransport>,
+        SimTransport<LoopbackTransport>,
+    ) {
+        SimTransport::loopback_pair(PeerId::new("alice"), PeerId::new("bob"), conditions)
+    }
+
+    /// Send `count` numbered payloads from `a` and collect what `b` receives
+    /// within `wait`.
+    async fn exchange(
+        a: &SimTransport<LoopbackTransport>,
+        b: &SimTransport<LoopbackTransport>,
+        count: u8,
+        wait: Duration,
+    ) -> Vec<u8> {
+        for i in 0..count {
+            a.send(&PeerId::new("bob"), &[i]).await.unwrap();
+        }
+        let mut received = Vec::new();
+        while let Ok(Ok((_, payload))) = tokio::time::timeout(wait, b.recv()).await {
+            received.push(payload[0]);
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn perfect_link_delivers_everything_in_order() {
+        let (a, b) = pair(NetworkConditions::default());
+        let received = exchange(&a, &b, 10, Duration::from_millis(20)).await;
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert_eq!(a.stats().sent, 10);
+        assert_eq!(a.stats().dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn same_seed_drops_same_payloads() {
+        let conditions = NetworkConditions {
+            loss: 0.5,
+            seed: 42,
+            ..NetworkConditions::default()
+        };
+        let (a1, b1) = pair(conditions);
+        let (a2, b2) = pair(conditions);
+        let first = exchange(&a1, &b1, 50, Duration::from_millis(20)).await;
+        let second = exchange(&a2, &b2, 50, Duration::from_millis(20)).await;
+        assert_eq!(first, second);
+        assert!(!first.is_empty() && first.len() < 50, "got {first:?}");
+        assert_eq!(a1.stats().dropped, 50 - first.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_delivered_twice() {
+        let (a, b) = pair(NetworkConditions {
+            duplicate: 1.0,
+            ..NetworkConditions::default()
+        });
+        let received = exchange(&a, &b, 3, Duration::from_millis(20)).await;
+        assert_eq!(received, [0, 0, 1, 1, 2, 2]);
+        assert_eq!(a.stats().duplicated, 3);
+    }
+
+    #[tokio::test]
+    async fn reordered_payloads_arrive_late() {
+        let (a, b) = pair(NetworkConditions {
+            reorder: 0.5,
+            seed: 7,
+            ..NetworkConditions::default()
+        });
+        let received = exchange(&a, &b, 20, Duration::from_millis(100)).await;
+        let mut sorted = received.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+        assert_ne!(received, sorted, "some payloads should be overtaken");
+        assert!(a.stats().reordered > 0);
+    }
+
+    #[tokio::test]
+    async fn latency_delays_delivery() {
+        let (a, b) = pair(NetworkConditions {
+            latency: Duration::from_millis(30),
+            ..NetworkConditions::default()
+        });
+        let start = Instant::now();
+        a.send(&PeerId::new("bob"), b"slow").await.unwrap();
+        let (_, payload) = b.recv().await.unwrap();
+        assert_eq!(payload, b"slow");
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn disconnect_fails_both_ends_until_reconnect() {
+        let (a, b) = pair(NetworkConditions::default());
+        let pending = tokio::spawn(async move {
+            let result = b.recv().await;
+            (b, result)
+        });
+        tokio::task::yield_now().await;
+
+        a.link().disconnect();
+        let (b, result) = pending.await.unwrap();
+        assert!(matches!(result, Err(TransportError::ConnectionClosed)));
+        assert!(matches!(
+            a.send(&PeerId::new("bob"), b"lost").await,
+            Err(TransportError::ConnectionClosed)
+        ));
+        assert!(!a.is_connected(&PeerId::new("bob")));
+
+        a.link().reconnect();
+        a.send(&PeerId::new("bob"), b"back").await.unwrap();
+        let (_, payload) = b.recv().await.unwrap();
+        assert_eq!(payload, b"back");
+    }
+
+    #[tokio::test]
+    async fn timed_outage_ends_by_itself() {
+        let (a, _b) = pair(NetworkConditions::default());
+        a.link().disconnect_for(Duration::from_millis(30));
+        assert!(!a.link().is_up());
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(a.link().is_up());
+    }
+
+    #[tokio::test]
+    async fn random_disconnect_takes_link_down() {
+        let (a, _b) = pair(NetworkConditions {
+            disconnect: 1.0,
+            outage: Duration::from_secs(5),
+            ..NetworkConditions::default()
+        });
+        assert!(matches!(
+            a.send(&PeerId::new("bob"), b"trip").await,
+            Err(TransportError::ConnectionClosed)
+        ));
+        assert!(!a.link().is_up());
+        assert_eq!(a.stats().disconnects, 1);
+    }
+}
@@ -3,6 +3,7 @@
 pub mod chat_panel;
 pub mod sidebar;
 pub mod status_bar;
+pub mod task_board;
 pub mod task_panel;
 pub mod theme;
 
@@ -24,6 +25,13 @@ pub fn draw(frame: &mut Frame, app: &App) {
     let content_area = main_chunks[0];
     let status_area = main_chunks[1];
 
+    // The task board replaces the panels while it is open
+    if let Some(board) = &app.task_board {
+        task_board::render(frame, content_area, app, board);
+        status_bar::render(frame, status_area, app);
+        return;
+    }
+
     // Create three-column layout for content
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
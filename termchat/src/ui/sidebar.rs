@@ -25,7 +25,7 @@ const fn presence_indicator(status: PresenceStatus) -> (&'static str, ratatui::s
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     let is_focused = app.focus == PanelFocus::Sidebar;
 
-    let items: Vec<ListItem> = app
+    let mut items: Vec<ListItem> = app
         .conversations
         .iter()
         .enumerate()
@@ -83,6 +83,23 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         })
         .collect();
 
+    // Peers on the local network, listed below the conversations.
+    let lan_peers = app.visible_lan_peers();
+    if !lan_peers.is_empty() {
+        items.push(ListItem::new(Line::from(Span::styled(
+            "LAN",
+            theme::dimmed(),
+        ))));
+        items.extend(lan_peers.iter().map(|peer| {
+            ListItem::new(Line::from(vec![
+                Span::raw("  "),
+                Span::raw(&peer.peer_id),
+                Span::raw(" "),
+                Span::styled(&peer.addr, theme::dimmed()),
+            ]))
+        }));
+    }
+
     let block = Block::default()
         .title("Conversations")
         .title_style(theme::panel_title(theme::SIDEBAR_TITLE))
@@ -8,23 +8,43 @@ use ratatui::{
 };
 
 use super::theme;
-use crate::app::{App, PanelFocus};
+use crate::app::{App, BoardPrompt, PanelFocus};
 
 /// Render the status bar at the bottom of the screen.
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     let help_text = match app.focus {
+        _ if app
+            .task_board
+            .as_ref()
+            .is_some_and(|b| matches!(b.prompt, Some(BoardPrompt::Filter(_)))) =>
+        {
+            "@name #label | Enter: apply filter | Esc: cancel"
+        }
+        _ if app.task_board.as_ref().is_some_and(|b| b.prompt.is_some()) => {
+            "Enter: add card | Esc: cancel"
+        }
+        _ if app.task_board.is_some() => {
+            "←→/hl: column | ↑↓/jk: card | H/L: move card | n: new card | f: filter | Esc: close board"
+        }
         PanelFocus::Input => "Enter: send | Tab: switch panel | Esc: quit | ←→: move cursor",
         PanelFocus::Sidebar => "Tab: switch panel | ↑↓/jk: navigate | Esc: quit",
         PanelFocus::Chat => "Tab: switch panel | ↑↓/jk: scroll | Esc: quit",
+        PanelFocus::Tasks if app.task_detail.is_some() => {
+            "Tab: switch panel | ↑↓/jk: navigate | Enter: toggle status | Esc: close task"
+        }
         PanelFocus::Tasks => {
-            "Tab: switch panel | ↑↓/jk: navigate | Enter: toggle status | Esc: quit"
+            "Tab: switch panel | ↑↓/jk: navigate | Enter: toggle status | o: open | b: board | Esc: quit"
         }
     };
 
     let (dot_color, status_text) = if app.is_connected {
+        let latency = app
+            .relay_latency_ms
+            .map(|ms| format!(" ({ms} ms)"))
+            .unwrap_or_default();
         (
             theme::SUCCESS,
-            format!("Connected via {}", app.connection_info),
+            format!("Connected via {}{latency}", app.connection_info),
         )
     } else if app.connection_info == "Reconnecting" {
         (theme::WARNING, "Reconnecting...".to_string())
@@ -0,0 +1,107 @@
+//! Full-screen task board rendering.
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+
+use super::theme;
+use crate::app::{App, BlockerState, BoardPrompt, TaskBoard};
+use crate::tasks::board::{BOARD_COLUMNS, column_title};
+
+/// Render the task board with one column per status.
+pub fn render(frame: &mut Frame, area: Rect, app: &App, board: &TaskBoard) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3)])
+        .split(area);
+
+    let mut header = vec![Span::styled(
+        "Task board",
+        theme::panel_title(theme::TASKS_TITLE),
+    )];
+    if board.filter.is_empty() {
+        header.push(Span::styled("  f: filter", theme::dimmed()));
+    } else {
+        header.push(Span::styled(
+            format!("  filter: {}", board.filter),
+            theme::highlighted(),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(header)), chunks[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(chunks[1]);
+
+    for (status, &column_area) in BOARD_COLUMNS.into_iter().zip(columns.iter()) {
+        let cards = app.board_cards(status);
+        let current = status == board.column;
+        let border_style = if current {
+            theme::highlighted()
+        } else {
+            theme::normal()
+        };
+        let block = Block::default()
+            .title(format!("{} ({})", column_title(status), cards.len()))
+            .title_style(theme::panel_title(theme::TASKS_TITLE))
+            .borders(Borders::ALL)
+            .border_style(border_style);
+
+        let mut items = Vec::new();
+        // The quick-create card sits at the top of its column.
+        if current {
+            match &board.prompt {
+                Some(BoardPrompt::NewCard(title)) => items.push(prompt_item("+ ", title)),
+                Some(BoardPrompt::Filter(filter)) => items.push(prompt_item("filter: ", filter)),
+                None => {}
+            }
+        }
+
+        for (row, task) in cards.iter().enumerate() {
+            let style = if current && row == board.row && board.prompt.is_none() {
+                theme::selected()
+            } else {
+                theme::normal()
+            };
+            let assignee_str = task
+                .assignee
+                .as_ref()
+                .map_or(String::new(), |a| format!(" (@{a})"));
+            let mut title = vec![Span::styled(
+                format!("#{} {}{assignee_str}", task.number, task.title),
+                style,
+            )];
+            if let BlockerState::Blocked(_) = app.blocker_state(task) {
+                title.push(Span::styled(
+                    " [blocked]",
+                    Style::default().fg(theme::WARNING),
+                ));
+            }
+            let mut lines = vec![Line::from(title)];
+            let details = task.details();
+            if !details.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", details.trim_start()),
+                    theme::dimmed(),
+                )));
+            }
+            items.push(ListItem::new(lines));
+        }
+
+        frame.render_widget(List::new(items).block(block), column_area);
+    }
+}
+
+/// The board's input line, with a cursor after the text.
+fn prompt_item(label: &str, text: &str) -> ListItem<'static> {
+    ListItem::new(Line::from(vec![
+        Span::styled(label.to_string(), theme::highlighted()),
+        Span::raw(text.to_string()),
+        Span::styled(" ", theme::input_cursor()),
+    ]))
+}
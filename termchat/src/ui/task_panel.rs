@@ -3,12 +3,15 @@
 use ratatui::{
     Frame,
     layout::{Alignment, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
 
+use std::collections::HashMap;
+
 use super::theme;
-use crate::app::{App, PanelFocus, TaskDisplayStatus};
+use crate::app::{App, BlockerState, DisplayTask, PanelFocus, TaskDisplayStatus, format_hlc_time};
 
 /// Render the task panel with interactive task list from app state.
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
@@ -34,40 +37,183 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
-    let items: Vec<ListItem> = app
-        .tasks
-        .iter()
-        .enumerate()
-        .map(|(i, task)| {
-            let is_selected = focused && i == app.selected_task;
-
-            let status_style = match task.status {
-                TaskDisplayStatus::Completed => theme::dimmed(),
-                TaskDisplayStatus::InProgress => theme::highlighted(),
-                TaskDisplayStatus::Open => theme::normal(),
-            };
-
-            let assignee_str = task
-                .assignee
-                .as_ref()
-                .map_or(String::new(), |a| format!(" (@{a})"));
-
-            let line_style = if is_selected {
-                theme::selected()
-            } else {
-                status_style
-            };
-
-            let line = Line::from(vec![
-                Span::styled(format!("#{} ", task.number), line_style),
-                Span::styled(task.status.symbol(), line_style),
-                Span::styled(format!(" {}{assignee_str}", task.title), line_style),
-            ]);
-
-            ListItem::new(line)
-        })
-        .collect();
+    if let Some(task) = app
+        .task_detail
+        .and_then(|number| app.tasks.iter().find(|t| t.number == number))
+    {
+        render_detail(frame, area, app, task, border_style);
+        return;
+    }
+
+    let items: Vec<ListItem> =
+        app.tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let is_selected = focused && i == app.selected_task;
+
+                let status_style = match task.status {
+                    TaskDisplayStatus::Completed => theme::dimmed(),
+                    TaskDisplayStatus::InProgress => theme::highlighted(),
+                    TaskDisplayStatus::Open => theme::normal(),
+                };
+
+                let assignee_str = task
+                    .assignee
+                    .as_ref()
+                    .map_or(String::new(), |a| format!(" (@{a})"));
+
+                let line_style = if is_selected {
+                    theme::selected()
+                } else {
+                    status_style
+                };
+
+                // Subtasks are marked rather than reordered, so task
+                // numbers keep matching their position.
+                let indent = if task.parent.is_some() {
+                    "\u{21b3} "
+                } else {
+                    ""
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{indent}#{} ", task.number), line_style),
+                    Span::styled(task.status.symbol(), line_style),
+                    Span::styled(format!(" {}{assignee_str}", task.title), line_style),
+                ];
+                if let Some((done, total)) = app.subtask_progress(task.number) {
+                    spans.push(Span::styled(format!(" ({done}/{total})"), theme::dimmed()));
+                }
+                spans.push(Span::styled(task.details(), theme::dimmed()));
+                match app.blocker_state(task) {
+                    BlockerState::Blocked(open) => {
+                        let open: Vec<String> = open.iter().map(|n| format!("#{n}")).collect();
+                        spans.push(Span::styled(
+                            format!(" [blocked by {}]", open.join(" ")),
+                            Style::default().fg(theme::WARNING),
+                        ));
+                    }
+                    BlockerState::Unblocked if task.status != TaskDisplayStatus::Completed => {
+                        spans.push(Span::styled(
+                            " [unblocked]",
+                            Style::default().fg(theme::SUCCESS),
+                        ));
+                    }
+                    BlockerState::None | BlockerState::Unblocked => {}
+                }
+                let mut lines = vec![Line::from(spans)];
+
+                // Expand the description under the selected task.
+                if is_selected {
+                    lines.extend(task.description.lines().map(|line| {
+                        Line::from(Span::styled(format!("    {line}"), theme::dimmed()))
+                    }));
+                }
+
+                ListItem::new(lines)
+            })
+            .collect();
 
     let list = List::new(items).block(block);
     frame.render_widget(list, area);
 }
+
+/// Render one task's description, comment threads and activity feed,
+/// reading the comments and the activity log from the task manager.
+fn render_detail(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    task: &DisplayTask,
+    border_style: Style,
+) {
+    let block = Block::default()
+        .title(format!("Task #{}", task.number))
+        .title_style(theme::panel_title(theme::TASKS_TITLE))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    let assignee_str = task
+        .assignee
+        .as_ref()
+        .map_or(String::new(), |a| format!(" (@{a})"));
+    let mut lines = vec![Line::from(vec![
+        Span::styled(task.status.symbol(), theme::normal()),
+        Span::styled(format!(" {}{assignee_str}", task.title), theme::bold()),
+        Span::styled(task.details(), theme::dimmed()),
+    ])];
+    lines.extend(
+        task.description
+            .lines()
+            .map(|line| Line::from(Span::styled(line.to_string(), theme::dimmed()))),
+    );
+
+    let tasks = app.task_manager().lock();
+    let local_peer = tasks.local_peer_id();
+    let author = |peer: &str| {
+        if peer == local_peer {
+            "You".to_string()
+        } else {
+            peer.to_string()
+        }
+    };
+
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled("Comments", theme::bold())));
+    // Comments are numbered `[N]` in posting order, as `/task reply` takes them.
+    let numbers: HashMap<_, _> = tasks
+        .get_task(app.task_room(), &task.id)
+        .map(|t| t.comments.keys().zip(1..).collect())
+        .unwrap_or_default();
+    let thread = tasks.comment_thread(app.task_room(), &task.id);
+    if thread.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("/task comment {} <text>", task.number),
+            theme::dimmed(),
+        )));
+    }
+    for (depth, comment) in thread {
+        let indent = "  ".repeat(depth);
+        let marker = if depth > 0 { "\u{21b3} " } else { "" };
+        let name = author(&comment.author);
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "{indent}{marker}[{}] ",
+                numbers.get(&comment.id).copied().unwrap_or_default()
+            )),
+            Span::styled(name.clone(), theme::normal().fg(theme::sender_color(&name))),
+            Span::styled(
+                format!(" {}", format_hlc_time(comment.timestamp)),
+                theme::timestamp(),
+            ),
+        ]));
+        lines.push(Line::from(format!("{indent}  {}", comment.body)));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled("Activity", theme::bold())));
+    let activity = tasks.activity(&task.id);
+    if activity.is_empty() {
+        lines.push(Line::from(Span::styled("No changes yet", theme::dimmed())));
+    }
+    // Newest first.
+    for entry in activity.iter().rev() {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{} ", format_hlc_time(entry.timestamp)),
+                theme::timestamp(),
+            ),
+            Span::raw(format!(
+                "{} {}",
+                author(&entry.author),
+                app.describe_change(entry)
+            )),
+        ]));
+    }
+    drop(tasks);
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
@@ -1,3 +1,7 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(clippy::expect_used, clippy::doc_markdown, clippy::too_many_lines)]
+
 //! Integration tests for UC-007: Join Room as Agent Participant.
 //!
 //! Tests the agent bridge lifecycle, handshake protocol, message
@@ -17,9 +21,11 @@ use termchat::agent::AgentError;
 use termchat::agent::bridge::{AgentBridge, AgentConnection, HeartbeatConfig, heartbeat_loop};
 use termchat::agent::participant::{AgentParticipant, DisconnectReason, RoomEvent};
 use termchat::agent::protocol::{
-    AgentMessage, BridgeHistoryEntry, BridgeMemberInfo, BridgeMessage, PROTOCOL_VERSION,
-    decode_line, encode_line,
+    AgentMessage, BridgeHistoryEntry, BridgeMemberInfo, BridgeMessage, BridgeTaskInfo,
+    PROTOCOL_VERSION, decode_line, encode_line,
 };
+use termchat::tasks::TaskManager;
+use termchat_proto::task::Recurrence;
 
 // =============================================================================
 // Test helpers
@@ -793,6 +799,60 @@ async fn participant_receives_membership_update_via_event_channel() {
     let _ = tokio::time::timeout(Duration::from_millis(500), run_handle).await;
 }
 
+#[tokio::test]
+async fn participant_receives_task_reminder_via_event_channel() {
+    let (_bridge, mut conn, mut reader, mut writer) =
+        setup_agent_bridge("part-reminder", "room-1").await;
+
+    let _ = do_handshake_on_conn(&mut conn, &mut writer, &mut reader, "bot", "Bot").await;
+
+    let (outbound_tx, _outbound_rx) = mpsc::channel(64);
+    let (room_tx, room_rx) = mpsc::channel(64);
+
+    let mut participant =
+        AgentParticipant::new(conn, "room-1", "agent:bot", "Bot", outbound_tx, room_rx);
+    participant.mark_ready();
+
+    let run_handle = tokio::spawn(async move { participant.run().await });
+
+    let mut tasks = TaskManager::new("peer-alice".to_string());
+    let (task, _) = tasks.create_task("room-1", "Standup").expect("create");
+    tasks
+        .set_recurrence("room-1", &task.id, Some(Recurrence::Daily))
+        .expect("repeat");
+    tasks
+        .update_due("room-1", &task.id, Some(1_000))
+        .expect("due");
+    for task in tasks.due_reminders(2_000) {
+        room_tx
+            .send(RoomEvent::TaskReminder {
+                task: BridgeTaskInfo::from_task(&task),
+            })
+            .await
+            .expect("send");
+    }
+
+    let msg = read_json_line_timeout(&mut reader, Duration::from_millis(500))
+        .await
+        .expect("message");
+    match msg {
+        BridgeMessage::TaskReminder {
+            room_id,
+            task: info,
+        } => {
+            assert_eq!(room_id, "room-1");
+            assert_eq!(info.task_id, task.id.to_string());
+            assert_eq!(info.title, "Standup");
+            assert_eq!(info.due_date.as_deref(), Some("1970-01-01"));
+            assert_eq!(info.recurrence.as_deref(), Some("daily"));
+        }
+        other => panic!("expected TaskReminder, got {other:?}"),
+    }
+
+    drop(room_tx);
+    let _ = tokio::time::timeout(Duration::from_millis(500), run_handle).await;
+}
+
 #[tokio::test]
 async fn participant_peer_id_has_agent_prefix() {
     let (_bridge, conn, _reader, _writer) = setup_agent_bridge("part-prefix", "room-1").await;
@@ -1,3 +1,7 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(clippy::unwrap_used, clippy::redundant_clone)]
+
 //! Integration tests for UC-005: E2E Encryption with Noise XX.
 //!
 //! These tests verify the complete handshake flow and transport mode
@@ -7,8 +11,9 @@ use std::time::{Duration, Instant};
 use termchat::crypto::{
     CryptoError, CryptoSession,
     keys::{Identity, InMemoryKeyStore, KeyStore, PeerKeyCache},
-    noise::{NoiseHandshake, NoiseXXSession},
+    noise::{HandshakeState, NoiseHandshake, NoiseXXSession},
 };
+use termchat_proto::version::{self, FeatureFlags, PROTOCOL_VERSION, ProtocolHello};
 
 // ============================================================================
 // Basic Handshake Tests
@@ -432,6 +437,67 @@ fn key_store_saves_and_loads_identity() {
     assert_eq!(loaded.public_key(), identity.public_key());
 }
 
+// ============================================================================
+// Protocol Version Negotiation Tests
+// ============================================================================
+
+#[test]
+fn handshake_hello_negotiates_protocol() {
+    let alice_identity = Identity::generate().unwrap();
+    let bob_identity = Identity::generate().unwrap();
+
+    let mut alice = NoiseHandshake::new_initiator(&alice_identity).unwrap();
+    let mut bob = NoiseHandshake::new_responder(&bob_identity).unwrap();
+
+    let msg1 = alice.write_hello().unwrap();
+    let bob_view = bob.read_hello(&msg1).unwrap().unwrap();
+    let msg2 = bob.write_hello().unwrap();
+    let alice_view = alice.read_hello(&msg2).unwrap().unwrap();
+    let msg3 = alice.write_hello().unwrap();
+    bob.read_hello(&msg3).unwrap();
+
+    assert_eq!(alice_view, bob_view);
+    assert_eq!(alice_view.version, PROTOCOL_VERSION);
+    assert_eq!(alice_view.features, FeatureFlags::SUPPORTED);
+
+    let alice_session = alice.into_transport().unwrap();
+    let bob_session = bob.into_transport().unwrap();
+    assert_eq!(alice_session.negotiated(), Some(alice_view));
+    assert_eq!(bob_session.negotiated(), Some(bob_view));
+}
+
+#[test]
+fn handshake_without_hello_leaves_protocol_unset() {
+    let alice_identity = Identity::generate().unwrap();
+    let bob_identity = Identity::generate().unwrap();
+
+    let (alice_session, bob_session) = complete_handshake(&alice_identity, &bob_identity);
+    assert!(alice_session.negotiated().is_none());
+    assert!(bob_session.negotiated().is_none());
+}
+
+#[test]
+fn handshake_fails_on_incompatible_protocol() {
+    let alice_identity = Identity::generate().unwrap();
+    let bob_identity = Identity::generate().unwrap();
+
+    let mut alice = NoiseHandshake::new_initiator(&alice_identity).unwrap();
+    let mut bob = NoiseHandshake::new_responder(&bob_identity).unwrap();
+
+    let future = ProtocolHello {
+        version: PROTOCOL_VERSION + 10,
+        min_version: PROTOCOL_VERSION + 10,
+        features: FeatureFlags::NONE,
+    };
+    let msg1 = alice
+        .write_message(&version::encode_hello(&future).unwrap())
+        .unwrap();
+
+    let result = bob.read_hello(&msg1);
+    assert!(matches!(result, Err(CryptoError::HandshakeFailed(_))));
+    assert!(matches!(bob.state(), HandshakeState::Failed(_)));
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
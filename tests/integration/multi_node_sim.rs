@@ -0,0 +1,1018 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::expect_used,
+    clippy::unwrap_used,
+    clippy::doc_markdown,
+    clippy::too_many_lines
+)]
+
+//! Deterministic multi-node simulation of the whole client/relay stack.
+//!
+//! Spins up N clients around an in-process [`RelayState`]. Each client has
+//! a [`ChatManager`] per remote peer, a [`TaskManager`] and a
+//! [`RoomManager`], and reaches the relay through [`SimTransport`] links, so
+//! payloads are delayed, lost, duplicated and reordered by a seeded RNG and
+//! links can flap. Time is virtual (`start_paused`): long scenarios run
+//! instantly, and a failing seed can be rerun.
+//!
+//! Scenarios are scripted as [`Step`]s, either by hand or generated from a
+//! seed. Afterwards [`Sim::settle`] heals every link and runs recovery
+//! rounds (resending unacked messages under their original IDs and
+//! exchanging task digests), and the invariants are checked:
+//! - a message its sender saw acked has reached the recipient
+//! - no message reaches a recipient's UI twice
+//! - once settled, every message was delivered exactly once
+//! - once settled, all room members hold identical task state
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex as SyncMutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use termchat::chat::history::InMemoryStore;
+use termchat::chat::room::RoomManager;
+use termchat::chat::{ChatEvent, ChatManager};
+use termchat::crypto::noise::StubNoiseSession;
+use termchat::tasks::TaskManager;
+use termchat::transport::sim::{NetworkConditions, SimLink, SimTransport};
+use termchat::transport::{PeerId, Transport, TransportError, TransportType};
+use termchat_proto::message::{
+    ChatMessage, ConversationId, Envelope, MessageContent, MessageId, MessageMetadata,
+    MessageStatus, SenderId, Timestamp,
+};
+use termchat_proto::relay::{self, RelayMessage};
+use termchat_proto::room::{self, RoomMessage};
+use termchat_proto::task::{self as task_proto, TaskId, TaskStatus, TaskSyncMessage};
+use termchat_relay::local::{LocalConnection, LocalSender};
+use termchat_relay::relay::RelayState;
+
+type Link = SimTransport<RelayPipe>;
+type Chat = ChatManager<StubNoiseSession, Link, InMemoryStore>;
+type RelaySlot = Arc<SyncMutex<Option<LocalSender>>>;
+
+/// Name of the room scenarios share tasks in.
+const ROOM_NAME: &str = "sim-room";
+
+/// Virtual time between recovery rounds in [`Sim::settle`].
+const SETTLE_ROUND: Duration = Duration::from_millis(500);
+
+/// Recovery rounds after which [`Sim::settle`] gives up.
+const MAX_SETTLE_ROUNDS: usize = 40;
+
+/// How long a receive loop backs off while its link is down.
+const OFFLINE_BACKOFF: Duration = Duration::from_millis(10);
+
+// ---------------------------------------------------------------------------
+// Transport: one client's view of one remote peer over its relay connection
+// ---------------------------------------------------------------------------
+
+/// Carries a client's traffic with one remote peer over the client's relay
+/// connection. The client's router demultiplexes inbound payloads by sender
+/// into each pipe's channel.
+struct RelayPipe {
+    local: String,
+    remote: PeerId,
+    relay: RelaySlot,
+    inbound: tokio::sync::Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+impl Transport for RelayPipe {
+    async fn send(&self, peer: &PeerId, payload: &[u8]) -> Result<(), TransportError> {
+        let Some(relay) = self.relay.lock().clone() else {
+            return Err(TransportError::ConnectionClosed);
+        };
+        let frame = relay::encode(&RelayMessage::RelayPayload {
+            from: self.local.clone(),
+            to: peer.as_str().to_string(),
+            payload: payload.to_vec(),
+        })
+        .map_err(|e| TransportError::Io(std::io::Error::other(e)))?;
+        if relay.send(&frame).await {
+            Ok(())
+        } else {
+            Err(TransportError::ConnectionClosed)
+        }
+    }
+
+    async fn recv(&self) -> Result<(PeerId, Vec<u8>), TransportError> {
+        let payload = self
+            .inbound
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)?;
+        Ok((self.remote.clone(), payload))
+    }
+
+    fn is_connected(&self, peer: &PeerId) -> bool {
+        *peer == self.remote && self.relay.lock().is_some()
+    }
+
+    fn transport_type(&self) -> TransportType {
+        TransportType::Relay
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Nodes
+// ---------------------------------------------------------------------------
+
+/// Client state shared with the node's background tasks.
+struct NodeState {
+    id: String,
+    tasks: SyncMutex<TaskManager>,
+    rooms: SyncMutex<RoomManager>,
+    /// Room ID -> members this client knows about.
+    memberships: SyncMutex<BTreeMap<String, BTreeSet<String>>>,
+    /// Chat messages shown in this client's UI, as (sender, message ID).
+    received: SyncMutex<Vec<(String, MessageId)>>,
+}
+
+/// A simulated client.
+struct Node {
+    shared: Arc<NodeState>,
+    /// The client's access link; shared by all of its pipes.
+    link: SimLink,
+    /// Current relay connection, `None` while offline.
+    relay: RelaySlot,
+    /// Routes frames from the current relay connection.
+    router: Option<JoinHandle<()>>,
+    /// Remote peer -> channel feeding that peer's pipe.
+    inbound: Arc<BTreeMap<String, mpsc::UnboundedSender<Vec<u8>>>>,
+    /// Remote peer -> chat session with that peer.
+    chats: BTreeMap<String, Arc<Chat>>,
+}
+
+impl Node {
+    fn id(&self) -> &str {
+        &self.shared.id
+    }
+
+    fn is_online(&self) -> bool {
+        self.relay.lock().is_some()
+    }
+
+    /// Send a frame to the relay; dropped if offline.
+    async fn send_frame(&self, msg: &RelayMessage) {
+        let relay = self.relay.lock().clone();
+        if let Some(relay) = relay {
+            relay.send(&relay::encode(msg).unwrap()).await;
+        }
+    }
+
+    /// Send a task sync message to every room member this client knows.
+    async fn broadcast(&self, room_id: &str, msg: &TaskSyncMessage) {
+        let members = self
+            .shared
+            .memberships
+            .lock()
+            .get(room_id)
+            .cloned()
+            .unwrap_or_default();
+        for member in members.iter().filter(|m| *m != self.id()) {
+            self.chats[member].send_task_sync(msg).await;
+        }
+    }
+}
+
+/// Route frames from a relay connection to the client's pipes and room
+/// state until the connection closes.
+fn spawn_router(
+    mut conn: LocalConnection,
+    inbound: Arc<BTreeMap<String, mpsc::UnboundedSender<Vec<u8>>>>,
+    shared: Arc<NodeState>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let relay = conn.sender();
+        while let Some(frame) = conn.recv().await {
+            match relay::decode(&frame) {
+                Ok(RelayMessage::RelayPayload { from, payload, .. }) => {
+                    if let Some(pipe) = inbound.get(&from) {
+                        let _ = pipe.send(payload);
+                    }
+                }
+                Ok(RelayMessage::Room(bytes)) => {
+                    if let Ok(msg) = room::decode(&bytes) {
+                        handle_room_message(&shared, &relay, msg).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+/// Admin side: approve every join request. Joiner side: record the
+/// approved member list.
+async fn handle_room_message(shared: &NodeState, relay: &LocalSender, msg: RoomMessage) {
+    match msg {
+        RoomMessage::JoinRequest {
+            room_id,
+            peer_id,
+            display_name,
+        } => {
+            let approved = {
+                let mut rooms = shared.rooms.lock();
+                rooms
+                    .handle_join_request(&room_id, &peer_id, &display_name)
+                    .and_then(|()| rooms.approve_join(&room_id, &peer_id))
+                    .map(|(_, members)| {
+                        let name = rooms.get_room(&room_id).unwrap().name.clone();
+                        (name, members)
+                    })
+            };
+            let Ok((name, members)) = approved else {
+                return;
+            };
+            shared.tasks.lock().set_members(&room_id, &members);
+            shared
+                .memberships
+                .lock()
+                .entry(room_id.clone())
+                .or_default()
+                .insert(peer_id.clone());
+            let approval = RoomMessage::JoinApproved {
+                room_id,
+                name,
+                members,
+                target_peer_id: peer_id,
+            };
+            let frame = relay::encode(&RelayMessage::Room(room::encode(&approval).unwrap()));
+            relay.send(&frame.unwrap()).await;
+        }
+        RoomMessage::JoinApproved {
+            room_id, members, ..
+        } => {
+            shared.tasks.lock().set_members(&room_id, &members);
+            shared
+                .memberships
+                .lock()
+                .insert(room_id, members.into_iter().map(|m| m.peer_id).collect());
+        }
+        _ => {}
+    }
+}
+
+/// Receive envelopes for one chat session with `remote` forever, applying
+/// task sync as sent by `remote`.
+fn spawn_receiver(chat: Arc<Chat>, remote: PeerId, shared: Arc<NodeState>) {
+    tokio::spawn(async move {
+        loop {
+            match chat.receive_one().await {
+                Ok(Envelope::TaskSync(data)) => {
+                    let Ok(msg) = task_proto::decode(&data) else {
+                        continue;
+                    };
+                    let replies = {
+                        let mut tasks = shared.tasks.lock();
+                        tasks.apply_remote(&remote, &msg);
+                        tasks.reply_to(&msg)
+                    };
+                    for reply in &replies {
+                        chat.send_task_sync(reply).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => tokio::time::sleep(OFFLINE_BACKOFF).await,
+            }
+        }
+    });
+}
+
+/// Record chat messages shown in a client's UI.
+fn spawn_event_log(mut events: mpsc::Receiver<ChatEvent>, shared: Arc<NodeState>) {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            if let ChatEvent::MessageReceived { message, from }
+            | ChatEvent::MessageReceivedWithClockSkew { message, from, .. } = event
+            {
+                shared
+                    .received
+                    .lock()
+                    .push((from.as_str().to_string(), message.metadata.message_id));
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Scenario steps
+// ---------------------------------------------------------------------------
+
+/// One scripted action.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Send a chat message.
+    Chat { from: usize, to: usize },
+    /// Create a task in the shared room.
+    CreateTask { node: usize },
+    /// Change the status of the `task`-th task created so far.
+    SetStatus {
+        node: usize,
+        task: usize,
+        status: TaskStatus,
+    },
+    /// Assign the `task`-th task created so far to node `to`.
+    Assign { node: usize, task: usize, to: usize },
+    /// Take a client's link down and disconnect it from the relay.
+    Offline(usize),
+    /// Reconnect a client.
+    Online(usize),
+    /// Let virtual time pass.
+    Advance(Duration),
+}
+
+/// Generate a random script over `nodes` clients that have all joined the
+/// shared room.
+fn random_script(seed: u64, nodes: usize, len: usize) -> Vec<Step> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut script = Vec::with_capacity(len);
+    let mut tasks = 0;
+    while script.len() < len {
+        let node = rng.random_range(0..nodes);
+        let step = match rng.random_range(0..100) {
+            0..35 => Step::Chat {
+                from: node,
+                to: (node + rng.random_range(1..nodes)) % nodes,
+            },
+            35..45 => {
+                tasks += 1;
+                Step::CreateTask { node }
+            }
+            45..55 if tasks > 0 => {
+                let task = rng.random_range(0..tasks);
+                let status = [
+                    TaskStatus::InProgress,
+                    TaskStatus::Completed,
+                    TaskStatus::Deleted,
+                ][rng.random_range(0..3)];
+                Step::SetStatus { node, task, status }
+            }
+            55..65 if tasks > 0 => {
+                let task = rng.random_range(0..tasks);
+                let to = rng.random_range(0..nodes);
+                Step::Assign { node, task, to }
+            }
+            65..72 => Step::Offline(node),
+            72..80 => Step::Online(node),
+            _ => Step::Advance(Duration::from_millis(rng.random_range(10..300))),
+        };
+        script.push(step);
+    }
+    script
+}
+
+// ---------------------------------------------------------------------------
+// The simulator
+// ---------------------------------------------------------------------------
+
+/// N clients and a relay over simulated links.
+struct Sim {
+    seed: u64,
+    relay: Arc<RelayState>,
+    nodes: Vec<Node>,
+    /// Every chat message sent, as (sender, recipient, message).
+    sent: Vec<(usize, usize, ChatMessage)>,
+    /// Tasks created so far, in creation order.
+    tasks: Vec<TaskId>,
+    /// The shared room, once created.
+    room_id: Option<String>,
+    /// Clients that asked to join the shared room.
+    joining: BTreeSet<usize>,
+}
+
+impl Sim {
+    /// Start `n` online clients. Every pipe applies `conditions`, each with
+    /// its own seed derived from `conditions.seed`.
+    async fn new(n: usize, conditions: NetworkConditions) -> Self {
+        let relay = Arc::new(RelayState::new());
+        let ids: Vec<String> = (0..n).map(|i| format!("peer-{i}")).collect();
+        let mut nodes = Vec::with_capacity(n);
+        for (i, id) in ids.iter().enumerate() {
+            let (rooms, _room_events) = RoomManager::new();
+            let shared = Arc::new(NodeState {
+                id: id.clone(),
+                tasks: SyncMutex::new(TaskManager::new(id.clone())),
+                rooms: SyncMutex::new(rooms),
+                memberships: SyncMutex::new(BTreeMap::new()),
+                received: SyncMutex::new(Vec::new()),
+            });
+            let link = SimLink::new();
+            let relay_slot: RelaySlot = Arc::new(SyncMutex::new(None));
+            let mut inbound = BTreeMap::new();
+            let mut chats = BTreeMap::new();
+            for (j, remote) in ids.iter().enumerate().filter(|&(j, _)| j != i) {
+                let (tx, rx) = mpsc::unbounded_channel();
+                inbound.insert(remote.clone(), tx);
+                let pipe = RelayPipe {
+                    local: id.clone(),
+                    remote: PeerId::new(remote.clone()),
+                    relay: Arc::clone(&relay_slot),
+                    inbound: tokio::sync::Mutex::new(rx),
+                };
+                let pipe_conditions = NetworkConditions {
+                    seed: conditions.seed.wrapping_add((i * n + j) as u64),
+                    ..conditions
+                };
+                let transport = SimTransport::with_link(pipe, pipe_conditions, link.clone());
+                let (chat, events) = ChatManager::new(
+                    StubNoiseSession::new(true),
+                    transport,
+                    SenderId::new(id.as_bytes().to_vec()),
+                    PeerId::new(remote.clone()),
+                    256,
+                );
+                let chat = Arc::new(chat);
+                spawn_receiver(
+                    Arc::clone(&chat),
+                    PeerId::new(remote.clone()),
+                    Arc::clone(&shared),
+                );
+                spawn_event_log(events, Arc::clone(&shared));
+                chats.insert(remote.clone(), chat);
+            }
+            nodes.push(Node {
+                shared,
+                link,
+                relay: relay_slot,
+                router: None,
+                inbound: Arc::new(inbound),
+                chats,
+            });
+        }
+        let mut sim = Self {
+            seed: conditions.seed,
+            relay,
+            nodes,
+            sent: Vec::new(),
+            tasks: Vec::new(),
+            room_id: None,
+            joining: BTreeSet::new(),
+        };
+        for i in 0..n {
+            sim.go_online(i).await;
+        }
+        sim
+    }
+
+    fn node(&self, i: usize) -> &Node {
+        &self.nodes[i]
+    }
+
+    /// Connect client `i` to the relay and bring its link up.
+    async fn go_online(&mut self, i: usize) {
+        if self.nodes[i].is_online() {
+            return;
+        }
+        let conn = LocalConnection::connect(Arc::clone(&self.relay), self.nodes[i].id())
+            .await
+            .expect("relay should accept the client");
+        let node = &mut self.nodes[i];
+        *node.relay.lock() = Some(conn.sender());
+        node.router = Some(spawn_router(
+            conn,
+            Arc::clone(&node.inbound),
+            Arc::clone(&node.shared),
+        ));
+        node.link.reconnect();
+    }
+
+    /// Take client `i`'s link down and disconnect it from the relay.
+    async fn go_offline(&mut self, i: usize) {
+        let node = &mut self.nodes[i];
+        node.link.disconnect();
+        let relay = node.relay.lock().take();
+        if let Some(relay) = relay {
+            relay.disconnect().await;
+        }
+        if let Some(router) = node.router.take() {
+            router.abort();
+        }
+    }
+
+    /// Send a chat message; failures are left for [`settle`](Self::settle)
+    /// to retry.
+    async fn chat(&mut self, from: usize, to: usize) {
+        let sender = self.nodes[from].id().to_string();
+        let message = ChatMessage {
+            metadata: MessageMetadata {
+                message_id: MessageId::new(),
+                timestamp: Timestamp::now(),
+                sender_id: SenderId::new(sender.as_bytes().to_vec()),
+                conversation_id: ConversationId::new(),
+            },
+            content: MessageContent::Text(format!("{sender} -> peer-{to} #{}", self.sent.len())),
+        };
+        self.sent.push((from, to, message.clone()));
+        let recipient = self.nodes[to].id().to_string();
+        let _ = self.nodes[from].chats[&recipient]
+            .send_chat_message(message)
+            .await;
+    }
+
+    /// Create the shared room with client `admin` as admin.
+    async fn create_room(&mut self, admin: usize) {
+        let node = &self.nodes[admin];
+        let room = node
+            .shared
+            .rooms
+            .lock()
+            .create_room(ROOM_NAME, node.id(), node.id())
+            .unwrap();
+        node.shared
+            .tasks
+            .lock()
+            .set_members(&room.room_id, &room.members);
+        node.shared.memberships.lock().insert(
+            room.room_id.clone(),
+            BTreeSet::from([node.id().to_string()]),
+        );
+        let register = RoomMessage::RegisterRoom {
+            room_id: room.room_id.clone(),
+            name: room.name,
+            admin_peer_id: node.id().to_string(),
+        };
+        node.send_frame(&RelayMessage::Room(room::encode(&register).unwrap()))
+            .await;
+        self.room_id = Some(room.room_id);
+    }
+
+    /// Ask to join the shared room on behalf of client `i`.
+    async fn join_room(&mut self, i: usize) {
+        self.joining.insert(i);
+        self.send_join_request(i).await;
+    }
+
+    async fn send_join_request(&self, i: usize) {
+        let node = &self.nodes[i];
+        let request = RoomMessage::JoinRequest {
+            room_id: self.room().to_string(),
+            peer_id: node.id().to_string(),
+            display_name: node.id().to_string(),
+        };
+        node.send_frame(&RelayMessage::Room(room::encode(&request).unwrap()))
+            .await;
+    }
+
+    fn room(&self) -> &str {
+        self.room_id.as_deref().expect("scenario has no room")
+    }
+
+    /// Create a task on client `i` and broadcast it.
+    async fn create_task(&mut self, i: usize) {
+        let room_id = self.room().to_string();
+        let title = format!("task {}", self.tasks.len());
+        let (task, msg) = self.nodes[i]
+            .shared
+            .tasks
+            .lock()
+            .create_task(&room_id, &title)
+            .unwrap();
+        self.tasks.push(task.id);
+        self.nodes[i].broadcast(&room_id, &msg).await;
+    }
+
+    /// Edit a task on client `i` and broadcast the change. Skipped if the
+    /// client has not heard of the task yet.
+    async fn edit_task(
+        &self,
+        i: usize,
+        task: usize,
+        edit: impl FnOnce(&mut TaskManager, &str, &TaskId) -> Option<TaskSyncMessage>,
+    ) {
+        let room_id = self.room().to_string();
+        let Some(task_id) = self.tasks.get(task) else {
+            return;
+        };
+        let msg = edit(&mut self.nodes[i].shared.tasks.lock(), &room_id, task_id);
+        if let Some(msg) = msg {
+            self.nodes[i].broadcast(&room_id, &msg).await;
+        }
+    }
+
+    async fn run_step(&mut self, step: &Step) {
+        match *step {
+            Step::Chat { from, to } => self.chat(from, to).await,
+            Step::CreateTask { node } => self.create_task(node).await,
+            Step::SetStatus { node, task, status } => {
+                self.edit_task(node, task, |tasks, room, id| {
+                    tasks.update_status(room, id, status).ok()
+                })
+                .await;
+            }
+            Step::Assign { node, task, to } => {
+                let assignee = self.nodes[to].id().to_string();
+                self.edit_task(node, task, |tasks, room, id| {
+                    tasks.update_assignee(room, id, Some(assignee)).ok()
+                })
+                .await;
+            }
+            Step::Offline(i) => self.go_offline(i).await,
+            Step::Online(i) => self.go_online(i).await,
+            Step::Advance(duration) => tokio::time::sleep(duration).await,
+        }
+    }
+
+    /// Run a script, checking safety invariants whenever time advances.
+    async fn run(&mut self, script: &[Step]) {
+        for step in script {
+            self.run_step(step).await;
+            if matches!(step, Step::Advance(_)) {
+                let violations = self.safety_violations().await;
+                assert!(
+                    violations.is_empty(),
+                    "seed {}: safety violated after {step:?}:\n{}",
+                    self.seed,
+                    violations.join("\n")
+                );
+            }
+        }
+    }
+
+    /// Heal every link and run recovery rounds until nothing is pending.
+    ///
+    /// Each round resends unacked messages under their original IDs,
+    /// repeats unanswered join requests, and has every client send a task
+    /// digest to the room members it knows, who answer with whatever
+    /// differs. Returns the rounds used.
+    async fn settle(&mut self) -> usize {
+        for i in 0..self.nodes.len() {
+            self.go_online(i).await;
+        }
+        for round in 1..=MAX_SETTLE_ROUNDS {
+            for (from, to, message) in &self.sent {
+                let chat = &self.node(*from).chats[self.node(*to).id()];
+                if chat.get_status(&message.metadata.message_id).await
+                    != Some(MessageStatus::Delivered)
+                {
+                    let _ = chat.send_chat_message(message.clone()).await;
+                }
+            }
+            if let Some(room_id) = self.room_id.clone() {
+                for &i in &self.joining {
+                    if !self.is_member(i) {
+                        self.send_join_request(i).await;
+                    }
+                }
+                for node in &self.nodes {
+                    let digest = node.shared.tasks.lock().build_digest(&room_id);
+                    node.broadcast(&room_id, &digest).await;
+                }
+            }
+            tokio::time::sleep(SETTLE_ROUND).await;
+            if self.liveness_violations().await.is_empty() {
+                return round;
+            }
+        }
+        MAX_SETTLE_ROUNDS
+    }
+
+    /// Whether client `i` knows it is in the shared room.
+    fn is_member(&self, i: usize) -> bool {
+        let node = self.node(i);
+        self.room_id
+            .as_ref()
+            .is_some_and(|room| node.shared.memberships.lock().contains_key(room))
+    }
+
+    /// Invariants that must hold at every point of a scenario.
+    async fn safety_violations(&self) -> Vec<String> {
+        // Let in-flight handlers finish before inspecting state.
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        let mut violations = Vec::new();
+        for node in &self.nodes {
+            let received = node.shared.received.lock().clone();
+            let unique: BTreeSet<String> = received.iter().map(|(_, id)| id.to_string()).collect();
+            if unique.len() != received.len() {
+                violations.push(format!(
+                    "{} showed {} messages but only {} distinct",
+                    node.id(),
+                    received.len(),
+                    unique.len()
+                ));
+            }
+        }
+        for (from, to, message) in &self.sent {
+            let id = &message.metadata.message_id;
+            let acked = self.node(*from).chats[self.node(*to).id()]
+                .get_status(id)
+                .await
+                == Some(MessageStatus::Delivered);
+            let shown = self
+                .node(*to)
+                .shared
+                .received
+                .lock()
+                .iter()
+                .any(|(_, seen)| seen == id);
+            if acked && !shown {
+                violations.push(format!(
+                    "message {id} from {} acked but never shown to {}",
+                    self.node(*from).id(),
+                    self.node(*to).id()
+                ));
+            }
+        }
+        violations
+    }
+
+    /// Invariants that must hold once the network has settled.
+    async fn liveness_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        for (from, to, message) in &self.sent {
+            let id = &message.metadata.message_id;
+            let sender = self.node(*from);
+            let recipient = self.node(*to);
+            let copies = recipient
+                .shared
+                .received
+                .lock()
+                .iter()
+                .filter(|(shown_from, seen)| seen == id && shown_from == sender.id())
+                .count();
+            if copies != 1 {
+                violations.push(format!(
+                    "message {id} from {} shown {copies} times to {}",
+                    sender.id(),
+                    recipient.id()
+                ));
+            }
+            if sender.chats[recipient.id()].get_status(id).await != Some(MessageStatus::Delivered) {
+                violations.push(format!("message {id} from {} never acked", sender.id()));
+            }
+        }
+        for &i in &self.joining {
+            if !self.is_member(i) {
+                violations.push(format!("{} never joined the room", self.node(i).id()));
+            }
+        }
+        violations.extend(self.task_divergence());
+        violations
+    }
+
+    /// Room members whose task state differs from the admin's.
+    fn task_divergence(&self) -> Vec<String> {
+        let Some(room_id) = &self.room_id else {
+            return Vec::new();
+        };
+        let Some(admin) = self
+            .nodes
+            .iter()
+            .find(|n| n.shared.rooms.lock().get_room(room_id).is_ok())
+        else {
+            return Vec::new();
+        };
+        let members = admin.shared.rooms.lock().get_room_members(room_id).unwrap();
+        let expected = task_snapshot(admin, room_id);
+        members
+            .iter()
+            .filter_map(|member| self.nodes.iter().find(|n| n.id() == member.peer_id))
+            .filter(|node| task_snapshot(node, room_id) != expected)
+            .map(|node| {
+                format!(
+                    "{} task state diverged from admin {}: {:?} vs {:?}",
+                    node.id(),
+                    admin.id(),
+                    task_snapshot(node, room_id),
+                    expected
+                )
+            })
+            .collect()
+    }
+
+    /// Settle, then assert every invariant.
+    async fn assert_invariants(&mut self) {
+        let rounds = self.settle().await;
+        let mut violations = self.safety_violations().await;
+        violations.extend(self.liveness_violations().await);
+        assert!(
+            violations.is_empty(),
+            "seed {}: invariants violated after {rounds} settle rounds:\n{}",
+            self.seed,
+            violations.join("\n")
+        );
+    }
+
+    /// Create the shared room on client 0 and have everyone else join.
+    async fn with_everyone_in_room(mut self) -> Self {
+        self.create_room(0).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        for i in 1..self.nodes.len() {
+            self.join_room(i).await;
+        }
+        self.settle().await;
+        for i in 0..self.nodes.len() {
+            assert!(self.is_member(i), "peer-{i} should be in the room");
+        }
+        self
+    }
+}
+
+/// A client's task state for a room, comparable across clients.
+fn task_snapshot(node: &Node, room_id: &str) -> Vec<(String, String, TaskStatus, Option<String>)> {
+    let Some(TaskSyncMessage::FullState { tasks, .. }) =
+        node.shared.tasks.lock().build_full_state(room_id)
+    else {
+        return Vec::new();
+    };
+    let mut snapshot: Vec<_> = tasks
+        .into_iter()
+        .map(|t| {
+            (
+                t.id.to_string(),
+                t.title.value,
+                t.status.value,
+                t.assignee.value,
+            )
+        })
+        .collect();
+    snapshot.sort_by(|a, b| a.0.cmp(&b.0));
+    snapshot
+}
+
+/// Realistically bad network: tens of milliseconds of latency and jitter,
+/// with loss, duplication and reordering.
+fn rough_network(seed: u64) -> NetworkConditions {
+    NetworkConditions {
+        latency: Duration::from_millis(20),
+        jitter: Duration::from_millis(30),
+        loss: 0.1,
+        duplicate: 0.05,
+        reorder: 0.1,
+        reorder_delay: Duration::from_millis(50),
+        seed,
+        ..NetworkConditions::default()
+    }
+}
+
+// ===========================================================================
+// Test 1: Chat between five clients over lossy links is exactly-once
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn five_clients_chat_exactly_once_over_rough_links() {
+    let mut sim = Sim::new(5, rough_network(1)).await;
+    for round in 0..3 {
+        for from in 0..5 {
+            for to in (0..5).filter(|&to| to != from) {
+                sim.chat(from, to).await;
+            }
+        }
+        sim.run(&[Step::Advance(Duration::from_millis(40 * (round + 1)))])
+            .await;
+    }
+    assert_eq!(sim.sent.len(), 60);
+    sim.assert_invariants().await;
+}
+
+// ===========================================================================
+// Test 2: Flapping clients get their messages via relay store-and-forward
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn flapping_clients_receive_stored_messages() {
+    let mut sim = Sim::new(5, rough_network(2)).await;
+    for cycle in 0..4 {
+        let flapping = 1 + cycle % 2;
+        sim.run(&[Step::Offline(flapping)]).await;
+        for from in (0..5).filter(|&from| from != flapping) {
+            sim.chat(from, flapping).await;
+        }
+        sim.run(&[
+            Step::Advance(Duration::from_millis(200)),
+            Step::Online(flapping),
+            Step::Advance(Duration::from_millis(200)),
+        ])
+        .await;
+    }
+
+    // Sent while the recipients were offline, so only the relay's store
+    // could have delivered these before settling.
+    for flapping in [1, 2] {
+        assert!(
+            !sim.node(flapping).shared.received.lock().is_empty(),
+            "peer-{flapping} should get stored messages on reconnect"
+        );
+    }
+    sim.assert_invariants().await;
+}
+
+// ===========================================================================
+// Test 3: Concurrent task edits converge across the room
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn concurrent_task_edits_converge() {
+    let mut sim = Sim::new(5, rough_network(3))
+        .await
+        .with_everyone_in_room()
+        .await;
+    for node in 0..5 {
+        sim.run(&[Step::CreateTask { node }]).await;
+    }
+    sim.run(&[Step::Advance(Duration::from_millis(300))]).await;
+
+    // Everyone edits the same tasks at once, one client while offline.
+    sim.run(&[Step::Offline(4)]).await;
+    for node in 0..5 {
+        sim.run(&[
+            Step::SetStatus {
+                node,
+                task: 0,
+                status: [TaskStatus::InProgress, TaskStatus::Completed][node % 2],
+            },
+            Step::Assign {
+                node,
+                task: 1,
+                to: (node + 1) % 5,
+            },
+        ])
+        .await;
+    }
+    sim.run(&[Step::Advance(Duration::from_millis(100))]).await;
+    sim.assert_invariants().await;
+
+    let snapshot = task_snapshot(sim.node(0), sim.room());
+    assert_eq!(snapshot.len(), 5);
+}
+
+// ===========================================================================
+// Test 4: A late joiner catches up on existing tasks
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn late_joiner_catches_up_on_tasks() {
+    let mut sim = Sim::new(4, rough_network(4)).await;
+    sim.create_room(0).await;
+    sim.run(&[Step::Advance(Duration::from_millis(100))]).await;
+    sim.join_room(1).await;
+    sim.settle().await;
+    for node in [0, 1, 0] {
+        sim.run(&[Step::CreateTask { node }]).await;
+    }
+
+    sim.join_room(2).await;
+    sim.join_room(3).await;
+    sim.assert_invariants().await;
+    for i in 0..4 {
+        assert_eq!(task_snapshot(sim.node(i), sim.room()).len(), 3);
+    }
+}
+
+// ===========================================================================
+// Test 5: The invariant checks catch divergence
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn divergent_task_state_is_reported() {
+    let mut sim = Sim::new(3, NetworkConditions::default())
+        .await
+        .with_everyone_in_room()
+        .await;
+    sim.run(&[Step::CreateTask { node: 0 }]).await;
+    sim.settle().await;
+    assert!(sim.task_divergence().is_empty());
+
+    // A local edit that is never broadcast.
+    let room_id = sim.room().to_string();
+    let task_id = sim.tasks[0].clone();
+    sim.node(2)
+        .shared
+        .tasks
+        .lock()
+        .update_status(&room_id, &task_id, TaskStatus::Completed)
+        .unwrap();
+    let divergence = sim.task_divergence();
+    assert_eq!(divergence.len(), 1, "{divergence:?}");
+    assert!(divergence[0].starts_with("peer-2"));
+}
+
+// ===========================================================================
+// Test 6: Random scenarios hold every invariant
+// ===========================================================================
+
+#[tokio::test(start_paused = true)]
+async fn random_scenarios_hold_invariants() {
+    for seed in 1..=8 {
+        let mut sim = Sim::new(5, rough_network(seed))
+            .await
+            .with_everyone_in_room()
+            .await;
+        let script = random_script(seed, 5, 80);
+        sim.run(&script).await;
+        sim.assert_invariants().await;
+    }
+}
+
+#[test]
+fn random_scripts_are_reproducible() {
+    assert_eq!(random_script(9, 5, 50), random_script(9, 5, 50));
+    assert_ne!(random_script(9, 5, 50), random_script(10, 5, 50));
+}
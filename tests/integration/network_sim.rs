@@ -0,0 +1,282 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::type_complexity
+)]
+
+//! Integration tests for the chat pipeline over a degraded network.
+//!
+//! Alice and Bob talk through [`SimTransport`] endpoints that drop,
+//! duplicate, delay and reorder payloads, or lose the link entirely.
+//! Verifies:
+//! - ack timeouts plus resending under the original message ID eventually
+//!   deliver every message over a lossy link
+//! - duplicated payloads reach the UI exactly once
+//! - sends fail while the link is down and succeed once it is back
+//! - the same seed replays the same losses
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use termchat::chat::history::InMemoryStore;
+use termchat::chat::{ChatEvent, ChatManager, RetryConfig, SendError};
+use termchat::crypto::noise::StubNoiseSession;
+use termchat::transport::PeerId;
+use termchat::transport::loopback::LoopbackTransport;
+use termchat::transport::sim::{NetworkConditions, SimTransport};
+
+use termchat_proto::message::{
+    ChatMessage, ConversationId, MessageContent, MessageId, MessageMetadata, MessageStatus,
+    SenderId, Timestamp,
+};
+use tokio::sync::mpsc;
+
+type SimManager = ChatManager<StubNoiseSession, SimTransport<LoopbackTransport>, InMemoryStore>;
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Create Alice and Bob connected through simulated endpoints sharing one
+/// link. Bob's receive loop runs in the background, acking as it goes.
+///
+/// Returns (alice, alice_events, bob, bob_events).
+fn create_sim_pair(
+    conditions: NetworkConditions,
+) -> (
+    SimManager,
+    mpsc::Receiver<ChatEvent>,
+    Arc<SimManager>,
+    mpsc::Receiver<ChatEvent>,
+) {
+    let (transport_a, transport_b) =
+        SimTransport::loopback_pair(PeerId::new("alice"), PeerId::new("bob"), conditions);
+
+    let (alice, alice_events) = ChatManager::new(
+        StubNoiseSession::new(true),
+        transport_a,
+        SenderId::new(vec![0xAA]),
+        PeerId::new("bob"),
+        256,
+    );
+    let (bob, bob_events) = ChatManager::new(
+        StubNoiseSession::new(true),
+        transport_b,
+        SenderId::new(vec![0xBB]),
+        PeerId::new("alice"),
+        256,
+    );
+
+    let bob = Arc::new(bob);
+    let receiver = Arc::clone(&bob);
+    tokio::spawn(async move {
+        loop {
+            if receiver.receive_one().await.is_err() && !receiver.transport().link().is_up() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    });
+
+    (alice, alice_events, bob, bob_events)
+}
+
+fn text_message(conversation: &ConversationId, text: String) -> ChatMessage {
+    ChatMessage {
+        metadata: MessageMetadata {
+            message_id: MessageId::new(),
+            timestamp: Timestamp::now(),
+            sender_id: SenderId::new(vec![0xAA]),
+            conversation_id: conversation.clone(),
+        },
+        content: MessageContent::Text(text),
+    }
+}
+
+/// Send `message` and resend it under the same ID until it is acked.
+///
+/// Returns how many transmissions it took.
+async fn send_until_delivered(alice: &SimManager, message: ChatMessage) -> u32 {
+    let config = RetryConfig {
+        ack_timeout: Duration::from_millis(50),
+        ack_retries: 0,
+        ..Default::default()
+    };
+    let message_id = message.metadata.message_id.clone();
+    for attempt in 1..=50 {
+        alice
+            .send_chat_message(message.clone())
+            .await
+            .expect("send over a lossy link should not fail");
+        if alice.await_ack(&message_id, &config).await == MessageStatus::Delivered {
+            return attempt;
+        }
+    }
+    panic!("message {message_id} was never acknowledged");
+}
+
+/// Drain every `MessageReceived` text currently queued on `events`.
+fn received_texts(events: &mut mpsc::Receiver<ChatEvent>) -> Vec<String> {
+    let mut texts = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let ChatEvent::MessageReceived { message, .. } = event {
+            let MessageContent::Text(text) = message.content;
+            texts.push(text);
+        }
+    }
+    texts
+}
+
+/// Send ten messages over a lossy link and return the transmissions each took.
+async fn lossy_exchange(seed: u64) -> (Vec<u32>, Vec<String>) {
+    let (alice, _alice_events, _bob, mut bob_events) = create_sim_pair(NetworkConditions {
+        latency: Duration::from_millis(2),
+        loss: 0.3,
+        seed,
+        ..NetworkConditions::default()
+    });
+    let conversation = ConversationId::new();
+
+    let mut attempts = Vec::new();
+    for i in 0..10 {
+        let message = text_message(&conversation, format!("msg {i}"));
+        attempts.push(send_until_delivered(&alice, message).await);
+    }
+    // Let any late duplicates land before counting.
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    (attempts, received_texts(&mut bob_events))
+}
+
+// ===========================================================================
+// Test 1: Ack/retry delivers everything over a lossy link
+// ===========================================================================
+
+#[tokio::test]
+async fn resends_deliver_every_message_over_lossy_link() {
+    let (attempts, received) = lossy_exchange(11).await;
+
+    assert!(
+        attempts.iter().any(|&n| n > 1),
+        "30% loss should force at least one resend: {attempts:?}"
+    );
+    let expected: Vec<String> = (0..10).map(|i| format!("msg {i}")).collect();
+    assert_eq!(
+        received, expected,
+        "each message reaches Bob's UI exactly once, in order"
+    );
+}
+
+// ===========================================================================
+// Test 2: Duplicated and reordered payloads are deduplicated
+// ===========================================================================
+
+#[tokio::test]
+async fn duplicated_payloads_reach_ui_once() {
+    let (alice, _alice_events, bob, mut bob_events) = create_sim_pair(NetworkConditions {
+        jitter: Duration::from_millis(5),
+        duplicate: 1.0,
+        reorder: 0.3,
+        seed: 3,
+        ..NetworkConditions::default()
+    });
+    let conversation = ConversationId::new();
+
+    for i in 0..5 {
+        let message = text_message(&conversation, format!("dup {i}"));
+        send_until_delivered(&alice, message).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(alice.transport().stats().duplicated, 5);
+    let mut received = received_texts(&mut bob_events);
+    received.sort();
+    let expected: Vec<String> = (0..5).map(|i| format!("dup {i}")).collect();
+    assert_eq!(received, expected, "duplicates must not reach the UI");
+    assert!(
+        bob.transport().stats().duplicated > 0,
+        "acks were duplicated too"
+    );
+}
+
+// ===========================================================================
+// Test 3: Sends fail while the link is down and recover afterwards
+// ===========================================================================
+
+#[tokio::test]
+async fn send_fails_during_outage_and_recovers() {
+    let (alice, _alice_events, _bob, mut bob_events) =
+        create_sim_pair(NetworkConditions::default());
+    let conversation = ConversationId::new();
+    let config = RetryConfig {
+        send_retries: 2,
+        ack_timeout: Duration::from_millis(100),
+        ack_retries: 0,
+    };
+
+    alice.transport().link().disconnect();
+    let result = alice
+        .send_message_with_retry(
+            MessageContent::Text("into the void".into()),
+            conversation.clone(),
+            &config,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(SendError::Transport(_))),
+        "send should fail while the link is down, got {result:?}"
+    );
+
+    alice.transport().link().reconnect();
+    let (message_id, _) = alice
+        .send_message_with_retry(
+            MessageContent::Text("back online".into()),
+            conversation,
+            &config,
+        )
+        .await
+        .expect("send should succeed once the link is back");
+    assert_eq!(
+        alice.await_ack(&message_id, &config).await,
+        MessageStatus::Delivered
+    );
+    assert_eq!(received_texts(&mut bob_events), ["back online"]);
+}
+
+// ===========================================================================
+// Test 4: A timed outage drops in-flight payloads, then the link heals
+// ===========================================================================
+
+#[tokio::test]
+async fn payloads_in_flight_during_outage_are_lost() {
+    let (alice, _alice_events, _bob, mut bob_events) = create_sim_pair(NetworkConditions {
+        latency: Duration::from_millis(20),
+        ..NetworkConditions::default()
+    });
+    let conversation = ConversationId::new();
+
+    let lost = text_message(&conversation, "lost in flight".into());
+    alice.send_chat_message(lost).await.unwrap();
+    alice
+        .transport()
+        .link()
+        .disconnect_for(Duration::from_millis(40));
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    let kept = text_message(&conversation, "after the outage".into());
+    send_until_delivered(&alice, kept).await;
+    assert_eq!(received_texts(&mut bob_events), ["after the outage"]);
+    assert_eq!(alice.transport().stats().dropped, 1);
+}
+
+// ===========================================================================
+// Test 5: The same seed replays the same losses
+// ===========================================================================
+
+#[tokio::test]
+async fn same_seed_replays_same_losses() {
+    let (first, _) = lossy_exchange(29).await;
+    let (second, _) = lossy_exchange(29).await;
+    assert_eq!(first, second);
+}
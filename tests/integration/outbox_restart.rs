@@ -0,0 +1,130 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(clippy::expect_used, clippy::doc_markdown, clippy::needless_continue)]
+
+//! Integration tests for the persistent offline message queue.
+//!
+//! Validates that messages left in the outbox by a previous session are:
+//! - Reported to the UI as `QueuedMessageRestored` on startup
+//! - Resent under their original `MessageId`s and received exactly once
+//! - Removed from the outbox file once sent
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use termchat::chat::outbox::Outbox;
+use termchat::net::{self, NetConfig, NetEvent};
+use termchat_proto::message::{
+    ChatMessage, ConversationId, MessageContent, MessageId, MessageMetadata, SenderId, Timestamp,
+};
+use tokio::sync::mpsc;
+
+/// Start the relay server in-process and return a ws:// URL.
+async fn start_relay() -> (String, tokio::task::JoinHandle<()>) {
+    let (addr, handle) = termchat_relay::relay::start_server("127.0.0.1:0")
+        .await
+        .expect("failed to start relay server");
+    (format!("ws://{addr}/ws"), handle)
+}
+
+/// A fresh outbox path under the system temp directory.
+fn temp_outbox_path() -> PathBuf {
+    std::env::temp_dir()
+        .join("termchat-integ-outbox")
+        .join(format!("{}.bin", uuid::Uuid::now_v7()))
+}
+
+/// A text message from `sender`, as the previous session would have queued it.
+fn queued_message(sender: &str, text: &str) -> ChatMessage {
+    ChatMessage {
+        metadata: MessageMetadata {
+            message_id: MessageId::new(),
+            timestamp: Timestamp::now(),
+            sender_id: SenderId::new(sender.as_bytes().to_vec()),
+            conversation_id: ConversationId::new(),
+        },
+        content: MessageContent::Text(text.to_string()),
+    }
+}
+
+/// Wait for the first `ConnectionStatus { connected: true }`.
+async fn wait_connected(evt_rx: &mut mpsc::Receiver<NetEvent>) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match evt_rx.recv().await {
+                Some(NetEvent::ConnectionStatus {
+                    connected: true, ..
+                }) => return,
+                Some(_) => continue,
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("timeout waiting for connection");
+}
+
+/// Collect every `MessageReceived` text arriving within `window`.
+async fn received_within(evt_rx: &mut mpsc::Receiver<NetEvent>, window: Duration) -> Vec<String> {
+    let mut texts = Vec::new();
+    let deadline = tokio::time::Instant::now() + window;
+    while let Ok(Some(event)) = tokio::time::timeout_at(deadline, evt_rx.recv()).await {
+        if let NetEvent::MessageReceived { content, .. } = event {
+            texts.push(content);
+        }
+    }
+    texts
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queued_messages_resent_once_after_restart() {
+    let (url, _relay) = start_relay().await;
+    let path = temp_outbox_path();
+
+    // A previous session queued two messages before exiting.
+    {
+        let mut outbox = Outbox::open(&path, 10).expect("open outbox");
+        outbox
+            .push(queued_message("alice-ob", "typed offline"))
+            .expect("push");
+        outbox
+            .push(queued_message("alice-ob", "and another"))
+            .expect("push");
+    }
+
+    let (_bob_cmd, mut bob_evt) = net::spawn_net(NetConfig::new(
+        url.clone(),
+        "bob-ob".to_string(),
+        "alice-ob".to_string(),
+    ))
+    .await
+    .expect("bob spawn_net failed");
+    wait_connected(&mut bob_evt).await;
+
+    let mut alice_config = NetConfig::new(url, "alice-ob".to_string(), "bob-ob".to_string());
+    alice_config.outbox_path = Some(path.clone());
+    let (_alice_cmd, mut alice_evt) = net::spawn_net(alice_config)
+        .await
+        .expect("alice spawn_net failed");
+
+    // The UI is told about both messages, in order, before they are resent.
+    for expected in ["typed offline", "and another"] {
+        match alice_evt.recv().await {
+            Some(NetEvent::QueuedMessageRestored {
+                recipient, text, ..
+            }) => {
+                assert_eq!(recipient, "bob-ob");
+                assert_eq!(text, expected);
+            }
+            other => panic!("expected QueuedMessageRestored, got {other:?}"),
+        }
+    }
+
+    // Each message arrives exactly once.
+    let received = received_within(&mut bob_evt, Duration::from_secs(2)).await;
+    assert_eq!(received, ["typed offline", "and another"]);
+
+    // Sent messages no longer survive a restart.
+    assert!(Outbox::open(&path, 10).expect("reopen").is_empty());
+    let _ = std::fs::remove_file(&path);
+}
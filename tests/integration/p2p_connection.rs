@@ -1,3 +1,11 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::cast_possible_truncation
+)]
+
 //! Integration tests for UC-003: Establish P2P Connection.
 //!
 //! Validates all success postconditions from the use case document.
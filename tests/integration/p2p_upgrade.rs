@@ -0,0 +1,304 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(clippy::expect_used, clippy::doc_markdown, clippy::needless_continue)]
+
+//! Integration tests for direct P2P links in the networking layer.
+//!
+//! Validates that `spawn_net`:
+//! - Dials the remote peer directly over QUIC before falling back to the relay
+//! - Falls back to the relay when the peer is not directly reachable
+//! - Upgrades to P2P once the peer becomes reachable
+//! - Reports the active `TransportType` in `NetEvent::ConnectionStatus`
+//! - Punches through NAT using relay-observed addresses, settling on the
+//!   relay when simultaneous dials time out
+//! - Finds the remote peer on the LAN via mDNS and connects directly
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use termchat::net::{self, NetCommand, NetConfig, NetEvent};
+use termchat::transport::relay::RelayTransport;
+use termchat::transport::{PeerId, Transport, TransportType};
+use tokio::sync::mpsc;
+
+/// Start the relay server in-process and return a ws:// URL.
+async fn start_relay() -> (String, tokio::task::JoinHandle<()>) {
+    let (addr, handle) = termchat_relay::relay::start_server("127.0.0.1:0")
+        .await
+        .expect("failed to start relay server");
+    (format!("ws://{addr}/ws"), handle)
+}
+
+/// Reserve a free local UDP port for a QUIC listener.
+fn free_udp_addr() -> SocketAddr {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").expect("bind probe socket");
+    socket.local_addr().expect("probe local addr")
+}
+
+/// Helper: create a `NetConfig` with a short P2P upgrade interval.
+fn make_config(
+    relay_url: &str,
+    local: &str,
+    remote: &str,
+    listen_addr: Option<SocketAddr>,
+    peer_addr: Option<SocketAddr>,
+) -> NetConfig {
+    let mut config = NetConfig::new(relay_url.to_string(), local.to_string(), remote.to_string());
+    config.p2p.listen_addr = listen_addr;
+    config.p2p.peer_addr = peer_addr;
+    config.p2p.connect_timeout = Duration::from_millis(500);
+    config.p2p.upgrade_interval = Duration::from_millis(200);
+    config
+}
+
+/// Wait for a `ConnectionStatus { connected: true }` with the given transport.
+async fn wait_for_transport(evt_rx: &mut mpsc::Receiver<NetEvent>, expected: TransportType) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match evt_rx.recv().await {
+                Some(NetEvent::ConnectionStatus {
+                    connected: true,
+                    transport_type,
+                }) if transport_type == expected => return,
+                Some(_) => continue,
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timeout waiting for {expected} connection status"));
+}
+
+/// Wait for the next `MessageReceived` and return its content.
+async fn wait_for_message(evt_rx: &mut mpsc::Receiver<NetEvent>) -> String {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match evt_rx.recv().await {
+                Some(NetEvent::MessageReceived { content, .. }) => return content,
+                Some(_) => continue,
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("timeout waiting for MessageReceived")
+}
+
+async fn send_text(cmd_tx: &mpsc::Sender<NetCommand>, text: &str) {
+    cmd_tx
+        .send(NetCommand::SendMessage {
+            conversation_id: "@ p2p".to_string(),
+            text: text.to_string(),
+        })
+        .await
+        .expect("send command failed");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn direct_link_preferred_when_peer_reachable() {
+    let (url, _relay) = start_relay().await;
+    let bob_addr = free_udp_addr();
+
+    let (_bob_cmd, mut bob_evt) = net::spawn_net(make_config(
+        &url,
+        "bob-p2p",
+        "alice-p2p",
+        Some(bob_addr),
+        None,
+    ))
+    .await
+    .expect("bob spawn_net failed");
+    wait_for_transport(&mut bob_evt, TransportType::Relay).await;
+
+    let (alice_cmd, mut alice_evt) = net::spawn_net(make_config(
+        &url,
+        "alice-p2p",
+        "bob-p2p",
+        None,
+        Some(bob_addr),
+    ))
+    .await
+    .expect("alice spawn_net failed");
+
+    // Alice dialed before touching the relay; both sides report P2P.
+    wait_for_transport(&mut alice_evt, TransportType::P2p).await;
+    wait_for_transport(&mut bob_evt, TransportType::P2p).await;
+
+    send_text(&alice_cmd, "over quic").await;
+    assert_eq!(wait_for_message(&mut bob_evt).await, "over quic");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unreachable_peer_falls_back_to_relay_then_upgrades() {
+    let (url, _relay) = start_relay().await;
+    let bob_addr = free_udp_addr();
+
+    // Nobody listens on bob_addr yet: Alice falls back to the relay.
+    let (alice_cmd, mut alice_evt) = net::spawn_net(make_config(
+        &url,
+        "alice-up",
+        "bob-up",
+        None,
+        Some(bob_addr),
+    ))
+    .await
+    .expect("alice spawn_net failed");
+    wait_for_transport(&mut alice_evt, TransportType::Relay).await;
+
+    let (bob_cmd, mut bob_evt) = net::spawn_net(make_config(
+        &url,
+        "bob-up",
+        "alice-up",
+        Some(bob_addr),
+        None,
+    ))
+    .await
+    .expect("bob spawn_net failed");
+    wait_for_transport(&mut bob_evt, TransportType::Relay).await;
+
+    // Alice's periodic re-dial reaches Bob's listener.
+    wait_for_transport(&mut alice_evt, TransportType::P2p).await;
+    wait_for_transport(&mut bob_evt, TransportType::P2p).await;
+
+    send_text(&bob_cmd, "upgraded").await;
+    assert_eq!(wait_for_message(&mut alice_evt).await, "upgraded");
+    send_text(&alice_cmd, "both ways").await;
+    assert_eq!(wait_for_message(&mut bob_evt).await, "both ways");
+}
+
+#[tokio::test]
+async fn relay_only_without_p2p_config() {
+    let (url, _relay) = start_relay().await;
+
+    let (alice_cmd, mut alice_evt) =
+        net::spawn_net(make_config(&url, "alice-relay", "bob-relay", None, None))
+            .await
+            .expect("alice spawn_net failed");
+    let (_bob_cmd, mut bob_evt) =
+        net::spawn_net(make_config(&url, "bob-relay", "alice-relay", None, None))
+            .await
+            .expect("bob spawn_net failed");
+    wait_for_transport(&mut alice_evt, TransportType::Relay).await;
+    wait_for_transport(&mut bob_evt, TransportType::Relay).await;
+
+    send_text(&alice_cmd, "via relay").await;
+    assert_eq!(wait_for_message(&mut bob_evt).await, "via relay");
+}
+
+/// Helper: a config with relay-brokered hole punching and no known addresses.
+fn punch_config(relay_url: &str, local: &str, remote: &str) -> NetConfig {
+    let mut config = make_config(relay_url, local, remote, None, None);
+    config.p2p.hole_punch = true;
+    config.p2p.punch_timeout = Duration::from_millis(500);
+    config
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hole_punch_connects_peers_via_observed_addresses() {
+    let (url, _relay) = start_relay().await;
+
+    // Neither side knows the other's address; both bind 0.0.0.0:0 and learn
+    // the other's candidates from the relay.
+    let (_bob_cmd, mut bob_evt) = net::spawn_net(punch_config(&url, "bob-np", "alice-np"))
+        .await
+        .expect("bob spawn_net failed");
+    wait_for_transport(&mut bob_evt, TransportType::Relay).await;
+    let (alice_cmd, mut alice_evt) = net::spawn_net(punch_config(&url, "alice-np", "bob-np"))
+        .await
+        .expect("alice spawn_net failed");
+
+    wait_for_transport(&mut alice_evt, TransportType::P2p).await;
+    wait_for_transport(&mut bob_evt, TransportType::P2p).await;
+
+    send_text(&alice_cmd, "punched").await;
+    assert_eq!(wait_for_message(&mut bob_evt).await, "punched");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hole_punch_times_out_to_relay() {
+    let (url, _relay) = start_relay().await;
+
+    // Bob answers punch offers with a rewritten address that swallows
+    // packets, like a NAT that never opens.
+    let blackhole = std::net::UdpSocket::bind("127.0.0.1:0").expect("bind blackhole");
+    let blackhole_port = blackhole.local_addr().expect("addr").port();
+    let bob = RelayTransport::connect(&url, PeerId::new("bob-nat"))
+        .await
+        .expect("bob connect");
+    let (offer_tx, mut offer_rx) = mpsc::channel(8);
+    bob.set_punch_handler(offer_tx);
+
+    let (alice_cmd, mut alice_evt) = net::spawn_net(punch_config(&url, "alice-nat", "bob-nat"))
+        .await
+        .expect("alice spawn_net failed");
+    wait_for_transport(&mut alice_evt, TransportType::Relay).await;
+
+    let offer = tokio::time::timeout(Duration::from_secs(5), offer_rx.recv())
+        .await
+        .expect("timeout waiting for punch offer")
+        .expect("offer channel closed");
+    assert_eq!(offer.from, PeerId::new("alice-nat"));
+    assert!(
+        !offer.addrs.is_empty(),
+        "relay should report alice's address"
+    );
+    bob.request_punch(&PeerId::new("alice-nat"), blackhole_port, Vec::new())
+        .await
+        .expect("bob punch request");
+
+    // Alice gives up after the punch timeout and keeps using the relay.
+    let switched = tokio::time::timeout(
+        Duration::from_millis(1500),
+        wait_for_transport(&mut alice_evt, TransportType::P2p),
+    )
+    .await;
+    assert!(switched.is_err(), "alice should stay on the relay");
+
+    send_text(&alice_cmd, "still relayed").await;
+    let (from, _) = tokio::time::timeout(Duration::from_secs(5), bob.recv())
+        .await
+        .expect("timeout waiting for relayed payload")
+        .expect("bob recv");
+    assert_eq!(from, PeerId::new("alice-nat"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lan_discovery_connects_peers_directly() {
+    let (url, _relay) = start_relay().await;
+
+    // No listen or peer addresses: the only way to find each other
+    // directly is the mDNS advertisement.
+    let mut bob_config = make_config(&url, "bob-lan", "alice-lan", None, None);
+    bob_config.p2p.lan_discovery = true;
+    let (_bob_cmd, mut bob_evt) = net::spawn_net(bob_config)
+        .await
+        .expect("bob spawn_net failed");
+
+    let mut alice_config = make_config(&url, "alice-lan", "bob-lan", None, None);
+    alice_config.p2p.lan_discovery = true;
+    alice_config.p2p.fingerprint = Some("a1b2c3d4".to_string());
+    let (alice_cmd, mut alice_evt) = net::spawn_net(alice_config)
+        .await
+        .expect("alice spawn_net failed");
+
+    let fingerprint = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match bob_evt.recv().await {
+                Some(NetEvent::LanPeerDiscovered {
+                    peer_id,
+                    fingerprint,
+                    ..
+                }) if peer_id == "alice-lan" => return fingerprint,
+                Some(_) => continue,
+                None => panic!("event channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("timeout waiting for LanPeerDiscovered");
+    assert_eq!(fingerprint.as_deref(), Some("a1b2c3d4"));
+
+    wait_for_transport(&mut alice_evt, TransportType::P2p).await;
+    send_text(&alice_cmd, "over the lan").await;
+    assert_eq!(wait_for_message(&mut bob_evt).await, "over the lan");
+}
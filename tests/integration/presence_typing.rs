@@ -1,3 +1,13 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::doc_markdown,
+    clippy::type_complexity,
+    clippy::unchecked_time_subtraction,
+    clippy::unnecessary_get_then_check
+)]
+
 //! Integration tests for UC-009: Typing Indicators & Presence Status.
 //!
 //! Verifies:
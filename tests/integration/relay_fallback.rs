@@ -1,3 +1,15 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::collapsible_if,
+    clippy::items_after_statements,
+    clippy::match_wild_err_arm,
+    clippy::uninlined_format_args
+)]
+
 //! Integration tests for UC-004: Relay Messages via Server.
 //!
 //! Validates all success postconditions for relay transport:
@@ -233,6 +245,7 @@ async fn disconnect_detection_after_server_close() {
     // Use a minimal server that closes after registration.
     use futures_util::{SinkExt, StreamExt};
     use termchat_proto::relay::{self, RelayMessage};
+    use termchat_proto::version::ProtocolHello;
     use tokio_tungstenite::tungstenite as ws;
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -244,8 +257,11 @@ async fn disconnect_detection_after_server_close() {
         let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
 
         if let Some(Ok(ws::Message::Binary(data))) = ws_stream.next().await {
-            if let Ok(RelayMessage::Register { peer_id }) = relay::decode(&data) {
-                let ack = RelayMessage::Registered { peer_id };
+            if let Ok(RelayMessage::Register { peer_id, .. }) = relay::decode(&data) {
+                let ack = RelayMessage::Registered {
+                    peer_id,
+                    protocol: ProtocolHello::current(),
+                };
                 let bytes = relay::encode(&ack).unwrap();
                 let _ = ws_stream.send(ws::Message::Binary(bytes.into())).await;
             }
@@ -274,6 +290,7 @@ async fn disconnect_detection_after_server_close() {
 async fn send_after_disconnect_returns_error() {
     use futures_util::{SinkExt, StreamExt};
     use termchat_proto::relay::{self, RelayMessage};
+    use termchat_proto::version::ProtocolHello;
     use tokio_tungstenite::tungstenite as ws;
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -285,8 +302,11 @@ async fn send_after_disconnect_returns_error() {
         let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
 
         if let Some(Ok(ws::Message::Binary(data))) = ws_stream.next().await {
-            if let Ok(RelayMessage::Register { peer_id }) = relay::decode(&data) {
-                let ack = RelayMessage::Registered { peer_id };
+            if let Ok(RelayMessage::Register { peer_id, .. }) = relay::decode(&data) {
+                let ack = RelayMessage::Registered {
+                    peer_id,
+                    protocol: ProtocolHello::current(),
+                };
                 let bytes = relay::encode(&ack).unwrap();
                 let _ = ws_stream.send(ws::Message::Binary(bytes.into())).await;
             }
@@ -322,6 +342,7 @@ async fn send_after_disconnect_returns_error() {
 async fn recv_after_disconnect_returns_error() {
     use futures_util::{SinkExt, StreamExt};
     use termchat_proto::relay::{self, RelayMessage};
+    use termchat_proto::version::ProtocolHello;
     use tokio_tungstenite::tungstenite as ws;
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -333,8 +354,11 @@ async fn recv_after_disconnect_returns_error() {
         let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
 
         if let Some(Ok(ws::Message::Binary(data))) = ws_stream.next().await {
-            if let Ok(RelayMessage::Register { peer_id }) = relay::decode(&data) {
-                let ack = RelayMessage::Registered { peer_id };
+            if let Ok(RelayMessage::Register { peer_id, .. }) = relay::decode(&data) {
+                let ack = RelayMessage::Registered {
+                    peer_id,
+                    protocol: ProtocolHello::current(),
+                };
                 let bytes = relay::encode(&ack).unwrap();
                 let _ = ws_stream.send(ws::Message::Binary(bytes.into())).await;
             }
@@ -496,12 +520,15 @@ async fn peer_id_enforcement_via_relay() {
     // since RelayTransport always sets from = local_id.
     use futures_util::{SinkExt, StreamExt};
     use termchat_proto::relay::{self, RelayMessage};
+    use termchat_proto::version::ProtocolHello;
     use tokio_tungstenite::tungstenite as ws;
 
     // Alice registers with a raw WebSocket.
     let (mut ws_alice, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
     let reg = RelayMessage::Register {
         peer_id: "alice".to_string(),
+        invite_token: None,
+        protocol: ProtocolHello::current(),
     };
     let bytes = relay::encode(&reg).unwrap();
     ws_alice
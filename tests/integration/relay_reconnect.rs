@@ -11,7 +11,8 @@
     clippy::redundant_pub_crate,
     clippy::missing_panics_doc,
     clippy::missing_errors_doc,
-    clippy::missing_docs_in_private_items
+    clippy::missing_docs_in_private_items,
+    clippy::duration_suboptimal_units
 )]
 
 //! Integration tests for UC-011: Auto-Reconnect to Relay.
@@ -26,6 +27,8 @@
 //! - Exponential backoff timing is correct
 //! - Graceful shutdown works during reconnection
 //! - Messages sent during active reconnection attempts are queued
+//! - Reconnects rotate to a fallback relay when the primary is unreachable
+//! - Messages stored on a relay the client left are swept and delivered
 //!
 //! ## Disconnect simulation
 //!
@@ -647,3 +650,246 @@ async fn graceful_shutdown_during_reconnect() {
         let _ = result;
     }
 }
+
+// =============================================================================
+// Test 6: Relay drain notice delays reconnect
+// =============================================================================
+
+/// Verifies that when the relay drains (graceful shutdown), the client
+/// surfaces the notice and waits at least the advertised `retry_after`
+/// before its first reconnect attempt, instead of retrying immediately.
+#[tokio::test]
+async fn relay_drain_delays_reconnect() {
+    let state = Arc::new(termchat_relay::relay::RelayState::new());
+    let (addr, _relay_handle) =
+        termchat_relay::relay::start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .expect("failed to start relay server");
+    let url = format!("ws://{addr}/ws");
+
+    let config = make_reconnect_config(&url, "alice-t6", "bob-t6");
+    let (_cmd_tx, mut evt_rx) = net::spawn_net(config).await.expect("spawn_net failed");
+    drain_connection_events(&mut evt_rx).await;
+
+    let retry_after = Duration::from_millis(800);
+    let notice = termchat_relay::relay::ShutdownNotice {
+        reason: "maintenance".to_string(),
+        retry_after,
+        redirect_url: None,
+    };
+    let drain_started = Instant::now();
+    state.drain(notice, Duration::from_secs(5)).await;
+
+    let evt = wait_for_event(
+        &mut evt_rx,
+        Duration::from_secs(10),
+        "RelayShutdown",
+        |evt| matches!(evt, NetEvent::RelayShutdown { .. }),
+    )
+    .await;
+    match evt {
+        NetEvent::RelayShutdown {
+            reason,
+            retry_after_ms,
+        } => {
+            assert_eq!(reason, "maintenance");
+            assert_eq!(retry_after_ms, 800);
+        }
+        other => panic!("expected RelayShutdown, got: {other:?}"),
+    }
+
+    wait_for_reconnecting(&mut evt_rx).await;
+    assert!(
+        drain_started.elapsed() >= retry_after,
+        "first reconnect attempt came after {:?}, before retry_after {retry_after:?}",
+        drain_started.elapsed()
+    );
+}
+
+// =============================================================================
+// Test 7: Relay drain redirect
+// =============================================================================
+
+/// Verifies that a drain notice carrying a `redirect_url` sends the client
+/// to the alternate relay, where messaging resumes.
+#[tokio::test]
+async fn relay_drain_redirects_to_alternate_relay() {
+    let state = Arc::new(termchat_relay::relay::RelayState::new());
+    let (primary_addr, _primary_handle) =
+        termchat_relay::relay::start_server_with_state("127.0.0.1:0", Arc::clone(&state))
+            .await
+            .expect("failed to start primary relay");
+    let (backup_addr, _backup_handle) = start_relay().await;
+    let backup_url = format!("ws://{backup_addr}/ws");
+
+    let config = make_reconnect_config(&format!("ws://{primary_addr}/ws"), "alice-t7", "bob-t7");
+    let (cmd_tx, mut evt_rx) = net::spawn_net(config).await.expect("spawn_net failed");
+    drain_connection_events(&mut evt_rx).await;
+
+    let notice = termchat_relay::relay::ShutdownNotice {
+        reason: "moving".to_string(),
+        retry_after: Duration::from_secs(30),
+        redirect_url: Some(backup_url.clone()),
+    };
+    state.drain(notice, Duration::from_secs(5)).await;
+
+    // The 30s retry_after only applies to the primary; the redirect is immediate.
+    wait_for_connected(&mut evt_rx).await;
+
+    let bob_config = make_reconnect_config(&backup_url, "bob-t7", "alice-t7");
+    let (_bob_cmd_tx, mut bob_evt_rx) = net::spawn_net(bob_config)
+        .await
+        .expect("bob spawn_net failed");
+    drain_connection_events(&mut bob_evt_rx).await;
+
+    cmd_tx
+        .send(NetCommand::SendMessage {
+            conversation_id: "@ test".to_string(),
+            text: "via backup".to_string(),
+        })
+        .await
+        .expect("send command failed");
+
+    match wait_for_message_received(&mut bob_evt_rx).await {
+        NetEvent::MessageReceived {
+            sender, content, ..
+        } => {
+            assert_eq!(sender, "alice-t7");
+            assert_eq!(content, "via backup");
+        }
+        other => panic!("expected MessageReceived, got: {other:?}"),
+    }
+}
+
+// =============================================================================
+// Test 8: Failover across multiple relays
+// =============================================================================
+
+/// Verifies that when the primary relay becomes unreachable, the supervisor
+/// rotates to the next configured relay, re-registers there, and messaging
+/// resumes through it.
+#[tokio::test]
+async fn failover_to_next_relay_when_primary_dies() {
+    let (primary_addr, _primary_handle) = start_relay().await;
+    let (backup_addr, _backup_handle) = start_relay().await;
+    let backup_url = format!("ws://{backup_addr}/ws");
+
+    let proxy = TcpProxy::new(0, &primary_addr).await;
+    let proxy_url = format!("ws://{}/ws", proxy.client_addr);
+
+    let mut config = make_reconnect_config(&proxy_url, "alice-t8", "bob-t8");
+    config.fallback_relays = vec![backup_url.clone()];
+    let (cmd_tx, mut evt_rx) = net::spawn_net(config).await.expect("spawn_net failed");
+    drain_connection_events(&mut evt_rx).await;
+
+    // The primary becomes unreachable for good.
+    proxy.kill();
+    wait_for_disconnected(&mut evt_rx).await;
+
+    match wait_for_event(
+        &mut evt_rx,
+        Duration::from_secs(15),
+        "RelayChanged",
+        |evt| matches!(evt, NetEvent::RelayChanged { .. }),
+    )
+    .await
+    {
+        NetEvent::RelayChanged { url } => assert_eq!(url, backup_url),
+        other => panic!("expected RelayChanged, got: {other:?}"),
+    }
+    wait_for_connected(&mut evt_rx).await;
+
+    let bob_config = make_reconnect_config(&backup_url, "bob-t8", "alice-t8");
+    let (_bob_cmd_tx, mut bob_evt_rx) = net::spawn_net(bob_config)
+        .await
+        .expect("bob spawn_net failed");
+    drain_connection_events(&mut bob_evt_rx).await;
+
+    cmd_tx
+        .send(NetCommand::SendMessage {
+            conversation_id: "@ test".to_string(),
+            text: "via failover".to_string(),
+        })
+        .await
+        .expect("send command failed");
+
+    match wait_for_message_received(&mut bob_evt_rx).await {
+        NetEvent::MessageReceived {
+            sender, content, ..
+        } => {
+            assert_eq!(sender, "alice-t8");
+            assert_eq!(content, "via failover");
+        }
+        other => panic!("expected MessageReceived, got: {other:?}"),
+    }
+}
+
+// =============================================================================
+// Test 9: Messages stored on the old relay survive failover
+// =============================================================================
+
+/// Verifies that messages the old relay stored for a peer after it failed
+/// over elsewhere are still delivered: once the old relay is reachable again,
+/// the health monitor sweeps it and hands the messages to the new connection.
+#[tokio::test]
+async fn messages_stored_on_old_relay_delivered_after_failover() {
+    let (primary_addr, _primary_handle) = start_relay().await;
+    let (backup_addr, _backup_handle) = start_relay().await;
+    let primary_url = format!("ws://{primary_addr}/ws");
+    let backup_url = format!("ws://{backup_addr}/ws");
+
+    // Bob reaches the primary through a proxy; alice connects directly and
+    // only knows the primary.
+    let proxy_port = find_free_port().await;
+    let proxy = TcpProxy::new(proxy_port, &primary_addr).await;
+    let proxy_url = format!("ws://{}/ws", proxy.client_addr);
+
+    let mut bob_config = make_reconnect_config(&proxy_url, "bob-t9", "alice-t9");
+    bob_config.fallback_relays = vec![backup_url.clone()];
+    bob_config.relay_health_interval = Duration::from_millis(200);
+    let (_bob_cmd_tx, mut bob_evt_rx) = net::spawn_net(bob_config)
+        .await
+        .expect("bob spawn_net failed");
+    drain_connection_events(&mut bob_evt_rx).await;
+
+    let alice_config = make_reconnect_config(&primary_url, "alice-t9", "bob-t9");
+    let (alice_cmd_tx, mut alice_evt_rx) = net::spawn_net(alice_config)
+        .await
+        .expect("alice spawn_net failed");
+    drain_connection_events(&mut alice_evt_rx).await;
+
+    // Bob loses the primary and moves to the backup.
+    proxy.kill();
+    wait_for_event(
+        &mut bob_evt_rx,
+        Duration::from_secs(15),
+        "RelayChanged",
+        |evt| matches!(evt, NetEvent::RelayChanged { url } if *url == backup_url),
+    )
+    .await;
+    wait_for_connected(&mut bob_evt_rx).await;
+
+    // Alice, still on the primary, writes while bob is away from it; the
+    // primary stores the message.
+    alice_cmd_tx
+        .send(NetCommand::SendMessage {
+            conversation_id: "@ test".to_string(),
+            text: "left on the primary".to_string(),
+        })
+        .await
+        .expect("send command failed");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // The primary becomes reachable for bob again; the sweep picks it up.
+    let _proxy2 = TcpProxy::new(proxy_port, &primary_addr).await;
+
+    match wait_for_message_received(&mut bob_evt_rx).await {
+        NetEvent::MessageReceived {
+            sender, content, ..
+        } => {
+            assert_eq!(sender, "alice-t9");
+            assert_eq!(content, "left on the primary");
+        }
+        other => panic!("expected MessageReceived, got: {other:?}"),
+    }
+}
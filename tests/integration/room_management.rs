@@ -1,3 +1,12 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::similar_names
+)]
+
 //! Integration tests for UC-006: Create Room.
 //!
 //! Tests room creation, discovery via relay, join request flow,
@@ -17,6 +26,7 @@ use termchat::chat::room::{
 use termchat_proto::message::ConversationId;
 use termchat_proto::relay::{self, RelayMessage};
 use termchat_proto::room::{self, RoomMessage};
+use termchat_proto::version::ProtocolHello;
 use termchat_relay::relay::start_server;
 
 // =============================================================================
@@ -40,6 +50,8 @@ async fn connect_and_register(addr: std::net::SocketAddr, peer_id: &str) -> WsSt
 
     let reg = RelayMessage::Register {
         peer_id: peer_id.to_string(),
+        invite_token: None,
+        protocol: ProtocolHello::current(),
     };
     let bytes = relay::encode(&reg).unwrap();
     ws.send(tungstenite::Message::Binary(bytes.into()))
@@ -738,12 +750,14 @@ async fn join_approved_reaches_requester_via_relay() {
                 display_name: "Alice".to_string(),
                 is_admin: true,
                 is_agent: false,
+                is_read_only: false,
             },
             room::MemberInfo {
                 peer_id: "bob".to_string(),
                 display_name: "Bob".to_string(),
                 is_admin: false,
                 is_agent: false,
+                is_read_only: false,
             },
         ],
         target_peer_id: "bob".to_string(),
@@ -1,3 +1,12 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::type_complexity
+)]
+
 //! Integration tests for UC-001: Send Direct Message (T-001-17).
 //!
 //! Verifies the five postconditions and two invariants from the use case:
@@ -13,6 +22,7 @@
 
 use termchat::chat::history::InMemoryStore;
 use termchat::chat::{ChatEvent, ChatManager, RetryConfig, SendError};
+use termchat::config::{ChatConfig, MAX_INFLATE_RATIO};
 use termchat::crypto::CryptoSession;
 use termchat::crypto::noise::StubNoiseSession;
 use termchat::transport::loopback::LoopbackTransport;
@@ -21,8 +31,9 @@ use termchat::transport::{PeerId, Transport};
 use termchat_proto::codec;
 use termchat_proto::message::{
     ChatMessage, ConversationId, Envelope, MessageContent, MessageId, MessageMetadata,
-    MessageStatus, SenderId, Timestamp,
+    MessageStatus, NackReason, SenderId, Timestamp,
 };
+use termchat_proto::version::{FeatureFlags, PROTOCOL_VERSION};
 
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -946,6 +957,266 @@ async fn deserialization_failure_sends_nack() {
     }
 }
 
+/// A message using a variant from a newer protocol is NACKed with
+/// `UnsupportedVersion`, and the sender marks it failed.
+#[tokio::test]
+async fn newer_version_message_nacked_and_marked_failed() {
+    let (alice, mut alice_events, _warnings, bob, _bob_events) = create_connected_pair();
+
+    let (msg_id, _) = alice
+        .send_message(
+            MessageContent::Text("from the future".into()),
+            ConversationId::new(),
+        )
+        .await
+        .unwrap();
+    let _sent_event = alice_events.try_recv().unwrap();
+
+    // Intercept the message and rewrite its content tag to one Bob doesn't
+    // know, as a newer client would send.
+    let (_, encrypted) = bob.transport().recv().await.unwrap();
+    let mut plaintext = bob.crypto().decrypt(&encrypted).unwrap();
+    let Envelope::Chat(msg) = codec::decode(&plaintext).unwrap() else {
+        panic!("expected chat envelope");
+    };
+    let metadata_len = postcard::to_allocvec(&msg.metadata).unwrap().len();
+    plaintext[1 + metadata_len] = 0x7f;
+    let tampered = alice.crypto().encrypt(&plaintext).unwrap();
+    alice
+        .transport()
+        .send(&PeerId::new("bob"), &tampered)
+        .await
+        .unwrap();
+
+    let result = bob.receive_one().await;
+    assert!(
+        matches!(
+            result,
+            Err(SendError::Codec(codec::CodecError::UnknownVariant { .. }))
+        ),
+        "unknown variant should be reported distinctly, got {result:?}"
+    );
+
+    match alice.receive_one().await.unwrap() {
+        Envelope::Nack(nack) => {
+            assert_eq!(nack.message_id, msg_id);
+            assert_eq!(
+                nack.reason,
+                NackReason::UnsupportedVersion {
+                    protocol_version: PROTOCOL_VERSION
+                }
+            );
+        }
+        other => panic!("expected Nack, got: {other:?}"),
+    }
+
+    assert!(matches!(
+        alice.get_status(&msg_id).await,
+        Some(MessageStatus::Failed(_))
+    ));
+    match alice_events.try_recv().unwrap() {
+        ChatEvent::StatusChanged { message_id, status } => {
+            assert_eq!(message_id, msg_id);
+            assert!(matches!(status, MessageStatus::Failed(_)));
+        }
+        other => panic!("expected StatusChanged, got: {other:?}"),
+    }
+}
+
+/// Large messages are compressed once the peer negotiated compression, and
+/// the receiver learns it may compress replies.
+#[tokio::test]
+async fn large_message_compressed_after_negotiation() {
+    let (alice, _alice_events, _warnings, bob, mut bob_events) = create_connected_pair();
+    alice.set_peer_features(FeatureFlags::SUPPORTED).await;
+    assert!(
+        !bob.peer_features()
+            .await
+            .contains(FeatureFlags::COMPRESSION)
+    );
+
+    let log = "2024-05-01T12:00:00Z INFO worker: job finished ok\n".repeat(1200);
+    assert!(log.len() > 56 * 1024);
+    alice
+        .send_message(MessageContent::Text(log.clone()), ConversationId::new())
+        .await
+        .unwrap();
+
+    // Inspect the wire bytes, then hand them back to Bob.
+    let (_, encrypted) = bob.transport().recv().await.unwrap();
+    assert!(encrypted.len() < 8 * 1024, "got {} bytes", encrypted.len());
+    assert!(codec::is_compressed(
+        &bob.crypto().decrypt(&encrypted).unwrap()
+    ));
+    alice
+        .transport()
+        .send(&PeerId::new("bob"), &encrypted)
+        .await
+        .unwrap();
+
+    bob.receive_one().await.unwrap();
+    match bob_events.try_recv().unwrap() {
+        ChatEvent::MessageReceived { message, .. } => {
+            assert_eq!(message.content, MessageContent::Text(log));
+        }
+        other => panic!("expected MessageReceived, got: {other:?}"),
+    }
+    assert!(
+        bob.peer_features()
+            .await
+            .contains(FeatureFlags::COMPRESSION)
+    );
+}
+
+/// Exchange protocol hellos between Alice and Bob.
+async fn exchange_hellos(
+    alice: &ChatManager<StubNoiseSession, LoopbackTransport, InMemoryStore>,
+    bob: &ChatManager<StubNoiseSession, LoopbackTransport, InMemoryStore>,
+) {
+    alice.send_hello().await.unwrap();
+    bob.receive_one().await.unwrap();
+    alice.receive_one().await.unwrap();
+}
+
+/// A hello is answered once, and both sides then use the features they
+/// share.
+#[tokio::test]
+async fn hello_exchange_negotiates_compression() {
+    let (alice, _alice_events, _warnings, bob, _bob_events) = create_connected_pair();
+    exchange_hellos(&alice, &bob).await;
+
+    assert_eq!(alice.peer_features().await, FeatureFlags::SUPPORTED);
+    assert_eq!(bob.peer_features().await, FeatureFlags::SUPPORTED);
+    // Alice does not answer Bob's reply.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), bob.receive_one())
+            .await
+            .is_err()
+    );
+}
+
+/// Once compression is negotiated, text longer than the 64 KB wire limit
+/// is delivered as long as it compresses below it.
+#[tokio::test]
+async fn compressed_message_may_exceed_wire_limit() {
+    let (alice, _alice_events, _warnings, bob, mut bob_events) = create_connected_pair();
+    exchange_hellos(&alice, &bob).await;
+
+    let log = "2024-05-01T12:00:00Z INFO worker: job finished ok\n".repeat(4000);
+    assert!(log.len() > termchat_proto::message::MAX_MESSAGE_SIZE * 3);
+    alice
+        .send_message(MessageContent::Text(log.clone()), ConversationId::new())
+        .await
+        .unwrap();
+
+    bob.receive_one().await.unwrap();
+    match bob_events.try_recv().unwrap() {
+        ChatEvent::MessageReceived { message, .. } => {
+            assert_eq!(message.content, MessageContent::Text(log));
+        }
+        other => panic!("expected MessageReceived, got: {other:?}"),
+    }
+}
+
+/// The size limit applies to the payload as sent: text that does not
+/// compress below `max_payload_size` is rejected.
+#[tokio::test]
+async fn incompressible_message_over_wire_limit_rejected() {
+    let (alice, _alice_events, _warnings, bob, _bob_events) = create_connected_pair();
+    exchange_hellos(&alice, &bob).await;
+
+    // Pseudo-random printable text barely compresses.
+    let mut state: u32 = 0x1234_5678;
+    let noise: String = (0..100 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            char::from(b'!' + u8::try_from(state >> 24).unwrap() % 94)
+        })
+        .collect();
+    let result = alice
+        .send_message(MessageContent::Text(noise), ConversationId::new())
+        .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(SendError::Validation(
+                termchat_proto::message::ValidationError::TooLarge { max, .. }
+            )) if max == 64 * 1024
+        ),
+        "expected TooLarge, got {result:?}"
+    );
+}
+
+/// Without negotiation, payloads go out as plain postcard so older peers
+/// can still read them.
+#[tokio::test]
+async fn message_not_compressed_without_negotiation() {
+    let (alice, _alice_events, _warnings, bob, _bob_events) = create_connected_pair();
+
+    alice
+        .send_message(
+            MessageContent::Text("x".repeat(8 * 1024)),
+            ConversationId::new(),
+        )
+        .await
+        .unwrap();
+
+    let (_, encrypted) = bob.transport().recv().await.unwrap();
+    let plaintext = bob.crypto().decrypt(&encrypted).unwrap();
+    assert!(!codec::is_compressed(&plaintext));
+    assert!(plaintext.len() > 8 * 1024);
+}
+
+/// A compressed payload that would inflate past the receiver's bounded
+/// multiple of `max_payload_size` is rejected even though its wire size is
+/// small (decompression bomb guard).
+#[tokio::test]
+async fn compressed_payload_over_limit_rejected() {
+    let (transport_a, transport_b) =
+        LoopbackTransport::create_pair(PeerId::new("alice"), PeerId::new("bob"), 64);
+    let (alice, _alice_events) =
+        ChatManager::<StubNoiseSession, LoopbackTransport, InMemoryStore>::new(
+            StubNoiseSession::new(true),
+            transport_a,
+            SenderId::new(vec![0xAA]),
+            PeerId::new("bob"),
+            64,
+        );
+    let (bob, _bob_events) =
+        ChatManager::<StubNoiseSession, LoopbackTransport, InMemoryStore>::new_with_config(
+            StubNoiseSession::new(true),
+            transport_b,
+            SenderId::new(vec![0xBB]),
+            PeerId::new("alice"),
+            64,
+            ChatConfig {
+                max_payload_size: 16 * 1024,
+                ..ChatConfig::default()
+            },
+        );
+    alice.set_peer_features(FeatureFlags::SUPPORTED).await;
+
+    alice
+        .send_message(
+            MessageContent::Text("a".repeat(300 * 1024)),
+            ConversationId::new(),
+        )
+        .await
+        .unwrap();
+
+    let result = bob.receive_one().await;
+    assert!(
+        matches!(
+            result,
+            Err(SendError::Codec(
+                codec::CodecError::DecompressedTooLarge { max, .. }
+            )) if max == 16 * 1024 * MAX_INFLATE_RATIO
+        ),
+        "expected DecompressedTooLarge, got {result:?}"
+    );
+}
+
 /// UC-002 Extension 6a: Message with clock skew is still displayed with a warning.
 #[tokio::test]
 async fn receive_with_clock_skew_still_displays() {
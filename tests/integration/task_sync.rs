@@ -11,12 +11,13 @@
     clippy::cloned_ref_to_slice_refs
 )]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use termchat::tasks::{TaskError, TaskManager, merge_lww, merge_task, merge_task_list};
+use termchat::transport::PeerId;
 use termchat_proto::task::{
-    LwwRegister, MAX_TASK_TITLE_LENGTH, Task, TaskFieldUpdate, TaskId, TaskStatus, TaskSyncMessage,
-    decode, encode,
+    LwwRegister, MAX_TASK_TITLE_LENGTH, Task, TaskFieldUpdate, TaskId, TaskPriority, TaskStatus,
+    TaskSyncMessage, decode, encode,
 };
 
 // ---------------------------------------------------------------------------
@@ -36,6 +37,14 @@ fn make_test_task(id: TaskId, room_id: &str, title: &str, ts: u64, author: &str)
         title: LwwRegister::new(title.to_string(), ts, author.to_string()),
         status: LwwRegister::new(TaskStatus::Open, ts, author.to_string()),
         assignee: LwwRegister::new(None, ts, author.to_string()),
+        description: LwwRegister::new(String::new(), ts, author.to_string()),
+        due: LwwRegister::new(None, ts, author.to_string()),
+        recurrence: LwwRegister::new(None, ts, author.to_string()),
+        priority: LwwRegister::new(TaskPriority::Normal, ts, author.to_string()),
+        labels: BTreeMap::new(),
+        parent: LwwRegister::new(None, ts, author.to_string()),
+        blocked_by: BTreeMap::new(),
+        comments: BTreeMap::new(),
         created_at: ts,
         created_by: author.to_string(),
     }
@@ -228,7 +237,7 @@ fn task_manager_apply_remote_field_update_newer_wins() {
             "peer-b".to_string(),
         )),
     };
-    mgr.apply_remote(&msg);
+    mgr.apply_remote(&PeerId::new("peer-b"), &msg);
     let tasks = mgr.get_tasks("room-1");
     // Deleted filter: Completed is not deleted, should appear
     assert_eq!(tasks[0].status.value, TaskStatus::Completed);
@@ -247,7 +256,7 @@ fn task_manager_apply_remote_field_update_stale_rejected() {
             "peer-b".to_string(),
         )),
     };
-    mgr.apply_remote(&msg);
+    mgr.apply_remote(&PeerId::new("peer-b"), &msg);
     let tasks = mgr.get_tasks("room-1");
     assert_eq!(tasks[0].title.value, "My task"); // unchanged
 }
@@ -262,7 +271,7 @@ fn task_manager_apply_remote_full_state_adds_new_tasks() {
         room_id: "room-1".to_string(),
         tasks: vec![remote_task],
     };
-    mgr.apply_remote(&msg);
+    mgr.apply_remote(&PeerId::new("peer-b"), &msg);
     assert_eq!(mgr.get_tasks("room-1").len(), 1);
     assert_eq!(mgr.get_tasks("room-1")[0].title.value, "Remote task");
 }
@@ -283,7 +292,7 @@ fn task_manager_apply_remote_full_state_merges_existing() {
         room_id: "room-1".to_string(),
         tasks: vec![remote_task],
     };
-    mgr.apply_remote(&msg);
+    mgr.apply_remote(&PeerId::new("peer-b"), &msg);
     let tasks = mgr.get_tasks("room-1");
     assert_eq!(tasks.len(), 1);
     // Title updated (remote is newer with u64::MAX)
@@ -305,7 +314,7 @@ fn task_manager_build_full_state_round_trip() {
     let state_msg = mgr_a.build_full_state("room-1").expect("should have state");
 
     let mut mgr_b = make_manager("peer-b");
-    mgr_b.apply_remote(&state_msg);
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &state_msg);
 
     assert_eq!(mgr_b.get_tasks("room-1").len(), 2);
 }
@@ -345,11 +354,11 @@ fn two_manager_convergence() {
 
     // Exchange full state: A -> B
     let state_a = mgr_a.build_full_state("room-1").expect("state A");
-    mgr_b.apply_remote(&state_a);
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &state_a);
 
     // Exchange full state: B -> A
     let state_b = mgr_b.build_full_state("room-1").expect("state B");
-    mgr_a.apply_remote(&state_b);
+    mgr_a.apply_remote(&PeerId::new("peer-b"), &state_b);
 
     // Both should now have both tasks
     let tasks_a = mgr_a.get_tasks("room-1");
@@ -419,7 +428,7 @@ fn three_peer_convergence() {
 
     // Sync A -> B via FullState
     let state_a = mgr_a.build_full_state("room-1").expect("state A");
-    mgr_b.apply_remote(&state_a);
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &state_a);
 
     // B updates the status
     let status_msg = mgr_b
@@ -427,10 +436,10 @@ fn three_peer_convergence() {
         .expect("update");
 
     // Sync B's update -> A and C
-    mgr_a.apply_remote(&status_msg);
+    mgr_a.apply_remote(&PeerId::new("peer-b"), &status_msg);
     // C first gets the full state from A (which now has B's update)
     let state_a2 = mgr_a.build_full_state("room-1").expect("state A2");
-    mgr_c.apply_remote(&state_a2);
+    mgr_c.apply_remote(&PeerId::new("peer-a"), &state_a2);
 
     // All three should converge: status = InProgress
     assert_eq!(
@@ -462,7 +471,7 @@ fn concurrent_updates_to_different_fields_both_survive() {
     // A creates a task, sync to B
     let (task, _) = mgr_a.create_task("room-1", "Shared task").expect("create");
     let state = mgr_a.build_full_state("room-1").expect("state");
-    mgr_b.apply_remote(&state);
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &state);
 
     // Small sleep to ensure update timestamps are strictly newer than creation
     std::thread::sleep(std::time::Duration::from_millis(2));
@@ -476,8 +485,8 @@ fn concurrent_updates_to_different_fields_both_survive() {
         .expect("status");
 
     // Now sync: A gets B's status update, B gets A's assignee update
-    mgr_a.apply_remote(&status_msg);
-    mgr_b.apply_remote(&assign_msg);
+    mgr_a.apply_remote(&PeerId::new("peer-b"), &status_msg);
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &assign_msg);
 
     // Both fields should survive on both managers
     let tasks_a = mgr_a.get_tasks("room-1");
@@ -507,6 +516,14 @@ fn stale_field_update_rejected_after_full_state_with_newer_timestamp() {
         ),
         status: make_lww_status(TaskStatus::Open, u64::MAX - 1, "peer-b"),
         assignee: make_lww_assignee(None, u64::MAX - 1, "peer-b"),
+        description: LwwRegister::new(String::new(), u64::MAX - 1, "peer-b".to_string()),
+        due: LwwRegister::new(None, u64::MAX - 1, "peer-b".to_string()),
+        recurrence: LwwRegister::new(None, u64::MAX - 1, "peer-b".to_string()),
+        priority: LwwRegister::new(TaskPriority::Normal, u64::MAX - 1, "peer-b".to_string()),
+        labels: BTreeMap::new(),
+        parent: LwwRegister::new(None, u64::MAX - 1, "peer-b".to_string()),
+        blocked_by: BTreeMap::new(),
+        comments: BTreeMap::new(),
         created_at: task.created_at,
         created_by: "peer-a".to_string(),
     };
@@ -514,7 +531,7 @@ fn stale_field_update_rejected_after_full_state_with_newer_timestamp() {
         room_id: "room-1".to_string(),
         tasks: vec![updated_task],
     };
-    mgr.apply_remote(&full_state);
+    mgr.apply_remote(&PeerId::new("peer-b"), &full_state);
 
     // Now a stale FieldUpdate arrives with timestamp 1 — should be rejected
     let stale_update = TaskSyncMessage::FieldUpdate {
@@ -526,8 +543,109 @@ fn stale_field_update_rejected_after_full_state_with_newer_timestamp() {
             "peer-c".to_string(),
         )),
     };
-    mgr.apply_remote(&stale_update);
+    mgr.apply_remote(&PeerId::new("peer-c"), &stale_update);
 
     let tasks = mgr.get_tasks("room-1");
     assert_eq!(tasks[0].title.value, "FullState title"); // stale was rejected
 }
+
+// --- Digest anti-entropy ---
+
+/// Runs one digest exchange from `a` (`peer-a`) to `b` (`peer-b`) and
+/// back, returning the total encoded bytes sent.
+fn digest_exchange(a: &mut TaskManager, b: &mut TaskManager, room_id: &str) -> usize {
+    let digest = a.build_digest(room_id);
+    let mut bytes = encode(&digest).expect("encode").len();
+    for reply in b.reply_to(&digest) {
+        bytes += encode(&reply).expect("encode").len();
+        a.apply_remote(&PeerId::new("peer-b"), &reply);
+        for answer in a.reply_to(&reply) {
+            bytes += encode(&answer).expect("encode").len();
+            b.apply_remote(&PeerId::new("peer-a"), &answer);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn digest_sync_sends_only_differing_buckets() {
+    let mut mgr_a = make_manager("peer-a");
+    let mut mgr_b = make_manager("peer-b");
+    let ids: Vec<TaskId> = (0..2000)
+        .map(|i| {
+            let (task, _) = mgr_a
+                .create_task("room-1", &format!("Task {i}"))
+                .expect("create");
+            task.id
+        })
+        .collect();
+    let full_state = mgr_a.build_full_state("room-1").expect("state");
+    let full_state_bytes = encode(&full_state).expect("encode").len();
+    mgr_b.apply_remote(&PeerId::new("peer-a"), &full_state);
+
+    // In sync: only the digest itself goes over the wire.
+    let digest = mgr_a.build_digest("room-1");
+    assert!(mgr_b.reply_to(&digest).is_empty());
+
+    // One edit on each side that the other missed.
+    mgr_a
+        .update_status("room-1", &ids[0], TaskStatus::Completed)
+        .expect("update");
+    mgr_b
+        .update_assignee("room-1", &ids[1999], Some("peer-b".to_string()))
+        .expect("update");
+
+    let synced_bytes = digest_exchange(&mut mgr_a, &mut mgr_b, "room-1");
+    assert!(
+        synced_bytes * 10 < full_state_bytes,
+        "delta sync sent {synced_bytes} bytes, full state is {full_state_bytes}"
+    );
+    let find = |mgr: &TaskManager, id: &TaskId| {
+        mgr.get_tasks("room-1")
+            .into_iter()
+            .find(|t| t.id == *id)
+            .cloned()
+            .expect("task")
+    };
+    assert_eq!(find(&mgr_b, &ids[0]).status.value, TaskStatus::Completed);
+    assert_eq!(
+        find(&mgr_a, &ids[1999]).assignee.value.as_deref(),
+        Some("peer-b")
+    );
+    assert!(mgr_b.reply_to(&mgr_a.build_digest("room-1")).is_empty());
+}
+
+#[test]
+fn digest_sync_fills_empty_peer() {
+    let mut mgr_a = make_manager("peer-a");
+    let mut mgr_b = make_manager("peer-b");
+    for i in 0..20 {
+        mgr_b
+            .create_task("room-1", &format!("Task {i}"))
+            .expect("create");
+    }
+    // A newly joined peer with no tasks sends an all-zero digest.
+    digest_exchange(&mut mgr_a, &mut mgr_b, "room-1");
+    assert_eq!(mgr_a.get_tasks("room-1").len(), 20);
+    assert!(mgr_a.reply_to(&mgr_b.build_digest("room-1")).is_empty());
+}
+
+#[test]
+fn request_full_state_reply_is_whole_room() {
+    let mut mgr = make_manager("peer-a");
+    mgr.create_task("room-1", "One").expect("create");
+    mgr.create_task("room-1", "Two").expect("create");
+    let replies = mgr.reply_to(&TaskSyncMessage::RequestFullState {
+        room_id: "room-1".to_string(),
+    });
+    assert!(matches!(
+        replies.as_slice(),
+        [TaskSyncMessage::FullState { tasks, .. }] if tasks.len() == 2
+    ));
+    assert!(
+        mgr.reply_to(&TaskSyncMessage::RequestFullState {
+            room_id: "room-2".to_string(),
+        })
+        .is_empty()
+    );
+}
@@ -1,3 +1,7 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(clippy::expect_used, clippy::unnecessary_get_then_check)]
+
 //! Integration tests for UC-017: Connect TUI to Live Backend State.
 //!
 //! Tests verify postconditions from the use case document.
@@ -15,7 +19,7 @@
 //! - Conversation deduplication
 //! - Conversation name tracking
 
-use termchat::app::{App, ConversationItem, DisplayMessage, MessageStatus};
+use termchat::app::{App, DisplayMessage, MessageStatus};
 use termchat_proto::presence::PresenceStatus;
 
 // =============================================================================
@@ -1,3 +1,13 @@
+// Test-specific lint overrides: integration tests use unwrap/expect freely,
+// and some pedantic/nursery lints are not appropriate for test code.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::doc_markdown,
+    clippy::needless_continue,
+    clippy::used_underscore_binding
+)]
+
 //! Integration tests for UC-010: Connect to Relay and Exchange Live Messages.
 //!
 //! Tests that the `net` module correctly wires `ChatManager` + `RelayTransport`
@@ -9,10 +19,17 @@
 //! - Connection failure falls back gracefully (returns error, not panic)
 //! - Delivery status transitions: Sent → Delivered
 //! - Shutdown command terminates cleanly
+//! - Task sync messages are applied by the receiver, and rejected ones reported
+//! - Periodic task digests bring a peer that missed a change up to date
 
+use std::sync::Arc;
 use std::time::Duration;
 
+use parking_lot::Mutex;
 use termchat::net::{self, NetCommand, NetConfig, NetEvent};
+use termchat::tasks::TaskManager;
+use termchat::transport::TransportType;
+use termchat_proto::room::MemberInfo;
 
 /// Start the relay server in-process and return a ws:// URL.
 async fn start_relay() -> (String, tokio::task::JoinHandle<()>) {
@@ -58,7 +75,7 @@ async fn spawn_net_connects_to_relay() {
             transport_type,
         } => {
             assert!(connected, "should be connected");
-            assert_eq!(transport_type, "Relay");
+            assert_eq!(transport_type, TransportType::Relay);
         }
         other => panic!("expected ConnectionStatus, got: {other:?}"),
     }
@@ -267,6 +284,112 @@ async fn bidirectional_message_exchange() {
     }
 }
 
+// =============================================================================
+// Task sync
+// =============================================================================
+
+#[tokio::test]
+async fn task_sync_is_applied_and_rejections_reported() {
+    let (url, _handle) = start_relay().await;
+
+    let alice_config = make_config(&url, "alice-tasks", "bob-tasks");
+    let bob_config = make_config(&url, "bob-tasks", "alice-tasks");
+
+    let bob_tasks = Arc::new(Mutex::new(TaskManager::new("bob-tasks".to_string())));
+    let member = |peer_id: &str, is_admin, is_read_only| MemberInfo {
+        peer_id: peer_id.to_string(),
+        display_name: peer_id.to_string(),
+        is_admin,
+        is_agent: false,
+        is_read_only,
+    };
+    bob_tasks.lock().set_members(
+        "room-1",
+        &[
+            member("bob-tasks", true, false),
+            member("alice-tasks", false, true),
+        ],
+    );
+
+    let (alice_cmd_tx, mut alice_evt_rx) = net::spawn_net(alice_config)
+        .await
+        .expect("alice spawn_net failed");
+    let (_bob_cmd_tx, mut bob_evt_rx) =
+        net::spawn_net_with_tasks(bob_config, Arc::clone(&bob_tasks))
+            .await
+            .expect("bob spawn_net failed");
+
+    drain_connection_events(&mut alice_evt_rx).await;
+    drain_connection_events(&mut bob_evt_rx).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Alice is read-only in Bob's view of the room, so her task is refused.
+    let mut alice_tasks = TaskManager::new("alice-tasks".to_string());
+    let (task, create) = alice_tasks.create_task("room-1", "Ship it").unwrap();
+    alice_cmd_tx
+        .send(NetCommand::SendTaskSync(create.clone()))
+        .await
+        .unwrap();
+
+    match wait_for_task_change_rejected(&mut bob_evt_rx).await {
+        NetEvent::TaskChangeRejected(warning) => {
+            assert_eq!(warning.sender, "alice-tasks");
+            assert_eq!(warning.task_id, task.id);
+        }
+        other => panic!("expected TaskChangeRejected, got: {other:?}"),
+    }
+    assert!(bob_tasks.lock().get_tasks("room-1").is_empty());
+
+    // Once she may write, the same message is applied to Bob's manager.
+    bob_tasks.lock().set_members(
+        "room-1",
+        &[
+            member("bob-tasks", true, false),
+            member("alice-tasks", false, false),
+        ],
+    );
+    alice_cmd_tx
+        .send(NetCommand::SendTaskSync(create))
+        .await
+        .unwrap();
+
+    // Bob's TUI is told to redraw once the task is in his manager.
+    wait_for_tasks_changed(&mut bob_evt_rx).await;
+    assert_eq!(bob_tasks.lock().get_tasks("room-1")[0].id, task.id);
+}
+
+#[tokio::test]
+async fn task_digests_repair_missed_changes() {
+    let (url, _handle) = start_relay().await;
+
+    let mut alice_config = make_config(&url, "alice-digest", "bob-digest");
+    alice_config.task_sync_interval = Duration::from_millis(200);
+    let bob_config = make_config(&url, "bob-digest", "alice-digest");
+
+    // Alice created a task before Bob ever connected.
+    let alice_tasks = Arc::new(Mutex::new(TaskManager::new("alice-digest".to_string())));
+    let (task, _) = alice_tasks.lock().create_task("room-1", "Missed").unwrap();
+    let bob_tasks = Arc::new(Mutex::new(TaskManager::new("bob-digest".to_string())));
+
+    let (_alice_cmd_tx, _alice_evt_rx) =
+        net::spawn_net_with_tasks(alice_config, Arc::clone(&alice_tasks))
+            .await
+            .expect("alice spawn_net failed");
+    let (_bob_cmd_tx, _bob_evt_rx) = net::spawn_net_with_tasks(bob_config, Arc::clone(&bob_tasks))
+        .await
+        .expect("bob spawn_net failed");
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while bob_tasks.lock().get_tasks("room-1").is_empty() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "timeout waiting for the digest exchange to reach Bob"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(bob_tasks.lock().get_tasks("room-1")[0].id, task.id);
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
@@ -316,3 +439,31 @@ async fn wait_for_status_changed(rx: &mut tokio::sync::mpsc::Receiver<NetEvent>)
     }
     panic!("timeout waiting for StatusChanged(delivered=true) event");
 }
+
+/// Wait for a `TasksChanged` event, skipping other events.
+async fn wait_for_tasks_changed(rx: &mut tokio::sync::mpsc::Receiver<NetEvent>) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while tokio::time::Instant::now() < deadline {
+        match tokio::time::timeout(Duration::from_secs(5), rx.recv()).await {
+            Ok(Some(NetEvent::TasksChanged)) => return,
+            Ok(Some(_)) => continue,
+            Ok(None) => panic!("channel closed while waiting for TasksChanged"),
+            Err(_) => break,
+        }
+    }
+    panic!("timeout waiting for TasksChanged event");
+}
+
+/// Wait for a `TaskChangeRejected` event, skipping other events.
+async fn wait_for_task_change_rejected(rx: &mut tokio::sync::mpsc::Receiver<NetEvent>) -> NetEvent {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while tokio::time::Instant::now() < deadline {
+        match tokio::time::timeout(Duration::from_secs(5), rx.recv()).await {
+            Ok(Some(evt @ NetEvent::TaskChangeRejected(_))) => return evt,
+            Ok(Some(_)) => continue,
+            Ok(None) => panic!("channel closed while waiting for TaskChangeRejected"),
+            Err(_) => break,
+        }
+    }
+    panic!("timeout waiting for TaskChangeRejected event");
+}
@@ -9,16 +9,23 @@
 
 #![allow(clippy::expect_used, clippy::unwrap_used)]
 
+use std::collections::BTreeMap;
+
+use bytes::BytesMut;
 use proptest::prelude::*;
 use termchat_proto::agent::{AgentCapability, AgentInfo};
 use termchat_proto::codec;
+use termchat_proto::framing::EnvelopeCodec;
 use termchat_proto::message::*;
 use termchat_proto::presence::{PresenceMessage, PresenceStatus};
 use termchat_proto::relay::{self, RelayMessage};
 use termchat_proto::task::{
-    self, LwwRegister, Task, TaskFieldUpdate, TaskId, TaskStatus, TaskSyncMessage,
+    self, CommentId, LwwRegister, Recurrence, Task, TaskComment, TaskFieldUpdate, TaskId,
+    TaskPriority, TaskStatus, TaskSyncMessage,
 };
 use termchat_proto::typing::TypingMessage;
+use termchat_proto::version::{FeatureFlags, ProtocolHello};
+use tokio_util::codec::{Decoder, Encoder};
 use uuid::Uuid;
 
 // --- Arbitrary implementations for protocol types ---
@@ -147,6 +154,24 @@ proptest! {
         prop_assert_eq!(consumed, frame.len());
     }
 
+    /// Any valid Envelope survives a compressed encode → decode round-trip,
+    /// whether or not compression kicked in.
+    #[test]
+    fn compressed_envelope_round_trip(envelope in arb_envelope(), threshold in 0usize..256) {
+        let bytes = codec::encode_compressed(&envelope, codec::Compression::Deflate, threshold)
+            .expect("encode_compressed should succeed");
+        let decoded = codec::decode(&bytes).expect("decode should succeed");
+        prop_assert_eq!(envelope, decoded);
+    }
+
+    /// Random bytes behind a compressed marker never panic or inflate past the limit.
+    #[test]
+    fn random_compressed_bytes_decode_no_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let mut payload = vec![0x81];
+        payload.extend_from_slice(&bytes);
+        let _ = codec::decode_with_limit(&payload, 1024);
+    }
+
     /// Random bytes never cause a panic when decoded — they return Err gracefully.
     #[test]
     fn random_bytes_decode_no_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
@@ -160,6 +185,50 @@ proptest! {
         let _ = codec::decode_framed(&bytes);
     }
 
+    /// A stream of frames split at arbitrary read boundaries decodes to the
+    /// original envelopes, in order, with nothing left over.
+    #[test]
+    fn stream_codec_arbitrary_chunking(
+        envelopes in prop::collection::vec(arb_envelope(), 1..8),
+        chunk_sizes in prop::collection::vec(1usize..64, 1..32),
+    ) {
+        let mut codec = EnvelopeCodec::new();
+        let mut wire = BytesMut::new();
+        for envelope in &envelopes {
+            codec.encode(envelope, &mut wire).expect("encode should succeed");
+        }
+
+        let mut buf = BytesMut::new();
+        let mut decoded = Vec::new();
+        let mut offset = 0;
+        for size in chunk_sizes.iter().cycle() {
+            if offset >= wire.len() {
+                break;
+            }
+            let end = (offset + size).min(wire.len());
+            buf.extend_from_slice(&wire[offset..end]);
+            offset = end;
+            while let Some(envelope) = codec.decode(&mut buf).expect("decode should succeed") {
+                decoded.push(envelope);
+            }
+        }
+        prop_assert_eq!(decoded, envelopes);
+        prop_assert!(buf.is_empty());
+    }
+
+    /// Random bytes fed to the stream decoder never panic, and a frame
+    /// longer than the maximum is never returned.
+    #[test]
+    fn stream_codec_random_bytes_no_panic(
+        bytes in prop::collection::vec(any::<u8>(), 0..1024),
+        max in 0usize..512,
+    ) {
+        let mut codec = EnvelopeCodec::with_max_frame_len(max);
+        let mut buf = BytesMut::from(&bytes[..]);
+        while let Ok(Some(_)) = codec.decode(&mut buf) {}
+        let _ = codec.decode_eof(&mut buf);
+    }
+
     /// `MessageStatus` survives round-trip through postcard encoding.
     /// (`MessageStatus` is not in `Envelope` directly, but we test it can be
     /// encoded/decoded through postcard independently.)
@@ -223,28 +292,158 @@ fn arb_task() -> impl Strategy<Value = Task> {
         arb_lww_string(),
         arb_lww_status(),
         arb_lww_assignee(),
+        arb_task_extras(),
+        arb_task_links(),
         any::<u64>(),
         "[a-z]{1,16}",
     )
         .prop_map(
-            |(id, room_id, title, status, assignee, created_at, created_by)| Task {
-                id,
-                room_id,
-                title,
-                status,
-                assignee,
-                created_at,
-                created_by,
+            |(id, room_id, title, status, assignee, extras, links, created_at, created_by)| {
+                let (description, due, recurrence, priority, labels) = extras;
+                let (parent, blocked_by, comments) = links;
+                Task {
+                    id,
+                    room_id,
+                    title,
+                    status,
+                    assignee,
+                    description,
+                    due,
+                    recurrence,
+                    priority,
+                    labels,
+                    parent,
+                    blocked_by,
+                    comments,
+                    created_at,
+                    created_by,
+                }
             },
         )
 }
 
+/// A task's description, due date, recurrence, priority and labels.
+type TaskExtras = (
+    LwwRegister<String>,
+    LwwRegister<Option<u64>>,
+    LwwRegister<Option<Recurrence>>,
+    LwwRegister<TaskPriority>,
+    BTreeMap<String, LwwRegister<bool>>,
+);
+
+/// Strategy for a task's description, due date, recurrence, priority and
+/// labels.
+fn arb_task_extras() -> impl Strategy<Value = TaskExtras> {
+    (
+        arb_lww_string(),
+        (prop::option::of(any::<u64>()), any::<u64>(), "[a-z]{1,16}")
+            .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author)),
+        arb_lww_recurrence(),
+        (arb_task_priority(), any::<u64>(), "[a-z]{1,16}")
+            .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author)),
+        prop::collection::btree_map(
+            "[a-z]{1,8}",
+            (any::<bool>(), any::<u64>(), "[a-z]{1,16}")
+                .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author)),
+            0..4,
+        ),
+    )
+}
+
+/// A task's parent, blockers and comments.
+type TaskLinks = (
+    LwwRegister<Option<TaskId>>,
+    BTreeMap<TaskId, LwwRegister<bool>>,
+    BTreeMap<CommentId, TaskComment>,
+);
+
+/// Strategy for a task's parent, blockers and comments.
+fn arb_task_links() -> impl Strategy<Value = TaskLinks> {
+    (
+        (prop::option::of(arb_task_id()), any::<u64>(), "[a-z]{1,16}")
+            .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author)),
+        prop::collection::btree_map(
+            arb_task_id(),
+            (any::<bool>(), any::<u64>(), "[a-z]{1,16}")
+                .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author)),
+            0..4,
+        ),
+        prop::collection::vec(arb_task_comment(), 0..4).prop_map(|comments| {
+            comments
+                .into_iter()
+                .map(|comment| (comment.id.clone(), comment))
+                .collect()
+        }),
+    )
+}
+
+/// Strategy for generating arbitrary `TaskComment` values.
+fn arb_task_comment() -> impl Strategy<Value = TaskComment> {
+    (
+        any::<u128>(),
+        prop::option::of(any::<u128>()),
+        "[a-z]{1,16}",
+        "[a-zA-Z0-9 ]{1,64}",
+        any::<u64>(),
+    )
+        .prop_map(|(id, reply_to, author, body, timestamp)| TaskComment {
+            id: CommentId::from_uuid(Uuid::from_u128(id)),
+            reply_to: reply_to.map(|n| CommentId::from_uuid(Uuid::from_u128(n))),
+            author,
+            body,
+            timestamp,
+        })
+}
+
+/// Strategy for generating arbitrary recurrence registers.
+fn arb_lww_recurrence() -> impl Strategy<Value = LwwRegister<Option<Recurrence>>> {
+    let rule = prop_oneof![
+        Just(Recurrence::Daily),
+        Just(Recurrence::Weekly),
+        "[0-9*/,-]{1,8}( [0-9*/,-]{1,8}){4}".prop_map(Recurrence::Cron),
+    ];
+    (prop::option::of(rule), any::<u64>(), "[a-z]{1,16}")
+        .prop_map(|(v, ts, author)| LwwRegister::new(v, ts, author))
+}
+
+/// Strategy for generating arbitrary `TaskPriority` values.
+fn arb_task_priority() -> impl Strategy<Value = TaskPriority> {
+    prop_oneof![
+        Just(TaskPriority::Low),
+        Just(TaskPriority::Normal),
+        Just(TaskPriority::High),
+        Just(TaskPriority::Urgent),
+    ]
+}
+
 /// Strategy for generating arbitrary `TaskFieldUpdate` values.
 fn arb_task_field_update() -> impl Strategy<Value = TaskFieldUpdate> {
     prop_oneof![
         arb_lww_string().prop_map(TaskFieldUpdate::Title),
         arb_lww_status().prop_map(TaskFieldUpdate::Status),
         arb_lww_assignee().prop_map(TaskFieldUpdate::Assignee),
+        arb_lww_string().prop_map(TaskFieldUpdate::Description),
+        (prop::option::of(any::<u64>()), any::<u64>(), "[a-z]{1,16}")
+            .prop_map(|(v, ts, author)| TaskFieldUpdate::Due(LwwRegister::new(v, ts, author))),
+        (arb_task_priority(), any::<u64>(), "[a-z]{1,16}").prop_map(|(v, ts, author)| {
+            TaskFieldUpdate::Priority(LwwRegister::new(v, ts, author))
+        }),
+        ("[a-z]{1,8}", any::<bool>(), any::<u64>(), "[a-z]{1,16}").prop_map(
+            |(label, v, ts, author)| TaskFieldUpdate::Label {
+                label,
+                present: LwwRegister::new(v, ts, author),
+            }
+        ),
+        (prop::option::of(arb_task_id()), any::<u64>(), "[a-z]{1,16}")
+            .prop_map(|(v, ts, author)| TaskFieldUpdate::Parent(LwwRegister::new(v, ts, author))),
+        (arb_task_id(), any::<bool>(), any::<u64>(), "[a-z]{1,16}").prop_map(
+            |(blocker, v, ts, author)| TaskFieldUpdate::BlockedBy {
+                blocker,
+                present: LwwRegister::new(v, ts, author),
+            }
+        ),
+        arb_task_comment().prop_map(TaskFieldUpdate::Comment),
+        arb_lww_recurrence().prop_map(TaskFieldUpdate::Recurrence),
     ]
 }
 
@@ -261,6 +460,10 @@ fn arb_task_sync_message() -> impl Strategy<Value = TaskSyncMessage> {
         ("[a-z]{1,32}", prop::collection::vec(arb_task(), 0..8))
             .prop_map(|(room_id, tasks)| TaskSyncMessage::FullState { room_id, tasks }),
         "[a-z]{1,32}".prop_map(|room_id| TaskSyncMessage::RequestFullState { room_id }),
+        ("[a-z]{1,32}", prop::collection::vec(any::<u64>(), 0..=64))
+            .prop_map(|(room_id, buckets)| TaskSyncMessage::Digest { room_id, buckets }),
+        ("[a-z]{1,32}", prop::collection::vec(0..64u16, 0..8))
+            .prop_map(|(room_id, buckets)| TaskSyncMessage::RequestBuckets { room_id, buckets }),
     ]
 }
 
@@ -347,11 +550,38 @@ fn arb_typing_message() -> impl Strategy<Value = TypingMessage> {
     })
 }
 
+/// Strategy for generating arbitrary `ProtocolHello` values.
+fn arb_protocol_hello() -> impl Strategy<Value = ProtocolHello> {
+    (any::<u16>(), any::<u16>(), any::<u64>()).prop_map(|(version, min_version, bits)| {
+        ProtocolHello {
+            version,
+            min_version,
+            features: FeatureFlags::from_bits(bits),
+        }
+    })
+}
+
+/// Strategy for generating socket addresses that survive serialization
+/// (serde does not encode IPv6 flow info or scope IDs).
+fn arb_socket_addr() -> impl Strategy<Value = std::net::SocketAddr> {
+    (any::<std::net::IpAddr>(), any::<u16>()).prop_map(std::net::SocketAddr::from)
+}
+
 /// Strategy for generating arbitrary `RelayMessage` values.
 fn arb_relay_message() -> impl Strategy<Value = RelayMessage> {
     prop_oneof![
-        "[a-z]{1,16}".prop_map(|peer_id| RelayMessage::Register { peer_id }),
-        "[a-z]{1,16}".prop_map(|peer_id| RelayMessage::Registered { peer_id }),
+        (
+            "[a-z]{1,16}",
+            proptest::option::of("[a-z0-9.]{1,32}"),
+            arb_protocol_hello(),
+        )
+            .prop_map(|(peer_id, invite_token, protocol)| RelayMessage::Register {
+                peer_id,
+                invite_token,
+                protocol,
+            }),
+        ("[a-z]{1,16}", arb_protocol_hello())
+            .prop_map(|(peer_id, protocol)| RelayMessage::Registered { peer_id, protocol }),
         (
             "[a-z]{1,16}",
             "[a-z]{1,16}",
@@ -365,6 +595,21 @@ fn arb_relay_message() -> impl Strategy<Value = RelayMessage> {
         ("[a-z]{1,16}", any::<u32>()).prop_map(|(to, count)| RelayMessage::Queued { to, count }),
         "[a-z]{1,16}".prop_map(|reason| RelayMessage::Error { reason }),
         prop::collection::vec(any::<u8>(), 0..256).prop_map(RelayMessage::Room),
+        (
+            "[a-z]{1,16}",
+            any::<u16>(),
+            prop::collection::vec(arb_socket_addr(), 0..4),
+        )
+            .prop_map(|(to, port, local_addrs)| RelayMessage::PunchRequest {
+                to,
+                port,
+                local_addrs,
+            }),
+        (
+            "[a-z]{1,16}",
+            prop::collection::vec(arb_socket_addr(), 0..4),
+        )
+            .prop_map(|(from, addrs)| RelayMessage::PunchCandidates { from, addrs }),
     ]
 }
 
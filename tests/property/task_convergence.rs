@@ -0,0 +1,338 @@
+//! Property-based convergence tests for task sync under clock skew.
+//!
+//! Uses proptest to verify:
+//! 1. Hybrid clock timestamps always exceed everything issued or observed,
+//!    and far-future remote timestamps move the clock at most the maximum
+//!    skew ahead of physical time.
+//! 2. Peers with arbitrarily skewed clocks converge to identical task state
+//!    whatever order their sync messages arrive in.
+//! 3. An edit made after seeing another peer's edit wins on every replica
+//!    while the two clocks are within the maximum skew of each other.
+//! 4. Concurrent parent and blocker edits that together form cycles resolve
+//!    to the same acyclic dependency graph on every replica.
+
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use proptest::prelude::*;
+use termchat::tasks::clock::DEFAULT_MAX_SKEW_MS;
+use termchat::tasks::{HybridClock, TaskManager};
+use termchat::transport::PeerId;
+use termchat_proto::task::{TaskId, TaskStatus, TaskSyncMessage};
+
+const ROOM: &str = "room-1";
+
+/// Up to a day of clock skew either way, in milliseconds.
+const MAX_SKEW_MS: i64 = 86_400_000;
+
+/// Skew either way that keeps two clocks within the clock's maximum skew of
+/// each other, in milliseconds.
+#[allow(clippy::cast_possible_wrap)]
+const TOLERATED_SKEW_MS: i64 = DEFAULT_MAX_SKEW_MS as i64 / 2;
+
+/// Current wall-clock time in milliseconds since epoch.
+fn wall_ms() -> u64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap();
+    u64::try_from(now.as_millis()).unwrap()
+}
+
+/// One step of a randomized sync scenario.
+#[derive(Debug, Clone)]
+enum Action {
+    /// A peer creates a task.
+    Create { peer: usize },
+    /// A peer changes the status of one of the tasks it knows.
+    SetStatus {
+        peer: usize,
+        task: usize,
+        status: TaskStatus,
+    },
+    /// A peer assigns one of the tasks it knows.
+    Assign { peer: usize, task: usize, to: usize },
+    /// A peer makes one task a subtask of, or blocked by, another.
+    Link {
+        peer: usize,
+        task: usize,
+        to: usize,
+        blocker: bool,
+    },
+    /// Deliver one in-flight message, chosen by index, so messages arrive
+    /// out of order.
+    Deliver { index: usize },
+}
+
+fn arb_status() -> impl Strategy<Value = TaskStatus> {
+    prop_oneof![
+        Just(TaskStatus::Open),
+        Just(TaskStatus::InProgress),
+        Just(TaskStatus::Completed),
+        Just(TaskStatus::Deleted),
+    ]
+}
+
+fn arb_action(peers: usize) -> impl Strategy<Value = Action> {
+    prop_oneof![
+        1 => (0..peers).prop_map(|peer| Action::Create { peer }),
+        2 => (0..peers, any::<usize>(), arb_status())
+            .prop_map(|(peer, task, status)| Action::SetStatus { peer, task, status }),
+        2 => (0..peers, any::<usize>(), 0..peers)
+            .prop_map(|(peer, task, to)| Action::Assign { peer, task, to }),
+        2 => (0..peers, any::<usize>(), any::<usize>(), any::<bool>())
+            .prop_map(|(peer, task, to, blocker)| Action::Link { peer, task, to, blocker }),
+        4 => any::<usize>().prop_map(|index| Action::Deliver { index }),
+    ]
+}
+
+/// Peers with skewed clocks exchanging sync messages over a network that
+/// delivers in any order.
+struct Cluster {
+    peers: Vec<TaskManager>,
+    /// Messages sent but not yet delivered, as (sender, recipient, message).
+    in_flight: Vec<(usize, usize, TaskSyncMessage)>,
+}
+
+impl Cluster {
+    fn new(skews: &[i64]) -> Self {
+        let peers = skews
+            .iter()
+            .enumerate()
+            .map(|(i, &skew)| {
+                TaskManager::with_clock(format!("peer-{i}"), HybridClock::with_offset(skew))
+            })
+            .collect();
+        Self {
+            peers,
+            in_flight: Vec::new(),
+        }
+    }
+
+    fn broadcast(&mut self, from: usize, msg: &TaskSyncMessage) {
+        for to in (0..self.peers.len()).filter(|&to| to != from) {
+            self.in_flight.push((from, to, msg.clone()));
+        }
+    }
+
+    /// Hands `msg` from peer `from` to peer `to`.
+    fn deliver(&mut self, from: usize, to: usize, msg: &TaskSyncMessage) {
+        self.peers[to].apply_remote(&PeerId::new(format!("peer-{from}")), msg);
+    }
+
+    /// The `n`-th task (modulo) the peer knows about, if any.
+    fn known_task(&self, peer: usize, n: usize) -> Option<TaskId> {
+        let TaskSyncMessage::FullState { mut tasks, .. } =
+            self.peers[peer].build_full_state(ROOM)?
+        else {
+            return None;
+        };
+        tasks.sort_by_key(|t| t.id.to_string());
+        Some(tasks[n % tasks.len()].id.clone())
+    }
+
+    fn apply(&mut self, action: &Action) {
+        let msg = match *action {
+            Action::Create { peer } => {
+                let (_, msg) = self.peers[peer].create_task(ROOM, "task").unwrap();
+                Some((peer, msg))
+            }
+            Action::SetStatus { peer, task, status } => self.known_task(peer, task).map(|id| {
+                let msg = self.peers[peer].update_status(ROOM, &id, status).unwrap();
+                (peer, msg)
+            }),
+            Action::Assign { peer, task, to } => self.known_task(peer, task).map(|id| {
+                let msg = self.peers[peer]
+                    .update_assignee(ROOM, &id, Some(format!("peer-{to}")))
+                    .unwrap();
+                (peer, msg)
+            }),
+            Action::Link {
+                peer,
+                task,
+                to,
+                blocker,
+            } => {
+                let (Some(task), Some(to)) =
+                    (self.known_task(peer, task), self.known_task(peer, to))
+                else {
+                    return;
+                };
+                // Locally refused cycles are fine; concurrent ones get through.
+                let msg = if blocker {
+                    self.peers[peer].add_blocker(ROOM, &task, &to)
+                } else {
+                    self.peers[peer].set_parent(ROOM, &task, Some(&to))
+                };
+                msg.ok().map(|msg| (peer, msg))
+            }
+            Action::Deliver { index } => {
+                if !self.in_flight.is_empty() {
+                    let (from, to, msg) = self.in_flight.swap_remove(index % self.in_flight.len());
+                    self.deliver(from, to, &msg);
+                }
+                None
+            }
+        };
+        if let Some((from, msg)) = msg {
+            self.broadcast(from, &msg);
+        }
+    }
+
+    /// Deliver everything still in flight, newest first.
+    fn drain(&mut self) {
+        while let Some((from, to, msg)) = self.in_flight.pop() {
+            self.deliver(from, to, &msg);
+        }
+    }
+
+    fn snapshot(&self, peer: usize) -> Vec<String> {
+        let Some(TaskSyncMessage::FullState { tasks, .. }) =
+            self.peers[peer].build_full_state(ROOM)
+        else {
+            return Vec::new();
+        };
+        let graph = self.peers[peer].graph(ROOM);
+        let mut snapshot: Vec<String> = tasks
+            .iter()
+            .map(|t| {
+                format!(
+                    "{} {:?} {} {:?} parent={:?} blockers={:?}",
+                    t.id,
+                    t.title.value,
+                    t.status.value,
+                    t.assignee.value,
+                    graph.parent(&t.id),
+                    graph.blockers(&t.id).collect::<Vec<_>>()
+                )
+            })
+            .collect();
+        snapshot.sort();
+        snapshot
+    }
+}
+
+// --- Property tests ---
+
+proptest! {
+    /// Local timestamps exceed every timestamp issued or accepted before,
+    /// whatever the skew and whatever remote timestamps arrive, and never
+    /// run more than the maximum skew ahead of the clock's physical time,
+    /// even after adversarially far-future timestamps.
+    #[test]
+    fn clock_is_monotonic_and_bounded(
+        skew in -MAX_SKEW_MS..MAX_SKEW_MS,
+        events in prop::collection::vec(
+            prop::option::of(prop_oneof![
+                any::<u64>().prop_map(|t| t >> 1),
+                Just(u64::MAX),
+                (0..u64::from(u16::MAX)).prop_map(|t| u64::MAX - t),
+            ]),
+            1..200,
+        ),
+    ) {
+        let mut clock = HybridClock::with_offset(skew);
+        let mut high = 0;
+        for event in events {
+            if let Some(remote) = event {
+                if clock.observe(remote) {
+                    high = high.max(remote);
+                }
+            } else {
+                let ts = clock.now();
+                prop_assert!(ts > high);
+                high = ts;
+                let limit = wall_ms().saturating_add_signed(skew) + DEFAULT_MAX_SKEW_MS;
+                prop_assert!(HybridClock::physical_ms(ts) <= limit);
+            }
+        }
+    }
+
+    /// Peers with skewed clocks converge once every message is delivered,
+    /// in any order.
+    #[test]
+    fn skewed_peers_converge(
+        skews in prop::collection::vec(-MAX_SKEW_MS..MAX_SKEW_MS, 3),
+        actions in prop::collection::vec(arb_action(3), 1..80),
+    ) {
+        let mut cluster = Cluster::new(&skews);
+        for action in &actions {
+            cluster.apply(action);
+        }
+        cluster.drain();
+
+        let expected = cluster.snapshot(0);
+        for peer in 1..3 {
+            prop_assert_eq!(&cluster.snapshot(peer), &expected);
+        }
+    }
+
+    /// Whatever cycles concurrent edits create, every replica's effective
+    /// parent chains end and its blocker edges stay acyclic.
+    #[test]
+    fn dependency_graph_stays_acyclic(
+        actions in prop::collection::vec(arb_action(3), 1..80),
+    ) {
+        let mut cluster = Cluster::new(&[0, 0, 0]);
+        for action in &actions {
+            cluster.apply(action);
+        }
+        cluster.drain();
+
+        for peer in 0..3 {
+            let graph = cluster.peers[peer].graph(ROOM);
+            for task in cluster.peers[peer].get_tasks(ROOM) {
+                let mut seen = vec![&task.id];
+                let mut current = &task.id;
+                while let Some(parent) = graph.parent(current) {
+                    prop_assert!(!seen.contains(&parent));
+                    seen.push(parent);
+                    current = parent;
+                }
+                let mut stack: Vec<_> = graph.blockers(&task.id).collect();
+                let mut visited = Vec::new();
+                while let Some(blocker) = stack.pop() {
+                    prop_assert_ne!(blocker, &task.id);
+                    if !visited.contains(&blocker) {
+                        visited.push(blocker);
+                        stack.extend(graph.blockers(blocker));
+                    }
+                }
+            }
+        }
+    }
+
+    /// A write made after applying another peer's write wins everywhere,
+    /// even when the first writer's clock runs ahead, as long as it is
+    /// within the maximum skew.
+    #[test]
+    fn causally_later_edit_wins(
+        fast_skew in 0..TOLERATED_SKEW_MS,
+        slow_skew in -TOLERATED_SKEW_MS..0,
+        first in arb_status(),
+        second in arb_status(),
+        reversed in any::<bool>(),
+    ) {
+        let mut cluster = Cluster::new(&[fast_skew, slow_skew, 0]);
+        let (task, created) = cluster.peers[0].create_task(ROOM, "task").unwrap();
+        let first_msg = cluster.peers[0].update_status(ROOM, &task.id, first).unwrap();
+        cluster.deliver(0, 1, &created);
+        cluster.deliver(0, 1, &first_msg);
+        let second_msg = cluster.peers[1].update_status(ROOM, &task.id, second).unwrap();
+
+        cluster.deliver(1, 0, &second_msg);
+        let mut to_observer = vec![(0, created), (0, first_msg), (1, second_msg)];
+        if reversed {
+            to_observer.reverse();
+        }
+        for (from, msg) in &to_observer {
+            cluster.deliver(*from, 2, msg);
+        }
+
+        for peer in 0..3 {
+            let state = cluster.peers[peer].build_full_state(ROOM).unwrap();
+            let TaskSyncMessage::FullState { tasks, .. } = state else {
+                panic!("expected FullState");
+            };
+            prop_assert_eq!(tasks[0].status.value, second);
+        }
+    }
+}